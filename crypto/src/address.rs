@@ -0,0 +1,380 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checksummed human-readable format of public keys and hashes.
+//!
+//! Public keys and hashes are encoded with [Bech32]: a prefix indicating the type of the value,
+//! the `1` separator, the value in a 32-character alphabet and a 6-character checksum.
+//! The checksum guarantees detection of up to 4 mistyped characters, so a typo in an address
+//! leads to a parsing error instead of silently referring to another wallet.
+//!
+//! ```
+//! # extern crate exonum_crypto;
+//! use exonum_crypto::{Address, PublicKey, PUBLIC_KEY_LENGTH};
+//!
+//! # fn main() {
+//! let key = PublicKey::new([0; PUBLIC_KEY_LENGTH]);
+//! let address = key.to_address();
+//! assert_eq!(address, "expk1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqy849gs");
+//! assert_eq!(PublicKey::from_address(&address).unwrap(), key);
+//! // The hexadecimal representation is accepted as well.
+//! assert_eq!(key.to_hex().parse::<PublicKey>().unwrap(), key);
+//! # }
+//! ```
+//!
+//! [Bech32]: https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki
+
+use hex::FromHex;
+
+use std::{error::Error, fmt, str::FromStr};
+
+use super::{Hash, PublicKey};
+
+/// Alphabet of the data part.
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+/// Separator between the prefix and the data part.
+const SEPARATOR: char = '1';
+/// Size of the checksum in characters.
+const CHECKSUM_SIZE: usize = 6;
+/// Maximal length of an encoded string.
+const MAX_LENGTH: usize = 90;
+/// Generator coefficients of the checksum.
+const GENERATOR: [u32; 5] = [
+    0x3b6a_57b2,
+    0x2650_8e6d,
+    0x1ea1_19fa,
+    0x3d42_33dd,
+    0x2a14_62b3,
+];
+
+/// Error parsing a checksummed address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressError {
+    /// The string is too long or has no separator.
+    InvalidFormat,
+    /// The prefix doesn't correspond to the type of the parsed value.
+    InvalidPrefix,
+    /// The string contains a character outside of the alphabet.
+    InvalidCharacter(char),
+    /// The string contains both lower case and upper case characters.
+    MixedCase,
+    /// The checksum doesn't match, most likely because of a typo.
+    InvalidChecksum,
+    /// The decoded value has incorrect length.
+    InvalidLength,
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AddressError::InvalidCharacter(c) => write!(f, "{} `{}`", self.description(), c),
+            _ => write!(f, "{}", self.description()),
+        }
+    }
+}
+
+impl Error for AddressError {
+    fn description(&self) -> &str {
+        match *self {
+            AddressError::InvalidFormat => "Invalid address format",
+            AddressError::InvalidPrefix => "Invalid address prefix",
+            AddressError::InvalidCharacter(_) => "Invalid character in address",
+            AddressError::MixedCase => "Mixed case in address",
+            AddressError::InvalidChecksum => "Invalid address checksum",
+            AddressError::InvalidLength => "Invalid address length",
+        }
+    }
+}
+
+/// Values having a checksummed human-readable representation.
+pub trait Address: Sized {
+    /// Prefix of the representation, which indicates the type of the value.
+    const PREFIX: &'static str;
+
+    /// Creates a value from bytes, returning `None` if the length is incorrect.
+    fn from_address_bytes(bytes: &[u8]) -> Option<Self>;
+
+    /// Returns bytes of the value.
+    fn address_bytes(&self) -> &[u8];
+
+    /// Returns the checksummed representation of the value.
+    fn to_address(&self) -> String {
+        encode(Self::PREFIX, self.address_bytes())
+    }
+
+    /// Parses the checksummed representation of the value.
+    fn from_address(s: &str) -> Result<Self, AddressError> {
+        let (prefix, bytes) = decode(s)?;
+        if prefix != Self::PREFIX {
+            return Err(AddressError::InvalidPrefix);
+        }
+        Self::from_address_bytes(&bytes).ok_or(AddressError::InvalidLength)
+    }
+}
+
+impl Address for PublicKey {
+    const PREFIX: &'static str = "expk";
+
+    fn from_address_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::from_slice(bytes)
+    }
+
+    fn address_bytes(&self) -> &[u8] {
+        self.as_ref()
+    }
+}
+
+impl Address for Hash {
+    const PREFIX: &'static str = "exhash";
+
+    fn from_address_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::from_slice(bytes)
+    }
+
+    fn address_bytes(&self) -> &[u8] {
+        self.as_ref()
+    }
+}
+
+/// Parses either the checksummed representation of the value or its hexadecimal representation.
+/// Strings starting with the address prefix are always parsed as addresses.
+fn parse_address_or_hex<T>(s: &str) -> Result<T, String>
+where
+    T: Address + FromHex,
+{
+    if s.to_lowercase().starts_with(T::PREFIX) {
+        T::from_address(s).map_err(|e| e.to_string())
+    } else {
+        T::from_hex(s).map_err(|_| "Invalid hex".to_owned())
+    }
+}
+
+impl FromStr for PublicKey {
+    type Err = String;
+
+    /// Parses a public key from its checksummed or hexadecimal representation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_address_or_hex(s)
+    }
+}
+
+impl FromStr for Hash {
+    type Err = String;
+
+    /// Parses a hash from its checksummed or hexadecimal representation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_address_or_hex(s)
+    }
+}
+
+fn polymod(values: impl Iterator<Item = u8>) -> u32 {
+    values.fold(1, |checksum, value| {
+        let top = checksum >> 25;
+        let mut checksum = ((checksum & 0x01ff_ffff) << 5) ^ u32::from(value);
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+        checksum
+    })
+}
+
+fn expand_prefix(prefix: &str) -> impl Iterator<Item = u8> + '_ {
+    let high = prefix.bytes().map(|b| b >> 5);
+    let low = prefix.bytes().map(|b| b & 0x1f);
+    high.chain(Some(0)).chain(low)
+}
+
+/// Regroups bits of the values, padding the last group with zeros if `pad` is set.
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc = 0_u32;
+    let mut bits = 0;
+    let mut result = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    let max = (1 << to) - 1;
+    for &value in data {
+        acc = (acc << from) | u32::from(value);
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            result.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            result.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || (acc << (to - bits)) & max != 0 {
+        return None;
+    }
+    Some(result)
+}
+
+fn encode(prefix: &str, bytes: &[u8]) -> String {
+    let data = convert_bits(bytes, 8, 5, true).expect("Padded conversion never fails");
+    let checksum = polymod(
+        expand_prefix(prefix)
+            .chain(data.iter().cloned())
+            .chain([0; CHECKSUM_SIZE].iter().cloned()),
+    ) ^ 1;
+
+    let mut encoded = String::with_capacity(prefix.len() + 1 + data.len() + CHECKSUM_SIZE);
+    encoded.push_str(prefix);
+    encoded.push(SEPARATOR);
+    for value in data {
+        encoded.push(char::from(CHARSET[value as usize]));
+    }
+    for i in 0..CHECKSUM_SIZE {
+        let value = (checksum >> (5 * (CHECKSUM_SIZE - 1 - i))) & 0x1f;
+        encoded.push(char::from(CHARSET[value as usize]));
+    }
+    encoded
+}
+
+fn decode(s: &str) -> Result<(String, Vec<u8>), AddressError> {
+    if s.len() > MAX_LENGTH {
+        return Err(AddressError::InvalidFormat);
+    }
+    let has_lower = s.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = s.chars().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return Err(AddressError::MixedCase);
+    }
+    let s = s.to_ascii_lowercase();
+
+    let separator = s.rfind(SEPARATOR).ok_or(AddressError::InvalidFormat)?;
+    let (prefix, data) = (&s[..separator], &s[separator + 1..]);
+    if prefix.is_empty() || data.len() < CHECKSUM_SIZE {
+        return Err(AddressError::InvalidFormat);
+    }
+    if let Some(c) = prefix.chars().find(|&c| c < '!' || c > '~') {
+        return Err(AddressError::InvalidCharacter(c));
+    }
+
+    let data = data
+        .chars()
+        .map(|c| {
+            CHARSET
+                .iter()
+                .position(|&b| char::from(b) == c)
+                .map(|value| value as u8)
+                .ok_or(AddressError::InvalidCharacter(c))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if polymod(expand_prefix(prefix).chain(data.iter().cloned())) != 1 {
+        return Err(AddressError::InvalidChecksum);
+    }
+
+    let data = &data[..data.len() - CHECKSUM_SIZE];
+    let bytes = convert_bits(data, 5, 8, false).ok_or(AddressError::InvalidLength)?;
+    Ok((prefix.to_owned(), bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_vectors() {
+        let valid = [
+            "A12UEL5L",
+            "a12uel5l",
+            "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw",
+            "split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w",
+        ];
+        for s in &valid {
+            assert!(decode(s).is_ok(), "{}", s);
+        }
+
+        assert_eq!(decode("A12UEl5L"), Err(AddressError::MixedCase));
+        assert_eq!(decode("pzry9x0s0muk"), Err(AddressError::InvalidFormat));
+        assert_eq!(decode("1pzry9x0s0muk"), Err(AddressError::InvalidFormat));
+        assert_eq!(decode("li1dgmt3"), Err(AddressError::InvalidFormat));
+        assert_eq!(
+            decode("x1b4n0q5v"),
+            Err(AddressError::InvalidCharacter('b'))
+        );
+        assert_eq!(decode("a12uel5x"), Err(AddressError::InvalidChecksum));
+    }
+
+    #[test]
+    fn test_public_key_address() {
+        let mut bytes = [0; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let key = PublicKey::new(bytes);
+        let address = key.to_address();
+        assert_eq!(
+            address,
+            "expk1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0sdn78gf"
+        );
+        assert_eq!(PublicKey::from_address(&address), Ok(key));
+        assert_eq!(PublicKey::from_address(&address.to_uppercase()), Ok(key));
+        assert_eq!(address.parse::<PublicKey>(), Ok(key));
+        assert_eq!(key.to_hex().parse::<PublicKey>(), Ok(key));
+
+        let hash = Hash::new(bytes);
+        assert_eq!(
+            hash.to_address(),
+            "exhash1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0svqzlfu"
+        );
+        assert_eq!(
+            PublicKey::from_address(&hash.to_address()),
+            Err(AddressError::InvalidPrefix)
+        );
+    }
+
+    #[test]
+    fn test_typos_are_detected() {
+        let address = PublicKey::new([7; 32]).to_address();
+        let data_start = PublicKey::PREFIX.len() + 1;
+        for i in data_start..address.len() {
+            for &c in CHARSET.iter() {
+                let c = char::from(c);
+                if address[i..].starts_with(c) {
+                    continue;
+                }
+                let mut typo = address[..i].to_owned();
+                typo.push(c);
+                typo.push_str(&address[i + 1..]);
+                assert_eq!(
+                    PublicKey::from_address(&typo),
+                    Err(AddressError::InvalidChecksum)
+                );
+            }
+        }
+
+        // Swapping adjacent characters is detected as well.
+        let mut swapped: Vec<char> = address.chars().collect();
+        let mut i = data_start;
+        while swapped[i] == swapped[i + 1] {
+            i += 1;
+        }
+        swapped.swap(i, i + 1);
+        let swapped: String = swapped.into_iter().collect();
+        assert!(PublicKey::from_address(&swapped).is_err());
+    }
+
+    #[test]
+    fn test_invalid_length() {
+        let short = encode(PublicKey::PREFIX, &[1; 31]);
+        assert_eq!(
+            PublicKey::from_address(&short),
+            Err(AddressError::InvalidLength)
+        );
+        assert!("expk1".parse::<PublicKey>().is_err());
+        assert_eq!("0102".parse::<PublicKey>(), Err("Invalid hex".to_owned()));
+    }
+}
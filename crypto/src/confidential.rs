@@ -0,0 +1,400 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Confidential amounts based on Pedersen commitments and range proofs.
+//!
+//! A [`Commitment`] hides an amount `v` as `v * G + r * H`, where `G` and `H` are
+//! independent generators of the Ristretto group and `r` is a secret [`Blinding`]
+//! factor. Commitments are additively homomorphic: the sum of two commitments commits
+//! to the sum of the amounts under the sum of the blinding factors. Thus, validators
+//! may check that a transfer neither creates nor destroys funds by comparing sums
+//! of commitments, without learning the amounts.
+//!
+//! Since amounts are defined modulo the group order, the homomorphism alone does not
+//! prevent inflation: a "negative" amount would balance the equation as well. A
+//! [`RangeProof`] (Bulletproof) shows that the committed amount lies
+//! in `[0, 2^64)`; with range proofs for every output, the sum check guarantees
+//! that no funds are created.
+//!
+//! The module is available with the `confidential` feature. Its dependencies require
+//! Rust 1.41 or newer, while the rest of the crate supports older toolchains.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate exonum_crypto;
+//! use exonum_crypto::confidential::{Blinding, Commitment, RangeProof};
+//!
+//! # exonum_crypto::init();
+//! let balance_blinding = Blinding::random();
+//! let balance = Commitment::new(100, &balance_blinding);
+//!
+//! // Transfer of 30 tokens; the amount and the remaining balance are proven
+//! // to be non-negative.
+//! let amount_blinding = Blinding::random();
+//! let (amount_proof, amount) = RangeProof::new(30, &amount_blinding);
+//! let rest_blinding = balance_blinding - amount_blinding;
+//! let (rest_proof, _) = RangeProof::new(70, &rest_blinding);
+//!
+//! assert!(amount_proof.verify(&amount));
+//! assert!(rest_proof.verify(&(balance - amount)));
+//! ```
+//!
+//! [`Commitment`]: struct.Commitment.html
+//! [`Blinding`]: struct.Blinding.html
+//! [`RangeProof`]: struct.RangeProof.html
+
+use bulletproofs::{BulletproofGens, PedersenGens, RangeProof as Bulletproof};
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
+use merlin::Transcript;
+use serde::{
+    de::{self, Deserialize, Deserializer},
+    Serialize, Serializer,
+};
+
+use std::{
+    fmt,
+    ops::{Add, Sub},
+};
+
+use hex::{encode as encode_hex, FromHex};
+
+use super::{crypto_impl, write_short_hex};
+
+/// Number of bytes in a `Commitment`.
+pub const COMMITMENT_LENGTH: usize = 32;
+
+/// Number of bytes in a `Blinding`.
+pub const BLINDING_LENGTH: usize = 32;
+
+/// Bit size of the range proven by a `RangeProof`.
+pub const RANGE_PROOF_BITS: usize = 64;
+
+/// Domain separation label of the range proof transcripts.
+const TRANSCRIPT_LABEL: &[u8] = b"exonum.confidential.range_proof";
+
+/// Secret blinding factor of a `Commitment`.
+///
+/// The blinding factor must be known to open the commitment or to create a range
+/// proof for it, so it should be kept secret or shared only with the counterparty.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Blinding(Scalar);
+
+impl Blinding {
+    /// Generates a blinding factor using a cryptographically secure pseudo-random
+    /// number generator.
+    pub fn random() -> Self {
+        let mut bytes = [0; 64];
+        crypto_impl::fill_random(&mut bytes);
+        Blinding(Scalar::from_bytes_mod_order_wide(&bytes))
+    }
+
+    /// Returns the zero blinding factor. Commitments with the zero blinding factor
+    /// do not hide the amount and are used for publicly known amounts.
+    pub fn zero() -> Self {
+        Blinding(Scalar::zero())
+    }
+
+    /// Creates a blinding factor from its canonical byte representation. Returns `None`
+    /// if the slice has an invalid length or does not encode a reduced scalar.
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != BLINDING_LENGTH {
+            return None;
+        }
+        let mut buf = [0; BLINDING_LENGTH];
+        buf.copy_from_slice(bytes);
+        Scalar::from_canonical_bytes(buf).map(Blinding)
+    }
+
+    /// Returns the byte representation of the blinding factor.
+    pub fn to_bytes(&self) -> [u8; BLINDING_LENGTH] {
+        self.0.to_bytes()
+    }
+}
+
+impl Add for Blinding {
+    type Output = Blinding;
+
+    fn add(self, other: Blinding) -> Blinding {
+        Blinding(self.0 + other.0)
+    }
+}
+
+impl Sub for Blinding {
+    type Output = Blinding;
+
+    fn sub(self, other: Blinding) -> Blinding {
+        Blinding(self.0 - other.0)
+    }
+}
+
+impl fmt::Debug for Blinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Blinding(..)")
+    }
+}
+
+/// Pedersen commitment to a 64-bit amount.
+///
+/// The commitment is stored as a compressed Ristretto point; a `Commitment` can be
+/// constructed only from a valid point.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Commitment([u8; COMMITMENT_LENGTH]);
+
+impl Commitment {
+    /// Commits to the amount with the given blinding factor.
+    pub fn new(amount: u64, blinding: &Blinding) -> Self {
+        let point = PedersenGens::default().commit(Scalar::from(amount), blinding.0);
+        Commitment::from_point(&point)
+    }
+
+    /// Creates a commitment from its byte representation. Returns `None` if the slice
+    /// has an invalid length or does not encode a valid point.
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != COMMITMENT_LENGTH {
+            return None;
+        }
+        let mut buf = [0; COMMITMENT_LENGTH];
+        buf.copy_from_slice(bytes);
+        CompressedRistretto(buf)
+            .decompress()
+            .map(|_| Commitment(buf))
+    }
+
+    /// Checks that the commitment opens to the given amount and blinding factor.
+    pub fn opens_to(&self, amount: u64, blinding: &Blinding) -> bool {
+        *self == Commitment::new(amount, blinding)
+    }
+
+    fn from_point(point: &RistrettoPoint) -> Self {
+        Commitment(point.compress().to_bytes())
+    }
+
+    fn to_point(&self) -> RistrettoPoint {
+        CompressedRistretto(self.0)
+            .decompress()
+            .expect("Commitment is always a valid point")
+    }
+}
+
+impl Add for Commitment {
+    type Output = Commitment;
+
+    fn add(self, other: Commitment) -> Commitment {
+        Commitment::from_point(&(self.to_point() + other.to_point()))
+    }
+}
+
+impl Sub for Commitment {
+    type Output = Commitment;
+
+    fn sub(self, other: Commitment) -> Commitment {
+        Commitment::from_point(&(self.to_point() - other.to_point()))
+    }
+}
+
+impl AsRef<[u8]> for Commitment {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Commitment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Commitment(")?;
+        write_short_hex(f, &self.0)?;
+        write!(f, ")")
+    }
+}
+
+impl Serialize for Commitment {
+    fn serialize<S>(&self, ser: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        ser.serialize_str(&encode_hex(&self.0[..]))
+    }
+}
+
+impl<'de> Deserialize<'de> for Commitment {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bytes =
+            Vec::<u8>::from_hex(String::deserialize(deserializer)?).map_err(de::Error::custom)?;
+        Commitment::from_slice(&bytes).ok_or_else(|| de::Error::custom("Invalid commitment"))
+    }
+}
+
+/// Proof that a `Commitment` hides an amount in the range `[0, 2^64)`.
+///
+/// The proof is kept in the serialized form; it is parsed on verification.
+#[derive(Clone, PartialEq, Eq)]
+pub struct RangeProof(Vec<u8>);
+
+impl RangeProof {
+    /// Proves that the amount is within the range and returns the proof together
+    /// with the commitment to the amount.
+    pub fn new(amount: u64, blinding: &Blinding) -> (Self, Commitment) {
+        let mut transcript = Transcript::new(TRANSCRIPT_LABEL);
+        let (proof, commitment) = Bulletproof::prove_single(
+            &BulletproofGens::new(RANGE_PROOF_BITS, 1),
+            &PedersenGens::default(),
+            &mut transcript,
+            amount,
+            &blinding.0,
+            RANGE_PROOF_BITS,
+        )
+        .expect("Range proof for a 64-bit amount cannot fail");
+        (
+            RangeProof(proof.to_bytes()),
+            Commitment(commitment.to_bytes()),
+        )
+    }
+
+    /// Creates a proof from its byte representation. Returns `None` if the bytes
+    /// are not a well-formed range proof.
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        Bulletproof::from_bytes(bytes)
+            .ok()
+            .map(|_| RangeProof(bytes.to_vec()))
+    }
+
+    /// Returns the byte representation of the proof.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Verifies that the commitment hides an amount in the range `[0, 2^64)`.
+    pub fn verify(&self, commitment: &Commitment) -> bool {
+        let proof = match Bulletproof::from_bytes(&self.0) {
+            Ok(proof) => proof,
+            Err(_) => return false,
+        };
+        let mut transcript = Transcript::new(TRANSCRIPT_LABEL);
+        proof
+            .verify_single(
+                &BulletproofGens::new(RANGE_PROOF_BITS, 1),
+                &PedersenGens::default(),
+                &mut transcript,
+                &CompressedRistretto(commitment.0),
+                RANGE_PROOF_BITS,
+            )
+            .is_ok()
+    }
+}
+
+impl fmt::Debug for RangeProof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RangeProof(")?;
+        write_short_hex(f, &self.0)?;
+        write!(f, ")")
+    }
+}
+
+impl Serialize for RangeProof {
+    fn serialize<S>(&self, ser: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        ser.serialize_str(&encode_hex(&self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for RangeProof {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bytes =
+            Vec::<u8>::from_hex(String::deserialize(deserializer)?).map_err(de::Error::custom)?;
+        RangeProof::from_slice(&bytes).ok_or_else(|| de::Error::custom("Invalid range proof"))
+    }
+}
+
+/// Checks that the sum of the `inputs` commits to the same amount as the sum
+/// of the `outputs`, assuming that both sums are computed with the same blinding
+/// factor. Together with range proofs for the outputs this guarantees that
+/// no funds are created.
+pub fn verify_balance(inputs: &[Commitment], outputs: &[Commitment]) -> bool {
+    let sum = |commitments: &[Commitment]| {
+        commitments
+            .iter()
+            .fold(RistrettoPoint::default(), |acc, c| acc + c.to_point())
+    };
+    sum(inputs) == sum(outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json;
+
+    #[test]
+    fn range_proof() {
+        let blinding = Blinding::random();
+        let (proof, commitment) = RangeProof::new(42, &blinding);
+        assert!(commitment.opens_to(42, &blinding));
+        assert!(!commitment.opens_to(43, &blinding));
+        assert!(proof.verify(&commitment));
+        assert!(!proof.verify(&Commitment::new(42, &Blinding::random())));
+
+        let mut bytes = proof.as_bytes().to_vec();
+        bytes[0] ^= 1;
+        assert!(RangeProof::from_slice(&bytes).map_or(true, |proof| !proof.verify(&commitment)));
+    }
+
+    #[test]
+    fn homomorphism() {
+        let (r1, r2) = (Blinding::random(), Blinding::random());
+        let sum = Commitment::new(30, &r1) + Commitment::new(12, &r2);
+        assert_eq!(sum, Commitment::new(42, &(r1 + r2)));
+        assert_eq!(sum - Commitment::new(12, &r2), Commitment::new(30, &r1));
+
+        let (r3, r4) = (Blinding::random(), r1 + r2 - Blinding::random());
+        let r5 = r1 + r2 - r3 - r4;
+        assert!(verify_balance(
+            &[Commitment::new(30, &r1), Commitment::new(12, &r2)],
+            &[
+                Commitment::new(20, &r3),
+                Commitment::new(20, &r4),
+                Commitment::new(2, &r5),
+            ],
+        ));
+        assert!(!verify_balance(
+            &[Commitment::new(30, &r1)],
+            &[Commitment::new(31, &r1)],
+        ));
+    }
+
+    #[test]
+    fn serialization() {
+        let (proof, commitment) = RangeProof::new(7, &Blinding::random());
+        let json = serde_json::to_string(&(&proof, &commitment)).unwrap();
+        let (de_proof, de_commitment): (RangeProof, Commitment) =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(de_proof, proof);
+        assert_eq!(de_commitment, commitment);
+
+        assert!(Commitment::from_slice(&[1; COMMITMENT_LENGTH - 1]).is_none());
+        let blinding = Blinding::random();
+        assert_eq!(Blinding::from_slice(&blinding.to_bytes()), Some(blinding));
+        assert!(Blinding::from_slice(&[0xff; BLINDING_LENGTH]).is_none());
+    }
+}
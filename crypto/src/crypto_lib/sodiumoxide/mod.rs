@@ -54,7 +54,8 @@ pub use self::ed25519::State as SignState;
 /// for sodiumoxide-based implementation.
 pub use self::sha256::State as HashState;
 
-use self::sodiumoxide::crypto::{hash::sha256, sign::ed25519};
+use self::sodiumoxide::crypto::{box_, hash::sha256, sealedbox, sign::ed25519};
+use self::sodiumoxide::randombytes;
 
 pub mod x25519;
 
@@ -73,6 +74,9 @@ pub const SEED_LENGTH: usize = ed25519::SEEDBYTES;
 /// Number of bytes in a signature.
 pub const SIGNATURE_LENGTH: usize = ed25519::SIGNATUREBYTES;
 
+/// Number of bytes added to a message by the sealed box encryption.
+pub const SEALED_BOX_OVERHEAD: usize = sealedbox::SEALBYTES;
+
 /// Hash of an empty slice.
 pub const EMPTY_SLICE_HASH: Hash = Hash([
     227, 176, 196, 66, 152, 252, 28, 20, 154, 251, 244, 200, 153, 111, 185, 36, 39, 174, 65, 228,
@@ -112,3 +116,28 @@ pub fn verify(sig: &Signature, data: &[u8], pub_key: &PublicKey) -> bool {
 pub fn hash(data: &[u8]) -> Hash {
     sha256::hash(data)
 }
+
+/// Encrypts `data` to the Curve25519 counterpart of the given Ed25519 public key
+/// with an anonymous sender.
+pub fn seal(data: &[u8], public_key: &PublicKey) -> Vec<u8> {
+    let public_key = ed25519::convert_ed_pk_to_curve25519(&public_key.0);
+    sealedbox::seal(data, &box_::PublicKey(public_key))
+}
+
+/// Decrypts the sealed box with the Curve25519 counterparts of the given Ed25519 keys.
+pub fn open_sealed(
+    ciphertext: &[u8],
+    public_key: &PublicKey,
+    secret_key: &SecretKey,
+) -> Option<Vec<u8>> {
+    let (public_key, secret_key) =
+        ed25519::convert_ed_keypair_to_curve25519(public_key.clone(), secret_key.clone());
+    let public_key = box_::PublicKey::from_slice(&public_key.0[..])?;
+    let secret_key = box_::SecretKey::from_slice(&secret_key.0[..box_::SECRETKEYBYTES])?;
+    sealedbox::open(ciphertext, &public_key, &secret_key).ok()
+}
+
+/// Fills the buffer with bytes from a cryptographically secure pseudo-random number generator.
+pub fn fill_random(buf: &mut [u8]) {
+    randombytes::randombytes_into(buf)
+}
@@ -20,17 +20,25 @@
 //! cryptography applied in the system and add abstractions best
 //! suited for Exonum.
 
+#[cfg(feature = "confidential")]
+extern crate bulletproofs;
 extern crate byteorder;
 extern crate chrono;
+#[cfg(feature = "confidential")]
+extern crate curve25519_dalek;
 extern crate hex;
+#[cfg(feature = "confidential")]
+extern crate merlin;
 extern crate rust_decimal;
 extern crate serde;
 extern crate serde_json;
 extern crate uuid;
 
+pub use self::address::{Address, AddressError};
 #[doc(inline)]
 pub use self::crypto_impl::{
-    HASH_SIZE, PUBLIC_KEY_LENGTH, SECRET_KEY_LENGTH, SEED_LENGTH, SIGNATURE_LENGTH,
+    HASH_SIZE, PUBLIC_KEY_LENGTH, SEALED_BOX_OVERHEAD, SECRET_KEY_LENGTH, SEED_LENGTH,
+    SIGNATURE_LENGTH,
 };
 #[cfg(feature = "sodiumoxide-crypto")]
 pub use self::crypto_lib::sodiumoxide::x25519;
@@ -60,6 +68,11 @@ use self::crypto_lib::sodiumoxide as crypto_impl;
 #[macro_use]
 mod macros;
 
+mod address;
+
+#[cfg(feature = "confidential")]
+pub mod confidential;
+
 pub(crate) mod crypto_lib;
 
 /// The size to crop the string in debug messages.
@@ -178,6 +191,44 @@ pub fn hash(data: &[u8]) -> Hash {
     Hash(dig)
 }
 
+/// Encrypts `data` to the owner of the given public key.
+///
+/// The data is encrypted with the [sealed box] construction: the sender is anonymous,
+/// and only the owner of the corresponding secret key can decrypt the resulting
+/// ciphertext with [`open_sealed`]. The Ed25519 key is converted to its Curve25519
+/// counterpart, so the same keys are used for signing and encryption. The ciphertext
+/// is [`SEALED_BOX_OVERHEAD`] bytes longer than the data.
+///
+/// [sealed box]: https://download.libsodium.org/doc/public-key_cryptography/sealed_boxes
+/// [`open_sealed`]: fn.open_sealed.html
+/// [`SEALED_BOX_OVERHEAD`]: constant.SEALED_BOX_OVERHEAD.html
+///
+/// # Examples
+///
+/// ```
+/// # extern crate exonum_crypto;
+///
+/// # exonum_crypto::init();
+/// let (public_key, secret_key) = exonum_crypto::gen_keypair();
+/// let data = b"Confidential data";
+/// let ciphertext = exonum_crypto::seal(data, &public_key);
+/// let decrypted = exonum_crypto::open_sealed(&ciphertext, &public_key, &secret_key);
+/// assert_eq!(decrypted.unwrap(), data);
+/// ```
+pub fn seal(data: &[u8], public_key: &PublicKey) -> Vec<u8> {
+    crypto_impl::seal(data, &public_key.0)
+}
+
+/// Decrypts the data encrypted with [`seal`](fn.seal.html) to the given public key.
+/// Returns `None` if the ciphertext is malformed or is encrypted to another key.
+pub fn open_sealed(
+    ciphertext: &[u8],
+    public_key: &PublicKey,
+    secret_key: &SecretKey,
+) -> Option<Vec<u8>> {
+    crypto_impl::open_sealed(ciphertext, &public_key.0, &secret_key.0)
+}
+
 /// A common trait for the ability to compute a cryptographic hash.
 pub trait CryptoHash {
     /// Returns a hash of the value.
@@ -490,8 +541,8 @@ implement_private_crypto_wrapper! {
     struct Seed, SEED_LENGTH
 }
 
-implement_serde! {Hash}
-implement_serde! {PublicKey}
+implement_serde! {Hash, FromStr}
+implement_serde! {PublicKey, FromStr}
 implement_serde! {SecretKey}
 implement_serde! {Seed}
 implement_serde! {Signature}
@@ -581,6 +632,22 @@ impl CryptoHash for i64 {
     }
 }
 
+impl CryptoHash for u128 {
+    fn hash(&self) -> Hash {
+        let mut v = [0; 16];
+        LittleEndian::write_u128(&mut v, *self);
+        hash(&v)
+    }
+}
+
+impl CryptoHash for i128 {
+    fn hash(&self) -> Hash {
+        let mut v = [0; 16];
+        LittleEndian::write_i128(&mut v, *self);
+        hash(&v)
+    }
+}
+
 impl CryptoHash for () {
     fn hash(&self) -> Hash {
         Hash(crypto_impl::EMPTY_SLICE_HASH)
@@ -674,6 +741,22 @@ mod tests {
         assert_eq!(original, from_hex);
     }
 
+    #[test]
+    fn seal_open() {
+        let (public_key, secret_key) = gen_keypair();
+        let (other_public_key, other_secret_key) = gen_keypair();
+        let data = b"Confidential data";
+
+        let ciphertext = seal(data, &public_key);
+        assert_eq!(ciphertext.len(), data.len() + SEALED_BOX_OVERHEAD);
+        assert_eq!(
+            open_sealed(&ciphertext, &public_key, &secret_key).unwrap(),
+            data
+        );
+        assert!(open_sealed(&ciphertext, &other_public_key, &other_secret_key).is_none());
+        assert!(open_sealed(&ciphertext[1..], &public_key, &secret_key).is_none());
+    }
+
     #[test]
     fn zero_hash() {
         let hash = Hash::zero();
@@ -121,7 +121,7 @@ macro_rules! implement_private_crypto_wrapper {
 }
 
 macro_rules! implement_serde {
-    ($name:ident) => {
+    (@common $name:ident) => {
         impl FromHex for $name {
             type Error = FromHexError;
 
@@ -144,6 +144,9 @@ macro_rules! implement_serde {
                 ser.serialize_str(&hex_string)
             }
         }
+    };
+    ($name:ident) => {
+        implement_serde!(@common $name);
 
         impl<'de> Deserialize<'de> for $name {
             fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -168,6 +171,34 @@ macro_rules! implement_serde {
             }
         }
     };
+    // Values having a checksummed representation are serialized as hex, but can be
+    // deserialized from both representations.
+    ($name:ident, FromStr) => {
+        implement_serde!(@common $name);
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct StrVisitor;
+
+                impl<'v> Visitor<'v> for StrVisitor {
+                    type Value = $name;
+                    fn expecting(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+                        write!(fmt, "expecting hex or checksummed str.")
+                    }
+                    fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+                    where
+                        E: de::Error,
+                    {
+                        s.parse().map_err(de::Error::custom)
+                    }
+                }
+                deserializer.deserialize_str(StrVisitor)
+            }
+        }
+    };
 }
 
 macro_rules! implement_index_traits {
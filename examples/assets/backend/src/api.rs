@@ -0,0 +1,126 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! REST API.
+
+use exonum::{
+    api::{self, ServiceApiBuilder, ServiceApiState},
+    blockchain::{self, BlockProof, TransactionMessage},
+    crypto::Hash,
+    explorer::BlockchainExplorer,
+    storage::{ListProof, MapProof},
+};
+
+use schema::{Asset, Schema};
+use ASSETS_SERVICE_ID;
+
+/// Describes query parameters for the `handle_asset` and `handle_asset_history` endpoints.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AssetQuery {
+    /// Identifier of the requested asset.
+    pub id: Hash,
+}
+
+impl AssetQuery {
+    /// Creates new `AssetQuery` with the given `id`.
+    pub fn new(id: Hash) -> Self {
+        AssetQuery { id }
+    }
+}
+
+/// Provenance history of the asset.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssetHistory {
+    /// Proof of the list of transaction hashes.
+    pub proof: ListProof<Hash>,
+    /// Transactions which have registered and transferred the asset, in the order
+    /// of their execution.
+    pub transactions: Vec<TransactionMessage>,
+}
+
+/// Describes the information required to prove the current state of the asset
+/// and its provenance.
+///
+/// The `history` proof is checked against the `history_hash` of the asset, which in turn
+/// is proven by `asset_proof`, `state_proof` and `block_info`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssetInfo {
+    /// Proof of the last block.
+    pub block_info: BlockProof,
+    /// Proof of the asset registry table in the blockchain state.
+    pub state_proof: MapProof<Hash, Hash>,
+    /// Proof of the asset in the table of assets.
+    pub asset_proof: MapProof<Hash, Asset>,
+    /// Provenance history of the asset, or `None` if the asset does not exist.
+    pub history: Option<AssetHistory>,
+}
+
+/// Public service API.
+#[derive(Debug, Clone, Copy)]
+pub struct PublicApi;
+
+impl PublicApi {
+    /// Endpoint for getting a single asset.
+    pub fn handle_asset(state: &ServiceApiState, query: AssetQuery) -> api::Result<Option<Asset>> {
+        let snapshot = state.snapshot();
+        let schema = Schema::new(&snapshot);
+        Ok(schema.assets().get(&query.id))
+    }
+
+    /// Endpoint for getting the asset together with its provenance history and proofs.
+    pub fn handle_asset_history(
+        state: &ServiceApiState,
+        query: AssetQuery,
+    ) -> api::Result<AssetInfo> {
+        let snapshot = state.snapshot();
+        let (state_proof, block_info) = {
+            let core_schema = blockchain::Schema::new(&snapshot);
+            let last_block_height = state.blockchain().last_block().height();
+            let block_proof = core_schema.block_and_precommits(last_block_height).unwrap();
+            let state_proof = core_schema.get_proof_to_service_table(ASSETS_SERVICE_ID, 0);
+            (state_proof, block_proof)
+        };
+
+        let schema = Schema::new(&snapshot);
+        let asset_proof = schema.assets().get_proof(query.id);
+        let explorer = BlockchainExplorer::new(state.blockchain());
+        let history = schema.assets().get(&query.id).map(|_| {
+            let history = schema.asset_history(&query.id);
+            let proof = history.get_range_proof(0, history.len());
+            let transactions = history
+                .iter()
+                .map(|tx_hash| explorer.transaction_without_proof(&tx_hash).unwrap())
+                .collect();
+            AssetHistory {
+                proof,
+                transactions,
+            }
+        });
+
+        Ok(AssetInfo {
+            block_info,
+            state_proof,
+            asset_proof,
+            history,
+        })
+    }
+
+    /// Wires the above endpoints to public API scope of the given `ServiceApiBuilder`.
+    pub fn wire(builder: &mut ServiceApiBuilder) {
+        builder
+            .public_scope()
+            .endpoint("v1/assets", Self::handle_asset)
+            .endpoint("v1/assets/history", Self::handle_asset_history);
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Asset registry demo. This example shows how to use Exonum framework to track
+//! the ownership of unique assets, such as real estate or works of art.
+//!
+//! Each asset is identified by a hash, refers to its metadata stored off-chain by hash,
+//! and has a single owner. The provenance of the asset, i.e., the full history of
+//! its registration and transfers, is stored in a Merkelized list, so that it can
+//! be proven to a client along with the current state of the asset.
+
+#![deny(
+    missing_debug_implementations,
+    missing_docs,
+    unsafe_code,
+    bare_trait_objects
+)]
+
+#[macro_use]
+extern crate exonum;
+#[macro_use]
+extern crate failure;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+
+pub mod api;
+pub mod schema;
+pub mod transactions;
+
+use exonum::{
+    api::ServiceApiBuilder,
+    blockchain::{self, Transaction, TransactionSet},
+    crypto::Hash,
+    encoding::Error as EncodingError,
+    helpers::fabric,
+    messages::RawTransaction,
+    storage::Snapshot,
+};
+
+use api::PublicApi;
+use schema::Schema;
+use transactions::AssetTransactions;
+
+/// Unique service ID.
+const ASSETS_SERVICE_ID: u16 = 132;
+/// Name of the service.
+const SERVICE_NAME: &str = "assets";
+
+/// Exonum `Service` implementation.
+#[derive(Debug, Default)]
+pub struct Service;
+
+impl blockchain::Service for Service {
+    fn service_id(&self) -> u16 {
+        ASSETS_SERVICE_ID
+    }
+
+    fn service_name(&self) -> &'static str {
+        SERVICE_NAME
+    }
+
+    fn state_hash(&self, view: &dyn Snapshot) -> Vec<Hash> {
+        let schema = Schema::new(view);
+        schema.state_hash()
+    }
+
+    fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<dyn Transaction>, EncodingError> {
+        AssetTransactions::tx_from_raw(raw).map(Into::into)
+    }
+
+    fn wire_api(&self, builder: &mut ServiceApiBuilder) {
+        PublicApi::wire(builder);
+    }
+}
+
+/// An asset registry service creator for the `NodeBuilder`.
+#[derive(Debug, Clone, Copy)]
+pub struct ServiceFactory;
+
+impl fabric::ServiceFactory for ServiceFactory {
+    fn service_name(&self) -> &str {
+        SERVICE_NAME
+    }
+
+    fn make_service(&mut self, _: &fabric::Context) -> Box<dyn blockchain::Service> {
+        Box::new(Service)
+    }
+}
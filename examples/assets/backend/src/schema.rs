@@ -0,0 +1,124 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Asset registry database schema.
+
+use exonum::{
+    crypto::{Hash, PublicKey},
+    storage::{Fork, ProofListIndex, ProofMapIndex, Snapshot},
+};
+
+encoding_struct! {
+    /// Asset information stored in the database.
+    struct Asset {
+        /// Identifier of the asset.
+        id: &Hash,
+
+        /// Hash of the asset metadata stored off-chain.
+        metadata_hash: &Hash,
+
+        /// Current owner of the asset.
+        owner: &PublicKey,
+
+        /// Length of the provenance history.
+        history_len: u64,
+
+        /// Root hash of the provenance history.
+        history_hash: &Hash,
+    }
+}
+
+/// Asset registry database schema.
+#[derive(Debug)]
+pub struct Schema<T> {
+    view: T,
+}
+
+impl<T> Schema<T> {
+    /// Creates a new schema from the database view.
+    pub fn new(view: T) -> Self {
+        Schema { view }
+    }
+}
+
+impl<T> Schema<T>
+where
+    T: AsRef<dyn Snapshot>,
+{
+    /// Returns the `ProofMapIndex` of assets keyed by their identifiers.
+    pub fn assets(&self) -> ProofMapIndex<&T, Hash, Asset> {
+        ProofMapIndex::new("assets.assets", &self.view)
+    }
+
+    /// Returns the provenance history of the asset, i.e., hashes of the transactions
+    /// which have registered and transferred the asset.
+    pub fn asset_history(&self, id: &Hash) -> ProofListIndex<&T, Hash> {
+        ProofListIndex::new_in_family("assets.asset_history", id, &self.view)
+    }
+
+    /// Returns the state hash of the asset registry service.
+    pub fn state_hash(&self) -> Vec<Hash> {
+        vec![self.assets().merkle_root()]
+    }
+}
+
+impl<'a> Schema<&'a mut Fork> {
+    /// Returns the mutable `ProofMapIndex` of assets.
+    pub fn assets_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, Asset> {
+        ProofMapIndex::new("assets.assets", &mut self.view)
+    }
+
+    /// Returns the mutable provenance history of the asset.
+    pub fn asset_history_mut(&mut self, id: &Hash) -> ProofListIndex<&mut Fork, Hash> {
+        ProofListIndex::new_in_family("assets.asset_history", id, &mut self.view)
+    }
+
+    /// Registers the asset owned by the given key and starts its history.
+    pub fn register_asset(
+        &mut self,
+        id: &Hash,
+        metadata_hash: &Hash,
+        owner: &PublicKey,
+        transaction: &Hash,
+    ) {
+        let asset = {
+            let mut history = self.asset_history_mut(id);
+            history.push(*transaction);
+            Asset::new(
+                id,
+                metadata_hash,
+                owner,
+                history.len(),
+                &history.merkle_root(),
+            )
+        };
+        self.assets_mut().put(id, asset);
+    }
+
+    /// Changes the owner of the asset and appends the transaction to its history.
+    pub fn transfer_asset(&mut self, asset: Asset, new_owner: &PublicKey, transaction: &Hash) {
+        let asset = {
+            let mut history = self.asset_history_mut(asset.id());
+            history.push(*transaction);
+            Asset::new(
+                asset.id(),
+                asset.metadata_hash(),
+                new_owner,
+                history.len(),
+                &history.merkle_root(),
+            )
+        };
+        self.assets_mut().put(asset.id(), asset.clone());
+    }
+}
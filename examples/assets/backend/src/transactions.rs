@@ -0,0 +1,144 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Asset registry transactions.
+
+// Workaround for `failure` see https://github.com/rust-lang-nursery/failure/issues/223 and
+// ECR-1771 for the details.
+#![allow(bare_trait_objects)]
+// Suppress a warning in `transactions!` macro call:
+#![cfg_attr(feature = "cargo-clippy", allow(clippy::redundant_field_names))]
+
+use exonum::{
+    blockchain::{ExecutionError, ExecutionResult, Transaction, TransactionContext},
+    crypto::{Hash, PublicKey, SecretKey},
+    messages::{Message, RawTransaction, Signed},
+};
+
+use schema::Schema;
+use ASSETS_SERVICE_ID;
+
+/// Error codes emitted by asset registry transactions during execution.
+#[derive(Debug, Fail)]
+#[repr(u8)]
+pub enum Error {
+    /// Asset with the given identifier is already registered.
+    #[fail(display = "Asset already exists")]
+    AssetAlreadyExists = 0,
+
+    /// Asset with the given identifier does not exist.
+    #[fail(display = "Asset not found")]
+    AssetNotFound = 1,
+
+    /// The author of the transaction does not own the asset.
+    #[fail(display = "Sender is not the owner of the asset")]
+    NotOwner = 2,
+}
+
+impl From<Error> for ExecutionError {
+    fn from(value: Error) -> ExecutionError {
+        let description = value.to_string();
+        ExecutionError::with_description(value as u8, description)
+    }
+}
+
+transactions! {
+    /// Transaction group.
+    pub AssetTransactions {
+
+        /// Registers a new asset owned by the author of the transaction.
+        struct TxRegisterAsset {
+            /// Identifier of the asset.
+            id: &Hash,
+            /// Hash of the asset metadata stored off-chain.
+            metadata_hash: &Hash,
+        }
+
+        /// Transfers the asset owned by the author of the transaction to another owner.
+        struct TxTransferAsset {
+            /// Identifier of the asset.
+            id: &Hash,
+            /// Key of the new owner.
+            new_owner: &PublicKey,
+            /// Auxiliary number to guarantee non-idempotence of transactions.
+            seed: u64,
+        }
+    }
+}
+
+impl TxRegisterAsset {
+    #[doc(hidden)]
+    pub fn sign(
+        author: &PublicKey,
+        id: &Hash,
+        metadata_hash: &Hash,
+        key: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            TxRegisterAsset::new(id, metadata_hash),
+            ASSETS_SERVICE_ID,
+            *author,
+            key,
+        )
+    }
+}
+
+impl TxTransferAsset {
+    #[doc(hidden)]
+    pub fn sign(
+        author: &PublicKey,
+        id: &Hash,
+        new_owner: &PublicKey,
+        seed: u64,
+        key: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            TxTransferAsset::new(id, new_owner, seed),
+            ASSETS_SERVICE_ID,
+            *author,
+            key,
+        )
+    }
+}
+
+impl Transaction for TxRegisterAsset {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let author = context.author();
+        let tx_hash = context.tx_hash();
+
+        let mut schema = Schema::new(context.fork());
+        if schema.assets().contains(self.id()) {
+            Err(Error::AssetAlreadyExists)?;
+        }
+
+        schema.register_asset(self.id(), self.metadata_hash(), &author, &tx_hash);
+        Ok(())
+    }
+}
+
+impl Transaction for TxTransferAsset {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let author = context.author();
+        let tx_hash = context.tx_hash();
+
+        let mut schema = Schema::new(context.fork());
+        let asset = schema.assets().get(self.id()).ok_or(Error::AssetNotFound)?;
+        if *asset.owner() != author {
+            Err(Error::NotOwner)?;
+        }
+
+        schema.transfer_asset(asset, self.new_owner(), &tx_hash);
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[macro_use]
+extern crate serde_json;
+
+#[macro_use]
+extern crate exonum_testkit;
+
+extern crate exonum;
+extern crate exonum_assets;
+
+use exonum::{
+    api::node::public::explorer::TransactionQuery,
+    crypto::{gen_keypair, hash, Hash},
+    messages::{RawTransaction, Signed},
+};
+use exonum_testkit::{ApiKind, TestKit, TestKitApi, TestKitBuilder};
+
+use exonum_assets::{
+    api::{AssetInfo, AssetQuery},
+    schema::Asset,
+    transactions::{TxRegisterAsset, TxTransferAsset},
+    Service,
+};
+
+fn init_testkit() -> TestKit {
+    TestKitBuilder::validator().with_service(Service).create()
+}
+
+/// Assert transaction status
+fn assert_status(
+    api: &TestKitApi,
+    tx: &Signed<RawTransaction>,
+    expected_status: &serde_json::Value,
+) {
+    let info: serde_json::Value = api
+        .public(ApiKind::Explorer)
+        .query(&TransactionQuery::new(tx.hash()))
+        .get("v1/transactions")
+        .unwrap();
+
+    if let serde_json::Value::Object(mut info) = info {
+        let tx_status = info.remove("status").unwrap();
+        assert_eq!(tx_status, *expected_status);
+    } else {
+        panic!("Invalid transaction info format, object expected");
+    }
+}
+
+fn get_asset(api: &TestKitApi, id: Hash) -> Option<Asset> {
+    api.public(ApiKind::Service("assets"))
+        .query(&AssetQuery::new(id))
+        .get("v1/assets")
+        .unwrap()
+}
+
+#[test]
+fn test_register_asset() {
+    let mut testkit = init_testkit();
+    let api = testkit.api();
+
+    let keypair = gen_keypair();
+    let id = hash(b"asset");
+    let tx = TxRegisterAsset::sign(&keypair.0, &id, &hash(b"metadata"), &keypair.1);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_status(&api, &tx, &json!({ "type": "success" }));
+
+    let asset = get_asset(&api, id).unwrap();
+    assert_eq!(asset.owner(), &keypair.0);
+    assert_eq!(asset.metadata_hash(), &hash(b"metadata"));
+    assert_eq!(asset.history_len(), 1);
+
+    // Identifiers of the assets are unique.
+    let other = gen_keypair();
+    let tx = TxRegisterAsset::sign(&other.0, &id, &hash(b"other"), &other.1);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_status(
+        &api,
+        &tx,
+        &json!({ "type": "error", "code": 0, "description": "Asset already exists" }),
+    );
+    assert_eq!(get_asset(&api, id).unwrap().owner(), &keypair.0);
+}
+
+#[test]
+fn test_transfer_asset() {
+    let mut testkit = init_testkit();
+    let api = testkit.api();
+
+    let (alice, bob) = (gen_keypair(), gen_keypair());
+    let id = hash(b"asset");
+    let tx_register = TxRegisterAsset::sign(&alice.0, &id, &hash(b"metadata"), &alice.1);
+    let tx_transfer = TxTransferAsset::sign(&alice.0, &id, &bob.0, 0, &alice.1);
+    // Alice does not own the asset anymore.
+    let tx_invalid = TxTransferAsset::sign(&alice.0, &id, &alice.0, 1, &alice.1);
+    let tx_unknown = TxTransferAsset::sign(&alice.0, &Hash::zero(), &bob.0, 0, &alice.1);
+    testkit.create_block_with_transactions(txvec![
+        tx_register.clone(),
+        tx_transfer.clone(),
+        tx_invalid.clone(),
+        tx_unknown.clone(),
+    ]);
+
+    assert_status(&api, &tx_transfer, &json!({ "type": "success" }));
+    assert_status(
+        &api,
+        &tx_invalid,
+        &json!({ "type": "error", "code": 2, "description": "Sender is not the owner of the asset" }),
+    );
+    assert_status(
+        &api,
+        &tx_unknown,
+        &json!({ "type": "error", "code": 1, "description": "Asset not found" }),
+    );
+
+    let asset = get_asset(&api, id).unwrap();
+    assert_eq!(asset.owner(), &bob.0);
+    assert_eq!(asset.history_len(), 2);
+}
+
+#[test]
+fn test_asset_history() {
+    let mut testkit = init_testkit();
+    let api = testkit.api();
+
+    let (alice, bob) = (gen_keypair(), gen_keypair());
+    let id = hash(b"asset");
+    let txs = vec![
+        TxRegisterAsset::sign(&alice.0, &id, &hash(b"metadata"), &alice.1),
+        TxTransferAsset::sign(&alice.0, &id, &bob.0, 0, &alice.1),
+        TxTransferAsset::sign(&bob.0, &id, &alice.0, 0, &bob.1),
+    ];
+    for tx in &txs {
+        testkit.create_block_with_transaction(tx.clone());
+    }
+
+    let info: AssetInfo = api
+        .public(ApiKind::Service("assets"))
+        .query(&AssetQuery::new(id))
+        .get("v1/assets/history")
+        .unwrap();
+
+    let state_proof = info.state_proof.check().unwrap();
+    assert_eq!(
+        state_proof.merkle_root(),
+        *info.block_info.block.state_hash()
+    );
+    let asset_proof = info.asset_proof.check().unwrap();
+    assert!(state_proof
+        .entries()
+        .any(|(_, table_hash)| *table_hash == asset_proof.merkle_root()));
+    let asset = asset_proof
+        .entries()
+        .find(|&(asset_id, _)| *asset_id == id)
+        .map(|(_, asset)| asset.clone())
+        .unwrap();
+    assert_eq!(asset.owner(), &alice.0);
+
+    let history = info.history.unwrap();
+    let tx_hashes = history
+        .proof
+        .validate(*asset.history_hash(), asset.history_len())
+        .unwrap();
+    assert_eq!(
+        tx_hashes
+            .into_iter()
+            .map(|(_, tx_hash)| *tx_hash)
+            .collect::<Vec<_>>(),
+        txs.iter().map(|tx| tx.hash()).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        history
+            .transactions
+            .iter()
+            .map(|tx| tx.message().hash())
+            .collect::<Vec<_>>(),
+        txs.iter().map(|tx| tx.hash()).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_unknown_asset_history() {
+    let testkit = init_testkit();
+    let info: AssetInfo = testkit
+        .api()
+        .public(ApiKind::Service("assets"))
+        .query(&AssetQuery::new(Hash::zero()))
+        .get("v1/assets/history")
+        .unwrap();
+
+    let asset_proof = info.asset_proof.check().unwrap();
+    assert!(asset_proof.missing_keys().any(|id| *id == Hash::zero()));
+    assert!(info.history.is_none());
+}
@@ -16,13 +16,17 @@
 
 use exonum::{
     api::{self, ServiceApiBuilder, ServiceApiState},
-    blockchain::{self, BlockProof, TransactionMessage},
+    blockchain::{self, BlockProof, TransactionMessage, TransactionSet},
     crypto::{Hash, PublicKey},
     explorer::BlockchainExplorer,
     helpers::Height,
     storage::{ListProof, MapProof},
 };
 
+use exchange::{Deposit, ExchangeAccount};
+use lock::HashLock;
+use memo::SealedMemo;
+use transactions::WalletTransactions;
 use wallet::Wallet;
 use {Schema, CRYPTOCURRENCY_SERVICE_ID};
 
@@ -33,6 +37,40 @@ pub struct WalletQuery {
     pub pub_key: PublicKey,
 }
 
+/// Describes the query parameters for the `wallets_by_name` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WalletNameQuery {
+    /// Name of the queried wallets.
+    pub name: String,
+}
+
+/// Describes the query parameters for the `lock_info` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct LockQuery {
+    /// Identifier of the queried lock.
+    pub lock_id: Hash,
+}
+
+/// Describes the query parameters for the `deposits` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct DepositsQuery {
+    /// Public key of the master wallet.
+    pub master: PublicKey,
+    /// Height of the first block of the interval.
+    pub from_height: Height,
+    /// Height of the block following the last block of the interval.
+    pub to_height: Height,
+}
+
+/// Describes the query parameters for the `transfer_memo` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MemoQuery {
+    /// Hash of the `TransferWithMemo` transaction.
+    pub tx_hash: Hash,
+    /// Public key of the party the memo is disclosed to.
+    pub pub_key: PublicKey,
+}
+
 /// Response to an incoming transaction returned by the REST API.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TransactionResponse {
@@ -69,6 +107,41 @@ pub struct WalletInfo {
     pub wallet_history: Option<WalletHistory>,
 }
 
+/// Pending hash-locked transfer.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingLock {
+    /// Identifier of the lock, i.e., the hash of the transaction which has created it.
+    pub lock_id: Hash,
+    /// Escrowed funds.
+    pub lock: HashLock,
+}
+
+/// Deposits credited to the master wallet within the requested interval.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DepositsInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the exchange accounts table.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Proof of the exchange account in this table.
+    pub to_account: MapProof<PublicKey, ExchangeAccount>,
+    /// Proof of the deposits within the interval, or `None` if there are no such deposits.
+    /// The proof is checked against the `deposits_hash` of the exchange account.
+    pub deposits: Option<ListProof<Deposit>>,
+}
+
+/// Memo of a transfer encrypted to a single party.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct TransferMemo {
+    /// `PublicKey` of the receiver's wallet.
+    pub to: PublicKey,
+    /// Transferred amount.
+    pub amount: u64,
+    /// Copy of the memo encrypted to the party, which is decrypted by the party
+    /// with `SealedMemo::open`.
+    pub memo: SealedMemo,
+}
+
 /// Public service API description.
 #[derive(Debug, Clone, Copy)]
 pub struct PublicApi;
@@ -123,10 +196,121 @@ impl PublicApi {
         })
     }
 
-    /// Wires the above endpoint to public scope of the given `ServiceApiBuilder`.
+    /// Endpoint for getting the wallets with the given name.
+    pub fn wallets_by_name(
+        state: &ServiceApiState,
+        query: WalletNameQuery,
+    ) -> api::Result<Vec<Wallet>> {
+        let snapshot = state.snapshot();
+        let schema = Schema::new(&snapshot);
+        Ok(schema.wallets_by_name(&query.name))
+    }
+
+    /// Endpoint for getting a single pending hash-locked transfer.
+    pub fn lock_info(state: &ServiceApiState, query: LockQuery) -> api::Result<Option<HashLock>> {
+        let snapshot = state.snapshot();
+        let schema = Schema::new(&snapshot);
+        Ok(schema.lock(&query.lock_id))
+    }
+
+    /// Endpoint for getting pending hash-locked transfers sent or received by the wallet.
+    pub fn wallet_locks(
+        state: &ServiceApiState,
+        query: WalletQuery,
+    ) -> api::Result<Vec<PendingLock>> {
+        let snapshot = state.snapshot();
+        let schema = Schema::new(&snapshot);
+        let locks = schema
+            .locks()
+            .iter()
+            .filter(|(_, lock)| *lock.from() == query.pub_key || *lock.to() == query.pub_key)
+            .map(|(lock_id, lock)| PendingLock { lock_id, lock })
+            .collect();
+        Ok(locks)
+    }
+
+    /// Endpoint for reconciliation of the deposits credited to the master wallet
+    /// within an interval of heights.
+    pub fn deposits(state: &ServiceApiState, query: DepositsQuery) -> api::Result<DepositsInfo> {
+        if query.from_height > query.to_height {
+            Err(api::Error::BadRequest(
+                "`from_height` is greater than `to_height`".to_owned(),
+            ))?
+        }
+
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let currency_schema = Schema::new(&snapshot);
+
+        let block_proof = general_schema
+            .block_and_precommits(general_schema.height())
+            .unwrap();
+        let to_table = general_schema.get_proof_to_service_table(CRYPTOCURRENCY_SERVICE_ID, 2);
+        let to_account = currency_schema.exchange_accounts().get_proof(query.master);
+
+        // Deposits are ordered by height, so the interval is found by binary search.
+        let deposits = currency_schema.deposits(&query.master);
+        let lower_bound = |height: Height| {
+            let (mut lo, mut hi) = (0, deposits.len());
+            while lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                if deposits.get(mid).unwrap().height() < height {
+                    lo = mid + 1;
+                } else {
+                    hi = mid;
+                }
+            }
+            lo
+        };
+        let (from, to) = (lower_bound(query.from_height), lower_bound(query.to_height));
+        let deposits = if from < to {
+            Some(deposits.get_range_proof(from, to))
+        } else {
+            None
+        };
+
+        Ok(DepositsInfo {
+            block_proof,
+            to_table,
+            to_account,
+            deposits,
+        })
+    }
+
+    /// Endpoint for getting the copy of the transfer memo encrypted to the given party.
+    /// The memo is decrypted by the party itself, so the secret key never leaves the client.
+    pub fn transfer_memo(state: &ServiceApiState, query: MemoQuery) -> api::Result<TransferMemo> {
+        let snapshot = state.snapshot();
+        let raw = blockchain::Schema::new(&snapshot)
+            .transactions()
+            .get(&query.tx_hash)
+            .ok_or_else(|| api::Error::NotFound("Transaction not found".to_owned()))?;
+        let tx = match WalletTransactions::tx_from_raw(raw.payload().clone()) {
+            Ok(WalletTransactions::TransferWithMemo(tx)) => tx,
+            _ => Err(api::Error::BadRequest(
+                "Transaction is not a transfer with memo".to_owned(),
+            ))?,
+        };
+
+        let memo = tx
+            .memo_for(&query.pub_key)
+            .ok_or_else(|| api::Error::NotFound("Memo is not disclosed to the key".to_owned()))?;
+        Ok(TransferMemo {
+            to: *tx.to(),
+            amount: tx.amount(),
+            memo,
+        })
+    }
+
+    /// Wires the above endpoints to public scope of the given `ServiceApiBuilder`.
     pub fn wire(builder: &mut ServiceApiBuilder) {
         builder
             .public_scope()
-            .endpoint("v1/wallets/info", Self::wallet_info);
+            .endpoint("v1/wallets/info", Self::wallet_info)
+            .endpoint("v1/wallets/by_name", Self::wallets_by_name)
+            .endpoint("v1/wallets/locks", Self::wallet_locks)
+            .endpoint("v1/locks/info", Self::lock_info)
+            .endpoint("v1/exchange/deposits", Self::deposits)
+            .endpoint("v1/transfers/memo", Self::transfer_memo);
     }
 }
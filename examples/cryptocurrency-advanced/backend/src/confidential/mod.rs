@@ -0,0 +1,80 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Confidential transfers service.
+//!
+//! Wallets may move public funds of the cryptocurrency service into confidential
+//! accounts. Balances of confidential accounts are stored as Pedersen commitments,
+//! and confidential transfers hide the transferred amounts. Each transfer carries
+//! range proofs for the amount and for the remaining balance of the sender, so
+//! validators check that no funds are created without learning the amounts.
+//!
+//! The service is available with the `confidential` feature.
+
+pub mod schema;
+pub mod transactions;
+
+use exonum::{
+    blockchain::{self, Transaction, TransactionSet},
+    crypto::Hash,
+    encoding::Error as EncodingError,
+    helpers::fabric,
+    messages::RawTransaction,
+    storage::Snapshot,
+};
+
+use self::schema::ConfidentialSchema;
+use self::transactions::ConfidentialTransactions;
+
+/// Unique identifier of the confidential transfers service.
+pub const CONFIDENTIAL_SERVICE_ID: u16 = 130;
+/// Name of the confidential transfers service.
+pub const CONFIDENTIAL_SERVICE_NAME: &str = "confidential";
+
+/// Exonum `Service` implementation for the confidential transfers service.
+#[derive(Debug, Default)]
+pub struct ConfidentialService;
+
+impl blockchain::Service for ConfidentialService {
+    fn service_id(&self) -> u16 {
+        CONFIDENTIAL_SERVICE_ID
+    }
+
+    fn service_name(&self) -> &str {
+        CONFIDENTIAL_SERVICE_NAME
+    }
+
+    fn state_hash(&self, view: &dyn Snapshot) -> Vec<Hash> {
+        let schema = ConfidentialSchema::new(view);
+        schema.state_hash()
+    }
+
+    fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<dyn Transaction>, EncodingError> {
+        ConfidentialTransactions::tx_from_raw(raw).map(Into::into)
+    }
+}
+
+/// A confidential transfers service creator for the `NodeBuilder`.
+#[derive(Debug)]
+pub struct ConfidentialServiceFactory;
+
+impl fabric::ServiceFactory for ConfidentialServiceFactory {
+    fn service_name(&self) -> &str {
+        CONFIDENTIAL_SERVICE_NAME
+    }
+
+    fn make_service(&mut self, _: &fabric::Context) -> Box<dyn blockchain::Service> {
+        Box::new(ConfidentialService)
+    }
+}
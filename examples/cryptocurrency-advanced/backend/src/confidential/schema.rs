@@ -0,0 +1,93 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Confidential transfers service database schema.
+
+use exonum::{
+    crypto::{
+        confidential::{Blinding, Commitment},
+        Hash, PublicKey,
+    },
+    storage::{Fork, ProofMapIndex, Snapshot},
+};
+
+encoding_struct! {
+    /// Confidential account of a wallet.
+    struct ConfidentialAccount {
+        /// Public key of the wallet.
+        pub_key: &PublicKey,
+        /// Commitment to the balance of the account.
+        balance: &[u8],
+    }
+}
+
+impl ConfidentialAccount {
+    /// Returns the commitment to the balance of the account.
+    pub fn balance_commitment(&self) -> Commitment {
+        Commitment::from_slice(self.balance()).expect("Invalid balance commitment")
+    }
+}
+
+/// Database schema of the confidential transfers service.
+#[derive(Debug)]
+pub struct ConfidentialSchema<T> {
+    view: T,
+}
+
+impl<T> ConfidentialSchema<T>
+where
+    T: AsRef<dyn Snapshot>,
+{
+    /// Creates a new schema from the database view.
+    pub fn new(view: T) -> Self {
+        ConfidentialSchema { view }
+    }
+
+    /// Returns `ProofMapIndex` with confidential accounts keyed by wallet keys.
+    pub fn accounts(&self) -> ProofMapIndex<&T, PublicKey, ConfidentialAccount> {
+        ProofMapIndex::new("confidential.accounts", &self.view)
+    }
+
+    /// Returns the confidential account for the given key.
+    pub fn account(&self, pub_key: &PublicKey) -> Option<ConfidentialAccount> {
+        self.accounts().get(pub_key)
+    }
+
+    /// Returns the commitment to the balance of the given key. Keys without
+    /// a confidential account have a zero balance.
+    pub fn balance(&self, pub_key: &PublicKey) -> Commitment {
+        self.account(pub_key)
+            .map(|account| account.balance_commitment())
+            .unwrap_or_else(|| Commitment::new(0, &Blinding::zero()))
+    }
+
+    /// Returns the state hash of the confidential transfers service.
+    pub fn state_hash(&self) -> Vec<Hash> {
+        vec![self.accounts().merkle_root()]
+    }
+}
+
+/// Implementation of mutable methods.
+impl<'a> ConfidentialSchema<&'a mut Fork> {
+    /// Returns mutable `ProofMapIndex` with confidential accounts.
+    pub fn accounts_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, ConfidentialAccount> {
+        ProofMapIndex::new("confidential.accounts", &mut self.view)
+    }
+
+    /// Sets the balance commitment of the given key, creating the account if necessary.
+    pub fn set_balance(&mut self, pub_key: &PublicKey, balance: &Commitment) {
+        let account = ConfidentialAccount::new(pub_key, balance.as_ref());
+        self.accounts_mut().put(pub_key, account);
+    }
+}
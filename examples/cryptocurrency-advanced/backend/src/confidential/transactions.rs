@@ -0,0 +1,202 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Confidential transfers service transactions.
+
+// Workaround for `failure` see https://github.com/rust-lang-nursery/failure/issues/223 and
+// ECR-1771 for the details.
+#![allow(bare_trait_objects)]
+
+use exonum::{
+    blockchain::{ExecutionError, ExecutionResult, Transaction, TransactionContext},
+    crypto::{
+        confidential::{Blinding, Commitment, RangeProof},
+        PublicKey, SecretKey,
+    },
+    messages::{Message, RawTransaction, Signed},
+};
+
+use super::schema::ConfidentialSchema;
+use super::CONFIDENTIAL_SERVICE_ID;
+use schema::Schema;
+
+/// Error codes emitted by confidential transactions during execution.
+#[derive(Debug, Fail)]
+#[repr(u8)]
+pub enum Error {
+    /// Wallet of the transaction author does not exist.
+    ///
+    /// Can be emitted by `Shield`.
+    #[fail(display = "Wallet doesn't exist")]
+    WalletNotFound = 0,
+
+    /// Public balance of the wallet is insufficient.
+    ///
+    /// Can be emitted by `Shield`.
+    #[fail(display = "Insufficient currency amount")]
+    InsufficientCurrencyAmount = 1,
+
+    /// The amount commitment or one of the range proofs is malformed.
+    ///
+    /// Can be emitted by `ConfidentialTransfer`.
+    #[fail(display = "Malformed commitment or range proof")]
+    MalformedProof = 2,
+
+    /// The transferred amount is not proven to be non-negative.
+    ///
+    /// Can be emitted by `ConfidentialTransfer`.
+    #[fail(display = "Invalid range proof for the amount")]
+    InvalidAmountProof = 3,
+
+    /// The remaining balance of the sender is not proven to be non-negative.
+    ///
+    /// Can be emitted by `ConfidentialTransfer`.
+    #[fail(display = "Invalid range proof for the remaining balance")]
+    InvalidBalanceProof = 4,
+
+    /// Sender and receiver of the transfer are the same.
+    ///
+    /// Can be emitted by `ConfidentialTransfer`.
+    #[fail(display = "Sender same as receiver")]
+    SenderSameAsReceiver = 5,
+}
+
+impl From<Error> for ExecutionError {
+    fn from(value: Error) -> ExecutionError {
+        let description = format!("{}", value);
+        ExecutionError::with_description(value as u8, description)
+    }
+}
+
+transactions! {
+    /// Transaction group.
+    pub ConfidentialTransactions {
+
+        /// Move `amount` of public funds of the author's wallet to its confidential
+        /// account. The amount is public and is committed with the zero blinding factor.
+        struct Shield {
+            /// Amount of currency to move.
+            amount: u64,
+            /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+            ///
+            /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+            seed:   u64,
+        }
+
+        /// Transfer a hidden amount between confidential accounts.
+        ///
+        /// The sender should share the opening of the amount commitment (the amount
+        /// and the blinding factor) with the receiver, e.g., in an encrypted memo.
+        struct ConfidentialTransfer {
+            /// `PublicKey` of the receiver's wallet.
+            to:            &PublicKey,
+            /// Commitment to the transferred amount.
+            amount:        &[u8],
+            /// Range proof for the amount commitment.
+            amount_proof:  &[u8],
+            /// Range proof for the remaining balance of the sender, that is,
+            /// the balance commitment minus the amount commitment.
+            balance_proof: &[u8],
+            /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+            ///
+            /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+            seed:          u64,
+        }
+    }
+}
+
+impl Shield {
+    #[doc(hidden)]
+    pub fn sign(pk: &PublicKey, amount: u64, seed: u64, sk: &SecretKey) -> Signed<RawTransaction> {
+        Message::sign_transaction(Shield::new(amount, seed), CONFIDENTIAL_SERVICE_ID, *pk, sk)
+    }
+}
+
+impl ConfidentialTransfer {
+    #[doc(hidden)]
+    pub fn sign(
+        pk: &PublicKey,
+        to: &PublicKey,
+        amount: &Commitment,
+        amount_proof: &RangeProof,
+        balance_proof: &RangeProof,
+        seed: u64,
+        sk: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            ConfidentialTransfer::new(
+                to,
+                amount.as_ref(),
+                amount_proof.as_bytes(),
+                balance_proof.as_bytes(),
+                seed,
+            ),
+            CONFIDENTIAL_SERVICE_ID,
+            *pk,
+            sk,
+        )
+    }
+}
+
+impl Transaction for Shield {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let author = &context.author();
+        let hash = context.tx_hash();
+        let amount = self.amount();
+
+        {
+            let mut schema = Schema::new(context.fork());
+            let wallet = schema.wallet(author).ok_or(Error::WalletNotFound)?;
+            if wallet.balance() < amount {
+                Err(Error::InsufficientCurrencyAmount)?
+            }
+            schema.decrease_wallet_balance(wallet, amount, &hash);
+        }
+
+        let mut schema = ConfidentialSchema::new(context.fork());
+        let balance = schema.balance(author) + Commitment::new(amount, &Blinding::zero());
+        schema.set_balance(author, &balance);
+        Ok(())
+    }
+}
+
+impl Transaction for ConfidentialTransfer {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let from = &context.author();
+        let to = self.to();
+        if from == to {
+            Err(Error::SenderSameAsReceiver)?
+        }
+
+        let amount = Commitment::from_slice(self.amount()).ok_or(Error::MalformedProof)?;
+        let amount_proof =
+            RangeProof::from_slice(self.amount_proof()).ok_or(Error::MalformedProof)?;
+        let balance_proof =
+            RangeProof::from_slice(self.balance_proof()).ok_or(Error::MalformedProof)?;
+
+        let mut schema = ConfidentialSchema::new(context.fork());
+        let remaining = schema.balance(from) - amount;
+        if !amount_proof.verify(&amount) {
+            Err(Error::InvalidAmountProof)?
+        }
+        if !balance_proof.verify(&remaining) {
+            Err(Error::InvalidBalanceProof)?
+        }
+
+        let received = schema.balance(to) + amount;
+        schema.set_balance(from, &remaining);
+        schema.set_balance(to, &received);
+        Ok(())
+    }
+}
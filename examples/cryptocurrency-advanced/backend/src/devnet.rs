@@ -0,0 +1,123 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Extension of the `devnet` command creating funded test wallets.
+
+use exonum::{
+    crypto::{gen_keypair, PublicKey, SecretKey},
+    helpers::fabric::{keys, Argument, CommandExtension, Context},
+    messages::to_hex_string,
+};
+use failure;
+use serde_json;
+
+use std::{fs::File, path::Path};
+
+use transactions::{CreateWallet, Issue};
+
+// Context entry for the directory with the network data.
+const OUTPUT_DIR: &str = "OUTPUT_DIR";
+// Context entry for the number of the test wallets.
+const TEST_WALLETS: &str = "TEST_WALLETS";
+// Context entry for the amount issued to each test wallet.
+const TEST_FUNDS: &str = "TEST_FUNDS";
+
+/// Name of the file with the keys of the test wallets within the devnet directory.
+pub const WALLETS_FILE: &str = "wallets.json";
+
+/// Keys of a test wallet.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TestWallet {
+    /// Name of the wallet.
+    pub name: String,
+    /// Public key of the wallet.
+    pub public_key: PublicKey,
+    /// Secret key of the wallet.
+    pub secret_key: SecretKey,
+}
+
+/// Extension of the `devnet` command creating the given number of test wallets
+/// and issuing the given amount to each of them. The keys of the wallets are saved
+/// to the [`WALLETS_FILE`] within the devnet directory.
+///
+/// [`WALLETS_FILE`]: constant.WALLETS_FILE.html
+#[derive(Debug)]
+pub struct Devnet;
+
+impl CommandExtension for Devnet {
+    fn args(&self) -> Vec<Argument> {
+        vec![
+            Argument::new_named(
+                TEST_WALLETS,
+                false,
+                "Number of test wallets to create.",
+                None,
+                "test-wallets",
+                false,
+            ),
+            Argument::new_named(
+                TEST_FUNDS,
+                false,
+                "Amount issued to each test wallet in addition to the initial balance.",
+                None,
+                "test-funds",
+                false,
+            ),
+        ]
+    }
+
+    fn execute(&self, mut context: Context) -> Result<Context, failure::Error> {
+        let count = context.arg::<u64>(TEST_WALLETS).unwrap_or(0);
+        if count == 0 {
+            return Ok(context);
+        }
+        let funds = context.arg::<u64>(TEST_FUNDS).unwrap_or(0);
+        let dir = context.arg::<String>(OUTPUT_DIR)?;
+
+        let wallets = (0..count)
+            .map(|idx| {
+                let (public_key, secret_key) = gen_keypair();
+                TestWallet {
+                    name: format!("test-wallet-{}", idx),
+                    public_key,
+                    secret_key,
+                }
+            }).collect::<Vec<_>>();
+        serde_json::to_writer_pretty(File::create(Path::new(&dir).join(WALLETS_FILE))?, &wallets)?;
+
+        // Funds can be issued only to the wallets created in the previous batch.
+        let create = wallets
+            .iter()
+            .map(|w| to_hex_string(&CreateWallet::sign(&w.name, &w.public_key, &w.secret_key)))
+            .collect();
+        let mut batches = context.get(keys::DEVNET_TRANSACTIONS).unwrap_or_default();
+        batches.push(create);
+        if funds > 0 {
+            let issue = wallets
+                .iter()
+                .enumerate()
+                .map(|(seed, w)| {
+                    to_hex_string(&Issue::sign(
+                        &w.public_key,
+                        funds,
+                        seed as u64,
+                        &w.secret_key,
+                    ))
+                }).collect();
+            batches.push(issue);
+        }
+        context.set(keys::DEVNET_TRANSACTIONS, batches);
+        Ok(context)
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deposit addresses for exchange integrations.
+//!
+//! An exchange derives deposit addresses from its master wallet key and hands them out
+//! to its clients. Transfers to deposit addresses are recorded as deposits of the master
+//! wallet, and the credited funds are swept to the master wallet by validators.
+
+use exonum::{
+    crypto::{Hash, HashStream, PublicKey},
+    helpers::Height,
+    storage::StorageValue,
+};
+
+encoding_struct! {
+    /// Deposit address derived from the master wallet key.
+    struct DepositAddress {
+        /// Deposit address.
+        address:            &PublicKey,
+        /// `PublicKey` of the master wallet.
+        master:             &PublicKey,
+        /// Derivation index of the address.
+        index:              u64,
+        /// Amount of currency credited to the address, but not swept yet.
+        balance:            u64,
+    }
+}
+
+impl DepositAddress {
+    /// Returns a copy of this address with updated balance.
+    pub fn set_balance(self, balance: u64) -> Self {
+        Self::new(self.address(), self.master(), self.index(), balance)
+    }
+}
+
+encoding_struct! {
+    /// Transfer credited to a deposit address.
+    struct Deposit {
+        /// Deposit address.
+        address:            &PublicKey,
+        /// `PublicKey` of the sender's wallet.
+        from:               &PublicKey,
+        /// Amount of transferred currency.
+        amount:             u64,
+        /// Height of the block with the transfer.
+        height:             Height,
+        /// Hash of the transfer transaction.
+        tx_hash:            &Hash,
+    }
+}
+
+encoding_struct! {
+    /// Deposit account of the master wallet.
+    struct ExchangeAccount {
+        /// `PublicKey` of the master wallet.
+        master:             &PublicKey,
+        /// Number of derived deposit addresses.
+        addresses_count:    u64,
+        /// Length of the deposits list.
+        deposits_len:       u64,
+        /// `Hash` of the deposits list.
+        deposits_hash:      &Hash,
+    }
+}
+
+/// Derives the deposit address with the given index from the master wallet key.
+///
+/// Deposit addresses have no corresponding secret keys; the funds credited to them
+/// can only be swept to the master wallet.
+pub fn derive_deposit_address(master: &PublicKey, index: u64) -> PublicKey {
+    let hash = HashStream::new()
+        .update(master.as_ref())
+        .update(&index.into_bytes())
+        .hash();
+    PublicKey::from_slice(hash.as_ref()).unwrap()
+}
@@ -0,0 +1,244 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Faucet for the test networks.
+//!
+//! The faucet issues a capped amount of the currency to the wallet with the given key
+//! on request, so that the QA and demo environments do not require manual issuance.
+//! The faucet is enabled only if the `testnet` flag is set in its configuration.
+//! The configuration is read from the `faucet` section of the local service
+//! configurations of the node, and is written to the global configuration
+//! of the cryptocurrency service in the genesis block.
+//!
+//! The faucet API endpoint `v1/faucet` accepts a [`FaucetQuery`] and broadcasts
+//! an [`IssueFromFaucet`] transaction signed by the service key of the node.
+//! Requests for the same wallet key and requests from the same IP address
+//! are rate-limited.
+//!
+//! [`FaucetQuery`]: struct.FaucetQuery.html
+//! [`IssueFromFaucet`]: ../transactions/struct.IssueFromFaucet.html
+
+use actix_web::{self, http::Method, AsyncResponder, HttpMessage, HttpResponse};
+use exonum::{
+    api::{
+        backends::actix::{FutureResponse, HttpRequest, RawHandler, RequestHandler},
+        Error as ApiError, ServiceApiBackend, ServiceApiBuilder, ServiceApiState,
+    },
+    blockchain,
+    crypto::PublicKey,
+    storage::Snapshot,
+};
+use futures::Future;
+use serde_json;
+
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use api::TransactionResponse;
+use transactions::IssueFromFaucet;
+use {Schema, SERVICE_NAME};
+
+/// Name of the local service configuration of the faucet.
+pub const FAUCET_CONFIG_NAME: &str = "faucet";
+
+/// Configuration of the faucet.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct FaucetConfig {
+    /// If true, the network is a test one and the faucet is enabled.
+    pub testnet: bool,
+    /// Maximal amount issued by a single request.
+    pub max_amount: u64,
+    /// Minimal interval in seconds between the requests for the same wallet key.
+    pub key_interval: u64,
+    /// Minimal interval in seconds between the requests from the same IP address.
+    pub ip_interval: u64,
+}
+
+impl Default for FaucetConfig {
+    fn default() -> Self {
+        FaucetConfig {
+            testnet: false,
+            max_amount: 1000,
+            key_interval: 3600,
+            ip_interval: 60,
+        }
+    }
+}
+
+impl FaucetConfig {
+    /// Returns the faucet configuration from the actual global configuration
+    /// of the cryptocurrency service.
+    pub fn actual<T: AsRef<dyn Snapshot>>(snapshot: T) -> Self {
+        let config = blockchain::Schema::new(snapshot).actual_configuration();
+        config
+            .services
+            .get(SERVICE_NAME)
+            .and_then(|value| value.get(FAUCET_CONFIG_NAME))
+            .map(|value| serde_json::from_value(value.clone()).expect("Invalid faucet config"))
+            .unwrap_or_default()
+    }
+}
+
+/// Describes the body of the request to the faucet.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct FaucetQuery {
+    /// Public key of the wallet.
+    pub pub_key: PublicKey,
+    /// Requested amount.
+    pub amount: u64,
+}
+
+/// Rate limits of the faucet requests.
+#[derive(Debug)]
+pub struct RateLimiter {
+    key_interval: Duration,
+    ip_interval: Duration,
+    keys: HashMap<PublicKey, Instant>,
+    ips: HashMap<IpAddr, Instant>,
+}
+
+impl RateLimiter {
+    /// Creates a rate limiter with the intervals from the given configuration.
+    pub fn new(config: &FaucetConfig) -> Self {
+        RateLimiter {
+            key_interval: Duration::from_secs(config.key_interval),
+            ip_interval: Duration::from_secs(config.ip_interval),
+            keys: HashMap::new(),
+            ips: HashMap::new(),
+        }
+    }
+
+    /// Registers the request for the given key from the given IP address at the moment
+    /// `now`. Returns `false` if the request exceeds the rate limits.
+    pub fn try_acquire(&mut self, key: &PublicKey, ip: Option<IpAddr>, now: Instant) -> bool {
+        let (key_interval, ip_interval) = (self.key_interval, self.ip_interval);
+        let is_recent = |last: Option<&Instant>, interval| {
+            last.map_or(false, |last| now.duration_since(*last) < interval)
+        };
+        if is_recent(self.keys.get(key), key_interval) {
+            return false;
+        }
+        if let Some(ip) = ip {
+            if is_recent(self.ips.get(&ip), ip_interval) {
+                return false;
+            }
+            self.ips.insert(ip, now);
+        }
+        self.keys.insert(*key, now);
+
+        // Forget the requests which do not affect the limits anymore.
+        self.keys
+            .retain(|_, last| now.duration_since(*last) < key_interval);
+        self.ips
+            .retain(|_, last| now.duration_since(*last) < ip_interval);
+        true
+    }
+}
+
+/// Public API of the faucet.
+#[derive(Debug, Clone)]
+pub struct FaucetApi {
+    config: FaucetConfig,
+    limiter: Arc<Mutex<RateLimiter>>,
+}
+
+impl FaucetApi {
+    /// Creates the faucet API with the given configuration.
+    pub fn new(config: FaucetConfig) -> Self {
+        let limiter = Arc::new(Mutex::new(RateLimiter::new(&config)));
+        FaucetApi { config, limiter }
+    }
+
+    /// Issues the requested amount to the wallet by broadcasting a transaction
+    /// signed by the service key of the node.
+    pub fn issue(
+        &self,
+        state: &ServiceApiState,
+        query: FaucetQuery,
+        ip: Option<IpAddr>,
+    ) -> Result<Option<TransactionResponse>, ApiError> {
+        if query.amount == 0 || query.amount > self.config.max_amount {
+            return Err(ApiError::BadRequest(format!(
+                "Amount should be positive and not greater than {}",
+                self.config.max_amount
+            )));
+        }
+        if Schema::new(state.snapshot())
+            .wallet(&query.pub_key)
+            .is_none()
+        {
+            return Err(ApiError::NotFound("Wallet not found".to_owned()));
+        }
+        let acquired = self
+            .limiter
+            .lock()
+            .expect("Faucet rate limiter is poisoned")
+            .try_acquire(&query.pub_key, ip, Instant::now());
+        if !acquired {
+            return Ok(None);
+        }
+
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs() * 1_000_000_000 + u64::from(elapsed.subsec_nanos()))
+            .unwrap_or(0);
+        let tx = IssueFromFaucet::sign(
+            state.public_key(),
+            &query.pub_key,
+            query.amount,
+            seed,
+            state.secret_key(),
+        );
+        let tx_hash = tx.hash();
+        state
+            .sender()
+            .broadcast_transaction(tx)
+            .map_err(ApiError::from)?;
+        Ok(Some(TransactionResponse { tx_hash }))
+    }
+
+    /// Wires the faucet endpoint into the public API.
+    pub fn wire(self, builder: &mut ServiceApiBuilder) {
+        let index = move |request: HttpRequest| -> FutureResponse {
+            let faucet = self.clone();
+            let state = request.state().clone();
+            let ip = request.peer_addr().map(|address| address.ip());
+            request
+                .json()
+                .from_err::<actix_web::Error>()
+                .and_then(move |query: FaucetQuery| {
+                    let response = match faucet.issue(&state, query, ip)? {
+                        Some(response) => HttpResponse::Ok().json(response),
+                        None => HttpResponse::TooManyRequests()
+                            .body("Faucet request rate limit is exceeded"),
+                    };
+                    Ok(response)
+                }).responder()
+        };
+
+        builder
+            .public_scope()
+            .web_backend()
+            .raw_handler(RequestHandler {
+                name: "v1/faucet".to_owned(),
+                method: Method::POST,
+                inner: Arc::from(index) as Arc<RawHandler>,
+            });
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Wallets created in the genesis block.
+//!
+//! The wallets are listed in a TOML file passed with the `--genesis-wallets` argument
+//! to the `generate-template` or `generate-testnet` commands:
+//!
+//! ```toml
+//! [[wallets]]
+//! pub_key = "..."
+//! name = "Alice"
+//! balance = 1000
+//! ```
+//!
+//! The list is stored in the `genesis_wallets` section of the common configuration,
+//! copied to the node configuration by the `finalize` command and used
+//! by the service to create the wallets in the genesis block.
+
+use exonum::{
+    crypto::PublicKey,
+    helpers::{
+        config::ConfigFile,
+        fabric::{keys, Argument, CommandExtension, Context},
+    },
+    node::NodeConfig,
+};
+use failure;
+use toml::Value;
+
+/// Name of the configuration section with the genesis wallets.
+pub const GENESIS_WALLETS_CONFIG_NAME: &str = "genesis_wallets";
+
+// Context entry for the path to the file with the genesis wallets.
+const GENESIS_WALLETS: &str = "GENESIS_WALLETS";
+
+/// Wallet created in the genesis block.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GenesisWallet {
+    /// Public key of the wallet.
+    pub pub_key: PublicKey,
+    /// Name of the wallet.
+    pub name: String,
+    /// Balance of the wallet.
+    pub balance: u64,
+}
+
+/// List of the genesis wallets as stored in the configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GenesisWallets {
+    /// Genesis wallets.
+    #[serde(default)]
+    pub wallets: Vec<GenesisWallet>,
+}
+
+impl GenesisWallets {
+    /// Returns the genesis wallets from the local service configurations of the node.
+    pub fn from_node_config(config: &NodeConfig) -> Option<Self> {
+        config
+            .services_configs
+            .get(GENESIS_WALLETS_CONFIG_NAME)
+            .map(|value| {
+                value
+                    .clone()
+                    .try_into()
+                    .expect("Invalid genesis wallets config")
+            })
+    }
+}
+
+fn genesis_wallets_arg() -> Argument {
+    Argument::new_named(
+        GENESIS_WALLETS,
+        false,
+        "Path to the TOML file with the wallets created in the genesis block.",
+        None,
+        "genesis-wallets",
+        false,
+    )
+}
+
+fn load_genesis_wallets(context: &Context) -> Result<Option<Value>, failure::Error> {
+    let path = match context.arg::<String>(GENESIS_WALLETS) {
+        Ok(path) => path,
+        Err(_) => return Ok(None),
+    };
+    let wallets: GenesisWallets = ConfigFile::load(path)?;
+    Ok(Some(Value::try_from(wallets)?))
+}
+
+/// Extension of the `generate-template` command adding the genesis wallets
+/// to the common configuration.
+#[derive(Debug)]
+pub struct GenerateCommonConfig;
+
+impl CommandExtension for GenerateCommonConfig {
+    fn args(&self) -> Vec<Argument> {
+        vec![genesis_wallets_arg()]
+    }
+
+    fn execute(&self, mut context: Context) -> Result<Context, failure::Error> {
+        if let Some(wallets) = load_genesis_wallets(&context)? {
+            let mut values = context.get(keys::SERVICES_CONFIG)?;
+            values.insert(GENESIS_WALLETS_CONFIG_NAME.to_owned(), wallets);
+            context.set(keys::SERVICES_CONFIG, values);
+        }
+        Ok(context)
+    }
+}
+
+/// Extension of the `finalize` command copying the genesis wallets from the common
+/// configuration to the node configuration.
+#[derive(Debug)]
+pub struct Finalize;
+
+impl CommandExtension for Finalize {
+    fn args(&self) -> Vec<Argument> {
+        vec![]
+    }
+
+    fn execute(&self, mut context: Context) -> Result<Context, failure::Error> {
+        let common_config = context.get(keys::COMMON_CONFIG)?;
+        if let Some(wallets) = common_config
+            .services_config
+            .get(GENESIS_WALLETS_CONFIG_NAME)
+        {
+            let mut node_config = context.get(keys::NODE_CONFIG)?;
+            node_config
+                .services_configs
+                .insert(GENESIS_WALLETS_CONFIG_NAME.to_owned(), wallets.clone());
+            context.set(keys::NODE_CONFIG, node_config);
+        }
+        Ok(context)
+    }
+}
+
+/// Extension of the `generate-testnet` command adding the genesis wallets
+/// to the configurations of all the nodes.
+#[derive(Debug)]
+pub struct GenerateTestnet;
+
+impl CommandExtension for GenerateTestnet {
+    fn args(&self) -> Vec<Argument> {
+        vec![genesis_wallets_arg()]
+    }
+
+    fn execute(&self, mut context: Context) -> Result<Context, failure::Error> {
+        if let Some(wallets) = load_genesis_wallets(&context)? {
+            let mut configs = context.get(keys::CONFIGS)?;
+            for config in &mut configs {
+                config
+                    .services_configs
+                    .insert(GENESIS_WALLETS_CONFIG_NAME.to_owned(), wallets.clone());
+            }
+            context.set(keys::CONFIGS, configs);
+        }
+        Ok(context)
+    }
+}
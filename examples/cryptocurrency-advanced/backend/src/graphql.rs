@@ -0,0 +1,70 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Wallets and events of the cryptocurrency exposed via the GraphQL API.
+
+use exonum::{
+    api::node::public::graphql::{Event, GraphQLProvider, Wallet},
+    blockchain,
+    crypto::{Hash, PublicKey},
+    storage::Snapshot,
+};
+
+use transactions::WalletTransactions;
+use {Schema, Service};
+
+impl GraphQLProvider for Service {
+    fn wallet(&self, snapshot: &dyn Snapshot, pub_key: &PublicKey) -> Option<Wallet> {
+        let schema = Schema::new(snapshot);
+        let wallet = schema.wallet(pub_key)?;
+        Some(Wallet {
+            pub_key: *pub_key,
+            name: wallet.name().to_owned(),
+            balance: wallet.balance(),
+            history: schema.wallet_history(pub_key).iter().collect(),
+        })
+    }
+
+    /// Emits an event for every successful transaction creating a wallet, issuing
+    /// or transferring funds.
+    fn events(&self, snapshot: &dyn Snapshot, tx_hash: &Hash) -> Vec<Event> {
+        let schema = blockchain::Schema::new(snapshot);
+        let is_successful = schema
+            .transaction_results()
+            .get(tx_hash)
+            .map_or(false, |result| result.0.is_ok());
+        let raw = match schema.transactions().get(tx_hash) {
+            Some(raw) if is_successful => raw,
+            _ => return Vec::new(),
+        };
+
+        let author = raw.author();
+        let event = match WalletTransactions::tx_from_raw(raw.payload().clone()) {
+            Ok(WalletTransactions::CreateWallet(tx)) => Event {
+                name: "wallet_created".to_owned(),
+                data: json!({ "pub_key": author, "name": tx.name() }),
+            },
+            Ok(WalletTransactions::Issue(tx)) => Event {
+                name: "issue".to_owned(),
+                data: json!({ "to": author, "amount": tx.amount() }),
+            },
+            Ok(WalletTransactions::Transfer(tx)) => Event {
+                name: "transfer".to_owned(),
+                data: json!({ "from": author, "to": tx.to(), "amount": tx.amount() }),
+            },
+            _ => return Vec::new(),
+        };
+        vec![event]
+    }
+}
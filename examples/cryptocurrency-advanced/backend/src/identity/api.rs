@@ -0,0 +1,78 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Identity service API.
+
+use exonum::{
+    api::{self, ServiceApiBuilder, ServiceApiState},
+    blockchain::{self, BlockProof},
+    crypto::{Hash, PublicKey},
+    storage::MapProof,
+};
+
+use super::schema::{Attestation, IdentitySchema};
+use super::IDENTITY_SERVICE_ID;
+
+/// Describes the query parameters for the `attestation_info` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct AttestationQuery {
+    /// Public key of the attested wallet.
+    pub pub_key: PublicKey,
+}
+
+/// Attestation information.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttestationInfo {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the whole database table.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Proof of the attestation in this table.
+    pub to_attestation: MapProof<PublicKey, Attestation>,
+}
+
+/// Public service API description.
+#[derive(Debug, Clone, Copy)]
+pub struct PublicApi;
+
+impl PublicApi {
+    /// Endpoint for getting the attestation of a single wallet key.
+    pub fn attestation_info(
+        state: &ServiceApiState,
+        query: AttestationQuery,
+    ) -> api::Result<AttestationInfo> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let identity_schema = IdentitySchema::new(&snapshot);
+
+        let block_proof = general_schema
+            .block_and_precommits(general_schema.height())
+            .unwrap();
+        let to_table = general_schema.get_proof_to_service_table(IDENTITY_SERVICE_ID, 0);
+        let to_attestation = identity_schema.attestations().get_proof(query.pub_key);
+
+        Ok(AttestationInfo {
+            block_proof,
+            to_table,
+            to_attestation,
+        })
+    }
+
+    /// Wires the above endpoint to public scope of the given `ServiceApiBuilder`.
+    pub fn wire(builder: &mut ServiceApiBuilder) {
+        builder
+            .public_scope()
+            .endpoint("v1/attestations/info", Self::attestation_info);
+    }
+}
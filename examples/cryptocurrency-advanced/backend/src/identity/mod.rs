@@ -0,0 +1,134 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Identity attestation service.
+//!
+//! Designated attestors issue on-chain attestations for wallet keys. Each attestation
+//! has a level and expires at a certain blockchain height. If the transfer policy
+//! is enabled in the service configuration, the cryptocurrency service accepts
+//! transfers only between keys holding valid attestations of a sufficient level.
+
+pub mod api;
+pub mod schema;
+pub mod transactions;
+
+use exonum::{
+    api::ServiceApiBuilder,
+    blockchain::{self, Transaction, TransactionSet},
+    crypto::{Hash, PublicKey},
+    encoding::Error as EncodingError,
+    helpers::fabric,
+    messages::RawTransaction,
+    storage::{Fork, Snapshot},
+};
+use serde_json::{self, Value};
+
+use self::api::PublicApi;
+use self::schema::IdentitySchema;
+use self::transactions::IdentityTransactions;
+
+/// Unique identifier of the identity service.
+pub const IDENTITY_SERVICE_ID: u16 = 129;
+/// Name of the identity service.
+pub const IDENTITY_SERVICE_NAME: &str = "identity";
+
+/// Policy applied by the cryptocurrency service to transfers.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct TransferPolicy {
+    /// Minimal attestation level required from both the sender and the receiver.
+    pub min_level: u8,
+}
+
+/// Global configuration of the identity service.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct IdentityConfig {
+    /// Keys authorized to issue and revoke attestations.
+    pub attestors: Vec<PublicKey>,
+    /// Transfer policy; if `None`, transfers do not require attestations.
+    pub transfer_policy: Option<TransferPolicy>,
+}
+
+impl IdentityConfig {
+    /// Returns the actual configuration of the service.
+    pub fn actual<T: AsRef<dyn Snapshot>>(snapshot: T) -> Self {
+        let config = blockchain::Schema::new(snapshot).actual_configuration();
+        config
+            .services
+            .get(IDENTITY_SERVICE_NAME)
+            .map(|value| {
+                serde_json::from_value(value.clone()).expect("Invalid identity service config")
+            }).unwrap_or_default()
+    }
+
+    /// Checks whether the given key belongs to an authorized attestor.
+    pub fn is_attestor(&self, key: &PublicKey) -> bool {
+        self.attestors.contains(key)
+    }
+}
+
+/// Exonum `Service` implementation for the identity service.
+#[derive(Debug, Default)]
+pub struct IdentityService {
+    config: IdentityConfig,
+}
+
+impl IdentityService {
+    /// Creates a service with the given initial configuration.
+    pub fn new(config: IdentityConfig) -> Self {
+        IdentityService { config }
+    }
+}
+
+impl blockchain::Service for IdentityService {
+    fn service_id(&self) -> u16 {
+        IDENTITY_SERVICE_ID
+    }
+
+    fn service_name(&self) -> &str {
+        IDENTITY_SERVICE_NAME
+    }
+
+    fn state_hash(&self, view: &dyn Snapshot) -> Vec<Hash> {
+        let schema = IdentitySchema::new(view);
+        schema.state_hash()
+    }
+
+    fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<dyn Transaction>, EncodingError> {
+        IdentityTransactions::tx_from_raw(raw).map(Into::into)
+    }
+
+    fn initialize(&self, _fork: &mut Fork) -> Value {
+        serde_json::to_value(self.config.clone()).unwrap()
+    }
+
+    fn wire_api(&self, builder: &mut ServiceApiBuilder) {
+        PublicApi::wire(builder);
+    }
+}
+
+/// An identity service creator for the `NodeBuilder`. The service is created with
+/// no attestors and with the transfer policy disabled; both should be changed
+/// via the configuration service.
+#[derive(Debug)]
+pub struct IdentityServiceFactory;
+
+impl fabric::ServiceFactory for IdentityServiceFactory {
+    fn service_name(&self) -> &str {
+        IDENTITY_SERVICE_NAME
+    }
+
+    fn make_service(&mut self, _: &fabric::Context) -> Box<dyn blockchain::Service> {
+        Box::new(IdentityService::default())
+    }
+}
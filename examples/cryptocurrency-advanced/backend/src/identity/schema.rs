@@ -0,0 +1,82 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Identity service database schema.
+
+use exonum::{
+    crypto::{Hash, PublicKey},
+    helpers::Height,
+    storage::{Fork, ProofMapIndex, Snapshot},
+};
+
+encoding_struct! {
+    /// Attestation of a wallet key.
+    struct Attestation {
+        /// Attested key.
+        pub_key: &PublicKey,
+        /// Key of the attestor which has issued the attestation.
+        attestor: &PublicKey,
+        /// Attestation level.
+        level: u8,
+        /// Height of the first block at which the attestation is no longer valid.
+        expiry: Height,
+    }
+}
+
+impl Attestation {
+    /// Checks whether the attestation is valid at the given height and has at least
+    /// the given level.
+    pub fn is_valid(&self, height: Height, min_level: u8) -> bool {
+        height < self.expiry() && self.level() >= min_level
+    }
+}
+
+/// Database schema of the identity service.
+#[derive(Debug)]
+pub struct IdentitySchema<T> {
+    view: T,
+}
+
+impl<T> IdentitySchema<T>
+where
+    T: AsRef<dyn Snapshot>,
+{
+    /// Creates a new schema from the database view.
+    pub fn new(view: T) -> Self {
+        IdentitySchema { view }
+    }
+
+    /// Returns `ProofMapIndex` with attestations keyed by attested keys.
+    pub fn attestations(&self) -> ProofMapIndex<&T, PublicKey, Attestation> {
+        ProofMapIndex::new("identity.attestations", &self.view)
+    }
+
+    /// Returns attestation for the given key.
+    pub fn attestation(&self, pub_key: &PublicKey) -> Option<Attestation> {
+        self.attestations().get(pub_key)
+    }
+
+    /// Returns the state hash of the identity service.
+    pub fn state_hash(&self) -> Vec<Hash> {
+        vec![self.attestations().merkle_root()]
+    }
+}
+
+/// Implementation of mutable methods.
+impl<'a> IdentitySchema<&'a mut Fork> {
+    /// Returns mutable `ProofMapIndex` with attestations.
+    pub fn attestations_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, Attestation> {
+        ProofMapIndex::new("identity.attestations", &mut self.view)
+    }
+}
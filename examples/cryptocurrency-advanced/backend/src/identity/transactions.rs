@@ -0,0 +1,139 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Identity service transactions.
+
+// Workaround for `failure` see https://github.com/rust-lang-nursery/failure/issues/223 and
+// ECR-1771 for the details.
+#![allow(bare_trait_objects)]
+
+use exonum::{
+    blockchain::{ExecutionError, ExecutionResult, Transaction, TransactionContext},
+    crypto::{PublicKey, SecretKey},
+    helpers::Height,
+    messages::{Message, RawTransaction, Signed},
+};
+
+use super::schema::{Attestation, IdentitySchema};
+use super::{IdentityConfig, IDENTITY_SERVICE_ID};
+
+/// Error codes emitted by identity transactions during execution.
+#[derive(Debug, Fail)]
+#[repr(u8)]
+pub enum Error {
+    /// Author of the transaction is not an authorized attestor.
+    ///
+    /// Can be emitted by `Attest` or `Revoke`.
+    #[fail(display = "Sender is not an authorized attestor")]
+    NotAttestor = 0,
+
+    /// Attestation for the key does not exist.
+    ///
+    /// Can be emitted by `Revoke`.
+    #[fail(display = "Attestation doesn't exist")]
+    AttestationNotFound = 1,
+}
+
+impl From<Error> for ExecutionError {
+    fn from(value: Error) -> ExecutionError {
+        let description = format!("{}", value);
+        ExecutionError::with_description(value as u8, description)
+    }
+}
+
+transactions! {
+    /// Transaction group.
+    pub IdentityTransactions {
+
+        /// Issue an attestation for the `pub_key`, replacing the existing one, if any.
+        struct Attest {
+            /// Attested key.
+            pub_key: &PublicKey,
+            /// Attestation level.
+            level:   u8,
+            /// Height of the first block at which the attestation is no longer valid.
+            expiry:  Height,
+        }
+
+        /// Revoke the attestation of the `pub_key`.
+        struct Revoke {
+            /// Key whose attestation is revoked.
+            pub_key: &PublicKey,
+            /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+            ///
+            /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+            seed:    u64,
+        }
+    }
+}
+
+impl Attest {
+    #[doc(hidden)]
+    pub fn sign(
+        pk: &PublicKey,
+        pub_key: &PublicKey,
+        level: u8,
+        expiry: Height,
+        sk: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            Attest::new(pub_key, level, expiry),
+            IDENTITY_SERVICE_ID,
+            *pk,
+            sk,
+        )
+    }
+}
+
+impl Revoke {
+    #[doc(hidden)]
+    pub fn sign(
+        pk: &PublicKey,
+        pub_key: &PublicKey,
+        seed: u64,
+        sk: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(Revoke::new(pub_key, seed), IDENTITY_SERVICE_ID, *pk, sk)
+    }
+}
+
+impl Transaction for Attest {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let attestor = &context.author();
+        if !IdentityConfig::actual(context.fork()).is_attestor(attestor) {
+            Err(Error::NotAttestor)?
+        }
+
+        let attestation = Attestation::new(self.pub_key(), attestor, self.level(), self.expiry());
+        let mut schema = IdentitySchema::new(context.fork());
+        schema.attestations_mut().put(self.pub_key(), attestation);
+        Ok(())
+    }
+}
+
+impl Transaction for Revoke {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let attestor = &context.author();
+        if !IdentityConfig::actual(context.fork()).is_attestor(attestor) {
+            Err(Error::NotAttestor)?
+        }
+
+        let mut schema = IdentitySchema::new(context.fork());
+        if schema.attestation(self.pub_key()).is_none() {
+            Err(Error::AttestationNotFound)?
+        }
+        schema.attestations_mut().remove(self.pub_key());
+        Ok(())
+    }
+}
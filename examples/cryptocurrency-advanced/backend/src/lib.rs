@@ -21,32 +21,60 @@
     bare_trait_objects
 )]
 
+extern crate actix_web;
 #[macro_use]
 extern crate exonum;
 #[macro_use]
 extern crate failure;
+extern crate futures;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+#[macro_use]
+extern crate serde_json;
+extern crate toml;
 
 pub use schema::Schema;
 
 pub mod api;
+#[cfg(feature = "confidential")]
+pub mod confidential;
+pub mod devnet;
+pub mod exchange;
+pub mod faucet;
+pub mod genesis;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+pub mod identity;
+pub mod lock;
+pub mod memo;
+pub mod owner;
 pub mod schema;
 pub mod transactions;
 pub mod wallet;
 
+#[cfg(feature = "graphql")]
+use exonum::api::node::public::graphql::GraphQLProvider;
 use exonum::{
     api::ServiceApiBuilder,
-    blockchain::{self, Transaction, TransactionSet},
+    blockchain::{self, ServiceContext, Transaction, TransactionSet},
     crypto::Hash,
     encoding::Error as EncodingError,
-    helpers::fabric::{self, Context},
+    helpers::{
+        fabric::{self, CommandExtension, CommandName, Context},
+        Height,
+    },
     messages::RawTransaction,
-    storage::Snapshot,
+    storage::{Fork, Snapshot},
 };
+use serde_json::Value;
+
+use std::sync::Arc;
 
-use transactions::WalletTransactions;
+use faucet::{FaucetApi, FaucetConfig, FAUCET_CONFIG_NAME};
+use genesis::{GenesisWallet, GenesisWallets};
+use owner::{WalletOwner, WalletOwners};
+use transactions::{Sweep, WalletTransactions, WithWalletOwners};
 
 /// Unique service ID.
 const CRYPTOCURRENCY_SERVICE_ID: u16 = 128;
@@ -57,7 +85,36 @@ const INITIAL_BALANCE: u64 = 100;
 
 /// Exonum `Service` implementation.
 #[derive(Default, Debug)]
-pub struct Service;
+pub struct Service {
+    owners: Arc<WalletOwners>,
+    faucet: Option<FaucetConfig>,
+    genesis_wallets: Vec<GenesisWallet>,
+}
+
+impl Service {
+    /// Registers the logic of the service owning wallets. Transfers from the wallets owned
+    /// by this service are executed only if `owner` authorizes them.
+    pub fn with_wallet_owner<O: WalletOwner>(mut self, owner: O) -> Self {
+        Arc::make_mut(&mut self.owners).insert(Arc::new(owner));
+        self
+    }
+
+    /// Configures the faucet of the service. The faucet is written to the global
+    /// configuration and its API is enabled only if `config.testnet` is set.
+    pub fn with_faucet(mut self, config: FaucetConfig) -> Self {
+        self.faucet = Some(config);
+        self
+    }
+
+    /// Adds wallets created in the genesis block.
+    pub fn with_genesis_wallets<I>(mut self, wallets: I) -> Self
+    where
+        I: IntoIterator<Item = GenesisWallet>,
+    {
+        self.genesis_wallets.extend(wallets);
+        self
+    }
+}
 
 impl blockchain::Service for Service {
     fn service_id(&self) -> u16 {
@@ -74,11 +131,80 @@ impl blockchain::Service for Service {
     }
 
     fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<dyn Transaction>, EncodingError> {
-        WalletTransactions::tx_from_raw(raw).map(Into::into)
+        let tx: Box<dyn Transaction> = match WalletTransactions::tx_from_raw(raw)? {
+            WalletTransactions::Transfer(tx) => {
+                Box::new(WithWalletOwners::new(tx, Arc::clone(&self.owners)))
+            }
+            WalletTransactions::LockTransfer(tx) => {
+                Box::new(WithWalletOwners::new(tx, Arc::clone(&self.owners)))
+            }
+            WalletTransactions::TransferWithMemo(tx) => {
+                Box::new(WithWalletOwners::new(tx, Arc::clone(&self.owners)))
+            }
+            tx => tx.into(),
+        };
+        Ok(tx)
+    }
+
+    /// Indexes the names of the wallets created before the index of the wallet names
+    /// was introduced.
+    fn before_commit(&self, fork: &mut Fork) {
+        Schema::new(fork).index_wallet_names();
+    }
+
+    /// Emits `Sweep` transactions for deposit addresses with credited funds.
+    ///
+    /// Validators take turns emitting sweeps, so that each committed block
+    /// leads to a single set of sweeps; if the transactions of a validator are lost,
+    /// they are emitted again by the next one.
+    fn after_commit(&self, context: &ServiceContext) {
+        let schema = Schema::new(context.snapshot());
+        for address in &schema.pending_sweeps() {
+            context.emit_transaction(Sweep::new(&address, context.height().0));
+        }
+    }
+
+    /// Preloads the wallets of the authors of the service transactions committed
+    /// since the `from` height.
+    fn prefetch(&self, snapshot: &dyn Snapshot, from: Height) {
+        let core_schema = blockchain::Schema::new(snapshot);
+        let schema = Schema::new(snapshot);
+        let transactions = core_schema.transactions();
+        for height in from.0..=core_schema.height().0 {
+            for tx_hash in &core_schema.block_transactions(Height(height)) {
+                let tx = transactions
+                    .get(&tx_hash)
+                    .expect("Committed transaction not found");
+                if tx.payload().service_id() == CRYPTOCURRENCY_SERVICE_ID {
+                    schema.wallet(&tx.author());
+                }
+            }
+        }
+    }
+
+    fn initialize(&self, fork: &mut Fork) -> Value {
+        let mut schema = Schema::new(fork);
+        for wallet in &self.genesis_wallets {
+            schema.create_genesis_wallet(&wallet.pub_key, &wallet.name, wallet.balance);
+        }
+        match self.faucet {
+            Some(ref config) => json!({ FAUCET_CONFIG_NAME: config }),
+            None => Value::Null,
+        }
     }
 
     fn wire_api(&self, builder: &mut ServiceApiBuilder) {
         api::PublicApi::wire(builder);
+        if let Some(ref config) = self.faucet {
+            if config.testnet {
+                FaucetApi::new(config.clone()).wire(builder);
+            }
+        }
+    }
+
+    #[cfg(feature = "graphql")]
+    fn graphql_provider(&self) -> Option<&dyn GraphQLProvider> {
+        Some(self)
     }
 }
 
@@ -91,7 +217,34 @@ impl fabric::ServiceFactory for ServiceFactory {
         SERVICE_NAME
     }
 
-    fn make_service(&mut self, _: &Context) -> Box<dyn blockchain::Service> {
-        Box::new(Service)
+    fn command(&mut self, command: CommandName) -> Option<Box<dyn CommandExtension>> {
+        use exonum::helpers::fabric::Command;
+        Some(match command {
+            v if v == fabric::GenerateCommonConfig.name() => {
+                Box::new(genesis::GenerateCommonConfig)
+            }
+            v if v == fabric::Finalize.name() => Box::new(genesis::Finalize),
+            v if v == fabric::GenerateTestnet.name() => Box::new(genesis::GenerateTestnet),
+            v if v == fabric::Devnet.name() => Box::new(devnet::Devnet),
+            _ => return None,
+        })
+    }
+
+    fn make_service(&mut self, context: &Context) -> Box<dyn blockchain::Service> {
+        let node_config = context.get(fabric::keys::NODE_CONFIG).ok();
+        let faucet = node_config
+            .as_ref()
+            .and_then(|config| config.services_configs.get(FAUCET_CONFIG_NAME).cloned())
+            .map(|value| value.try_into().expect("Invalid faucet configuration"));
+        let genesis_wallets = node_config
+            .as_ref()
+            .and_then(GenesisWallets::from_node_config)
+            .unwrap_or_default();
+
+        let service = Service::default().with_genesis_wallets(genesis_wallets.wallets);
+        Box::new(match faucet {
+            Some(config) => service.with_faucet(config),
+            None => service,
+        })
     }
 }
@@ -0,0 +1,52 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hash-locked transfers.
+
+use exonum::{
+    crypto::{self, Hash, PublicKey},
+    helpers::Height,
+};
+
+encoding_struct! {
+    /// Funds escrowed by a hash-locked transfer.
+    ///
+    /// The funds can be claimed by the receiver by revealing the preimage of `hash`
+    /// before the `timeout_height`, or refunded to the sender afterwards.
+    struct HashLock {
+        /// `PublicKey` of the sender's wallet.
+        from:               &PublicKey,
+        /// `PublicKey` of the receiver's wallet.
+        to:                 &PublicKey,
+        /// Amount of escrowed currency.
+        amount:             u64,
+        /// SHA-256 hash of the secret preimage.
+        hash:               &Hash,
+        /// Height of the first block at which the funds can no longer be claimed
+        /// and can be refunded instead.
+        timeout_height:     Height,
+    }
+}
+
+impl HashLock {
+    /// Checks whether the given preimage unlocks the funds.
+    pub fn matches(&self, preimage: &[u8]) -> bool {
+        crypto::hash(preimage) == *self.hash()
+    }
+
+    /// Checks whether the lock is timed out at the given height.
+    pub fn is_expired(&self, height: Height) -> bool {
+        height >= self.timeout_height()
+    }
+}
@@ -26,6 +26,11 @@ fn main() {
 
     let node = NodeBuilder::new()
         .with_service(Box::new(configuration::ServiceFactory))
-        .with_service(Box::new(cryptocurrency::ServiceFactory));
+        .with_service(Box::new(cryptocurrency::ServiceFactory))
+        .with_service(Box::new(cryptocurrency::identity::IdentityServiceFactory));
+    #[cfg(feature = "confidential")]
+    let node = node.with_service(Box::new(
+        cryptocurrency::confidential::ConfidentialServiceFactory,
+    ));
     node.run();
 }
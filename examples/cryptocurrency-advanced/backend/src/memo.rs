@@ -0,0 +1,61 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encrypted memos attached to transfers.
+//!
+//! A memo carries business data (e.g., an invoice number) which should not be
+//! world-readable. The sender encrypts the memo separately to each party it is disclosed to,
+//! such as the receiver, the sender itself or an auditor, so the blockchain stores only
+//! the ciphertexts. A party obtains its copy via the public API and decrypts it locally
+//! with the secret key of its wallet.
+//!
+//! Nodes cannot check that all copies of the memo contain the same plaintext;
+//! the memo is not interpreted by the service.
+
+use exonum::crypto::{self, PublicKey, SecretKey, SEALED_BOX_OVERHEAD};
+
+/// Maximal size of the memo plaintext in bytes.
+pub const MAX_MEMO_SIZE: usize = 1024;
+
+/// Maximal number of parties a memo can be disclosed to.
+pub const MAX_MEMO_RECIPIENTS: usize = 4;
+
+encoding_struct! {
+    /// Copy of the memo encrypted to a single party.
+    struct SealedMemo {
+        /// `PublicKey` of the party able to decrypt the memo.
+        recipient:          &PublicKey,
+        /// Memo encrypted with the sealed box construction.
+        ciphertext:         &[u8],
+    }
+}
+
+impl SealedMemo {
+    /// Encrypts the memo to the owner of the `recipient` key.
+    pub fn seal(recipient: &PublicKey, memo: &[u8]) -> Self {
+        SealedMemo::new(recipient, &crypto::seal(memo, recipient))
+    }
+
+    /// Decrypts the memo with the secret key of the recipient. Returns `None` if the key
+    /// does not match or the ciphertext is malformed.
+    pub fn open(&self, secret_key: &SecretKey) -> Option<Vec<u8>> {
+        crypto::open_sealed(self.ciphertext(), self.recipient(), secret_key)
+    }
+
+    /// Checks whether the size of the ciphertext is within the limits.
+    pub fn has_valid_size(&self) -> bool {
+        let len = self.ciphertext().len();
+        len >= SEALED_BOX_OVERHEAD && len - SEALED_BOX_OVERHEAD <= MAX_MEMO_SIZE
+    }
+}
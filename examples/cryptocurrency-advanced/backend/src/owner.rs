@@ -0,0 +1,61 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Wallets owned by services.
+//!
+//! A wallet may declare an owning service with the `SetWalletOwner` transaction. Transfers
+//! from such a wallet are executed only if the owning service authorizes them, which allows
+//! to implement custodial or programmatic accounts on top of the cryptocurrency service.
+//! The logic of the owning services is registered with `Service::with_wallet_owner`.
+
+use exonum::{blockchain::ExecutionResult, crypto::PublicKey, storage::Fork};
+
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+/// Service logic owning wallets and authorizing transfers from them.
+pub trait WalletOwner: fmt::Debug + Send + Sync + 'static {
+    /// Identifier of the owning service.
+    fn service_id(&self) -> u16;
+
+    /// Checks whether the transfer of `amount` from the owned wallet `from` to the wallet `to`
+    /// is authorized. The check is performed during the execution of the transfer, so it may
+    /// read the state of the owning service from `fork`.
+    ///
+    /// An error is returned as the result of the transfer transaction.
+    fn authorize_transfer(
+        &self,
+        fork: &Fork,
+        from: &PublicKey,
+        to: &PublicKey,
+        amount: u64,
+    ) -> ExecutionResult;
+}
+
+/// Registry of the wallet owners keyed by service identifiers.
+#[derive(Debug, Clone, Default)]
+pub struct WalletOwners {
+    owners: BTreeMap<u16, Arc<dyn WalletOwner>>,
+}
+
+impl WalletOwners {
+    /// Adds the owner to the registry, replacing the owner with the same service identifier.
+    pub fn insert(&mut self, owner: Arc<dyn WalletOwner>) {
+        self.owners.insert(owner.service_id(), owner);
+    }
+
+    /// Returns the owner with the given service identifier.
+    pub fn get(&self, service_id: u16) -> Option<&dyn WalletOwner> {
+        self.owners.get(&service_id).map(|owner| owner.as_ref())
+    }
+}
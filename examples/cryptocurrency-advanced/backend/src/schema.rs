@@ -16,9 +16,16 @@
 
 use exonum::{
     crypto::{Hash, PublicKey},
-    storage::{Fork, ProofListIndex, ProofMapIndex, Snapshot},
+    helpers::Height,
+    storage::{
+        Fork, IndexedProofMapIndex, KeySetIndex, ProofListIndex, ProofMapIndex, SecondaryIndex,
+        Snapshot,
+    },
 };
 
+use exchange::{derive_deposit_address, Deposit, DepositAddress, ExchangeAccount};
+use lock::HashLock;
+use transactions::Error;
 use wallet::Wallet;
 use INITIAL_BALANCE;
 
@@ -58,17 +65,138 @@ where
         self.wallets().get(pub_key)
     }
 
+    /// Returns `SecondaryIndex` mapping names of the wallets to their public keys.
+    pub fn wallet_names(&self) -> SecondaryIndex<&T, str, PublicKey> {
+        SecondaryIndex::new("cryptocurrency.wallet_names", &self.view)
+    }
+
+    /// Returns wallets with the given name, ordered by their public keys.
+    pub fn wallets_by_name(&self, name: &str) -> Vec<Wallet> {
+        let wallets = self.wallets();
+        self.wallet_names()
+            .primary_keys(name)
+            .filter_map(|pub_key| wallets.get(&pub_key))
+            .collect()
+    }
+
+    /// Returns `ProofMapIndex` with pending hash-locked transfers keyed by the hashes
+    /// of the transactions which have created them.
+    pub fn locks(&self) -> ProofMapIndex<&T, Hash, HashLock> {
+        ProofMapIndex::new("cryptocurrency.locks", &self.view)
+    }
+
+    /// Returns pending hash-locked transfer with the given identifier.
+    pub fn lock(&self, id: &Hash) -> Option<HashLock> {
+        self.locks().get(id)
+    }
+
+    /// Returns `ProofMapIndex` with exchange accounts keyed by master wallet keys.
+    pub fn exchange_accounts(&self) -> ProofMapIndex<&T, PublicKey, ExchangeAccount> {
+        ProofMapIndex::new("cryptocurrency.exchange_accounts", &self.view)
+    }
+
+    /// Returns exchange account of the given master wallet.
+    pub fn exchange_account(&self, master: &PublicKey) -> Option<ExchangeAccount> {
+        self.exchange_accounts().get(master)
+    }
+
+    /// Returns `ProofMapIndex` with deposit addresses.
+    pub fn deposit_addresses(&self) -> ProofMapIndex<&T, PublicKey, DepositAddress> {
+        ProofMapIndex::new("cryptocurrency.deposit_addresses", &self.view)
+    }
+
+    /// Returns deposit address information, if the given key is a deposit address.
+    pub fn deposit_address(&self, address: &PublicKey) -> Option<DepositAddress> {
+        self.deposit_addresses().get(address)
+    }
+
+    /// Returns deposits credited to the master wallet, in the order of their heights.
+    pub fn deposits(&self, master: &PublicKey) -> ProofListIndex<&T, Deposit> {
+        ProofListIndex::new_in_family("cryptocurrency.deposits", master, &self.view)
+    }
+
+    /// Returns deposit addresses with funds which are not swept yet.
+    pub fn pending_sweeps(&self) -> KeySetIndex<&T, PublicKey> {
+        KeySetIndex::new("cryptocurrency.pending_sweeps", &self.view)
+    }
+
+    /// Returns `ProofMapIndex` with identifiers of the services owning wallets.
+    pub fn wallet_owners(&self) -> ProofMapIndex<&T, PublicKey, u16> {
+        ProofMapIndex::new("cryptocurrency.wallet_owners", &self.view)
+    }
+
+    /// Returns identifier of the service owning the wallet, if any.
+    pub fn wallet_owner(&self, pub_key: &PublicKey) -> Option<u16> {
+        self.wallet_owners().get(pub_key)
+    }
+
     /// Returns the state hash of cryptocurrency service.
     pub fn state_hash(&self) -> Vec<Hash> {
-        vec![self.wallets().merkle_root()]
+        vec![
+            self.wallets().merkle_root(),
+            self.locks().merkle_root(),
+            self.exchange_accounts().merkle_root(),
+            self.deposit_addresses().merkle_root(),
+            self.wallet_owners().merkle_root(),
+        ]
     }
 }
 
 /// Implementation of mutable methods.
 impl<'a> Schema<&'a mut Fork> {
-    /// Returns mutable `ProofMapIndex` with wallets.
-    pub fn wallets_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, Wallet> {
-        ProofMapIndex::new("cryptocurrency.wallets", &mut self.view)
+    /// Returns mutable `ProofMapIndex` with wallets, which keeps the index of the wallet
+    /// names up to date on every write.
+    pub fn wallets_mut(&mut self) -> IndexedProofMapIndex<PublicKey, Wallet, str> {
+        IndexedProofMapIndex::new(
+            "cryptocurrency.wallets",
+            "cryptocurrency.wallet_names",
+            &mut self.view,
+            Wallet::name,
+        )
+    }
+
+    /// Indexes the names of the wallets created before the index of the wallet names
+    /// was introduced. Does nothing if the index is already populated.
+    pub fn index_wallet_names(&mut self) {
+        let needs_backfill =
+            self.wallet_names().is_empty() && self.wallets().keys().next().is_some();
+        if needs_backfill {
+            self.wallets_mut().reindex();
+        }
+    }
+
+    /// Returns mutable `ProofMapIndex` with pending hash-locked transfers.
+    pub fn locks_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, HashLock> {
+        ProofMapIndex::new("cryptocurrency.locks", &mut self.view)
+    }
+
+    /// Returns mutable `ProofMapIndex` with exchange accounts.
+    pub fn exchange_accounts_mut(
+        &mut self,
+    ) -> ProofMapIndex<&mut Fork, PublicKey, ExchangeAccount> {
+        ProofMapIndex::new("cryptocurrency.exchange_accounts", &mut self.view)
+    }
+
+    /// Returns mutable `ProofMapIndex` with deposit addresses.
+    pub fn deposit_addresses_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, DepositAddress> {
+        ProofMapIndex::new("cryptocurrency.deposit_addresses", &mut self.view)
+    }
+
+    /// Returns mutable deposits of the master wallet.
+    pub fn deposits_mut(&mut self, master: &PublicKey) -> ProofListIndex<&mut Fork, Deposit> {
+        ProofListIndex::new_in_family("cryptocurrency.deposits", master, &mut self.view)
+    }
+
+    /// Returns mutable set of deposit addresses with funds which are not swept yet.
+    pub fn pending_sweeps_mut(&mut self) -> KeySetIndex<&mut Fork, PublicKey> {
+        KeySetIndex::new("cryptocurrency.pending_sweeps", &mut self.view)
+    }
+
+    /// Returns mutable `ProofMapIndex` with identifiers of the services owning wallets.
+    ///
+    /// Owning services may use this index to release the wallets they own.
+    pub fn wallet_owners_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, u16> {
+        ProofMapIndex::new("cryptocurrency.wallet_owners", &mut self.view)
     }
 
     /// Returns history for the wallet by the given public key.
@@ -81,16 +209,26 @@ impl<'a> Schema<&'a mut Fork> {
 
     /// Increase balance of the wallet and append new record to its history.
     ///
+    /// Returns an error without changing the wallet if its balance would overflow.
     /// Panics if there is no wallet with given public key.
-    pub fn increase_wallet_balance(&mut self, wallet: Wallet, amount: u64, transaction: &Hash) {
+    pub fn increase_wallet_balance(
+        &mut self,
+        wallet: Wallet,
+        amount: u64,
+        transaction: &Hash,
+    ) -> Result<(), Error> {
+        let balance = wallet
+            .balance()
+            .checked_add(amount)
+            .ok_or(Error::BalanceOverflow)?;
         let wallet = {
             let mut history = self.wallet_history_mut(wallet.pub_key());
             history.push(*transaction);
             let history_hash = history.merkle_root();
-            let balance = wallet.balance();
-            wallet.set_balance(balance + amount, &history_hash)
+            wallet.set_balance(balance, &history_hash)
         };
-        self.wallets_mut().put(wallet.pub_key(), wallet.clone());
+        self.put_wallet(wallet);
+        Ok(())
     }
 
     /// Decrease balance of the wallet and append new record to its history.
@@ -104,7 +242,7 @@ impl<'a> Schema<&'a mut Fork> {
             let balance = wallet.balance();
             wallet.set_balance(balance - amount, &history_hash)
         };
-        self.wallets_mut().put(wallet.pub_key(), wallet.clone());
+        self.put_wallet(wallet);
     }
 
     /// Create new wallet and append first record to its history.
@@ -115,6 +253,104 @@ impl<'a> Schema<&'a mut Fork> {
             let history_hash = history.merkle_root();
             Wallet::new(key, name, INITIAL_BALANCE, history.len(), &history_hash)
         };
-        self.wallets_mut().put(key, wallet);
+        self.put_wallet(wallet);
+    }
+
+    /// Create a wallet with the given balance in the genesis block. The history
+    /// of the wallet is empty.
+    pub fn create_genesis_wallet(&mut self, key: &PublicKey, name: &str, balance: u64) {
+        let history_hash = self.wallet_history_mut(key).merkle_root();
+        let wallet = Wallet::new(key, name, balance, 0, &history_hash);
+        self.put_wallet(wallet);
+    }
+
+    fn put_wallet(&mut self, wallet: Wallet) {
+        let pub_key = *wallet.pub_key();
+        self.wallets_mut().put(&pub_key, wallet);
+    }
+
+    /// Derives `count` new deposit addresses for the master wallet.
+    pub fn create_deposit_addresses(&mut self, master: &PublicKey, count: u64) {
+        let account = self
+            .exchange_account(master)
+            .unwrap_or_else(|| ExchangeAccount::new(master, 0, 0, &Hash::zero()));
+        let first_index = account.addresses_count();
+        for index in first_index..first_index + count {
+            let address = derive_deposit_address(master, index);
+            let deposit_address = DepositAddress::new(&address, master, index, 0);
+            self.deposit_addresses_mut().put(&address, deposit_address);
+        }
+        let account = ExchangeAccount::new(
+            master,
+            first_index + count,
+            account.deposits_len(),
+            account.deposits_hash(),
+        );
+        self.exchange_accounts_mut().put(master, account);
+    }
+
+    /// Credits the transfer to the deposit address and records the deposit
+    /// of the master wallet.
+    ///
+    /// Returns an error without changing the address if its balance would overflow.
+    pub fn credit_deposit(
+        &mut self,
+        address: DepositAddress,
+        from: &PublicKey,
+        amount: u64,
+        height: Height,
+        transaction: &Hash,
+    ) -> Result<(), Error> {
+        let balance = address
+            .balance()
+            .checked_add(amount)
+            .ok_or(Error::BalanceOverflow)?;
+        let master = *address.master();
+        let account = self
+            .exchange_account(&master)
+            .expect("Exchange account of a deposit address doesn't exist");
+        let account = {
+            let mut deposits = self.deposits_mut(&master);
+            deposits.push(Deposit::new(
+                address.address(),
+                from,
+                amount,
+                height,
+                transaction,
+            ));
+            ExchangeAccount::new(
+                &master,
+                account.addresses_count(),
+                deposits.len(),
+                &deposits.merkle_root(),
+            )
+        };
+        self.exchange_accounts_mut().put(&master, account);
+
+        let address = address.set_balance(balance);
+        self.pending_sweeps_mut().insert(*address.address());
+        self.deposit_addresses_mut()
+            .put(address.address(), address.clone());
+        Ok(())
+    }
+
+    /// Moves funds credited to the deposit address to the master wallet.
+    ///
+    /// Returns an error if the balance of the master wallet would overflow.
+    /// Panics if there is no master wallet.
+    pub fn sweep_deposit_address(
+        &mut self,
+        address: DepositAddress,
+        transaction: &Hash,
+    ) -> Result<(), Error> {
+        let master = self
+            .wallet(address.master())
+            .expect("Master wallet of a deposit address doesn't exist");
+        self.increase_wallet_balance(master, address.balance(), transaction)?;
+        self.pending_sweeps_mut().remove(address.address());
+        let address = address.set_balance(0);
+        self.deposit_addresses_mut()
+            .put(address.address(), address.clone());
+        Ok(())
     }
 }
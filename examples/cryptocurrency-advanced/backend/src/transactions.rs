@@ -19,16 +19,29 @@
 #![allow(bare_trait_objects)]
 
 use exonum::{
-    blockchain::{ExecutionError, ExecutionResult, Transaction, TransactionContext},
-    crypto::{PublicKey, SecretKey},
+    blockchain::{self, ExecutionError, ExecutionResult, Transaction, TransactionContext},
+    crypto::{Hash, PublicKey, SecretKey},
+    helpers::Height,
     messages::{Message, RawTransaction, Signed},
+    storage::Fork,
 };
+use serde::{Serialize, Serializer};
 
+use std::{fmt, sync::Arc};
+
+use faucet::FaucetConfig;
+use identity::{schema::IdentitySchema, IdentityConfig};
+use lock::HashLock;
+use memo::{SealedMemo, MAX_MEMO_RECIPIENTS};
+use owner::WalletOwners;
 use schema::Schema;
 use CRYPTOCURRENCY_SERVICE_ID;
 
 const ERROR_SENDER_SAME_AS_RECEIVER: u8 = 0;
 
+/// Maximal number of deposit addresses derived by a single transaction.
+pub const MAX_DEPOSIT_ADDRESSES: u64 = 100;
+
 /// Error codes emitted by wallet transactions during execution.
 #[derive(Debug, Fail)]
 #[repr(u8)]
@@ -56,6 +69,115 @@ pub enum Error {
     /// Can be emitted by `Transfer`.
     #[fail(display = "Insufficient currency amount")]
     InsufficientCurrencyAmount = 3,
+
+    /// Sender doesn't hold a valid attestation required by the transfer policy.
+    ///
+    /// Can be emitted by `Transfer`.
+    #[fail(display = "Sender is not attested")]
+    SenderNotAttested = 4,
+
+    /// Receiver doesn't hold a valid attestation required by the transfer policy.
+    ///
+    /// Can be emitted by `Transfer`.
+    #[fail(display = "Receiver is not attested")]
+    ReceiverNotAttested = 5,
+
+    /// Hash-locked transfer doesn't exist.
+    ///
+    /// Can be emitted by `Claim` or `Refund`.
+    #[fail(display = "Lock doesn't exist")]
+    LockNotFound = 6,
+
+    /// Transaction author is not allowed to unlock the funds.
+    ///
+    /// Can be emitted by `Claim` (if the author is not the receiver) or `Refund`
+    /// (if the author is not the sender).
+    #[fail(display = "Sender is not allowed to unlock the funds")]
+    LockNotOwned = 7,
+
+    /// Preimage doesn't match the hash of the lock.
+    ///
+    /// Can be emitted by `Claim`.
+    #[fail(display = "Invalid preimage")]
+    InvalidPreimage = 8,
+
+    /// Lock is timed out, so the funds can only be refunded.
+    ///
+    /// Can be emitted by `Claim`.
+    #[fail(display = "Lock is timed out")]
+    LockTimedOut = 9,
+
+    /// Lock is not timed out yet, so the funds cannot be refunded.
+    ///
+    /// Can be emitted by `Refund`.
+    #[fail(display = "Lock is not timed out yet")]
+    LockNotTimedOut = 10,
+
+    /// Timeout of the lock is not in the future.
+    ///
+    /// Can be emitted by `LockTransfer`.
+    #[fail(display = "Invalid lock timeout")]
+    InvalidTimeout = 11,
+
+    /// Number of deposit addresses is zero or exceeds `MAX_DEPOSIT_ADDRESSES`.
+    ///
+    /// Can be emitted by `CreateDepositAddresses`.
+    #[fail(display = "Invalid number of deposit addresses")]
+    InvalidAddressCount = 12,
+
+    /// Deposit address doesn't exist.
+    ///
+    /// Can be emitted by `Sweep`.
+    #[fail(display = "Deposit address doesn't exist")]
+    DepositAddressNotFound = 13,
+
+    /// Deposit address has no funds to sweep.
+    ///
+    /// Can be emitted by `Sweep`.
+    #[fail(display = "Nothing to sweep")]
+    NothingToSweep = 14,
+
+    /// Wallet is already owned by a service.
+    ///
+    /// Can be emitted by `SetWalletOwner`.
+    #[fail(display = "Wallet is already owned by a service")]
+    WalletAlreadyOwned = 15,
+
+    /// Service owning the sender's wallet is not registered.
+    ///
+    /// Can be emitted by `Transfer` or `LockTransfer`.
+    #[fail(display = "Unknown wallet owner")]
+    UnknownWalletOwner = 16,
+
+    /// Balance of the receiver would overflow.
+    ///
+    /// Can be emitted by `Transfer`, `Issue`, `Claim`, `Refund` or `Sweep`.
+    #[fail(display = "Balance overflow")]
+    BalanceOverflow = 17,
+
+    /// Faucet is disabled in the global configuration.
+    ///
+    /// Can be emitted by `IssueFromFaucet`.
+    #[fail(display = "Faucet is disabled")]
+    FaucetDisabled = 18,
+
+    /// Issued amount exceeds the limit of the faucet.
+    ///
+    /// Can be emitted by `IssueFromFaucet`.
+    #[fail(display = "Faucet amount limit is exceeded")]
+    FaucetLimitExceeded = 19,
+
+    /// Transaction author is not a validator.
+    ///
+    /// Can be emitted by `IssueFromFaucet`.
+    #[fail(display = "Transaction author is not a validator")]
+    NotValidator = 20,
+
+    /// Memo has too many recipients, a duplicate recipient or an oversized ciphertext.
+    ///
+    /// Can be emitted by `TransferWithMemo`.
+    #[fail(display = "Invalid memo")]
+    InvalidMemo = 21,
 }
 
 impl From<Error> for ExecutionError {
@@ -96,6 +218,102 @@ transactions! {
             /// Name of the new wallet.
             name:    &str,
         }
+
+        /// Escrow `amount` of the currency, which can be claimed by the receiver
+        /// by revealing the preimage of `hash` before `timeout_height`, or refunded
+        /// to the sender afterwards.
+        ///
+        /// The hash of the transaction is used as the identifier of the lock.
+        struct LockTransfer {
+            /// `PublicKey` of receiver's wallet.
+            to:                 &PublicKey,
+            /// Amount of currency to escrow.
+            amount:             u64,
+            /// SHA-256 hash of the secret preimage.
+            hash:               &Hash,
+            /// Height of the first block at which the funds can be refunded.
+            timeout_height:     Height,
+            /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+            ///
+            /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+            seed:               u64,
+        }
+
+        /// Claim the escrowed funds by revealing the `preimage`.
+        struct Claim {
+            /// Identifier of the lock.
+            lock_id:  &Hash,
+            /// Preimage of the lock hash.
+            preimage: &[u8],
+        }
+
+        /// Refund the escrowed funds to the sender after the lock has timed out.
+        struct Refund {
+            /// Identifier of the lock.
+            lock_id:  &Hash,
+        }
+
+        /// Derive `count` new deposit addresses for the wallet of the transaction author.
+        struct CreateDepositAddresses {
+            /// Number of derived addresses.
+            count:    u64,
+            /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+            ///
+            /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+            seed:     u64,
+        }
+
+        /// Transfer the ownership of the author's wallet to the service with the given
+        /// identifier. Afterwards, transfers from the wallet are executed only if
+        /// the owning service authorizes them. The ownership cannot be revoked
+        /// with the wallet key.
+        struct SetWalletOwner {
+            /// Identifier of the owning service.
+            service_id: u16,
+        }
+
+        /// Move the funds credited to the deposit `address` to its master wallet.
+        ///
+        /// The transaction can be signed by any key; it is broadcast by validators
+        /// after the block with the credited deposits is committed.
+        struct Sweep {
+            /// Deposit address.
+            address:  &PublicKey,
+            /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+            ///
+            /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+            seed:     u64,
+        }
+
+        /// Issue `amount` of the currency from the faucet to the wallet `to`.
+        ///
+        /// The transaction is signed by the service key of a validator; it is accepted
+        /// only if the faucet is enabled in the global configuration.
+        struct IssueFromFaucet {
+            /// `PublicKey` of receiver's wallet.
+            to:       &PublicKey,
+            /// Issued amount of currency.
+            amount:   u64,
+            /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+            ///
+            /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+            seed:     u64,
+        }
+
+        /// Transfer `amount` of the currency from one wallet to another with a memo
+        /// encrypted to the parties it is disclosed to. Executed as a `Transfer`.
+        struct TransferWithMemo {
+            /// `PublicKey` of receiver's wallet.
+            to:       &PublicKey,
+            /// Amount of currency to transfer.
+            amount:   u64,
+            /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+            ///
+            /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+            seed:     u64,
+            /// Copies of the memo encrypted to each of the parties.
+            memos:    Vec<SealedMemo>,
+        }
     }
 }
 
@@ -124,30 +342,283 @@ impl Transfer {
     }
 }
 
+impl Issue {
+    #[doc(hidden)]
+    pub fn sign(pk: &PublicKey, amount: u64, seed: u64, sk: &SecretKey) -> Signed<RawTransaction> {
+        Message::sign_transaction(Issue::new(amount, seed), CRYPTOCURRENCY_SERVICE_ID, *pk, sk)
+    }
+}
+
+impl LockTransfer {
+    #[doc(hidden)]
+    pub fn sign(
+        pk: &PublicKey,
+        to: &PublicKey,
+        amount: u64,
+        hash: &Hash,
+        timeout_height: Height,
+        seed: u64,
+        sk: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            LockTransfer::new(to, amount, hash, timeout_height, seed),
+            CRYPTOCURRENCY_SERVICE_ID,
+            *pk,
+            sk,
+        )
+    }
+}
+
+impl Claim {
+    #[doc(hidden)]
+    pub fn sign(
+        pk: &PublicKey,
+        lock_id: &Hash,
+        preimage: &[u8],
+        sk: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            Claim::new(lock_id, preimage),
+            CRYPTOCURRENCY_SERVICE_ID,
+            *pk,
+            sk,
+        )
+    }
+}
+
+impl Refund {
+    #[doc(hidden)]
+    pub fn sign(pk: &PublicKey, lock_id: &Hash, sk: &SecretKey) -> Signed<RawTransaction> {
+        Message::sign_transaction(Refund::new(lock_id), CRYPTOCURRENCY_SERVICE_ID, *pk, sk)
+    }
+}
+
+impl CreateDepositAddresses {
+    #[doc(hidden)]
+    pub fn sign(pk: &PublicKey, count: u64, seed: u64, sk: &SecretKey) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            CreateDepositAddresses::new(count, seed),
+            CRYPTOCURRENCY_SERVICE_ID,
+            *pk,
+            sk,
+        )
+    }
+}
+
+impl SetWalletOwner {
+    #[doc(hidden)]
+    pub fn sign(pk: &PublicKey, service_id: u16, sk: &SecretKey) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            SetWalletOwner::new(service_id),
+            CRYPTOCURRENCY_SERVICE_ID,
+            *pk,
+            sk,
+        )
+    }
+}
+
+impl IssueFromFaucet {
+    #[doc(hidden)]
+    pub fn sign(
+        pk: &PublicKey,
+        to: &PublicKey,
+        amount: u64,
+        seed: u64,
+        sk: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            IssueFromFaucet::new(to, amount, seed),
+            CRYPTOCURRENCY_SERVICE_ID,
+            *pk,
+            sk,
+        )
+    }
+}
+
+impl Sweep {
+    #[doc(hidden)]
+    pub fn sign(
+        pk: &PublicKey,
+        address: &PublicKey,
+        seed: u64,
+        sk: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            Sweep::new(address, seed),
+            CRYPTOCURRENCY_SERVICE_ID,
+            *pk,
+            sk,
+        )
+    }
+}
+
+impl TransferWithMemo {
+    #[doc(hidden)]
+    pub fn sign(
+        pk: &PublicKey,
+        to: &PublicKey,
+        amount: u64,
+        seed: u64,
+        memos: Vec<SealedMemo>,
+        sk: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            TransferWithMemo::new(to, amount, seed, memos),
+            CRYPTOCURRENCY_SERVICE_ID,
+            *pk,
+            sk,
+        )
+    }
+
+    /// Returns the copy of the memo encrypted to the given party.
+    pub fn memo_for(&self, recipient: &PublicKey) -> Option<SealedMemo> {
+        self.memos()
+            .into_iter()
+            .find(|memo| memo.recipient() == recipient)
+    }
+}
+
+/// Transaction moving funds from the wallet of its author, which needs access to the owners
+/// of service-owned wallets.
+pub(crate) trait AuthorizedTransaction {
+    /// Executes the transaction with the given wallet owners.
+    fn execute_with(&self, context: TransactionContext, owners: &WalletOwners) -> ExecutionResult;
+}
+
+/// Wrapper executing the transaction with the wallet owners registered in the service.
+pub(crate) struct WithWalletOwners<T> {
+    tx: T,
+    owners: Arc<WalletOwners>,
+}
+
+impl<T> WithWalletOwners<T> {
+    /// Wraps the transaction.
+    pub(crate) fn new(tx: T, owners: Arc<WalletOwners>) -> Self {
+        WithWalletOwners { tx, owners }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for WithWalletOwners<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.tx.fmt(f)
+    }
+}
+
+impl<T: Serialize> Serialize for WithWalletOwners<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.tx.serialize(serializer)
+    }
+}
+
+impl<T> Transaction for WithWalletOwners<T>
+where
+    T: AuthorizedTransaction + fmt::Debug + Serialize + Send + 'static,
+{
+    fn execute(&self, context: TransactionContext) -> ExecutionResult {
+        self.tx.execute_with(context, &self.owners)
+    }
+}
+
+/// Returns the height of the block the transactions are currently executed in.
+fn current_height(fork: &Fork) -> Height {
+    blockchain::Schema::new(fork).height().next()
+}
+
+/// Checks the transfer policy of the identity service, if it is enabled.
+fn check_attestations(fork: &Fork, from: &PublicKey, to: &PublicKey) -> ExecutionResult {
+    let policy = match IdentityConfig::actual(fork).transfer_policy {
+        Some(policy) => policy,
+        None => return Ok(()),
+    };
+    let height = current_height(fork);
+    let schema = IdentitySchema::new(fork);
+    let is_attested = |key: &PublicKey| {
+        schema.attestation(key).map_or(false, |attestation| {
+            attestation.is_valid(height, policy.min_level)
+        })
+    };
+
+    if !is_attested(from) {
+        Err(Error::SenderNotAttested)?
+    }
+    if !is_attested(to) {
+        Err(Error::ReceiverNotAttested)?
+    }
+    Ok(())
+}
+
+/// Asks the service owning the sender's wallet, if any, to authorize the transfer.
+fn check_wallet_owner(
+    fork: &Fork,
+    owners: &WalletOwners,
+    from: &PublicKey,
+    to: &PublicKey,
+    amount: u64,
+) -> ExecutionResult {
+    match Schema::new(fork).wallet_owner(from) {
+        Some(service_id) => owners
+            .get(service_id)
+            .ok_or(Error::UnknownWalletOwner)?
+            .authorize_transfer(fork, from, to, amount),
+        None => Ok(()),
+    }
+}
+
+/// Transfers are executed without wallet owners, unless they are wrapped by the service,
+/// so transfers from service-owned wallets are rejected.
 impl Transaction for Transfer {
-    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+    fn execute(&self, context: TransactionContext) -> ExecutionResult {
+        self.execute_with(context, &WalletOwners::default())
+    }
+}
+
+impl AuthorizedTransaction for Transfer {
+    fn execute_with(
+        &self,
+        mut context: TransactionContext,
+        owners: &WalletOwners,
+    ) -> ExecutionResult {
         let from = &context.author();
         let hash = context.tx_hash();
+        let height = current_height(context.fork());
+
+        // Transfers to deposit addresses are subject to the policy of the master wallet.
+        let deposit_address = Schema::new(context.fork()).deposit_address(self.to());
+        let beneficiary = deposit_address
+            .as_ref()
+            .map_or(*self.to(), |address| *address.master());
+        check_attestations(context.fork(), from, &beneficiary)?;
+        check_wallet_owner(context.fork(), owners, from, self.to(), self.amount())?;
 
         let mut schema = Schema::new(context.fork());
 
         let to = self.to();
         let amount = self.amount();
 
-        if from == to {
+        if from == to || *from == beneficiary {
             return Err(ExecutionError::new(ERROR_SENDER_SAME_AS_RECEIVER));
         }
 
         let sender = schema.wallet(from).ok_or(Error::SenderNotFound)?;
 
+        if let Some(address) = deposit_address {
+            if sender.balance() < amount {
+                Err(Error::InsufficientCurrencyAmount)?
+            }
+
+            schema.credit_deposit(address, from, amount, height, &hash)?;
+            schema.decrease_wallet_balance(sender, amount, &hash);
+            return Ok(());
+        }
+
         let receiver = schema.wallet(to).ok_or(Error::ReceiverNotFound)?;
 
         if sender.balance() < amount {
             Err(Error::InsufficientCurrencyAmount)?
         }
 
+        schema.increase_wallet_balance(receiver, amount, &hash)?;
         schema.decrease_wallet_balance(sender, amount, &hash);
-        schema.increase_wallet_balance(receiver, amount, &hash);
 
         Ok(())
     }
@@ -162,7 +633,7 @@ impl Transaction for Issue {
 
         if let Some(wallet) = schema.wallet(pub_key) {
             let amount = self.amount();
-            schema.increase_wallet_balance(wallet, amount, &hash);
+            schema.increase_wallet_balance(wallet, amount, &hash)?;
             Ok(())
         } else {
             Err(Error::ReceiverNotFound)?
@@ -186,3 +657,209 @@ impl Transaction for CreateWallet {
         }
     }
 }
+
+/// Transfers with memos are executed in the same way as plain transfers.
+impl Transaction for TransferWithMemo {
+    fn execute(&self, context: TransactionContext) -> ExecutionResult {
+        self.execute_with(context, &WalletOwners::default())
+    }
+}
+
+impl AuthorizedTransaction for TransferWithMemo {
+    fn execute_with(&self, context: TransactionContext, owners: &WalletOwners) -> ExecutionResult {
+        let memos = self.memos();
+        let mut recipients = memos.iter().map(SealedMemo::recipient).collect::<Vec<_>>();
+        recipients.sort();
+        recipients.dedup();
+        if memos.len() > MAX_MEMO_RECIPIENTS
+            || recipients.len() != memos.len()
+            || !memos.iter().all(SealedMemo::has_valid_size)
+        {
+            Err(Error::InvalidMemo)?
+        }
+
+        Transfer::new(self.to(), self.amount(), self.seed()).execute_with(context, owners)
+    }
+}
+
+/// Locks are created without wallet owners, unless they are wrapped by the service,
+/// so locks from service-owned wallets are rejected.
+impl Transaction for LockTransfer {
+    fn execute(&self, context: TransactionContext) -> ExecutionResult {
+        self.execute_with(context, &WalletOwners::default())
+    }
+}
+
+impl AuthorizedTransaction for LockTransfer {
+    fn execute_with(
+        &self,
+        mut context: TransactionContext,
+        owners: &WalletOwners,
+    ) -> ExecutionResult {
+        let from = &context.author();
+        let lock_id = context.tx_hash();
+        let to = self.to();
+        let amount = self.amount();
+
+        if from == to {
+            return Err(ExecutionError::new(ERROR_SENDER_SAME_AS_RECEIVER));
+        }
+        if self.timeout_height() <= current_height(context.fork()) {
+            Err(Error::InvalidTimeout)?
+        }
+        check_attestations(context.fork(), from, to)?;
+        check_wallet_owner(context.fork(), owners, from, to, amount)?;
+
+        let mut schema = Schema::new(context.fork());
+
+        let sender = schema.wallet(from).ok_or(Error::SenderNotFound)?;
+
+        if schema.wallet(to).is_none() {
+            Err(Error::ReceiverNotFound)?
+        }
+
+        if sender.balance() < amount {
+            Err(Error::InsufficientCurrencyAmount)?
+        }
+
+        let lock = HashLock::new(from, to, amount, self.hash(), self.timeout_height());
+        schema.decrease_wallet_balance(sender, amount, &lock_id);
+        schema.locks_mut().put(&lock_id, lock);
+
+        Ok(())
+    }
+}
+
+impl Transaction for Claim {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let author = &context.author();
+        let hash = context.tx_hash();
+        let height = current_height(context.fork());
+
+        let mut schema = Schema::new(context.fork());
+
+        let lock = schema.lock(self.lock_id()).ok_or(Error::LockNotFound)?;
+        if lock.to() != author {
+            Err(Error::LockNotOwned)?
+        }
+        if lock.is_expired(height) {
+            Err(Error::LockTimedOut)?
+        }
+        if !lock.matches(self.preimage()) {
+            Err(Error::InvalidPreimage)?
+        }
+
+        let receiver = schema.wallet(lock.to()).ok_or(Error::ReceiverNotFound)?;
+        schema.increase_wallet_balance(receiver, lock.amount(), &hash)?;
+        schema.locks_mut().remove(self.lock_id());
+
+        Ok(())
+    }
+}
+
+impl Transaction for Refund {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let author = &context.author();
+        let hash = context.tx_hash();
+        let height = current_height(context.fork());
+
+        let mut schema = Schema::new(context.fork());
+
+        let lock = schema.lock(self.lock_id()).ok_or(Error::LockNotFound)?;
+        if lock.from() != author {
+            Err(Error::LockNotOwned)?
+        }
+        if !lock.is_expired(height) {
+            Err(Error::LockNotTimedOut)?
+        }
+
+        let sender = schema.wallet(lock.from()).ok_or(Error::SenderNotFound)?;
+        schema.increase_wallet_balance(sender, lock.amount(), &hash)?;
+        schema.locks_mut().remove(self.lock_id());
+
+        Ok(())
+    }
+}
+
+impl Transaction for CreateDepositAddresses {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let pub_key = &context.author();
+        let count = self.count();
+
+        let mut schema = Schema::new(context.fork());
+
+        if count == 0 || count > MAX_DEPOSIT_ADDRESSES {
+            Err(Error::InvalidAddressCount)?
+        }
+        if schema.wallet(pub_key).is_none() {
+            Err(Error::ReceiverNotFound)?
+        }
+
+        schema.create_deposit_addresses(pub_key, count);
+        Ok(())
+    }
+}
+
+impl Transaction for Sweep {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let hash = context.tx_hash();
+
+        let mut schema = Schema::new(context.fork());
+
+        let address = schema
+            .deposit_address(self.address())
+            .ok_or(Error::DepositAddressNotFound)?;
+        if address.balance() == 0 {
+            Err(Error::NothingToSweep)?
+        }
+
+        schema.sweep_deposit_address(address, &hash)?;
+        Ok(())
+    }
+}
+
+impl Transaction for IssueFromFaucet {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let pub_key = &context.author();
+        let hash = context.tx_hash();
+
+        let config = FaucetConfig::actual(context.fork());
+        if !config.testnet {
+            Err(Error::FaucetDisabled)?
+        }
+        if self.amount() > config.max_amount {
+            Err(Error::FaucetLimitExceeded)?
+        }
+        let is_validator = blockchain::Schema::new(context.fork())
+            .actual_configuration()
+            .validator_keys
+            .iter()
+            .any(|keys| keys.service_key == *pub_key);
+        if !is_validator {
+            Err(Error::NotValidator)?
+        }
+
+        let mut schema = Schema::new(context.fork());
+        let wallet = schema.wallet(self.to()).ok_or(Error::ReceiverNotFound)?;
+        schema.increase_wallet_balance(wallet, self.amount(), &hash)?;
+        Ok(())
+    }
+}
+
+impl Transaction for SetWalletOwner {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let pub_key = &context.author();
+
+        let mut schema = Schema::new(context.fork());
+
+        if schema.wallet(pub_key).is_none() {
+            Err(Error::SenderNotFound)?
+        }
+        if schema.wallet_owner(pub_key).is_some() {
+            Err(Error::WalletAlreadyOwned)?
+        }
+
+        schema.wallet_owners_mut().put(pub_key, self.service_id());
+        Ok(())
+    }
+}
@@ -28,13 +28,14 @@ use exonum::{
     api::node::public::explorer::{TransactionQuery, TransactionResponse},
     crypto::{self, Hash, PublicKey, SecretKey},
     messages::{self, RawTransaction, Signed},
+    storage::ProofMapIndex,
 };
 use exonum_testkit::{ApiKind, TestKit, TestKitApi, TestKitBuilder};
 
 // Import data types used in tests from the crate where the service is defined.
 use cryptocurrency::{
-    api::{WalletInfo, WalletQuery},
-    transactions::{CreateWallet, Transfer},
+    api::{WalletInfo, WalletNameQuery, WalletQuery},
+    transactions::{CreateWallet, Issue, Transfer},
     wallet::Wallet,
     Service,
 };
@@ -195,6 +196,70 @@ fn test_transfer_overcharge() {
     assert_eq!(wallet.balance(), 100);
 }
 
+/// Check that issuance which would overflow the balance is rejected.
+#[test]
+fn test_issue_overflow() {
+    let (mut testkit, api) = create_testkit();
+
+    let (tx_alice, key_alice) = api.create_wallet(ALICE_NAME);
+    testkit.create_block();
+
+    // Alice already has 100 tokens, so issuing the maximal amount overflows her balance.
+    let tx = Issue::sign(&tx_alice.author(), u64::max_value(), 0, &key_alice);
+    api.transfer(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({ "type": "error", "code": 17, "description": "Balance overflow" }),
+    );
+
+    let wallet = api.get_wallet(tx_alice.author()).unwrap();
+    assert_eq!(wallet.balance(), 100);
+    assert_eq!(wallet.history_len(), 1);
+}
+
+/// Check that wallets can be looked up by name.
+#[test]
+fn test_wallets_by_name() {
+    let (mut testkit, api) = create_testkit();
+    let (tx_alice, _) = api.create_wallet(ALICE_NAME);
+    let (tx_bob, _) = api.create_wallet(BOB_NAME);
+    let (tx_other_alice, _) = api.create_wallet(ALICE_NAME);
+    testkit.create_block();
+
+    let mut alice_keys = vec![tx_alice.author(), tx_other_alice.author()];
+    alice_keys.sort();
+    let keys = |name: &str| {
+        api.get_wallets_by_name(name)
+            .iter()
+            .map(|wallet| *wallet.pub_key())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(keys(ALICE_NAME), alice_keys);
+    assert_eq!(keys(BOB_NAME), vec![tx_bob.author()]);
+    assert!(keys("Carol").is_empty());
+}
+
+/// Check that the wallets stored before the index of the wallet names was introduced
+/// are indexed with the next block.
+#[test]
+fn test_wallets_by_name_backfill() {
+    let (mut testkit, api) = create_testkit();
+    let (pub_key, _) = crypto::gen_keypair();
+    let wallet = Wallet::new(&pub_key, ALICE_NAME, 100, 0, &Hash::zero());
+    {
+        // Write the wallet bypassing the index of the wallet names.
+        let blockchain = testkit.blockchain_mut();
+        let mut fork = blockchain.fork();
+        ProofMapIndex::new("cryptocurrency.wallets", &mut fork).put(&pub_key, wallet.clone());
+        blockchain.merge(fork.into_patch()).unwrap();
+    }
+    assert!(api.get_wallets_by_name(ALICE_NAME).is_empty());
+
+    testkit.create_block();
+    assert_eq!(api.get_wallets_by_name(ALICE_NAME), vec![wallet]);
+}
+
 #[test]
 fn test_unknown_wallet_request() {
     let (_testkit, api) = create_testkit();
@@ -250,6 +315,16 @@ impl CryptocurrencyApi {
         wallet
     }
 
+    fn get_wallets_by_name(&self, name: &str) -> Vec<Wallet> {
+        self.inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&WalletNameQuery {
+                name: name.to_owned(),
+            })
+            .get("v1/wallets/by_name")
+            .unwrap()
+    }
+
     /// Sends a transfer transaction over HTTP and checks the synchronous result.
     fn transfer(&self, tx: &Signed<RawTransaction>) {
         let data = messages::to_hex_string(&tx);
@@ -295,7 +370,9 @@ impl CryptocurrencyApi {
 
 /// Creates a testkit together with the API wrapper defined above.
 fn create_testkit() -> (TestKit, CryptocurrencyApi) {
-    let testkit = TestKitBuilder::validator().with_service(Service).create();
+    let testkit = TestKitBuilder::validator()
+        .with_service(Service::default())
+        .create();
     let api = CryptocurrencyApi {
         inner: testkit.api(),
     };
@@ -0,0 +1,183 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests of the confidential transfers service.
+
+#![cfg(feature = "confidential")]
+
+extern crate exonum;
+extern crate exonum_cryptocurrency_advanced as cryptocurrency;
+#[macro_use]
+extern crate exonum_testkit;
+#[macro_use]
+extern crate serde_json;
+
+use exonum::{
+    api::node::public::explorer::TransactionQuery,
+    crypto::{
+        self,
+        confidential::{Blinding, Commitment, RangeProof},
+        PublicKey, SecretKey,
+    },
+    messages::{RawTransaction, Signed},
+};
+use exonum_testkit::{ApiKind, TestKit, TestKitBuilder};
+
+use cryptocurrency::{
+    confidential::{
+        schema::ConfidentialSchema,
+        transactions::{ConfidentialTransfer, Shield},
+        ConfidentialService,
+    },
+    transactions::CreateWallet,
+    Schema, Service,
+};
+
+use constants::{ALICE_NAME, BOB_NAME};
+
+mod constants;
+
+/// Check that shielded funds are transferred confidentially.
+#[test]
+fn test_confidential_transfer() {
+    let (mut testkit, alice, bob) = create_testkit();
+    let tx = Shield::sign(&alice.0, 40, 0, &alice.1);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(&testkit, &tx, &json!({ "type": "success" }));
+    assert_eq!(public_balance(&testkit, &alice.0), 60);
+    assert!(confidential_balance(&testkit, &alice.0).opens_to(40, &Blinding::zero()));
+
+    let blinding = Blinding::random();
+    let (amount_proof, amount) = RangeProof::new(15, &blinding);
+    let (balance_proof, _) = RangeProof::new(25, &(Blinding::zero() - blinding));
+    let tx = ConfidentialTransfer::sign(
+        &alice.0,
+        &bob.0,
+        &amount,
+        &amount_proof,
+        &balance_proof,
+        0,
+        &alice.1,
+    );
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(&testkit, &tx, &json!({ "type": "success" }));
+
+    // The amount is not revealed, but the parties are able to open their balances.
+    assert!(confidential_balance(&testkit, &alice.0).opens_to(25, &(Blinding::zero() - blinding)));
+    assert!(confidential_balance(&testkit, &bob.0).opens_to(15, &blinding));
+    assert_eq!(public_balance(&testkit, &bob.0), 100);
+}
+
+/// Check that transfers exceeding the confidential balance are rejected.
+#[test]
+fn test_confidential_transfer_overspend() {
+    let (mut testkit, alice, bob) = create_testkit();
+    testkit.create_block_with_transaction(Shield::sign(&alice.0, 10, 0, &alice.1));
+
+    // The remaining balance of Alice would be "negative", so the honest prover
+    // cannot create a valid range proof for it.
+    let blinding = Blinding::random();
+    let (amount_proof, amount) = RangeProof::new(20, &blinding);
+    let (balance_proof, _) = RangeProof::new(0, &(Blinding::zero() - blinding));
+    let tx = ConfidentialTransfer::sign(
+        &alice.0,
+        &bob.0,
+        &amount,
+        &amount_proof,
+        &balance_proof,
+        0,
+        &alice.1,
+    );
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(
+        &testkit,
+        &tx,
+        &json!({
+            "type": "error",
+            "code": 4,
+            "description": "Invalid range proof for the remaining balance",
+        }),
+    );
+
+    // A proof for a different commitment is rejected as well.
+    let (other_proof, _) = RangeProof::new(5, &Blinding::random());
+    let amount = Commitment::new(5, &blinding);
+    let tx = ConfidentialTransfer::sign(
+        &alice.0,
+        &bob.0,
+        &amount,
+        &other_proof,
+        &balance_proof,
+        1,
+        &alice.1,
+    );
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(
+        &testkit,
+        &tx,
+        &json!({
+            "type": "error",
+            "code": 3,
+            "description": "Invalid range proof for the amount",
+        }),
+    );
+    assert!(confidential_balance(&testkit, &alice.0).opens_to(10, &Blinding::zero()));
+}
+
+/// Creates a testkit with the cryptocurrency and confidential transfers services
+/// and wallets for Alice and Bob.
+fn create_testkit() -> (TestKit, (PublicKey, SecretKey), (PublicKey, SecretKey)) {
+    let mut testkit = TestKitBuilder::validator()
+        .with_service(Service::default())
+        .with_service(ConfidentialService)
+        .create();
+    let (alice, bob) = (crypto::gen_keypair(), crypto::gen_keypair());
+    testkit.create_block_with_transactions(txvec![
+        CreateWallet::sign(ALICE_NAME, &alice.0, &alice.1),
+        CreateWallet::sign(BOB_NAME, &bob.0, &bob.1),
+    ]);
+    (testkit, alice, bob)
+}
+
+fn public_balance(testkit: &TestKit, pub_key: &PublicKey) -> u64 {
+    Schema::new(testkit.snapshot())
+        .wallet(pub_key)
+        .unwrap()
+        .balance()
+}
+
+fn confidential_balance(testkit: &TestKit, pub_key: &PublicKey) -> Commitment {
+    ConfidentialSchema::new(testkit.snapshot()).balance(pub_key)
+}
+
+/// Asserts that the transaction has a specified status.
+fn assert_tx_status(
+    testkit: &TestKit,
+    tx: &Signed<RawTransaction>,
+    expected_status: &serde_json::Value,
+) {
+    let info: serde_json::Value = testkit
+        .api()
+        .public(ApiKind::Explorer)
+        .query(&TransactionQuery::new(tx.hash()))
+        .get("v1/transactions")
+        .unwrap();
+
+    if let serde_json::Value::Object(mut info) = info {
+        let tx_status = info.remove("status").unwrap();
+        assert_eq!(tx_status, *expected_status);
+    } else {
+        panic!("Invalid transaction info format, object expected");
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests of the extension of the `devnet` command creating funded test wallets.
+
+extern crate exonum;
+extern crate exonum_cryptocurrency_advanced as cryptocurrency;
+extern crate exonum_testkit;
+#[macro_use]
+extern crate serde_json;
+
+use exonum::{
+    api::node::public::explorer::TransactionResponse,
+    helpers::fabric::{keys, CommandExtension, Context},
+};
+use exonum_testkit::{ApiKind, TestKitBuilder};
+
+use std::{
+    env,
+    fs::{self, File},
+};
+
+use cryptocurrency::{
+    devnet::{Devnet, TestWallet, WALLETS_FILE},
+    Schema, Service,
+};
+
+#[test]
+fn test_devnet_wallets() {
+    let dir = env::temp_dir().join("exonum-cryptocurrency-devnet");
+    fs::create_dir_all(&dir).unwrap();
+
+    let mut context = Context::default();
+    context.set_arg("OUTPUT_DIR", dir.to_string_lossy().into_owned());
+    context.set_arg("TEST_WALLETS", "3".to_owned());
+    context.set_arg("TEST_FUNDS", "50".to_owned());
+    let context = Devnet.execute(context).unwrap();
+
+    let wallets: Vec<TestWallet> =
+        serde_json::from_reader(File::open(dir.join(WALLETS_FILE)).unwrap()).unwrap();
+    assert_eq!(wallets.len(), 3);
+    let batches = context.get(keys::DEVNET_TRANSACTIONS).unwrap();
+    assert_eq!(batches.len(), 2);
+
+    let mut testkit = TestKitBuilder::validator()
+        .with_service(Service::default())
+        .create();
+    let api = testkit.api();
+    for batch in batches {
+        for tx_body in batch {
+            let _: TransactionResponse = api
+                .public(ApiKind::Explorer)
+                .query(&json!({ "tx_body": tx_body }))
+                .post("v1/transactions")
+                .unwrap();
+        }
+        testkit.create_block();
+    }
+
+    let snapshot = testkit.snapshot();
+    let schema = Schema::new(&snapshot);
+    for wallet in &wallets {
+        let stored = schema.wallet(&wallet.public_key).unwrap();
+        assert_eq!(stored.name(), wallet.name);
+        assert_eq!(stored.balance(), 150);
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests of deposit addresses for exchange integrations.
+
+extern crate exonum;
+extern crate exonum_cryptocurrency_advanced as cryptocurrency;
+#[macro_use]
+extern crate exonum_testkit;
+#[macro_use]
+extern crate serde_json;
+
+use exonum::{
+    api::node::public::explorer::TransactionQuery,
+    crypto::{self, PublicKey, SecretKey},
+    helpers::Height,
+    messages::{RawTransaction, Signed},
+};
+use exonum_testkit::{ApiKind, TestKit, TestKitBuilder};
+
+use cryptocurrency::{
+    api::{DepositsInfo, DepositsQuery},
+    exchange::derive_deposit_address,
+    transactions::{CreateDepositAddresses, CreateWallet, Sweep, Transfer},
+    Schema, Service,
+};
+
+use constants::{ALICE_NAME, BOB_NAME};
+
+mod constants;
+
+/// Check that transfers to deposit addresses are swept to the master wallet.
+#[test]
+fn test_deposit_and_sweep() {
+    let (mut testkit, exchange, client) = create_testkit();
+    let tx = CreateDepositAddresses::sign(&exchange.0, 3, 0, &exchange.1);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(&testkit, &tx, &json!({ "type": "success" }));
+
+    let addresses = (0..3)
+        .map(|index| derive_deposit_address(&exchange.0, index))
+        .collect::<Vec<_>>();
+    {
+        let snapshot = testkit.snapshot();
+        let schema = Schema::new(&snapshot);
+        for (index, address) in addresses.iter().enumerate() {
+            let info = schema.deposit_address(address).unwrap();
+            assert_eq!(info.master(), &exchange.0);
+            assert_eq!(info.index(), index as u64);
+        }
+        assert!(schema
+            .deposit_address(&derive_deposit_address(&exchange.0, 3))
+            .is_none());
+    }
+
+    let tx = Transfer::sign(&client.0, &addresses[1], 10, 0, &client.1);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(&testkit, &tx, &json!({ "type": "success" }));
+    assert_eq!(balance(&testkit, &client.0), 90);
+    assert_eq!(balance(&testkit, &exchange.0), 100);
+    assert_eq!(deposit_balance(&testkit, &addresses[1]), 10);
+
+    // The sweep is broadcast in `after_commit` and included into the next block.
+    let block = testkit.create_block();
+    assert_eq!(block.len(), 1);
+    assert_eq!(balance(&testkit, &exchange.0), 110);
+    assert_eq!(deposit_balance(&testkit, &addresses[1]), 0);
+    assert_eq!(
+        Schema::new(testkit.snapshot())
+            .pending_sweeps()
+            .iter()
+            .count(),
+        0
+    );
+
+    // There is nothing to sweep anymore.
+    let (pk, sk) = crypto::gen_keypair();
+    let tx = Sweep::sign(&pk, &addresses[1], 0, &sk);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(
+        &testkit,
+        &tx,
+        &json!({ "type": "error", "code": 14, "description": "Nothing to sweep" }),
+    );
+}
+
+/// Check that the number of derived addresses is bounded.
+#[test]
+fn test_create_deposit_addresses_invalid_count() {
+    let (mut testkit, exchange, _) = create_testkit();
+    let tx_zero = CreateDepositAddresses::sign(&exchange.0, 0, 0, &exchange.1);
+    let tx_many = CreateDepositAddresses::sign(&exchange.0, 1_000, 0, &exchange.1);
+    testkit.create_block_with_transactions(txvec![tx_zero.clone(), tx_many.clone()]);
+    for tx in &[tx_zero, tx_many] {
+        assert_tx_status(
+            &testkit,
+            tx,
+            &json!({ "type": "error", "code": 12, "description": "Invalid number of deposit addresses" }),
+        );
+    }
+}
+
+/// Check that the reconciliation API returns proven deposits within the interval.
+#[test]
+fn test_deposits_reconciliation() {
+    let (mut testkit, exchange, client) = create_testkit();
+    testkit.create_block_with_transaction(CreateDepositAddresses::sign(
+        &exchange.0,
+        2,
+        0,
+        &exchange.1,
+    ));
+    let addresses = (0..2)
+        .map(|index| derive_deposit_address(&exchange.0, index))
+        .collect::<Vec<_>>();
+
+    // Deposits at heights 3 and 5; sweeps at heights 4 and 6.
+    testkit.create_block_with_transaction(Transfer::sign(
+        &client.0,
+        &addresses[0],
+        10,
+        0,
+        &client.1,
+    ));
+    testkit.create_block();
+    testkit.create_block_with_transaction(Transfer::sign(
+        &client.0,
+        &addresses[1],
+        5,
+        1,
+        &client.1,
+    ));
+    testkit.create_block();
+    assert_eq!(balance(&testkit, &exchange.0), 115);
+
+    let amounts = |from_height, to_height| {
+        let info = get_deposits(
+            &testkit,
+            &exchange.0,
+            Height(from_height),
+            Height(to_height),
+        );
+        let to_account = info.to_account.check().unwrap();
+        assert!(info
+            .to_table
+            .check()
+            .unwrap()
+            .entries()
+            .any(|(_, hash)| *hash == to_account.merkle_root()));
+        let account = to_account
+            .entries()
+            .find(|(key, _)| **key == exchange.0)
+            .map(|(_, account)| account.clone())
+            .unwrap();
+        assert_eq!(account.deposits_len(), 2);
+
+        info.deposits.map_or_else(Vec::new, |proof| {
+            proof
+                .validate(*account.deposits_hash(), account.deposits_len())
+                .unwrap()
+                .into_iter()
+                .map(|(_, deposit)| deposit.amount())
+                .collect()
+        })
+    };
+    assert_eq!(amounts(0, 10), vec![10, 5]);
+    assert_eq!(amounts(3, 4), vec![10]);
+    assert_eq!(amounts(4, 6), vec![5]);
+    assert!(amounts(6, 10).is_empty());
+}
+
+/// Creates a testkit with the wallets of the exchange and its client.
+fn create_testkit() -> (TestKit, (PublicKey, SecretKey), (PublicKey, SecretKey)) {
+    let mut testkit = TestKitBuilder::validator()
+        .with_service(Service::default())
+        .create();
+    let (exchange, client) = (crypto::gen_keypair(), crypto::gen_keypair());
+    testkit.create_block_with_transactions(txvec![
+        CreateWallet::sign(ALICE_NAME, &exchange.0, &exchange.1),
+        CreateWallet::sign(BOB_NAME, &client.0, &client.1),
+    ]);
+    (testkit, exchange, client)
+}
+
+fn balance(testkit: &TestKit, pub_key: &PublicKey) -> u64 {
+    Schema::new(testkit.snapshot())
+        .wallet(pub_key)
+        .unwrap()
+        .balance()
+}
+
+fn deposit_balance(testkit: &TestKit, address: &PublicKey) -> u64 {
+    Schema::new(testkit.snapshot())
+        .deposit_address(address)
+        .unwrap()
+        .balance()
+}
+
+fn get_deposits(
+    testkit: &TestKit,
+    master: &PublicKey,
+    from_height: Height,
+    to_height: Height,
+) -> DepositsInfo {
+    testkit
+        .api()
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&DepositsQuery {
+            master: *master,
+            from_height,
+            to_height,
+        })
+        .get("v1/exchange/deposits")
+        .unwrap()
+}
+
+/// Asserts that the transaction has a specified status.
+fn assert_tx_status(
+    testkit: &TestKit,
+    tx: &Signed<RawTransaction>,
+    expected_status: &serde_json::Value,
+) {
+    let info: serde_json::Value = testkit
+        .api()
+        .public(ApiKind::Explorer)
+        .query(&TransactionQuery::new(tx.hash()))
+        .get("v1/transactions")
+        .unwrap();
+
+    if let serde_json::Value::Object(mut info) = info {
+        let tx_status = info.remove("status").unwrap();
+        assert_eq!(tx_status, *expected_status);
+    } else {
+        panic!("Invalid transaction info format, object expected");
+    }
+}
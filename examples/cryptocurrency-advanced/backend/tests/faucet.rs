@@ -0,0 +1,172 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests of the faucet for the test networks.
+
+extern crate exonum;
+extern crate exonum_cryptocurrency_advanced as cryptocurrency;
+#[macro_use]
+extern crate exonum_testkit;
+#[macro_use]
+extern crate serde_json;
+
+use exonum::{
+    api::node::public::explorer::TransactionQuery,
+    crypto::{self, PublicKey},
+};
+use exonum_testkit::{ApiKind, TestKit, TestKitBuilder};
+
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    time::{Duration, Instant},
+};
+
+use cryptocurrency::{
+    api::TransactionResponse,
+    faucet::{FaucetConfig, FaucetQuery, RateLimiter},
+    transactions::{CreateWallet, IssueFromFaucet},
+    Schema, Service,
+};
+
+use constants::ALICE_NAME;
+
+mod constants;
+
+/// Check that the faucet issues the requested amount to the wallet.
+#[test]
+fn test_faucet_issue() {
+    let mut testkit = create_testkit(true);
+    let alice = create_wallet(&mut testkit);
+
+    let response: TransactionResponse = testkit
+        .api()
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&FaucetQuery {
+            pub_key: alice,
+            amount: 500,
+        }).post("v1/faucet")
+        .unwrap();
+    testkit.create_block();
+    assert_tx_status(&testkit, &response.tx_hash, &json!({ "type": "success" }));
+    assert_eq!(wallet_balance(&testkit, &alice), 600);
+}
+
+/// Check that the faucet rejects requests exceeding the amount limit.
+#[test]
+fn test_faucet_amount_limit() {
+    let mut testkit = create_testkit(true);
+    let alice = create_wallet(&mut testkit);
+
+    let response = testkit
+        .api()
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&FaucetQuery {
+            pub_key: alice,
+            amount: 1001,
+        }).post::<TransactionResponse>("v1/faucet");
+    assert!(response.is_err());
+}
+
+/// Check that faucet transactions are executed only if the faucet is enabled
+/// and are authorized only by validators.
+#[test]
+fn test_faucet_transaction() {
+    let mut testkit = create_testkit(false);
+    let alice = create_wallet(&mut testkit);
+    let (validator_pk, validator_sk) = {
+        let keys = testkit.network().us().service_keypair();
+        (*keys.0, keys.1.clone())
+    };
+    let tx = IssueFromFaucet::sign(&validator_pk, &alice, 100, 0, &validator_sk);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(
+        &testkit,
+        &tx.hash(),
+        &json!({ "type": "error", "code": 18, "description": "Faucet is disabled" }),
+    );
+
+    let mut testkit = create_testkit(true);
+    let alice = create_wallet(&mut testkit);
+    let (mallory_pk, mallory_sk) = crypto::gen_keypair();
+    let tx = IssueFromFaucet::sign(&mallory_pk, &alice, 100, 0, &mallory_sk);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(
+        &testkit,
+        &tx.hash(),
+        &json!({ "type": "error", "code": 20, "description": "Transaction author is not a validator" }),
+    );
+    assert_eq!(wallet_balance(&testkit, &alice), 100);
+}
+
+/// Check the per-key and per-IP rate limits of the faucet.
+#[test]
+fn test_faucet_rate_limits() {
+    let mut limiter = RateLimiter::new(&FaucetConfig {
+        testnet: true,
+        key_interval: 60,
+        ip_interval: 10,
+        ..FaucetConfig::default()
+    });
+    let (alice, _) = crypto::gen_keypair();
+    let (bob, _) = crypto::gen_keypair();
+    let ip = Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
+    let now = Instant::now();
+
+    assert!(limiter.try_acquire(&alice, ip, now));
+    assert!(!limiter.try_acquire(&bob, ip, now + Duration::from_secs(5)));
+    assert!(limiter.try_acquire(&bob, ip, now + Duration::from_secs(10)));
+    assert!(!limiter.try_acquire(&alice, None, now + Duration::from_secs(30)));
+    assert!(limiter.try_acquire(&alice, ip, now + Duration::from_secs(60)));
+}
+
+fn create_testkit(testnet: bool) -> TestKit {
+    let config = FaucetConfig {
+        testnet,
+        ..FaucetConfig::default()
+    };
+    TestKitBuilder::validator()
+        .with_service(Service::default().with_faucet(config))
+        .create()
+}
+
+fn create_wallet(testkit: &mut TestKit) -> PublicKey {
+    let (pk, sk) = crypto::gen_keypair();
+    testkit.create_block_with_transactions(txvec![CreateWallet::sign(ALICE_NAME, &pk, &sk)]);
+    pk
+}
+
+fn wallet_balance(testkit: &TestKit, key: &PublicKey) -> u64 {
+    let snapshot = testkit.snapshot();
+    Schema::new(&snapshot).wallet(key).unwrap().balance()
+}
+
+fn assert_tx_status(
+    testkit: &TestKit,
+    tx_hash: &crypto::Hash,
+    expected_status: &serde_json::Value,
+) {
+    let info: serde_json::Value = testkit
+        .api()
+        .public(ApiKind::Explorer)
+        .query(&TransactionQuery::new(*tx_hash))
+        .get("v1/transactions")
+        .unwrap();
+
+    if let serde_json::Value::Object(mut info) = info {
+        let tx_status = info.remove("status").unwrap();
+        assert_eq!(tx_status, *expected_status);
+    } else {
+        panic!("Invalid transaction info format, object expected");
+    }
+}
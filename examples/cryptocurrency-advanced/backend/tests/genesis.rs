@@ -0,0 +1,97 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests of the wallets created in the genesis block.
+
+extern crate exonum;
+extern crate exonum_cryptocurrency_advanced as cryptocurrency;
+extern crate exonum_testkit;
+
+use exonum::{
+    crypto,
+    helpers::{
+        config::ConfigFile,
+        fabric::{keys, AbstractConfig, CommandExtension, Context},
+    },
+};
+use exonum_testkit::TestKitBuilder;
+
+use std::{env, fs};
+
+use cryptocurrency::{
+    genesis::{GenerateCommonConfig, GenesisWallet, GenesisWallets},
+    Schema, Service,
+};
+
+use constants::{ALICE_NAME, BOB_NAME};
+
+mod constants;
+
+/// Check that the genesis wallets are created with the given balances.
+#[test]
+fn test_genesis_wallets() {
+    let (alice, _) = crypto::gen_keypair();
+    let (bob, _) = crypto::gen_keypair();
+    let testkit = TestKitBuilder::validator()
+        .with_service(Service::default().with_genesis_wallets(vec![
+            GenesisWallet {
+                pub_key: alice,
+                name: ALICE_NAME.to_owned(),
+                balance: 1000,
+            },
+            GenesisWallet {
+                pub_key: bob,
+                name: BOB_NAME.to_owned(),
+                balance: 0,
+            },
+        ])).create();
+
+    let snapshot = testkit.snapshot();
+    let schema = Schema::new(&snapshot);
+    let wallet = schema.wallet(&alice).unwrap();
+    assert_eq!(wallet.name(), ALICE_NAME);
+    assert_eq!(wallet.balance(), 1000);
+    assert_eq!(wallet.history_len(), 0);
+    assert!(schema.wallet_history(&alice).is_empty());
+    assert_eq!(schema.wallet(&bob).unwrap().balance(), 0);
+}
+
+/// Check that the genesis wallets are read from the file passed to `generate-template`.
+#[test]
+fn test_genesis_wallets_template() {
+    let (alice, _) = crypto::gen_keypair();
+    let wallets = GenesisWallets {
+        wallets: vec![GenesisWallet {
+            pub_key: alice,
+            name: ALICE_NAME.to_owned(),
+            balance: 1000,
+        }],
+    };
+    let dir = env::temp_dir().join("exonum-cryptocurrency-genesis");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("wallets.toml");
+    ConfigFile::save(&wallets, &path).unwrap();
+
+    let mut context = Context::default();
+    context.set_arg("GENESIS_WALLETS", path.to_string_lossy().into_owned());
+    context.set(keys::SERVICES_CONFIG, AbstractConfig::default());
+    let context = GenerateCommonConfig.execute(context).unwrap();
+
+    let services_config = context.get(keys::SERVICES_CONFIG).unwrap();
+    let stored: GenesisWallets = services_config["genesis_wallets"]
+        .clone()
+        .try_into()
+        .unwrap();
+    assert_eq!(stored, wallets);
+}
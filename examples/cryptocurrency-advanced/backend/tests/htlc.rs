@@ -0,0 +1,215 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests of hash-locked transfers.
+
+extern crate exonum;
+extern crate exonum_cryptocurrency_advanced as cryptocurrency;
+#[macro_use]
+extern crate exonum_testkit;
+#[macro_use]
+extern crate serde_json;
+
+use exonum::{
+    api::node::public::explorer::TransactionQuery,
+    crypto::{self, Hash, PublicKey, SecretKey},
+    helpers::Height,
+    messages::{RawTransaction, Signed},
+};
+use exonum_testkit::{ApiKind, TestKit, TestKitBuilder};
+
+use cryptocurrency::{
+    api::{LockQuery, PendingLock, WalletQuery},
+    lock::HashLock,
+    transactions::{Claim, CreateWallet, LockTransfer, Refund},
+    Schema, Service,
+};
+
+use constants::{ALICE_NAME, BOB_NAME};
+
+mod constants;
+
+const PREIMAGE: &[u8] = b"secret";
+
+/// Check that the receiver claims the escrowed funds by revealing the preimage.
+#[test]
+fn test_claim() {
+    let (mut testkit, alice, bob) = create_testkit();
+    let tx_lock = LockTransfer::sign(
+        &alice.0,
+        &bob.0,
+        10,
+        &crypto::hash(PREIMAGE),
+        Height(10),
+        0,
+        &alice.1,
+    );
+    testkit.create_block_with_transaction(tx_lock.clone());
+    assert_tx_status(&testkit, &tx_lock, &json!({ "type": "success" }));
+    assert_eq!(balance(&testkit, &alice.0), 90);
+    assert_eq!(balance(&testkit, &bob.0), 100);
+
+    let lock = get_lock(&testkit, tx_lock.hash()).unwrap();
+    assert_eq!(lock.from(), &alice.0);
+    assert_eq!(lock.amount(), 10);
+    for key in &[alice.0, bob.0] {
+        let locks = get_wallet_locks(&testkit, *key);
+        assert_eq!(locks.len(), 1);
+        assert_eq!(locks[0].lock_id, tx_lock.hash());
+    }
+
+    let tx_wrong_author = Claim::sign(&alice.0, &tx_lock.hash(), PREIMAGE, &alice.1);
+    let tx_wrong_preimage = Claim::sign(&bob.0, &tx_lock.hash(), b"guess", &bob.1);
+    let tx_claim = Claim::sign(&bob.0, &tx_lock.hash(), PREIMAGE, &bob.1);
+    testkit.create_block_with_transactions(txvec![
+        tx_wrong_author.clone(),
+        tx_wrong_preimage.clone(),
+        tx_claim.clone(),
+    ]);
+    assert_tx_status(
+        &testkit,
+        &tx_wrong_author,
+        &json!({ "type": "error", "code": 7, "description": "Sender is not allowed to unlock the funds" }),
+    );
+    assert_tx_status(
+        &testkit,
+        &tx_wrong_preimage,
+        &json!({ "type": "error", "code": 8, "description": "Invalid preimage" }),
+    );
+    assert_tx_status(&testkit, &tx_claim, &json!({ "type": "success" }));
+
+    assert_eq!(balance(&testkit, &alice.0), 90);
+    assert_eq!(balance(&testkit, &bob.0), 110);
+    assert!(get_lock(&testkit, tx_lock.hash()).is_none());
+    assert!(get_wallet_locks(&testkit, alice.0).is_empty());
+}
+
+/// Check that the sender is refunded only after the lock has timed out.
+#[test]
+fn test_refund() {
+    let (mut testkit, alice, bob) = create_testkit();
+    // Wallets are created at height 1.
+    let timeout_height = Height(4);
+    let tx_lock = LockTransfer::sign(
+        &alice.0,
+        &bob.0,
+        10,
+        &crypto::hash(PREIMAGE),
+        timeout_height,
+        0,
+        &alice.1,
+    );
+    let tx_early_refund = Refund::sign(&alice.0, &tx_lock.hash(), &alice.1);
+    testkit.create_block_with_transactions(txvec![tx_lock.clone(), tx_early_refund.clone()]);
+    assert_tx_status(
+        &testkit,
+        &tx_early_refund,
+        &json!({ "type": "error", "code": 10, "description": "Lock is not timed out yet" }),
+    );
+
+    testkit.create_blocks_until(timeout_height.previous());
+    let tx_claim = Claim::sign(&bob.0, &tx_lock.hash(), PREIMAGE, &bob.1);
+    let tx_refund = Refund::sign(&alice.0, &tx_lock.hash(), &alice.1);
+    testkit.create_block_with_transactions(txvec![tx_claim.clone(), tx_refund.clone()]);
+    assert_eq!(testkit.height(), timeout_height);
+    assert_tx_status(
+        &testkit,
+        &tx_claim,
+        &json!({ "type": "error", "code": 9, "description": "Lock is timed out" }),
+    );
+    assert_tx_status(&testkit, &tx_refund, &json!({ "type": "success" }));
+
+    assert_eq!(balance(&testkit, &alice.0), 100);
+    assert_eq!(balance(&testkit, &bob.0), 100);
+    assert!(get_lock(&testkit, tx_lock.hash()).is_none());
+}
+
+/// Check that locks with a timeout in the past are rejected.
+#[test]
+fn test_lock_with_invalid_timeout() {
+    let (mut testkit, alice, bob) = create_testkit();
+    let tx_lock = LockTransfer::sign(
+        &alice.0,
+        &bob.0,
+        10,
+        &crypto::hash(PREIMAGE),
+        Height(2),
+        0,
+        &alice.1,
+    );
+    testkit.create_block_with_transaction(tx_lock.clone());
+    assert_tx_status(
+        &testkit,
+        &tx_lock,
+        &json!({ "type": "error", "code": 11, "description": "Invalid lock timeout" }),
+    );
+    assert_eq!(balance(&testkit, &alice.0), 100);
+}
+
+/// Creates a testkit with the wallets of Alice and Bob.
+fn create_testkit() -> (TestKit, (PublicKey, SecretKey), (PublicKey, SecretKey)) {
+    let mut testkit = TestKitBuilder::validator()
+        .with_service(Service::default())
+        .create();
+    let (alice, bob) = (crypto::gen_keypair(), crypto::gen_keypair());
+    testkit.create_block_with_transactions(txvec![
+        CreateWallet::sign(ALICE_NAME, &alice.0, &alice.1),
+        CreateWallet::sign(BOB_NAME, &bob.0, &bob.1),
+    ]);
+    (testkit, alice, bob)
+}
+
+fn balance(testkit: &TestKit, pub_key: &PublicKey) -> u64 {
+    let snapshot = testkit.snapshot();
+    Schema::new(&snapshot).wallet(pub_key).unwrap().balance()
+}
+
+fn get_lock(testkit: &TestKit, lock_id: Hash) -> Option<HashLock> {
+    testkit
+        .api()
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&LockQuery { lock_id })
+        .get("v1/locks/info")
+        .unwrap()
+}
+
+fn get_wallet_locks(testkit: &TestKit, pub_key: PublicKey) -> Vec<PendingLock> {
+    testkit
+        .api()
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&WalletQuery { pub_key })
+        .get("v1/wallets/locks")
+        .unwrap()
+}
+
+/// Asserts that the transaction has a specified status.
+fn assert_tx_status(
+    testkit: &TestKit,
+    tx: &Signed<RawTransaction>,
+    expected_status: &serde_json::Value,
+) {
+    let info: serde_json::Value = testkit
+        .api()
+        .public(ApiKind::Explorer)
+        .query(&TransactionQuery::new(tx.hash()))
+        .get("v1/transactions")
+        .unwrap();
+
+    if let serde_json::Value::Object(mut info) = info {
+        let tx_status = info.remove("status").unwrap();
+        assert_eq!(tx_status, *expected_status);
+    } else {
+        panic!("Invalid transaction info format, object expected");
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests of the identity service and the transfer policy it imposes on the cryptocurrency
+//! service.
+
+extern crate exonum;
+extern crate exonum_cryptocurrency_advanced as cryptocurrency;
+#[macro_use]
+extern crate exonum_testkit;
+#[macro_use]
+extern crate serde_json;
+
+use exonum::{
+    api::node::public::explorer::TransactionQuery,
+    crypto::{self, PublicKey, SecretKey},
+    helpers::Height,
+    messages::{RawTransaction, Signed},
+};
+use exonum_testkit::{ApiKind, TestKit, TestKitBuilder};
+
+use cryptocurrency::{
+    identity::{
+        api::{AttestationInfo, AttestationQuery},
+        transactions::{Attest, Revoke},
+        IdentityConfig, IdentityService, TransferPolicy,
+    },
+    transactions::{CreateWallet, Transfer},
+    Service,
+};
+
+use constants::{ALICE_NAME, BOB_NAME};
+
+mod constants;
+
+/// Attestation level required by the transfer policy in the tests.
+const MIN_LEVEL: u8 = 2;
+
+/// Check that attestations are issued only by authorized attestors.
+#[test]
+fn test_attest() {
+    let (mut testkit, attestor) = create_testkit(None);
+    let (alice, _) = crypto::gen_keypair();
+    let (mallory_pk, mallory_sk) = crypto::gen_keypair();
+
+    let tx_attest = Attest::sign(&attestor.0, &alice, 3, Height(100), &attestor.1);
+    let tx_forged = Attest::sign(&mallory_pk, &mallory_pk, 3, Height(100), &mallory_sk);
+    testkit.create_block_with_transactions(txvec![tx_attest.clone(), tx_forged.clone()]);
+    assert_tx_status(&testkit, &tx_attest, &json!({ "type": "success" }));
+    assert_tx_status(
+        &testkit,
+        &tx_forged,
+        &json!({ "type": "error", "code": 0, "description": "Sender is not an authorized attestor" }),
+    );
+
+    let info: AttestationInfo = testkit
+        .api()
+        .public(ApiKind::Service("identity"))
+        .query(&AttestationQuery { pub_key: alice })
+        .get("v1/attestations/info")
+        .unwrap();
+    let to_attestation = info.to_attestation.check().unwrap();
+    let attestation = to_attestation
+        .entries()
+        .find(|(key, _)| **key == alice)
+        .map(|(_, attestation)| attestation.clone())
+        .unwrap();
+    assert_eq!(attestation.attestor(), &attestor.0);
+    assert_eq!(attestation.level(), 3);
+    assert_eq!(attestation.expiry(), Height(100));
+    assert!(to_attestation.missing_keys().next().is_none());
+    assert!(info
+        .to_table
+        .check()
+        .unwrap()
+        .entries()
+        .any(|(_, hash)| *hash == to_attestation.merkle_root()));
+}
+
+/// Check that transfers are not restricted if the transfer policy is disabled.
+#[test]
+fn test_transfer_without_policy() {
+    let (mut testkit, _) = create_testkit(None);
+    let (alice, bob) = create_wallets(&mut testkit);
+
+    let tx = Transfer::sign(&alice.0, &bob.0, 10, 0, &alice.1);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(&testkit, &tx, &json!({ "type": "success" }));
+}
+
+/// Check that the transfer policy requires valid attestations from both parties.
+#[test]
+fn test_transfer_with_policy() {
+    let (mut testkit, attestor) = create_testkit(Some(TransferPolicy {
+        min_level: MIN_LEVEL,
+    }));
+    let (alice, bob) = create_wallets(&mut testkit);
+
+    let tx = Transfer::sign(&alice.0, &bob.0, 10, 0, &alice.1);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(
+        &testkit,
+        &tx,
+        &json!({ "type": "error", "code": 4, "description": "Sender is not attested" }),
+    );
+
+    // Bob's attestation level is insufficient.
+    testkit.create_block_with_transactions(txvec![
+        Attest::sign(&attestor.0, &alice.0, MIN_LEVEL, Height(100), &attestor.1),
+        Attest::sign(&attestor.0, &bob.0, MIN_LEVEL - 1, Height(100), &attestor.1),
+    ]);
+    let tx = Transfer::sign(&alice.0, &bob.0, 10, 1, &alice.1);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(
+        &testkit,
+        &tx,
+        &json!({ "type": "error", "code": 5, "description": "Receiver is not attested" }),
+    );
+
+    testkit.create_block_with_transaction(Attest::sign(
+        &attestor.0,
+        &bob.0,
+        MIN_LEVEL,
+        Height(100),
+        &attestor.1,
+    ));
+    let tx = Transfer::sign(&alice.0, &bob.0, 10, 2, &alice.1);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(&testkit, &tx, &json!({ "type": "success" }));
+
+    // Revoked attestations are no longer accepted.
+    let tx_revoke = Revoke::sign(&attestor.0, &alice.0, 0, &attestor.1);
+    testkit.create_block_with_transaction(tx_revoke.clone());
+    assert_tx_status(&testkit, &tx_revoke, &json!({ "type": "success" }));
+    let tx = Transfer::sign(&bob.0, &alice.0, 10, 0, &bob.1);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(
+        &testkit,
+        &tx,
+        &json!({ "type": "error", "code": 5, "description": "Receiver is not attested" }),
+    );
+}
+
+/// Check that expired attestations are not accepted by the transfer policy.
+#[test]
+fn test_transfer_with_expired_attestation() {
+    let (mut testkit, attestor) = create_testkit(Some(TransferPolicy {
+        min_level: MIN_LEVEL,
+    }));
+    let (alice, bob) = create_wallets(&mut testkit);
+
+    // The wallets are created at height 1, so the next block has height 2.
+    let expiry = Height(4);
+    testkit.create_block_with_transactions(txvec![
+        Attest::sign(&attestor.0, &alice.0, MIN_LEVEL, expiry, &attestor.1),
+        Attest::sign(&attestor.0, &bob.0, MIN_LEVEL, Height(100), &attestor.1),
+    ]);
+
+    let tx = Transfer::sign(&alice.0, &bob.0, 10, 0, &alice.1);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_eq!(testkit.height(), Height(3));
+    assert_tx_status(&testkit, &tx, &json!({ "type": "success" }));
+
+    let tx = Transfer::sign(&alice.0, &bob.0, 10, 1, &alice.1);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_eq!(testkit.height(), expiry);
+    assert_tx_status(
+        &testkit,
+        &tx,
+        &json!({ "type": "error", "code": 4, "description": "Sender is not attested" }),
+    );
+}
+
+/// Creates a testkit with the cryptocurrency and identity services. Returns the testkit
+/// together with the keypair of the only authorized attestor.
+fn create_testkit(transfer_policy: Option<TransferPolicy>) -> (TestKit, (PublicKey, SecretKey)) {
+    let attestor = crypto::gen_keypair();
+    let config = IdentityConfig {
+        attestors: vec![attestor.0],
+        transfer_policy,
+    };
+    let testkit = TestKitBuilder::validator()
+        .with_service(Service::default())
+        .with_service(IdentityService::new(config))
+        .create();
+    (testkit, attestor)
+}
+
+/// Creates wallets for Alice and Bob in a new block.
+fn create_wallets(testkit: &mut TestKit) -> ((PublicKey, SecretKey), (PublicKey, SecretKey)) {
+    let (alice, bob) = (crypto::gen_keypair(), crypto::gen_keypair());
+    testkit.create_block_with_transactions(txvec![
+        CreateWallet::sign(ALICE_NAME, &alice.0, &alice.1),
+        CreateWallet::sign(BOB_NAME, &bob.0, &bob.1),
+    ]);
+    (alice, bob)
+}
+
+/// Asserts that the transaction has a specified status.
+fn assert_tx_status(
+    testkit: &TestKit,
+    tx: &Signed<RawTransaction>,
+    expected_status: &serde_json::Value,
+) {
+    let info: serde_json::Value = testkit
+        .api()
+        .public(ApiKind::Explorer)
+        .query(&TransactionQuery::new(tx.hash()))
+        .get("v1/transactions")
+        .unwrap();
+
+    if let serde_json::Value::Object(mut info) = info {
+        let tx_status = info.remove("status").unwrap();
+        assert_eq!(tx_status, *expected_status);
+    } else {
+        panic!("Invalid transaction info format, object expected");
+    }
+}
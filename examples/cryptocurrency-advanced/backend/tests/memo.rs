@@ -0,0 +1,153 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests of transfers with encrypted memos.
+
+extern crate exonum;
+extern crate exonum_cryptocurrency_advanced as cryptocurrency;
+#[macro_use]
+extern crate exonum_testkit;
+#[macro_use]
+extern crate serde_json;
+
+use exonum::{
+    api::node::public::explorer::TransactionQuery,
+    crypto::{self, Hash, PublicKey, SecretKey},
+    messages::{RawTransaction, Signed},
+};
+use exonum_testkit::{ApiKind, TestKit, TestKitBuilder};
+
+use cryptocurrency::{
+    api::{MemoQuery, TransferMemo},
+    memo::{SealedMemo, MAX_MEMO_RECIPIENTS, MAX_MEMO_SIZE},
+    transactions::{CreateWallet, TransferWithMemo},
+    Schema, Service,
+};
+
+use constants::{ALICE_NAME, BOB_NAME};
+
+mod constants;
+
+const MEMO: &[u8] = b"Invoice #42";
+
+/// Check that the memo is stored encrypted and is disclosed only to the selected parties.
+#[test]
+fn test_transfer_with_memo() {
+    let (mut testkit, alice, bob) = create_testkit();
+    let auditor = crypto::gen_keypair();
+    let memos = vec![
+        SealedMemo::seal(&bob.0, MEMO),
+        SealedMemo::seal(&alice.0, MEMO),
+    ];
+    let tx = TransferWithMemo::sign(&alice.0, &bob.0, 10, 0, memos, &alice.1);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(&testkit, &tx, &json!({ "type": "success" }));
+    assert_eq!(balance(&testkit, &alice.0), 90);
+    assert_eq!(balance(&testkit, &bob.0), 110);
+
+    // The plaintext is not stored on the blockchain.
+    let tx_bytes = tx.clone().serialize();
+    assert!(!tx_bytes.windows(MEMO.len()).any(|window| window == MEMO));
+
+    for party in &[&alice, &bob] {
+        let memo = get_memo(&testkit, tx.hash(), &party.0).unwrap();
+        assert_eq!(memo.to, bob.0);
+        assert_eq!(memo.amount, 10);
+        assert_eq!(memo.memo.open(&party.1).unwrap(), MEMO);
+    }
+    assert!(get_memo(&testkit, tx.hash(), &auditor.0).is_none());
+
+    // The copy of the memo can be decrypted only by its party.
+    let memo = get_memo(&testkit, tx.hash(), &bob.0).unwrap();
+    assert!(memo.memo.open(&alice.1).is_none());
+}
+
+/// Check that transfers with invalid memos are rejected.
+#[test]
+fn test_transfer_with_invalid_memo() {
+    let (mut testkit, alice, bob) = create_testkit();
+    let duplicate = vec![
+        SealedMemo::seal(&bob.0, MEMO),
+        SealedMemo::seal(&bob.0, MEMO),
+    ];
+    let too_many = (0..MAX_MEMO_RECIPIENTS + 1)
+        .map(|_| SealedMemo::seal(&crypto::gen_keypair().0, MEMO))
+        .collect();
+    let oversized = vec![SealedMemo::seal(&bob.0, &[0; MAX_MEMO_SIZE + 1])];
+
+    let txs = vec![
+        TransferWithMemo::sign(&alice.0, &bob.0, 10, 0, duplicate, &alice.1),
+        TransferWithMemo::sign(&alice.0, &bob.0, 10, 1, too_many, &alice.1),
+        TransferWithMemo::sign(&alice.0, &bob.0, 10, 2, oversized, &alice.1),
+    ];
+    testkit.create_block_with_transactions(txs.clone());
+    for tx in &txs {
+        assert_tx_status(
+            &testkit,
+            tx,
+            &json!({ "type": "error", "code": 21, "description": "Invalid memo" }),
+        );
+    }
+    assert_eq!(balance(&testkit, &alice.0), 100);
+}
+
+/// Creates a testkit with the wallets of Alice and Bob.
+fn create_testkit() -> (TestKit, (PublicKey, SecretKey), (PublicKey, SecretKey)) {
+    let mut testkit = TestKitBuilder::validator()
+        .with_service(Service::default())
+        .create();
+    let (alice, bob) = (crypto::gen_keypair(), crypto::gen_keypair());
+    testkit.create_block_with_transactions(txvec![
+        CreateWallet::sign(ALICE_NAME, &alice.0, &alice.1),
+        CreateWallet::sign(BOB_NAME, &bob.0, &bob.1),
+    ]);
+    (testkit, alice, bob)
+}
+
+fn balance(testkit: &TestKit, pub_key: &PublicKey) -> u64 {
+    let snapshot = testkit.snapshot();
+    Schema::new(&snapshot).wallet(pub_key).unwrap().balance()
+}
+
+fn get_memo(testkit: &TestKit, tx_hash: Hash, pub_key: &PublicKey) -> Option<TransferMemo> {
+    testkit
+        .api()
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&MemoQuery {
+            tx_hash,
+            pub_key: *pub_key,
+        }).get("v1/transfers/memo")
+        .ok()
+}
+
+/// Asserts that the transaction has a specified status.
+fn assert_tx_status(
+    testkit: &TestKit,
+    tx: &Signed<RawTransaction>,
+    expected_status: &serde_json::Value,
+) {
+    let info: serde_json::Value = testkit
+        .api()
+        .public(ApiKind::Explorer)
+        .query(&TransactionQuery::new(tx.hash()))
+        .get("v1/transactions")
+        .unwrap();
+
+    if let serde_json::Value::Object(mut info) = info {
+        let tx_status = info.remove("status").unwrap();
+        assert_eq!(tx_status, *expected_status);
+    } else {
+        panic!("Invalid transaction info format, object expected");
+    }
+}
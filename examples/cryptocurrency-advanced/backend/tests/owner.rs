@@ -0,0 +1,169 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests of wallets owned by services.
+
+extern crate exonum;
+extern crate exonum_cryptocurrency_advanced as cryptocurrency;
+#[macro_use]
+extern crate exonum_testkit;
+#[macro_use]
+extern crate serde_json;
+
+use exonum::{
+    api::node::public::explorer::TransactionQuery,
+    blockchain::{ExecutionError, ExecutionResult},
+    crypto::{self, PublicKey, SecretKey},
+    messages::{RawTransaction, Signed},
+    storage::Fork,
+};
+use exonum_testkit::{ApiKind, TestKit, TestKitBuilder};
+
+use cryptocurrency::{
+    owner::WalletOwner,
+    transactions::{CreateWallet, SetWalletOwner, Transfer},
+    Schema, Service,
+};
+
+use constants::{ALICE_NAME, BOB_NAME};
+
+mod constants;
+
+const CUSTODIAN_SERVICE_ID: u16 = 1000;
+const TRANSFER_LIMIT: u64 = 20;
+
+/// Custodian allowing transfers which do not exceed the limit.
+#[derive(Debug)]
+struct Custodian;
+
+impl WalletOwner for Custodian {
+    fn service_id(&self) -> u16 {
+        CUSTODIAN_SERVICE_ID
+    }
+
+    fn authorize_transfer(
+        &self,
+        _fork: &Fork,
+        _from: &PublicKey,
+        _to: &PublicKey,
+        amount: u64,
+    ) -> ExecutionResult {
+        if amount > TRANSFER_LIMIT {
+            Err(ExecutionError::with_description(
+                0,
+                "Transfer limit exceeded",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Check that transfers from the owned wallet are authorized by the owning service.
+#[test]
+fn test_owned_wallet_transfers() {
+    let (mut testkit, alice, bob) = create_testkit();
+    let tx = SetWalletOwner::sign(&alice.0, CUSTODIAN_SERVICE_ID, &alice.1);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(&testkit, &tx, &json!({ "type": "success" }));
+    assert_eq!(
+        Schema::new(testkit.snapshot()).wallet_owner(&alice.0),
+        Some(CUSTODIAN_SERVICE_ID)
+    );
+
+    let tx_allowed = Transfer::sign(&alice.0, &bob.0, TRANSFER_LIMIT, 0, &alice.1);
+    let tx_denied = Transfer::sign(&alice.0, &bob.0, TRANSFER_LIMIT + 1, 1, &alice.1);
+    // Transfers to the owned wallet are not restricted.
+    let tx_incoming = Transfer::sign(&bob.0, &alice.0, 50, 0, &bob.1);
+    testkit.create_block_with_transactions(txvec![
+        tx_allowed.clone(),
+        tx_denied.clone(),
+        tx_incoming.clone(),
+    ]);
+    assert_tx_status(&testkit, &tx_allowed, &json!({ "type": "success" }));
+    assert_tx_status(
+        &testkit,
+        &tx_denied,
+        &json!({ "type": "error", "code": 0, "description": "Transfer limit exceeded" }),
+    );
+    assert_tx_status(&testkit, &tx_incoming, &json!({ "type": "success" }));
+    assert_eq!(balance(&testkit, &alice.0), 130);
+    assert_eq!(balance(&testkit, &bob.0), 70);
+
+    // The ownership cannot be changed with the wallet key.
+    let tx = SetWalletOwner::sign(&alice.0, 0, &alice.1);
+    testkit.create_block_with_transaction(tx.clone());
+    assert_tx_status(
+        &testkit,
+        &tx,
+        &json!({ "type": "error", "code": 15, "description": "Wallet is already owned by a service" }),
+    );
+}
+
+/// Check that transfers from the wallets owned by unknown services are rejected.
+#[test]
+fn test_unknown_wallet_owner() {
+    let (mut testkit, alice, bob) = create_testkit();
+    let tx_owner = SetWalletOwner::sign(&alice.0, CUSTODIAN_SERVICE_ID + 1, &alice.1);
+    let tx_transfer = Transfer::sign(&alice.0, &bob.0, 10, 0, &alice.1);
+    testkit.create_block_with_transactions(txvec![tx_owner.clone(), tx_transfer.clone()]);
+    assert_tx_status(&testkit, &tx_owner, &json!({ "type": "success" }));
+    assert_tx_status(
+        &testkit,
+        &tx_transfer,
+        &json!({ "type": "error", "code": 16, "description": "Unknown wallet owner" }),
+    );
+    assert_eq!(balance(&testkit, &alice.0), 100);
+}
+
+/// Creates a testkit with the custodian registered and the wallets of Alice and Bob.
+fn create_testkit() -> (TestKit, (PublicKey, SecretKey), (PublicKey, SecretKey)) {
+    let mut testkit = TestKitBuilder::validator()
+        .with_service(Service::default().with_wallet_owner(Custodian))
+        .create();
+    let (alice, bob) = (crypto::gen_keypair(), crypto::gen_keypair());
+    testkit.create_block_with_transactions(txvec![
+        CreateWallet::sign(ALICE_NAME, &alice.0, &alice.1),
+        CreateWallet::sign(BOB_NAME, &bob.0, &bob.1),
+    ]);
+    (testkit, alice, bob)
+}
+
+fn balance(testkit: &TestKit, pub_key: &PublicKey) -> u64 {
+    Schema::new(testkit.snapshot())
+        .wallet(pub_key)
+        .unwrap()
+        .balance()
+}
+
+/// Asserts that the transaction has a specified status.
+fn assert_tx_status(
+    testkit: &TestKit,
+    tx: &Signed<RawTransaction>,
+    expected_status: &serde_json::Value,
+) {
+    let info: serde_json::Value = testkit
+        .api()
+        .public(ApiKind::Explorer)
+        .query(&TransactionQuery::new(tx.hash()))
+        .get("v1/transactions")
+        .unwrap();
+
+    if let serde_json::Value::Object(mut info) = info {
+        let tx_status = info.remove("status").unwrap();
+        assert_eq!(tx_status, *expected_status);
+    } else {
+        panic!("Invalid transaction info format, object expected");
+    }
+}
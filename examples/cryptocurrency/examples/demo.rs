@@ -17,7 +17,9 @@ extern crate exonum_cryptocurrency as cryptocurrency;
 
 use exonum::blockchain::{GenesisConfig, ValidatorKeys};
 use exonum::node::{Node, NodeApiConfig, NodeConfig};
-use exonum::storage::MemoryDB;
+use exonum::storage::{self, Database, DbOptions, MemoryDB};
+
+use std::env;
 
 use cryptocurrency::service::CurrencyService;
 
@@ -53,16 +55,45 @@ fn node_config() -> NodeConfig {
         mempool: Default::default(),
         services_configs: Default::default(),
         database: Default::default(),
-        thread_pool_size: Default::default(),
+        threads: Default::default(),
+        plugins: Default::default(),
+        propose_precheck: false,
+        compaction: None,
+        status_file: None,
+        consensus_archive: None,
+        ping_interval: None,
+        max_clock_skew: None,
+        propose_ahead: false,
+        cold_storage: None,
+        memory_db: None,
+        pruning: None,
+        alerting: None,
+        paranoid: false,
+        replica: None,
+        forensics: None,
+    }
+}
+
+/// Opens the persistent database if its path is passed as the first argument,
+/// or creates an in-memory database otherwise.
+fn database() -> Box<dyn Database> {
+    match env::args().nth(1) {
+        Some(path) => {
+            println!("Opening database at {}...", path);
+            storage::open_database(&path, &DbOptions::default()).unwrap()
+        }
+        None => {
+            println!("Creating in-memory database...");
+            Box::new(MemoryDB::new())
+        }
     }
 }
 
 fn main() {
     exonum::helpers::init_logger().unwrap();
 
-    println!("Creating in-memory database...");
     let node = Node::new(
-        MemoryDB::new(),
+        database(),
         vec![Box::new(CurrencyService)],
         node_config(),
         None,
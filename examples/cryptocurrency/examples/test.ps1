@@ -123,9 +123,9 @@ function Main () {
   $resp = (Invoke-WebRequest "$BASE_URL/wallets").Content | ConvertFrom-Json;
   # Wallet records in the response are deterministically ordered by increasing
   # public key. As Alice's pubkey is lexicographically lesser than Bob's, it is possible to
-  # determine his wallet as .[0] and hers as .[1].
-  Check-Wallet $resp[0] 'Alice' '85';
-  Check-Wallet $resp[1] 'Bob' '115';
+  # determine his wallet as .items[0] and hers as .items[1].
+  Check-Wallet $resp.items[0] 'Alice' '85';
+  Check-Wallet $resp.items[1] 'Bob' '115';
 
   echo "Retrieving info on Alice's wallet...";
   $pubkey = '6ce29b2d3ecadc434107ce52c287001c968a1b6eca3e5a1eb62a2419e2924b85';
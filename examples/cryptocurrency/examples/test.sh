@@ -140,9 +140,9 @@ echo "Retrieving info on all wallets..."
 RESP=`curl $BASE_URL/wallets 2>/dev/null`
 # Wallet records in the response are deterministically ordered by increasing
 # public key. As Alice's pubkey is lexicographically lesser than Bob's, it it possible to
-# determine his wallet as .[0] and hers as .[1].
-check-request "Alice" 85 "`echo $RESP | jq .[0]`"
-check-request "Bob" 115 "`echo $RESP | jq .[1]`"
+# determine his wallet as .items[0] and hers as .items[1].
+check-request "Alice" 85 "`echo $RESP | jq .items[0]`"
+check-request "Bob" 115 "`echo $RESP | jq .items[1]`"
 
 echo "Retrieving info on Alice's wallet..."
 RESP=`curl $BASE_URL/wallet?pub_key=6ce29b2d3ecadc434107ce52c287001c968a1b6eca3e5a1eb62a2419e2924b85 2>/dev/null`
@@ -42,8 +42,8 @@ extern crate serde_json;
 /// Persistent data.
 pub mod schema {
     use exonum::{
-        crypto::PublicKey,
-        storage::{Fork, MapIndex, Snapshot},
+        crypto::{Hash, PublicKey},
+        storage::{Fork, ProofMapIndex, Snapshot},
     };
 
     // Declare the data to be stored in the blockchain, namely wallets with balances.
@@ -85,10 +85,10 @@ pub mod schema {
         view: T,
     }
 
-    /// Declare the layout of data managed by the service. An instance of [`MapIndex`] is used
+    /// Declare the layout of data managed by the service. An instance of [`ProofMapIndex`] is used
     /// to keep wallets in the storage. Index values are serialized [`Wallet`] structs.
     ///
-    /// [`MapIndex`]: https://exonum.com/doc/architecture/storage#mapindex
+    /// [`ProofMapIndex`]: https://exonum.com/doc/architecture/storage#proofmapindex
     /// [`Wallet`]: struct.Wallet.html
     impl<T: AsRef<dyn Snapshot>> CurrencySchema<T> {
         /// Creates a new schema instance.
@@ -97,22 +97,28 @@ pub mod schema {
         }
 
         /// Returns an immutable version of the wallets table.
-        pub fn wallets(&self) -> MapIndex<&dyn Snapshot, PublicKey, Wallet> {
-            MapIndex::new("cryptocurrency.wallets", self.view.as_ref())
+        pub fn wallets(&self) -> ProofMapIndex<&dyn Snapshot, PublicKey, Wallet> {
+            ProofMapIndex::new("cryptocurrency.wallets", self.view.as_ref())
         }
 
         /// Gets a specific wallet from the storage.
         pub fn wallet(&self, pub_key: &PublicKey) -> Option<Wallet> {
             self.wallets().get(pub_key)
         }
+
+        /// Returns the state hash of the service, which consists of the root hash
+        /// of the wallets table.
+        pub fn state_hash(&self) -> Vec<Hash> {
+            vec![self.wallets().merkle_root()]
+        }
     }
 
     /// A mutable version of the schema with an additional method to persist wallets
     /// to the storage.
     impl<'a> CurrencySchema<&'a mut Fork> {
         /// Returns a mutable version of the wallets table.
-        pub fn wallets_mut(&mut self) -> MapIndex<&mut Fork, PublicKey, Wallet> {
-            MapIndex::new("cryptocurrency.wallets", &mut self.view)
+        pub fn wallets_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, Wallet> {
+            ProofMapIndex::new("cryptocurrency.wallets", &mut self.view)
         }
     }
 }
@@ -301,11 +307,23 @@ pub mod contracts {
 /// REST API.
 pub mod api {
     use exonum::{
-        api::{self, ServiceApiBuilder, ServiceApiState},
+        api::{
+            self,
+            pagination::{self, KeyPageQuery, Page},
+            ServiceApiBuilder, ServiceApiState,
+        },
+        blockchain::{BlockProof, Schema},
         crypto::{Hash, PublicKey},
+        encoding::serialize::{FromHex, ToHex},
+        helpers::Height,
+        storage::MapProof,
     };
 
     use schema::{CurrencySchema, Wallet};
+    use service::SERVICE_ID;
+
+    /// Maximum number of wallets returned per request.
+    pub const MAX_WALLETS_PER_REQUEST: usize = 1000;
 
     /// Public service API description.
     #[derive(Debug, Clone)]
@@ -318,6 +336,37 @@ pub mod api {
         pub pub_key: PublicKey,
     }
 
+    /// The structure describes the query parameters for the `get_wallets_proof` endpoint.
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    pub struct WalletsQuery {
+        /// Comma-separated hex-encoded public keys of the queried wallets.
+        pub pub_keys: String,
+    }
+
+    impl WalletsQuery {
+        /// Creates a query for the wallets with the specified public keys.
+        pub fn new<'a, I>(pub_keys: I) -> Self
+        where
+            I: IntoIterator<Item = &'a PublicKey>,
+        {
+            let pub_keys: Vec<_> = pub_keys.into_iter().map(|key| key.to_hex()).collect();
+            WalletsQuery {
+                pub_keys: pub_keys.join(","),
+            }
+        }
+
+        /// Parses the public keys from the query.
+        pub fn pub_keys(&self) -> api::Result<Vec<PublicKey>> {
+            self.pub_keys
+                .split(',')
+                .map(|key| {
+                    PublicKey::from_hex(key.trim()).map_err(|e| {
+                        api::Error::BadRequest(format!("Invalid public key {:?}: {}", key, e))
+                    })
+                }).collect()
+        }
+    }
+
     /// The structure returned by the REST API.
     #[derive(Debug, Serialize, Deserialize)]
     pub struct TransactionResponse {
@@ -325,6 +374,19 @@ pub mod api {
         pub tx_hash: Hash,
     }
 
+    /// Proof of existence (or absence) of specific wallets, which can be checked
+    /// against the block hash.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct WalletProof {
+        /// Proof of the latest committed block.
+        pub block_proof: BlockProof,
+        /// Proof of the wallets table in the state hash of the block.
+        pub to_table: MapProof<Hash, Hash>,
+        /// Proof of the wallets in the wallets table. The internal nodes of the table
+        /// are shared among the proofs of individual wallets.
+        pub to_wallet: MapProof<PublicKey, Wallet>,
+    }
+
     impl CryptocurrencyApi {
         /// Endpoint for getting a single wallet.
         pub fn get_wallet(state: &ServiceApiState, query: WalletQuery) -> api::Result<Wallet> {
@@ -335,13 +397,59 @@ pub mod api {
                 .ok_or_else(|| api::Error::NotFound("\"Wallet not found\"".to_owned()))
         }
 
-        /// Endpoint for dumping all wallets from the storage.
-        pub fn get_wallets(state: &ServiceApiState, _query: ()) -> api::Result<Vec<Wallet>> {
+        /// Endpoint for getting a single wallet together with a proof tying it
+        /// to the latest committed block.
+        pub fn get_wallet_proof(
+            state: &ServiceApiState,
+            query: WalletQuery,
+        ) -> api::Result<WalletProof> {
+            Ok(Self::wallets_proof(state, vec![query.pub_key]))
+        }
+
+        /// Endpoint for getting several wallets together with a single combined proof
+        /// tying them to the latest committed block.
+        pub fn get_wallets_proof(
+            state: &ServiceApiState,
+            query: WalletsQuery,
+        ) -> api::Result<WalletProof> {
+            let pub_keys = query.pub_keys()?;
+            pagination::check_limit(pub_keys.len(), MAX_WALLETS_PER_REQUEST)?;
+            Ok(Self::wallets_proof(state, pub_keys))
+        }
+
+        fn wallets_proof(state: &ServiceApiState, pub_keys: Vec<PublicKey>) -> WalletProof {
+            let snapshot = state.snapshot();
+            let general_schema = Schema::new(&snapshot);
+            let currency_schema = CurrencySchema::new(&snapshot);
+
+            let max_height = general_schema.block_hashes_by_height().len() - 1;
+            let block_proof = general_schema
+                .block_and_precommits(Height(max_height))
+                .unwrap();
+            let to_table = general_schema.get_proof_to_service_table(SERVICE_ID, 0);
+            let to_wallet = currency_schema.wallets().get_multiproof(pub_keys);
+
+            WalletProof {
+                block_proof,
+                to_table,
+                to_wallet,
+            }
+        }
+
+        /// Endpoint for listing wallets from the storage. Wallets are ordered
+        /// by their public keys and returned page by page.
+        pub fn get_wallets(
+            state: &ServiceApiState,
+            query: KeyPageQuery<PublicKey>,
+        ) -> api::Result<Page<Wallet, PublicKey>> {
             let snapshot = state.snapshot();
             let schema = CurrencySchema::new(snapshot);
             let idx = schema.wallets();
-            let wallets = idx.values().collect();
-            Ok(wallets)
+            let wallets = match query.from {
+                Some(ref from) => idx.iter_from(from),
+                None => idx.iter(),
+            };
+            query.paginate(MAX_WALLETS_PER_REQUEST, wallets)
         }
 
         /// 'ServiceApiBuilder' facilitates conversion between transactions/read requests and REST
@@ -352,7 +460,9 @@ pub mod api {
             builder
                 .public_scope()
                 .endpoint("v1/wallet", Self::get_wallet)
-                .endpoint("v1/wallets", Self::get_wallets);
+                .endpoint("v1/wallet/proof", Self::get_wallet_proof)
+                .endpoint("v1/wallets", Self::get_wallets)
+                .endpoint("v1/wallets/proof", Self::get_wallets_proof);
         }
     }
 }
@@ -369,6 +479,7 @@ pub mod service {
     };
 
     use api::CryptocurrencyApi;
+    use schema::CurrencySchema;
     use transactions::CurrencyTransactions;
 
     /// Service ID for the `Service` trait.
@@ -390,11 +501,32 @@ pub mod service {
     /// If a wallet with the specified pubkey is not in the storage, returns a string
     /// `"Wallet not found"` with the HTTP 404 status.
     ///
-    /// ## Dump wallets
+    /// ## Retrieve wallet proof
+    ///
+    /// GET `v1/wallet/proof?pub_key={hash}`
+    ///
+    /// Returns a [`WalletProof`] of presence or absence of a wallet with the specified
+    /// public key. The proof consists of the latest committed block with its precommits,
+    /// a proof of the wallets table root in the block state hash, and a proof of the wallet
+    /// in the wallets table. Thus, a client may verify the wallet balance against
+    /// the block hash without trusting the node.
+    ///
+    /// ## Retrieve proof for several wallets
+    ///
+    /// GET `v1/wallets/proof?pub_keys={hash},{hash},...`
+    ///
+    /// Returns a [`WalletProof`] of presence or absence of each wallet with the specified
+    /// public keys. Unlike separate proofs for each wallet, the combined proof shares
+    /// the common nodes of the wallets table, and the block is included only once.
+    ///
+    /// ## List wallets
     ///
-    /// GET `v1/wallets`
+    /// GET `v1/wallets?from={pub_key}&limit={count}`
     ///
-    /// Returns an array of all wallets in the storage.
+    /// Returns wallets in the storage ordered by their public keys. All query parameters
+    /// are optional; see [`KeyPageQuery`] for their meaning. The response has the form
+    /// `{ "items": [<wallet>, ...], "next_cursor": <pub_key> }`; pass `next_cursor`
+    /// as the `from` parameter to get the following page.
     ///
     /// ## Create new wallet
     ///
@@ -410,6 +542,8 @@ pub mod service {
     /// Accepts a [`TxTransfer`] transaction from an external client. Returns the hex-encoded
     /// hash of the transaction encumbered in an object: `{ "tx_hash": <hash> }`.
     ///
+    /// [`KeyPageQuery`]: https://docs.rs/exonum/0.9.0/exonum/api/pagination/struct.KeyPageQuery.html
+    /// [`WalletProof`]: ../api/struct.WalletProof.html
     /// [`TxCreateWallet`]: ../transactions/struct.TxCreateWallet.html
     /// [`TxTransfer`]: ../transactions/struct.TxTransfer.html
     #[derive(Debug)]
@@ -434,12 +568,12 @@ pub mod service {
         }
 
         // Hashes for the service tables that will be included into the state hash.
-        // To simplify things, we don't have [Merkelized tables][merkle] in the service storage
-        // for now, so we return an empty vector.
+        // The wallets table is a [Merkelized table][merkle], so its root hash is aggregated
+        // into the block state hash and wallets can be proven to light clients.
         //
         // [merkle]: https://exonum.com/doc/architecture/storage/#merklized-indices
-        fn state_hash(&self, _: &dyn Snapshot) -> Vec<Hash> {
-            vec![]
+        fn state_hash(&self, snapshot: &dyn Snapshot) -> Vec<Hash> {
+            CurrencySchema::new(snapshot).state_hash()
         }
 
         // Links the service api implementation to the Exonum.
@@ -27,16 +27,21 @@ extern crate exonum_testkit;
 extern crate serde_json;
 
 use exonum::{
-    api::{self, node::public::explorer::TransactionQuery},
+    api::{
+        self,
+        node::public::explorer::TransactionQuery,
+        pagination::{KeyPageQuery, Page},
+    },
+    blockchain::Blockchain,
     crypto::{self, Hash, PublicKey, SecretKey},
     messages::{self, RawTransaction, Signed},
 };
 use exonum_testkit::{ApiKind, TestKit, TestKitApi, TestKitBuilder};
 
 // Import data types used in tests from the crate where the service is defined.
-use cryptocurrency::api::WalletQuery;
+use cryptocurrency::api::{WalletProof, WalletQuery, WalletsQuery};
 use cryptocurrency::schema::Wallet;
-use cryptocurrency::service::CurrencyService;
+use cryptocurrency::service::{CurrencyService, SERVICE_ID};
 use cryptocurrency::transactions::{TxCreateWallet, TxTransfer};
 
 // Imports shared test constants.
@@ -216,6 +221,130 @@ fn test_unknown_wallet_request() {
     );
 }
 
+/// Check that wallet proofs can be verified against the latest block.
+#[test]
+fn test_wallet_proof() {
+    let (mut testkit, api) = create_testkit();
+    let (tx_alice, _) = api.create_wallet(ALICE_NAME);
+    testkit.create_block();
+
+    // Proof of presence.
+    let proof = api.get_wallet_proof(tx_alice.author());
+    let block = proof.block_proof.block;
+    let to_table = proof.to_table.check().unwrap();
+    assert_eq!(to_table.merkle_root(), *block.state_hash());
+    let table_key = Blockchain::service_table_unique_key(SERVICE_ID, 0);
+    let table_root = to_table
+        .entries()
+        .find(|(key, _)| **key == table_key)
+        .map(|(_, hash)| *hash)
+        .unwrap();
+
+    let to_wallet = proof.to_wallet.check().unwrap();
+    assert_eq!(to_wallet.merkle_root(), table_root);
+    let wallet = to_wallet
+        .entries()
+        .find(|(key, _)| **key == tx_alice.author())
+        .map(|(_, wallet)| wallet.clone())
+        .unwrap();
+    assert_eq!(wallet.balance(), 100);
+
+    // Proof of absence.
+    let (pub_key, _) = crypto::gen_keypair();
+    let proof = api.get_wallet_proof(pub_key);
+    let to_wallet = proof.to_wallet.check().unwrap();
+    assert_eq!(to_wallet.merkle_root(), table_root);
+    assert_eq!(to_wallet.missing_keys().collect::<Vec<_>>(), vec![&pub_key]);
+}
+
+/// Check that a single proof for several wallets can be retrieved and verified.
+#[test]
+fn test_wallets_proof() {
+    let (mut testkit, api) = create_testkit();
+    let (tx_alice, _) = api.create_wallet(ALICE_NAME);
+    let (tx_bob, _) = api.create_wallet(BOB_NAME);
+    testkit.create_block();
+
+    let (missing_key, _) = crypto::gen_keypair();
+    let pub_keys = [tx_alice.author(), tx_bob.author(), missing_key];
+    let proof = api.get_wallets_proof(&pub_keys);
+    let block = proof.block_proof.block;
+    let to_table = proof.to_table.check().unwrap();
+    assert_eq!(to_table.merkle_root(), *block.state_hash());
+    let table_key = Blockchain::service_table_unique_key(SERVICE_ID, 0);
+    let table_root = to_table
+        .entries()
+        .find(|(key, _)| **key == table_key)
+        .map(|(_, hash)| *hash)
+        .unwrap();
+
+    let to_wallets = proof.to_wallet.check().unwrap();
+    assert_eq!(to_wallets.merkle_root(), table_root);
+    let mut names: Vec<_> = to_wallets
+        .entries()
+        .map(|(_, wallet)| wallet.name().to_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec![ALICE_NAME, BOB_NAME]);
+    assert_eq!(
+        to_wallets.missing_keys().collect::<Vec<_>>(),
+        vec![&missing_key]
+    );
+
+    // Malformed keys are rejected.
+    let err = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .query(&WalletsQuery {
+            pub_keys: "not-a-key".to_owned(),
+        }).get::<WalletProof>("v1/wallets/proof")
+        .unwrap_err();
+    assert_matches!(err, api::Error::BadRequest(_));
+}
+
+/// Check that wallets are listed page by page in the order of their public keys.
+#[test]
+fn test_wallets_pages() {
+    let (mut testkit, api) = create_testkit();
+    let mut pub_keys: Vec<_> = ["Alice", "Bob", "Carol"]
+        .iter()
+        .map(|name| api.create_wallet(name).0.author())
+        .collect();
+    testkit.create_block();
+    pub_keys.sort();
+
+    let page = api.get_wallets(None, Some(2));
+    let page_keys: Vec<_> = page.items.iter().map(|wallet| *wallet.pub_key()).collect();
+    assert_eq!(page_keys, &pub_keys[..2]);
+    assert_eq!(page.total, None);
+    assert_eq!(page.next_cursor, Some(pub_keys[2]));
+
+    let page = api.get_wallets(page.next_cursor, Some(2));
+    let page_keys: Vec<_> = page.items.iter().map(|wallet| *wallet.pub_key()).collect();
+    assert_eq!(page_keys, &pub_keys[2..]);
+    assert_eq!(page.next_cursor, None);
+
+    let page = api.get_wallets(None, None);
+    assert_eq!(page.items.len(), 3);
+    assert_eq!(page.next_cursor, None);
+
+    // The response is a JSON object with the wallets and the cursor to the next page.
+    let response: serde_json::Value = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .get("v1/wallets?limit=1")
+        .unwrap();
+    assert_eq!(response["items"].as_array().map(Vec::len), Some(1));
+    assert_eq!(response["next_cursor"], json!(pub_keys[1]));
+
+    let err = api
+        .inner
+        .public(ApiKind::Service("cryptocurrency"))
+        .get::<serde_json::Value>("v1/wallets?limit=0")
+        .unwrap_err();
+    assert_matches!(err, api::Error::BadRequest(_));
+}
+
 /// Wrapper for the cryptocurrency service API allowing to easily use it
 /// (compared to `TestKitApi` calls).
 struct CryptocurrencyApi {
@@ -264,6 +393,36 @@ impl CryptocurrencyApi {
             .unwrap()
     }
 
+    /// Gets the proof of a particular wallet using an HTTP request.
+    fn get_wallet_proof(&self, pub_key: PublicKey) -> WalletProof {
+        self.inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&WalletQuery { pub_key })
+            .get("v1/wallet/proof")
+            .unwrap()
+    }
+
+    /// Gets a page of wallets using an HTTP request.
+    fn get_wallets(
+        &self,
+        from: Option<PublicKey>,
+        limit: Option<usize>,
+    ) -> Page<Wallet, PublicKey> {
+        self.inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&KeyPageQuery { from, limit })
+            .get("v1/wallets")
+            .unwrap()
+    }
+
+    fn get_wallets_proof(&self, pub_keys: &[PublicKey]) -> WalletProof {
+        self.inner
+            .public(ApiKind::Service("cryptocurrency"))
+            .query(&WalletsQuery::new(pub_keys))
+            .get("v1/wallets/proof")
+            .unwrap()
+    }
+
     /// Asserts that a wallet with the specified public key is not known to the blockchain.
     fn assert_no_wallet(&self, pub_key: PublicKey) {
         let err = self
@@ -18,13 +18,15 @@ use exonum::{
     api::{self, ServiceApiBuilder, ServiceApiState},
     blockchain::{self, BlockProof},
     crypto::Hash,
-    storage::MapProof,
+    messages::{RawTransaction, Signed},
+    storage::{ListProof, MapProof},
 };
 
 use schema::{Schema, TimestampEntry};
 use TIMESTAMPING_SERVICE;
 
-/// Describes query parameters for `handle_timestamp` and `handle_timestamp_proof` endpoints.
+/// Describes query parameters for `handle_timestamp`, `handle_timestamp_proof`
+/// and `handle_registration_proof` endpoints.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct TimestampQuery {
     /// Hash of the requested timestamp.
@@ -49,6 +51,26 @@ pub struct TimestampProof {
     pub timestamp_proof: MapProof<Hash, TimestampEntry>,
 }
 
+/// Describes the information required to prove that the timestamp was registered
+/// in the blockchain at a certain height.
+///
+/// Unlike `TimestampProof`, this proof does not change as the blockchain grows, so it can be
+/// stored along with the timestamped document. To verify the proof, check that:
+///
+/// - the block is authorized by the +2/3 majority of the validators via `precommits`;
+/// - `location_proof` is valid for the `tx_hash` and `tx_count` fields of the block and
+///   contains the hash of `transaction`;
+/// - `transaction` registers the requested content hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistrationProof {
+    /// Proof of the block containing the timestamping transaction.
+    pub block_info: BlockProof,
+    /// Proof of the transaction inclusion into the block.
+    pub location_proof: ListProof<Hash>,
+    /// Timestamping transaction.
+    pub transaction: Signed<RawTransaction>,
+}
+
 /// Public service API.
 #[derive(Debug, Clone, Copy)]
 pub struct PublicApi;
@@ -86,11 +108,45 @@ impl PublicApi {
         })
     }
 
+    /// Endpoint for getting the proof of the timestamp registration at the height of the block
+    /// containing the timestamping transaction. Returns `None` if the hash is not timestamped.
+    pub fn handle_registration_proof(
+        state: &ServiceApiState,
+        query: TimestampQuery,
+    ) -> api::Result<Option<RegistrationProof>> {
+        let snapshot = state.snapshot();
+        let entry = match Schema::new(&snapshot).timestamps().get(&query.hash) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        let core_schema = blockchain::Schema::new(&snapshot);
+        let tx_hash = entry.tx_hash();
+        // Unwraps are OK here, because the entry is added only by a committed transaction.
+        let location = core_schema.transactions_locations().get(tx_hash).unwrap();
+        let block_info = core_schema
+            .block_and_precommits(location.block_height())
+            .unwrap();
+        let location_proof = core_schema
+            .block_transactions(location.block_height())
+            .get_proof(location.position_in_block());
+        let transaction = core_schema.transactions().get(tx_hash).unwrap();
+        Ok(Some(RegistrationProof {
+            block_info,
+            location_proof,
+            transaction,
+        }))
+    }
+
     /// Wires the above endpoints to public API scope of the given `ServiceApiBuilder`.
     pub fn wire(builder: &mut ServiceApiBuilder) {
         builder
             .public_scope()
             .endpoint("v1/timestamps/value", Self::handle_timestamp)
-            .endpoint("v1/timestamps/proof", Self::handle_timestamp_proof);
+            .endpoint("v1/timestamps/proof", Self::handle_timestamp_proof)
+            .endpoint(
+                "v1/timestamps/registration",
+                Self::handle_registration_proof,
+            );
     }
 }
@@ -15,6 +15,13 @@
 //! Timestamping demo. This example shows how to use Exonum framework to create a fast
 //! and secure service to prove the existence of a specific file at some moment
 //! of time using blockchain as a secure database.
+//!
+//! The service is meant to be run together with the time service, which provides
+//! the time of the timestamps, and the configuration service. A timestamp is owned by
+//! the author of the transaction, which registered it; see the [`api`] module for the ways
+//! to prove the registration of a timestamp.
+//!
+//! [`api`]: api/index.html
 
 #![deny(
     missing_debug_implementations,
@@ -34,7 +34,7 @@ use exonum_time::{time_provider::MockTimeProvider, TimeService};
 use std::time::SystemTime;
 
 use exonum_timestamping::{
-    api::TimestampQuery,
+    api::{RegistrationProof, TimestampQuery},
     schema::{Timestamp, TimestampEntry},
     transactions::TxTimestamp,
     Service,
@@ -125,6 +125,43 @@ fn test_api_get_timestamp_proof() {
     // TODO: Implement proof validation. (ECR-1639)
 }
 
+#[test]
+fn test_api_get_registration_proof() {
+    let (mut testkit, _) = init_testkit();
+
+    let keypair = gen_keypair();
+    let info = Timestamp::new(&Hash::zero(), "metadata");
+    let tx = TxTimestamp::sign(&keypair.0, info, &keypair.1);
+    testkit.create_block_with_transactions(txvec![tx.clone()]);
+    let height = testkit.height();
+    testkit.create_blocks_until(Height(10));
+
+    let api = testkit.api();
+    let proof: Option<RegistrationProof> = api
+        .public(ApiKind::Service("timestamping"))
+        .query(&TimestampQuery::new(Hash::zero()))
+        .get("v1/timestamps/registration")
+        .unwrap();
+
+    // The proof refers to the block with the timestamping transaction.
+    let proof = proof.unwrap();
+    let block = &proof.block_info.block;
+    assert_eq!(block.height(), height);
+    assert_eq!(proof.transaction, tx);
+    let tx_hashes = proof
+        .location_proof
+        .validate(*block.tx_hash(), u64::from(block.tx_count()))
+        .unwrap();
+    assert_eq!(tx_hashes, vec![(0, &tx.hash())]);
+
+    let proof: Option<RegistrationProof> = api
+        .public(ApiKind::Service("timestamping"))
+        .query(&TimestampQuery::new(hash(&[1])))
+        .get("v1/timestamps/registration")
+        .unwrap();
+    assert!(proof.is_none());
+}
+
 #[test]
 fn test_api_get_timestamp_entry() {
     let (mut testkit, _) = init_testkit();
@@ -0,0 +1,133 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! REST API.
+
+use exonum::{
+    api::{self, ServiceApiBuilder, ServiceApiState},
+    blockchain::{self, BlockProof},
+    crypto::{Hash, PublicKey},
+    storage::MapProof,
+};
+
+use schema::{Ballot, Poll, Schema};
+use VOTING_SERVICE_ID;
+
+/// Describes query parameters for the `handle_poll` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PollQuery {
+    /// Identifier of the requested poll.
+    pub poll_id: Hash,
+}
+
+impl PollQuery {
+    /// Creates new `PollQuery` with the given `poll_id`.
+    pub fn new(poll_id: Hash) -> Self {
+        PollQuery { poll_id }
+    }
+}
+
+/// Describes query parameters for the `handle_ballot_proof` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BallotQuery {
+    /// Identifier of the poll.
+    pub poll_id: Hash,
+    /// Key of the voter.
+    pub voter: PublicKey,
+}
+
+impl BallotQuery {
+    /// Creates new `BallotQuery` with the given `poll_id` and `voter`.
+    pub fn new(poll_id: Hash, voter: PublicKey) -> Self {
+        BallotQuery { poll_id, voter }
+    }
+}
+
+/// Poll together with the number of votes for each option.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollInfo {
+    /// Poll as recorded in the blockchain.
+    pub poll: Poll,
+    /// Number of votes for each option.
+    pub tally: Vec<u64>,
+}
+
+/// Describes the information required to prove that the ballot is accounted
+/// in the results of the poll.
+///
+/// The proof is verified as follows:
+///
+/// - `state_proof` links the root hash of the voting service table to the `state_hash`
+///   of the block authorized by `block_info`;
+/// - `poll_proof` links the poll to the root hash of the voting service table;
+/// - `ballot_proof` links the ballot (or its absence) to the `ballots_hash` of the poll.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BallotProof {
+    /// Proof of the last block.
+    pub block_info: BlockProof,
+    /// Proof of the voting service table in the blockchain state.
+    pub state_proof: MapProof<Hash, Hash>,
+    /// Proof of the poll in the table of polls.
+    pub poll_proof: MapProof<Hash, Poll>,
+    /// Proof of the ballot in the ballots of the poll.
+    pub ballot_proof: MapProof<PublicKey, Ballot>,
+}
+
+/// Public service API.
+#[derive(Debug, Clone, Copy)]
+pub struct PublicApi;
+
+impl PublicApi {
+    /// Endpoint for getting a single poll with its results.
+    pub fn handle_poll(state: &ServiceApiState, query: PollQuery) -> api::Result<Option<PollInfo>> {
+        let snapshot = state.snapshot();
+        let schema = Schema::new(&snapshot);
+        Ok(schema.polls().get(&query.poll_id).map(|poll| PollInfo {
+            poll,
+            tally: schema.tally(&query.poll_id).iter().collect(),
+        }))
+    }
+
+    /// Endpoint for getting the proof of a single ballot.
+    pub fn handle_ballot_proof(
+        state: &ServiceApiState,
+        query: BallotQuery,
+    ) -> api::Result<BallotProof> {
+        let snapshot = state.snapshot();
+        let (state_proof, block_info) = {
+            let core_schema = blockchain::Schema::new(&snapshot);
+            let last_block_height = state.blockchain().last_block().height();
+            let block_proof = core_schema.block_and_precommits(last_block_height).unwrap();
+            let state_proof = core_schema.get_proof_to_service_table(VOTING_SERVICE_ID, 0);
+            (state_proof, block_proof)
+        };
+        let schema = Schema::new(&snapshot);
+        let poll_proof = schema.polls().get_proof(query.poll_id);
+        let ballot_proof = schema.ballots(&query.poll_id).get_proof(query.voter);
+        Ok(BallotProof {
+            block_info,
+            state_proof,
+            poll_proof,
+            ballot_proof,
+        })
+    }
+
+    /// Wires the above endpoints to public API scope of the given `ServiceApiBuilder`.
+    pub fn wire(builder: &mut ServiceApiBuilder) {
+        builder
+            .public_scope()
+            .endpoint("v1/polls", Self::handle_poll)
+            .endpoint("v1/polls/ballot", Self::handle_ballot_proof);
+    }
+}
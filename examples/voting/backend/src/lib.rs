@@ -0,0 +1,147 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Voting demo. This example shows how to use Exonum framework to conduct polls
+//! among a set of authorized voters, so that every voter can prove that their ballot
+//! is accounted in the results.
+//!
+//! Voters cast signed ballots, one per key in each poll. Each ballot is added
+//! to the number of votes for the chosen option; the results stored in the polls
+//! are updated in `before_commit`, once per block. The set of authorized voters is a part
+//! of the global configuration of the service, so it is changed via the configuration
+//! service.
+
+#![deny(
+    missing_debug_implementations,
+    missing_docs,
+    unsafe_code,
+    bare_trait_objects
+)]
+
+#[macro_use]
+extern crate exonum;
+#[macro_use]
+extern crate failure;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+
+pub mod api;
+pub mod schema;
+pub mod transactions;
+
+use exonum::{
+    api::ServiceApiBuilder,
+    blockchain::{self, Transaction, TransactionSet},
+    crypto::{Hash, PublicKey},
+    encoding::Error as EncodingError,
+    helpers::fabric,
+    messages::RawTransaction,
+    storage::{Fork, Snapshot},
+};
+use serde_json::Value;
+
+use api::PublicApi;
+use schema::Schema;
+use transactions::VotingTransactions;
+
+/// Unique service ID.
+pub const VOTING_SERVICE_ID: u16 = 131;
+/// Name of the service.
+pub const SERVICE_NAME: &str = "voting";
+
+/// Global configuration of the voting service.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct VotingConfig {
+    /// Keys of the voters authorized to create polls and cast ballots.
+    pub voters: Vec<PublicKey>,
+}
+
+impl VotingConfig {
+    /// Returns the actual configuration of the service.
+    pub fn actual<T: AsRef<dyn Snapshot>>(snapshot: T) -> Self {
+        let config = blockchain::Schema::new(snapshot).actual_configuration();
+        config
+            .services
+            .get(SERVICE_NAME)
+            .map(|value| {
+                serde_json::from_value(value.clone()).expect("Invalid voting service config")
+            }).unwrap_or_default()
+    }
+
+    /// Checks whether the given key belongs to an authorized voter.
+    pub fn is_voter(&self, key: &PublicKey) -> bool {
+        self.voters.contains(key)
+    }
+}
+
+/// Exonum `Service` implementation.
+#[derive(Debug, Default)]
+pub struct Service {
+    config: VotingConfig,
+}
+
+impl Service {
+    /// Creates a service with the given initial configuration.
+    pub fn new(config: VotingConfig) -> Self {
+        Service { config }
+    }
+}
+
+impl blockchain::Service for Service {
+    fn service_id(&self) -> u16 {
+        VOTING_SERVICE_ID
+    }
+
+    fn service_name(&self) -> &'static str {
+        SERVICE_NAME
+    }
+
+    fn state_hash(&self, view: &dyn Snapshot) -> Vec<Hash> {
+        let schema = Schema::new(view);
+        schema.state_hash()
+    }
+
+    fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<dyn Transaction>, EncodingError> {
+        VotingTransactions::tx_from_raw(raw).map(Into::into)
+    }
+
+    fn initialize(&self, _fork: &mut Fork) -> Value {
+        serde_json::to_value(self.config.clone()).unwrap()
+    }
+
+    fn before_commit(&self, fork: &mut Fork) {
+        Schema::new(fork).update_tallies();
+    }
+
+    fn wire_api(&self, builder: &mut ServiceApiBuilder) {
+        PublicApi::wire(builder);
+    }
+}
+
+/// A voting service creator for the `NodeBuilder`. The service is created with
+/// no authorized voters; they should be added via the configuration service.
+#[derive(Debug, Clone, Copy)]
+pub struct ServiceFactory;
+
+impl fabric::ServiceFactory for ServiceFactory {
+    fn service_name(&self) -> &str {
+        SERVICE_NAME
+    }
+
+    fn make_service(&mut self, _: &fabric::Context) -> Box<dyn blockchain::Service> {
+        Box::new(Service::default())
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Voting database schema.
+
+use exonum::{
+    crypto::{Hash, PublicKey},
+    storage::{Fork, KeySetIndex, ProofListIndex, ProofMapIndex, Snapshot},
+};
+
+encoding_struct! {
+    /// Poll with the current results of the voting.
+    struct Poll {
+        /// Title of the poll.
+        title: &str,
+
+        /// Number of the options to choose from.
+        options_count: u32,
+
+        /// Number of the cast ballots.
+        ballots_count: u64,
+
+        /// Root hash of the ballots cast in the poll.
+        ballots_hash: &Hash,
+
+        /// Root hash of the number of votes for each option.
+        tally_hash: &Hash,
+    }
+}
+
+encoding_struct! {
+    /// Ballot cast by a voter.
+    struct Ballot {
+        /// Identifier of the poll.
+        poll_id: &Hash,
+
+        /// Chosen option.
+        option: u32,
+
+        /// Hash of the transaction, which has cast the ballot.
+        tx_hash: &Hash,
+    }
+}
+
+/// Voting database schema.
+#[derive(Debug)]
+pub struct Schema<T> {
+    view: T,
+}
+
+impl<T> Schema<T> {
+    /// Creates a new schema from the database view.
+    pub fn new(view: T) -> Self {
+        Schema { view }
+    }
+}
+
+impl<T> Schema<T>
+where
+    T: AsRef<dyn Snapshot>,
+{
+    /// Returns the `ProofMapIndex` of polls keyed by the hash of the transaction
+    /// which has created the poll.
+    pub fn polls(&self) -> ProofMapIndex<&T, Hash, Poll> {
+        ProofMapIndex::new("voting.polls", &self.view)
+    }
+
+    /// Returns the `ProofMapIndex` of ballots cast in the given poll keyed by the voter key.
+    pub fn ballots(&self, poll_id: &Hash) -> ProofMapIndex<&T, PublicKey, Ballot> {
+        ProofMapIndex::new_in_family("voting.ballots", poll_id, &self.view)
+    }
+
+    /// Returns the `ProofListIndex` with the number of votes for each option of the given poll.
+    pub fn tally(&self, poll_id: &Hash) -> ProofListIndex<&T, u64> {
+        ProofListIndex::new_in_family("voting.tally", poll_id, &self.view)
+    }
+
+    /// Returns the set of polls with the ballots cast in the current block.
+    pub fn pending_polls(&self) -> KeySetIndex<&T, Hash> {
+        KeySetIndex::new("voting.pending_polls", &self.view)
+    }
+
+    /// Returns the state hash of the voting service.
+    pub fn state_hash(&self) -> Vec<Hash> {
+        vec![self.polls().merkle_root()]
+    }
+}
+
+impl<'a> Schema<&'a mut Fork> {
+    /// Returns the mutable `ProofMapIndex` of polls.
+    pub fn polls_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, Poll> {
+        ProofMapIndex::new("voting.polls", &mut self.view)
+    }
+
+    /// Returns the mutable `ProofMapIndex` of ballots cast in the given poll.
+    pub fn ballots_mut(&mut self, poll_id: &Hash) -> ProofMapIndex<&mut Fork, PublicKey, Ballot> {
+        ProofMapIndex::new_in_family("voting.ballots", poll_id, &mut self.view)
+    }
+
+    /// Returns the mutable `ProofListIndex` with the number of votes for each option
+    /// of the given poll.
+    pub fn tally_mut(&mut self, poll_id: &Hash) -> ProofListIndex<&mut Fork, u64> {
+        ProofListIndex::new_in_family("voting.tally", poll_id, &mut self.view)
+    }
+
+    /// Returns the mutable set of polls with the ballots cast in the current block.
+    pub fn pending_polls_mut(&mut self) -> KeySetIndex<&mut Fork, Hash> {
+        KeySetIndex::new("voting.pending_polls", &mut self.view)
+    }
+
+    /// Creates a poll with the given identifier and no votes.
+    pub fn create_poll(&mut self, poll_id: &Hash, title: &str, options_count: u32) {
+        let tally_hash = {
+            let mut tally = self.tally_mut(poll_id);
+            tally.extend((0..options_count).map(|_| 0));
+            tally.merkle_root()
+        };
+        let ballots_hash = self.ballots(poll_id).merkle_root();
+        let poll = Poll::new(title, options_count, 0, &ballots_hash, &tally_hash);
+        self.polls_mut().put(poll_id, poll);
+    }
+
+    /// Records the ballot of the voter and adds it to the number of votes for the chosen
+    /// option. The poll itself is updated in `update_tallies`.
+    pub fn cast_ballot(&mut self, voter: &PublicKey, ballot: Ballot) {
+        let poll_id = *ballot.poll_id();
+        let option = u64::from(ballot.option());
+        self.ballots_mut(&poll_id).put(voter, ballot);
+        {
+            let mut tally = self.tally_mut(&poll_id);
+            let votes = tally.get(option).expect("Option is absent");
+            tally.set(option, votes + 1);
+        }
+        self.pending_polls_mut().insert(poll_id);
+    }
+
+    /// Updates the results stored in the polls with the ballots cast in the current block.
+    /// The cost of the update does not depend on the number of the cast ballots.
+    pub fn update_tallies(&mut self) {
+        let poll_ids = self.pending_polls().iter().collect::<Vec<_>>();
+        for poll_id in &poll_ids {
+            let poll = self.polls().get(poll_id).expect("Poll is absent");
+            let (ballots_count, tally_hash) = {
+                let tally = self.tally(poll_id);
+                (tally.iter().sum::<u64>(), tally.merkle_root())
+            };
+            let ballots_hash = self.ballots(poll_id).merkle_root();
+            let poll = Poll::new(
+                poll.title(),
+                poll.options_count(),
+                ballots_count,
+                &ballots_hash,
+                &tally_hash,
+            );
+            self.polls_mut().put(poll_id, poll);
+        }
+        self.pending_polls_mut().clear();
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Voting transactions.
+
+// Workaround for `failure` see https://github.com/rust-lang-nursery/failure/issues/223 and
+// ECR-1771 for the details.
+#![allow(bare_trait_objects)]
+// Suppress a warning in `transactions!` macro call:
+#![cfg_attr(feature = "cargo-clippy", allow(clippy::redundant_field_names))]
+
+use exonum::{
+    blockchain::{ExecutionError, ExecutionResult, Transaction, TransactionContext},
+    crypto::{Hash, PublicKey, SecretKey},
+    messages::{Message, RawTransaction, Signed},
+};
+
+use schema::{Ballot, Schema};
+use {VotingConfig, VOTING_SERVICE_ID};
+
+/// Maximum number of the options in a poll.
+pub const MAX_OPTIONS: u32 = 256;
+
+/// Error codes emitted by voting transactions during execution.
+#[derive(Debug, Fail)]
+#[repr(u8)]
+pub enum Error {
+    /// The author of the transaction is not an authorized voter.
+    #[fail(display = "Voter is not authorized")]
+    UnauthorizedVoter = 0,
+
+    /// Poll has no options to choose from.
+    #[fail(display = "Poll should have at least one option")]
+    NoOptions = 1,
+
+    /// Poll with the given identifier does not exist.
+    #[fail(display = "Poll not found")]
+    PollNotFound = 2,
+
+    /// Chosen option does not exist in the poll.
+    #[fail(display = "Invalid option")]
+    InvalidOption = 3,
+
+    /// Voter has already cast a ballot in the poll.
+    #[fail(display = "Voter has already cast a ballot")]
+    AlreadyVoted = 4,
+
+    /// Poll has more than `MAX_OPTIONS` options.
+    #[fail(display = "Poll has too many options")]
+    TooManyOptions = 5,
+}
+
+impl From<Error> for ExecutionError {
+    fn from(value: Error) -> ExecutionError {
+        let description = value.to_string();
+        ExecutionError::with_description(value as u8, description)
+    }
+}
+
+transactions! {
+    /// Transaction group.
+    pub VotingTransactions {
+
+        /// Creates a poll. The hash of the transaction is used as the poll identifier.
+        struct TxCreatePoll {
+            /// Title of the poll.
+            title: &str,
+            /// Number of the options to choose from.
+            options_count: u32,
+        }
+
+        /// Casts a ballot signed by the voter.
+        struct TxVote {
+            /// Identifier of the poll.
+            poll_id: &Hash,
+            /// Chosen option.
+            option: u32,
+        }
+    }
+}
+
+impl TxCreatePoll {
+    #[doc(hidden)]
+    pub fn sign(
+        author: &PublicKey,
+        title: &str,
+        options_count: u32,
+        key: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            TxCreatePoll::new(title, options_count),
+            VOTING_SERVICE_ID,
+            *author,
+            key,
+        )
+    }
+}
+
+impl TxVote {
+    #[doc(hidden)]
+    pub fn sign(
+        author: &PublicKey,
+        poll_id: &Hash,
+        option: u32,
+        key: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            TxVote::new(poll_id, option),
+            VOTING_SERVICE_ID,
+            *author,
+            key,
+        )
+    }
+}
+
+impl Transaction for TxCreatePoll {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let author = context.author();
+        let tx_hash = context.tx_hash();
+        if !VotingConfig::actual(context.fork()).is_voter(&author) {
+            Err(Error::UnauthorizedVoter)?;
+        }
+        if self.options_count() == 0 {
+            Err(Error::NoOptions)?;
+        }
+        if self.options_count() > MAX_OPTIONS {
+            Err(Error::TooManyOptions)?;
+        }
+
+        let mut schema = Schema::new(context.fork());
+        schema.create_poll(&tx_hash, self.title(), self.options_count());
+        Ok(())
+    }
+}
+
+impl Transaction for TxVote {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let author = context.author();
+        let tx_hash = context.tx_hash();
+        if !VotingConfig::actual(context.fork()).is_voter(&author) {
+            Err(Error::UnauthorizedVoter)?;
+        }
+
+        let mut schema = Schema::new(context.fork());
+        let poll = schema
+            .polls()
+            .get(self.poll_id())
+            .ok_or(Error::PollNotFound)?;
+        if self.option() >= poll.options_count() {
+            Err(Error::InvalidOption)?;
+        }
+        if schema.ballots(self.poll_id()).contains(&author) {
+            Err(Error::AlreadyVoted)?;
+        }
+
+        let ballot = Ballot::new(self.poll_id(), self.option(), &tx_hash);
+        schema.cast_ballot(&author, ballot);
+        Ok(())
+    }
+}
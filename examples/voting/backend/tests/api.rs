@@ -0,0 +1,221 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[macro_use]
+extern crate serde_json;
+
+#[macro_use]
+extern crate exonum_testkit;
+
+extern crate exonum;
+extern crate exonum_voting;
+
+use exonum::{
+    api::node::public::explorer::TransactionQuery,
+    crypto::{gen_keypair, Hash, PublicKey, SecretKey},
+    messages::{RawTransaction, Signed},
+};
+use exonum_testkit::{ApiKind, TestKit, TestKitApi, TestKitBuilder};
+
+use exonum_voting::{
+    api::{BallotProof, BallotQuery, PollInfo, PollQuery},
+    transactions::{TxCreatePoll, TxVote, MAX_OPTIONS},
+    Service, VotingConfig,
+};
+
+fn init_testkit(voters: &[PublicKey]) -> TestKit {
+    let config = VotingConfig {
+        voters: voters.to_vec(),
+    };
+    TestKitBuilder::validator()
+        .with_service(Service::new(config))
+        .create()
+}
+
+/// Assert transaction status
+fn assert_status(
+    api: &TestKitApi,
+    tx: &Signed<RawTransaction>,
+    expected_status: &serde_json::Value,
+) {
+    let info: serde_json::Value = api
+        .public(ApiKind::Explorer)
+        .query(&TransactionQuery::new(tx.hash()))
+        .get("v1/transactions")
+        .unwrap();
+
+    if let serde_json::Value::Object(mut info) = info {
+        let tx_status = info.remove("status").unwrap();
+        assert_eq!(tx_status, *expected_status);
+    } else {
+        panic!("Invalid transaction info format, object expected");
+    }
+}
+
+fn get_poll(api: &TestKitApi, poll_id: Hash) -> Option<PollInfo> {
+    api.public(ApiKind::Service("voting"))
+        .query(&PollQuery::new(poll_id))
+        .get("v1/polls")
+        .unwrap()
+}
+
+fn create_poll(testkit: &mut TestKit, keypair: &(PublicKey, SecretKey)) -> Hash {
+    let tx = TxCreatePoll::sign(&keypair.0, "Lunch", 3, &keypair.1);
+    testkit.create_block_with_transaction(tx.clone());
+    tx.hash()
+}
+
+#[test]
+fn test_create_poll() {
+    let keypair = gen_keypair();
+    let mut testkit = init_testkit(&[keypair.0]);
+    let poll_id = create_poll(&mut testkit, &keypair);
+
+    let api = testkit.api();
+    let info = get_poll(&api, poll_id).unwrap();
+    assert_eq!(info.poll.title(), "Lunch");
+    assert_eq!(info.poll.ballots_count(), 0);
+    assert_eq!(info.tally, vec![0, 0, 0]);
+    assert!(get_poll(&api, Hash::zero()).is_none());
+}
+
+#[test]
+fn test_create_poll_with_too_many_options() {
+    let keypair = gen_keypair();
+    let mut testkit = init_testkit(&[keypair.0]);
+    let tx_max = TxCreatePoll::sign(&keypair.0, "Max", MAX_OPTIONS, &keypair.1);
+    let tx_over = TxCreatePoll::sign(&keypair.0, "Over", MAX_OPTIONS + 1, &keypair.1);
+    testkit.create_block_with_transactions(txvec![tx_max.clone(), tx_over.clone()]);
+
+    let api = testkit.api();
+    assert_status(&api, &tx_max, &json!({ "type": "success" }));
+    assert_status(
+        &api,
+        &tx_over,
+        &json!({ "type": "error", "code": 5, "description": "Poll has too many options" }),
+    );
+    assert!(get_poll(&api, tx_over.hash()).is_none());
+}
+
+#[test]
+fn test_tally() {
+    let voters = (0..4).map(|_| gen_keypair()).collect::<Vec<_>>();
+    let keys = voters.iter().map(|keypair| keypair.0).collect::<Vec<_>>();
+    let mut testkit = init_testkit(&keys);
+    let poll_id = create_poll(&mut testkit, &voters[0]);
+
+    let ballots = voters
+        .iter()
+        .zip(&[2, 0, 2])
+        .map(|(keypair, &option)| TxVote::sign(&keypair.0, &poll_id, option, &keypair.1))
+        .collect::<Vec<_>>();
+    testkit.create_block_with_transactions(ballots);
+
+    let api = testkit.api();
+    let info = get_poll(&api, poll_id).unwrap();
+    assert_eq!(info.poll.ballots_count(), 3);
+    assert_eq!(info.tally, vec![1, 0, 2]);
+
+    let tx = TxVote::sign(&voters[3].0, &poll_id, 1, &voters[3].1);
+    testkit.create_block_with_transaction(tx);
+    let info = get_poll(&api, poll_id).unwrap();
+    assert_eq!(info.poll.ballots_count(), 4);
+    assert_eq!(info.tally, vec![1, 1, 2]);
+}
+
+#[test]
+fn test_invalid_ballots() {
+    let keypair = gen_keypair();
+    let outsider = gen_keypair();
+    let mut testkit = init_testkit(&[keypair.0]);
+    let poll_id = create_poll(&mut testkit, &keypair);
+    let api = testkit.api();
+
+    let tx_unauthorized = TxVote::sign(&outsider.0, &poll_id, 0, &outsider.1);
+    let tx_unknown_poll = TxVote::sign(&keypair.0, &Hash::zero(), 0, &keypair.1);
+    let tx_invalid_option = TxVote::sign(&keypair.0, &poll_id, 3, &keypair.1);
+    let tx_ok = TxVote::sign(&keypair.0, &poll_id, 1, &keypair.1);
+    let tx_again = TxVote::sign(&keypair.0, &poll_id, 2, &keypair.1);
+    testkit.create_block_with_transactions(txvec![
+        tx_unauthorized.clone(),
+        tx_unknown_poll.clone(),
+        tx_invalid_option.clone(),
+        tx_ok.clone(),
+        tx_again.clone(),
+    ]);
+
+    assert_status(
+        &api,
+        &tx_unauthorized,
+        &json!({ "type": "error", "code": 0, "description": "Voter is not authorized" }),
+    );
+    assert_status(
+        &api,
+        &tx_unknown_poll,
+        &json!({ "type": "error", "code": 2, "description": "Poll not found" }),
+    );
+    assert_status(
+        &api,
+        &tx_invalid_option,
+        &json!({ "type": "error", "code": 3, "description": "Invalid option" }),
+    );
+    assert_status(&api, &tx_ok, &json!({ "type": "success" }));
+    assert_status(
+        &api,
+        &tx_again,
+        &json!({ "type": "error", "code": 4, "description": "Voter has already cast a ballot" }),
+    );
+    assert_eq!(get_poll(&api, poll_id).unwrap().tally, vec![0, 1, 0]);
+}
+
+#[test]
+fn test_ballot_proof() {
+    let keypair = gen_keypair();
+    let mut testkit = init_testkit(&[keypair.0]);
+    let poll_id = create_poll(&mut testkit, &keypair);
+    let tx = TxVote::sign(&keypair.0, &poll_id, 1, &keypair.1);
+    testkit.create_block_with_transaction(tx.clone());
+
+    let api = testkit.api();
+    let proof: BallotProof = api
+        .public(ApiKind::Service("voting"))
+        .query(&BallotQuery::new(poll_id, keypair.0))
+        .get("v1/polls/ballot")
+        .unwrap();
+
+    let poll_proof = proof.poll_proof.check().unwrap();
+    let poll = poll_proof
+        .entries()
+        .find(|&(id, _)| *id == poll_id)
+        .map(|(_, poll)| poll.clone())
+        .unwrap();
+    let ballot_proof = proof.ballot_proof.check().unwrap();
+    assert_eq!(ballot_proof.merkle_root(), *poll.ballots_hash());
+    let ballot = ballot_proof
+        .entries()
+        .find(|&(voter, _)| *voter == keypair.0)
+        .map(|(_, ballot)| ballot.clone())
+        .unwrap();
+    assert_eq!(ballot.option(), 1);
+    assert_eq!(ballot.tx_hash(), &tx.hash());
+
+    let state_proof = proof.state_proof.check().unwrap();
+    assert_eq!(
+        state_proof.merkle_root(),
+        *proof.block_info.block.state_hash()
+    );
+    assert!(state_proof
+        .entries()
+        .any(|(_, table_hash)| *table_hash == poll_proof.merkle_root()));
+}
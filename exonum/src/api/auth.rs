@@ -0,0 +1,165 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Token-based access control for the API endpoints.
+//!
+//! Each token is associated with an [`ApiRole`]. Roles are ordered, so a token
+//! with a higher role may call every endpoint available to the lower ones.
+//! Unless specified otherwise, reading endpoints require the `observer` role
+//! and modifying endpoints require the `operator` role.
+//!
+//! [`ApiRole`]: enum.ApiRole.html
+
+use std::{collections::HashMap, path::Path};
+
+use failure;
+
+use super::Error as ApiError;
+use helpers::config::ConfigFile;
+
+/// Role of the API consumer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiRole {
+    /// Read-only access, e.g. for monitoring systems.
+    Observer,
+    /// Access to reading endpoints and routine maintenance actions.
+    Operator,
+    /// Full access, including the endpoints that can stop the node.
+    Admin,
+}
+
+/// API token along with the role it grants.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiToken {
+    /// Secret token value, passed by the client in the `Authorization: Bearer <token>` header.
+    pub token: String,
+    /// Role granted by the token.
+    pub role: ApiRole,
+}
+
+/// Contents of a local token file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApiTokensFile {
+    /// List of tokens.
+    #[serde(default)]
+    pub tokens: Vec<ApiToken>,
+}
+
+/// Set of known API tokens used to authorize incoming requests.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ApiAccessControl {
+    tokens: HashMap<String, ApiRole>,
+}
+
+impl ApiAccessControl {
+    /// Creates access control from the given tokens.
+    pub fn new<I: IntoIterator<Item = ApiToken>>(tokens: I) -> Self {
+        Self {
+            tokens: tokens
+                .into_iter()
+                .map(|token| (token.token, token.role))
+                .collect(),
+        }
+    }
+
+    /// Loads tokens from the TOML-encoded token file and adds them to the given ones.
+    pub fn load<P: AsRef<Path>>(
+        tokens: Vec<ApiToken>,
+        tokens_file: Option<P>,
+    ) -> Result<Self, failure::Error> {
+        let mut access_control = Self::new(tokens);
+        if let Some(path) = tokens_file {
+            let file: ApiTokensFile = ConfigFile::load(path)?;
+            access_control.tokens.extend(
+                file.tokens
+                    .into_iter()
+                    .map(|token| (token.token, token.role)),
+            );
+        }
+        Ok(access_control)
+    }
+
+    /// Returns `true` if no tokens are configured.
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Returns the role granted by the given token, if the token is known.
+    pub fn role(&self, token: &str) -> Option<ApiRole> {
+        self.tokens.get(token).cloned()
+    }
+
+    /// Checks that the given token grants at least the `required` role.
+    pub fn authorize(&self, token: Option<&str>, required: ApiRole) -> Result<(), ApiError> {
+        let role = token
+            .and_then(|token| self.role(token))
+            .ok_or(ApiError::Unauthorized)?;
+        if role >= required {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden)
+        }
+    }
+}
+
+/// Extracts a token from the value of the `Authorization` header.
+pub(crate) fn bearer_token(header: &str) -> Option<&str> {
+    let mut parts = header.splitn(2, ' ');
+    match (parts.next(), parts.next()) {
+        (Some(scheme), Some(token)) if scheme.eq_ignore_ascii_case("bearer") => Some(token.trim()),
+        _ => None,
+    }
+}
+
+#[test]
+fn authorize_by_role() {
+    let access_control = ApiAccessControl::new(vec![
+        ApiToken {
+            token: "monitoring".to_owned(),
+            role: ApiRole::Observer,
+        },
+        ApiToken {
+            token: "root".to_owned(),
+            role: ApiRole::Admin,
+        },
+    ]);
+
+    assert!(access_control
+        .authorize(Some("monitoring"), ApiRole::Observer)
+        .is_ok());
+    match access_control.authorize(Some("monitoring"), ApiRole::Admin) {
+        Err(ApiError::Forbidden) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    match access_control.authorize(Some("unknown"), ApiRole::Observer) {
+        Err(ApiError::Unauthorized) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    match access_control.authorize(None, ApiRole::Observer) {
+        Err(ApiError::Unauthorized) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    assert!(access_control
+        .authorize(Some("root"), ApiRole::Operator)
+        .is_ok());
+}
+
+#[test]
+fn bearer_token_parsing() {
+    assert_eq!(bearer_token("Bearer abc"), Some("abc"));
+    assert_eq!(bearer_token("bearer  abc "), Some("abc"));
+    assert_eq!(bearer_token("Basic abc"), None);
+    assert_eq!(bearer_token("abc"), None);
+}
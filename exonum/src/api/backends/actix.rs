@@ -24,28 +24,34 @@ use actix_net::server::Server;
 use actix_web::{
     self,
     error::ResponseError,
+    http::header,
     server::{HttpServer, StopServer},
-    AsyncResponder, FromRequest, HttpMessage, HttpResponse, Query,
+    AsyncResponder, Body, FromRequest, HttpMessage, HttpResponse, Query,
 };
 use failure;
-use futures::{Future, IntoFuture};
+use futures::{future, Future, IntoFuture, Stream};
 use serde::{
     de::{self, DeserializeOwned},
     ser, Serialize,
 };
 
 use std::{
+    collections::HashMap,
     fmt,
     net::SocketAddr,
     result,
     str::FromStr,
     sync::{mpsc, Arc},
     thread::{self, JoinHandle},
+    time::Instant,
 };
 
 use api::{
-    error::Error as ApiError, ApiAccess, ApiAggregator, ExtendApiBackend, FutureResult, Immutable,
-    Mutable, NamedWith, Result, ServiceApiBackend, ServiceApiScope, ServiceApiState,
+    auth::{self, ApiAccessControl, ApiRole},
+    consumers::{ApiConsumers, ConsumerId},
+    error::Error as ApiError,
+    ApiAccess, ApiAggregator, ExtendApiBackend, FutureResult, Immutable, Mutable, NamedWith,
+    Result, ServiceApiBackend, ServiceApiScope, ServiceApiState,
 };
 
 /// Type alias for the concrete `actix-web` HTTP response.
@@ -83,6 +89,8 @@ impl fmt::Debug for RequestHandler {
 #[derive(Debug, Clone, Default)]
 pub struct ApiBuilder {
     handlers: Vec<RequestHandler>,
+    roles: HashMap<String, ApiRole>,
+    read_roles: HashMap<String, ApiRole>,
 }
 
 impl ApiBuilder {
@@ -90,6 +98,34 @@ impl ApiBuilder {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Sets the role required to call the mutable endpoint with the given name.
+    /// By default, mutable endpoints require the `operator` role.
+    pub fn restrict<N: Into<String>>(&mut self, name: N, role: ApiRole) -> &mut Self {
+        self.roles.insert(name.into(), role);
+        self
+    }
+
+    /// Sets the role required to call the reading endpoint with the given name.
+    /// By default, reading endpoints require the `observer` role.
+    pub fn restrict_read<N: Into<String>>(&mut self, name: N, role: ApiRole) -> &mut Self {
+        self.read_roles.insert(name.into(), role);
+        self
+    }
+
+    fn required_role(&self, handler: &RequestHandler) -> ApiRole {
+        if handler.method == actix_web::http::Method::GET {
+            self.read_roles
+                .get(&handler.name)
+                .cloned()
+                .unwrap_or(ApiRole::Observer)
+        } else {
+            self.roles
+                .get(&handler.name)
+                .cloned()
+                .unwrap_or(ApiRole::Operator)
+        }
+    }
 }
 
 impl ServiceApiBackend for ApiBuilder {
@@ -103,15 +139,75 @@ impl ServiceApiBackend for ApiBuilder {
 
     fn wire(&self, mut output: Self::Backend) -> Self::Backend {
         for handler in self.handlers.clone() {
+            let role = self.required_role(&handler);
             let inner = handler.inner;
-            output = output.route(&handler.name, handler.method.clone(), move |request| {
-                inner(request)
-            });
+            output = output.route(
+                &handler.name,
+                handler.method.clone(),
+                move |request: HttpRequest| -> FutureResponse {
+                    let token = request
+                        .headers()
+                        .get(header::AUTHORIZATION)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(auth::bearer_token)
+                        .map(str::to_owned);
+                    // Clients are identified by the token only if it is known; otherwise,
+                    // random tokens could be used to evade the quota.
+                    let consumers = request.state().consumers().cloned();
+                    let consumer = ConsumerId::new(
+                        request
+                            .state()
+                            .authenticate(token.as_ref().map(String::as_str)),
+                        request.peer_addr().map(|address| address.ip()),
+                    );
+                    if let Some(ref consumers) = consumers {
+                        if let Err(e) = consumers.acquire(&consumer, Instant::now()) {
+                            return Box::new(future::err(e.into()));
+                        }
+                    }
+
+                    let authorized = request
+                        .state()
+                        .authorize(token.as_ref().map(String::as_str), role);
+                    match (authorized, consumers) {
+                        (Ok(()), Some(consumers)) => Box::new(
+                            inner(request)
+                                .map(move |response| record_bytes(response, consumers, consumer)),
+                        ),
+                        (Ok(()), None) => inner(request),
+                        (Err(e), _) => Box::new(future::err(e.into())),
+                    }
+                },
+            );
         }
         output
     }
 }
 
+/// Accounts the body of the response to the consumer. Streamed bodies are accounted
+/// chunk by chunk as they are sent. The frames sent over WebSocket connections
+/// are not accounted.
+fn record_bytes(
+    mut response: HttpResponse,
+    consumers: ApiConsumers,
+    consumer: ConsumerId,
+) -> HttpResponse {
+    match response.replace_body(Body::Empty) {
+        Body::Binary(binary) => {
+            consumers.record_bytes(&consumer, binary.len() as u64, Instant::now());
+            response.set_body(binary);
+        }
+        Body::Streaming(stream) => {
+            let stream = stream.inspect(move |chunk| {
+                consumers.record_bytes(&consumer, chunk.len() as u64, Instant::now());
+            });
+            response.set_body(Body::Streaming(Box::new(stream)));
+        }
+        body => response.set_body(body),
+    }
+    response
+}
+
 impl ExtendApiBackend for actix_web::Scope<ServiceApiState> {
     fn extend<'a, I>(mut self, items: I) -> Self
     where
@@ -134,7 +230,13 @@ impl ResponseError for ApiError {
             ApiError::Io(err) => HttpResponse::InternalServerError().body(err.to_string()),
             ApiError::Storage(err) => HttpResponse::InternalServerError().body(err.to_string()),
             ApiError::NotFound(err) => HttpResponse::NotFound().body(err.to_string()),
+            ApiError::Gone(err) => HttpResponse::Gone().body(err.to_string()),
             ApiError::Unauthorized => HttpResponse::Unauthorized().finish(),
+            ApiError::Forbidden => HttpResponse::Forbidden().finish(),
+            ApiError::TooManyRequests => HttpResponse::TooManyRequests().finish(),
+            ApiError::ServiceUnavailable(err) => {
+                HttpResponse::ServiceUnavailable().body(err.to_string())
+            }
         }
     }
 }
@@ -148,11 +250,12 @@ where
     fn from(f: NamedWith<Q, I, Result<I>, F, Immutable>) -> Self {
         let handler = f.inner.handler;
         let index = move |request: HttpRequest| -> FutureResponse {
+            let format = ResponseFormat::from_request(&request);
             let context = request.state();
             let future = Query::from_request(&request, &())
                 .map(|query: Query<Q>| query.into_inner())
                 .and_then(|query| handler(context, query).map_err(From::from))
-                .and_then(|value| Ok(HttpResponse::Ok().json(value)))
+                .and_then(|value| format.response(&value))
                 .into_future();
             Box::new(future)
         };
@@ -176,13 +279,14 @@ where
         let index = move |request: HttpRequest| -> FutureResponse {
             let handler = handler.clone();
             let context = request.state().clone();
+            let format = ResponseFormat::from_request(&request);
             request
                 .json()
                 .from_err()
                 .and_then(move |query: Q| {
                     handler(&context, query)
-                        .map(|value| HttpResponse::Ok().json(value))
                         .map_err(From::from)
+                        .and_then(|value| format.response(&value))
                 }).responder()
         };
 
@@ -205,11 +309,12 @@ where
         let index = move |request: HttpRequest| -> FutureResponse {
             let context = request.state().clone();
             let handler = handler.clone();
+            let format = ResponseFormat::from_request(&request);
             Query::from_request(&request, &())
                 .map(move |query: Query<Q>| query.into_inner())
                 .into_future()
                 .and_then(move |query| handler(&context, query).map_err(From::from))
-                .map(|value| HttpResponse::Ok().json(value))
+                .and_then(move |value| format.response(&value))
                 .responder()
         };
 
@@ -232,13 +337,14 @@ where
         let index = move |request: HttpRequest| -> FutureResponse {
             let handler = handler.clone();
             let context = request.state().clone();
+            let format = ResponseFormat::from_request(&request);
             request
                 .json()
                 .from_err()
                 .and_then(move |query: Q| {
                     handler(&context, query)
-                        .map(|value| HttpResponse::Ok().json(value))
                         .map_err(From::from)
+                        .and_then(move |value| format.response(&value))
                 }).responder()
         };
 
@@ -250,11 +356,78 @@ where
     }
 }
 
+/// Serialization format of the endpoint responses, selected by the `Accept` header
+/// of the request. JSON is used unless the client explicitly accepts CBOR or MessagePack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFormat {
+    /// JSON, `application/json`.
+    Json,
+    /// [CBOR](https://tools.ietf.org/html/rfc7049), `application/cbor`.
+    Cbor,
+    /// [MessagePack](https://msgpack.org/), `application/msgpack`.
+    MessagePack,
+}
+
+impl ResponseFormat {
+    /// Selects the format by the value of the `Accept` header. The first supported
+    /// media type listed in the header is used.
+    pub fn from_accept(accept: &str) -> Self {
+        accept
+            .split(',')
+            .filter_map(|media_range| {
+                let media_type = media_range.split(';').next().unwrap_or("").trim();
+                match media_type.to_lowercase().as_str() {
+                    "application/json" => Some(ResponseFormat::Json),
+                    "application/cbor" => Some(ResponseFormat::Cbor),
+                    "application/msgpack" | "application/x-msgpack" => {
+                        Some(ResponseFormat::MessagePack)
+                    }
+                    _ => None,
+                }
+            }).next()
+            .unwrap_or(ResponseFormat::Json)
+    }
+
+    /// Selects the format by the `Accept` header of the request.
+    pub fn from_request(request: &HttpRequest) -> Self {
+        request
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .map_or(ResponseFormat::Json, Self::from_accept)
+    }
+
+    /// Creates a successful response with the value serialized in this format.
+    pub fn response<T: Serialize>(
+        self,
+        value: &T,
+    ) -> result::Result<HttpResponse, actix_web::Error> {
+        let (content_type, body) = match self {
+            ResponseFormat::Json => return Ok(HttpResponse::Ok().json(value)),
+            ResponseFormat::Cbor => (
+                "application/cbor",
+                serde_cbor::to_vec(value).map_err(|e| ApiError::InternalError(e.into()))?,
+            ),
+            ResponseFormat::MessagePack => (
+                "application/msgpack",
+                rmp_serde::to_vec_named(value).map_err(|e| ApiError::InternalError(e.into()))?,
+            ),
+        };
+        Ok(HttpResponse::Ok().content_type(content_type).body(body))
+    }
+}
+
 /// Creates `actix_web::App` for the given aggregator and runtime configuration.
 pub(crate) fn create_app(aggregator: &ApiAggregator, runtime_config: ApiRuntimeConfig) -> App {
     let app_config = runtime_config.app_config;
     let access = runtime_config.access;
-    let state = ServiceApiState::new(aggregator.blockchain.clone());
+    let mut state = ServiceApiState::new(aggregator.blockchain.clone());
+    if let Some(access_control) = runtime_config.access_control {
+        state = state.with_access_control(access_control);
+    }
+    if let Some(consumers) = runtime_config.consumers {
+        state = state.with_consumers(consumers);
+    }
     let mut app = App::with_state(state);
     app = app.scope("api", |scope| aggregator.extend_backend(access, scope));
     if let Some(app_config) = app_config {
@@ -272,6 +445,13 @@ pub struct ApiRuntimeConfig {
     pub access: ApiAccess,
     /// Optional App configuration.
     pub app_config: Option<AppConfig>,
+    /// Optional token-based access control. If not set, all requests are authorized.
+    pub access_control: Option<ApiAccessControl>,
+    /// Optional resource accounting per API consumer, which may be shared
+    /// by several runtimes.
+    pub consumers: Option<ApiConsumers>,
+    /// Number of worker threads. If not set, the number of CPU cores is used.
+    pub workers: Option<usize>,
 }
 
 impl ApiRuntimeConfig {
@@ -281,6 +461,9 @@ impl ApiRuntimeConfig {
             listen_address,
             access,
             app_config: Default::default(),
+            access_control: Default::default(),
+            consumers: Default::default(),
+            workers: Default::default(),
         }
     }
 }
@@ -291,6 +474,9 @@ impl fmt::Debug for ApiRuntimeConfig {
             .field("listen_address", &self.listen_address)
             .field("access", &self.access)
             .field("app_config", &self.app_config.as_ref().map(drop))
+            .field("access_control", &self.access_control)
+            .field("consumers", &self.consumers)
+            .field("workers", &self.workers)
             .finish()
     }
 }
@@ -337,11 +523,17 @@ impl SystemRuntime {
                 debug!("Runtime: {:?}", runtime_config);
                 let access = runtime_config.access;
                 let listen_address = runtime_config.listen_address;
+                let workers = runtime_config.workers;
                 info!("Starting {} web api on {}", access, listen_address);
 
                 let aggregator = aggregator.clone();
-                HttpServer::new(move || create_app(&aggregator, runtime_config.clone()))
-                    .disable_signals()
+                let mut server =
+                    HttpServer::new(move || create_app(&aggregator, runtime_config.clone()))
+                        .disable_signals();
+                if let Some(workers) = workers {
+                    server = server.workers(workers);
+                }
+                server
                     .bind(listen_address)
                     .map(|server| server.start())
             });
@@ -547,3 +739,133 @@ fn allow_origin_from_str() {
         AllowOrigin::Whitelist(vec!["http://a.org".to_string(), "http://b.org".to_string()]),
     );
 }
+
+#[test]
+fn response_format_from_accept() {
+    assert_eq!(ResponseFormat::from_accept("*/*"), ResponseFormat::Json);
+    assert_eq!(
+        ResponseFormat::from_accept("application/json"),
+        ResponseFormat::Json
+    );
+    assert_eq!(
+        ResponseFormat::from_accept("application/cbor"),
+        ResponseFormat::Cbor
+    );
+    assert_eq!(
+        ResponseFormat::from_accept("text/html, Application/MsgPack;q=0.9, application/json"),
+        ResponseFormat::MessagePack
+    );
+    assert_eq!(
+        ResponseFormat::from_accept("application/json, application/cbor"),
+        ResponseFormat::Json
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::{header, Method, StatusCode},
+        test::TestServer,
+    };
+    use futures::sync::mpsc;
+
+    use super::{ApiBuilder, App};
+    use api::{
+        auth::{ApiAccessControl, ApiRole, ApiToken},
+        Result, ServiceApiBackend, ServiceApiState,
+    };
+    use blockchain::Blockchain;
+    use crypto::gen_keypair;
+    use node::ApiSender;
+    use storage::MemoryDB;
+
+    fn token(token: &str, role: ApiRole) -> ApiToken {
+        ApiToken {
+            token: token.to_owned(),
+            role,
+        }
+    }
+
+    fn handler(_: &ServiceApiState, _: ()) -> Result<u8> {
+        Ok(1)
+    }
+
+    fn create_server() -> TestServer {
+        let (public_key, secret_key) = gen_keypair();
+        let (api_sender, _) = mpsc::channel(0);
+        let blockchain = Blockchain::new(
+            MemoryDB::new(),
+            Vec::new(),
+            public_key,
+            secret_key,
+            ApiSender::new(api_sender),
+        );
+        let access_control = ApiAccessControl::new(vec![
+            token("observer", ApiRole::Observer),
+            token("operator", ApiRole::Operator),
+            token("admin", ApiRole::Admin),
+        ]);
+        let state = ServiceApiState::new(blockchain).with_access_control(access_control);
+
+        let mut builder = ApiBuilder::new();
+        builder
+            .endpoint("v1/status", handler)
+            .endpoint_mut("v1/peers", handler)
+            .endpoint_mut("v1/shutdown", handler)
+            .restrict("v1/shutdown", ApiRole::Admin);
+
+        TestServer::with_factory(move || {
+            App::with_state(state.clone()).scope("api", |scope| builder.wire(scope))
+        })
+    }
+
+    fn send(
+        server: &mut TestServer,
+        method: Method,
+        name: &str,
+        token: Option<&str>,
+    ) -> StatusCode {
+        let mut request = server.client(method.clone(), &format!("/api/{}", name));
+        if let Some(token) = token {
+            request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let request = if method == Method::GET {
+            request.finish()
+        } else {
+            request.json(())
+        };
+        let response = server.execute(request.unwrap().send()).unwrap();
+        response.status()
+    }
+
+    #[test]
+    fn unauthorized_requests() {
+        let mut server = create_server();
+        let status = send(&mut server, Method::GET, "v1/status", None);
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let status = send(&mut server, Method::GET, "v1/status", Some("unknown"));
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let status = send(&mut server, Method::POST, "v1/peers", None);
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn forbidden_requests() {
+        let mut server = create_server();
+        let status = send(&mut server, Method::POST, "v1/peers", Some("observer"));
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let status = send(&mut server, Method::POST, "v1/shutdown", Some("operator"));
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn allowed_requests() {
+        let mut server = create_server();
+        let status = send(&mut server, Method::GET, "v1/status", Some("observer"));
+        assert_eq!(status, StatusCode::OK);
+        let status = send(&mut server, Method::POST, "v1/peers", Some("operator"));
+        assert_eq!(status, StatusCode::OK);
+        let status = send(&mut server, Method::POST, "v1/shutdown", Some("admin"));
+        assert_eq!(status, StatusCode::OK);
+    }
+}
@@ -0,0 +1,381 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resource accounting per API consumer.
+//!
+//! Consumers are identified by the API token passed in the `Authorization` header
+//! or, if no token known to the access control of the API is passed, by the IP address
+//! of the client. Requests and bytes served to each consumer are counted over a sliding
+//! window; the statistics are available with the `v1/consumers` endpoint of the private
+//! system API.
+//!
+//! Each consumer may be given a quota on the number of requests and bytes served
+//! within the window. Requests exceeding the quota are rejected with
+//! the `429 Too Many Requests` status.
+
+use std::{
+    cmp,
+    collections::{HashMap, VecDeque},
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use super::Error as ApiError;
+use crypto::{self, Hash};
+
+/// Identifier of an API consumer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id", rename_all = "snake_case")]
+pub enum ConsumerId {
+    /// Consumer passing the API token with the given hash. The token itself is not
+    /// exposed.
+    Token(Hash),
+    /// Consumer without a token, identified by the IP address.
+    Ip(IpAddr),
+    /// Consumer without a token and a known IP address.
+    Unknown,
+}
+
+impl ConsumerId {
+    /// Identifies the consumer by the API token or, if there is no token, by the IP address.
+    /// The token must be authenticated by the caller.
+    pub fn new(token: Option<&str>, ip: Option<IpAddr>) -> Self {
+        match (token, ip) {
+            (Some(token), _) => ConsumerId::Token(crypto::hash(token.as_bytes())),
+            (None, Some(ip)) => ConsumerId::Ip(ip),
+            (None, None) => ConsumerId::Unknown,
+        }
+    }
+}
+
+/// Limits on the resources served to a consumer within the accounting window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quota {
+    /// Maximum number of requests.
+    #[serde(default)]
+    pub max_requests: Option<u64>,
+    /// Maximum number of bytes in the response bodies.
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+}
+
+impl Quota {
+    fn is_exceeded(&self, requests: u64, bytes: u64) -> bool {
+        self.max_requests.map_or(false, |max| requests >= max)
+            || self.max_bytes.map_or(false, |max| bytes >= max)
+    }
+}
+
+/// Quota of the consumer with the given token or IP address.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsumerQuota {
+    /// API token of the consumer. Applies only to the APIs with access control
+    /// which accepts the token.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// IP address of the consumer. Ignored if the token is set.
+    #[serde(default)]
+    pub ip: Option<IpAddr>,
+    /// Quota of the consumer.
+    pub quota: Quota,
+}
+
+/// Configuration of the resource accounting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConsumersConfig {
+    /// Length of the sliding accounting window in seconds.
+    pub window: u64,
+    /// Quota of the consumers without an individual quota. Consumers are not limited
+    /// if not set.
+    pub default_quota: Option<Quota>,
+    /// Individual quotas of the consumers.
+    pub quotas: Vec<ConsumerQuota>,
+}
+
+impl Default for ConsumersConfig {
+    fn default() -> Self {
+        Self {
+            window: 60,
+            default_quota: None,
+            quotas: Vec::new(),
+        }
+    }
+}
+
+/// Resources served to a consumer within the accounting window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsumerStats {
+    /// Consumer identifier.
+    pub consumer: ConsumerId,
+    /// Number of requests.
+    pub requests: u64,
+    /// Number of bytes in the response bodies.
+    pub bytes: u64,
+    /// Quota of the consumer, if any.
+    pub quota: Option<Quota>,
+}
+
+// Resources served within one second.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    second: u64,
+    requests: u64,
+    bytes: u64,
+}
+
+#[derive(Debug, Default)]
+struct Usage {
+    buckets: VecDeque<Bucket>,
+}
+
+impl Usage {
+    fn prune(&mut self, second: u64, window: u64) {
+        while self
+            .buckets
+            .front()
+            .map_or(false, |bucket| bucket.second + window <= second)
+        {
+            self.buckets.pop_front();
+        }
+    }
+
+    fn add(&mut self, second: u64, requests: u64, bytes: u64) {
+        match self.buckets.back_mut() {
+            Some(ref mut bucket) if bucket.second == second => {
+                bucket.requests += requests;
+                bucket.bytes += bytes;
+                return;
+            }
+            _ => {}
+        }
+        self.buckets.push_back(Bucket {
+            second,
+            requests,
+            bytes,
+        });
+    }
+
+    fn totals(&self) -> (u64, u64) {
+        self.buckets
+            .iter()
+            .fold((0, 0), |(requests, bytes), bucket| {
+                (requests + bucket.requests, bytes + bucket.bytes)
+            })
+    }
+}
+
+#[derive(Debug)]
+struct Accounting {
+    start: Instant,
+    window: u64,
+    default_quota: Option<Quota>,
+    quotas: HashMap<ConsumerId, Quota>,
+    usage: HashMap<ConsumerId, Usage>,
+    // Second of the last removal of the consumers without requests within the window.
+    swept_at: u64,
+}
+
+impl Accounting {
+    fn second(&self, now: Instant) -> u64 {
+        now.duration_since(self.start).as_secs()
+    }
+
+    // Forgets the consumers without requests within the window.
+    fn sweep(&mut self, second: u64) {
+        let window = self.window;
+        for usage in self.usage.values_mut() {
+            usage.prune(second, window);
+        }
+        self.usage.retain(|_, usage| !usage.buckets.is_empty());
+        self.swept_at = second;
+    }
+
+    fn quota(&self, consumer: &ConsumerId) -> Option<Quota> {
+        self.quotas.get(consumer).cloned().or(self.default_quota)
+    }
+}
+
+/// Resource accounting shared by the API handlers.
+#[derive(Debug, Clone)]
+pub struct ApiConsumers {
+    inner: Arc<Mutex<Accounting>>,
+}
+
+impl ApiConsumers {
+    /// Creates resource accounting with the given configuration.
+    pub fn new(config: ConsumersConfig) -> Self {
+        let quotas = config
+            .quotas
+            .into_iter()
+            .map(|quota| {
+                (
+                    ConsumerId::new(quota.token.as_ref().map(String::as_str), quota.ip),
+                    quota.quota,
+                )
+            }).collect();
+        let accounting = Accounting {
+            start: Instant::now(),
+            window: cmp::max(config.window, 1),
+            default_quota: config.default_quota,
+            quotas,
+            usage: HashMap::new(),
+            swept_at: 0,
+        };
+        Self {
+            inner: Arc::new(Mutex::new(accounting)),
+        }
+    }
+
+    /// Accounts a new request of the consumer. Returns an error if the consumer
+    /// has exhausted its quota; such requests are not accounted.
+    ///
+    /// Consumers without requests within the window are forgotten at most once per window.
+    pub fn acquire(&self, consumer: &ConsumerId, now: Instant) -> Result<(), ApiError> {
+        let mut accounting = self.inner.lock().expect("Expected consumers lock");
+        let second = accounting.second(now);
+        let window = accounting.window;
+        if accounting.swept_at + window <= second {
+            accounting.sweep(second);
+        }
+        let quota = accounting.quota(consumer);
+        let usage = accounting
+            .usage
+            .entry(*consumer)
+            .or_insert_with(Usage::default);
+        usage.prune(second, window);
+        let (requests, bytes) = usage.totals();
+        if quota.map_or(false, |quota| quota.is_exceeded(requests, bytes)) {
+            return Err(ApiError::TooManyRequests);
+        }
+        usage.add(second, 1, 0);
+        Ok(())
+    }
+
+    /// Accounts bytes served to the consumer.
+    pub fn record_bytes(&self, consumer: &ConsumerId, bytes: u64, now: Instant) {
+        let mut accounting = self.inner.lock().expect("Expected consumers lock");
+        let second = accounting.second(now);
+        accounting
+            .usage
+            .entry(*consumer)
+            .or_insert_with(Usage::default)
+            .add(second, 0, bytes);
+    }
+
+    /// Returns the resources served to the consumers within the window, starting from
+    /// the most active consumers. Consumers without requests within the window
+    /// are forgotten.
+    pub fn stats(&self, now: Instant) -> Vec<ConsumerStats> {
+        let mut accounting = self.inner.lock().expect("Expected consumers lock");
+        let second = accounting.second(now);
+        accounting.sweep(second);
+
+        let mut stats = accounting
+            .usage
+            .iter()
+            .map(|(consumer, usage)| {
+                let (requests, bytes) = usage.totals();
+                ConsumerStats {
+                    consumer: *consumer,
+                    requests,
+                    bytes,
+                    quota: accounting.quota(consumer),
+                }
+            }).collect::<Vec<_>>();
+        stats.sort_by(|a, b| {
+            b.requests
+                .cmp(&a.requests)
+                .then_with(|| a.consumer.cmp(&b.consumer))
+        });
+        stats
+    }
+}
+
+#[test]
+fn consumer_quotas() {
+    use std::time::Duration;
+
+    let ip: IpAddr = "127.0.0.1".parse().unwrap();
+    let consumers = ApiConsumers::new(ConsumersConfig {
+        window: 10,
+        default_quota: Some(Quota {
+            max_requests: Some(2),
+            max_bytes: None,
+        }),
+        quotas: vec![ConsumerQuota {
+            token: Some("explorer".to_owned()),
+            ip: None,
+            quota: Quota {
+                max_requests: None,
+                max_bytes: Some(100),
+            },
+        }],
+    });
+    let now = Instant::now();
+
+    let client = ConsumerId::new(None, Some(ip));
+    assert!(consumers.acquire(&client, now).is_ok());
+    assert!(consumers.acquire(&client, now).is_ok());
+    match consumers.acquire(&client, now + Duration::from_secs(5)) {
+        Err(ApiError::TooManyRequests) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    assert!(consumers
+        .acquire(&client, now + Duration::from_secs(10))
+        .is_ok());
+
+    let explorer = ConsumerId::new(Some("explorer"), Some(ip));
+    assert!(consumers.acquire(&explorer, now).is_ok());
+    consumers.record_bytes(&explorer, 100, now);
+    assert!(consumers.acquire(&explorer, now).is_err());
+
+    let stats = consumers.stats(now + Duration::from_secs(10));
+    assert_eq!(
+        stats,
+        vec![ConsumerStats {
+            consumer: client,
+            requests: 1,
+            bytes: 0,
+            quota: Some(Quota {
+                max_requests: Some(2),
+                max_bytes: None,
+            }),
+        },]
+    );
+}
+
+#[test]
+fn idle_consumers_are_forgotten() {
+    use std::time::Duration;
+
+    let consumers = ApiConsumers::new(ConsumersConfig {
+        window: 10,
+        default_quota: None,
+        quotas: Vec::new(),
+    });
+    let now = Instant::now();
+    for i in 0..100 {
+        let ip = IpAddr::from([10, 0, 0, i]);
+        assert!(consumers.acquire(&ConsumerId::Ip(ip), now).is_ok());
+    }
+    let client = ConsumerId::Ip(IpAddr::from([127, 0, 0, 1]));
+    assert!(consumers
+        .acquire(&client, now + Duration::from_secs(10))
+        .is_ok());
+
+    let accounting = consumers.inner.lock().unwrap();
+    assert_eq!(accounting.usage.keys().collect::<Vec<_>>(), vec![&client]);
+}
@@ -49,10 +49,30 @@ pub enum Error {
     #[fail(display = "Internal server error: {}", _0)]
     InternalError(failure::Error),
 
+    /// Gone. This error occurs when the requested resource existed, but has been removed,
+    /// e.g., the data of the pruned blocks.
+    #[fail(display = "Gone: {}", _0)]
+    Gone(String),
+
     /// Unauthorized error. This error occurs when the request lacks valid
     /// authentication credentials.
     #[fail(display = "Unauthorized")]
     Unauthorized,
+
+    /// Forbidden error. This error occurs when the request credentials are valid,
+    /// but do not grant access to the requested resource.
+    #[fail(display = "Forbidden")]
+    Forbidden,
+
+    /// Too many requests. This error occurs when the API consumer has exhausted
+    /// its quota.
+    #[fail(display = "Too many requests")]
+    TooManyRequests,
+
+    /// Service unavailable. This error occurs when the node temporarily cannot process
+    /// the request, e.g., it cannot accept transactions without the quorum of validators.
+    #[fail(display = "Service unavailable: {}", _0)]
+    ServiceUnavailable(String),
 }
 
 impl From<io::Error> for Error {
@@ -23,14 +23,17 @@ use serde::{de::DeserializeOwned, Serialize};
 
 use std::{collections::BTreeMap, fmt};
 
-use self::{backends::actix, node::public::ExplorerApi};
+use self::{auth::ApiRole, backends::actix, node::public::ExplorerApi};
 use blockchain::{Blockchain, SharedNodeState};
 use crypto::PublicKey;
 use node::ApiSender;
 
+pub mod auth;
 pub mod backends;
+pub mod consumers;
 pub mod error;
 pub mod node;
+pub mod pagination;
 mod state;
 pub(crate) mod websocket;
 mod with;
@@ -134,6 +137,22 @@ impl ServiceApiScope {
         self
     }
 
+    /// Sets the role required to call the mutable endpoint with the given name,
+    /// if the API is served with access control enabled. Reading endpoints
+    /// require the `observer` role, and mutable ones require the `operator` role
+    /// unless restricted further.
+    pub fn restrict(&mut self, name: &'static str, role: ApiRole) -> &mut Self {
+        self.actix_backend.restrict(name, role);
+        self
+    }
+
+    /// Sets the role required to call the reading endpoint with the given name,
+    /// if the API is served with access control enabled.
+    pub fn restrict_read(&mut self, name: &'static str, role: ApiRole) -> &mut Self {
+        self.actix_backend.restrict_read(name, role);
+        self
+    }
+
     /// Returns a mutable reference to the underlying web backend.
     pub fn web_backend(&mut self) -> &mut actix::ApiBuilder {
         &mut self.actix_backend
@@ -320,6 +339,8 @@ impl ApiAggregator {
             "explorer".to_owned(),
             Self::explorer_api(&blockchain, node_state.clone()),
         );
+        #[cfg(feature = "graphql")]
+        inner.insert("graphql".to_owned(), Self::graphql_api());
         // Adds services APIs.
         inner.extend(blockchain.service_map().iter().map(|(_, service)| {
             let mut builder = ServiceApiBuilder::with_blockchain(blockchain.clone());
@@ -336,6 +357,21 @@ impl ApiAggregator {
         }
     }
 
+    /// Aggregates only the explorer API for the given blockchain and node state,
+    /// e.g., to browse a database without running a node.
+    pub fn explorer_only(blockchain: Blockchain, node_state: SharedNodeState) -> Self {
+        let mut inner = BTreeMap::new();
+        inner.insert(
+            "explorer".to_owned(),
+            Self::explorer_api(&blockchain, node_state.clone()),
+        );
+        Self {
+            inner,
+            blockchain,
+            node_state,
+        }
+    }
+
     /// Returns a reference to the blockchain used by the aggregator.
     pub fn blockchain(&self) -> &Blockchain {
         &self.blockchain
@@ -369,6 +405,14 @@ impl ApiAggregator {
         let mut builder = ServiceApiBuilder::new();
         let service_api_state = ServiceApiState::new(blockchain.clone());
         ExplorerApi::wire(builder.public_scope(), service_api_state, shared_node_state);
+        ExplorerApi::wire_private(builder.private_scope());
+        builder
+    }
+
+    #[cfg(feature = "graphql")]
+    fn graphql_api() -> ServiceApiBuilder {
+        let mut builder = ServiceApiBuilder::new();
+        self::node::public::GraphQLApi::wire(builder.public_scope());
         builder
     }
 
@@ -17,13 +17,30 @@
 //! Private API includes requests that are available only to the blockchain
 //! administrators, e.g. view the list of services on the current node.
 
-use std::{collections::HashMap, net::SocketAddr};
+use actix_web::{http::Method, HttpResponse};
+use chrono::{DateTime, Utc};
+use futures::{future, Future};
 
-use api::{Error as ApiError, ServiceApiScope, ServiceApiState};
-use blockchain::{Service, SharedNodeState};
-use crypto::PublicKey;
-use messages::PROTOCOL_MAJOR_VERSION;
-use node::{ConnectInfo, ExternalMessage};
+use std::{collections::HashMap, net::SocketAddr, path::PathBuf, sync::Arc, time::Instant};
+
+use api::{
+    auth::ApiRole,
+    backends::actix::{FutureResponse, HttpRequest, RawHandler, RequestHandler},
+    Error as ApiError, FutureResult, ServiceApiBackend, ServiceApiScope, ServiceApiState,
+};
+use blockchain::{Schema, Service, SharedNodeState};
+use crypto::{Hash, PublicKey};
+use helpers::{Height, Round, ValidatorId};
+use messages::{
+    Consensus as ConsensusMessage, Message, Precommit, Prevote, Propose, Signed,
+    PROTOCOL_MAJOR_VERSION,
+};
+#[cfg(debug_assertions)]
+use node::chaos::ChaosConfig;
+use node::{
+    forensics::{TableRootDiff, TableRoots},
+    maintenance, ConnectInfo, ExternalMessage,
+};
 
 /// Short information about the service.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -100,6 +117,112 @@ struct ConsensusEnabledQuery {
     enabled: bool,
 }
 
+/// Parameters of the manual storage compaction.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct CompactQuery {
+    /// Prefixes of the names of the indexes to compact. All the indexes are compacted
+    /// if the list is empty.
+    #[serde(default)]
+    pub prefixes: Vec<String>,
+}
+
+/// Parameters of the database checkpoint request.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CheckpointQuery {
+    /// Path at which the checkpoint database is created. The path should not exist.
+    pub path: PathBuf,
+}
+
+/// Parameters of the archived consensus messages query.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ConsensusMessagesQuery {
+    /// Height of the messages.
+    pub height: Height,
+    /// Round of the messages. Messages of all rounds are returned if not set.
+    pub round: Option<Round>,
+    /// Validator which has authored the messages. Messages of all validators
+    /// are returned if not set.
+    pub validator: Option<ValidatorId>,
+}
+
+/// Consensus message from the archive.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ArchivedMessage {
+    /// `Propose` message.
+    Propose(Signed<Propose>),
+    /// `Prevote` message.
+    Prevote(Signed<Prevote>),
+    /// `Precommit` message.
+    Precommit(Signed<Precommit>),
+}
+
+impl From<ConsensusMessage> for ArchivedMessage {
+    fn from(msg: ConsensusMessage) -> Self {
+        match msg {
+            ConsensusMessage::Propose(msg) => ArchivedMessage::Propose(msg),
+            ConsensusMessage::Prevote(msg) => ArchivedMessage::Prevote(msg),
+            ConsensusMessage::Precommit(msg) => ArchivedMessage::Precommit(msg),
+        }
+    }
+}
+
+/// Parameters of the transaction pool query.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct MempoolQuery {
+    /// Author of the transactions. Transactions of all authors are returned if not set.
+    pub author: Option<PublicKey>,
+}
+
+/// Transaction from the pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MempoolTransaction {
+    /// Hash of the transaction.
+    pub tx_hash: Hash,
+    /// Author of the transaction.
+    pub author: PublicKey,
+    /// Identifier of the service the transaction belongs to.
+    pub service_id: u16,
+    /// Size of the transaction in bytes.
+    pub size: usize,
+}
+
+/// Statistics of a storage index.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IndexStatsInfo {
+    /// Name of the index. The indexes of a family are accounted together.
+    pub name: String,
+    /// Number of the entries in the index.
+    pub entries: u64,
+    /// Approximate size of the keys and values of the index in bytes.
+    pub bytes: u64,
+    /// Height of the latest block at which the index has been modified.
+    pub modified_at: Height,
+}
+
+/// Parameters of the table root hashes query.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct TableRootsQuery {
+    /// Height of the block. The root hashes at the latest height are returned if not set.
+    pub height: Option<Height>,
+}
+
+/// Synchronization status of a peer.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PeerSyncInfo {
+    /// Peer public key.
+    pub public_key: PublicKey,
+    /// Identifier of the peer if it is a validator.
+    pub validator_id: Option<ValidatorId>,
+    /// Height reported by the peer in the last `Status` message.
+    pub height: Height,
+    /// Number of blocks the peer is behind the current node. The value is negative
+    /// if the peer is ahead of the current node.
+    pub lag: i64,
+    /// Time when the last `Status` message was received from the peer.
+    pub last_status_time: DateTime<Utc>,
+}
+
 /// Private system API.
 #[derive(Clone, Debug)]
 pub struct SystemApi {
@@ -117,15 +240,43 @@ impl SystemApi {
     }
 
     /// Adds private system API endpoints to the corresponding scope.
+    ///
+    /// Endpoints changing the set of peers, switching consensus, stopping the node
+    /// or injecting faults require the `admin` role if the private API is served
+    /// with access control.
     pub fn wire(self, api_scope: &mut ServiceApiScope) -> &mut ServiceApiScope {
         self.handle_peers_info("v1/peers", api_scope)
             .handle_peer_add("v1/peers", api_scope)
             .handle_network_info("v1/network", api_scope)
+            .handle_network_latency("v1/network/latency", api_scope)
+            .handle_network_clock_skew("v1/network/clock_skew", api_scope)
+            .handle_network_sync("v1/network/sync", api_scope)
             .handle_is_consensus_enabled("v1/consensus_enabled", api_scope)
             .handle_set_consensus_enabled("v1/consensus_enabled", api_scope)
             .handle_shutdown("v1/shutdown", api_scope)
-            .handle_rebroadcast("v1/rebroadcast", api_scope);
+            .handle_rebroadcast("v1/rebroadcast", api_scope)
+            .handle_flush("v1/flush", api_scope)
+            .handle_compact("v1/compact", api_scope)
+            .handle_checkpoint("v1/checkpoint", api_scope)
+            .handle_consensus_messages("v1/consensus/messages", api_scope)
+            .handle_mempool_transactions("v1/mempool/transactions", api_scope)
+            .handle_consumers("v1/consumers", api_scope)
+            .handle_storage_stats("v1/storage", api_scope)
+            .handle_divergence_report("v1/forensics/report", api_scope)
+            .handle_table_roots("v1/forensics/table_roots", api_scope)
+            .handle_compare_table_roots("v1/forensics/compare", api_scope)
+            .handle_evict_transaction("v1/mempool/transactions/{hash}", api_scope)
+            .handle_chaos_hooks("v1/chaos", api_scope);
         api_scope
+            .restrict("v1/peers", ApiRole::Admin)
+            .restrict("v1/consensus_enabled", ApiRole::Admin)
+            .restrict("v1/shutdown", ApiRole::Admin)
+            .restrict("v1/flush", ApiRole::Admin)
+            .restrict("v1/compact", ApiRole::Admin)
+            .restrict("v1/checkpoint", ApiRole::Admin)
+            .restrict("v1/chaos", ApiRole::Admin)
+            .restrict("v1/mempool/transactions/{hash}", ApiRole::Admin)
+            .restrict_read("v1/consensus/messages", ApiRole::Admin)
     }
 
     fn handle_peers_info(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
@@ -184,6 +335,51 @@ impl SystemApi {
         self_
     }
 
+    fn handle_network_latency(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
+        let self_ = self.clone();
+        api_scope.endpoint(name, move |_state: &ServiceApiState, _query: ()| {
+            let mut latencies = self.shared_api_state.peer_latencies();
+            latencies.sort_by_key(|latency| (latency.validator_id.is_none(), latency.validator_id));
+            Ok(latencies)
+        });
+        self_
+    }
+
+    fn handle_network_clock_skew(
+        self,
+        name: &'static str,
+        api_scope: &mut ServiceApiScope,
+    ) -> Self {
+        let self_ = self.clone();
+        api_scope.endpoint(name, move |_state: &ServiceApiState, _query: ()| {
+            let mut skews = self.shared_api_state.peer_clock_skews();
+            skews.sort_by_key(|skew| (skew.validator_id.is_none(), skew.validator_id));
+            Ok(skews)
+        });
+        self_
+    }
+
+    fn handle_network_sync(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
+        let self_ = self.clone();
+        api_scope.endpoint(name, move |state: &ServiceApiState, _query: ()| {
+            let height = state.blockchain().last_block().height().next();
+            let mut peers = self
+                .shared_api_state
+                .peer_statuses()
+                .into_iter()
+                .map(|status| PeerSyncInfo {
+                    public_key: status.public_key,
+                    validator_id: status.validator_id,
+                    height: status.height,
+                    lag: height.0 as i64 - status.height.0 as i64,
+                    last_status_time: status.time,
+                }).collect::<Vec<_>>();
+            peers.sort_by_key(|peer| (peer.validator_id.is_none(), peer.validator_id));
+            Ok(peers)
+        });
+        self_
+    }
+
     fn handle_is_consensus_enabled(
         self,
         name: &'static str,
@@ -233,4 +429,260 @@ impl SystemApi {
         });
         self
     }
+
+    fn handle_flush(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
+        api_scope.endpoint_mut(name, move |state: &ServiceApiState, _query: ()| {
+            state
+                .blockchain()
+                .flush()
+                .map_err(|e| ApiError::InternalError(e.into()))
+        });
+        self
+    }
+
+    /// Compacts the storage in a separate thread, so that the API worker is not blocked.
+    /// The response is sent once the compaction is finished.
+    fn handle_compact(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
+        api_scope.endpoint_mut(
+            name,
+            move |state: &ServiceApiState, query: CompactQuery| -> FutureResult<()> {
+                let result =
+                    maintenance::spawn_compaction(state.blockchain().clone(), query.prefixes);
+                Box::new(result.then(|result| match result {
+                    Ok(result) => result.map_err(|e| ApiError::InternalError(e.into())),
+                    Err(_) => Err(ApiError::InternalError(format_err!(
+                        "Compaction thread has stopped unexpectedly"
+                    ))),
+                }))
+            },
+        );
+        self
+    }
+
+    /// Writes a checkpoint of the database and returns the height of the latest block in it.
+    fn handle_checkpoint(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
+        api_scope.endpoint_mut(
+            name,
+            move |state: &ServiceApiState, query: CheckpointQuery| {
+                state
+                    .blockchain()
+                    .checkpoint(&query.path)
+                    .map_err(|e| ApiError::InternalError(e.into()))
+            },
+        );
+        self
+    }
+
+    fn handle_consensus_messages(
+        self,
+        name: &'static str,
+        api_scope: &mut ServiceApiScope,
+    ) -> Self {
+        api_scope.endpoint(
+            name,
+            move |state: &ServiceApiState, query: ConsensusMessagesQuery| {
+                let snapshot = state.snapshot();
+                let schema = Schema::new(&snapshot);
+                let messages = schema
+                    .consensus_archive(query.height)
+                    .iter()
+                    .filter_map(|msg| match msg {
+                        Message::Consensus(msg) => Some(msg),
+                        _ => None,
+                    }).filter(|msg| query.round.map_or(true, |round| msg.round() == round))
+                    .filter(|msg| {
+                        query
+                            .validator
+                            .map_or(true, |validator| msg.validator() == validator)
+                    }).map(ArchivedMessage::from)
+                    .collect::<Vec<_>>();
+                Ok(messages)
+            },
+        );
+        self
+    }
+
+    fn handle_mempool_transactions(
+        self,
+        name: &'static str,
+        api_scope: &mut ServiceApiScope,
+    ) -> Self {
+        api_scope.endpoint(name, move |state: &ServiceApiState, query: MempoolQuery| {
+            let snapshot = state.snapshot();
+            let schema = Schema::new(&snapshot);
+            let transactions = schema.transactions();
+            let pool = schema
+                .transactions_pool()
+                .iter()
+                .filter_map(|tx_hash| transactions.get(&tx_hash))
+                .filter(|tx| query.author.map_or(true, |author| tx.author() == author))
+                .map(|tx| MempoolTransaction {
+                    tx_hash: tx.hash(),
+                    author: tx.author(),
+                    service_id: tx.payload().service_id(),
+                    size: tx.signed_message().raw().len(),
+                }).collect::<Vec<_>>();
+            Ok(pool)
+        });
+        self
+    }
+
+    fn handle_consumers(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
+        api_scope.endpoint(name, move |state: &ServiceApiState, _query: ()| {
+            let stats = state
+                .consumers()
+                .map(|consumers| consumers.stats(Instant::now()))
+                .unwrap_or_default();
+            Ok(stats)
+        });
+        self
+    }
+
+    fn handle_storage_stats(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
+        api_scope.endpoint(name, move |state: &ServiceApiState, _query: ()| {
+            let snapshot = state.snapshot();
+            let schema = Schema::new(&snapshot);
+            let mut stats = schema
+                .storage_stats()
+                .iter()
+                .map(|(name, stats)| IndexStatsInfo {
+                    name,
+                    entries: stats.entries(),
+                    bytes: stats.bytes(),
+                    modified_at: stats.modified_at(),
+                }).collect::<Vec<_>>();
+            // The largest indexes go first.
+            stats.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+            Ok(stats)
+        });
+        self
+    }
+
+    fn handle_divergence_report(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
+        let self_ = self.clone();
+        api_scope.endpoint(name, move |_state: &ServiceApiState, _query: ()| {
+            Ok(self.shared_api_state.divergence_report())
+        });
+        self_
+    }
+
+    fn handle_table_roots(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
+        let self_ = self.clone();
+        api_scope.endpoint(
+            name,
+            move |_state: &ServiceApiState, query: TableRootsQuery| {
+                self.shared_api_state
+                    .table_roots(query.height)
+                    .ok_or_else(|| {
+                        ApiError::NotFound(
+                            "Table root hashes are not kept for the requested height".to_owned(),
+                        )
+                    })
+            },
+        );
+        self_
+    }
+
+    /// Compares the table root hashes provided by a peer with the ones from
+    /// the divergence report and returns the differing tables.
+    fn handle_compare_table_roots(
+        self,
+        name: &'static str,
+        api_scope: &mut ServiceApiScope,
+    ) -> Self {
+        let self_ = self.clone();
+        api_scope.endpoint_mut(
+            name,
+            move |_state: &ServiceApiState, peer_roots: TableRoots| {
+                Self::compare_table_roots(&self.shared_api_state, &peer_roots)
+            },
+        );
+        self_
+    }
+
+    fn compare_table_roots(
+        shared_api_state: &SharedNodeState,
+        peer_roots: &TableRoots,
+    ) -> Result<Vec<TableRootDiff>, ApiError> {
+        let report = shared_api_state
+            .divergence_report()
+            .ok_or_else(|| ApiError::BadRequest("No state divergence is captured".to_owned()))?;
+        if report.height != peer_roots.height {
+            return Err(ApiError::BadRequest(format!(
+                "State divergence is captured at height {}, while table root hashes \
+                 are provided for height {}",
+                report.height, peer_roots.height
+            )));
+        }
+        Ok(report.compare_roots(&peer_roots.roots))
+    }
+
+    /// Evicts the transaction with the hash specified in the path from the pool.
+    /// The transaction is not evicted if it is already included into a proposal.
+    fn handle_evict_transaction(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
+        let index = move |request: HttpRequest| -> FutureResponse {
+            let response = Self::evict_transaction(&request).map(|()| HttpResponse::Ok().json(()));
+            Box::new(future::result(response).from_err())
+        };
+
+        api_scope.web_backend().raw_handler(RequestHandler {
+            name: name.to_owned(),
+            method: Method::DELETE,
+            inner: Arc::from(index) as Arc<RawHandler>,
+        });
+        self
+    }
+
+    fn evict_transaction(request: &HttpRequest) -> Result<(), ApiError> {
+        let tx_hash = request
+            .match_info()
+            .query::<Hash>("hash")
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        let state = request.state();
+        let snapshot = state.snapshot();
+        if !Schema::new(&snapshot).transactions_pool().contains(&tx_hash) {
+            return Err(ApiError::NotFound(format!(
+                "Transaction {:?} is not in the pool",
+                tx_hash
+            )));
+        }
+        state
+            .sender()
+            .send_external_message(ExternalMessage::EvictTransaction(tx_hash))
+            .map_err(ApiError::from)
+    }
+
+    /// Adds the endpoints of the chaos testing hooks, which are available only
+    /// in debug builds.
+    #[cfg(debug_assertions)]
+    fn handle_chaos_hooks(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
+        self.handle_chaos(name, api_scope)
+            .handle_set_chaos(name, api_scope)
+    }
+
+    #[cfg(not(debug_assertions))]
+    fn handle_chaos_hooks(self, _name: &'static str, _api_scope: &mut ServiceApiScope) -> Self {
+        self
+    }
+
+    #[cfg(debug_assertions)]
+    fn handle_chaos(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
+        let self_ = self.clone();
+        api_scope.endpoint(name, move |_state: &ServiceApiState, _query: ()| {
+            Ok(self.shared_api_state.chaos())
+        });
+        self_
+    }
+
+    #[cfg(debug_assertions)]
+    fn handle_set_chaos(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
+        api_scope.endpoint_mut(name, move |state: &ServiceApiState, config: ChaosConfig| {
+            config.validate().map_err(ApiError::BadRequest)?;
+            state
+                .sender()
+                .send_external_message(ExternalMessage::Chaos(config))
+                .map_err(ApiError::from)
+        });
+        self
+    }
 }
@@ -15,29 +15,58 @@
 //! Exonum blockchain explorer API.
 
 use actix::Arbiter;
-use actix_web::{http, ws};
+use actix_web::{http, ws, FromRequest, HttpResponse, Query};
+use bytes::Bytes;
 use chrono::{DateTime, Utc};
-use futures::IntoFuture;
-use serde_json;
+use futures::{future, stream, Future, IntoFuture};
+use serde_json::{self, Value};
 
+use std::cmp;
+use std::collections::VecDeque;
 use std::ops::Range;
 use std::sync::{Arc, Mutex};
 
 use api::{
-    backends::actix::{self, FutureResponse, HttpRequest, RawHandler, RequestHandler},
+    backends::actix::{
+        self, FutureResponse, HttpRequest, RawHandler, RequestHandler, ResponseFormat,
+    },
+    pagination,
     websocket::{Server, Session},
     Error as ApiError, ServiceApiBackend, ServiceApiScope, ServiceApiState,
 };
-use blockchain::{Block, SharedNodeState};
-use crypto::Hash;
-use explorer::{self, BlockchainExplorer, TransactionInfo};
+use blockchain::{
+    archive, offchain, Block, ConsensusConfig, Schema, SharedNodeState, StoredConfiguration,
+    TxLocation, ValidatorKeys,
+};
+use crypto::{CryptoHash, Hash, PublicKey};
+use explorer::{self, BlockchainExplorer, Cursor, TransactionInfo};
 use helpers::Height;
 use messages::{Message, Precommit, RawTransaction, Signed, SignedMessage};
+use storage::{ListProof, Snapshot};
 
 /// The maximum number of blocks to return per blocks request, in this way
 /// the parameter limits the maximum execution time for such requests.
+/// Applies to the responses which are not streamed, i.e., to the CBOR and MessagePack
+/// responses of the `v1/blocks` endpoint and to the GraphQL queries.
 pub const MAX_BLOCKS_PER_REQUEST: usize = 1000;
 
+/// The maximum number of blocks to return per blocks request with a JSON response,
+/// which is streamed.
+pub const MAX_STREAMED_BLOCKS: usize = 100_000;
+
+/// The maximum number of transactions to return per block transactions request.
+pub const MAX_TRANSACTIONS_PER_REQUEST: usize = 1000;
+
+/// The maximum number of blocks scanned per committed transactions request.
+/// Bounds the execution time of the requests over the blocks without transactions.
+pub const MAX_SCANNED_BLOCKS_PER_REQUEST: usize = 1000;
+
+/// The number of blocks serialized into a single chunk of the streamed response.
+const BLOCKS_PER_CHUNK: usize = 100;
+
+/// The maximum number of encoded archive segments kept in memory.
+const MAX_CACHED_SEGMENTS: usize = 4;
+
 /// Information on blocks coupled with the corresponding range in the blockchain.
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct BlocksRange {
@@ -47,6 +76,9 @@ pub struct BlocksRange {
     pub blocks: Vec<Block>,
     /// Optional median time from the corresponding blocks precommits.
     pub times: Option<Vec<DateTime<Utc>>>,
+    /// Cursor to pass in the next request to get the following page of blocks,
+    /// or `None` if the genesis block has been reached.
+    pub next_cursor: Option<Cursor>,
 }
 
 /// Information about a block in the blockchain.
@@ -62,15 +94,54 @@ pub struct BlockInfo {
     pub time: DateTime<Utc>,
 }
 
+/// Transactions from a contiguous range within a block, together with a single proof
+/// of their inclusion into the block.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct BlockTransactions {
+    /// Block header as recorded in the blockchain.
+    pub block: Block,
+    /// Precommits authorizing the block.
+    pub precommits: Vec<Signed<Precommit>>,
+    /// Proof of inclusion of the transaction hashes in the requested range
+    /// into the block, checked against the `tx_hash` of the block header.
+    pub proof: ListProof<Hash>,
+    /// Transactions in the requested range.
+    pub transactions: Vec<Signed<RawTransaction>>,
+}
+
+/// Committed transaction together with its location in the blockchain.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct LocatedTransaction {
+    /// Location of the transaction in the blockchain.
+    pub location: TxLocation,
+    /// Transaction message.
+    pub content: Signed<RawTransaction>,
+}
+
+/// Committed transactions in the reverse order of their commitment.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct TransactionsRange {
+    /// Transactions with their locations in the blockchain.
+    pub transactions: Vec<LocatedTransaction>,
+    /// Cursor to pass in the next request to get the following page of transactions,
+    /// or `None` if there are no more transactions.
+    pub next_cursor: Option<Cursor>,
+}
+
 /// Blocks in range parameters.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
 pub struct BlocksQuery {
-    /// The number of blocks to return. Should not be greater than `MAX_BLOCKS_PER_REQUEST`.
+    /// The number of blocks to return. Should not be greater than `MAX_STREAMED_BLOCKS`
+    /// for JSON responses and `MAX_BLOCKS_PER_REQUEST` for other formats.
     pub count: usize,
     /// The maximum height of the returned blocks. The blocks are returned in reverse order,
     /// starting from the latest and at least up to the `latest` - `count` + 1.
     /// The default value is the height of the latest block in the blockchain.
     pub latest: Option<Height>,
+    /// Cursor obtained from the `next_cursor` field of the previous response. Unlike
+    /// `latest`, the cursor guarantees that no blocks are skipped or repeated between pages.
+    /// Cannot be used together with `latest`.
+    pub cursor: Option<Cursor>,
     /// If true, then only non-empty blocks are returned. The default value is false.
     #[serde(default)]
     pub skip_empty_blocks: bool,
@@ -94,6 +165,30 @@ impl BlockQuery {
     }
 }
 
+/// Block transactions query parameters.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct BlockTransactionsQuery {
+    /// The height of the block.
+    pub height: Height,
+    /// Index of the first returned transaction within the block. The default value is 0.
+    #[serde(default)]
+    pub from: u64,
+    /// Index of the transaction following the last returned one. The default value
+    /// is the number of transactions in the block.
+    pub to: Option<u64>,
+}
+
+/// Committed transactions query parameters.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct TransactionsQuery {
+    /// The number of transactions to return. Should not be greater than
+    /// `MAX_TRANSACTIONS_PER_REQUEST`.
+    pub count: usize,
+    /// Cursor obtained from the `next_cursor` field of the previous response.
+    /// By default, the transactions are returned starting from the latest committed one.
+    pub cursor: Option<Cursor>,
+}
+
 /// Raw Transaction in hex representation.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TransactionHex {
@@ -108,6 +203,27 @@ pub struct TransactionResponse {
     pub tx_hash: Hash,
 }
 
+/// Human-readable representation of a serialized transaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DecodedTransaction {
+    /// Hash of the transaction.
+    pub tx_hash: Hash,
+    /// Public key of the transaction author.
+    pub author: PublicKey,
+    /// `true` if the transaction signature is valid.
+    pub signature_valid: bool,
+    /// Identifier of the service the transaction belongs to.
+    pub service_id: u16,
+    /// Name of the service, or `None` if the service is unknown.
+    pub service_name: Option<String>,
+    /// Identifier of the transaction type within the service.
+    pub message_id: u16,
+    /// Name of the transaction type, or `None` if it is unknown.
+    pub message_type: Option<String>,
+    /// Transaction fields in JSON representation.
+    pub content: Value,
+}
+
 /// Transaction query parameters.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub struct TransactionQuery {
@@ -122,6 +238,71 @@ impl TransactionQuery {
     }
 }
 
+/// Query parameters for the off-chain content of a transaction.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct OffchainContentQuery {
+    /// The hash of the transaction referencing the content.
+    pub hash: Hash,
+    /// Index of the reference in the list returned by `Transaction::offchain_refs`.
+    #[serde(default)]
+    pub index: usize,
+}
+
+/// Off-chain content referenced by a transaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OffchainContent {
+    /// Hash of the content.
+    pub content_hash: Hash,
+    /// URI of the content in the off-chain storage.
+    pub uri: String,
+    /// The content in hex representation, verified against `content_hash`.
+    pub data: String,
+}
+
+/// Validator set query parameters.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct ValidatorsQuery {
+    /// The height for which the validator set is requested. The default value is
+    /// the height of the latest block in the blockchain.
+    pub height: Option<Height>,
+}
+
+impl ValidatorsQuery {
+    /// Creates a new validator set query with the given height.
+    pub fn new(height: Height) -> Self {
+        Self {
+            height: Some(height),
+        }
+    }
+}
+
+/// Validator set and consensus parameters active at a certain height.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ValidatorsInfo {
+    /// The height for which the information is returned.
+    pub height: Height,
+    /// The height, starting from which the configuration is active.
+    pub actual_from: Height,
+    /// Hash of the configuration.
+    pub config_hash: Hash,
+    /// Consensus and service public keys of the validators.
+    pub validator_keys: Vec<ValidatorKeys>,
+    /// Consensus algorithm parameters.
+    pub consensus: ConsensusConfig,
+}
+
+impl ValidatorsInfo {
+    fn new(height: Height, config: StoredConfiguration) -> Self {
+        Self {
+            height,
+            actual_from: config.actual_from,
+            config_hash: config.hash(),
+            validator_keys: config.validator_keys,
+            consensus: config.consensus,
+        }
+    }
+}
+
 /// Exonum blockchain explorer API.
 #[derive(Debug, Clone, Copy)]
 pub struct ExplorerApi;
@@ -131,17 +312,21 @@ impl ExplorerApi {
     /// and largest heights traversed to collect the number of blocks specified in
     /// the [`BlocksQuery`] struct.
     ///
+    /// To get the following page, pass the returned `next_cursor` as the `cursor` parameter
+    /// of the next request.
+    ///
+    /// The `v1/blocks` endpoint uses this method only for the responses in binary formats;
+    /// JSON responses are streamed by [`handle_blocks`].
+    ///
     /// [`BlocksQuery`]: struct.BlocksQuery.html
+    /// [`handle_blocks`]: #method.handle_blocks
     pub fn blocks(state: &ServiceApiState, query: BlocksQuery) -> Result<BlocksRange, ApiError> {
         let explorer = BlockchainExplorer::new(state.blockchain());
-        if query.count > MAX_BLOCKS_PER_REQUEST {
-            return Err(ApiError::BadRequest(format!(
-                "Max block count per request exceeded ({})",
-                MAX_BLOCKS_PER_REQUEST
-            )));
-        }
+        pagination::check_limit(query.count, MAX_BLOCKS_PER_REQUEST)?;
+        pagination::check_exclusive(("cursor", &query.cursor), ("latest", &query.latest))?;
 
-        let (upper, blocks_iter) = if let Some(upper) = query.latest {
+        let latest = query.cursor.map(|cursor| cursor.height).or(query.latest);
+        let (upper, blocks_iter) = if let Some(upper) = latest {
             (upper, explorer.blocks(..upper.next()))
         } else {
             (explorer.height(), explorer.blocks(..))
@@ -154,11 +339,18 @@ impl ExplorerApi {
             .filter(|block| !query.skip_empty_blocks || !block.is_empty())
             .take(query.count)
             .inspect(|block| {
-                if query.add_blocks_time {
+                if query.add_blocks_time && !explorer.is_pruned(block.height()) {
                     times.push(median_precommits_time(&block.precommits()));
                 }
             }).map(|block| block.into_header())
             .collect();
+        if query.add_blocks_time && times.len() < blocks.len() {
+            return Err(ApiError::Gone(format!(
+                "Precommits of the blocks below height {} are pruned, the time of the blocks \
+                 is not available",
+                explorer.pruned_height()
+            )));
+        }
 
         let height = if blocks.len() < query.count {
             Height(0)
@@ -166,6 +358,8 @@ impl ExplorerApi {
             blocks.last().map_or(Height(0), |block| block.height())
         };
 
+        let next_cursor = height.checked_previous().map(Cursor::at_height);
+
         Ok(BlocksRange {
             range: height..upper.next(),
             blocks,
@@ -174,6 +368,7 @@ impl ExplorerApi {
             } else {
                 None
             },
+            next_cursor,
         })
     }
 
@@ -182,9 +377,164 @@ impl ExplorerApi {
         state: &ServiceApiState,
         query: BlockQuery,
     ) -> Result<Option<BlockInfo>, ApiError> {
-        Ok(BlockchainExplorer::new(state.blockchain())
-            .block(query.height)
-            .map(From::from))
+        let explorer = BlockchainExplorer::new(state.blockchain());
+        if explorer.is_pruned(query.height) {
+            return Err(ApiError::Gone(format!(
+                "Block at height {} is pruned",
+                query.height
+            )));
+        }
+        Ok(explorer.block(query.height).map(From::from))
+    }
+
+    /// Returns the transactions with indexes in the `from..to` range within the block
+    /// at the given height, together with a single proof of their inclusion into the block.
+    ///
+    /// Light clients requesting many transactions from a block should prefer this endpoint
+    /// to the `v1/transactions` one, which returns a separate proof for each transaction.
+    pub fn block_transactions(
+        state: &ServiceApiState,
+        query: BlockTransactionsQuery,
+    ) -> Result<BlockTransactions, ApiError> {
+        let explorer = BlockchainExplorer::new(state.blockchain());
+        if explorer.is_pruned(query.height) {
+            return Err(ApiError::Gone(format!(
+                "Block at height {} is pruned",
+                query.height
+            )));
+        }
+        let block = explorer.block(query.height).ok_or_else(|| {
+            ApiError::NotFound(format!("Block at height {} not found", query.height))
+        })?;
+
+        let len = block.len() as u64;
+        let to = query.to.unwrap_or(len);
+        if query.from >= to || to > len {
+            return Err(ApiError::BadRequest(format!(
+                "Invalid range of transactions {}..{}, the block contains {} transactions",
+                query.from, to, len
+            )));
+        }
+        pagination::check_limit((to - query.from) as usize, MAX_TRANSACTIONS_PER_REQUEST)?;
+
+        let schema = Schema::new(state.snapshot());
+        let transactions = schema.transactions();
+        let transactions = block.transaction_hashes()[query.from as usize..to as usize]
+            .iter()
+            .map(|tx_hash| {
+                transactions
+                    .get(tx_hash)
+                    .expect("BUG: Committed transaction not found")
+            }).collect();
+
+        let proof = block.transaction_range_proof(query.from, to);
+        let precommits = block.precommits().to_vec();
+        Ok(BlockTransactions {
+            block: block.into_header(),
+            precommits,
+            proof,
+            transactions,
+        })
+    }
+
+    /// Returns committed transactions in the reverse order of their commitment, starting
+    /// from the transaction the cursor points to, or from the latest committed transaction
+    /// if the cursor is not specified.
+    ///
+    /// To get the following page, pass the returned `next_cursor` as the `cursor` parameter
+    /// of the next request. Transactions committed in between the requests do not affect
+    /// the following pages. Transactions from the pruned blocks are not returned.
+    ///
+    /// At most `MAX_SCANNED_BLOCKS_PER_REQUEST` blocks are scanned per request, so
+    /// the page may contain fewer transactions than requested even if `next_cursor`
+    /// is specified.
+    pub fn transactions(
+        state: &ServiceApiState,
+        query: TransactionsQuery,
+    ) -> Result<TransactionsRange, ApiError> {
+        pagination::check_limit(query.count, MAX_TRANSACTIONS_PER_REQUEST)?;
+
+        let snapshot = state.snapshot();
+        let schema = Schema::new(&snapshot);
+        let height = schema.height();
+        let cursor = query
+            .cursor
+            .unwrap_or_else(|| Cursor::new(height, u64::max_value()));
+        if cursor.height > height {
+            return Err(ApiError::BadRequest(format!(
+                "Cursor points to the block at height {}, which is not committed yet",
+                cursor.height
+            )));
+        }
+
+        let pruned_height = schema.pruned_height();
+        let contents = schema.transactions();
+        let mut transactions = Vec::new();
+        let mut next_cursor = None;
+        let mut block_height = Some(cursor.height);
+        let mut scanned_blocks = 0;
+        'blocks: while let Some(current) = block_height {
+            if current < pruned_height {
+                break;
+            }
+            if scanned_blocks == MAX_SCANNED_BLOCKS_PER_REQUEST {
+                next_cursor = Some(Cursor::new(current, u64::max_value()));
+                break;
+            }
+            scanned_blocks += 1;
+            let tx_hashes = schema.block_transactions(current);
+            let end = if current == cursor.height {
+                cmp::min(cursor.index.saturating_add(1), tx_hashes.len())
+            } else {
+                tx_hashes.len()
+            };
+            for index in (0..end).rev() {
+                if transactions.len() == query.count {
+                    next_cursor = Some(Cursor::new(current, index));
+                    break 'blocks;
+                }
+                let tx_hash = tx_hashes
+                    .get(index)
+                    .expect("BUG: Transaction hash not found in the block");
+                let content = contents
+                    .get(&tx_hash)
+                    .expect("BUG: Committed transaction not found");
+                transactions.push(LocatedTransaction {
+                    location: TxLocation::new(current, index),
+                    content,
+                });
+            }
+            block_height = current.checked_previous();
+        }
+
+        Ok(TransactionsRange {
+            transactions,
+            next_cursor,
+        })
+    }
+
+    /// Returns the validator set and consensus parameters active at the given height.
+    ///
+    /// Light clients can use the returned keys to verify precommits of the blocks
+    /// committed before the validator set has been changed.
+    pub fn validators(
+        state: &ServiceApiState,
+        query: ValidatorsQuery,
+    ) -> Result<ValidatorsInfo, ApiError> {
+        let snapshot = state.snapshot();
+        let schema = Schema::new(&snapshot);
+        let next_height = schema.height().next();
+        let height = query.height.unwrap_or_else(|| schema.height());
+        if height > next_height {
+            return Err(ApiError::BadRequest(format!(
+                "Requested height {} is greater than the next height of the blockchain {}",
+                height, next_height
+            )));
+        }
+        Ok(ValidatorsInfo::new(
+            height,
+            schema.configuration_by_height(height),
+        ))
     }
 
     /// Searches for a transaction, either committed or uncommitted, by the hash.
@@ -192,13 +542,18 @@ impl ExplorerApi {
         state: &ServiceApiState,
         query: TransactionQuery,
     ) -> Result<TransactionInfo, ApiError> {
-        BlockchainExplorer::new(state.blockchain())
-            .transaction(&query.hash)
-            .ok_or_else(|| {
-                let description = serde_json::to_string(&json!({ "type": "unknown" })).unwrap();
-                debug!("{}", description);
-                ApiError::NotFound(description)
-            })
+        let explorer = BlockchainExplorer::new(state.blockchain());
+        explorer.transaction(&query.hash).ok_or_else(|| {
+            if explorer.is_transaction_pruned(&query.hash) {
+                return ApiError::Gone(format!(
+                    "Transaction {:?} is committed in a pruned block",
+                    query.hash
+                ));
+            }
+            let description = serde_json::to_string(&json!({ "type": "unknown" })).unwrap();
+            debug!("{}", description);
+            ApiError::NotFound(description)
+        })
     }
     /// Adds transaction into unconfirmed tx pool, and broadcast transaction to other nodes.
     pub fn add_transaction(
@@ -213,6 +568,15 @@ impl ExplorerApi {
         let tx_hash = signed.hash();
         let signed = RawTransaction::try_from(Message::deserialize(signed)?)
             .map_err(|_| format_err!("Couldn't deserialize transaction message."))?;
+        if let Some(fetcher) = state.blockchain().offchain_fetcher() {
+            // Transactions that cannot be parsed are rejected later by the node itself.
+            if let Ok(transaction) = state.blockchain().tx_from_raw(signed.payload().clone()) {
+                for reference in transaction.offchain_refs() {
+                    offchain::fetch_verified(fetcher.as_ref(), &reference)
+                        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+                }
+            }
+        }
         let _ = state
             .sender()
             .broadcast_transaction(signed)
@@ -220,6 +584,90 @@ impl ExplorerApi {
         Ok(TransactionResponse { tx_hash })
     }
 
+    /// Decodes the serialized transaction without broadcasting it.
+    pub fn decode_transaction(
+        state: &ServiceApiState,
+        query: TransactionHex,
+    ) -> Result<DecodedTransaction, ApiError> {
+        use messages::ProtocolMessage;
+
+        let buf: Vec<u8> =
+            ::hex::decode(query.tx_body).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        let (signed, signature_valid) = SignedMessage::from_raw_buffer_unverified(buf)
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        let tx_hash = signed.hash();
+        let signed = Message::deserialize(signed)
+            .ok()
+            .and_then(|message| RawTransaction::try_from(message).ok())
+            .ok_or_else(|| ApiError::BadRequest("Message is not a transaction.".to_owned()))?;
+
+        let raw = signed.payload().clone();
+        let service_id = raw.service_id();
+        let (message_id, _) = raw.clone().service_transaction().into_raw_parts();
+        let service = state.blockchain().service_registry().get(service_id);
+        let message_type = service.and_then(|service| {
+            service
+                .transactions
+                .iter()
+                .find(|tx| tx.id == message_id)
+                .map(|tx| tx.name.clone())
+        });
+        let transaction = state
+            .blockchain()
+            .tx_from_raw(raw)
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        let content =
+            serde_json::to_value(&transaction).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+        Ok(DecodedTransaction {
+            tx_hash,
+            author: signed.author(),
+            signature_valid,
+            service_id,
+            service_name: service.map(|service| service.name.clone()),
+            message_id,
+            message_type,
+            content,
+        })
+    }
+
+    /// Retrieves the off-chain content referenced by a known transaction with the fetcher
+    /// of the node and checks it against the hash committed in the transaction.
+    pub fn offchain_content(
+        state: &ServiceApiState,
+        query: OffchainContentQuery,
+    ) -> Result<OffchainContent, ApiError> {
+        let fetcher = state.blockchain().offchain_fetcher().ok_or_else(|| {
+            ApiError::BadRequest("Off-chain content fetcher is not configured".to_owned())
+        })?;
+        let raw = Schema::new(state.snapshot())
+            .transactions()
+            .get(&query.hash)
+            .ok_or_else(|| ApiError::NotFound(format!("Transaction {:?} not found", query.hash)))?;
+        let transaction = state
+            .blockchain()
+            .tx_from_raw(raw.payload().clone())
+            .map_err(|e| ApiError::InternalError(format_err!("{}", e)))?;
+        let reference = transaction
+            .offchain_refs()
+            .into_iter()
+            .nth(query.index)
+            .ok_or_else(|| {
+                ApiError::NotFound(format!(
+                    "Transaction {:?} has no off-chain reference with index {}",
+                    query.hash, query.index
+                ))
+            })?;
+
+        let data = offchain::fetch_verified(fetcher.as_ref(), &reference)
+            .map_err(|e| ApiError::InternalError(e.into()))?;
+        Ok(OffchainContent {
+            content_hash: *reference.content_hash(),
+            uri: reference.uri().to_owned(),
+            data: ::hex::encode(data),
+        })
+    }
+
     /// Subscribes to block commits events.
     pub fn handle_subscribe(
         name: &'static str,
@@ -250,23 +698,291 @@ impl ExplorerApi {
         });
     }
 
+    /// Serves the segment of the block archive with the number specified in the path.
+    /// Only complete segments are served, so that the response for a segment never changes
+    /// and the recently requested segments can be cached.
+    pub fn handle_archive(name: &'static str, backend: &mut actix::ApiBuilder) {
+        let cache = Arc::new(Mutex::new(VecDeque::with_capacity(MAX_CACHED_SEGMENTS)));
+        let index = move |request: HttpRequest| -> FutureResponse {
+            let response = Self::archive_segment(&request, &cache).map(|archive| {
+                HttpResponse::Ok()
+                    .content_type("application/octet-stream")
+                    .body(archive)
+            });
+            Box::new(future::result(response).from_err())
+        };
+
+        backend.raw_handler(RequestHandler {
+            name: name.to_owned(),
+            method: http::Method::GET,
+            inner: Arc::from(index) as Arc<RawHandler>,
+        });
+    }
+
+    fn archive_segment(
+        request: &HttpRequest,
+        cache: &Mutex<VecDeque<(u64, Bytes)>>,
+    ) -> Result<Bytes, ApiError> {
+        let segment = request
+            .match_info()
+            .query::<u64>("segment")
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        let snapshot = request.state().blockchain().snapshot();
+        if segment >= archive::complete_segments(&snapshot) {
+            return Err(ApiError::NotFound(format!(
+                "Archive segment {} is not complete yet",
+                segment
+            )));
+        }
+        if archive::is_pruned_segment(&snapshot, segment) {
+            return Err(ApiError::Gone(format!(
+                "Archive segment {} is pruned",
+                segment
+            )));
+        }
+
+        let cached = cache
+            .lock()
+            .expect("Expected mutex lock")
+            .iter()
+            .find(|&&(number, _)| number == segment)
+            .map(|&(_, ref archive)| archive.clone());
+        if let Some(archive) = cached {
+            return Ok(archive);
+        }
+
+        let archive = Bytes::from(archive::write_segment(&snapshot, segment, Vec::new())?);
+        let mut cache = cache.lock().expect("Expected mutex lock");
+        if cache.len() == MAX_CACHED_SEGMENTS {
+            cache.pop_front();
+        }
+        cache.push_back((segment, archive.clone()));
+        Ok(archive)
+    }
+
+    /// Serves the [`blocks`] endpoint. JSON responses are sent with the chunked transfer
+    /// encoding and the blocks are serialized lazily, so that large ranges are never held
+    /// in memory. Responses in other formats are built by [`blocks`].
+    ///
+    /// [`blocks`]: #method.blocks
+    pub fn handle_blocks(name: &'static str, backend: &mut actix::ApiBuilder) {
+        let index = move |request: HttpRequest| -> FutureResponse {
+            Box::new(future::result(Self::blocks_response(&request)))
+        };
+
+        backend.raw_handler(RequestHandler {
+            name: name.to_owned(),
+            method: http::Method::GET,
+            inner: Arc::from(index) as Arc<RawHandler>,
+        });
+    }
+
+    fn blocks_response(request: &HttpRequest) -> Result<HttpResponse, ::actix_web::Error> {
+        let query = Query::<BlocksQuery>::extract(request)
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?
+            .into_inner();
+        let format = ResponseFormat::from_request(request);
+        if format != ResponseFormat::Json {
+            let range = Self::blocks(request.state(), query)?;
+            return format.response(&range);
+        }
+
+        let chunks = Self::blocks_chunks(request.state(), query)?;
+        Ok(HttpResponse::Ok()
+            .content_type("application/json")
+            .chunked()
+            .streaming(stream::iter_ok::<_, ::actix_web::Error>(chunks)))
+    }
+
+    fn blocks_chunks(
+        state: &ServiceApiState,
+        query: BlocksQuery,
+    ) -> Result<BlocksChunks, ApiError> {
+        pagination::check_limit(query.count, MAX_STREAMED_BLOCKS)?;
+        pagination::check_exclusive(("cursor", &query.cursor), ("latest", &query.latest))?;
+
+        let snapshot = state.blockchain().snapshot();
+        let (upper, first) = {
+            let schema = Schema::new(&snapshot);
+            let height = schema.height();
+            let upper = query
+                .cursor
+                .map(|cursor| cursor.height)
+                .or(query.latest)
+                .unwrap_or(height);
+            let first = cmp::min(upper, height);
+            if query.add_blocks_time && reaches_pruned_blocks(&schema, first, &query) {
+                return Err(ApiError::Gone(format!(
+                    "Precommits of the blocks below height {} are pruned, the time of the blocks \
+                     is not available",
+                    schema.pruned_height()
+                )));
+            }
+            (upper, first)
+        };
+
+        Ok(BlocksChunks {
+            snapshot,
+            upper,
+            next_height: Some(first),
+            count: query.count,
+            returned: 0,
+            last_height: None,
+            skip_empty_blocks: query.skip_empty_blocks,
+            times: if query.add_blocks_time {
+                Some(Vec::new())
+            } else {
+                None
+            },
+            started: false,
+            finished: false,
+        })
+    }
+
+    /// Adds private explorer API endpoints to the corresponding scope.
+    pub fn wire_private(api_scope: &mut ServiceApiScope) -> &mut ServiceApiScope {
+        Self::handle_archive("v1/archive/{segment}", api_scope.web_backend());
+        api_scope
+    }
+
     /// Adds explorer API endpoints to the corresponding scope.
     pub fn wire(
         api_scope: &mut ServiceApiScope,
         service_api_state: ServiceApiState,
         shared_node_state: SharedNodeState,
     ) -> &mut ServiceApiScope {
+        let node_state = shared_node_state.clone();
         Self::handle_subscribe(
             "v1/blocks/subscribe",
             api_scope.web_backend(),
             service_api_state,
             shared_node_state,
         );
+        Self::handle_blocks("v1/blocks", api_scope.web_backend());
         api_scope
-            .endpoint("v1/blocks", Self::blocks)
             .endpoint("v1/block", Self::block)
+            .endpoint("v1/block/transactions", Self::block_transactions)
+            .endpoint("v1/validators", Self::validators)
             .endpoint("v1/transactions", Self::transaction_info)
-            .endpoint_mut("v1/transactions", Self::add_transaction)
+            .endpoint("v1/transactions/range", Self::transactions)
+            .endpoint_mut(
+                "v1/transactions",
+                move |state: &ServiceApiState, query: TransactionHex| {
+                    if node_state.is_read_only() {
+                        return Err(read_only_error(state));
+                    }
+                    Self::add_transaction(state, query)
+                },
+            ).endpoint_mut("v1/blockchain/decode", Self::decode_transaction)
+            .endpoint("v1/transactions/offchain", Self::offchain_content)
+    }
+}
+
+/// Returns the error for the transactions submitted while the node is in the read-only
+/// state, i.e., has lost the quorum of validators.
+fn read_only_error(state: &ServiceApiState) -> ApiError {
+    let height = Schema::new(state.snapshot()).height();
+    ApiError::ServiceUnavailable(format!(
+        "Node is connected to less validators than required for the consensus; \
+         transactions are not accepted. Latest finalized height: {}",
+        height
+    ))
+}
+
+/// Lazily serialized chunks of the JSON array of block headers.
+struct BlocksChunks {
+    snapshot: Box<dyn Snapshot>,
+    upper: Height,
+    next_height: Option<Height>,
+    count: usize,
+    returned: usize,
+    last_height: Option<Height>,
+    skip_empty_blocks: bool,
+    times: Option<Vec<DateTime<Utc>>>,
+    started: bool,
+    finished: bool,
+}
+
+/// The fields of `BlocksRange` following the streamed blocks.
+#[derive(Serialize)]
+struct BlocksRangeTail<'a> {
+    range: Range<Height>,
+    times: Option<&'a [DateTime<Utc>]>,
+    next_cursor: Option<Cursor>,
+}
+
+impl BlocksChunks {
+    fn write_tail(&self, chunk: &mut Vec<u8>) {
+        let start = if self.returned < self.count {
+            Height(0)
+        } else {
+            self.last_height.unwrap_or(Height(0))
+        };
+        let tail = BlocksRangeTail {
+            range: start..self.upper.next(),
+            times: self.times.as_ref().map(Vec::as_slice),
+            next_cursor: start.checked_previous().map(Cursor::at_height),
+        };
+        let tail = serde_json::to_vec(&tail).expect("Unable to serialize blocks range");
+        // The tail is appended to the object opened by the first chunk.
+        chunk.extend_from_slice(b"],");
+        chunk.extend_from_slice(&tail[1..]);
+    }
+}
+
+impl Iterator for BlocksChunks {
+    type Item = Bytes;
+
+    fn next(&mut self) -> Option<Bytes> {
+        if self.finished {
+            return None;
+        }
+
+        let schema = Schema::new(&self.snapshot);
+        let block_hashes = schema.block_hashes_by_height();
+        let blocks = schema.blocks();
+
+        let mut chunk = Vec::new();
+        if !self.started {
+            chunk.extend_from_slice(b"{\"blocks\":[");
+            self.started = true;
+        }
+        let mut chunk_len = 0;
+        while chunk_len < BLOCKS_PER_CHUNK {
+            let height = match self.next_height {
+                Some(height) if self.returned < self.count => height,
+                _ => {
+                    self.finished = true;
+                    break;
+                }
+            };
+            self.next_height = height.checked_previous();
+
+            let block_hash = block_hashes
+                .get(height.0)
+                .expect("Block hash not found for the height");
+            let block = blocks
+                .get(&block_hash)
+                .expect("Block not found for the stored block hash");
+            if self.skip_empty_blocks && block.tx_count() == 0 {
+                continue;
+            }
+            if let Some(ref mut times) = self.times {
+                let precommits: Vec<_> = schema.precommits(&block_hash).iter().collect();
+                times.push(median_precommits_time(&precommits));
+            }
+            if self.returned > 0 {
+                chunk.push(b',');
+            }
+            serde_json::to_writer(&mut chunk, &block).expect("Unable to serialize block");
+            self.returned += 1;
+            self.last_height = Some(height);
+            chunk_len += 1;
+        }
+        if self.finished {
+            self.write_tail(&mut chunk);
+        }
+        Some(Bytes::from(chunk))
     }
 }
 
@@ -281,6 +997,37 @@ impl<'a> From<explorer::BlockInfo<'a>> for BlockInfo {
     }
 }
 
+/// Returns `true` if the blocks returned for the query, starting from the `first` height,
+/// include a block with pruned precommits, so that the time of the block is not available.
+fn reaches_pruned_blocks<T>(schema: &Schema<T>, first: Height, query: &BlocksQuery) -> bool
+where
+    T: AsRef<dyn Snapshot>,
+{
+    let pruned_height = schema.pruned_height();
+    if pruned_height == Height(0) {
+        return false;
+    }
+
+    let block_hashes = schema.block_hashes_by_height();
+    let blocks = schema.blocks();
+    let is_returned = |height: u64| {
+        !query.skip_empty_blocks || {
+            let block_hash = block_hashes
+                .get(height)
+                .expect("Block hash not found for the height");
+            blocks
+                .get(&block_hash)
+                .map_or(false, |block| block.tx_count() > 0)
+        }
+    };
+    let retained = (pruned_height.0..first.0 + 1)
+        .rev()
+        .filter(|&height| is_returned(height))
+        .take(query.count)
+        .count();
+    retained < query.count && (0..cmp::min(pruned_height.0, first.0 + 1)).any(is_returned)
+}
+
 fn median_precommits_time(precommits: &[Signed<Precommit>]) -> DateTime<Utc> {
     debug_assert!(!precommits.is_empty(), "Precommits cannot be empty");
     let mut times: Vec<_> = precommits.iter().map(|p| p.time()).collect();
@@ -0,0 +1,570 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Depth and complexity limits of GraphQL queries.
+//!
+//! The schema contains cycles (e.g., `Block.transactions` and `Transaction.block`), so
+//! the cost of a query is unbounded unless it is checked before the execution. The query
+//! is parsed into a tree of field selections with fragments expanded; each field costs
+//! one unit, and the cost of the nested selection of a list field is multiplied by its
+//! `count` argument.
+
+use serde_json::Value;
+
+use std::collections::HashMap;
+
+/// Maximum nesting depth of the fields in a GraphQL query.
+pub const MAX_QUERY_DEPTH: usize = 10;
+
+/// Maximum complexity of a GraphQL query, i.e., the maximum number of the fields
+/// resolved during its execution.
+pub const MAX_QUERY_COMPLEXITY: u64 = 10_000;
+
+/// Checks that no operation of the query exceeds the depth and complexity limits.
+///
+/// The queries that cannot be parsed are rejected as well.
+pub fn check_query(query: &str, variables: Option<&Value>) -> Result<(), String> {
+    let document = Parser::new(tokenize(query)?).document()?;
+    for operation in &document.operations {
+        let mut analyzer = Analyzer {
+            fragments: &document.fragments,
+            variables,
+            defaults: &operation.defaults,
+            expanding: Vec::new(),
+            costs: HashMap::new(),
+        };
+        let complexity = analyzer.cost(&operation.selections, 1)?;
+        if complexity > MAX_QUERY_COMPLEXITY {
+            return Err(format!(
+                "Query complexity exceeds the maximum allowed ({})",
+                MAX_QUERY_COMPLEXITY
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Punct(char),
+    Spread,
+    Name(String),
+    Int(i64),
+    // Any other literal: a float, a string or a block string.
+    Value,
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' && chars[i] != '\r' {
+                    i += 1;
+                }
+            }
+            '"' => {
+                i = skip_string(&chars, i)?;
+                tokens.push(Token::Value);
+            }
+            '.' if chars[i..].starts_with(&['.', '.', '.']) => {
+                tokens.push(Token::Spread);
+                i += 3;
+            }
+            c if c.is_whitespace() || c == ',' || c == '\u{feff}' => i += 1,
+            c if "!$():=@[]{|}".contains(c) => {
+                tokens.push(Token::Punct(c));
+                i += 1;
+            }
+            c if c == '_' || c.is_ascii_alphabetic() => {
+                let start = i;
+                while i < chars.len() && (chars[i] == '_' || chars[i].is_ascii_alphanumeric()) {
+                    i += 1;
+                }
+                tokens.push(Token::Name(chars[start..i].iter().collect()));
+            }
+            c if c == '-' || c.is_ascii_digit() => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let is_float = i < chars.len() && ['.', 'e', 'E'].contains(&chars[i]);
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || "+-.".contains(chars[i]))
+                {
+                    i += 1;
+                }
+                let literal: String = chars[start..i].iter().collect();
+                tokens.push(match literal.parse::<i64>() {
+                    Ok(value) if !is_float => Token::Int(value),
+                    _ => Token::Value,
+                });
+            }
+            c => return Err(format!("Unexpected character `{}`", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Returns the position after the string literal starting at `start`.
+fn skip_string(chars: &[char], start: usize) -> Result<usize, String> {
+    let block_quote = ['"', '"', '"'];
+    if chars[start..].starts_with(&block_quote) {
+        let mut i = start + 3;
+        while i < chars.len() {
+            if chars[i..].starts_with(&['\\', '"', '"', '"']) {
+                i += 4;
+            } else if chars[i..].starts_with(&block_quote) {
+                return Ok(i + 3);
+            } else {
+                i += 1;
+            }
+        }
+    } else {
+        let mut i = start + 1;
+        while i < chars.len() {
+            match chars[i] {
+                '\\' => i += 2,
+                '"' => return Ok(i + 1),
+                '\n' | '\r' => break,
+                _ => i += 1,
+            }
+        }
+    }
+    Err("Unterminated string".to_owned())
+}
+
+#[derive(Debug)]
+enum Count {
+    Literal(i64),
+    Variable(String),
+}
+
+#[derive(Debug)]
+enum Selection {
+    Field {
+        count: Option<Count>,
+        selections: Vec<Selection>,
+    },
+    FragmentSpread(String),
+    InlineFragment(Vec<Selection>),
+}
+
+#[derive(Debug)]
+struct Operation {
+    selections: Vec<Selection>,
+    // Default values of the integer variables.
+    defaults: HashMap<String, i64>,
+}
+
+#[derive(Debug)]
+struct Document {
+    operations: Vec<Operation>,
+    fragments: HashMap<String, Vec<Selection>>,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn new(tokens: Vec<Token>) -> Self {
+        Parser {
+            tokens,
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn advance(&mut self) -> Result<Token, String> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| "Unexpected end of the query".to_owned())?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Punct(c))
+    }
+
+    fn is_name(&self, name: &str) -> bool {
+        match self.peek() {
+            Some(&Token::Name(ref token)) => token == name,
+            _ => false,
+        }
+    }
+
+    fn expect_punct(&mut self, c: char) -> Result<(), String> {
+        match self.advance()? {
+            Token::Punct(token) if token == c => Ok(()),
+            token => Err(format!("Expected `{}`, got {:?}", c, token)),
+        }
+    }
+
+    fn expect_name(&mut self) -> Result<String, String> {
+        match self.advance()? {
+            Token::Name(name) => Ok(name),
+            token => Err(format!("Expected name, got {:?}", token)),
+        }
+    }
+
+    fn document(&mut self) -> Result<Document, String> {
+        let mut operations = Vec::new();
+        let mut fragments = HashMap::new();
+        while self.peek().is_some() {
+            if self.is_punct('{') {
+                operations.push(Operation {
+                    selections: self.selection_set()?,
+                    defaults: HashMap::new(),
+                });
+            } else if self.is_name("fragment") {
+                self.advance()?;
+                let name = self.expect_name()?;
+                if !self.is_name("on") {
+                    return Err(format!("Expected type condition of fragment `{}`", name));
+                }
+                self.advance()?;
+                self.expect_name()?;
+                self.directives()?;
+                let selections = self.selection_set()?;
+                if fragments.insert(name.clone(), selections).is_some() {
+                    return Err(format!("Duplicate fragment `{}`", name));
+                }
+            } else {
+                // Operation type, e.g., `query`.
+                self.expect_name()?;
+                if let Some(&Token::Name(_)) = self.peek() {
+                    self.advance()?;
+                }
+                let defaults = if self.is_punct('(') {
+                    self.variable_definitions()?
+                } else {
+                    HashMap::new()
+                };
+                self.directives()?;
+                operations.push(Operation {
+                    selections: self.selection_set()?,
+                    defaults,
+                });
+            }
+        }
+        Ok(Document {
+            operations,
+            fragments,
+        })
+    }
+
+    fn variable_definitions(&mut self) -> Result<HashMap<String, i64>, String> {
+        let mut defaults = HashMap::new();
+        self.expect_punct('(')?;
+        while !self.is_punct(')') {
+            self.expect_punct('$')?;
+            let name = self.expect_name()?;
+            self.expect_punct(':')?;
+            // Skip the type, e.g., `[Int!]!`.
+            while self.is_punct('[') || self.is_punct(']') || self.is_punct('!') {
+                self.advance()?;
+            }
+            self.expect_name()?;
+            while self.is_punct(']') || self.is_punct('!') {
+                self.advance()?;
+            }
+            if self.is_punct('=') {
+                self.advance()?;
+                if let Some(&Token::Int(value)) = self.peek() {
+                    defaults.insert(name, value);
+                }
+                self.value()?;
+            }
+            self.directives()?;
+        }
+        self.advance()?;
+        Ok(defaults)
+    }
+
+    fn selection_set(&mut self) -> Result<Vec<Selection>, String> {
+        self.expect_punct('{')?;
+        let mut selections = Vec::new();
+        while !self.is_punct('}') {
+            selections.push(self.selection()?);
+        }
+        self.advance()?;
+        Ok(selections)
+    }
+
+    fn selection(&mut self) -> Result<Selection, String> {
+        if self.peek() == Some(&Token::Spread) {
+            self.advance()?;
+            if self.is_name("on") {
+                self.advance()?;
+                self.expect_name()?;
+            } else if let Some(&Token::Name(_)) = self.peek() {
+                let name = self.expect_name()?;
+                self.directives()?;
+                return Ok(Selection::FragmentSpread(name));
+            }
+            self.directives()?;
+            return Ok(Selection::InlineFragment(self.selection_set()?));
+        }
+
+        self.expect_name()?;
+        // The name of an aliased field.
+        if self.is_punct(':') {
+            self.advance()?;
+            self.expect_name()?;
+        }
+        let count = if self.is_punct('(') {
+            self.arguments()?
+        } else {
+            None
+        };
+        self.directives()?;
+        let selections = if self.is_punct('{') {
+            self.selection_set()?
+        } else {
+            Vec::new()
+        };
+        Ok(Selection::Field { count, selections })
+    }
+
+    /// Skips the arguments and returns the value of the `count` argument, if any.
+    fn arguments(&mut self) -> Result<Option<Count>, String> {
+        let mut count = None;
+        self.expect_punct('(')?;
+        while !self.is_punct(')') {
+            let name = self.expect_name()?;
+            self.expect_punct(':')?;
+            if name == "count" {
+                count = match self.peek() {
+                    Some(&Token::Int(value)) => Some(Count::Literal(value)),
+                    Some(&Token::Punct('$')) => match self.tokens.get(self.position + 1) {
+                        Some(&Token::Name(ref variable)) => Some(Count::Variable(variable.clone())),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+            }
+            self.value()?;
+        }
+        self.advance()?;
+        Ok(count)
+    }
+
+    fn directives(&mut self) -> Result<(), String> {
+        while self.is_punct('@') {
+            self.advance()?;
+            self.expect_name()?;
+            if self.is_punct('(') {
+                self.arguments()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<(), String> {
+        match self.advance()? {
+            Token::Punct('$') => {
+                self.expect_name()?;
+            }
+            Token::Punct('[') => {
+                while !self.is_punct(']') {
+                    self.value()?;
+                }
+                self.advance()?;
+            }
+            Token::Punct('{') => {
+                while !self.is_punct('}') {
+                    self.expect_name()?;
+                    self.expect_punct(':')?;
+                    self.value()?;
+                }
+                self.advance()?;
+            }
+            Token::Name(_) | Token::Int(_) | Token::Value => {}
+            token => return Err(format!("Expected value, got {:?}", token)),
+        }
+        Ok(())
+    }
+}
+
+struct Analyzer<'a> {
+    fragments: &'a HashMap<String, Vec<Selection>>,
+    variables: Option<&'a Value>,
+    defaults: &'a HashMap<String, i64>,
+    // Fragments being expanded, used to detect the cycles.
+    expanding: Vec<&'a str>,
+    // Costs of the fragments expanded at the given depth; without them, the fragments
+    // spread several times into each other would be expanded exponentially many times.
+    costs: HashMap<(&'a str, usize), u64>,
+}
+
+impl<'a> Analyzer<'a> {
+    /// Returns the cost of the selections with the fields at the given depth.
+    fn cost(&mut self, selections: &'a [Selection], depth: usize) -> Result<u64, String> {
+        let mut total = 0_u64;
+        for selection in selections {
+            let cost = match *selection {
+                Selection::Field {
+                    ref count,
+                    ref selections,
+                } => {
+                    if depth > MAX_QUERY_DEPTH {
+                        return Err(format!(
+                            "Query depth exceeds the maximum allowed ({})",
+                            MAX_QUERY_DEPTH
+                        ));
+                    }
+                    let nested = self.cost(selections, depth + 1)?;
+                    let multiplier = self.multiplier(count);
+                    multiplier.saturating_mul(nested).saturating_add(1)
+                }
+                Selection::FragmentSpread(ref name) => self.fragment_cost(name, depth)?,
+                Selection::InlineFragment(ref selections) => self.cost(selections, depth)?,
+            };
+            total = total.saturating_add(cost);
+        }
+        Ok(total)
+    }
+
+    fn fragment_cost(&mut self, name: &'a str, depth: usize) -> Result<u64, String> {
+        if let Some(&cost) = self.costs.get(&(name, depth)) {
+            return Ok(cost);
+        }
+        if self.expanding.contains(&name) {
+            return Err(format!("Fragment `{}` spreads itself", name));
+        }
+        let fragments = self.fragments;
+        let selections = fragments
+            .get(name)
+            .ok_or_else(|| format!("Unknown fragment `{}`", name))?;
+        self.expanding.push(name);
+        let cost = self.cost(selections, depth)?;
+        self.expanding.pop();
+        self.costs.insert((name, depth), cost);
+        Ok(cost)
+    }
+
+    fn multiplier(&self, count: &Option<Count>) -> u64 {
+        let value = match *count {
+            Some(Count::Literal(value)) => Some(value),
+            Some(Count::Variable(ref name)) => self
+                .variables
+                .and_then(|variables| variables.get(name))
+                .and_then(Value::as_i64)
+                .or_else(|| self.defaults.get(name).cloned()),
+            None => None,
+        };
+        // Invalid counts are rejected during the execution.
+        value.map_or(1, |value| value.max(0) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(query: &str) -> Result<(), String> {
+        check_query(query, None)
+    }
+
+    #[test]
+    fn test_query_limits_accepted() {
+        check("{ height }").unwrap();
+        check(
+            r#"
+            query Blocks($count: Int = 10) {
+                # Comment with { braces
+                blocks(count: $count, cursor: "}") {
+                    blocks { height, transactions(count: 50) { transactions { hash } } }
+                    nextCursor
+                }
+            }
+            "#,
+        ).unwrap();
+        check(
+            "{ transaction(hash: \"00\") { ...Tx } }
+             fragment Tx on Transaction { hash block { ... on Block { height } } }",
+        ).unwrap();
+    }
+
+    #[test]
+    fn test_query_depth_limit() {
+        let nested = |depth: usize| {
+            (1..depth).fold("height".to_owned(), |query, _| {
+                format!("block {{ {} }}", query)
+            })
+        };
+        check(&format!("{{ {} }}", nested(MAX_QUERY_DEPTH))).unwrap();
+        let error = check(&format!("{{ {} }}", nested(MAX_QUERY_DEPTH + 1))).unwrap_err();
+        assert!(error.contains("depth"));
+
+        // Fragments are expanded at the depth of their spreads.
+        let fragments: String = (0..MAX_QUERY_DEPTH)
+            .map(|i| format!("fragment F{} on Block {{ block {{ ...F{} }} }} ", i, i + 1))
+            .collect();
+        let query = format!(
+            "{{ ...F0 }} {} fragment F{} on Block {{ height }}",
+            fragments, MAX_QUERY_DEPTH
+        );
+        assert!(check(&query).unwrap_err().contains("depth"));
+    }
+
+    #[test]
+    fn test_query_complexity_limit() {
+        let query =
+            "{ blocks(count: 1000) { blocks { transactions(count: $count) { nextCursor } } } }";
+        check_query(query, Some(&json!({ "count": 1 }))).unwrap();
+        let error = check_query(query, Some(&json!({ "count": 1000 }))).unwrap_err();
+        assert!(error.contains("complexity"));
+
+        // Default values of the variables are taken into account.
+        let query = "query($count: Int = 1000) {
+            blocks(count: 1000) { blocks { transactions(count: $count) { nextCursor } } }
+        }";
+        assert!(check(query).unwrap_err().contains("complexity"));
+
+        // Fragments spread several times are counted each time.
+        let fragments: String = (0..8)
+            .map(|i| {
+                let spread = format!("block {{ ...F{} }}", i + 1);
+                format!(
+                    "fragment F{} on Block {{ a: {1} b: {1} c: {1} d: {1} }} ",
+                    i, spread
+                )
+            }).collect();
+        let query = format!(
+            "{{ ...F0 }} {} fragment F8 on Block {{ height }}",
+            fragments
+        );
+        assert!(check(&query).unwrap_err().contains("complexity"));
+    }
+
+    #[test]
+    fn test_query_limits_invalid() {
+        assert!(check("{ height").is_err());
+        assert!(check("{ block(height: \"1) { height } }").is_err());
+        assert!(check("{ ...F } fragment F on Query { ...F }").is_err());
+        assert!(check("{ ...Unknown }").is_err());
+    }
+}
@@ -0,0 +1,685 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! GraphQL API over the blockchain data.
+//!
+//! The API is available only if the crate is built with the `graphql` feature. It provides
+//! the same data as the [explorer API], but allows clients to fetch nested objects
+//! (e.g., blocks together with their transactions) and select only the necessary fields
+//! in a single request. Services expose their wallets and events via the API by
+//! implementing [`GraphQLProvider`].
+//!
+//! All objects returned within a request are read from the same blockchain snapshot.
+//!
+//! Heights, counts and indexes are represented as GraphQL `Int`s; balances are represented
+//! as decimal strings; hashes, public keys and cursors are represented as hex strings.
+//!
+//! All lists are paged with the `count` and `cursor` arguments. Queries exceeding
+//! [`MAX_QUERY_DEPTH`] or [`MAX_QUERY_COMPLEXITY`] are rejected before the execution.
+//!
+//! [explorer API]: ../explorer/index.html
+//! [`GraphQLProvider`]: trait.GraphQLProvider.html
+//! [`MAX_QUERY_DEPTH`]: constant.MAX_QUERY_DEPTH.html
+//! [`MAX_QUERY_COMPLEXITY`]: constant.MAX_QUERY_COMPLEXITY.html
+
+pub use self::limits::{MAX_QUERY_COMPLEXITY, MAX_QUERY_DEPTH};
+
+use juniper::{http, EmptyMutation, FieldResult, InputValue, RootNode};
+use serde_json;
+
+use std::{
+    cmp::{min, Ordering},
+    i32,
+};
+
+use api::{pagination, Error as ApiError, ServiceApiScope, ServiceApiState};
+use blockchain::{Block, Blockchain, Schema};
+use crypto::{CryptoHash, Hash, PublicKey};
+use encoding::serialize::FromHex;
+use explorer::{BlockchainExplorer, Cursor, TransactionInfo};
+use helpers::Height;
+use storage::Snapshot;
+
+use super::explorer::{MAX_BLOCKS_PER_REQUEST, MAX_TRANSACTIONS_PER_REQUEST};
+
+mod limits;
+
+/// Root of the GraphQL schema.
+pub type GraphQLSchema = RootNode<'static, QueryRoot, EmptyMutation<GraphQLContext>>;
+
+/// Provider of the service data exposed via the GraphQL API.
+///
+/// Services return the provider from [`Service::graphql_provider`]. All methods read
+/// the data from the given snapshot, which is shared by all objects returned within
+/// a GraphQL request.
+///
+/// [`Service::graphql_provider`]: ../../../../blockchain/trait.Service.html#method.graphql_provider
+pub trait GraphQLProvider {
+    /// Returns the wallet with the given public key, or `None` if the service has
+    /// no such wallet.
+    ///
+    /// *Default implementation returns `None`*
+    fn wallet(&self, _snapshot: &dyn Snapshot, _pub_key: &PublicKey) -> Option<Wallet> {
+        None
+    }
+
+    /// Returns the events emitted by the committed transaction of the service.
+    ///
+    /// *Default implementation returns no events*
+    fn events(&self, _snapshot: &dyn Snapshot, _tx_hash: &Hash) -> Vec<Event> {
+        Vec::new()
+    }
+}
+
+/// Wallet of a service exposed via the GraphQL API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wallet {
+    /// Public key of the wallet.
+    pub pub_key: PublicKey,
+    /// Name of the wallet.
+    pub name: String,
+    /// Current balance of the wallet.
+    pub balance: u64,
+    /// Hashes of the transactions affecting the wallet in the order of their execution.
+    pub history: Vec<Hash>,
+}
+
+/// Event emitted by a service transaction, exposed via the GraphQL API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    /// Name of the event.
+    pub name: String,
+    /// Data of the event.
+    pub data: serde_json::Value,
+}
+
+/// GraphQL request in the standard JSON format.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphQLRequest {
+    query: String,
+    #[serde(rename = "operationName")]
+    operation_name: Option<String>,
+    variables: Option<serde_json::Value>,
+}
+
+/// Context of a single GraphQL request.
+///
+/// All objects returned within the request are read from the same blockchain snapshot.
+pub struct GraphQLContext {
+    blockchain: Blockchain,
+    explorer: BlockchainExplorer<'static>,
+}
+
+impl ::std::fmt::Debug for GraphQLContext {
+    fn fmt(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        formatter.pad("GraphQLContext { .. }")
+    }
+}
+
+impl ::juniper::Context for GraphQLContext {}
+
+impl GraphQLContext {
+    /// Creates a new context over the current state of the blockchain.
+    pub fn new(blockchain: Blockchain) -> Self {
+        let explorer = BlockchainExplorer::owned(blockchain.clone());
+        Self {
+            blockchain,
+            explorer,
+        }
+    }
+
+    /// Executes the GraphQL query against the snapshot of the context and returns
+    /// the response in the standard GraphQL JSON format.
+    ///
+    /// The queries exceeding the depth or complexity limits are rejected without
+    /// the execution.
+    pub fn execute(&self, request: &GraphQLRequest) -> Result<serde_json::Value, ApiError> {
+        limits::check_query(&request.query, request.variables.as_ref())
+            .map_err(ApiError::BadRequest)?;
+        let variables = match request.variables {
+            Some(ref variables) => Some(
+                serde_json::from_value::<InputValue>(variables.clone())
+                    .map_err(|e| ApiError::BadRequest(e.to_string()))?,
+            ),
+            None => None,
+        };
+        let request = http::GraphQLRequest::new(
+            request.query.clone(),
+            request.operation_name.clone(),
+            variables,
+        );
+        let response = request.execute(&GraphQLApi::schema(), self);
+        serde_json::to_value(&response).map_err(|e| ApiError::InternalError(e.into()))
+    }
+
+    fn height(&self) -> Height {
+        self.explorer.height()
+    }
+
+    fn block(&self, height: Height) -> Option<BlockObject> {
+        let schema = Schema::new(self.explorer.snapshot());
+        let block_hash = schema.block_hashes_by_height().get(height.0)?;
+        schema.blocks().get(&block_hash).map(BlockObject)
+    }
+
+    fn transaction(&self, hash: &Hash) -> Option<TransactionObject> {
+        self.explorer.transaction(hash).map(TransactionObject)
+    }
+
+    fn block_transactions(&self, height: Height, start: u64, count: u64) -> TransactionsPage {
+        let tx_hashes = Schema::new(self.explorer.snapshot()).block_transactions(height);
+        let start = min(start, tx_hashes.len());
+        let transactions = tx_hashes
+            .iter_from(start)
+            .take(count as usize)
+            .filter_map(|hash| self.transaction(&hash))
+            .collect();
+        let end = start.saturating_add(count);
+        TransactionsPage {
+            transactions,
+            next_cursor: if end < tx_hashes.len() {
+                Some(Cursor::new(height, end))
+            } else {
+                None
+            },
+        }
+    }
+
+    fn block_events(&self, height: Height, start: u64, count: u64) -> EventsPage {
+        let tx_hashes = Schema::new(self.explorer.snapshot()).block_transactions(height);
+        let start = min(start, tx_hashes.len());
+        let events = tx_hashes
+            .iter_from(start)
+            .take(count as usize)
+            .flat_map(|hash| self.events(&hash))
+            .collect();
+        let end = start.saturating_add(count);
+        EventsPage {
+            events,
+            next_cursor: if end < tx_hashes.len() {
+                Some(Cursor::new(height, end))
+            } else {
+                None
+            },
+        }
+    }
+
+    fn wallet_transactions(
+        &self,
+        history: &[Hash],
+        count: u64,
+        cursor: Option<Cursor>,
+    ) -> TransactionsPage {
+        let schema = Schema::new(self.explorer.snapshot());
+        let locations = schema.transactions_locations();
+        let location = |hash: &Hash| locations.get(hash).map(Cursor::from);
+        // The history is ordered by the execution of the transactions, so the first
+        // transaction at or after the cursor is found by binary search.
+        let start = match cursor {
+            Some(cursor) => history
+                .binary_search_by(|hash| {
+                    if location(hash) < Some(cursor) {
+                        Ordering::Less
+                    } else {
+                        Ordering::Greater
+                    }
+                }).unwrap_or_else(|index| index),
+            None => 0,
+        };
+        let transactions = history[start..]
+            .iter()
+            .take(count as usize)
+            .filter_map(|hash| self.transaction(hash))
+            .collect();
+        let next_cursor = history
+            .get(start.saturating_add(count as usize))
+            .and_then(location);
+        TransactionsPage {
+            transactions,
+            next_cursor,
+        }
+    }
+
+    fn wallet(&self, pub_key: &PublicKey) -> Option<WalletObject> {
+        self.blockchain
+            .service_map()
+            .iter()
+            .filter_map(|(_, service)| {
+                let provider = service.graphql_provider()?;
+                let wallet = provider.wallet(self.explorer.snapshot(), pub_key)?;
+                Some(WalletObject {
+                    service_id: service.service_id(),
+                    wallet,
+                })
+            }).next()
+    }
+
+    fn events(&self, tx_hash: &Hash) -> Vec<EventObject> {
+        let schema = Schema::new(self.explorer.snapshot());
+        // Only committed transactions emit events.
+        if !schema.transactions_locations().contains(tx_hash) {
+            return Vec::new();
+        }
+        let service_id = match schema.transactions().get(tx_hash) {
+            Some(tx) => tx.payload().service_id(),
+            None => return Vec::new(),
+        };
+        let provider = self
+            .blockchain
+            .service_map()
+            .get(service_id as usize)
+            .and_then(|service| service.graphql_provider());
+        let provider = match provider {
+            Some(provider) => provider,
+            None => return Vec::new(),
+        };
+        provider
+            .events(self.explorer.snapshot(), tx_hash)
+            .into_iter()
+            .map(|event| EventObject {
+                service_id,
+                tx_hash: *tx_hash,
+                event,
+            }).collect()
+    }
+}
+
+/// Block header exposed via GraphQL.
+#[derive(Debug)]
+pub struct BlockObject(Block);
+
+/// Transaction exposed via GraphQL.
+#[derive(Debug)]
+pub struct TransactionObject(TransactionInfo);
+
+/// Wallet exposed via GraphQL.
+#[derive(Debug)]
+pub struct WalletObject {
+    service_id: u16,
+    wallet: Wallet,
+}
+
+/// Event exposed via GraphQL.
+#[derive(Debug)]
+pub struct EventObject {
+    service_id: u16,
+    tx_hash: Hash,
+    event: Event,
+}
+
+/// Page of blocks returned by the `blocks` query.
+#[derive(Debug)]
+pub struct BlocksPage {
+    blocks: Vec<BlockObject>,
+    next_cursor: Option<Cursor>,
+}
+
+/// Page of transactions returned by the `transactions` fields.
+#[derive(Debug)]
+pub struct TransactionsPage {
+    transactions: Vec<TransactionObject>,
+    next_cursor: Option<Cursor>,
+}
+
+/// Page of events returned by the `events` fields.
+#[derive(Debug)]
+pub struct EventsPage {
+    events: Vec<EventObject>,
+    next_cursor: Option<Cursor>,
+}
+
+/// Root query object.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryRoot;
+
+graphql_object!(QueryRoot: GraphQLContext as "Query" |&self| {
+    description: "Entry point for the queries over the blockchain data."
+
+    field height(&executor) -> i32 as "Height of the latest committed block." {
+        to_int(executor.context().height().0)
+    }
+
+    field block(&executor, height: i32) -> FieldResult<Option<BlockObject>>
+        as "Block at the given height."
+    {
+        Ok(executor.context().block(Height(from_int(height)?)))
+    }
+
+    field blocks(&executor, count: i32, cursor: Option<String>) -> FieldResult<BlocksPage>
+        as "Blocks in reverse order, starting from the latest one or the given cursor."
+    {
+        let context = executor.context();
+        let (count, cursor) = page(count, cursor, MAX_BLOCKS_PER_REQUEST)?;
+
+        let height = context.height();
+        let upper = match cursor {
+            Some(cursor) => min(cursor.height, height),
+            None => height,
+        };
+        let blocks: Vec<_> = (0..=upper.0)
+            .rev()
+            .take(count as usize)
+            .filter_map(|height| context.block(Height(height)))
+            .collect();
+        let next_cursor = match blocks.last() {
+            Some(block) if blocks.len() as u64 == count => {
+                block.0.height().checked_previous().map(Cursor::at_height)
+            }
+            _ => None,
+        };
+        Ok(BlocksPage { blocks, next_cursor })
+    }
+
+    field transaction(&executor, hash: String) -> FieldResult<Option<TransactionObject>>
+        as "Committed or pooled transaction with the given hash."
+    {
+        let hash = Hash::from_hex(&hash)?;
+        Ok(executor.context().transaction(&hash))
+    }
+
+    field wallet(&executor, pub_key: String) -> FieldResult<Option<WalletObject>>
+        as "Wallet with the given public key."
+    {
+        let pub_key = PublicKey::from_hex(&pub_key)?;
+        Ok(executor.context().wallet(&pub_key))
+    }
+
+    field events(&executor, height: i32, count: i32, cursor: Option<String>)
+        -> FieldResult<EventsPage>
+        as "Events emitted by at most `count` transactions in the block at the given height, \
+            starting from the first transaction or the given cursor."
+    {
+        let height = Height(from_int(height)?);
+        let (count, cursor) = page(count, cursor, MAX_TRANSACTIONS_PER_REQUEST)?;
+        let start = position_in_block(height, cursor)?;
+        Ok(executor.context().block_events(height, start, count))
+    }
+});
+
+graphql_object!(BlocksPage: GraphQLContext as "BlocksPage" |&self| {
+    description: "Page of blocks."
+
+    field blocks() -> &[BlockObject] as "Blocks in reverse order." {
+        &self.blocks
+    }
+
+    field next_cursor() -> Option<String>
+        as "Cursor to get the following page, or `null` if the genesis block has been reached."
+    {
+        self.next_cursor.map(|cursor| cursor.to_string())
+    }
+});
+
+graphql_object!(TransactionsPage: GraphQLContext as "TransactionsPage" |&self| {
+    description: "Page of transactions."
+
+    field transactions() -> &[TransactionObject]
+        as "Transactions in the order of their execution."
+    {
+        &self.transactions
+    }
+
+    field next_cursor() -> Option<String>
+        as "Cursor to get the following page, or `null` if there are no more transactions."
+    {
+        self.next_cursor.map(|cursor| cursor.to_string())
+    }
+});
+
+graphql_object!(EventsPage: GraphQLContext as "EventsPage" |&self| {
+    description: "Page of events."
+
+    field events() -> &[EventObject] as "Events in the order of their emission." {
+        &self.events
+    }
+
+    field next_cursor() -> Option<String>
+        as "Cursor to get the following page, or `null` if there are no more transactions."
+    {
+        self.next_cursor.map(|cursor| cursor.to_string())
+    }
+});
+
+graphql_object!(BlockObject: GraphQLContext as "Block" |&self| {
+    description: "Header of a committed block."
+
+    field height() -> i32 as "Height of the block." {
+        to_int(self.0.height().0)
+    }
+
+    field hash() -> String as "Hash of the block header." {
+        self.0.hash().to_hex()
+    }
+
+    field prev_hash() -> String as "Hash of the previous block." {
+        self.0.prev_hash().to_hex()
+    }
+
+    field proposer_id() -> i32 as "Identifier of the validator that proposed the block." {
+        i32::from(self.0.proposer_id().0)
+    }
+
+    field tx_count() -> i32 as "Number of transactions in the block." {
+        to_int(u64::from(self.0.tx_count()))
+    }
+
+    field tx_hash() -> String as "Root hash of the Merkle tree of the block transactions." {
+        self.0.tx_hash().to_hex()
+    }
+
+    field state_hash() -> String as "Hash of the blockchain state after applying the block." {
+        self.0.state_hash().to_hex()
+    }
+
+    field transactions(&executor, count: i32, cursor: Option<String>)
+        -> FieldResult<TransactionsPage>
+        as "Transactions in the block, starting from the first one or the given cursor."
+    {
+        let (count, cursor) = page(count, cursor, MAX_TRANSACTIONS_PER_REQUEST)?;
+        let start = position_in_block(self.0.height(), cursor)?;
+        Ok(executor.context().block_transactions(self.0.height(), start, count))
+    }
+
+    field events(&executor, count: i32, cursor: Option<String>) -> FieldResult<EventsPage>
+        as "Events emitted by at most `count` transactions in the block, \
+            starting from the first transaction or the given cursor."
+    {
+        let (count, cursor) = page(count, cursor, MAX_TRANSACTIONS_PER_REQUEST)?;
+        let start = position_in_block(self.0.height(), cursor)?;
+        Ok(executor.context().block_events(self.0.height(), start, count))
+    }
+});
+
+graphql_object!(TransactionObject: GraphQLContext as "Transaction" |&self| {
+    description: "Transaction, either committed or in the pool of unconfirmed transactions."
+
+    field hash() -> String as "Hash of the transaction message." {
+        self.0.content().message().hash().to_hex()
+    }
+
+    field status() -> &str as "Either `committed` or `in-pool`." {
+        if self.0.is_committed() {
+            "committed"
+        } else {
+            "in-pool"
+        }
+    }
+
+    field author() -> String as "Public key of the transaction author." {
+        self.0.content().message().author().to_hex()
+    }
+
+    field service_id() -> i32 as "Identifier of the service the transaction belongs to." {
+        i32::from(self.0.content().message().payload().service_id())
+    }
+
+    field content() -> FieldResult<String> as "JSON representation of the transaction." {
+        Ok(serde_json::to_string(self.0.content())?)
+    }
+
+    field block(&executor) -> Option<BlockObject> as "Block containing the transaction." {
+        self.0
+            .as_committed()
+            .and_then(|tx| executor.context().block(tx.location().block_height()))
+    }
+
+    field position_in_block() -> Option<i32> as "Zero-based position of the transaction in the block." {
+        self.0
+            .as_committed()
+            .map(|tx| to_int(tx.location().position_in_block()))
+    }
+
+    field cursor() -> Option<String> as "Cursor pointing to the transaction." {
+        self.0
+            .as_committed()
+            .map(|tx| Cursor::from(tx.location().clone()).to_string())
+    }
+
+    field success() -> Option<bool> as "Whether the transaction was executed successfully." {
+        self.0.as_committed().map(|tx| tx.status().is_ok())
+    }
+
+    field error() -> Option<String> as "Description of the transaction execution error." {
+        self.0
+            .as_committed()
+            .and_then(|tx| tx.status().err())
+            .map(|err| err.to_string())
+    }
+
+    field events(&executor) -> Vec<EventObject> as "Events emitted by the transaction." {
+        executor
+            .context()
+            .events(&self.0.content().message().hash())
+    }
+});
+
+graphql_object!(WalletObject: GraphQLContext as "Wallet" |&self| {
+    description: "Wallet maintained by a service."
+
+    field service_id() -> i32 as "Identifier of the service maintaining the wallet." {
+        i32::from(self.service_id)
+    }
+
+    field pub_key() -> String as "Public key of the wallet." {
+        self.wallet.pub_key.to_hex()
+    }
+
+    field name() -> &str as "Name of the wallet." {
+        &self.wallet.name
+    }
+
+    field balance() -> String as "Current balance of the wallet." {
+        self.wallet.balance.to_string()
+    }
+
+    field transactions(&executor, count: i32, cursor: Option<String>)
+        -> FieldResult<TransactionsPage>
+        as "Transactions affecting the wallet in the order of their execution, \
+            starting from the first one or the given cursor."
+    {
+        let (count, cursor) = page(count, cursor, MAX_TRANSACTIONS_PER_REQUEST)?;
+        Ok(executor
+            .context()
+            .wallet_transactions(&self.wallet.history, count, cursor))
+    }
+});
+
+graphql_object!(EventObject: GraphQLContext as "Event" |&self| {
+    description: "Event emitted by a committed service transaction."
+
+    field service_id() -> i32 as "Identifier of the service that emitted the event." {
+        i32::from(self.service_id)
+    }
+
+    field name() -> &str as "Name of the event." {
+        &self.event.name
+    }
+
+    field data() -> FieldResult<String> as "JSON representation of the event data." {
+        Ok(serde_json::to_string(&self.event.data)?)
+    }
+
+    field transaction(&executor) -> Option<TransactionObject>
+        as "Transaction that emitted the event."
+    {
+        executor.context().transaction(&self.tx_hash)
+    }
+});
+
+/// Exonum GraphQL API.
+#[derive(Debug, Clone, Copy)]
+pub struct GraphQLApi;
+
+impl GraphQLApi {
+    /// Creates the GraphQL schema.
+    pub fn schema() -> GraphQLSchema {
+        GraphQLSchema::new(QueryRoot, EmptyMutation::new())
+    }
+
+    /// Executes the GraphQL query and returns the response in the standard
+    /// GraphQL JSON format.
+    pub fn execute(
+        state: &ServiceApiState,
+        request: GraphQLRequest,
+    ) -> Result<serde_json::Value, ApiError> {
+        GraphQLContext::new(state.blockchain().clone()).execute(&request)
+    }
+
+    /// Adds GraphQL API endpoint to the corresponding scope.
+    pub fn wire(api_scope: &mut ServiceApiScope) -> &mut ServiceApiScope {
+        api_scope.endpoint_mut("", Self::execute)
+    }
+}
+
+/// Checks the `count` and `cursor` arguments of a paged list.
+fn page(
+    count: i32,
+    cursor: Option<String>,
+    max_count: usize,
+) -> FieldResult<(u64, Option<Cursor>)> {
+    let count = from_int(count)?;
+    pagination::check_limit(count as usize, max_count)?;
+    let cursor = match cursor {
+        Some(cursor) => Some(cursor.parse::<Cursor>()?),
+        None => None,
+    };
+    Ok((count, cursor))
+}
+
+/// Returns the position of the first transaction of the block to list.
+fn position_in_block(height: Height, cursor: Option<Cursor>) -> FieldResult<u64> {
+    match cursor {
+        Some(cursor) if cursor.height != height => {
+            Err(format!("Cursor does not point to the block at height {}", height.0).into())
+        }
+        Some(cursor) => Ok(cursor.index),
+        None => Ok(0),
+    }
+}
+
+fn to_int(value: u64) -> i32 {
+    if value > i32::MAX as u64 {
+        i32::MAX
+    } else {
+        value as i32
+    }
+}
+
+fn from_int(value: i32) -> Result<u64, String> {
+    if value < 0 {
+        Err(format!("Expected non-negative integer, got {}", value))
+    } else {
+        Ok(value as u64)
+    }
+}
@@ -17,7 +17,11 @@
 //! Public API includes requests for information which is available to outside
 //! users, e.g., for requesting proofs.
 
+#[cfg(feature = "graphql")]
+pub use self::graphql::GraphQLApi;
 pub use self::{explorer::ExplorerApi, system::SystemApi};
 
 pub mod explorer;
+#[cfg(feature = "graphql")]
+pub mod graphql;
 pub mod system;
@@ -14,9 +14,13 @@
 
 //! Public system API.
 
+use chrono::{DateTime, Utc};
+
 use api::{ServiceApiScope, ServiceApiState};
-use blockchain::{Schema, SharedNodeState};
-use helpers::user_agent;
+use blockchain::{RegisteredService, Schema, SharedNodeState};
+use explorer::PrefetchProgress;
+use helpers::{user_agent, Height};
+use node::ReplicaStatus;
 
 /// Information about the current state of the node memory pool.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -53,14 +57,42 @@ pub enum ConsensusStatus {
     Active,
 }
 
+/// Information about the loss of the connection to the quorum of validators.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct QuorumLossInfo {
+    /// Time since which the node is connected to less validators than required
+    /// for the consensus.
+    pub since: DateTime<Utc>,
+    /// `true` if the public API does not accept transactions until the quorum is restored.
+    pub read_only: bool,
+}
+
 /// Information about whether the node is connected to other peers and
 /// its consensus status.
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct HealthCheckInfo {
+    /// `true` if the consensus is enabled on the node and the node is connected to enough
+    /// validators for the consensus to progress.
+    pub healthy: bool,
     /// Consensus status.
     pub consensus_status: ConsensusStatus,
     /// Connectivity status.
     pub connectivity: ConnectivityStatus,
+    /// Information about the loss of the quorum of validators, or `None` if the node
+    /// is connected to enough validators.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quorum_lost: Option<QuorumLossInfo>,
+    /// Progress of the prefetch phase, or `None` if the prefetch is disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefetch: Option<PrefetchProgress>,
+    /// Synchronization status of the replica, or `None` if the node does not run
+    /// in the replica mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replica: Option<ReplicaStatus>,
+    /// Height of the latest committed block. Committed blocks are final and are never reverted.
+    pub finalized_height: Height,
+    /// Height of the block the node is currently working on.
+    pub height: Height,
 }
 
 /// Public system API.
@@ -86,6 +118,19 @@ impl SystemApi {
         self
     }
 
+    fn handle_services_info(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
+        api_scope.endpoint(name, move |state: &ServiceApiState, _query: ()| {
+            let services: Vec<RegisteredService> = state
+                .blockchain()
+                .service_registry()
+                .services()
+                .cloned()
+                .collect();
+            Ok(services)
+        });
+        self
+    }
+
     fn handle_user_agent_info(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
         api_scope.endpoint(name, move |_state: &ServiceApiState, _query: ()| {
             Ok(user_agent::get())
@@ -95,10 +140,18 @@ impl SystemApi {
 
     fn handle_healthcheck_info(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
         let self_ = self.clone();
-        api_scope.endpoint(name, move |_state: &ServiceApiState, _query: ()| {
+        api_scope.endpoint(name, move |state: &ServiceApiState, _query: ()| {
+            let finalized_height = Schema::new(state.snapshot()).height();
+            let consensus_status = self.get_consensus_status();
             Ok(HealthCheckInfo {
-                consensus_status: self.get_consensus_status(),
+                healthy: consensus_status == ConsensusStatus::Active,
+                consensus_status,
                 connectivity: self.get_connectivity_status(),
+                quorum_lost: self.get_quorum_loss_info(),
+                prefetch: self.shared_api_state.prefetch_progress(),
+                replica: self.shared_api_state.replica_status(),
+                finalized_height,
+                height: finalized_height.next(),
             })
         });
         self_
@@ -117,6 +170,15 @@ impl SystemApi {
         }
     }
 
+    fn get_quorum_loss_info(&self) -> Option<QuorumLossInfo> {
+        self.shared_api_state
+            .quorum_lost_since()
+            .map(|since| QuorumLossInfo {
+                since,
+                read_only: self.shared_api_state.is_read_only(),
+            })
+    }
+
     fn get_consensus_status(&self) -> ConsensusStatus {
         if self.shared_api_state.is_enabled() {
             if self.shared_api_state.consensus_status() {
@@ -133,7 +195,8 @@ impl SystemApi {
     pub fn wire(self, api_scope: &mut ServiceApiScope) -> &mut ServiceApiScope {
         self.handle_mempool_info("v1/mempool", api_scope)
             .handle_healthcheck_info("v1/healthcheck", api_scope)
-            .handle_user_agent_info("v1/user_agent", api_scope);
+            .handle_user_agent_info("v1/user_agent", api_scope)
+            .handle_services_info("v1/services", api_scope);
         api_scope
     }
 }
@@ -0,0 +1,345 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers for the API endpoints returning lists of items.
+//!
+//! Paginated endpoints accept the [`PageQuery`] parameters and return the items wrapped
+//! into the [`Page`] envelope. Items are addressed by their zero-based positions in the list;
+//! the cursor to the next page is the position of its first item.
+//!
+//! Endpoints listing the entries of a map index accept the [`KeyPageQuery`] parameters
+//! instead. Items are addressed by their keys, so that a page is read starting from the
+//! requested key without traversing the preceding entries.
+//!
+//! # Examples
+//!
+//! ```
+//! use exonum::api::pagination::{Order, PageQuery};
+//!
+//! let items = vec!["a", "b", "c", "d", "e"];
+//! let query = PageQuery {
+//!     limit: Some(2),
+//!     order: Order::Descending,
+//!     ..PageQuery::default()
+//! };
+//! let page = query
+//!     .paginate(items.len() as u64, 100, |pos| items.get(pos as usize).cloned())
+//!     .unwrap();
+//! assert_eq!(page.items, vec!["e", "d"]);
+//! assert_eq!(page.total, Some(5));
+//! assert_eq!(page.next_cursor, Some(2));
+//! ```
+//!
+//! [`PageQuery`]: struct.PageQuery.html
+//! [`KeyPageQuery`]: struct.KeyPageQuery.html
+//! [`Page`]: struct.Page.html
+
+use std::cmp;
+
+use super::Error as ApiError;
+
+/// Order in which the items are returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Order {
+    /// From the first item to the last one.
+    #[serde(rename = "asc")]
+    Ascending,
+    /// From the last item to the first one.
+    #[serde(rename = "desc")]
+    Descending,
+}
+
+impl Default for Order {
+    fn default() -> Self {
+        Order::Ascending
+    }
+}
+
+/// Query parameters of a paginated endpoint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PageQuery {
+    /// Position of the first returned item. Pass the `next_cursor` of the previous
+    /// response to get the following page. Defaults to the first item in the chosen order.
+    pub from: Option<u64>,
+    /// Position of the last item that can be returned. Defaults to the last item
+    /// in the chosen order.
+    pub to: Option<u64>,
+    /// Maximum number of returned items. Defaults to the maximum allowed by the endpoint.
+    pub limit: Option<usize>,
+    /// Order of the returned items. Defaults to the ascending order.
+    #[serde(default)]
+    pub order: Order,
+}
+
+/// Page of items returned by a paginated endpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<T, C = u64> {
+    /// Items on the page.
+    pub items: Vec<T>,
+    /// Total number of items in the list, or `None` if the endpoint does not count
+    /// the items, which is the case for the pages of map indexes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    /// Cursor to pass in the next request to get the following page, or `None`
+    /// if there are no more items.
+    pub next_cursor: Option<C>,
+}
+
+impl PageQuery {
+    /// Checks the parameters and returns the positions of the items on the page
+    /// within a list of `len` items together with the cursor to the next page.
+    pub fn positions(
+        &self,
+        len: u64,
+        max_limit: usize,
+    ) -> Result<(Vec<u64>, Option<u64>), ApiError> {
+        let limit = checked_limit(self.limit, max_limit)? as u64;
+
+        match self.order {
+            Order::Ascending => {
+                if let (Some(from), Some(to)) = (self.from, self.to) {
+                    if from > to {
+                        return Err(ApiError::BadRequest(
+                            "`from` should not be greater than `to` in the ascending order"
+                                .to_owned(),
+                        ));
+                    }
+                }
+
+                let start = self.from.unwrap_or(0);
+                let end = self
+                    .to
+                    .map_or(len, |to| cmp::min(to.saturating_add(1), len));
+                if start >= end {
+                    return Ok((Vec::new(), None));
+                }
+                let page_end = cmp::min(end, start.saturating_add(limit));
+                let next_cursor = if page_end < end { Some(page_end) } else { None };
+                Ok(((start..page_end).collect(), next_cursor))
+            }
+
+            Order::Descending => {
+                if let (Some(from), Some(to)) = (self.from, self.to) {
+                    if from < to {
+                        return Err(ApiError::BadRequest(
+                            "`from` should not be less than `to` in the descending order"
+                                .to_owned(),
+                        ));
+                    }
+                }
+
+                let end = self.to.unwrap_or(0);
+                if len == 0 || end >= len {
+                    return Ok((Vec::new(), None));
+                }
+                let start = self.from.map_or(len - 1, |from| cmp::min(from, len - 1));
+                let count = start - end + 1;
+                let (page_end, next_cursor) = if count > limit {
+                    (start - limit + 1, Some(start - limit))
+                } else {
+                    (end, None)
+                };
+                Ok(((page_end..=start).rev().collect(), next_cursor))
+            }
+        }
+    }
+
+    /// Builds a page of items from a list of `len` items. `get` returns the item
+    /// at the given position.
+    pub fn paginate<T, F>(&self, len: u64, max_limit: usize, get: F) -> Result<Page<T>, ApiError>
+    where
+        F: FnMut(u64) -> Option<T>,
+    {
+        let (positions, next_cursor) = self.positions(len, max_limit)?;
+        Ok(Page {
+            items: positions.into_iter().filter_map(get).collect(),
+            total: Some(len),
+            next_cursor,
+        })
+    }
+}
+
+/// Query parameters of an endpoint listing the entries of a map index in the order
+/// of their keys.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct KeyPageQuery<K> {
+    /// Key of the first returned item. Pass the `next_cursor` of the previous response
+    /// to get the following page. Defaults to the first key in the index.
+    pub from: Option<K>,
+    /// Maximum number of returned items. Defaults to the maximum allowed by the endpoint.
+    pub limit: Option<usize>,
+}
+
+impl<K> KeyPageQuery<K> {
+    /// Builds a page from the `(key, item)` pairs of the index starting from the `from` key,
+    /// e.g., from the iterator returned by `MapIndex::iter_from`. At most one entry
+    /// is read past the page to find the cursor to the next page. The total number
+    /// of items is not reported.
+    pub fn paginate<T, I>(&self, max_limit: usize, entries: I) -> Result<Page<T, K>, ApiError>
+    where
+        I: IntoIterator<Item = (K, T)>,
+    {
+        let limit = checked_limit(self.limit, max_limit)?;
+        let mut items = Vec::new();
+        let mut next_cursor = None;
+        for (key, item) in entries.into_iter().take(limit + 1) {
+            if items.len() == limit {
+                next_cursor = Some(key);
+            } else {
+                items.push(item);
+            }
+        }
+        Ok(Page {
+            items,
+            total: None,
+            next_cursor,
+        })
+    }
+}
+
+/// Returns the requested number of items or the maximum allowed by the endpoint
+/// if it is not specified.
+fn checked_limit(limit: Option<usize>, max_limit: usize) -> Result<usize, ApiError> {
+    let limit = limit.unwrap_or(max_limit);
+    check_limit(limit, max_limit)?;
+    if limit == 0 {
+        return Err(ApiError::BadRequest(
+            "`limit` should be positive".to_owned(),
+        ));
+    }
+    Ok(limit)
+}
+
+/// Checks that the requested number of items does not exceed the maximum allowed
+/// by the endpoint.
+pub fn check_limit(limit: usize, max_limit: usize) -> Result<(), ApiError> {
+    if limit > max_limit {
+        Err(ApiError::BadRequest(format!(
+            "Max item count per request exceeded ({})",
+            max_limit
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks that at most one of the mutually exclusive parameters is specified.
+pub fn check_exclusive<A, B>(
+    (first_name, first): (&str, &Option<A>),
+    (second_name, second): (&str, &Option<B>),
+) -> Result<(), ApiError> {
+    if first.is_some() && second.is_some() {
+        Err(ApiError::BadRequest(format!(
+            "`{}` and `{}` parameters cannot be used together",
+            first_name, second_name
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(from: Option<u64>, to: Option<u64>, limit: usize, order: Order) -> PageQuery {
+        PageQuery {
+            from,
+            to,
+            limit: Some(limit),
+            order,
+        }
+    }
+
+    #[test]
+    fn test_ascending_positions() {
+        let q = query(None, None, 3, Order::Ascending);
+        assert_eq!(q.positions(5, 10).unwrap(), (vec![0, 1, 2], Some(3)));
+        let q = query(Some(3), None, 3, Order::Ascending);
+        assert_eq!(q.positions(5, 10).unwrap(), (vec![3, 4], None));
+        let q = query(Some(1), Some(2), 3, Order::Ascending);
+        assert_eq!(q.positions(5, 10).unwrap(), (vec![1, 2], None));
+        let q = query(Some(7), None, 3, Order::Ascending);
+        assert_eq!(q.positions(5, 10).unwrap(), (vec![], None));
+        assert_eq!(q.positions(0, 10).unwrap(), (vec![], None));
+    }
+
+    #[test]
+    fn test_descending_positions() {
+        let q = query(None, None, 3, Order::Descending);
+        assert_eq!(q.positions(5, 10).unwrap(), (vec![4, 3, 2], Some(1)));
+        let q = query(Some(1), None, 3, Order::Descending);
+        assert_eq!(q.positions(5, 10).unwrap(), (vec![1, 0], None));
+        let q = query(Some(10), Some(2), 3, Order::Descending);
+        assert_eq!(q.positions(5, 10).unwrap(), (vec![4, 3, 2], None));
+        let q = query(None, Some(5), 3, Order::Descending);
+        assert_eq!(q.positions(5, 10).unwrap(), (vec![], None));
+        assert_eq!(q.positions(0, 10).unwrap(), (vec![], None));
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        assert!(query(None, None, 11, Order::Ascending)
+            .positions(5, 10)
+            .is_err());
+        assert!(query(None, None, 0, Order::Ascending)
+            .positions(5, 10)
+            .is_err());
+        assert!(query(Some(2), Some(1), 3, Order::Ascending)
+            .positions(5, 10)
+            .is_err());
+        assert!(query(Some(1), Some(2), 3, Order::Descending)
+            .positions(5, 10)
+            .is_err());
+        assert!(check_exclusive(("from", &Some(1)), ("to", &Some(2))).is_err());
+        assert!(check_exclusive(("from", &Some(1)), ("to", &None::<u64>)).is_ok());
+    }
+
+    #[test]
+    fn test_default_query() {
+        let q = PageQuery::default();
+        let page = q.paginate(3, 2, |pos| Some(pos * 10)).unwrap();
+        assert_eq!(
+            page,
+            Page {
+                items: vec![0, 10],
+                total: Some(3),
+                next_cursor: Some(2),
+            }
+        );
+    }
+
+    #[test]
+    fn test_key_pages() {
+        let entries = || (0..5_u64).map(|key| (key, key * 10));
+        let key_query = |from: Option<u64>, limit| KeyPageQuery { from, limit };
+
+        let page = key_query(None, None).paginate(10, entries()).unwrap();
+        assert_eq!(page.items, vec![0, 10, 20, 30, 40]);
+        assert_eq!(page.total, None);
+        assert_eq!(page.next_cursor, None);
+
+        let page = key_query(None, Some(2)).paginate(10, entries()).unwrap();
+        assert_eq!(page.items, vec![0, 10]);
+        assert_eq!(page.next_cursor, Some(2));
+        let page = key_query(Some(3), Some(2))
+            .paginate(10, entries().skip(3))
+            .unwrap();
+        assert_eq!(page.items, vec![30, 40]);
+        assert_eq!(page.next_cursor, None);
+
+        assert!(key_query(None, Some(11)).paginate(10, entries()).is_err());
+        assert!(key_query(None, Some(0)).paginate(10, entries()).is_err());
+    }
+}
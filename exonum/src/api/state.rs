@@ -12,6 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
+use super::{
+    auth::{ApiAccessControl, ApiRole},
+    consumers::ApiConsumers,
+    Error as ApiError,
+};
 use blockchain::Blockchain;
 use crypto::{PublicKey, SecretKey};
 use node::ApiSender;
@@ -26,12 +33,53 @@ use storage::Snapshot;
 #[derive(Debug, Clone)]
 pub struct ServiceApiState {
     blockchain: Blockchain,
+    access_control: Option<Arc<ApiAccessControl>>,
+    consumers: Option<ApiConsumers>,
 }
 
 impl ServiceApiState {
     /// Constructs state for the given blockchain.
     pub fn new(blockchain: Blockchain) -> Self {
-        Self { blockchain }
+        Self {
+            blockchain,
+            access_control: None,
+            consumers: None,
+        }
+    }
+
+    /// Enables token-based access control for the API served with this state.
+    pub fn with_access_control(mut self, access_control: ApiAccessControl) -> Self {
+        self.access_control = Some(Arc::new(access_control));
+        self
+    }
+
+    /// Enables resource accounting per API consumer for the API served with this state.
+    pub fn with_consumers(mut self, consumers: ApiConsumers) -> Self {
+        self.consumers = Some(consumers);
+        self
+    }
+
+    /// Returns the resource accounting per API consumer, if it is enabled.
+    pub fn consumers(&self) -> Option<&ApiConsumers> {
+        self.consumers.as_ref()
+    }
+
+    /// Returns the token if it is known to the access control of this API. Tokens are
+    /// never authenticated if access control is disabled.
+    pub fn authenticate<'a>(&self, token: Option<&'a str>) -> Option<&'a str> {
+        match self.access_control {
+            Some(ref access_control) => token.filter(|token| access_control.role(token).is_some()),
+            None => None,
+        }
+    }
+
+    /// Checks that the given token grants the `required` role. If access control is
+    /// disabled, every request is authorized.
+    pub fn authorize(&self, token: Option<&str>, required: ApiRole) -> Result<(), ApiError> {
+        match self.access_control {
+            Some(ref access_control) => access_control.authorize(token, required),
+            None => Ok(()),
+        }
     }
 
     /// Returns a reference to the blockchain of this node.
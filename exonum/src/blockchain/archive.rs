@@ -0,0 +1,405 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Block archive file format.
+//!
+//! Block archives allow distributing the blockchain history out-of-band, e.g., via
+//! a CDN or a file share, instead of downloading it from the peers block by block.
+//! The history is split into segments of [`SEGMENT_SIZE`] consecutive blocks each;
+//! a segment is stored in a single archive file.
+//!
+//! # Format
+//!
+//! All integers are encoded in little-endian byte order. An archive consists of:
+//!
+//! - header: magic bytes `EXNMARCH`, format version (`u16`) and the height
+//!   of the first block (`u64`);
+//! - block records, each consisting of the body length (`u32`), the body and
+//!   the SHA-256 hash of the body used as a checksum. The body contains the serialized
+//!   block header, precommits and transactions of the block, each one prefixed
+//!   by its length (`u32`); the lists of precommits and transactions are prefixed
+//!   by the number of their items (`u32`);
+//! - index, which contains the offsets of the block records (`u64` each);
+//! - footer: the offset of the index (`u64`), the number of blocks (`u32`)
+//!   and the magic bytes.
+//!
+//! Records are only appended to the archive, and the index is written after the last one.
+//! Hence, the archive can be produced in a streaming fashion, while the index allows
+//! reading any block without scanning the whole file.
+//!
+//! [`SEGMENT_SIZE`]: constant.SEGMENT_SIZE.html
+
+use byteorder::{ByteOrder, LittleEndian};
+use failure;
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use super::{Block, Schema};
+use crypto::{self, Hash, HASH_SIZE};
+use encoding::Field;
+use helpers::Height;
+use messages::{Message, Precommit, ProtocolMessage, RawTransaction, Signed};
+use storage::{Snapshot, StorageValue};
+
+/// Number of blocks in a single archive segment.
+pub const SEGMENT_SIZE: u64 = 1000;
+
+/// Current version of the archive format.
+pub const ARCHIVE_VERSION: u16 = 1;
+
+const MAGIC: &[u8; 8] = b"EXNMARCH";
+const HEADER_SIZE: usize = 18;
+const FOOTER_SIZE: usize = 20;
+
+/// Block data stored in the archive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchivedBlock {
+    /// Block header.
+    pub block: Block,
+    /// Precommits authorizing the block.
+    pub precommits: Vec<Signed<Precommit>>,
+    /// Transactions of the block in the order of their execution.
+    pub transactions: Vec<Signed<RawTransaction>>,
+}
+
+impl ArchivedBlock {
+    /// Reads the block at the given height from the blockchain state,
+    /// or returns `None` if the block is not committed yet.
+    pub fn from_snapshot<T: AsRef<dyn Snapshot>>(snapshot: T, height: Height) -> Option<Self> {
+        let schema = Schema::new(snapshot);
+        let block_hash = schema.block_hashes_by_height().get(height.0)?;
+        let block = schema.blocks().get(&block_hash)?;
+        let precommits = schema.precommits(&block_hash).iter().collect();
+        let transactions = schema
+            .block_transactions(height)
+            .iter()
+            .map(|tx_hash| {
+                schema
+                    .transactions()
+                    .get(&tx_hash)
+                    .expect("BUG: Cannot find transaction in database")
+            }).collect();
+        Some(Self {
+            block,
+            precommits,
+            transactions,
+        })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        write_bytes(&mut body, &self.block.clone().into_bytes());
+        write_u32(&mut body, self.precommits.len());
+        for precommit in &self.precommits {
+            write_bytes(&mut body, precommit.signed_message().raw());
+        }
+        write_u32(&mut body, self.transactions.len());
+        for transaction in &self.transactions {
+            write_bytes(&mut body, transaction.signed_message().raw());
+        }
+        body
+    }
+
+    fn decode(mut body: &[u8]) -> Result<Self, failure::Error> {
+        let block = read_block_header(read_bytes(&mut body)?)?;
+        let precommits = read_messages(&mut body)?;
+        let transactions = read_messages(&mut body)?;
+        ensure!(
+            body.is_empty(),
+            "Unexpected trailing bytes in the block record"
+        );
+        Ok(Self {
+            block,
+            precommits,
+            transactions,
+        })
+    }
+}
+
+/// Writer of the block archive.
+#[derive(Debug)]
+pub struct ArchiveWriter<W> {
+    inner: W,
+    offset: u64,
+    offsets: Vec<u64>,
+    next_height: Height,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    /// Starts an archive with the blocks from the given height.
+    pub fn new(mut inner: W, first_height: Height) -> io::Result<Self> {
+        let mut header = [0; HEADER_SIZE];
+        header[..8].copy_from_slice(MAGIC);
+        LittleEndian::write_u16(&mut header[8..10], ARCHIVE_VERSION);
+        LittleEndian::write_u64(&mut header[10..], first_height.0);
+        inner.write_all(&header)?;
+
+        Ok(Self {
+            inner,
+            offset: HEADER_SIZE as u64,
+            offsets: Vec::new(),
+            next_height: first_height,
+        })
+    }
+
+    /// Appends a block to the archive. Blocks should be appended in the order of their heights.
+    pub fn append(&mut self, block: &ArchivedBlock) -> Result<(), failure::Error> {
+        ensure!(
+            block.block.height() == self.next_height,
+            "Expected block at height {}, got {}",
+            self.next_height,
+            block.block.height()
+        );
+
+        let body = block.encode();
+        let mut record = Vec::with_capacity(4 + body.len() + HASH_SIZE);
+        write_bytes(&mut record, &body);
+        record.extend_from_slice(crypto::hash(&body).as_ref());
+        self.inner.write_all(&record)?;
+
+        self.offsets.push(self.offset);
+        self.offset += record.len() as u64;
+        self.next_height.increment();
+        Ok(())
+    }
+
+    /// Writes the index and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let mut index = vec![0; self.offsets.len() * 8];
+        LittleEndian::write_u64_into(&self.offsets, &mut index);
+        self.inner.write_all(&index)?;
+
+        let mut footer = [0; FOOTER_SIZE];
+        LittleEndian::write_u64(&mut footer[..8], self.offset);
+        LittleEndian::write_u32(&mut footer[8..12], self.offsets.len() as u32);
+        footer[12..].copy_from_slice(MAGIC);
+        self.inner.write_all(&footer)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+/// Reader of the block archive.
+///
+/// All the sizes read from the archive are checked against the size of the archive,
+/// so a malformed archive results in an error rather than in an excessive allocation.
+#[derive(Debug)]
+pub struct ArchiveReader<R> {
+    inner: R,
+    first_height: Height,
+    offsets: Vec<u64>,
+    index_offset: u64,
+}
+
+impl<R: Read + Seek> ArchiveReader<R> {
+    /// Opens an archive, checking its header and reading the index.
+    pub fn new(mut inner: R) -> Result<Self, failure::Error> {
+        let mut header = [0; HEADER_SIZE];
+        inner.seek(SeekFrom::Start(0))?;
+        inner.read_exact(&mut header)?;
+        ensure!(&header[..8] == MAGIC, "Not a block archive");
+        let version = LittleEndian::read_u16(&header[8..10]);
+        ensure!(
+            version == ARCHIVE_VERSION,
+            "Unsupported archive version {}",
+            version
+        );
+        let first_height = Height(LittleEndian::read_u64(&header[10..]));
+
+        let archive_len = inner.seek(SeekFrom::End(0))?;
+        ensure!(
+            archive_len >= (HEADER_SIZE + FOOTER_SIZE) as u64,
+            "Block archive is incomplete"
+        );
+        let mut footer = [0; FOOTER_SIZE];
+        inner.seek(SeekFrom::Start(archive_len - FOOTER_SIZE as u64))?;
+        inner.read_exact(&mut footer)?;
+        ensure!(&footer[12..] == MAGIC, "Block archive is incomplete");
+        let index_offset = LittleEndian::read_u64(&footer[..8]);
+        let len = u64::from(LittleEndian::read_u32(&footer[8..12]));
+        // The index is located right before the footer.
+        ensure!(
+            index_offset >= HEADER_SIZE as u64
+                && index_offset.checked_add(len * 8) == Some(archive_len - FOOTER_SIZE as u64),
+            "Invalid index of the block archive"
+        );
+
+        let mut index = vec![0; len as usize * 8];
+        inner.seek(SeekFrom::Start(index_offset))?;
+        inner.read_exact(&mut index)?;
+        let mut offsets = vec![0; len as usize];
+        LittleEndian::read_u64_into(&index, &mut offsets);
+        ensure!(
+            offsets
+                .iter()
+                .all(|&offset| offset >= HEADER_SIZE as u64 && offset < index_offset),
+            "Invalid index of the block archive"
+        );
+
+        Ok(Self {
+            inner,
+            first_height,
+            offsets,
+            index_offset,
+        })
+    }
+
+    /// Returns the height of the first block in the archive.
+    pub fn first_height(&self) -> Height {
+        self.first_height
+    }
+
+    /// Returns the number of blocks in the archive.
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Returns `true` if the archive contains no blocks.
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Reads the block at the given height, verifying its checksum and the signatures
+    /// of its messages. Returns an error if the block is not in the archive.
+    pub fn read_block(&mut self, height: Height) -> Result<ArchivedBlock, failure::Error> {
+        let offset = height
+            .0
+            .checked_sub(self.first_height.0)
+            .and_then(|position| self.offsets.get(position as usize))
+            .cloned()
+            .ok_or_else(|| format_err!("Block at height {} is not in the archive", height))?;
+
+        let mut len = [0; 4];
+        self.inner.seek(SeekFrom::Start(offset))?;
+        self.inner.read_exact(&mut len)?;
+        let record_len = u64::from(LittleEndian::read_u32(&len)) + HASH_SIZE as u64;
+        // Records are located between the header and the index.
+        ensure!(
+            offset + 4 + record_len <= self.index_offset,
+            "Invalid length of the record for the block at height {}",
+            height
+        );
+        let mut record = vec![0; record_len as usize];
+        self.inner.read_exact(&mut record)?;
+
+        let (body, checksum) = record.split_at(record.len() - HASH_SIZE);
+        ensure!(
+            Hash::from_slice(checksum) == Some(crypto::hash(body)),
+            "Checksum mismatch for the block at height {}",
+            height
+        );
+        let block = ArchivedBlock::decode(body)?;
+        ensure!(
+            block.block.height() == height,
+            "Unexpected block height {} in the record for height {}",
+            block.block.height(),
+            height
+        );
+        Ok(block)
+    }
+}
+
+/// Returns the number of segments which are complete for the given blockchain state.
+pub fn complete_segments<T: AsRef<dyn Snapshot>>(snapshot: T) -> u64 {
+    let blocks = Schema::new(snapshot).block_hashes_by_height().len();
+    blocks / SEGMENT_SIZE
+}
+
+/// Returns `true` if the data of some blocks in the segment with the given number is pruned,
+/// so that the segment cannot be written.
+pub fn is_pruned_segment<T: AsRef<dyn Snapshot>>(snapshot: T, segment: u64) -> bool {
+    segment * SEGMENT_SIZE < Schema::new(snapshot).pruned_height().0
+}
+
+/// Writes the segment with the given number into an archive.
+/// Returns an error if the segment is not complete yet or is pruned.
+pub fn write_segment<T: AsRef<dyn Snapshot>, W: Write>(
+    snapshot: T,
+    segment: u64,
+    writer: W,
+) -> Result<W, failure::Error> {
+    ensure!(
+        segment < complete_segments(&snapshot),
+        "Segment {} is not complete yet",
+        segment
+    );
+    ensure!(
+        !is_pruned_segment(&snapshot, segment),
+        "Segment {} is pruned",
+        segment
+    );
+
+    let first_height = Height(segment * SEGMENT_SIZE);
+    let mut writer = ArchiveWriter::new(writer, first_height)?;
+    for height in first_height.0..first_height.0 + SEGMENT_SIZE {
+        let block = ArchivedBlock::from_snapshot(&snapshot, Height(height))
+            .expect("BUG: Cannot find block in database");
+        writer.append(&block)?;
+    }
+    Ok(writer.finish()?)
+}
+
+fn write_u32(buffer: &mut Vec<u8>, value: usize) {
+    let mut bytes = [0; 4];
+    LittleEndian::write_u32(&mut bytes, value as u32);
+    buffer.extend_from_slice(&bytes);
+}
+
+fn write_bytes(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    write_u32(buffer, bytes.len());
+    buffer.extend_from_slice(bytes);
+}
+
+fn read_bytes_exact<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8], failure::Error> {
+    ensure!(data.len() >= len, "Unexpected end of the block record");
+    let (bytes, rest) = data.split_at(len);
+    *data = rest;
+    Ok(bytes)
+}
+
+fn read_u32(data: &mut &[u8]) -> Result<u32, failure::Error> {
+    read_bytes_exact(data, 4).map(LittleEndian::read_u32)
+}
+
+fn read_bytes<'a>(data: &mut &'a [u8]) -> Result<&'a [u8], failure::Error> {
+    let len = read_u32(data)? as usize;
+    read_bytes_exact(data, len)
+}
+
+/// Decodes the block header, checking the sizes of its fields.
+fn read_block_header(bytes: &[u8]) -> Result<Block, failure::Error> {
+    // The header is checked as a segment field of an enclosing structure.
+    let mut buffer = vec![0; 8];
+    Field::write(&bytes, &mut buffer, 0, 8);
+    <Block as Field>::check(&buffer, 0.into(), 8.into(), 8.into())
+        .map_err(|e| format_err!("Malformed block header: {}", e))?;
+    Ok(Block::from_bytes(bytes.into()))
+}
+
+fn read_messages<T: ProtocolMessage>(data: &mut &[u8]) -> Result<Vec<Signed<T>>, failure::Error> {
+    let count = read_u32(data)? as usize;
+    // Each message is prefixed by its length.
+    ensure!(
+        count <= data.len() / 4,
+        "Invalid number of messages in the block record"
+    );
+    let mut messages = Vec::with_capacity(count);
+    for _ in 0..count {
+        let message = Message::from_raw_buffer(read_bytes(data)?.to_vec())?;
+        let message = T::try_from(message)
+            .map_err(|_| format_err!("Unexpected message in the block record"))?;
+        messages.push(message);
+    }
+    Ok(messages)
+}
@@ -12,9 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crypto::Hash;
+use failure;
+
+use std::collections::HashSet;
+
+use super::ValidatorKeys;
+use crypto::{CryptoHash, Hash};
 use helpers::{Height, ValidatorId};
 use messages::{Precommit, Signed};
+use node::State;
 
 encoding_struct! {
     /// Exonum block header data structure.
@@ -57,10 +63,144 @@ pub struct BlockProof {
     pub precommits: Vec<Signed<Precommit>>,
 }
 
+impl BlockProof {
+    /// Verifies the proof against the given set of validators.
+    ///
+    /// See [`verify_block`](fn.verify_block.html) for details.
+    pub fn verify(&self, validator_keys: &[ValidatorKeys]) -> Result<(), failure::Error> {
+        verify_block(&self.block, &self.precommits, validator_keys)
+    }
+}
+
+/// Verifies that the block header is authenticated by the supermajority of validators.
+///
+/// The function does not require access to the blockchain storage and checks that:
+///
+/// - every `Precommit` has a valid signature of the consensus key of its validator
+///   from `validator_keys`,
+/// - every `Precommit` references the hash and the height of `block`,
+/// - all `Precommit`s belong to the same round and come from different validators,
+/// - there are precommits from at least 2/3+1 of the validators.
+///
+/// `validator_keys` must correspond to the configuration that was actual at
+/// the height of the block. The genesis block has no precommits and cannot
+/// be verified with this function. When verifying a chain of blocks, the caller
+/// should additionally check that the `prev_hash` of each block equals the hash
+/// of the preceding block.
+pub fn verify_block(
+    block: &Block,
+    precommits: &[Signed<Precommit>],
+    validator_keys: &[ValidatorKeys],
+) -> Result<(), failure::Error> {
+    let height = block.height();
+    let block_hash = block.hash();
+    let mut validators = HashSet::with_capacity(precommits.len());
+
+    for precommit in precommits {
+        // `Signed` values may be obtained bypassing signature verification
+        // (e.g., with `serde`), so the signature is checked once again.
+        Precommit::verify_precommit(precommit.signed_message().raw().to_vec()).map_err(|e| {
+            format_err!(
+                "Precommit for the block at height {} has invalid signature: {}",
+                height,
+                e
+            )
+        })?;
+
+        let validator = precommit.validator();
+        let keys = validator_keys.get(validator.0 as usize).ok_or_else(|| {
+            format_err!(
+                "Precommit for the block at height {} is authored by unknown validator {}",
+                height,
+                validator
+            )
+        })?;
+        ensure!(
+            precommit.author() == keys.consensus_key,
+            "Precommit for the block at height {} is not signed by validator {}",
+            height,
+            validator
+        );
+        ensure!(
+            precommit.height() == height && *precommit.block_hash() == block_hash,
+            "Precommit of validator {} does not correspond to the block at height {}",
+            validator,
+            height
+        );
+        ensure!(
+            precommit.round() == precommits[0].round(),
+            "Precommits for the block at height {} belong to different rounds",
+            height
+        );
+        ensure!(
+            validators.insert(validator),
+            "Block at height {} has several precommits from validator {}",
+            height,
+            validator
+        );
+    }
+
+    let majority_count = State::byzantine_majority_count(validator_keys.len());
+    ensure!(
+        validators.len() >= majority_count,
+        "Block at height {} has {} precommits, at least {} are required",
+        height,
+        validators.len(),
+        majority_count
+    );
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
+    use chrono::Utc;
+
     use super::*;
-    use crypto::hash;
+    use crypto::{gen_keypair, hash, SecretKey};
+    use helpers::Round;
+    use messages::Message;
+
+    fn create_validators(count: usize) -> (Vec<ValidatorKeys>, Vec<SecretKey>) {
+        (0..count)
+            .map(|_| {
+                let (consensus_key, secret_key) = gen_keypair();
+                let keys = ValidatorKeys {
+                    consensus_key,
+                    service_key: gen_keypair().0,
+                };
+                (keys, secret_key)
+            })
+            .unzip()
+    }
+
+    fn create_precommit(
+        validator: u16,
+        block: &Block,
+        round: Round,
+        keys: &ValidatorKeys,
+        secret_key: &SecretKey,
+    ) -> Signed<Precommit> {
+        let precommit = Precommit::new(
+            ValidatorId(validator),
+            block.height(),
+            round,
+            &hash(&[1]),
+            &block.hash(),
+            Utc::now(),
+        );
+        Message::concrete(precommit, keys.consensus_key, secret_key)
+    }
+
+    fn create_block() -> Block {
+        Block::new(
+            ValidatorId(0),
+            Height(5),
+            0,
+            &hash(&[1, 2]),
+            &Hash::zero(),
+            &hash(&[3, 4]),
+        )
+    }
 
     #[test]
     fn test_block() {
@@ -90,4 +230,71 @@ mod tests {
         let block1: Block = ::serde_json::from_str(&json_str).unwrap();
         assert_eq!(block1, block);
     }
+
+    #[test]
+    fn test_verify_block() {
+        let (keys, secret_keys) = create_validators(4);
+        let block = create_block();
+        let precommits = (0..3)
+            .map(|i| create_precommit(i as u16, &block, Round(1), &keys[i], &secret_keys[i]))
+            .collect::<Vec<_>>();
+
+        assert!(verify_block(&block, &precommits, &keys).is_ok());
+        let proof = BlockProof {
+            block: block.clone(),
+            precommits: precommits.clone(),
+        };
+        assert!(proof.verify(&keys).is_ok());
+
+        // Not enough precommits.
+        assert!(verify_block(&block, &precommits[..2], &keys).is_err());
+        // Duplicate precommits do not count towards the majority.
+        let duplicates = vec![
+            precommits[0].clone(),
+            precommits[0].clone(),
+            precommits[1].clone(),
+        ];
+        assert!(verify_block(&block, &duplicates, &keys).is_err());
+        // Precommits for a different block.
+        let other_block = Block::new(
+            ValidatorId(0),
+            Height(5),
+            0,
+            &hash(&[0]),
+            &Hash::zero(),
+            &hash(&[3, 4]),
+        );
+        assert!(verify_block(&other_block, &precommits, &keys).is_err());
+    }
+
+    #[test]
+    fn test_verify_block_with_wrong_signer() {
+        let (keys, secret_keys) = create_validators(4);
+        let block = create_block();
+        let precommit = |validator: u16, round: Round, signer: usize| {
+            create_precommit(
+                validator,
+                &block,
+                round,
+                &keys[signer],
+                &secret_keys[signer],
+            )
+        };
+        let mut precommits = vec![precommit(0, Round(1), 0), precommit(1, Round(1), 1)];
+
+        // Validator 3 signs on behalf of validator 2.
+        precommits.push(precommit(2, Round(1), 3));
+        assert!(verify_block(&block, &precommits, &keys).is_err());
+
+        // Precommits from different rounds.
+        precommits[2] = precommit(2, Round(2), 2);
+        assert!(verify_block(&block, &precommits, &keys).is_err());
+
+        // Unknown validator.
+        precommits[2] = precommit(7, Round(1), 2);
+        assert!(verify_block(&block, &precommits, &keys).is_err());
+
+        precommits[2] = precommit(2, Round(1), 2);
+        assert!(verify_block(&block, &precommits, &keys).is_ok());
+    }
 }
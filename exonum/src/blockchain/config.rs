@@ -26,8 +26,9 @@ use serde_json::{self, Error as JsonError};
 
 use std::collections::{BTreeMap, HashSet};
 
+use super::leader::{LeaderElection, RoundRobinElection, WeightedElection};
 use crypto::{hash, CryptoHash, Hash, PublicKey};
-use helpers::{Height, Milliseconds};
+use helpers::{Height, Milliseconds, Round, ValidatorId};
 use messages::EMPTY_SIGNED_MESSAGE_SIZE;
 use storage::StorageValue;
 
@@ -121,6 +122,114 @@ pub struct ConsensusConfig {
     /// in a block if the transaction pool is almost empty, and create blocks faster when there are
     /// enough transactions in the pool.
     pub propose_timeout_threshold: u32,
+    /// Strategy of choosing the leader of a consensus round. Validators propose blocks
+    /// in turns by default.
+    #[serde(default, skip_serializing_if = "LeaderElectionConfig::is_round_robin")]
+    pub leader_election: LeaderElectionConfig,
+    /// Senders whose transactions are guaranteed a reserved part of each block, so that
+    /// they cannot be crowded out by other transactions. There are no priority senders
+    /// by default.
+    #[serde(default, skip_serializing_if = "PrioritySendersConfig::is_empty")]
+    pub priority_senders: PrioritySendersConfig,
+    /// If set, the nodes drop and refuse the connections with the validators, for which
+    /// the evidence of equivocation is committed to the blockchain, until the next
+    /// configuration change. See [`EquivocationEvidence`] for details.
+    ///
+    /// [`EquivocationEvidence`]: struct.EquivocationEvidence.html
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub block_byzantine_peers: bool,
+    /// Height starting from which the root hash of the configuration history
+    /// ([`configs_actual_from`]) is aggregated into the state hash of the blocks.
+    /// The root hash is not aggregated if the height is not set, so that the validators
+    /// running the earlier versions agree on the state hash until the activation height.
+    /// Once activated, the aggregation cannot be turned off.
+    ///
+    /// [`configs_actual_from`]: struct.Schema.html#method.configs_actual_from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub configs_history_from: Option<Height>,
+}
+
+/// Strategy of choosing the leader of a consensus round.
+///
+/// # JSON presentation
+///
+/// ```json
+/// { "type": "weighted", "weights": [10, 20, 30, 40] }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LeaderElectionConfig {
+    /// Validators propose blocks in turns.
+    RoundRobin,
+    /// Leader is chosen pseudo-randomly with the probability proportional to the validator
+    /// weight. Weights are listed in the order of `validator_keys`, so they should be updated
+    /// together with the validator set.
+    Weighted {
+        /// Weights of the validators. Every weight must be positive.
+        weights: Vec<u64>,
+    },
+}
+
+impl LeaderElectionConfig {
+    /// Returns `true` if validators propose blocks in turns.
+    pub fn is_round_robin(&self) -> bool {
+        *self == LeaderElectionConfig::RoundRobin
+    }
+}
+
+impl Default for LeaderElectionConfig {
+    fn default() -> Self {
+        LeaderElectionConfig::RoundRobin
+    }
+}
+
+impl LeaderElection for LeaderElectionConfig {
+    fn leader(&self, height: Height, round: Round, validators_count: usize) -> ValidatorId {
+        match *self {
+            LeaderElectionConfig::RoundRobin => {
+                RoundRobinElection.leader(height, round, validators_count)
+            }
+            LeaderElectionConfig::Weighted { ref weights } => {
+                WeightedElection::new(weights).leader(height, round, validators_count)
+            }
+        }
+    }
+}
+
+/// Senders whose transactions are guaranteed a reserved part of each block, for example,
+/// the service keys of the validators emitting oracle or anchoring transactions.
+///
+/// The reserved part is counted within `txs_block_limit`: the proposer selects the
+/// transactions of the priority senders first, and fills the rest of the block with
+/// other transactions.
+///
+/// # JSON presentation
+///
+/// ```json
+/// { "keys": ["43a72e71..."], "reserved_percent": 10 }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct PrioritySendersConfig {
+    /// Public keys of the priority senders.
+    pub keys: Vec<PublicKey>,
+    /// Percentage of `txs_block_limit` reserved for the transactions of the priority senders.
+    pub reserved_percent: u8,
+}
+
+impl PrioritySendersConfig {
+    /// Returns `true` if there are no priority senders.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns the number of transactions in a block reserved for the priority senders.
+    pub fn reserved_txs(&self, txs_block_limit: u32) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let reserved = u64::from(txs_block_limit) * u64::from(self.reserved_percent);
+        ((reserved + 99) / 100) as usize
+    }
 }
 
 impl ConsensusConfig {
@@ -176,10 +285,19 @@ impl Default for ConsensusConfig {
             min_propose_timeout: 10,
             max_propose_timeout: 200,
             propose_timeout_threshold: 500,
+            leader_election: LeaderElectionConfig::default(),
+            priority_senders: PrioritySendersConfig::default(),
+            block_byzantine_peers: false,
+            configs_history_from: None,
         }
     }
 }
 
+#[cfg_attr(feature = "cargo-clippy", allow(clippy::trivially_copy_pass_by_ref))]
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
 impl StoredConfiguration {
     /// Tries to serialize the given configuration into a UTF-8 encoded JSON.
     /// The method returns either the result of execution or an error.
@@ -233,6 +351,40 @@ impl StoredConfiguration {
             ));
         }
 
+        // Check leader election weights.
+        if let LeaderElectionConfig::Weighted { ref weights } = config.consensus.leader_election {
+            if weights.len() != config.validator_keys.len() {
+                return Err(JsonError::custom(format!(
+                    "Number of leader election weights ({}) must be equal to the number \
+                     of validators ({})",
+                    weights.len(),
+                    config.validator_keys.len()
+                )));
+            }
+            if let Some(id) = weights.iter().position(|&weight| weight == 0) {
+                return Err(JsonError::custom(format!(
+                    "Leader election weight of the validator {} must be positive",
+                    id
+                )));
+            }
+            let total_weight = weights
+                .iter()
+                .try_fold(0_u64, |total, &weight| total.checked_add(weight));
+            if total_weight.is_none() {
+                return Err(JsonError::custom(
+                    "Total leader election weight must fit into u64",
+                ));
+            }
+        }
+
+        // Check the reserved part of blocks.
+        if config.consensus.priority_senders.reserved_percent > 100 {
+            return Err(JsonError::custom(format!(
+                "reserved_percent ({}) of priority senders must not be greater than 100",
+                config.consensus.priority_senders.reserved_percent
+            )));
+        }
+
         // Check maximum message length for sanity.
         if config.consensus.max_message_len < MINIMAL_MESSAGE_LENGTH {
             return Err(JsonError::custom(format!(
@@ -365,6 +517,97 @@ mod tests {
         serialize_deserialize(&configuration);
     }
 
+    #[test]
+    fn leader_election_serialization() {
+        let mut configuration = create_test_configuration();
+        let json = String::from_utf8(configuration.try_serialize().unwrap()).unwrap();
+        assert!(!json.contains("leader_election"));
+
+        configuration.consensus.leader_election = LeaderElectionConfig::Weighted {
+            weights: vec![1, 2, 3],
+        };
+        assert_eq!(configuration, serialize_deserialize(&configuration));
+        let toml = toml::to_string(&configuration).unwrap();
+        assert_eq!(configuration, toml::from_str(&toml).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "Number of leader election weights (2) must be equal")]
+    fn invalid_leader_election_weights_count() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.leader_election = LeaderElectionConfig::Weighted {
+            weights: vec![1, 2],
+        };
+        serialize_deserialize(&configuration);
+    }
+
+    #[test]
+    #[should_panic(expected = "Leader election weight of the validator 0 must be positive")]
+    fn zero_leader_election_weights() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.leader_election = LeaderElectionConfig::Weighted {
+            weights: vec![0, 0, 0],
+        };
+        serialize_deserialize(&configuration);
+    }
+
+    #[test]
+    #[should_panic(expected = "Leader election weight of the validator 2 must be positive")]
+    fn partially_zero_leader_election_weights() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.leader_election = LeaderElectionConfig::Weighted {
+            weights: vec![1, 5, 0],
+        };
+        serialize_deserialize(&configuration);
+    }
+
+    #[test]
+    #[should_panic(expected = "Total leader election weight must fit into u64")]
+    fn overflowing_leader_election_weights() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.leader_election = LeaderElectionConfig::Weighted {
+            weights: vec![u64::max_value(), 1, 1],
+        };
+        serialize_deserialize(&configuration);
+    }
+
+    #[test]
+    fn priority_senders_serialization() {
+        let mut configuration = create_test_configuration();
+        let json = String::from_utf8(configuration.try_serialize().unwrap()).unwrap();
+        assert!(!json.contains("priority_senders"));
+
+        configuration.consensus.priority_senders = PrioritySendersConfig {
+            keys: vec![configuration.validator_keys[0].service_key],
+            reserved_percent: 10,
+        };
+        assert_eq!(configuration, serialize_deserialize(&configuration));
+        let toml = toml::to_string(&configuration).unwrap();
+        assert_eq!(configuration, toml::from_str(&toml).unwrap());
+    }
+
+    #[test]
+    fn priority_senders_reserved_txs() {
+        let mut priority_senders = PrioritySendersConfig {
+            keys: Vec::new(),
+            reserved_percent: 10,
+        };
+        assert_eq!(priority_senders.reserved_txs(1000), 0);
+        priority_senders.keys.push(PublicKey::zero());
+        assert_eq!(priority_senders.reserved_txs(1000), 100);
+        assert_eq!(priority_senders.reserved_txs(5), 1);
+        priority_senders.reserved_percent = 100;
+        assert_eq!(priority_senders.reserved_txs(5), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "reserved_percent (101) of priority senders must not be greater")]
+    fn invalid_priority_senders_reserved_percent() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.priority_senders.reserved_percent = 101;
+        serialize_deserialize(&configuration);
+    }
+
     fn create_test_configuration() -> StoredConfiguration {
         let validator_keys = (1..4)
             .map(|i| ValidatorKeys {
@@ -0,0 +1,90 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Evidence of the equivocation of validators.
+//!
+//! A validator equivocates if it signs two conflicting `Precommit` messages at the same
+//! height and round. Such a pair of messages is a self-contained proof that the validator
+//! is Byzantine, which can be verified by anyone knowing the validator keys. Services may
+//! commit the evidence to the blockchain with [`Schema::commit_equivocation_evidence`];
+//! if the `block_byzantine_peers` flag of the consensus configuration is set, the nodes then
+//! drop and refuse the connections with the offending validator until the next
+//! configuration change.
+//!
+//! [`Schema::commit_equivocation_evidence`]: struct.Schema.html#method.commit_equivocation_evidence
+
+use failure;
+
+use blockchain::StoredConfiguration;
+use crypto::PublicKey;
+use helpers::{Height, ValidatorId};
+use messages::{Precommit, Signed};
+
+/// Two conflicting `Precommit` messages signed by the same validator.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EquivocationEvidence {
+    /// The first of the conflicting messages.
+    pub first: Signed<Precommit>,
+    /// The second of the conflicting messages.
+    pub second: Signed<Precommit>,
+}
+
+impl EquivocationEvidence {
+    /// Creates the evidence from two conflicting messages.
+    pub fn new(first: Signed<Precommit>, second: Signed<Precommit>) -> Self {
+        Self { first, second }
+    }
+
+    /// Returns the identifier of the offending validator.
+    pub fn validator(&self) -> ValidatorId {
+        self.first.validator()
+    }
+
+    /// Returns the height at which the conflicting messages have been signed.
+    pub fn height(&self) -> Height {
+        self.first.height()
+    }
+
+    /// Checks that the messages are signed by the same validator of the given configuration
+    /// at the same height and round and vote for different blocks. Returns the consensus key
+    /// of the offending validator.
+    pub fn verify(&self, config: &StoredConfiguration) -> Result<PublicKey, failure::Error> {
+        let (first, second) = (&self.first, &self.second);
+        ensure!(
+            first.validator() == second.validator(),
+            "Messages are signed by different validators"
+        );
+        ensure!(
+            first.height() == second.height() && first.round() == second.round(),
+            "Messages belong to different rounds"
+        );
+        ensure!(
+            first.propose_hash() != second.propose_hash()
+                || first.block_hash() != second.block_hash(),
+            "Messages do not conflict"
+        );
+
+        let key = config
+            .validator_keys
+            .get(first.validator().0 as usize)
+            .map(|keys| keys.consensus_key)
+            .ok_or_else(|| format_err!("Unknown validator {:?}", first.validator()))?;
+        ensure!(
+            first.author() == key && second.author() == key,
+            "Messages are not signed by the validator {:?}",
+            first.validator()
+        );
+        Ok(key)
+    }
+}
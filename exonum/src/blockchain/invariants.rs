@@ -0,0 +1,374 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks of the invariants of the blockchain data.
+//!
+//! The checks are used by the `check-db` maintenance command and by the nodes
+//! running in the paranoid mode (see [`Blockchain::set_paranoid`]). The finality
+//! of the committed blocks is checked on each commit regardless of the mode: a committed
+//! block can never be reverted or replaced, even if the consensus is broken by a bug.
+//!
+//! [`Blockchain::set_paranoid`]: ../struct.Blockchain.html#method.set_paranoid
+
+use failure;
+
+use super::{verify_block, Block, Blockchain, Schema, CORE_SERVICE};
+use crypto::{CryptoHash, Hash};
+use helpers::Height;
+use messages::{Precommit, Signed};
+use storage::{
+    proof_list_index, proof_map_index::ProofMapKey, Fork, ProofListIndex, ProofMapIndex, Snapshot,
+    StorageValue,
+};
+
+/// Walks over the committed blocks and checks their linkage, the root hashes
+/// of the block transactions and of the core tables, and the precommit signatures
+/// of the blocks which are not pruned. Returns the height of the latest block.
+pub(crate) fn check_chain<T: AsRef<dyn Snapshot>>(snapshot: T) -> Result<Height, failure::Error> {
+    let schema = Schema::new(snapshot);
+    ensure!(
+        !schema.block_hashes_by_height().is_empty(),
+        "Database does not contain the genesis block"
+    );
+    let tip = schema.height();
+    // Precommits of the pruned blocks are removed, so they cannot be checked.
+    let pruned_height = schema.pruned_height();
+
+    let mut prev_hash = Hash::zero();
+    for height in 0..=tip.0 {
+        let height = Height(height);
+        let block_hash = schema
+            .block_hashes_by_height()
+            .get(height.0)
+            .ok_or_else(|| format_err!("Hash of the block at height {} is absent", height))?;
+        let block = schema
+            .blocks()
+            .get(&block_hash)
+            .ok_or_else(|| format_err!("Block at height {} is absent", height))?;
+
+        ensure!(
+            block.hash() == block_hash,
+            "Block at height {} has hash {:?}, but is stored under {:?}",
+            height,
+            block.hash(),
+            block_hash
+        );
+        ensure!(
+            block.height() == height,
+            "Block at height {} reports height {}",
+            height,
+            block.height()
+        );
+        ensure!(
+            *block.prev_hash() == prev_hash,
+            "Block at height {} is not linked with the previous block: \
+             expected prev_hash {:?}, found {:?}",
+            height,
+            prev_hash,
+            block.prev_hash()
+        );
+
+        let transactions = schema.block_transactions(height);
+        ensure!(
+            transactions.len() == u64::from(block.tx_count()),
+            "Block at height {} contains {} transactions, but {} are stored",
+            height,
+            block.tx_count(),
+            transactions.len()
+        );
+        let tx_hash = list_root(&transactions);
+        ensure!(
+            *block.tx_hash() == tx_hash && transactions.merkle_root() == tx_hash,
+            "Transactions root hash of the block at height {} does not match: \
+             expected {:?}, recomputed {:?}",
+            height,
+            block.tx_hash(),
+            tx_hash
+        );
+
+        if height > Height(0) && height >= pruned_height {
+            check_precommits(&schema, &block)?;
+        }
+        prev_hash = block_hash;
+    }
+
+    check_core_state_hash(&schema, tip)?;
+    Ok(tip)
+}
+
+fn check_precommits<T: AsRef<dyn Snapshot>>(
+    schema: &Schema<T>,
+    block: &Block,
+) -> Result<(), failure::Error> {
+    let config = schema.configuration_by_height(block.height());
+    let precommits = schema.precommits(&block.hash()).iter().collect::<Vec<_>>();
+    verify_block(block, &precommits, &config.validator_keys)
+}
+
+fn check_core_state_hash<T: AsRef<dyn Snapshot>>(
+    schema: &Schema<T>,
+    tip: Height,
+) -> Result<(), failure::Error> {
+    let mut core_state = vec![
+        map_root(&schema.configs())?,
+        map_root(&schema.transaction_results())?,
+    ];
+    if schema.is_configs_history_aggregated() {
+        core_state.push(list_root(&schema.configs_actual_from()));
+    }
+    let aggregator = schema.state_hash_aggregator();
+    for (idx, table_hash) in core_state.into_iter().enumerate() {
+        let key = Blockchain::service_table_unique_key(CORE_SERVICE, idx);
+        ensure!(
+            aggregator.get(&key) == Some(table_hash),
+            "Root hash of the core table {} does not match the recomputed one {:?}",
+            idx,
+            table_hash
+        );
+    }
+
+    let state_hash = map_root(&aggregator)?;
+    let last_block = schema.last_block();
+    ensure!(
+        *last_block.state_hash() == state_hash && aggregator.merkle_root() == state_hash,
+        "State hash of the block at height {} does not match: expected {:?}, recomputed {:?}",
+        tip,
+        last_block.state_hash(),
+        state_hash
+    );
+    Ok(())
+}
+
+/// Recomputes the root hash of the list from its entries.
+fn list_root<T, V>(index: &ProofListIndex<T, V>) -> Hash
+where
+    T: AsRef<dyn Snapshot>,
+    V: StorageValue,
+{
+    proof_list_index::streaming_root_hash(index.iter().map(|value| value.hash()))
+}
+
+/// Recomputes the root hash of the map from its entries and checks that all the entries
+/// are reachable from the root of the tree.
+fn map_root<T, K, V>(index: &ProofMapIndex<T, K, V>) -> Result<Hash, failure::Error>
+where
+    T: AsRef<dyn Snapshot>,
+    K: ProofMapKey,
+    V: StorageValue,
+{
+    let (root_hash, reachable) = index.recompute_root()?;
+    let stored = index.values().count() as u64;
+    ensure!(
+        reachable == stored,
+        "Only {} of {} entries are reachable from the root of the tree",
+        reachable,
+        stored
+    );
+    Ok(root_hash)
+}
+
+/// Checks the invariants of the core indexes which must hold after each merge:
+///
+/// - the cached size of the transaction pool matches the actual one and each
+///   transaction in the pool is stored;
+/// - the latest block is stored under its height;
+/// - each configuration reference points to a stored configuration, and the
+///   configurations are ordered by the height they become actual from.
+pub(crate) fn check_core_indexes<T: AsRef<dyn Snapshot>>(
+    snapshot: T,
+) -> Result<(), failure::Error> {
+    let schema = Schema::new(snapshot);
+
+    let pool = schema.transactions_pool();
+    let transactions = schema.transactions();
+    let mut pool_len = 0;
+    for tx_hash in pool.iter() {
+        ensure!(
+            transactions.contains(&tx_hash),
+            "Transaction {:?} from the pool is absent",
+            tx_hash
+        );
+        pool_len += 1;
+    }
+    ensure!(
+        schema.transactions_pool_len() == pool_len,
+        "Transaction pool contains {} transactions, but its size is cached as {}",
+        pool_len,
+        schema.transactions_pool_len()
+    );
+
+    if let Some(block_hash) = schema.block_hashes_by_height().last() {
+        let tip = Height(schema.block_hashes_by_height().len() - 1);
+        let block = schema
+            .blocks()
+            .get(&block_hash)
+            .ok_or_else(|| format_err!("Block at height {} is absent", tip))?;
+        ensure!(
+            block.height() == tip && block.hash() == block_hash,
+            "Block at height {} reports height {} and hash {:?}, but is stored under {:?}",
+            tip,
+            block.height(),
+            block.hash(),
+            block_hash
+        );
+    }
+
+    let configs = schema.configs();
+    let mut prev_actual_from = None;
+    for reference in schema.configs_actual_from().iter() {
+        ensure!(
+            configs.contains(reference.cfg_hash()),
+            "Configuration {:?} actual from height {} is absent",
+            reference.cfg_hash(),
+            reference.actual_from()
+        );
+        ensure!(
+            prev_actual_from.map_or(true, |prev| prev < reference.actual_from()),
+            "Configuration {:?} actual from height {} is out of order",
+            reference.cfg_hash(),
+            reference.actual_from()
+        );
+        prev_actual_from = Some(reference.actual_from());
+    }
+    Ok(())
+}
+
+/// Checks that the state hash of the latest block matches the root hashes of the core
+/// and service tables, and that these hashes are recorded in the state hash aggregator.
+pub(crate) fn check_state_hash(
+    blockchain: &Blockchain,
+    snapshot: &dyn Snapshot,
+) -> Result<(), failure::Error> {
+    let schema = Schema::new(snapshot);
+    let aggregator = schema.state_hash_aggregator();
+
+    let mut table_hashes = Vec::new();
+    for (idx, table_hash) in schema.core_state_hash().into_iter().enumerate() {
+        table_hashes.push((CORE_SERVICE, idx, table_hash));
+    }
+    for service in blockchain.service_map().values() {
+        for (idx, table_hash) in service.state_hash(snapshot).into_iter().enumerate() {
+            table_hashes.push((service.service_id(), idx, table_hash));
+        }
+    }
+
+    let tables_count = aggregator.keys().count();
+    ensure!(
+        tables_count == table_hashes.len(),
+        "State hash aggregator contains {} tables, but {} are expected",
+        tables_count,
+        table_hashes.len()
+    );
+    for (service_id, idx, table_hash) in table_hashes {
+        let key = Blockchain::service_table_unique_key(service_id, idx);
+        ensure!(
+            aggregator.get(&key) == Some(table_hash),
+            "Root hash of the table {} of the service {} does not match the recomputed one {:?}",
+            idx,
+            service_id,
+            table_hash
+        );
+    }
+
+    let state_hash = map_root(&aggregator)?;
+    let last_block = schema.last_block();
+    ensure!(
+        *last_block.state_hash() == state_hash,
+        "State hash of the block at height {} does not match: expected {:?}, recomputed {:?}",
+        last_block.height(),
+        last_block.state_hash(),
+        state_hash
+    );
+    Ok(())
+}
+
+/// Checks that the `fork` with the changes of the block with the given hash only appends
+/// this block to the blocks committed in the `snapshot`: the committed blocks are neither
+/// removed nor replaced, and the new block is linked with the latest committed one.
+pub(crate) fn check_finality(
+    snapshot: &dyn Snapshot,
+    fork: &Fork,
+    block_hash: Hash,
+) -> Result<(), failure::Error> {
+    let committed = Schema::new(snapshot);
+    let next = Schema::new(fork);
+    let committed_len = committed.block_hashes_by_height().len();
+    let len = next.block_hashes_by_height().len();
+    ensure!(
+        len == committed_len + 1,
+        "Commit of the block {:?} changes the number of the committed blocks from {} to {}",
+        block_hash,
+        committed_len,
+        len
+    );
+    ensure!(
+        next.block_hash_by_height(Height(committed_len)) == Some(block_hash),
+        "Block {:?} is not committed at height {}",
+        block_hash,
+        committed_len
+    );
+    if committed_len == 0 {
+        return Ok(());
+    }
+
+    let tip = Height(committed_len - 1);
+    let tip_hash = committed.block_hash_by_height(tip);
+    ensure!(
+        next.block_hash_by_height(tip) == tip_hash,
+        "Commit of the block {:?} replaces the committed block {:?} at height {}",
+        block_hash,
+        tip_hash,
+        tip
+    );
+    let block = next
+        .blocks()
+        .get(&block_hash)
+        .ok_or_else(|| format_err!("Block {:?} is absent", block_hash))?;
+    ensure!(
+        Some(*block.prev_hash()) == tip_hash,
+        "Block {:?} is not linked with the committed block {:?} at height {}",
+        block_hash,
+        tip_hash,
+        tip
+    );
+    Ok(())
+}
+
+/// Checks that the block with the given precommits does not conflict with the block
+/// committed at the same height. Returns an error if the blocks differ and the majority
+/// of validators has precommitted the conflicting block, i.e., the finality of the committed
+/// block is violated. Blocks without a valid majority of precommits are not considered
+/// conflicting.
+pub(crate) fn check_conflicting_block(
+    snapshot: &dyn Snapshot,
+    block: &Block,
+    precommits: &[Signed<Precommit>],
+) -> Result<(), failure::Error> {
+    let schema = Schema::new(snapshot);
+    let committed_hash = match schema.block_hash_by_height(block.height()) {
+        Some(hash) if hash != block.hash() => hash,
+        _ => return Ok(()),
+    };
+    let config = schema.configuration_by_height(block.height());
+    if verify_block(block, precommits, &config.validator_keys).is_err() {
+        return Ok(());
+    }
+    bail!(
+        "Finality violation at height {}: block {:?} precommitted by the majority of validators \
+         conflicts with the committed block {:?}",
+        block.height(),
+        block.hash(),
+        committed_hash
+    )
+}
@@ -0,0 +1,155 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Strategies of choosing the leader of a consensus round.
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crypto::hash;
+use helpers::{Height, Round, ValidatorId};
+
+/// Strategy of choosing the validator proposing a block in a consensus round.
+///
+/// The strategy should be deterministic: all validators must choose the same leader
+/// for the same height and round.
+pub trait LeaderElection {
+    /// Returns the leader for the specified height and round among `validators_count`
+    /// validators.
+    fn leader(&self, height: Height, round: Round, validators_count: usize) -> ValidatorId;
+}
+
+/// Validators propose blocks in turns. This strategy is used by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RoundRobinElection;
+
+impl LeaderElection for RoundRobinElection {
+    fn leader(&self, height: Height, round: Round, validators_count: usize) -> ValidatorId {
+        let height: u64 = height.into();
+        let round: u64 = round.into();
+        ValidatorId(((height + round) % (validators_count as u64)) as u16)
+    }
+}
+
+/// Leader is chosen pseudo-randomly with the probability proportional to the validator
+/// weight (e.g., its stake). Weights must be positive, so that every validator can
+/// propose blocks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedElection<'a> {
+    weights: &'a [u64],
+    total_weight: u64,
+}
+
+impl<'a> WeightedElection<'a> {
+    /// Creates the strategy with the given validator weights.
+    ///
+    /// # Panics
+    ///
+    /// If there are no weights, any weight is zero or the total weight does not fit
+    /// into `u64`.
+    pub fn new(weights: &'a [u64]) -> Self {
+        assert!(!weights.is_empty(), "No weights of validators");
+        assert!(
+            weights.iter().all(|&weight| weight > 0),
+            "Weights of validators must be positive"
+        );
+        let total_weight = weights
+            .iter()
+            .try_fold(0_u64, |total, &weight| total.checked_add(weight))
+            .expect("Total weight of validators overflows u64");
+        Self {
+            weights,
+            total_weight,
+        }
+    }
+}
+
+impl<'a> LeaderElection for WeightedElection<'a> {
+    fn leader(&self, height: Height, round: Round, validators_count: usize) -> ValidatorId {
+        assert_eq!(
+            self.weights.len(),
+            validators_count,
+            "Number of weights does not match the number of validators"
+        );
+
+        let mut seed = [0; 12];
+        LittleEndian::write_u64(&mut seed[..8], height.into());
+        LittleEndian::write_u32(&mut seed[8..], round.into());
+        let mut point = LittleEndian::read_u64(&hash(&seed).as_ref()[..8]) % self.total_weight;
+
+        for (id, &weight) in self.weights.iter().enumerate() {
+            if point < weight {
+                return ValidatorId(id as u16);
+            }
+            point -= weight;
+        }
+        unreachable!("Point is always less than the total weight")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_robin() {
+        let election = RoundRobinElection;
+        assert_eq!(election.leader(Height(1), Round(1), 4), ValidatorId(2));
+        assert_eq!(election.leader(Height(1), Round(2), 4), ValidatorId(3));
+        assert_eq!(election.leader(Height(2), Round(3), 4), ValidatorId(1));
+    }
+
+    #[test]
+    fn test_weighted_distribution() {
+        let weights = [1, 2, 3, 4];
+        let election = WeightedElection::new(&weights);
+
+        let mut counts = [0; 4];
+        for height in 0..100 {
+            for round in 1..21 {
+                let leader = election.leader(Height(height), Round(round), weights.len());
+                counts[leader.0 as usize] += 1;
+            }
+        }
+        // Expected values are 200, 400, 600 and 800.
+        assert!(counts[0] > 120 && counts[0] < 280, "{:?}", counts);
+        assert!(counts[1] > 290 && counts[1] < 510, "{:?}", counts);
+        assert!(counts[2] > 470 && counts[2] < 730, "{:?}", counts);
+        assert!(counts[3] > 660 && counts[3] < 940, "{:?}", counts);
+    }
+
+    #[test]
+    fn test_weighted_is_deterministic() {
+        let weights = [5, 5, 5];
+        let first = WeightedElection::new(&weights);
+        let second = WeightedElection::new(&weights);
+        for round in 1..10 {
+            assert_eq!(
+                first.leader(Height(10), Round(round), 3),
+                second.leader(Height(10), Round(round), 3)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Weights of validators must be positive")]
+    fn test_weighted_zero_weights() {
+        WeightedElection::new(&[0, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Weights of validators must be positive")]
+    fn test_weighted_partially_zero_weights() {
+        WeightedElection::new(&[1, 0, 3, 4]);
+    }
+}
@@ -33,10 +33,16 @@
 //! [doc:create-service]: https://exonum.com/doc/get-started/create-service
 
 pub use self::{
-    block::{Block, BlockProof},
-    config::{ConsensusConfig, StoredConfiguration, ValidatorKeys},
+    block::{verify_block, Block, BlockProof},
+    config::{
+        ConsensusConfig, LeaderElectionConfig, PrioritySendersConfig, StoredConfiguration,
+        ValidatorKeys,
+    },
+    leader::{LeaderElection, RoundRobinElection, WeightedElection},
+    equivocation::EquivocationEvidence,
     genesis::GenesisConfig,
-    schema::{Schema, TxLocation},
+    registry::{RegisteredService, RegistryError, ServiceRegistry, TransactionInfo},
+    schema::{ConfigReference, IndexStats, Schema, TxLocation},
     service::{Service, ServiceContext, SharedNodeState},
     transaction::{
         ExecutionError, ExecutionResult, Transaction, TransactionContext, TransactionError,
@@ -44,28 +50,40 @@ pub use self::{
     },
 };
 
+pub mod archive;
 pub mod config;
+pub mod offchain;
 
 use byteorder::{ByteOrder, LittleEndian};
 use failure;
 use vec_map::VecMap;
 
 use std::{
+    cmp,
     collections::{BTreeMap, HashMap},
     error::Error as StdError,
     fmt, iter, mem, panic,
+    path::Path,
     sync::Arc,
 };
 
+use self::{offchain::ContentFetcher, scheduler::ExecutionPool};
 use crypto::{self, CryptoHash, Hash, PublicKey, SecretKey};
 use encoding::Error as MessageError;
 use helpers::{Height, Round, ValidatorId};
-use messages::{Connect, Message, Precommit, ProtocolMessage, RawTransaction, Signed};
+use messages::{
+    Connect, Message, Precommit, ProtocolMessage, RawTransaction, Signed, SignedMessage,
+};
 use node::ApiSender;
-use storage::{self, Database, Error, Fork, Patch, Snapshot};
+use storage::{self, CommitSyncPolicy, Database, Error, Fork, Patch, Snapshot};
 
 mod block;
+mod equivocation;
 mod genesis;
+pub(crate) mod invariants;
+mod leader;
+mod registry;
+mod scheduler;
 mod schema;
 mod service;
 #[macro_use]
@@ -76,6 +94,17 @@ mod tests;
 /// Id of core service table family.
 pub const CORE_SERVICE: u16 = 0;
 
+/// Root hash of a core or service table aggregated into the state hash of the block.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TableRoot {
+    /// Identifier of the service owning the table, or `CORE_SERVICE` for the core tables.
+    pub service_id: u16,
+    /// Index of the table in the list returned by `Service::state_hash`.
+    pub table_idx: usize,
+    /// Root hash of the table.
+    pub root: Hash,
+}
+
 /// Exonum blockchain instance with a certain services set and data storage.
 ///
 /// Only nodes with an identical set of services and genesis block can be combined
@@ -83,9 +112,14 @@ pub const CORE_SERVICE: u16 = 0;
 pub struct Blockchain {
     db: Arc<dyn Database>,
     service_map: Arc<VecMap<Box<dyn Service>>>,
+    registry: Arc<ServiceRegistry>,
     #[doc(hidden)]
     pub service_keypair: (PublicKey, SecretKey),
     pub(crate) api_sender: ApiSender,
+    commit_sync: CommitSyncPolicy,
+    execution_pool: Option<Arc<ExecutionPool>>,
+    paranoid: bool,
+    offchain_fetcher: Option<Arc<dyn ContentFetcher>>,
 }
 
 impl Blockchain {
@@ -97,23 +131,23 @@ impl Blockchain {
         service_secret_key: SecretKey,
         api_sender: ApiSender,
     ) -> Self {
+        let registry =
+            ServiceRegistry::with_services(&services).unwrap_or_else(|e| panic!("{}", e));
         let mut service_map = VecMap::new();
         for service in services {
-            let id = service.service_id() as usize;
-            if service_map.contains_key(id) {
-                panic!(
-                    "Services have already contain service with id={}, please change it.",
-                    id
-                );
-            }
-            service_map.insert(id, service);
+            service_map.insert(service.service_id() as usize, service);
         }
 
         Self {
             db: storage.into(),
             service_map: Arc::new(service_map),
+            registry: Arc::new(registry),
             service_keypair: (service_public_key, service_secret_key),
             api_sender,
+            commit_sync: CommitSyncPolicy::default(),
+            execution_pool: None,
+            paranoid: false,
+            offchain_fetcher: None,
         }
     }
 
@@ -126,6 +160,56 @@ impl Blockchain {
         }
     }
 
+    /// Sets the policy of syncing the block commits to the disk.
+    /// See [`CommitSyncPolicy`](../storage/enum.CommitSyncPolicy.html) for details.
+    pub fn set_commit_sync_policy(&mut self, policy: CommitSyncPolicy) {
+        self.commit_sync = policy;
+    }
+
+    /// Returns the policy of syncing the block commits to the disk.
+    pub fn commit_sync_policy(&self) -> CommitSyncPolicy {
+        self.commit_sync
+    }
+
+    /// Enables the parallel execution of the independent transactions within a block
+    /// on the pool with the given number of threads. Transactions are executed serially
+    /// if the number of threads is less than two.
+    pub fn set_execution_threads(&mut self, threads: usize) {
+        self.execution_pool = if threads > 1 {
+            Some(Arc::new(ExecutionPool::new(threads)))
+        } else {
+            None
+        };
+    }
+
+    /// Enables or disables the paranoid mode intended for long-running test networks.
+    ///
+    /// In this mode the blockchain validates the invariants of the changed indexes
+    /// on each merge, re-verifies the state hash after each committed block and
+    /// re-checks the whole chain, including the precommit signatures, on restart.
+    /// Violations are reported as errors (or panics on initialization), so that
+    /// the data corruption is caught early at the cost of performance.
+    pub fn set_paranoid(&mut self, paranoid: bool) {
+        self.paranoid = paranoid;
+    }
+
+    /// Returns `true` if the paranoid mode is enabled.
+    pub fn is_paranoid(&self) -> bool {
+        self.paranoid
+    }
+
+    /// Sets the fetcher used to check the availability of the off-chain content
+    /// referenced by the incoming transactions and to retrieve it in the explorer API.
+    /// See the [`offchain`](offchain/index.html) module for details.
+    pub fn set_offchain_fetcher(&mut self, fetcher: Arc<dyn ContentFetcher>) {
+        self.offchain_fetcher = Some(fetcher);
+    }
+
+    /// Returns the fetcher of the off-chain content, if any.
+    pub fn offchain_fetcher(&self) -> Option<&Arc<dyn ContentFetcher>> {
+        self.offchain_fetcher.as_ref()
+    }
+
     /// Returns the `VecMap` for all services. This is a map which
     /// contains service identifiers and service interfaces. The VecMap
     /// allows proceeding from the service identifier to the service itself.
@@ -133,6 +217,11 @@ impl Blockchain {
         &self.service_map
     }
 
+    /// Returns the registry of the services and their transaction types.
+    pub fn service_registry(&self) -> &ServiceRegistry {
+        &self.registry
+    }
+
     /// Creates a read-only snapshot of the current storage state.
     pub fn snapshot(&self) -> Box<dyn Snapshot> {
         self.db.snapshot()
@@ -160,9 +249,63 @@ impl Blockchain {
     }
 
     /// Commits changes from the patch to the blockchain storage.
+    /// The storage statistics of the changed indexes are updated in the same patch.
     /// See [`Fork`](../storage/struct.Fork.html) for details.
     pub fn merge(&mut self, patch: Patch) -> Result<(), Error> {
-        self.db.merge(patch)
+        let patch = self.with_storage_stats(patch);
+        if !self.paranoid {
+            return self.db.merge(patch);
+        }
+
+        let changed = Self::changed_indexes(&patch);
+        self.db.merge(patch)?;
+        self.check_merged(&changed)
+    }
+
+    /// Adds the updated statistics of the indexes changed by the patch to it.
+    /// The changes are attributed to the latest committed block.
+    fn with_storage_stats(&self, patch: Patch) -> Patch {
+        if patch.is_empty() {
+            return patch;
+        }
+        let snapshot = self.db.snapshot();
+        let blocks = Schema::new(&snapshot).block_hashes_by_height().len();
+        let height = Height(blocks.saturating_sub(1));
+
+        let mut fork = self.db.fork();
+        fork.merge(patch);
+        Schema::new(&mut fork).update_storage_stats(&*snapshot, height);
+        fork.into_patch()
+    }
+
+    /// Ensures that all the committed changes are saved to the persistent storage.
+    /// See [`Database::flush`](../storage/trait.Database.html#method.flush) for details.
+    pub fn flush(&self) -> Result<(), Error> {
+        self.db.flush()
+    }
+
+    /// Compacts the indexes with names starting with one of the given prefixes,
+    /// or all the indexes if `prefixes` is empty.
+    /// See [`Database::compact`](../storage/trait.Database.html#method.compact) for details.
+    pub fn compact(&self, prefixes: &[String]) -> Result<(), Error> {
+        self.db.compact(prefixes)
+    }
+
+    /// Returns `true` if the database can move the old blocks to the cold storage.
+    /// See [`Database::supports_archive`](../storage/trait.Database.html#method.supports_archive)
+    /// for details.
+    pub fn supports_archive(&self) -> bool {
+        self.db.supports_archive()
+    }
+
+    /// Writes a consistent copy of the blockchain state to a new persistent database
+    /// at the given path and returns the height of the latest block in the copy.
+    /// See [`write_checkpoint`](../storage/fn.write_checkpoint.html) for details.
+    pub fn checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<Height, Error> {
+        let snapshot = self.snapshot();
+        let height = Schema::new(&snapshot).height();
+        storage::write_checkpoint(&*snapshot, path)?;
+        Ok(height)
     }
 
     /// Returns the hash of the latest committed block.
@@ -189,12 +332,18 @@ impl Blockchain {
     ///
     /// * If the genesis block was not committed.
     /// * If storage version is not specified or not supported.
+    /// * If the identifier of a service differs from the one recorded in the genesis block.
     pub fn initialize(&mut self, cfg: GenesisConfig) -> Result<(), Error> {
         let has_genesis_block = !Schema::new(&self.snapshot())
             .block_hashes_by_height()
             .is_empty();
         if has_genesis_block {
             self.assert_storage_version();
+            self.assert_service_ids();
+            self.migrate_configs_actual_from();
+            if self.paranoid {
+                self.assert_integrity();
+            }
         } else {
             self.initialize_metadata();
             self.create_genesis_block(cfg)?;
@@ -228,6 +377,62 @@ impl Blockchain {
         }
     }
 
+    /// Moves the configuration references saved by an earlier version into the Merkelized
+    /// index, if any.
+    ///
+    /// The state hash does not change after the migration: the root hash of the migrated
+    /// index is aggregated into it only from the height set in the `configs_history_from`
+    /// field of the consensus configuration.
+    fn migrate_configs_actual_from(&mut self) {
+        let mut fork = self.fork();
+        if Schema::new(&mut fork).migrate_configs_actual_from() {
+            self.merge(fork.into_patch())
+                .expect("Unable to migrate the configuration references.");
+            info!("Configuration references have been migrated.");
+        }
+    }
+
+    /// Checks that the services have the identifiers recorded in the genesis block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the identifier of a service has changed.
+    fn assert_service_ids(&self) {
+        let snapshot = self.snapshot();
+        let allocated = Schema::new(&snapshot).service_ids().iter();
+        if let Err(e) = self.registry.check_allocation(allocated) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Re-checks the whole chain stored in the database and the state hash of the latest block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stored data is inconsistent.
+    fn assert_integrity(&self) {
+        let snapshot = self.snapshot();
+        let checked = invariants::check_chain(&snapshot)
+            .and_then(|height| invariants::check_state_hash(self, &*snapshot).map(|_| height));
+        match checked {
+            Ok(height) => info!("Blockchain is consistent up to height {}.", height),
+            Err(e) => panic!("Blockchain is inconsistent: {}", e),
+        }
+    }
+
+    /// Returns the names of the indexes changed by the patch.
+    fn changed_indexes(patch: &Patch) -> Vec<String> {
+        patch.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// Validates the invariants of the storage after the patch changing the given indexes
+    /// has been merged. Used in the paranoid mode only.
+    fn check_merged(&self, changed: &[String]) -> Result<(), Error> {
+        let snapshot = self.snapshot();
+        storage::check_indexes_registered(changed, &*snapshot)?;
+        invariants::check_core_indexes(&snapshot).map_err(|e| Error::new(e.to_string()))
+    }
+
     /// Creates and commits the genesis block with the given genesis configuration.
     fn create_genesis_block(&mut self, cfg: GenesisConfig) -> Result<(), Error> {
         let mut config_propose = StoredConfiguration {
@@ -243,14 +448,10 @@ impl Blockchain {
             // Update service tables
             for (_, service) in self.service_map.iter() {
                 let cfg = service.initialize(&mut fork);
-                let name = service.service_name();
-                if config_propose.services.contains_key(name) {
-                    panic!(
-                        "Services already contain service with '{}' name, please change it",
-                        name
-                    );
-                }
-                config_propose.services.insert(name.into(), cfg);
+                // Uniqueness of service names is checked by the service registry.
+                config_propose
+                    .services
+                    .insert(service.service_name().into(), cfg);
             }
             // Commit actual configuration
             {
@@ -260,6 +461,9 @@ impl Blockchain {
                     return Ok(());
                 }
                 schema.commit_configuration(config_propose);
+                for service in self.registry.services() {
+                    schema.service_ids_mut().put(&service.name, service.id);
+                }
             };
             self.merge(fork.into_patch())?;
             self.create_patch(ValidatorId::zero(), Height::zero(), &[])
@@ -269,6 +473,31 @@ impl Blockchain {
         Ok(())
     }
 
+    /// Returns the root hashes of the core and service tables which are aggregated
+    /// into the state hash of the block.
+    pub fn table_roots(&self, snapshot: &dyn Snapshot) -> Vec<TableRoot> {
+        let mut roots: Vec<_> = Schema::new(snapshot)
+            .core_state_hash()
+            .into_iter()
+            .enumerate()
+            .map(|(table_idx, root)| TableRoot {
+                service_id: CORE_SERVICE,
+                table_idx,
+                root,
+            }).collect();
+        for service in self.service_map.values() {
+            let service_id = service.service_id();
+            for (table_idx, root) in service.state_hash(snapshot).into_iter().enumerate() {
+                roots.push(TableRoot {
+                    service_id,
+                    table_idx,
+                    root,
+                });
+            }
+        }
+        roots
+    }
+
     /// Helper function to map a tuple (`u16`, `u16`) of service table coordinates
     /// to a 32-byte value to be used as the `ProofMapIndex` key (it currently
     /// supports only fixed size keys). The `hash` function is used to distribute
@@ -305,11 +534,18 @@ impl Blockchain {
             // Get last hash.
             let last_hash = self.last_hash();
             // Save & execute transactions.
-            for (index, hash) in tx_hashes.iter().enumerate() {
-                self.execute_transaction(*hash, height, index, &mut fork)
-                    // Execution could fail if the transaction
-                    // cannot be deserialized or it isn't in the pool.
-                    .expect("Transaction execution error.");
+            match self.execution_pool {
+                Some(ref pool) if tx_hashes.len() > 1 => {
+                    pool.execute_transactions(self, height, tx_hashes, &mut fork)
+                }
+                _ => {
+                    for (index, hash) in tx_hashes.iter().enumerate() {
+                        self.execute_transaction(*hash, height, index, &mut fork)
+                            // Execution could fail if the transaction
+                            // cannot be deserialized or it isn't in the pool.
+                            .expect("Transaction execution error.");
+                    }
+                }
             }
 
             // Invoke execute method for all services.
@@ -320,30 +556,17 @@ impl Blockchain {
                 }
             }
 
+            Schema::new(&mut fork).activate_configs_history(height);
+
             // Get tx & state hash.
             let (tx_hash, state_hash) = {
-                let state_hashes = {
-                    let schema = Schema::new(&fork);
-
-                    let vec_core_state = schema.core_state_hash();
-                    let mut state_hashes = Vec::new();
-
-                    for (idx, core_table_hash) in vec_core_state.into_iter().enumerate() {
-                        let key = Self::service_table_unique_key(CORE_SERVICE, idx);
-                        state_hashes.push((key, core_table_hash));
-                    }
-
-                    for service in self.service_map.values() {
-                        let service_id = service.service_id();
-                        let vec_service_state = service.state_hash(&fork);
-                        for (idx, service_table_hash) in vec_service_state.into_iter().enumerate() {
-                            let key = Self::service_table_unique_key(service_id, idx);
-                            state_hashes.push((key, service_table_hash));
-                        }
-                    }
-
-                    state_hashes
-                };
+                let state_hashes: Vec<_> = self
+                    .table_roots(&fork)
+                    .into_iter()
+                    .map(|table| {
+                        let key = Self::service_table_unique_key(table.service_id, table.table_idx);
+                        (key, table.root)
+                    }).collect();
 
                 let mut schema = Schema::new(&mut fork);
 
@@ -391,45 +614,100 @@ impl Blockchain {
         index: usize,
         fork: &mut Fork,
     ) -> Result<(), failure::Error> {
-        let (tx, raw, service_name) = {
-            let schema = Schema::new(&fork);
+        let (tx, raw, service_name) = self.prepare_transaction(tx_hash, fork)?;
+        let tx_result =
+            Self::run_transaction_with_dependency(tx_hash, tx.as_ref(), &raw, service_name, fork);
+        Self::save_transaction_result(tx_hash, tx_result, height, index, fork);
+        Ok(())
+    }
+
+    /// Executes the transaction on top of the fork if its dependency, if any, is committed.
+    /// Otherwise, the transaction fails with the `UnmetDependency` error without being executed.
+    ///
+    /// Both the serial and the parallel execution go through this method, so the result
+    /// does not depend on the number of the execution threads.
+    fn run_transaction_with_dependency(
+        tx_hash: Hash,
+        tx: &dyn Transaction,
+        raw: &Signed<RawTransaction>,
+        service_name: &str,
+        fork: &mut Fork,
+    ) -> TransactionResult {
+        match tx.depends_on() {
+            Some(dependency)
+                if !Schema::new(&*fork)
+                    .transactions_locations()
+                    .contains(&dependency) =>
+            {
+                let description = format!("Dependency {:?} is not committed", dependency);
+                TransactionResult(Err(TransactionError::unmet_dependency(Some(description))))
+            }
+            _ => Self::run_transaction(tx_hash, tx, raw, service_name, fork),
+        }
+    }
 
-            let raw = schema.transactions().get(&tx_hash).ok_or_else(|| {
+    /// Loads the transaction with the given hash from the storage and checks
+    /// that it can be executed.
+    fn prepare_transaction(
+        &self,
+        tx_hash: Hash,
+        fork: &Fork,
+    ) -> Result<(Box<dyn Transaction>, Signed<RawTransaction>, &str), failure::Error> {
+        let schema = Schema::new(fork);
+
+        // Transaction hashes are unique across all services, so a transaction
+        // cannot be executed more than once.
+        ensure!(
+            !schema.transactions_locations().contains(&tx_hash),
+            "Transaction is already committed. tx: {:?}",
+            tx_hash
+        );
+
+        let raw = schema.transactions().get(&tx_hash).ok_or_else(|| {
+            failure::err_msg(format!(
+                "BUG: Cannot find transaction in database. tx: {:?}",
+                tx_hash
+            ))
+        })?;
+
+        let service_name = self
+            .service_map
+            .get(raw.service_id() as usize)
+            .ok_or_else(|| {
                 failure::err_msg(format!(
-                    "BUG: Cannot find transaction in database. tx: {:?}",
-                    tx_hash
+                    "Service not found. Service id: {}",
+                    raw.service_id()
                 ))
-            })?;
-
-            let service_name = self
-                .service_map
-                .get(raw.service_id() as usize)
-                .ok_or_else(|| {
-                    failure::err_msg(format!(
-                        "Service not found. Service id: {}",
-                        raw.service_id()
-                    ))
-                })?.service_name();
-
-            let tx = self.tx_from_raw(raw.payload().clone()).or_else(|error| {
-                Err(failure::err_msg(format!(
-                    "Service <{}>: {}, tx: {:?}",
-                    service_name,
-                    error.description(),
-                    tx_hash
-                )))
-            })?;
-            (tx, raw, service_name)
-        };
+            })?.service_name();
+
+        let tx = self.tx_from_raw(raw.payload().clone()).or_else(|error| {
+            Err(failure::err_msg(format!(
+                "Service <{}>: {}, tx: {:?}",
+                service_name,
+                error.description(),
+                tx_hash
+            )))
+        })?;
+        Ok((tx, raw, service_name))
+    }
 
+    /// Executes the transaction on top of the fork. The changes made by the transaction
+    /// are rolled back if it fails or panics.
+    fn run_transaction(
+        tx_hash: Hash,
+        tx: &dyn Transaction,
+        raw: &Signed<RawTransaction>,
+        service_name: &str,
+        fork: &mut Fork,
+    ) -> TransactionResult {
         fork.checkpoint();
 
         let catch_result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
-            let context = TransactionContext::new(&mut *fork, &raw);
+            let context = TransactionContext::new(&mut *fork, raw);
             tx.execute(context)
         }));
 
-        let tx_result = TransactionResult(match catch_result {
+        TransactionResult(match catch_result {
             Ok(execution_result) => {
                 match execution_result {
                     Ok(()) => {
@@ -459,50 +737,120 @@ impl Blockchain {
                 );
                 Err(TransactionError::from_panic(&err))
             }
-        });
+        })
+    }
 
+    /// Records the result and the location of the executed transaction.
+    fn save_transaction_result(
+        tx_hash: Hash,
+        tx_result: TransactionResult,
+        height: Height,
+        index: usize,
+        fork: &mut Fork,
+    ) {
         let mut schema = Schema::new(fork);
         schema.transaction_results_mut().put(&tx_hash, tx_result);
         schema.commit_transaction(&tx_hash);
         schema.block_transactions_mut(height).push(tx_hash);
         let location = TxLocation::new(height, index as u64);
         schema.transactions_locations_mut().put(&tx_hash, location);
-        Ok(())
     }
 
     /// Commits to the blockchain a new block with the indicated changes (patch),
     /// hash and Precommit messages. After that invokes `after_commit`
     /// for each service in the increasing order of their identifiers.
+    ///
+    /// All the changes are written to the storage at once and are synced to the disk
+    /// according to the commit sync policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error without changing the storage if the patch does not append exactly
+    /// one block linked with the latest committed block, since the committed blocks are final.
     pub fn commit<I>(&mut self, patch: &Patch, block_hash: Hash, precommits: I) -> Result<(), Error>
     where
         I: Iterator<Item = Signed<Precommit>>,
     {
-        let patch = {
+        self.commit_with_archive(patch, block_hash, precommits, None)
+    }
+
+    /// Commits the block like [`commit`] and, if `archive` is specified, saves the given
+    /// consensus messages received at the height of the block to the consensus messages
+    /// archive in the same batch. The archived messages for the heights lower than
+    /// the specified one are removed.
+    ///
+    /// [`commit`]: #method.commit
+    pub(crate) fn commit_with_archive<I>(
+        &mut self,
+        patch: &Patch,
+        block_hash: Hash,
+        precommits: I,
+        archive: Option<(Vec<Message>, Height)>,
+    ) -> Result<(), Error>
+    where
+        I: Iterator<Item = Signed<Precommit>>,
+    {
+        let (patch, height) = {
             let mut fork = {
                 let mut fork = self.db.fork();
                 fork.merge(patch.clone()); // FIXME: Avoid cloning here. (ECR-1631)
                 fork
             };
+            let snapshot = self.db.snapshot();
+            if let Err(e) = invariants::check_finality(&*snapshot, &fork, block_hash) {
+                error!("Refusing to commit the block: {}", e);
+                return Err(Error::new(e.to_string()));
+            }
 
             {
                 let mut schema = Schema::new(&mut fork);
                 for precommit in precommits {
                     schema.precommits_mut(&block_hash).push(precommit.clone());
                 }
+                if let Some((messages, start)) = archive {
+                    let height = schema.last_block().height();
+                    schema.archive_consensus_messages(height, messages, start);
+                }
 
                 // Consensus messages cache is useful only during one height, so it should be
                 // cleared when a new height is achieved.
                 schema.consensus_messages_cache_mut().clear();
-                let txs_in_block = schema.last_block().tx_count();
+                // Only the transactions which were in the pool are accounted, since
+                // a transaction could be evicted from the pool of this node only.
+                let txs_in_pool = {
+                    let committed = Schema::new(&snapshot);
+                    let pool = committed.transactions_pool();
+                    let height = schema.last_block().height();
+                    schema
+                        .block_transactions(height)
+                        .iter()
+                        .filter(|tx_hash| pool.contains(tx_hash))
+                        .count() as u64
+                };
                 let txs_count = schema.transactions_pool_len_index().get().unwrap_or(0);
-                debug_assert!(txs_count >= u64::from(txs_in_block));
                 schema
                     .transactions_pool_len_index_mut()
-                    .set(txs_count - u64::from(txs_in_block));
+                    .set(txs_count.saturating_sub(txs_in_pool));
             }
-            fork.into_patch()
+            let height = Schema::new(&fork).height();
+            Schema::new(&mut fork).update_storage_stats(&*snapshot, height);
+            (fork.into_patch(), height)
         };
-        self.merge(patch)?;
+        let changed = if self.paranoid {
+            Self::changed_indexes(&patch)
+        } else {
+            Vec::new()
+        };
+        if self.commit_sync.should_sync(height.0) {
+            self.db.merge_sync(patch)?;
+        } else {
+            self.db.merge(patch)?;
+        }
+        if self.paranoid {
+            self.check_merged(&changed)?;
+            invariants::check_state_hash(self, &*self.snapshot())
+                .map_err(|e| Error::new(e.to_string()))?;
+        }
 
         // Invokes `after_commit` for each service in order of their identifiers
         for (service_id, service) in self.service_map.iter() {
@@ -546,10 +894,22 @@ impl Blockchain {
     }
 
     /// Returns `Connect` messages from peers saved in the cache, if any.
+    ///
+    /// Messages that cannot be decoded, for example, the ones saved in the format
+    /// of an earlier version, are skipped.
     pub fn get_saved_peers(&self) -> HashMap<PublicKey, Signed<Connect>> {
         let schema = Schema::new(self.snapshot());
-        let peers_cache = schema.peers_cache();
-        let it = peers_cache.iter().map(|(k, v)| (k, v.clone()));
+        let peers_cache = schema.peers_cache_raw();
+        let it = peers_cache.iter().filter_map(|(key, raw)| {
+            let peer = SignedMessage::from_raw_buffer(raw)
+                .and_then(Message::deserialize)
+                .ok()
+                .and_then(|message| Connect::try_from(message).ok());
+            if peer.is_none() {
+                warn!("Skipping malformed peer {:?} in the peers cache", key);
+            }
+            peer.map(|peer| (key, peer))
+        });
         it.collect()
     }
 
@@ -575,6 +935,68 @@ impl Blockchain {
         self.merge(fork.into_patch())
             .expect("Unable to save messages to the consensus cache");
     }
+
+    /// Moves the data of the blocks with the heights lower than `height` to the cold storage
+    /// of the database, at most `limit` blocks at once. Returns the number of moved blocks.
+    /// See [`Database::archive`](../storage/trait.Database.html#method.archive) for details.
+    ///
+    /// Nothing is moved if the database does not support the cold storage.
+    pub(crate) fn archive_blocks(&mut self, height: Height, limit: u64) -> Result<u64, Error> {
+        if !self.supports_archive() {
+            return Ok(0);
+        }
+        let (from, to, entries) = {
+            let snapshot = self.snapshot();
+            let schema = Schema::new(&snapshot);
+            let from = schema.cold_storage_height().0;
+            let to = cmp::min(height.0, from.saturating_add(limit));
+            let entries = (from..to)
+                .flat_map(|archived| schema.block_entries(Height(archived)))
+                .collect::<Vec<_>>();
+            (from, to, entries)
+        };
+        if to <= from {
+            return Ok(0);
+        }
+
+        self.db.archive(&entries)?;
+        let mut fork = self.fork();
+        Schema::new(&mut fork).set_cold_storage_height(Height(to));
+        self.merge(fork.into_patch())?;
+        Ok(to - from)
+    }
+
+    /// Removes the transaction bodies and the precommits of the blocks with the heights
+    /// lower than `height`, at most `limit` blocks at once. The block headers, the lists
+    /// of the transaction hashes, the transaction results and locations are kept.
+    /// Returns the number of pruned blocks.
+    pub(crate) fn prune_blocks(&mut self, height: Height, limit: u64) -> Result<u64, Error> {
+        let mut fork = self.fork();
+        let pruned = {
+            let mut schema = Schema::new(&mut fork);
+            let from = schema.pruned_height().0;
+            let to = cmp::min(height.0, from.saturating_add(limit));
+            if to <= from {
+                return Ok(0);
+            }
+            for pruned in from..to {
+                let height = Height(pruned);
+                let block_hash = schema
+                    .block_hash_by_height(height)
+                    .expect("BUG: Cannot find block hash in database");
+                schema.precommits_mut(&block_hash).clear();
+                let tx_hashes: Vec<_> = schema.block_transactions(height).iter().collect();
+                let mut transactions = schema.transactions_mut();
+                for tx_hash in tx_hashes {
+                    transactions.remove(&tx_hash);
+                }
+            }
+            schema.set_pruned_height(Height(to));
+            to - from
+        };
+        self.merge(fork.into_patch())?;
+        Ok(pruned)
+    }
 }
 
 fn before_commit(service: &dyn Service, fork: &mut Fork) {
@@ -607,8 +1029,13 @@ impl Clone for Blockchain {
         Self {
             db: Arc::clone(&self.db),
             service_map: Arc::clone(&self.service_map),
+            registry: Arc::clone(&self.registry),
             api_sender: self.api_sender.clone(),
             service_keypair: self.service_keypair.clone(),
+            commit_sync: self.commit_sync,
+            execution_pool: self.execution_pool.clone(),
+            paranoid: self.paranoid,
+            offchain_fetcher: self.offchain_fetcher.clone(),
         }
     }
 }
@@ -0,0 +1,253 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! References to the data stored outside of the blockchain.
+//!
+//! Large payloads such as documents or images are impractical to store on chain.
+//! Instead, a transaction may carry an [`OffchainRef`] with the hash of the content,
+//! its size and a URI of the storage, and return it from
+//! [`Transaction::offchain_refs`]. The content is then committed by its hash.
+//!
+//! If a [`ContentFetcher`] is set for the node (see [`Node::with_offchain_fetcher`]),
+//! the node rejects incoming transactions whose content cannot be retrieved, and the
+//! explorer API proxies the retrieval of the content of committed transactions. In both
+//! cases the retrieved data is checked against the hash and the size in the reference,
+//! so the storage does not need to be trusted.
+//!
+//! The framework provides [`FileFetcher`] for the content stored on the local filesystem;
+//! other storages (HTTP servers, IPFS gateways, etc.) are supported by implementing
+//! the `ContentFetcher` trait.
+//!
+//! [`OffchainRef`]: struct.OffchainRef.html
+//! [`Transaction::offchain_refs`]: ../trait.Transaction.html#method.offchain_refs
+//! [`ContentFetcher`]: trait.ContentFetcher.html
+//! [`Node::with_offchain_fetcher`]: ../../node/struct.Node.html#method.with_offchain_fetcher
+//! [`FileFetcher`]: struct.FileFetcher.html
+
+use failure;
+
+use std::{
+    fmt::Debug,
+    fs,
+    io::Read,
+    path::{Component, Path, PathBuf},
+};
+
+use crypto::{self, Hash};
+
+/// URI scheme supported by the `FileFetcher`.
+pub const FILE_SCHEME: &str = "file://";
+
+encoding_struct! {
+    /// Reference to the content stored outside of the blockchain.
+    struct OffchainRef {
+        /// Hash of the content.
+        content_hash: &Hash,
+        /// URI of the content in the off-chain storage.
+        uri: &str,
+        /// Size of the content in bytes.
+        size: u64,
+    }
+}
+
+/// Retrieves the off-chain content by its reference.
+///
+/// Implementations do not need to check the hash of the retrieved data; this is done
+/// by [`fetch_verified`](fn.fetch_verified.html). Since the fetcher is called while
+/// processing the incoming transactions, the implementations should bound the time
+/// spent on the retrieval.
+pub trait ContentFetcher: Send + Sync + Debug {
+    /// Retrieves the content referenced by `reference`.
+    fn fetch(&self, reference: &OffchainRef) -> Result<Vec<u8>, failure::Error>;
+}
+
+/// Errors that can occur while retrieving the off-chain content.
+#[derive(Debug, Fail)]
+pub enum OffchainError {
+    /// The content cannot be retrieved from the storage.
+    #[fail(display = "Content at `{}` is unavailable: {}", uri, error)]
+    Unavailable {
+        /// URI of the content.
+        uri: String,
+        /// Description of the retrieval error.
+        error: String,
+    },
+    /// The size of the retrieved content differs from the referenced one.
+    #[fail(
+        display = "Content at `{}` has size {}, expected {}",
+        uri, actual, expected
+    )]
+    SizeMismatch {
+        /// URI of the content.
+        uri: String,
+        /// Size specified in the reference.
+        expected: u64,
+        /// Size of the retrieved content.
+        actual: u64,
+    },
+    /// The hash of the retrieved content differs from the referenced one.
+    #[fail(display = "Content at `{}` does not match the committed hash", uri)]
+    HashMismatch {
+        /// URI of the content.
+        uri: String,
+    },
+}
+
+/// Retrieves the content with the given fetcher and checks it against the reference.
+pub fn fetch_verified(
+    fetcher: &dyn ContentFetcher,
+    reference: &OffchainRef,
+) -> Result<Vec<u8>, OffchainError> {
+    let uri = reference.uri().to_owned();
+    let data = fetcher
+        .fetch(reference)
+        .map_err(|e| OffchainError::Unavailable {
+            uri: uri.clone(),
+            error: e.to_string(),
+        })?;
+
+    if data.len() as u64 != reference.size() {
+        return Err(OffchainError::SizeMismatch {
+            uri,
+            expected: reference.size(),
+            actual: data.len() as u64,
+        });
+    }
+    if crypto::hash(&data) != *reference.content_hash() {
+        return Err(OffchainError::HashMismatch { uri });
+    }
+    Ok(data)
+}
+
+/// Fetcher of the content stored in the files under the given root directory.
+///
+/// The references should have URIs of the form `file://<path>`, where `path` is relative
+/// to the root directory. Absolute paths and paths leaving the root directory are rejected.
+#[derive(Debug, Clone)]
+pub struct FileFetcher {
+    root: PathBuf,
+}
+
+impl FileFetcher {
+    /// Creates a fetcher for the files under `root`.
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_owned(),
+        }
+    }
+
+    /// Returns the root directory of the fetcher.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn resolve(&self, uri: &str) -> Result<PathBuf, failure::Error> {
+        if !uri.starts_with(FILE_SCHEME) {
+            bail!("Unsupported URI scheme, expected `{}`", FILE_SCHEME);
+        }
+        let relative = Path::new(&uri[FILE_SCHEME.len()..]);
+        let is_normal = relative.components().all(|component| match component {
+            Component::Normal(_) | Component::CurDir => true,
+            _ => false,
+        });
+        if !is_normal || relative.as_os_str().is_empty() {
+            bail!("Path should be relative to the storage root");
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+impl ContentFetcher for FileFetcher {
+    fn fetch(&self, reference: &OffchainRef) -> Result<Vec<u8>, failure::Error> {
+        let path = self.resolve(reference.uri())?;
+        let file = fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        if len != reference.size() {
+            bail!("File has size {}, expected {}", len, reference.size());
+        }
+
+        let mut data = Vec::with_capacity(len as usize);
+        file.take(len).read_to_end(&mut data)?;
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use std::{fs::File, io::Write};
+
+    use super::*;
+
+    fn create_file(dir: &TempDir, name: &str, content: &[u8]) -> OffchainRef {
+        let mut file = File::create(dir.path().join(name)).unwrap();
+        file.write_all(content).unwrap();
+        OffchainRef::new(
+            &crypto::hash(content),
+            &format!("{}{}", FILE_SCHEME, name),
+            content.len() as u64,
+        )
+    }
+
+    #[test]
+    fn file_fetcher() {
+        let dir = TempDir::new("exonum_offchain").unwrap();
+        let fetcher = FileFetcher::new(dir.path());
+        let reference = create_file(&dir, "document.txt", b"Hello, world!");
+
+        let data = fetch_verified(&fetcher, &reference).unwrap();
+        assert_eq!(data, b"Hello, world!");
+    }
+
+    #[test]
+    fn file_fetcher_rejects_modified_content() {
+        let dir = TempDir::new("exonum_offchain").unwrap();
+        let fetcher = FileFetcher::new(dir.path());
+        let reference = create_file(&dir, "document.txt", b"Hello, world!");
+        create_file(&dir, "document.txt", b"Hello, World!");
+
+        match fetch_verified(&fetcher, &reference) {
+            Err(OffchainError::HashMismatch { ref uri }) => assert_eq!(uri, reference.uri()),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        create_file(&dir, "document.txt", b"Hello!");
+        match fetch_verified(&fetcher, &reference) {
+            Err(OffchainError::Unavailable { .. }) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn file_fetcher_rejects_paths_outside_root() {
+        let dir = TempDir::new("exonum_offchain").unwrap();
+        let fetcher = FileFetcher::new(dir.path().join("storage"));
+        let content = b"secret";
+        let hash = crypto::hash(content);
+
+        for uri in &[
+            "file://../secret.txt",
+            "file:///etc/passwd",
+            "file://",
+            "http://example.com/secret.txt",
+        ] {
+            let reference = OffchainRef::new(&hash, uri, content.len() as u64);
+            match fetch_verified(&fetcher, &reference) {
+                Err(OffchainError::Unavailable { .. }) => {}
+                other => panic!("Unexpected result for {}: {:?}", uri, other),
+            }
+        }
+    }
+}
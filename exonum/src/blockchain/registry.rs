@@ -0,0 +1,295 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Registry of the services and their transaction types.
+//!
+//! The registry is built when the blockchain is created and detects collisions
+//! of service identifiers, service names and transaction identifiers. Identifiers
+//! allocated to the services are recorded in the genesis block, so that a node
+//! refuses to start if the identifier of a service has changed afterwards.
+
+use std::collections::{btree_map::Entry, BTreeMap};
+
+use super::{Service, TransactionSet};
+
+/// Information about a transaction type of a service.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionInfo {
+    /// Identifier of the transaction type within the service.
+    pub id: u16,
+    /// Name of the transaction type.
+    pub name: String,
+}
+
+impl TransactionInfo {
+    /// Returns information about all transaction types of the given transaction set.
+    pub fn from_set<T: TransactionSet>() -> Vec<Self> {
+        T::transaction_types()
+            .into_iter()
+            .map(|(id, name)| Self {
+                id,
+                name: name.to_owned(),
+            }).collect()
+    }
+}
+
+/// Information about a registered service.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegisteredService {
+    /// Service identifier.
+    pub id: u16,
+    /// Service name.
+    pub name: String,
+    /// Transaction types of the service.
+    pub transactions: Vec<TransactionInfo>,
+}
+
+impl RegisteredService {
+    /// Collects information about the given service.
+    pub fn new(service: &dyn Service) -> Self {
+        Self {
+            id: service.service_id(),
+            name: service.service_name().to_owned(),
+            transactions: service.transaction_types(),
+        }
+    }
+}
+
+/// Errors that can occur while registering the services.
+#[derive(Debug, Clone, PartialEq, Eq, Fail)]
+pub enum RegistryError {
+    /// Two services have the same identifier.
+    #[fail(
+        display = "Services `{}` and `{}` have the same id {}, please change it",
+        first, second, id
+    )]
+    DuplicateServiceId {
+        /// Service identifier.
+        id: u16,
+        /// Name of the service registered first.
+        first: String,
+        /// Name of the service registered second.
+        second: String,
+    },
+    /// Two services have the same name.
+    #[fail(
+        display = "Services with ids {} and {} have the same name `{}`",
+        first, second, name
+    )]
+    DuplicateServiceName {
+        /// Service name.
+        name: String,
+        /// Identifier of the service registered first.
+        first: u16,
+        /// Identifier of the service registered second.
+        second: u16,
+    },
+    /// Two transaction types of a service have the same identifier.
+    #[fail(
+        display = "Service `{}` has several transactions with id {}",
+        service, id
+    )]
+    DuplicateTransactionId {
+        /// Service name.
+        service: String,
+        /// Transaction identifier.
+        id: u16,
+    },
+    /// The identifier of a service differs from the one recorded in the genesis block.
+    #[fail(
+        display = "Service `{}` has id {}, but id {} is recorded in the genesis block",
+        name, actual, recorded
+    )]
+    ChangedServiceId {
+        /// Service name.
+        name: String,
+        /// Identifier recorded in the genesis block.
+        recorded: u16,
+        /// Current identifier of the service.
+        actual: u16,
+    },
+}
+
+/// Registry of the services of the blockchain.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServiceRegistry {
+    services: BTreeMap<u16, RegisteredService>,
+}
+
+impl ServiceRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry with the given services.
+    pub fn with_services<'a, I>(services: I) -> Result<Self, RegistryError>
+    where
+        I: IntoIterator<Item = &'a Box<dyn Service>>,
+    {
+        let mut registry = Self::new();
+        for service in services {
+            registry.register(RegisteredService::new(service.as_ref()))?;
+        }
+        Ok(registry)
+    }
+
+    /// Registers a service. Returns an error if the identifier or the name of the service
+    /// is already used, or if the service has several transaction types with the same id.
+    pub fn register(&mut self, service: RegisteredService) -> Result<(), RegistryError> {
+        if let Some(other) = self.get_by_name(&service.name) {
+            return Err(RegistryError::DuplicateServiceName {
+                name: service.name.clone(),
+                first: other.id,
+                second: service.id,
+            });
+        }
+
+        let mut ids: Vec<_> = service.transactions.iter().map(|tx| tx.id).collect();
+        ids.sort();
+        if let Some(window) = ids.windows(2).find(|window| window[0] == window[1]) {
+            return Err(RegistryError::DuplicateTransactionId {
+                service: service.name.clone(),
+                id: window[0],
+            });
+        }
+
+        match self.services.entry(service.id) {
+            Entry::Occupied(entry) => Err(RegistryError::DuplicateServiceId {
+                id: service.id,
+                first: entry.get().name.clone(),
+                second: service.name,
+            }),
+            Entry::Vacant(entry) => {
+                entry.insert(service);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the registered services ordered by their identifiers.
+    pub fn services(&self) -> impl Iterator<Item = &RegisteredService> {
+        self.services.values()
+    }
+
+    /// Returns the service with the given identifier.
+    pub fn get(&self, id: u16) -> Option<&RegisteredService> {
+        self.services.get(&id)
+    }
+
+    /// Returns the service with the given name.
+    pub fn get_by_name(&self, name: &str) -> Option<&RegisteredService> {
+        self.services.values().find(|service| service.name == name)
+    }
+
+    /// Checks that the services have the identifiers allocated to them earlier.
+    /// Services missing from the `allocated` list are not checked.
+    pub fn check_allocation<I>(&self, allocated: I) -> Result<(), RegistryError>
+    where
+        I: IntoIterator<Item = (String, u16)>,
+    {
+        for (name, recorded) in allocated {
+            if let Some(service) = self.get_by_name(&name) {
+                if service.id != recorded {
+                    return Err(RegistryError::ChangedServiceId {
+                        name,
+                        recorded,
+                        actual: service.id,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(id: u16, name: &str, transactions: &[u16]) -> RegisteredService {
+        RegisteredService {
+            id,
+            name: name.to_owned(),
+            transactions: transactions
+                .iter()
+                .map(|&id| TransactionInfo {
+                    id,
+                    name: format!("Tx{}", id),
+                }).collect(),
+        }
+    }
+
+    #[test]
+    fn test_register() {
+        let mut registry = ServiceRegistry::new();
+        registry.register(service(1, "first", &[0, 1])).unwrap();
+        registry.register(service(0, "second", &[])).unwrap();
+
+        let ids: Vec<_> = registry.services().map(|service| service.id).collect();
+        assert_eq!(ids, vec![0, 1]);
+        assert_eq!(registry.get(1).unwrap().name, "first");
+        assert_eq!(registry.get_by_name("second").unwrap().id, 0);
+        assert!(registry.get(2).is_none());
+    }
+
+    #[test]
+    fn test_register_collisions() {
+        let mut registry = ServiceRegistry::new();
+        registry.register(service(1, "first", &[])).unwrap();
+
+        assert_eq!(
+            registry.register(service(1, "second", &[])),
+            Err(RegistryError::DuplicateServiceId {
+                id: 1,
+                first: "first".to_owned(),
+                second: "second".to_owned(),
+            })
+        );
+        assert_eq!(
+            registry.register(service(2, "first", &[])),
+            Err(RegistryError::DuplicateServiceName {
+                name: "first".to_owned(),
+                first: 1,
+                second: 2,
+            })
+        );
+        assert_eq!(
+            registry.register(service(2, "second", &[0, 1, 0])),
+            Err(RegistryError::DuplicateTransactionId {
+                service: "second".to_owned(),
+                id: 0,
+            })
+        );
+        assert_eq!(registry.services().count(), 1);
+    }
+
+    #[test]
+    fn test_check_allocation() {
+        let mut registry = ServiceRegistry::new();
+        registry.register(service(1, "first", &[])).unwrap();
+
+        assert!(registry
+            .check_allocation(vec![("first".to_owned(), 1), ("removed".to_owned(), 2)])
+            .is_ok());
+        assert_eq!(
+            registry.check_allocation(vec![("first".to_owned(), 3)]),
+            Err(RegistryError::ChangedServiceId {
+                name: "first".to_owned(),
+                recorded: 3,
+                actual: 1,
+            })
+        );
+    }
+}
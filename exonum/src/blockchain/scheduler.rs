@@ -0,0 +1,310 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parallel execution of the independent transactions within a block.
+//!
+//! Transactions of a block are split into batches of consecutive transactions. Transactions
+//! of a batch are executed speculatively on the worker threads, each one on top of the state
+//! left by the previous batches. Every key read by a transaction is tracked.
+//!
+//! The speculative results are then applied in the block order. If a transaction has read
+//! a key written by an earlier transaction of the same batch, its result is discarded and
+//! the transaction is executed again on top of the actual state. Thus, the resulting state
+//! is always identical to the one produced by the serial execution.
+//!
+//! The changes made by the previous batches are shared between the threads rather than
+//! copied, and only the changes of the current batch are copied for the re-execution.
+//!
+//! Note that the transactions updating the same Merkelized index always conflict with each
+//! other, since they modify the common nodes of the tree. Likewise, a transaction with
+//! a dependency reads the transaction locations and is executed again if any transaction
+//! was applied before it within the batch.
+
+use futures::{future, sync::oneshot, Future};
+use tokio_threadpool::{Builder as ThreadPoolBuilder, ThreadPool};
+
+use std::{
+    cell::RefCell,
+    collections::{Bound, HashSet},
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+    sync::Arc,
+};
+
+use super::{schema::TRANSACTION_BOOKKEEPING_INDEXES, Blockchain, Transaction, TransactionResult};
+use crypto::Hash;
+use helpers::Height;
+use messages::{RawTransaction, Signed};
+use storage::{Database, Fork, Iter, Patch, PatchedSnapshot, Snapshot};
+
+/// Keys read by a transaction during its execution.
+#[derive(Debug, Default)]
+struct ReadSet {
+    /// Individual keys identified by the index name and the key.
+    keys: HashSet<(String, Vec<u8>)>,
+    /// Names of the indexes iterated over.
+    ranges: HashSet<String>,
+}
+
+/// Changes made by the transactions already applied within the current batch.
+#[derive(Debug, Default)]
+struct WriteSet {
+    /// Individual keys identified by the index name and the key.
+    keys: HashSet<(String, Vec<u8>)>,
+    /// Names of the indexes with at least one changed key.
+    indexes: HashSet<String>,
+    /// Are the transaction results recorded by the core changed?
+    bookkeeping: bool,
+}
+
+impl WriteSet {
+    fn add_patch(&mut self, patch: &Patch) {
+        for (name, changes) in patch.iter() {
+            for (key, _) in changes.iter() {
+                self.keys.insert((name.clone(), key.clone()));
+            }
+            self.indexes.insert(name.clone());
+        }
+    }
+
+    fn conflicts_with(&self, reads: &ReadSet) -> bool {
+        let is_bookkeeping =
+            |name: &str| self.bookkeeping && TRANSACTION_BOOKKEEPING_INDEXES.contains(&name);
+
+        reads
+            .ranges
+            .iter()
+            .any(|name| self.indexes.contains(name) || is_bookkeeping(name))
+            || reads
+                .keys
+                .iter()
+                .any(|key| self.keys.contains(key) || is_bookkeeping(&key.0))
+    }
+}
+
+/// Snapshot recording the keys read from it.
+struct RecordingSnapshot {
+    inner: Box<dyn Snapshot>,
+    reads: Rc<RefCell<ReadSet>>,
+}
+
+impl Snapshot for RecordingSnapshot {
+    fn get(&self, name: &str, key: &[u8]) -> Option<Vec<u8>> {
+        self.record(name, key);
+        self.inner.get(name, key)
+    }
+
+    fn contains(&self, name: &str, key: &[u8]) -> bool {
+        self.record(name, key);
+        self.inner.contains(name, key)
+    }
+
+    fn iter<'a>(&'a self, name: &str, from: &[u8]) -> Iter<'a> {
+        self.reads.borrow_mut().ranges.insert(name.to_owned());
+        self.inner.iter(name, from)
+    }
+
+    fn iter_rev<'a>(&'a self, name: &str, to: Bound<&[u8]>) -> Iter<'a> {
+        self.reads.borrow_mut().ranges.insert(name.to_owned());
+        self.inner.iter_rev(name, to)
+    }
+}
+
+impl RecordingSnapshot {
+    fn record(&self, name: &str, key: &[u8]) {
+        self.reads
+            .borrow_mut()
+            .keys
+            .insert((name.to_owned(), key.to_vec()));
+    }
+}
+
+/// Transaction prepared for the execution.
+struct PreparedTransaction {
+    hash: Hash,
+    tx: Box<dyn Transaction>,
+    raw: Signed<RawTransaction>,
+    service_name: String,
+}
+
+/// Result of the transaction execution on top of a certain state.
+struct Speculation {
+    result: TransactionResult,
+    patch: Patch,
+    reads: ReadSet,
+}
+
+impl PreparedTransaction {
+    /// Executes the transaction on top of the given state.
+    fn speculate(&self, inner: Box<dyn Snapshot>) -> Speculation {
+        let reads = Rc::new(RefCell::new(ReadSet::default()));
+        let snapshot = RecordingSnapshot {
+            inner,
+            reads: Rc::clone(&reads),
+        };
+
+        let mut fork = Fork::from_snapshot(Box::new(snapshot));
+        let result = Blockchain::run_transaction_with_dependency(
+            self.hash,
+            self.tx.as_ref(),
+            &self.raw,
+            &self.service_name,
+            &mut fork,
+        );
+        let patch = fork.into_patch();
+        let reads = reads.replace(ReadSet::default());
+        Speculation {
+            result,
+            patch,
+            reads,
+        }
+    }
+}
+
+/// Thread pool executing the independent transactions of a block in parallel.
+pub(crate) struct ExecutionPool {
+    pool: ThreadPool,
+    threads: usize,
+}
+
+impl ExecutionPool {
+    /// Creates a pool with the given number of threads.
+    pub fn new(threads: usize) -> Self {
+        let pool = ThreadPoolBuilder::new()
+            .pool_size(threads)
+            .name_prefix("exonum-execution-")
+            .build();
+        Self { pool, threads }
+    }
+
+    /// Executes the transactions with the given hashes and records their results
+    /// into the fork in the same way as the serial execution does.
+    pub fn execute_transactions(
+        &self,
+        blockchain: &Blockchain,
+        height: Height,
+        tx_hashes: &[Hash],
+        fork: &mut Fork,
+    ) {
+        // Each transaction can be executed only once.
+        let unique_hashes = tx_hashes.iter().collect::<HashSet<_>>();
+        assert_eq!(
+            unique_hashes.len(),
+            tx_hashes.len(),
+            "Transaction execution error: duplicate transactions in the block."
+        );
+
+        // Changes made before the current batch. They are updated in place after each batch,
+        // when the worker threads no longer hold them.
+        let mut base = Arc::new(fork.patch().clone());
+        for (batch_index, batch) in tx_hashes.chunks(self.threads).enumerate() {
+            let offset = batch_index * self.threads;
+            let mut batch_fork =
+                Fork::from_snapshot(patched_snapshot(blockchain.db.as_ref(), &base));
+
+            let receivers = batch
+                .iter()
+                .map(|&hash| {
+                    let (tx, raw, service_name) = blockchain
+                        .prepare_transaction(hash, &batch_fork)
+                        // Execution could fail if the transaction
+                        // cannot be deserialized or it isn't in the pool.
+                        .expect("Transaction execution error.");
+                    let prepared = PreparedTransaction {
+                        hash,
+                        tx,
+                        raw,
+                        service_name: service_name.to_owned(),
+                    };
+                    self.spawn(prepared, Arc::clone(&blockchain.db), Arc::clone(&base))
+                }).collect::<Vec<_>>();
+
+            let mut writes = WriteSet::default();
+            for (position, receiver) in receivers.into_iter().enumerate() {
+                let (prepared, speculation) = receiver
+                    .wait()
+                    .expect("Transaction execution pool is shut down.");
+                let speculation = speculation
+                    .filter(|speculation| !writes.conflicts_with(&speculation.reads))
+                    // Execute the transaction again on top of the actual state. This also
+                    // propagates the panics caused by storage errors.
+                    .unwrap_or_else(|| {
+                        let snapshot = PatchedSnapshot::new(
+                            patched_snapshot(blockchain.db.as_ref(), &base),
+                            Arc::new(batch_fork.patch().clone()),
+                        );
+                        prepared.speculate(Box::new(snapshot))
+                    });
+
+                writes.add_patch(&speculation.patch);
+                batch_fork.merge(speculation.patch);
+                Blockchain::save_transaction_result(
+                    prepared.hash,
+                    speculation.result,
+                    height,
+                    offset + position,
+                    &mut batch_fork,
+                );
+                writes.bookkeeping = true;
+            }
+
+            let batch_patch = batch_fork.into_patch();
+            Arc::get_mut(&mut base)
+                .expect("Changes of the previous batches are still in use.")
+                .merge(batch_patch);
+        }
+
+        // The base changes include the ones already in the fork, which are applied again
+        // with the same values.
+        let patch = Arc::try_unwrap(base).expect("Changes of the block are still in use.");
+        fork.merge(patch);
+    }
+
+    /// Executes the transaction on the pool. Panics during the execution are caught
+    /// and result in an empty speculation.
+    fn spawn(
+        &self,
+        prepared: PreparedTransaction,
+        db: Arc<dyn Database>,
+        base: Arc<Patch>,
+    ) -> oneshot::Receiver<(PreparedTransaction, Option<Speculation>)> {
+        let (sender, receiver) = oneshot::channel();
+        self.pool.spawn(future::lazy(move || -> Result<(), ()> {
+            let speculation = panic::catch_unwind(AssertUnwindSafe(|| {
+                prepared.speculate(patched_snapshot(db.as_ref(), &base))
+            })).ok();
+            // The base changes are released before the result is reported, so that they
+            // can be updated once the batch is applied.
+            drop(base);
+            // The receiver is dropped only if the block execution is aborted.
+            let _ = sender.send((prepared, speculation));
+            Ok(())
+        }));
+        receiver
+    }
+}
+
+/// Returns the current state of the database with the `base` changes applied.
+fn patched_snapshot(db: &dyn Database, base: &Arc<Patch>) -> Box<dyn Snapshot> {
+    Box::new(PatchedSnapshot::new(db.snapshot(), Arc::clone(base)))
+}
+
+impl fmt::Debug for ExecutionPool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ExecutionPool")
+            .field("threads", &self.threads)
+            .finish()
+    }
+}
@@ -12,13 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::{config::StoredConfiguration, Block, BlockProof, Blockchain, TransactionResult};
+use failure;
+
+use super::{
+    config::StoredConfiguration, Block, BlockProof, Blockchain, EquivocationEvidence,
+    TransactionResult, CORE_SERVICE,
+};
 use crypto::{CryptoHash, Hash, PublicKey};
 use helpers::{Height, Round};
 use messages::{Connect, Message, Precommit, RawTransaction, Signed};
 use storage::{
-    Entry, Fork, KeySetIndex, ListIndex, MapIndex, MapProof, ProofListIndex, ProofMapIndex,
-    Snapshot,
+    Change, Entry, Fork, KeySetIndex, ListIndex, ListProof, MapIndex, MapProof, ProofListIndex,
+    ProofMapIndex, Snapshot, StorageKey,
 };
 
 /// Defines `&str` constants with given name and value.
@@ -43,13 +48,32 @@ define_names!(
     BLOCK_TRANSACTIONS => "block_transactions";
     PRECOMMITS => "precommits";
     CONFIGS => "configs";
-    CONFIGS_ACTUAL_FROM => "configs_actual_from";
+    CONFIGS_ACTUAL_FROM => "configs_history";
+    LEGACY_CONFIGS_ACTUAL_FROM => "configs_actual_from";
     STATE_HASH_AGGREGATOR => "state_hash_aggregator";
     PEERS_CACHE => "peers_cache";
     CONSENSUS_MESSAGES_CACHE => "consensus_messages_cache";
     CONSENSUS_ROUND => "consensus_round";
+    CONSENSUS_ARCHIVE => "consensus_archive";
+    CONSENSUS_ARCHIVE_HEIGHTS => "consensus_archive_heights";
+    COLD_STORAGE_HEIGHT => "cold_storage_height";
+    PRUNED_HEIGHT => "pruned_height";
+    SERVICE_IDS => "service_ids";
+    STORAGE_STATS => "storage_stats";
+    BYZANTINE_VALIDATORS => "byzantine_validators";
 );
 
+/// Index of the configuration history among the core tables aggregated into the state hash.
+pub(crate) const CONFIGS_HISTORY_TABLE: usize = 2;
+
+/// Names of the indexes updated by the core after the execution of each transaction.
+pub(crate) const TRANSACTION_BOOKKEEPING_INDEXES: [&str; 4] = [
+    TRANSACTION_RESULTS,
+    TRANSACTIONS_POOL,
+    BLOCK_TRANSACTIONS,
+    TRANSACTIONS_LOCATIONS,
+];
+
 encoding_struct! {
     /// Configuration index.
     struct ConfigReference {
@@ -72,6 +96,18 @@ encoding_struct! {
     }
 }
 
+encoding_struct! {
+    /// Statistics of a storage index maintained incrementally on each merge.
+    struct IndexStats {
+        /// Number of the entries in the index.
+        entries: u64,
+        /// Approximate size of the keys and values of the index in bytes.
+        bytes: u64,
+        /// Height of the latest block at which the index has been modified.
+        modified_at: Height,
+    }
+}
+
 /// Information schema for indices maintained by the Exonum core logic.
 ///
 /// Indices defined by this schema are present in the blockchain regardless of
@@ -93,6 +129,13 @@ where
 
     /// Returns a table that represents a map with a key-value pair of a
     /// transaction hash and raw transaction message.
+    ///
+    /// The transaction hash covers the whole signed message including the service
+    /// identifier, so transactions of different services never share the same hash.
+    ///
+    /// This is the only table storing the transaction bodies. The pool, the block
+    /// and the location tables refer to transactions by their hashes, so each body
+    /// is stored once regardless of the transaction status.
     pub fn transactions(&self) -> MapIndex<&T, Hash, Signed<RawTransaction>> {
         MapIndex::new(TRANSACTIONS, &self.view)
     }
@@ -128,6 +171,13 @@ where
         MapIndex::new(TRANSACTIONS_LOCATIONS, &self.view)
     }
 
+    /// Returns `true` if the transaction is known to the blockchain, i.e., it is either
+    /// stored in the pool or has been committed. Committed transactions are recognized by
+    /// their locations, since the bodies of the transactions from pruned blocks are removed.
+    pub fn is_known_transaction(&self, tx_hash: &Hash) -> bool {
+        self.transactions().contains(tx_hash) || self.transactions_locations().contains(tx_hash)
+    }
+
     /// Returns a table that stores a block object for every block height.
     pub fn blocks(&self) -> MapIndex<&T, Hash, Block> {
         MapIndex::new(BLOCKS, &self.view)
@@ -156,10 +206,13 @@ where
         ProofMapIndex::new(CONFIGS, &self.view)
     }
 
-    /// Returns an auxiliary table that keeps hash references to configurations in
+    /// Returns a table that keeps hash references to configurations in
     /// the increasing order of their `actual_from` height.
-    pub fn configs_actual_from(&self) -> ListIndex<&T, ConfigReference> {
-        ListIndex::new(CONFIGS_ACTUAL_FROM, &self.view)
+    ///
+    /// The table is Merkelized and its root hash is a part of the core state hash,
+    /// so the history of configurations can be proven to light clients.
+    pub fn configs_actual_from(&self) -> ProofListIndex<&T, ConfigReference> {
+        ProofListIndex::new(CONFIGS_ACTUAL_FROM, &self.view)
     }
 
     /// Returns the accessory `ProofMapIndex` for calculating
@@ -187,12 +240,102 @@ where
         MapIndex::new(PEERS_CACHE, &self.view)
     }
 
+    /// Returns the raw `Connect` messages from the peers cache. Unlike `peers_cache`,
+    /// this index can be read even if some of the messages cannot be decoded.
+    pub(crate) fn peers_cache_raw(&self) -> MapIndex<&T, PublicKey, Vec<u8>> {
+        MapIndex::new(PEERS_CACHE, &self.view)
+    }
+
     /// Returns consensus messages that have to be recovered in case of process restart
     /// after abnormal termination.
     pub(crate) fn consensus_messages_cache(&self) -> ListIndex<&T, Message> {
         ListIndex::new(CONSENSUS_MESSAGES_CACHE, &self.view)
     }
 
+    /// Returns consensus messages received by the node at the given height.
+    /// The messages are archived only if the archive is enabled in the node configuration.
+    pub(crate) fn consensus_archive(&self, height: Height) -> ListIndex<&T, Message> {
+        ListIndex::new_in_family(CONSENSUS_ARCHIVE, &height.0, &self.view)
+    }
+
+    /// Returns heights for which the consensus messages are archived.
+    pub(crate) fn consensus_archive_heights(&self) -> KeySetIndex<&T, u64> {
+        KeySetIndex::new(CONSENSUS_ARCHIVE_HEIGHTS, &self.view)
+    }
+
+    /// Returns the height below which the block data is moved to the cold storage.
+    pub(crate) fn cold_storage_height(&self) -> Height {
+        let entry: Entry<&T, u64> = Entry::new(COLD_STORAGE_HEIGHT, &self.view);
+        Height(entry.get().unwrap_or(0))
+    }
+
+    /// Returns the height below which the transaction bodies and the precommits
+    /// of the blocks are pruned.
+    pub(crate) fn pruned_height(&self) -> Height {
+        let entry: Entry<&T, u64> = Entry::new(PRUNED_HEIGHT, &self.view);
+        Height(entry.get().unwrap_or(0))
+    }
+
+    /// Returns the entries holding the data of the block at the given height: the block
+    /// itself, its precommits, the list of its transactions and the transaction bodies.
+    /// Each entry is specified by the index name and the key prefix.
+    pub(crate) fn block_entries(&self, height: Height) -> Vec<(String, Vec<u8>)> {
+        fn key_bytes<K: StorageKey + ?Sized>(key: &K) -> Vec<u8> {
+            let mut buffer = vec![0; key.size()];
+            key.write(&mut buffer);
+            buffer
+        }
+
+        let block_hash = match self.block_hash_by_height(height) {
+            Some(hash) => hash,
+            None => return Vec::new(),
+        };
+        let mut entries = vec![
+            (BLOCKS.to_owned(), key_bytes(&block_hash)),
+            (PRECOMMITS.to_owned(), key_bytes(&block_hash)),
+            (BLOCK_TRANSACTIONS.to_owned(), key_bytes(&height.0)),
+        ];
+        entries.extend(
+            self.block_transactions(height)
+                .iter()
+                .map(|tx_hash| (TRANSACTIONS.to_owned(), key_bytes(&tx_hash))),
+        );
+        entries
+    }
+
+    /// Returns identifiers of the services indexed by the service names.
+    /// The identifiers are recorded when the genesis block is created.
+    pub fn service_ids(&self) -> MapIndex<&T, String, u16> {
+        MapIndex::new(SERVICE_IDS, &self.view)
+    }
+
+    /// Returns the statistics of the storage indexes indexed by the index names.
+    ///
+    /// The statistics are updated on each merge into the blockchain storage, so the data
+    /// written before the statistics were introduced is not taken into account. The
+    /// indexes of a family are accounted together under the family name.
+    pub fn storage_stats(&self) -> MapIndex<&T, String, IndexStats> {
+        MapIndex::new(STORAGE_STATS, &self.view)
+    }
+
+    /// Returns the consensus keys of the validators with the committed evidence of
+    /// equivocation, mapped to the heights of the blocks the evidence is committed in.
+    pub fn byzantine_validators(&self) -> MapIndex<&T, PublicKey, Height> {
+        MapIndex::new(BYZANTINE_VALIDATORS, &self.view)
+    }
+
+    /// Returns the consensus keys of the validators with the evidence of equivocation
+    /// committed since the actual configuration has taken effect. A configuration change
+    /// reinstates the validators with the evidence committed before it.
+    pub fn actual_byzantine_validators(&self) -> Vec<PublicKey> {
+        let actual_from = self.actual_configuration().actual_from;
+        self.byzantine_validators()
+            .iter()
+            .filter(|&(_, height)| height >= actual_from)
+            .map(|(key, _)| key)
+            .collect()
+    }
+
     /// Returns the saved value of the consensus round. Returns the first round
     /// if it has not been saved.
     pub(crate) fn consensus_round(&self) -> Round {
@@ -312,11 +455,50 @@ where
     }
 
     /// Returns the `state_hash` table for core tables.
+    ///
+    /// The root hash of the [`configs_actual_from`][1] index is included only after
+    /// the height set in the `configs_history_from` field of the consensus configuration.
+    ///
+    /// [1]: struct.Schema.html#method.configs_actual_from
     pub fn core_state_hash(&self) -> Vec<Hash> {
-        vec![
+        let mut hashes = vec![
             self.configs().merkle_root(),
             self.transaction_results().merkle_root(),
-        ]
+        ];
+        if self.is_configs_history_aggregated() {
+            hashes.push(self.configs_actual_from().merkle_root());
+        }
+        hashes
+    }
+
+    /// Returns `true` if the root hash of the [`configs_actual_from`][1] index is
+    /// aggregated into the state hash.
+    ///
+    /// [1]: struct.Schema.html#method.configs_actual_from
+    pub fn is_configs_history_aggregated(&self) -> bool {
+        let key = Blockchain::service_table_unique_key(CORE_SERVICE, CONFIGS_HISTORY_TABLE);
+        self.state_hash_aggregator().contains(&key)
+    }
+
+    /// Returns the reference to the configuration actual at the given height
+    /// together with the proof of its inclusion into the [`configs_actual_from`][1] index.
+    ///
+    /// Once the aggregation is activated with the `configs_history_from` field of
+    /// the consensus configuration, the root hash of the index is a part of the core
+    /// state hash and can be proven with `get_proof_to_service_table(CORE_SERVICE, 2)`.
+    ///
+    /// [1]: struct.Schema.html#method.configs_actual_from
+    pub fn configuration_reference_by_height(
+        &self,
+        height: Height,
+    ) -> Option<(ConfigReference, ListProof<ConfigReference>)> {
+        let actual_from = self.configs_actual_from();
+        let (idx, reference) = actual_from
+            .iter()
+            .enumerate()
+            .take_while(|(_, reference)| reference.actual_from() <= height)
+            .last()?;
+        Some((reference, actual_from.get_proof(idx as u64)))
     }
 
     /// Constructs a proof of inclusion of a root hash of a specific service
@@ -456,8 +638,49 @@ impl<'a> Schema<&'a mut Fork> {
     /// Mutable reference to the [`configs_actual_from`][1] index.
     ///
     /// [1]: struct.Schema.html#method.configs_actual_from
-    pub(crate) fn configs_actual_from_mut(&mut self) -> ListIndex<&mut Fork, ConfigReference> {
-        ListIndex::new(CONFIGS_ACTUAL_FROM, self.view)
+    pub(crate) fn configs_actual_from_mut(&mut self) -> ProofListIndex<&mut Fork, ConfigReference> {
+        ProofListIndex::new(CONFIGS_ACTUAL_FROM, self.view)
+    }
+
+    /// Starts aggregating the root hash of the [`configs_actual_from`][1] index into the state
+    /// hash if the configuration actual at the given height activates it.
+    ///
+    /// [1]: struct.Schema.html#method.configs_actual_from
+    pub(crate) fn activate_configs_history(&mut self, height: Height) {
+        if self.is_configs_history_aggregated() {
+            return;
+        }
+        let activation = self
+            .configuration_by_height(height)
+            .consensus
+            .configs_history_from;
+        if activation.map_or(false, |from| from <= height) {
+            info!("Configuration history is aggregated into the state hash from now on.");
+            let root = self.configs_actual_from().merkle_root();
+            let key = Blockchain::service_table_unique_key(CORE_SERVICE, CONFIGS_HISTORY_TABLE);
+            self.state_hash_aggregator_mut().put(&key, root);
+        }
+    }
+
+    /// Moves the configuration references from the non-Merkelized index used by the earlier
+    /// versions to the [`configs_actual_from`][1] index. Returns `true` if there was anything
+    /// to migrate.
+    ///
+    /// [1]: struct.Schema.html#method.configs_actual_from
+    pub(crate) fn migrate_configs_actual_from(&mut self) -> bool {
+        let references = {
+            let legacy: ListIndex<_, ConfigReference> =
+                ListIndex::new(LEGACY_CONFIGS_ACTUAL_FROM, &self.view);
+            if legacy.is_empty() || !self.configs_actual_from().is_empty() {
+                return false;
+            }
+            legacy.iter().collect::<Vec<_>>()
+        };
+        self.configs_actual_from_mut().extend(references);
+        let mut legacy: ListIndex<_, ConfigReference> =
+            ListIndex::new(LEGACY_CONFIGS_ACTUAL_FROM, self.view);
+        legacy.clear();
+        true
     }
 
     /// Mutable reference to the [`state_hash_aggregator`][1] index.
@@ -481,12 +704,135 @@ impl<'a> Schema<&'a mut Fork> {
         ListIndex::new(CONSENSUS_MESSAGES_CACHE, self.view)
     }
 
+    /// Mutable reference to the [`consensus_archive`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.consensus_archive
+    pub(crate) fn consensus_archive_mut(
+        &mut self,
+        height: Height,
+    ) -> ListIndex<&mut Fork, Message> {
+        ListIndex::new_in_family(CONSENSUS_ARCHIVE, &height.0, self.view)
+    }
+
+    /// Mutable reference to the [`consensus_archive_heights`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.consensus_archive_heights
+    pub(crate) fn consensus_archive_heights_mut(&mut self) -> KeySetIndex<&mut Fork, u64> {
+        KeySetIndex::new(CONSENSUS_ARCHIVE_HEIGHTS, self.view)
+    }
+
+    /// Saves the consensus messages received at the given height to the archive
+    /// and removes the archived messages for the heights lower than `start`.
+    pub(crate) fn archive_consensus_messages<I>(
+        &mut self,
+        height: Height,
+        messages: I,
+        start: Height,
+    ) where
+        I: IntoIterator<Item = Message>,
+    {
+        let pruned: Vec<_> = self
+            .consensus_archive_heights()
+            .iter()
+            .take_while(|archived| *archived < start.0)
+            .collect();
+        for archived in pruned {
+            self.consensus_archive_mut(Height(archived)).clear();
+            self.consensus_archive_heights_mut().remove(&archived);
+        }
+        if height >= start {
+            self.consensus_archive_heights_mut().insert(height.0);
+            self.consensus_archive_mut(height).extend(messages);
+        }
+    }
+
+    /// Mutable reference to the [`service_ids`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.service_ids
+    pub(crate) fn service_ids_mut(&mut self) -> MapIndex<&mut Fork, String, u16> {
+        MapIndex::new(SERVICE_IDS, self.view)
+    }
+
+    /// Mutable reference to the [`byzantine_validators`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.byzantine_validators
+    pub(crate) fn byzantine_validators_mut(&mut self) -> MapIndex<&mut Fork, PublicKey, Height> {
+        MapIndex::new(BYZANTINE_VALIDATORS, self.view)
+    }
+
+    /// Mutable reference to the [`storage_stats`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.storage_stats
+    pub(crate) fn storage_stats_mut(&mut self) -> MapIndex<&mut Fork, String, IndexStats> {
+        MapIndex::new(STORAGE_STATS, self.view)
+    }
+
+    /// Updates the [`storage_stats`][1] of the indexes changed in the fork, comparing
+    /// the changes with the `snapshot` the fork is based on. The changes of the statistics
+    /// index itself are not accounted.
+    ///
+    /// [1]: struct.Schema.html#method.storage_stats
+    pub(crate) fn update_storage_stats(&mut self, snapshot: &dyn Snapshot, height: Height) {
+        let deltas: Vec<(String, i64, i64)> = self
+            .view
+            .patch()
+            .iter()
+            .filter(|&(name, _)| name != STORAGE_STATS)
+            .map(|(name, changes)| {
+                let (mut entries, mut bytes) = (0_i64, 0_i64);
+                for (key, change) in changes.iter() {
+                    if let Some(old_value) = snapshot.get(name, key) {
+                        entries -= 1;
+                        bytes -= (key.len() + old_value.len()) as i64;
+                    }
+                    if let Change::Put(ref value) = *change {
+                        entries += 1;
+                        bytes += (key.len() + value.len()) as i64;
+                    }
+                }
+                (name.clone(), entries, bytes)
+            }).collect();
+
+        let apply = |value: u64, delta: i64| {
+            if delta < 0 {
+                value.saturating_sub(delta.wrapping_neg() as u64)
+            } else {
+                value.saturating_add(delta as u64)
+            }
+        };
+        for (name, entries_delta, bytes_delta) in deltas {
+            let (entries, bytes) = self
+                .storage_stats()
+                .get(&name)
+                .map_or((0, 0), |stats| (stats.entries(), stats.bytes()));
+            let stats = IndexStats::new(
+                apply(entries, entries_delta),
+                apply(bytes, bytes_delta),
+                height,
+            );
+            self.storage_stats_mut().put(&name, stats);
+        }
+    }
+
     /// Saves the given consensus round value into the storage.
     pub(crate) fn set_consensus_round(&mut self, round: Round) {
         let mut entry: Entry<&mut Fork, _> = Entry::new(CONSENSUS_ROUND, self.view);
         entry.set(round);
     }
 
+    /// Saves the height below which the block data is moved to the cold storage.
+    pub(crate) fn set_cold_storage_height(&mut self, height: Height) {
+        let mut entry: Entry<&mut Fork, _> = Entry::new(COLD_STORAGE_HEIGHT, self.view);
+        entry.set(height.0);
+    }
+
+    /// Saves the height below which the transaction bodies and the precommits
+    /// of the blocks are pruned.
+    pub(crate) fn set_pruned_height(&mut self, height: Height) {
+        let mut entry: Entry<&mut Fork, _> = Entry::new(PRUNED_HEIGHT, self.view);
+        entry.set(height.0);
+    }
+
     /// Adds a new configuration to the blockchain, which will become actual at
     /// the `actual_from` height in `config_data`.
     pub fn commit_configuration(&mut self, config_data: StoredConfiguration) {
@@ -524,11 +870,38 @@ impl<'a> Schema<&'a mut Fork> {
         self.configs_actual_from_mut().push(cfg_ref);
     }
 
+    /// Commits the evidence of equivocation of a validator after checking it against
+    /// the configuration actual at the height of the conflicting messages.
+    ///
+    /// The evidence is usually committed by a service transaction; the validator is then
+    /// considered Byzantine until the next configuration change.
+    pub fn commit_equivocation_evidence(
+        &mut self,
+        evidence: &EquivocationEvidence,
+    ) -> Result<(), failure::Error> {
+        let config = self.configuration_by_height(evidence.height());
+        let key = evidence.verify(&config)?;
+        let height = self.next_height();
+        warn!(
+            "Committed evidence of equivocation of validator {:?} with key {:?}",
+            evidence.validator(),
+            key
+        );
+        self.byzantine_validators_mut().put(&key, height);
+        Ok(())
+    }
+
     /// Adds transaction into the persistent pool.
     /// This method increment `transactions_pool_len_index`,
     /// be sure to decrement it when transaction committed.
+    ///
+    /// Transaction hashes are global for all services, so the transaction is ignored
+    /// if a transaction with the same hash is already in the pool or committed.
     #[doc(hidden)]
     pub fn add_transaction_into_pool(&mut self, tx: Signed<RawTransaction>) {
+        if self.transactions().contains(&tx.hash()) {
+            return;
+        }
         self.transactions_pool_mut().insert(tx.hash());
         let x = self.transactions_pool_len_index().get().unwrap_or(0);
         self.transactions_pool_len_index_mut().set(x + 1);
@@ -540,19 +913,18 @@ impl<'a> Schema<&'a mut Fork> {
         self.transactions_pool_mut().remove(hash);
     }
 
-    /// Removes transaction from the persistent pool.
-    #[cfg(test)]
-    pub(crate) fn reject_transaction(&mut self, hash: &Hash) -> Result<(), ()> {
-        let contains = self.transactions_pool_mut().contains(hash);
+    /// Removes transaction from the persistent pool. Returns an error if the transaction
+    /// is not in the pool.
+    #[doc(hidden)]
+    pub fn reject_transaction(&mut self, hash: &Hash) -> Result<(), ()> {
+        if !self.transactions_pool().contains(hash) {
+            return Err(());
+        }
         self.transactions_pool_mut().remove(hash);
         self.transactions_mut().remove(hash);
 
-        if contains {
-            let x = self.transactions_pool_len_index().get().unwrap();
-            self.transactions_pool_len_index_mut().set(x - 1);
-            Ok(())
-        } else {
-            Err(())
-        }
+        let x = self.transactions_pool_len_index().get().unwrap();
+        self.transactions_pool_len_index_mut().set(x - 1);
+        Ok(())
     }
 }
@@ -16,24 +16,33 @@
 //! blockchain platforms, Exonum services encapsulate business logic of the blockchain application.
 
 use actix::Addr;
+use chrono::{DateTime, Utc};
 use serde_json::Value;
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     fmt,
     net::SocketAddr,
     sync::{Arc, RwLock},
 };
 
-use super::transaction::Transaction;
+use super::{registry::TransactionInfo, transaction::Transaction};
+#[cfg(feature = "graphql")]
+use api::node::public::graphql::GraphQLProvider;
 use api::{websocket, ServiceApiBuilder};
 use blockchain::{ConsensusConfig, Schema, StoredConfiguration, ValidatorKeys};
 use crypto::{Hash, PublicKey, SecretKey};
 use encoding::Error as MessageError;
 use events::network::ConnectedPeerAddr;
+use explorer::PrefetchProgress;
 use helpers::{Height, Milliseconds, ValidatorId};
 use messages::{Message, RawTransaction, ServiceTransaction, Signed};
-use node::{ApiSender, ConnectInfo, NodeRole, State};
+#[cfg(debug_assertions)]
+use node::chaos::ChaosConfig;
+use node::{
+    ApiSender, ConnectInfo, DivergenceReport, NodeRole, PeerClockSkew, PeerLatency, PeerStatus,
+    ReplicaStatus, State, TableRoots,
+};
 use storage::{Fork, Snapshot};
 
 /// A trait that describes the business logic of a certain service.
@@ -171,6 +180,17 @@ pub trait Service: Send + Sync + 'static {
     /// [the `Service` example above](#examples).
     fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<dyn Transaction>, MessageError>;
 
+    /// Returns the transaction types of the service, which are listed in the
+    /// [service registry](struct.ServiceRegistry.html).
+    ///
+    /// Services using the `transactions!` macro can implement the method as
+    /// `TransactionInfo::from_set::<MyTransactions>()`.
+    ///
+    /// *Default implementation returns an empty list.*
+    fn transaction_types(&self) -> Vec<TransactionInfo> {
+        Vec::new()
+    }
+
     /// Invoked for all deployed services during the blockchain initialization
     /// on genesis block creation each time a node is started.
     /// During the handling of the method the service is able to perform the following activities:
@@ -202,12 +222,30 @@ pub trait Service: Send + Sync + 'static {
     /// *Try not to perform long operations in this handler*.
     fn after_commit(&self, context: &ServiceContext) {}
 
+    /// Reads the frequently accessed entries of the service state, for example,
+    /// the ones touched by transactions in the blocks starting from the `from` height,
+    /// so that they get into the database caches. This handler is invoked for each
+    /// service during the optional prefetch phase on the node start, before the API
+    /// starts accepting requests. Services may also fill their own caches here.
+    ///
+    /// *Default implementation does nothing*
+    fn prefetch(&self, _snapshot: &dyn Snapshot, _from: Height) {}
+
     /// Extends API by handlers of this service. The request handlers are mounted on
     /// the `/api/services/{service_name}` path at the listen address of every
     /// full node in the blockchain network.
     ///
     /// *Default implementation does nothing*
     fn wire_api(&self, _builder: &mut ServiceApiBuilder) {}
+
+    /// Returns the provider of the service data, such as wallets and events,
+    /// exposed via the GraphQL API.
+    ///
+    /// *Default implementation returns `None`*
+    #[cfg(feature = "graphql")]
+    fn graphql_provider(&self) -> Option<&dyn GraphQLProvider> {
+        None
+    }
 }
 
 /// The current node state on which the blockchain is running, or in other words
@@ -320,6 +358,27 @@ impl ServiceContext {
         }
     }
 
+    /// Signs and broadcasts a transaction emitted by the service on behalf of the validators,
+    /// for example, an update of a time oracle.
+    ///
+    /// To avoid duplicates, the validators take turns emitting transactions: a transaction
+    /// is broadcast only by the validator whose identifier equals the current height modulo
+    /// the number of validators, on the other nodes this method does nothing. If the emitted
+    /// transaction is lost, the service should emit it again after the next block, so emitted
+    /// transactions are expected to be idempotent.
+    ///
+    /// Committed emitted transactions are attributed to the emitting validator by the explorer.
+    pub fn emit_transaction<T>(&self, tx: T)
+    where
+        T: Into<ServiceTransaction> + Transaction,
+    {
+        let validators_count = self.validators().len() as u64;
+        let emitter = ValidatorId((self.height.0 % validators_count) as u16);
+        if self.validator_id == Some(emitter) {
+            self.broadcast_transaction(tx);
+        }
+    }
+
     /// Broadcast transaction to other nodes in the network.
     /// This transaction should be signed externally.
     pub fn broadcast_signed_transaction(&self, msg: Signed<RawTransaction>) {
@@ -344,7 +403,18 @@ pub struct ApiNodeState {
     node_role: NodeRole,
     majority_count: usize,
     validators: Vec<ValidatorKeys>,
+    latencies: HashMap<PublicKey, PeerLatency>,
+    clock_skews: HashMap<PublicKey, PeerClockSkew>,
+    peer_statuses: HashMap<PublicKey, PeerStatus>,
     broadcast_server_address: Option<Addr<websocket::Server>>,
+    #[cfg(debug_assertions)]
+    chaos: ChaosConfig,
+    prefetch: Option<PrefetchProgress>,
+    replica: Option<ReplicaStatus>,
+    table_roots: VecDeque<TableRoots>,
+    divergence: Option<DivergenceReport>,
+    read_only_on_quorum_loss: bool,
+    quorum_lost_since: Option<DateTime<Utc>>,
 }
 
 impl fmt::Debug for ApiNodeState {
@@ -357,6 +427,15 @@ impl fmt::Debug for ApiNodeState {
             .field("node_role", &self.node_role)
             .field("majority_count", &self.majority_count)
             .field("validators", &self.validators)
+            .field("latencies", &self.latencies)
+            .field("clock_skews", &self.clock_skews)
+            .field("peer_statuses", &self.peer_statuses)
+            .field("prefetch", &self.prefetch)
+            .field("replica", &self.replica)
+            .field("table_roots", &self.table_roots)
+            .field("divergence", &self.divergence)
+            .field("read_only_on_quorum_loss", &self.read_only_on_quorum_loss)
+            .field("quorum_lost_since", &self.quorum_lost_since)
             .finish()
     }
 }
@@ -368,6 +447,28 @@ impl ApiNodeState {
             ..Default::default()
         }
     }
+
+    fn has_quorum(&self) -> bool {
+        let mut active_validators = self
+            .incoming_connections
+            .iter()
+            .chain(self.outgoing_connections.iter())
+            .filter(|ci| {
+                self.validators
+                    .iter()
+                    .any(|v| v.consensus_key == ci.public_key)
+            }).count();
+
+        if self.node_role.is_validator() {
+            // Peers list doesn't include current node address, so we have to increment its length.
+            // E.g. if we have 3 items in peers list, it means that we have 4 nodes overall.
+            active_validators += 1;
+        }
+
+        // Just after Node is started (node status isn't updated) majority_count = 0,
+        // so we have to check that majority count is greater than 0.
+        active_validators >= self.majority_count && self.majority_count > 0
+    }
 }
 
 /// Shared part of the context, used to take some values from the `Node`
@@ -431,6 +532,10 @@ impl SharedNodeState {
         lock.majority_count = state.majority_count();
         lock.node_role = NodeRole::new(state.validator_id());
         lock.validators = state.validators().to_vec();
+        lock.latencies
+            .retain(|key, _| state.connections().contains_key(key));
+        lock.clock_skews
+            .retain(|key, _| state.connections().contains_key(key));
 
         for (p, a) in state.connections() {
             match a {
@@ -455,25 +560,47 @@ impl SharedNodeState {
     /// Returns a boolean value which indicates whether the consensus is achieved.
     pub fn consensus_status(&self) -> bool {
         let lock = self.state.read().expect("Expected read lock.");
-        let mut active_validators = lock
-            .incoming_connections
-            .iter()
-            .chain(lock.outgoing_connections.iter())
-            .filter(|ci| {
-                lock.validators
-                    .iter()
-                    .any(|v| v.consensus_key == ci.public_key)
-            }).count();
+        lock.has_quorum()
+    }
 
-        if lock.node_role.is_validator() {
-            // Peers list doesn't include current node address, so we have to increment its length.
-            // E.g. if we have 3 items in peers list, it means that we have 4 nodes overall.
-            active_validators += 1;
-        }
+    /// Returns the time since which the node is connected to less validators than
+    /// required to achieve the consensus, or `None` if the node has the quorum.
+    pub fn quorum_lost_since(&self) -> Option<DateTime<Utc>> {
+        let lock = self.state.read().expect("Expected read lock.");
+        lock.quorum_lost_since
+    }
 
-        // Just after Node is started (node status isn't updated) majority_count = 0,
-        // so we have to check that majority count is greater than 0.
-        active_validators >= lock.majority_count && lock.majority_count > 0
+    /// Returns `true` if the public API should reject new transactions, i.e., the quorum
+    /// of validators is lost and the `read_only_on_quorum_loss` API option is set.
+    pub fn is_read_only(&self) -> bool {
+        let lock = self.state.read().expect("Expected read lock.");
+        lock.read_only_on_quorum_loss && lock.quorum_lost_since.is_some()
+    }
+
+    pub(crate) fn set_read_only_on_quorum_loss(&self, read_only: bool) {
+        let mut lock = self.state.write().expect("Expected write lock.");
+        lock.read_only_on_quorum_loss = read_only;
+    }
+
+    /// Records the loss or the restoration of the quorum of validators at the time `now`.
+    /// Should be called after `update_node_state`.
+    pub(crate) fn update_quorum_status(&self, now: DateTime<Utc>) {
+        let mut lock = self.state.write().expect("Expected write lock.");
+        let has_quorum = lock.has_quorum();
+        match (has_quorum, lock.quorum_lost_since) {
+            (false, None) => {
+                warn!(
+                    "Node is connected to less than {} validators; consensus cannot progress",
+                    lock.majority_count
+                );
+                lock.quorum_lost_since = Some(now);
+            }
+            (true, Some(since)) => {
+                info!("Quorum of validators lost since {} is restored", since);
+                lock.quorum_lost_since = None;
+            }
+            _ => {}
+        }
     }
 
     /// Returns a boolean value which indicates whether the node is enabled
@@ -495,6 +622,76 @@ impl SharedNodeState {
         state.node_role = role;
     }
 
+    /// Returns the progress of the prefetch phase, or `None` if the prefetch is disabled.
+    pub fn prefetch_progress(&self) -> Option<PrefetchProgress> {
+        let state = self.state.read().expect("Expected read lock.");
+        state.prefetch
+    }
+
+    pub(crate) fn set_prefetch_progress(&self, progress: PrefetchProgress) {
+        let mut state = self.state.write().expect("Expected write lock.");
+        state.prefetch = Some(progress);
+    }
+
+    /// Returns the synchronization status of the replica, or `None` if the node
+    /// does not run in the replica mode.
+    pub fn replica_status(&self) -> Option<ReplicaStatus> {
+        let state = self.state.read().expect("Expected read lock.");
+        state.replica
+    }
+
+    pub(crate) fn set_replica_status(&self, status: ReplicaStatus) {
+        let mut state = self.state.write().expect("Expected write lock.");
+        state.replica = Some(status);
+    }
+
+    /// Returns the table root hashes at the given height, or at the latest height
+    /// if `height` is `None`. Root hashes are kept only in the forensic mode
+    /// for a limited number of the latest heights.
+    pub fn table_roots(&self, height: Option<Height>) -> Option<TableRoots> {
+        let state = self.state.read().expect("Expected read lock.");
+        match height {
+            Some(height) => state
+                .table_roots
+                .iter()
+                .find(|roots| roots.height == height)
+                .cloned(),
+            None => state.table_roots.back().cloned(),
+        }
+    }
+
+    pub(crate) fn add_table_roots(&self, roots: TableRoots, depth: usize) {
+        let mut state = self.state.write().expect("Expected write lock.");
+        state.table_roots.push_back(roots);
+        while state.table_roots.len() > depth {
+            state.table_roots.pop_front();
+        }
+    }
+
+    /// Returns the report on the state divergence captured in the forensic mode, if any.
+    pub fn divergence_report(&self) -> Option<DivergenceReport> {
+        let state = self.state.read().expect("Expected read lock.");
+        state.divergence.clone()
+    }
+
+    pub(crate) fn set_divergence_report(&self, report: DivergenceReport) {
+        let mut state = self.state.write().expect("Expected write lock.");
+        state.divergence = Some(report);
+    }
+
+    /// Returns the faults injected by the chaos testing hooks.
+    #[cfg(debug_assertions)]
+    pub fn chaos(&self) -> ChaosConfig {
+        let state = self.state.read().expect("Expected read lock.");
+        state.chaos.clone()
+    }
+
+    #[cfg(debug_assertions)]
+    pub(crate) fn set_chaos(&self, config: ChaosConfig) {
+        let mut state = self.state.write().expect("Expected write lock.");
+        state.chaos = config;
+    }
+
     /// Returns the value of the `state_update_timeout`.
     pub fn state_update_timeout(&self) -> Milliseconds {
         self.state_update_timeout
@@ -522,6 +719,112 @@ impl SharedNodeState {
             .remove(addr)
     }
 
+    /// Returns round-trip times to the connected peers. The list is empty unless
+    /// the latency measurement is enabled in the node configuration.
+    pub fn peer_latencies(&self) -> Vec<PeerLatency> {
+        self.state
+            .read()
+            .expect("Expected read lock.")
+            .latencies
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Records the round-trip time in microseconds to the peer with the given key.
+    pub(crate) fn add_peer_latency(
+        &self,
+        public_key: PublicKey,
+        validator_id: Option<ValidatorId>,
+        rtt: u64,
+    ) {
+        self.state
+            .write()
+            .expect("Expected write lock.")
+            .latencies
+            .entry(public_key)
+            .and_modify(|latency| latency.update(rtt))
+            .or_insert_with(|| PeerLatency::new(public_key, validator_id, rtt));
+    }
+
+    /// Returns the round-trip time to the peer with the given key, if it was measured.
+    pub fn peer_latency(&self, public_key: &PublicKey) -> Option<PeerLatency> {
+        self.state
+            .read()
+            .expect("Expected read lock.")
+            .latencies
+            .get(public_key)
+            .cloned()
+    }
+
+    /// Returns the estimated clock skews of the connected validators.
+    pub fn peer_clock_skews(&self) -> Vec<PeerClockSkew> {
+        self.state
+            .read()
+            .expect("Expected read lock.")
+            .clock_skews
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the estimated clock skew of the peer with the given key.
+    pub fn peer_clock_skew(&self, public_key: &PublicKey) -> Option<PeerClockSkew> {
+        self.state
+            .read()
+            .expect("Expected read lock.")
+            .clock_skews
+            .get(public_key)
+            .cloned()
+    }
+
+    /// Records the estimated clock skew in milliseconds of the peer with the given key
+    /// and returns the updated estimation.
+    pub(crate) fn add_peer_clock_skew(
+        &self,
+        public_key: PublicKey,
+        validator_id: Option<ValidatorId>,
+        skew: i64,
+    ) -> PeerClockSkew {
+        self.state
+            .write()
+            .expect("Expected write lock.")
+            .clock_skews
+            .entry(public_key)
+            .and_modify(|clock_skew| clock_skew.update(skew))
+            .or_insert_with(|| PeerClockSkew::new(public_key, validator_id, skew))
+            .clone()
+    }
+
+    /// Returns the last `Status` messages received from the peers.
+    pub fn peer_statuses(&self) -> Vec<PeerStatus> {
+        self.state
+            .read()
+            .expect("Expected read lock.")
+            .peer_statuses
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Records the `Status` message received from a peer.
+    pub(crate) fn update_peer_status(&self, status: PeerStatus) {
+        self.state
+            .write()
+            .expect("Expected write lock.")
+            .peer_statuses
+            .insert(status.public_key, status);
+    }
+
+    /// Removes the `Status` message received from the disconnected peer.
+    pub(crate) fn remove_peer_status(&self, public_key: &PublicKey) {
+        self.state
+            .write()
+            .expect("Expected write lock.")
+            .peer_statuses
+            .remove(public_key);
+    }
+
     pub(crate) fn set_broadcast_server_address(&self, address: Addr<websocket::Server>) {
         let mut state = self.state.write().expect("Expected write lock");
         state.broadcast_server_address = Some(address);
@@ -547,3 +850,56 @@ impl<'a, S: Service> From<S> for Box<dyn Service + 'a> {
         Box::new(s) as Self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crypto::gen_keypair;
+
+    #[test]
+    fn quorum_loss() {
+        let api_state = SharedNodeState::new(1_000);
+        let peer = gen_keypair().0;
+        {
+            let mut state = api_state.state.write().unwrap();
+            state.node_role = NodeRole::new(Some(ValidatorId(0)));
+            state.majority_count = 2;
+            state.validators = vec![
+                ValidatorKeys {
+                    consensus_key: gen_keypair().0,
+                    service_key: gen_keypair().0,
+                },
+                ValidatorKeys {
+                    consensus_key: peer,
+                    service_key: gen_keypair().0,
+                },
+            ];
+        }
+
+        let start = Utc::now();
+        api_state.update_quorum_status(start);
+        assert_eq!(api_state.quorum_lost_since(), Some(start));
+        assert!(!api_state.is_read_only());
+        api_state.set_read_only_on_quorum_loss(true);
+        assert!(api_state.is_read_only());
+
+        // The time of the loss is kept while the quorum is not restored.
+        api_state.update_quorum_status(start + Duration::seconds(1));
+        assert_eq!(api_state.quorum_lost_since(), Some(start));
+
+        api_state
+            .state
+            .write()
+            .unwrap()
+            .outgoing_connections
+            .insert(ConnectInfo {
+                address: "127.0.0.1:2000".to_owned(),
+                public_key: peer,
+            });
+        api_state.update_quorum_status(start + Duration::seconds(2));
+        assert_eq!(api_state.quorum_lost_since(), None);
+        assert!(!api_state.is_read_only());
+    }
+}
@@ -14,20 +14,29 @@
 
 #![allow(dead_code, unsafe_code)]
 
+use byteorder::{ByteOrder, LittleEndian};
 use chrono::{DateTime, TimeZone, Utc};
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use serde_json;
 
+use std::{io::Cursor, iter};
+
 use blockchain::{
-    Blockchain, ExecutionResult, Schema, Service, Transaction, TransactionContext, TransactionSet,
+    archive::{ArchiveReader, ArchiveWriter, ArchivedBlock},
+    invariants, Block, Blockchain, ConfigReference, ExecutionResult, GenesisConfig, Schema,
+    Service, Transaction, TransactionContext, TransactionErrorType, TransactionSet, ValidatorKeys,
 };
-use crypto::{gen_keypair, Hash};
+use crypto::{gen_keypair, hash, CryptoHash, Hash};
 use encoding::Error as MessageError;
-use helpers::{Height, ValidatorId};
-use messages::{Message, RawTransaction};
-use storage::{Database, Error, Fork, ListIndex, Snapshot};
+use helpers::{Height, Round, ValidatorId};
+use messages::{Connect, Message, Precommit, RawTransaction};
+use storage::{
+    proof_map_index::ProofPath, Database, Error, Fork, ListIndex, MapIndex, MemoryDB, Snapshot,
+    StorageValue,
+};
 
 const IDX_NAME: &'static str = "idx_name";
+const MAP_NAME: &'static str = "map_name";
 const TEST_SERVICE_ID: u16 = 255;
 
 struct TestService;
@@ -55,6 +64,16 @@ transactions! {
         struct Tx {
             value: u64,
         }
+
+        struct TxAdd {
+            key: u64,
+            value: u64,
+        }
+
+        struct TxDependent {
+            dependency: &Hash,
+            key: u64,
+        }
     }
 }
 
@@ -119,6 +138,44 @@ fn date_time_json_serialization() {
 
 use encoding::Field;
 
+#[test]
+fn migrate_configs_actual_from() {
+    let db = MemoryDB::new();
+    let mut fork = db.fork();
+    let references = vec![
+        ConfigReference::new(Height(0), &hash(&[1])),
+        ConfigReference::new(Height(10), &hash(&[2])),
+    ];
+    {
+        let mut legacy = ListIndex::new("core.configs_actual_from", &mut fork);
+        legacy.extend(references.iter().cloned());
+    }
+
+    let mut schema = Schema::new(&mut fork);
+    assert!(schema
+        .configuration_reference_by_height(Height(5))
+        .is_none());
+    assert!(schema.migrate_configs_actual_from());
+    assert!(!schema.migrate_configs_actual_from());
+    assert_eq!(
+        schema.configs_actual_from().iter().collect::<Vec<_>>(),
+        references
+    );
+
+    let (reference, proof) = schema.configuration_reference_by_height(Height(5)).unwrap();
+    assert_eq!(reference, references[0]);
+    assert_eq!(
+        proof
+            .validate(schema.configs_actual_from().merkle_root(), 2)
+            .unwrap(),
+        vec![(0, &references[0])]
+    );
+    let (reference, _) = schema
+        .configuration_reference_by_height(Height(10))
+        .unwrap();
+    assert_eq!(reference, references[1]);
+}
+
 encoding_struct! {
     struct StructWithTwoSegments {
         first: &[u8],
@@ -163,6 +220,26 @@ fn segments_has_spaces_between() {
     <StructWithTwoSegments as Field>::check(&buffer, 0.into(), 8.into(), 8.into()).unwrap();
 }
 
+impl Transaction for TxAdd {
+    fn execute(&self, mut tc: TransactionContext) -> ExecutionResult {
+        let mut index = MapIndex::new(MAP_NAME, tc.fork());
+        let sum = index.get(&self.key()).unwrap_or(0) + self.value();
+        index.put(&self.key(), sum);
+        Ok(())
+    }
+}
+
+impl Transaction for TxDependent {
+    fn depends_on(&self) -> Option<Hash> {
+        Some(*self.dependency())
+    }
+
+    fn execute(&self, mut tc: TransactionContext) -> ExecutionResult {
+        MapIndex::new(MAP_NAME, tc.fork()).put(&self.key(), 1_u64);
+        Ok(())
+    }
+}
+
 fn gen_tempdir_name() -> String {
     thread_rng().sample_iter(&Alphanumeric).take(10).collect()
 }
@@ -246,6 +323,382 @@ fn handling_tx_panic_storage_error(blockchain: &mut Blockchain) {
     );
 }
 
+fn parallel_execution(serial: &mut Blockchain, parallel: &mut Blockchain) {
+    let (pk, sec_key) = gen_keypair();
+    let mut txs = (0..10)
+        .map(|i| Message::sign_transaction(TxAdd::new(i % 4, i), TEST_SERVICE_ID, pk, &sec_key))
+        .collect::<Vec<_>>();
+    // Transactions updating the same list conflict with each other.
+    txs.extend(
+        [3, 0, 4]
+            .iter()
+            .map(|&value| Message::sign_transaction(Tx::new(value), TEST_SERVICE_ID, pk, &sec_key)),
+    );
+    let tx_hashes = txs.iter().map(|tx| tx.hash()).collect::<Vec<_>>();
+
+    parallel.set_execution_threads(4);
+    for blockchain in &mut [&mut *serial, &mut *parallel] {
+        let mut fork = blockchain.fork();
+        {
+            let mut schema = Schema::new(&mut fork);
+            for tx in &txs {
+                schema.add_transaction_into_pool(tx.clone());
+            }
+        }
+        blockchain.merge(fork.into_patch()).unwrap();
+    }
+
+    let (serial_hash, serial_patch) =
+        serial.create_patch(ValidatorId::zero(), Height::zero(), &tx_hashes);
+    let (parallel_hash, parallel_patch) =
+        parallel.create_patch(ValidatorId::zero(), Height::zero(), &tx_hashes);
+    assert_eq!(serial_hash, parallel_hash);
+
+    serial.merge(serial_patch).unwrap();
+    parallel.merge(parallel_patch).unwrap();
+    let (serial_snapshot, parallel_snapshot) = (serial.snapshot(), parallel.snapshot());
+    let serial_map: MapIndex<_, u64, u64> = MapIndex::new(MAP_NAME, &serial_snapshot);
+    let parallel_map: MapIndex<_, u64, u64> = MapIndex::new(MAP_NAME, &parallel_snapshot);
+    assert_eq!(
+        serial_map.iter().collect::<Vec<_>>(),
+        vec![(0, 12), (1, 15), (2, 8), (3, 10)]
+    );
+    assert_eq!(
+        serial_map.iter().collect::<Vec<_>>(),
+        parallel_map.iter().collect::<Vec<_>>()
+    );
+    let list: ListIndex<_, u64> = ListIndex::new(IDX_NAME, &parallel_snapshot);
+    assert_eq!(list.iter().collect::<Vec<_>>(), vec![3, 14, 4, 10]);
+}
+
+fn parallel_execution_with_dependencies(serial: &mut Blockchain, parallel: &mut Blockchain) {
+    let (pk, sec_key) = gen_keypair();
+    let dependency = Message::sign_transaction(TxAdd::new(0, 1), TEST_SERVICE_ID, pk, &sec_key);
+    let late_dependency =
+        Message::sign_transaction(TxAdd::new(1, 1), TEST_SERVICE_ID, pk, &sec_key);
+    let dependent = |dependency: &Hash, key: u64| {
+        Message::sign_transaction(
+            TxDependent::new(dependency, key),
+            TEST_SERVICE_ID,
+            pk,
+            &sec_key,
+        )
+    };
+    let txs = vec![
+        dependency.clone(),
+        // Met within the same batch.
+        dependent(&dependency.hash(), 10),
+        // Not met, since the dependency goes later in the block.
+        dependent(&late_dependency.hash(), 11),
+        // Never met.
+        dependent(&hash(&[1, 2, 3]), 12),
+        late_dependency,
+    ];
+    let tx_hashes = txs.iter().map(|tx| tx.hash()).collect::<Vec<_>>();
+
+    parallel.set_execution_threads(4);
+    for blockchain in &mut [&mut *serial, &mut *parallel] {
+        let mut fork = blockchain.fork();
+        {
+            let mut schema = Schema::new(&mut fork);
+            for tx in &txs {
+                schema.add_transaction_into_pool(tx.clone());
+            }
+        }
+        blockchain.merge(fork.into_patch()).unwrap();
+    }
+
+    let (serial_hash, serial_patch) =
+        serial.create_patch(ValidatorId::zero(), Height::zero(), &tx_hashes);
+    let (parallel_hash, parallel_patch) =
+        parallel.create_patch(ValidatorId::zero(), Height::zero(), &tx_hashes);
+    assert_eq!(serial_hash, parallel_hash);
+
+    parallel.merge(parallel_patch).unwrap();
+    serial.merge(serial_patch).unwrap();
+    let snapshot = parallel.snapshot();
+    let schema = Schema::new(&snapshot);
+    let results = tx_hashes
+        .iter()
+        .map(|tx_hash| schema.transaction_results().get(tx_hash).unwrap().0)
+        .map(|result| result.map_err(|e| e.error_type()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        results,
+        vec![
+            Ok(()),
+            Ok(()),
+            Err(TransactionErrorType::UnmetDependency),
+            Err(TransactionErrorType::UnmetDependency),
+            Ok(()),
+        ]
+    );
+    let map: MapIndex<_, u64, u64> = MapIndex::new(MAP_NAME, &snapshot);
+    assert_eq!(map.keys().collect::<Vec<_>>(), vec![0, 1, 10]);
+}
+
+fn saved_peers_skip_malformed(blockchain: &mut Blockchain) {
+    let (public_key, secret_key) = gen_keypair();
+    let connect = Message::concrete(
+        Connect::new(
+            "127.0.0.1:2000".parse().unwrap(),
+            Utc.timestamp(0, 0),
+            "user agent",
+        ),
+        public_key,
+        &secret_key,
+    );
+    blockchain.save_peer(&public_key, connect.clone());
+
+    // E.g., a message saved in the format of an earlier version.
+    let mut fork = blockchain.fork();
+    MapIndex::new("core.peers_cache", &mut fork).put(&gen_keypair().0, vec![1_u8, 2, 3]);
+    blockchain.merge(fork.into_patch()).unwrap();
+
+    let peers = blockchain.get_saved_peers();
+    assert_eq!(peers.len(), 1);
+    assert_eq!(peers[&public_key], connect);
+}
+
+fn commit_evicted_tx(blockchain: &mut Blockchain) {
+    let (pk, sec_key) = gen_keypair();
+    let tx_evicted = Message::sign_transaction(Tx::new(3), TEST_SERVICE_ID, pk, &sec_key);
+    let tx_pooled = Message::sign_transaction(Tx::new(4), TEST_SERVICE_ID, pk, &sec_key);
+    let mut fork = blockchain.fork();
+    {
+        let mut schema = Schema::new(&mut fork);
+        schema.add_transaction_into_pool(tx_evicted.clone());
+        schema.add_transaction_into_pool(tx_pooled.clone());
+    }
+    blockchain.merge(fork.into_patch()).unwrap();
+
+    let (block_hash, patch) =
+        blockchain.create_patch(ValidatorId::zero(), Height::zero(), &[tx_evicted.hash()]);
+    // The transaction is evicted from the pool of this node after the block is executed.
+    let mut fork = blockchain.fork();
+    Schema::new(&mut fork)
+        .reject_transaction(&tx_evicted.hash())
+        .unwrap();
+    blockchain.merge(fork.into_patch()).unwrap();
+
+    blockchain
+        .commit(&patch, block_hash, iter::empty())
+        .unwrap();
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    assert_eq!(schema.transactions_pool_len(), 1);
+    assert!(schema.transactions_pool().contains(&tx_pooled.hash()));
+}
+
+fn archive_blocks_unsupported(blockchain: &mut Blockchain) {
+    // `MemoryDB` has no cold storage, so the blocks must stay where they are.
+    assert!(!blockchain.supports_archive());
+    assert_eq!(blockchain.archive_blocks(Height(5), 16).unwrap(), 0);
+    let snapshot = blockchain.snapshot();
+    assert_eq!(Schema::new(&snapshot).cold_storage_height(), Height(0));
+}
+
+fn paranoid_merge(blockchain: &mut Blockchain) {
+    blockchain.set_paranoid(true);
+
+    let mut fork = blockchain.fork();
+    ListIndex::new(IDX_NAME, &mut fork).push(1_u64);
+    blockchain.merge(fork.into_patch()).unwrap();
+
+    // Data written bypassing the indexes is not registered in the indexes metadata.
+    let mut fork = blockchain.fork();
+    fork.put("unregistered", vec![1], vec![2]);
+    assert!(blockchain.merge(fork.into_patch()).is_err());
+
+    // The cached size of the transaction pool must match the actual one.
+    let mut fork = blockchain.fork();
+    Schema::new(&mut fork).transactions_pool_len_index_mut().set(1);
+    assert!(blockchain.merge(fork.into_patch()).is_err());
+}
+
+fn storage_stats_on_merge(blockchain: &mut Blockchain) {
+    let index_stats = |blockchain: &Blockchain| {
+        let snapshot = blockchain.snapshot();
+        let stats = Schema::new(&snapshot)
+            .storage_stats()
+            .get(MAP_NAME)
+            .expect("Statistics of the index are missing");
+        (stats.entries(), stats.bytes())
+    };
+
+    let mut fork = blockchain.fork();
+    {
+        let mut index = MapIndex::new(MAP_NAME, &mut fork);
+        index.put(&1_u64, 10_u64);
+        index.put(&2_u64, 20_u64);
+    }
+    blockchain.merge(fork.into_patch()).unwrap();
+    assert_eq!(index_stats(blockchain), (2, 32));
+
+    // Overwritten values are not counted twice.
+    let mut fork = blockchain.fork();
+    MapIndex::new(MAP_NAME, &mut fork).put(&1_u64, 11_u64);
+    blockchain.merge(fork.into_patch()).unwrap();
+    assert_eq!(index_stats(blockchain), (2, 32));
+
+    let mut fork = blockchain.fork();
+    MapIndex::<_, u64, u64>::new(MAP_NAME, &mut fork).clear();
+    blockchain.merge(fork.into_patch()).unwrap();
+    assert_eq!(index_stats(blockchain), (0, 0));
+}
+
+fn append_block(fork: &mut Fork, height: u64, prev_hash: &Hash) -> Hash {
+    let block = Block::new(
+        ValidatorId::zero(),
+        Height(height),
+        0,
+        prev_hash,
+        &Hash::zero(),
+        &Hash::zero(),
+    );
+    let block_hash = block.hash();
+    let mut schema = Schema::new(fork);
+    schema.block_hashes_by_height_mut().push(block_hash);
+    schema.blocks_mut().put(&block_hash, block);
+    block_hash
+}
+
+fn commit_finality(blockchain: &mut Blockchain) {
+    let mut fork = blockchain.fork();
+    let genesis_hash = append_block(&mut fork, 0, &Hash::zero());
+    blockchain
+        .commit(&fork.into_patch(), genesis_hash, iter::empty())
+        .unwrap();
+
+    // The patch must append a block.
+    let fork = blockchain.fork();
+    assert!(blockchain
+        .commit(&fork.into_patch(), genesis_hash, iter::empty())
+        .is_err());
+
+    // The committed block cannot be replaced.
+    let mut fork = blockchain.fork();
+    Schema::new(&mut fork).block_hashes_by_height_mut().clear();
+    let forged_hash = append_block(&mut fork, 0, &hash(&[1]));
+    let block_hash = append_block(&mut fork, 1, &forged_hash);
+    assert!(blockchain
+        .commit(&fork.into_patch(), block_hash, iter::empty())
+        .is_err());
+
+    // The new block must be linked with the latest committed one.
+    let mut fork = blockchain.fork();
+    let block_hash = append_block(&mut fork, 1, &Hash::zero());
+    assert!(blockchain
+        .commit(&fork.into_patch(), block_hash, iter::empty())
+        .is_err());
+    assert_eq!(Schema::new(&blockchain.snapshot()).height(), Height(0));
+
+    let mut fork = blockchain.fork();
+    let block_hash = append_block(&mut fork, 1, &genesis_hash);
+    blockchain
+        .commit(&fork.into_patch(), block_hash, iter::empty())
+        .unwrap();
+    assert_eq!(Schema::new(&blockchain.snapshot()).height(), Height(1));
+}
+
+fn check_chain_detects_tampering(blockchain: &mut Blockchain) {
+    let (consensus_key, _) = gen_keypair();
+    let (service_key, _) = gen_keypair();
+    let genesis = GenesisConfig::new(iter::once(ValidatorKeys {
+        consensus_key,
+        service_key,
+    }));
+    blockchain.initialize(genesis).unwrap();
+    assert_eq!(
+        invariants::check_chain(blockchain.snapshot()).unwrap(),
+        Height(0)
+    );
+
+    // Replace the stored configuration bypassing the index, so that the root hash
+    // of the configurations table no longer matches the state hash.
+    let snapshot = blockchain.snapshot();
+    let (config_hash, mut config) = Schema::new(&snapshot).configs().iter().next().unwrap();
+    config.actual_from = Height(5);
+    let mut fork = blockchain.fork();
+    fork.put(
+        "core.configs",
+        ProofPath::new(&config_hash).as_bytes().to_vec(),
+        config.into_bytes(),
+    );
+    blockchain.merge(fork.into_patch()).unwrap();
+    assert!(invariants::check_chain(blockchain.snapshot()).is_err());
+}
+
+#[test]
+fn block_archive_roundtrip() {
+    let (pk, sec_key) = gen_keypair();
+    let blocks = (0..3)
+        .map(|height| {
+            let tx = Message::sign_transaction(Tx::new(height), TEST_SERVICE_ID, pk, &sec_key);
+            let block = Block::new(
+                ValidatorId::zero(),
+                Height(height),
+                1,
+                &Hash::zero(),
+                &tx.hash(),
+                &Hash::zero(),
+            );
+            let precommit = Message::concrete(
+                Precommit::new(
+                    ValidatorId::zero(),
+                    Height(height),
+                    Round::first(),
+                    &Hash::zero(),
+                    &block.hash(),
+                    Utc::now(),
+                ),
+                pk,
+                &sec_key,
+            );
+            ArchivedBlock {
+                block,
+                precommits: vec![precommit],
+                transactions: vec![tx],
+            }
+        }).collect::<Vec<_>>();
+
+    let mut writer = ArchiveWriter::new(Vec::new(), Height::zero()).unwrap();
+    for block in &blocks {
+        writer.append(block).unwrap();
+    }
+    // Blocks can be appended only in the order of their heights.
+    assert!(writer.append(&blocks[1]).is_err());
+    let archive = writer.finish().unwrap();
+
+    let mut reader = ArchiveReader::new(Cursor::new(archive.clone())).unwrap();
+    assert_eq!(reader.first_height(), Height::zero());
+    assert_eq!(reader.len(), 3);
+    assert_eq!(reader.read_block(Height(2)).unwrap(), blocks[2]);
+    assert_eq!(reader.read_block(Height(0)).unwrap(), blocks[0]);
+    assert!(reader.read_block(Height(3)).is_err());
+
+    // Corruption of the record is detected by its checksum.
+    let mut corrupted = archive.clone();
+    corrupted[30] ^= 1;
+    let mut reader = ArchiveReader::new(Cursor::new(corrupted)).unwrap();
+    assert!(reader.read_block(Height(0)).is_err());
+    assert_eq!(reader.read_block(Height(1)).unwrap(), blocks[1]);
+
+    // Lengths exceeding the archive size are rejected before allocating memory.
+    let mut corrupted = archive.clone();
+    let footer = corrupted.len() - 20;
+    LittleEndian::write_u32(&mut corrupted[footer + 8..footer + 12], u32::max_value());
+    assert!(ArchiveReader::new(Cursor::new(corrupted)).is_err());
+
+    let mut corrupted = archive.clone();
+    LittleEndian::write_u32(&mut corrupted[18..22], u32::max_value());
+    let mut reader = ArchiveReader::new(Cursor::new(corrupted)).unwrap();
+    assert!(reader.read_block(Height(0)).is_err());
+    assert_eq!(reader.read_block(Height(2)).unwrap(), blocks[2]);
+
+    assert!(ArchiveReader::new(Cursor::new(archive[..30].to_vec())).is_err());
+}
+
 mod transactions_tests {
     use super::TEST_SERVICE_ID;
     use blockchain::{ExecutionResult, Transaction, TransactionContext};
@@ -307,6 +760,22 @@ mod transactions_tests {
         round_trip(c);
     }
 
+    #[test]
+    fn deserialize_by_message_id() {
+        use blockchain::TransactionSet;
+
+        let b = B::new(1, 2);
+        let payload = serde_json::to_value(&b).unwrap();
+        let parsed = MyTransactions::tx_from_json(1, payload.clone()).unwrap();
+        match parsed {
+            MyTransactions::B(ref tx) => assert_eq!(*tx, b),
+            _ => panic!("Unexpected transaction type: {:?}", parsed),
+        }
+
+        assert!(MyTransactions::tx_from_json(0, payload.clone()).is_err());
+        assert!(MyTransactions::tx_from_json(3, payload).is_err());
+    }
+
     #[test]
     fn deserialize_from_raw() {
         use blockchain::TransactionSet;
@@ -471,6 +940,70 @@ mod memorydb_tests {
         super::handling_tx_panic_storage_error(&mut blockchain);
     }
 
+    #[test]
+    #[should_panic]
+    fn handling_tx_panic_storage_error_parallel() {
+        let mut blockchain = create_blockchain();
+        blockchain.set_execution_threads(4);
+        super::handling_tx_panic_storage_error(&mut blockchain);
+    }
+
+    #[test]
+    fn parallel_execution() {
+        let mut serial = create_blockchain();
+        let mut parallel = create_blockchain();
+        super::parallel_execution(&mut serial, &mut parallel);
+    }
+
+    #[test]
+    fn parallel_execution_with_dependencies() {
+        let mut serial = create_blockchain();
+        let mut parallel = create_blockchain();
+        super::parallel_execution_with_dependencies(&mut serial, &mut parallel);
+    }
+
+    #[test]
+    fn saved_peers_skip_malformed() {
+        let mut blockchain = create_blockchain();
+        super::saved_peers_skip_malformed(&mut blockchain);
+    }
+
+    #[test]
+    fn commit_evicted_tx() {
+        let mut blockchain = create_blockchain();
+        super::commit_evicted_tx(&mut blockchain);
+    }
+
+    #[test]
+    fn archive_blocks_unsupported() {
+        let mut blockchain = create_blockchain();
+        super::archive_blocks_unsupported(&mut blockchain);
+    }
+
+    #[test]
+    fn paranoid_merge() {
+        let mut blockchain = create_blockchain();
+        super::paranoid_merge(&mut blockchain);
+    }
+
+    #[test]
+    fn commit_finality() {
+        let mut blockchain = create_blockchain();
+        super::commit_finality(&mut blockchain);
+    }
+
+    #[test]
+    fn storage_stats_on_merge() {
+        let mut blockchain = create_blockchain();
+        super::storage_stats_on_merge(&mut blockchain);
+    }
+
+    #[test]
+    fn check_chain_detects_tampering() {
+        let mut blockchain = create_blockchain();
+        super::check_chain_detects_tampering(&mut blockchain);
+    }
+
     #[test]
     fn service_execute() {
         let blockchain = create_blockchain_with_service(Box::new(ServiceGood));
@@ -494,6 +1027,7 @@ mod memorydb_tests {
     }
 }
 
+#[cfg(feature = "rocksdb")]
 mod rocksdb_tests {
     use blockchain::{Blockchain, Service};
     use crypto::gen_keypair;
@@ -14,8 +14,10 @@
 
 //! `Transaction` related types.
 use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{self, Value};
 use std::{any::Any, borrow::Cow, convert::Into, error::Error, fmt, u8};
 
+use super::offchain::OffchainRef;
 use crypto::{CryptoHash, Hash, PublicKey};
 use encoding;
 use hex::ToHex;
@@ -29,6 +31,8 @@ const MAX_ERROR_CODE: u16 = u8::max_value() as u16;
 const TRANSACTION_STATUS_OK: u16 = MAX_ERROR_CODE + 1;
 // `Err(TransactionErrorType::Panic)`.
 const TRANSACTION_STATUS_PANIC: u16 = TRANSACTION_STATUS_OK + 1;
+// `Err(TransactionErrorType::UnmetDependency)`.
+const TRANSACTION_STATUS_UNMET_DEPENDENCY: u16 = TRANSACTION_STATUS_PANIC + 1;
 
 /// Returns a result of the `Transaction` `execute` method. This result may be
 /// either an empty unit type, in case of success, or an `ExecutionError`, if execution has
@@ -158,6 +162,34 @@ pub trait Transaction: ::std::fmt::Debug + Send + 'static + ::erased_serde::Seri
         true
     }
 
+    /// Returns the hash of the transaction which must be committed before this one.
+    ///
+    /// A transaction with an uncommitted dependency is held in the pool of unconfirmed
+    /// transactions and is not included into block proposals until its dependency is
+    /// committed. If such a transaction still gets into a block, for example, proposed
+    /// by a malicious validator, it fails with the `UnmetDependency` error without
+    /// being executed.
+    ///
+    /// *This method should not use external data, that is, it must be a pure function.*
+    ///
+    /// *Default implementation returns `None`*
+    fn depends_on(&self) -> Option<Hash> {
+        None
+    }
+
+    /// Returns the references to the off-chain content the transaction commits to.
+    ///
+    /// If the node has an off-chain content fetcher, the transactions submitted through
+    /// the API are accepted only if all the referenced content is available and matches
+    /// the committed hashes. See the [`offchain`](offchain/index.html) module for details.
+    ///
+    /// *This method should not use external data, that is, it must be a pure function.*
+    ///
+    /// *Default implementation returns an empty vector*
+    fn offchain_refs(&self) -> Vec<OffchainRef> {
+        Vec::new()
+    }
+
     /// Receives a `TransactionContext` witch contain fork
     /// of the current blockchain state and can modify it depending on the contents
     /// of the transaction.
@@ -288,6 +320,8 @@ pub enum TransactionErrorType {
     /// User-defined error code. Can have different meanings for different transactions and
     /// services.
     Code(u8),
+    /// The transaction dependency was not committed before the transaction.
+    UnmetDependency,
 }
 
 /// Result of unsuccessful transaction execution encompassing both service and framework-wide error
@@ -300,6 +334,8 @@ pub enum TransactionErrorType {
 ///   implementation for the details).
 /// - `TransactionErrorType::Panic` is set by the framework if panic is raised during transaction
 ///   execution.
+/// - `TransactionErrorType::UnmetDependency` is set by the framework if the transaction
+///   dependency was not committed before the transaction, which is not executed in this case.
 /// - `TransactionError` implements `Display` which can be used for obtaining a simple error
 ///   description.
 ///
@@ -357,13 +393,18 @@ impl TransactionError {
         Self::new(TransactionErrorType::Panic, description)
     }
 
+    /// Creates a new `TransactionError` for the transaction with an uncommitted dependency.
+    pub(crate) fn unmet_dependency(description: Option<String>) -> Self {
+        Self::new(TransactionErrorType::UnmetDependency, description)
+    }
+
     /// Creates a new `TransactionError` instance from `std::thread::Result`'s `Err`.
     pub(crate) fn from_panic(panic: &Box<dyn Any + Send>) -> Self {
         Self::panic(panic_description(panic))
     }
 
     /// Returns an error type of this `TransactionError` instance. This can be
-    /// either a panic or a user-defined error code.
+    /// a panic, a user-defined error code or an unmet dependency.
     pub fn error_type(&self) -> TransactionErrorType {
         self.error_type
     }
@@ -385,6 +426,7 @@ impl fmt::Display for TransactionError {
         match self.error_type {
             TransactionErrorType::Panic => write!(f, "Panic during execution")?,
             TransactionErrorType::Code(c) => write!(f, "Error code: {}", c)?,
+            TransactionErrorType::UnmetDependency => write!(f, "Unmet dependency")?,
         }
 
         if let Some(ref description) = self.description {
@@ -439,6 +481,9 @@ impl StorageValue for TransactionResult {
             value @ 0...MAX_ERROR_CODE => Err(TransactionError::code(value as u8, description)),
             TRANSACTION_STATUS_OK => Ok(()),
             TRANSACTION_STATUS_PANIC => Err(TransactionError::panic(description)),
+            TRANSACTION_STATUS_UNMET_DEPENDENCY => {
+                Err(TransactionError::unmet_dependency(description))
+            }
             value => panic!("Invalid TransactionResult value: {}", value),
         })
     }
@@ -450,6 +495,7 @@ fn status_as_u16(status: &TransactionResult) -> u16 {
         Err(ref e) => match e.error_type {
             TransactionErrorType::Panic => TRANSACTION_STATUS_PANIC,
             TransactionErrorType::Code(c) => u16::from(c),
+            TransactionErrorType::UnmetDependency => TRANSACTION_STATUS_UNMET_DEPENDENCY,
         },
     }
 }
@@ -462,6 +508,31 @@ pub trait TransactionSet:
 {
     /// Parses a transaction from this set from a `RawTransaction`.
     fn tx_from_raw(raw: RawTransaction) -> Result<Self, encoding::Error>;
+
+    /// Returns identifiers and names of the transactions in this set.
+    fn transaction_types() -> Vec<(u16, &'static str)> {
+        Vec::new()
+    }
+
+    /// Parses a transaction from this set from the JSON representation of its payload.
+    ///
+    /// The transaction type is selected by `message_id` in the same way as
+    /// in `tx_from_raw`, so the sets declared with the `transactions!` macro
+    /// do not need any additional code to support both formats.
+    fn tx_from_json(message_id: u16, payload: Value) -> Result<Self, encoding::Error> {
+        let name = Self::transaction_types()
+            .into_iter()
+            .find(|&(id, _)| id == message_id)
+            .map(|(_, name)| name)
+            .ok_or_else(|| {
+                encoding::Error::Basic(format!("Tag {} not found.", message_id).into())
+            })?;
+
+        let mut tagged = serde_json::Map::new();
+        tagged.insert(name.to_owned(), payload);
+        serde_json::from_value(Value::Object(tagged))
+            .map_err(|e| encoding::Error::Basic(e.to_string().into()))
+    }
 }
 
 /// `transactions!` is used to declare a set of transactions of a particular service.
@@ -649,6 +720,14 @@ macro_rules! transactions {
                 let (id, vec) = raw.service_transaction().into_raw_parts();
                 __enum_from_id_vec!($transaction_set (id, vec), $( $name )*)
             }
+
+            fn transaction_types() -> Vec<(u16, &'static str)> {
+                vec![$( stringify!($name) ),*]
+                    .into_iter()
+                    .enumerate()
+                    .map(|(id, name)| (id as u16, name))
+                    .collect()
+            }
         }
 
         impl Into<$crate::messages::ServiceTransaction> for $transaction_set {
@@ -806,6 +885,7 @@ mod tests {
         let values = [
             (TransactionErrorType::Panic, None),
             (TransactionErrorType::Panic, Some("panic")),
+            (TransactionErrorType::UnmetDependency, None),
             (TransactionErrorType::Code(0), None),
             (TransactionErrorType::Code(1), Some("")),
             (TransactionErrorType::Code(100), None),
@@ -849,6 +929,10 @@ mod tests {
             Err(TransactionError::panic(Some(
                 "Panic error description".to_owned(),
             ))),
+            Err(TransactionError::unmet_dependency(None)),
+            Err(TransactionError::unmet_dependency(Some(
+                "Dependency is not committed".to_owned(),
+            ))),
             Err(TransactionError::code(0, None)),
             Err(TransactionError::code(
                 0,
@@ -0,0 +1,385 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use byteorder::{ByteOrder, LittleEndian};
+use serde_json::value::Value;
+
+use std::{error::Error, fmt, str::FromStr};
+
+use super::{Error as EncodingError, Result as EncodingResult};
+use encoding::{
+    serialize::json::{ExonumJson, ExonumJsonDeserialize},
+    serialize::WriteBufferWrapper,
+    CheckedOffset, Field, Offset,
+};
+
+/// Size of the mantissa in bytes.
+const MANTISSA_SIZE: Offset = 16;
+
+/// Fixed-point decimal number with an `i128` mantissa and a decimal scale.
+///
+/// The represented value equals `mantissa * 10^(-scale)`. Scale cannot exceed
+/// [`MAX_SCALE`](#associatedconstant.MAX_SCALE). In the binary format the mantissa is encoded
+/// as 16 little-endian bytes followed by a single scale byte.
+///
+/// Compared to `rust_decimal::Decimal`, this type has a wider mantissa (128 bits instead
+/// of 96) and a larger maximal scale (38 instead of 28), which makes it suitable for
+/// high-precision amounts.
+///
+/// Equality is structural: `1.0` and `1.00` are different values, since they have
+/// different binary representations (and, therefore, different hashes). Use
+/// [`rescale`](#method.rescale) to bring values to a common scale before comparing them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BigDecimal {
+    mantissa: i128,
+    scale: u8,
+}
+
+impl BigDecimal {
+    /// Maximal supported scale.
+    pub const MAX_SCALE: u8 = 38;
+
+    /// Creates a new `BigDecimal` instance with the given `mantissa` and `scale`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `scale` exceeds `MAX_SCALE`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::encoding::BigDecimal;
+    ///
+    /// let val = BigDecimal::new(314, 2);
+    /// assert_eq!(val.to_string(), "3.14");
+    /// ```
+    pub fn new(mantissa: i128, scale: u8) -> Self {
+        Self::try_new(mantissa, scale).expect("BigDecimal scale is too large")
+    }
+
+    /// Creates a new `BigDecimal` instance with the given `mantissa` and `scale`. Returns `None`
+    /// if `scale` exceeds `MAX_SCALE`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::encoding::BigDecimal;
+    ///
+    /// assert!(BigDecimal::try_new(1, 38).is_some());
+    /// assert!(BigDecimal::try_new(1, 39).is_none());
+    /// ```
+    pub fn try_new(mantissa: i128, scale: u8) -> Option<Self> {
+        if scale <= Self::MAX_SCALE {
+            Some(Self { mantissa, scale })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the mantissa of this number.
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    /// Returns the scale of this number.
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    /// Converts this number to the given `scale` without losing precision. Returns `None`
+    /// if the scale is too large, the mantissa overflows or the conversion would require
+    /// rounding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::encoding::BigDecimal;
+    ///
+    /// let val = BigDecimal::new(150, 2);
+    /// assert_eq!(val.rescale(1), Some(BigDecimal::new(15, 1)));
+    /// assert_eq!(val.rescale(4), Some(BigDecimal::new(15_000, 4)));
+    /// assert_eq!(val.rescale(0), None);
+    /// ```
+    pub fn rescale(&self, scale: u8) -> Option<Self> {
+        if scale > Self::MAX_SCALE {
+            return None;
+        }
+        let mantissa = if scale >= self.scale {
+            let factor = pow10(scale - self.scale)?;
+            self.mantissa.checked_mul(factor)?
+        } else {
+            let factor = pow10(self.scale - scale)?;
+            if self.mantissa % factor != 0 {
+                return None;
+            }
+            self.mantissa / factor
+        };
+        Some(Self { mantissa, scale })
+    }
+
+    /// Adds two numbers, returning `None` if the result cannot be represented.
+    /// The result has the larger scale of the two operands.
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let (lhs, rhs) = self.align(other)?;
+        let mantissa = lhs.mantissa.checked_add(rhs.mantissa)?;
+        Some(Self {
+            mantissa,
+            scale: lhs.scale,
+        })
+    }
+
+    /// Subtracts `other` from this number, returning `None` if the result cannot be represented.
+    /// The result has the larger scale of the two operands.
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        let (lhs, rhs) = self.align(other)?;
+        let mantissa = lhs.mantissa.checked_sub(rhs.mantissa)?;
+        Some(Self {
+            mantissa,
+            scale: lhs.scale,
+        })
+    }
+
+    /// Multiplies two numbers, returning `None` if the result cannot be represented.
+    /// The scale of the result is the sum of the operand scales.
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let mantissa = self.mantissa.checked_mul(other.mantissa)?;
+        Self::try_new(mantissa, self.scale.checked_add(other.scale)?)
+    }
+
+    fn align(&self, other: &Self) -> Option<(Self, Self)> {
+        let scale = ::std::cmp::max(self.scale, other.scale);
+        Some((self.rescale(scale)?, other.rescale(scale)?))
+    }
+}
+
+fn pow10(exp: u8) -> Option<i128> {
+    (0..exp).try_fold(1i128, |acc, _| acc.checked_mul(10))
+}
+
+impl fmt::Display for BigDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let abs = if self.mantissa < 0 {
+            (self.mantissa as u128).wrapping_neg()
+        } else {
+            self.mantissa as u128
+        };
+        let digits = abs.to_string();
+        let scale = self.scale as usize;
+        if self.mantissa < 0 {
+            write!(f, "-")?;
+        }
+        if scale == 0 {
+            write!(f, "{}", digits)
+        } else if digits.len() > scale {
+            let (int, frac) = digits.split_at(digits.len() - scale);
+            write!(f, "{}.{}", int, frac)
+        } else {
+            let zeros = "0".repeat(scale - digits.len());
+            write!(f, "0.{}{}", zeros, digits)
+        }
+    }
+}
+
+impl FromStr for BigDecimal {
+    type Err = EncodingError;
+
+    /// Parses a decimal number in the `[-]digits[.digits]` format. The scale of the result
+    /// equals the number of digits after the decimal point.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (int, frac) = match s.find('.') {
+            Some(pos) => (&s[..pos], &s[pos + 1..]),
+            None => (s, ""),
+        };
+        let unsigned = if int.starts_with('-') { &int[1..] } else { int };
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if unsigned.is_empty() || !is_digits(unsigned) || !is_digits(frac) {
+            return Err("Invalid decimal number".into());
+        }
+        if s.ends_with('.') {
+            return Err("Invalid decimal number".into());
+        }
+        if frac.len() > Self::MAX_SCALE as usize {
+            return Err("BigDecimal scale is too large".into());
+        }
+        let mantissa = i128::from_str(&format!("{}{}", int, frac))
+            .map_err(|_| EncodingError::from("BigDecimal mantissa overflow"))?;
+        Ok(Self::new(mantissa, frac.len() as u8))
+    }
+}
+
+impl<'a> Field<'a> for BigDecimal {
+    fn field_size() -> Offset {
+        MANTISSA_SIZE + 1
+    }
+
+    unsafe fn read(buffer: &'a [u8], from: Offset, to: Offset) -> Self {
+        let buffer = &buffer[from as usize..to as usize];
+        Self::new(
+            LittleEndian::read_i128(&buffer[..MANTISSA_SIZE as usize]),
+            buffer[MANTISSA_SIZE as usize],
+        )
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>, from: Offset, to: Offset) {
+        let buffer = &mut buffer[from as usize..to as usize];
+        LittleEndian::write_i128(&mut buffer[..MANTISSA_SIZE as usize], self.mantissa);
+        buffer[MANTISSA_SIZE as usize] = self.scale;
+    }
+
+    fn check(
+        buffer: &'a [u8],
+        from: CheckedOffset,
+        to: CheckedOffset,
+        latest_segment: CheckedOffset,
+    ) -> EncodingResult {
+        debug_assert_eq!((to - from)?.unchecked_offset(), Self::field_size());
+
+        let position = (from + MANTISSA_SIZE)?.unchecked_offset();
+        let scale = buffer[position as usize];
+        if scale <= Self::MAX_SCALE {
+            Ok(latest_segment)
+        } else {
+            Err(EncodingError::IncorrectDecimalScale {
+                position,
+                value: scale,
+            })
+        }
+    }
+}
+
+impl ExonumJson for BigDecimal {
+    fn deserialize_field<B: WriteBufferWrapper>(
+        value: &Value,
+        buffer: &mut B,
+        from: Offset,
+        to: Offset,
+    ) -> Result<(), Box<Error>> {
+        buffer.write(from, to, Self::deserialize(value)?);
+        Ok(())
+    }
+
+    fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
+        Ok(Value::String(self.to_string()))
+    }
+}
+
+impl ExonumJsonDeserialize for BigDecimal {
+    fn deserialize(value: &Value) -> Result<Self, Box<Error>> {
+        let string = value.as_str().ok_or("Can't cast json as string")?;
+        Ok(string.parse()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BigDecimal;
+    use encoding::fields::Field;
+    use encoding::serialize::json::{ExonumJson, ExonumJsonDeserialize};
+
+    #[test]
+    fn test_decimal_display_and_parse() {
+        let cases = [
+            (BigDecimal::new(0, 0), "0"),
+            (BigDecimal::new(314, 2), "3.14"),
+            (BigDecimal::new(-314, 2), "-3.14"),
+            (BigDecimal::new(5, 3), "0.005"),
+            (BigDecimal::new(-5, 3), "-0.005"),
+            (
+                BigDecimal::new(i128::max_value(), 0),
+                "170141183460469231731687303715884105727",
+            ),
+            (
+                BigDecimal::new(i128::min_value(), 0),
+                "-170141183460469231731687303715884105728",
+            ),
+        ];
+        for &(value, string) in &cases {
+            assert_eq!(value.to_string(), string);
+            assert_eq!(string.parse::<BigDecimal>().unwrap(), value);
+        }
+        assert_eq!(
+            "-0.005".parse::<BigDecimal>().unwrap(),
+            BigDecimal::new(-5, 3)
+        );
+
+        let invalid = ["", "-", ".5", "1.", "1.2.3", "1e5", "+1", "0.x", "1 "];
+        for string in &invalid {
+            assert!(string.parse::<BigDecimal>().is_err(), "{}", string);
+        }
+        assert!("170141183460469231731687303715884105728"
+            .parse::<BigDecimal>()
+            .is_err());
+        assert!(format!("0.{}", "0".repeat(39))
+            .parse::<BigDecimal>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_decimal_arithmetic() {
+        let a = BigDecimal::new(150, 2);
+        let b = BigDecimal::new(25, 1);
+        assert_eq!(a.checked_add(&b), Some(BigDecimal::new(400, 2)));
+        assert_eq!(a.checked_sub(&b), Some(BigDecimal::new(-100, 2)));
+        assert_eq!(a.checked_mul(&b), Some(BigDecimal::new(3750, 3)));
+
+        let max = BigDecimal::new(i128::max_value(), 0);
+        assert_eq!(max.checked_add(&BigDecimal::new(1, 0)), None);
+        assert_eq!(max.checked_add(&BigDecimal::new(1, 1)), None);
+        assert_eq!(
+            BigDecimal::new(1, 20).checked_mul(&BigDecimal::new(1, 19)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_decimal_encoding() {
+        let value = BigDecimal::new(-123_456_789, 4);
+        let mut buffer = vec![0; 17];
+        value.write(&mut buffer, 0, 17);
+        assert_eq!(buffer[16], 4);
+        assert!(<BigDecimal as Field>::check(&buffer, 0.into(), 17.into(), 17.into()).is_ok());
+        assert_eq!(
+            unsafe { <BigDecimal as Field>::read(&buffer, 0, 17) },
+            value
+        );
+
+        buffer[16] = BigDecimal::MAX_SCALE + 1;
+        assert!(<BigDecimal as Field>::check(&buffer, 0.into(), 17.into(), 17.into()).is_err());
+    }
+
+    #[test]
+    fn test_decimal_json() {
+        let value = BigDecimal::new(-5, 3);
+        let json = value.serialize_field().unwrap();
+        assert_eq!(json, json!("-0.005"));
+        assert_eq!(BigDecimal::deserialize(&json).unwrap(), value);
+        assert!(BigDecimal::deserialize(&json!(0.005)).is_err());
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn test_decimal_struct() {
+        encoding_struct! {
+            struct Msg {
+                price: BigDecimal,
+                amounts: Vec<BigDecimal>,
+            }
+        }
+
+        let amounts = vec![BigDecimal::new(1, 0), BigDecimal::new(-25, 1)];
+        let msg = Msg::new(BigDecimal::new(99_99, 2), amounts.clone());
+        assert_eq!(msg.price(), BigDecimal::new(9999, 2));
+        assert_eq!(msg.amounts(), amounts);
+    }
+}
@@ -42,6 +42,13 @@ pub enum Error {
         /// Value represented as `f64`.
         value: f64,
     },
+    /// Decimal scale exceeds the maximal supported one.
+    IncorrectDecimalScale {
+        /// Position in buffer where error appears.
+        position: Offset,
+        /// Scale value.
+        value: u8,
+    },
     /// SocketAddr header is neither 0 nor 1.
     IncorrectSocketAddrHeader {
         /// Position in buffer where error appears.
@@ -107,6 +114,13 @@ pub enum Error {
         /// Nanoseconds in gotten duration.
         nanos: i32,
     },
+    /// Incorrect date and time representation.
+    IncorrectDateTime {
+        /// Seconds since the Unix epoch in gotten timestamp.
+        secs: i64,
+        /// Nanoseconds in gotten timestamp.
+        nanos: u32,
+    },
     /// Basic error support, for custom fields.
     Basic(Cow<'static, str>),
     /// Other error for custom fields.
@@ -125,6 +139,7 @@ impl StdError for Error {
             Error::UnexpectedlyShortPayload { .. } => "Unexpectedly short payload",
             Error::IncorrectBoolean { .. } => "Incorrect boolean value",
             Error::UnsupportedFloat { .. } => "Unsupported float value",
+            Error::IncorrectDecimalScale { .. } => "Incorrect decimal scale",
             Error::IncorrectSocketAddrHeader { .. } => "Incorrect SocketAddr header value",
             Error::IncorrectSocketAddrPadding { .. } => "Incorrect SocketAddr padding",
             Error::IncorrectSegmentReference { .. } => "Incorrect segment reference",
@@ -136,6 +151,7 @@ impl StdError for Error {
             Error::OffsetOverflow => "Offset pointers overflow",
             Error::DurationOverflow => "Overflow in Duration object",
             Error::IncorrectDuration { .. } => "Incorrect Duration object representation",
+            Error::IncorrectDateTime { .. } => "Incorrect DateTime object representation",
             Error::Basic(_) | Error::Other(_) => "Other error",
         }
     }
@@ -71,6 +71,13 @@ pub trait Field<'a> {
         debug_assert_eq!((to - from)?.unchecked_offset(), Self::field_size());
         Ok(latest_segment)
     }
+
+    /// Returns `true` if the field data is stored in a segment, so that the header
+    /// contains only the position and the size of the segment.
+    /// Default implementation returns `false`.
+    fn is_segment() -> bool {
+        false
+    }
 }
 
 /// Implements the [`Field`] trait for a type that has writer and reader functions.
@@ -255,6 +262,8 @@ implement_std_field!{u32 LittleEndian::read_u32; LittleEndian::write_u32}
 implement_std_field!{i32 LittleEndian::read_i32; LittleEndian::write_i32}
 implement_std_field!{u64 LittleEndian::read_u64; LittleEndian::write_u64}
 implement_std_field!{i64 LittleEndian::read_i64; LittleEndian::write_i64}
+implement_std_field!{u128 LittleEndian::read_u128; LittleEndian::write_u128}
+implement_std_field!{i128 LittleEndian::read_i128; LittleEndian::write_i128}
 
 implement_std_typedef_field!{Height(u64) LittleEndian::read_u64; LittleEndian::write_u64}
 implement_std_typedef_field!{Round(u32) LittleEndian::read_u32; LittleEndian::write_u32}
@@ -274,12 +283,20 @@ impl<'a> Field<'a> for DateTime<Utc> {
             LittleEndian::read_i64(&buffer[from as usize..from as usize + mem::size_of::<i64>()]);
         let nanos =
             LittleEndian::read_u32(&buffer[from as usize + mem::size_of::<i64>()..to as usize]);
+        // Assuming that buffer was checked and DateTime object can be constructed.
         Utc.timestamp(secs, nanos)
     }
 
     fn write(&self, buffer: &mut Vec<u8>, from: Offset, to: Offset) {
         let secs = self.timestamp();
         let nanos = self.timestamp_subsec_nanos();
+        // Values with non-canonical representation would fail the check on reading.
+        assert!(
+            date_time_from_timestamp(secs, nanos).is_some(),
+            "Got DateTime object with incorrect representation in Field::write: {}s {}ns",
+            secs,
+            nanos
+        );
         LittleEndian::write_i64(
             &mut buffer[from as usize..from as usize + mem::size_of::<i64>()],
             secs,
@@ -289,6 +306,41 @@ impl<'a> Field<'a> for DateTime<Utc> {
             nanos,
         );
     }
+
+    fn check(
+        buffer: &'a [u8],
+        from: CheckedOffset,
+        to: CheckedOffset,
+        latest_segment: CheckedOffset,
+    ) -> Result {
+        debug_assert_eq!((to - from)?.unchecked_offset(), Self::field_size());
+        let from_unchecked = from.unchecked_offset() as usize;
+        let to_unchecked = to.unchecked_offset() as usize;
+
+        let secs =
+            LittleEndian::read_i64(&buffer[from_unchecked..from_unchecked + mem::size_of::<i64>()]);
+        let nanos =
+            LittleEndian::read_u32(&buffer[from_unchecked + mem::size_of::<i64>()..to_unchecked]);
+
+        match date_time_from_timestamp(secs, nanos) {
+            Some(_) => Ok(latest_segment),
+            None => Err(Error::IncorrectDateTime { secs, nanos }),
+        }
+    }
+}
+
+/// Constructs `DateTime<Utc>` from its canonical representation, returning `None`
+/// if the representation is out of range or isn't canonical.
+///
+/// Nanoseconds must be less than 10 ** 9, except for leap seconds, which may only occur
+/// in the last second of a minute and are represented with nanoseconds in
+/// `[10 ** 9, 2 * 10 ** 9)`. This guarantees that each instant has exactly one representation.
+pub(crate) fn date_time_from_timestamp(secs: i64, nanos: u32) -> Option<DateTime<Utc>> {
+    let nanos_per_sec = 1_000_000_000;
+    if nanos >= nanos_per_sec && (secs % 60 + 60) % 60 != 59 {
+        return None;
+    }
+    Utc.timestamp_opt(secs, nanos).single()
 }
 
 fn is_duration_representation_valid(secs: i64, nanos: i32) -> bool {
@@ -381,47 +433,104 @@ impl<'a> Field<'a> for Duration {
     }
 }
 
+/// Size of the canonical `IpAddr` representation: the header followed by the octets
+/// of the address, padded to the size of an IPv6 address.
+const IP_ADDR_SIZE: usize = SOCKET_ADDR_HEADER_SIZE + IPV6_SIZE;
+
+unsafe fn read_ip_addr(buffer: &[u8], from: usize) -> IpAddr {
+    let addr_start = from + SOCKET_ADDR_HEADER_SIZE;
+    match buffer[from] {
+        IPV4_HEADER => {
+            let mut octets: [u8; IPV4_SIZE] = mem::uninitialized();
+            octets.copy_from_slice(&buffer[addr_start..addr_start + IPV4_SIZE]);
+            IpAddr::V4(Ipv4Addr::from(octets))
+        }
+        IPV6_HEADER => {
+            let mut octets: [u8; IPV6_SIZE] = mem::uninitialized();
+            octets.copy_from_slice(&buffer[addr_start..addr_start + IPV6_SIZE]);
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        header => panic!("Unknown header `{:X}` for IpAddr", header),
+    }
+}
+
+fn write_ip_addr(ip: &IpAddr, buffer: &mut [u8], from: usize) {
+    let addr_start = from + SOCKET_ADDR_HEADER_SIZE;
+    match *ip {
+        IpAddr::V4(ref ip) => {
+            buffer[from] = IPV4_HEADER;
+            buffer[addr_start..addr_start + IPV4_SIZE].copy_from_slice(&ip.octets());
+            // Padding.
+            buffer[addr_start + IPV4_SIZE..addr_start + IPV6_SIZE]
+                .copy_from_slice(&[0_u8; SIZE_DIFF]);
+        }
+        IpAddr::V6(ref ip) => {
+            buffer[from] = IPV6_HEADER;
+            buffer[addr_start..addr_start + IPV6_SIZE].copy_from_slice(&ip.octets());
+        }
+    }
+}
+
+fn check_ip_addr(buffer: &[u8], from: usize) -> StdResult<(), Error> {
+    let header = buffer[from];
+    if header != IPV4_HEADER && header != IPV6_HEADER {
+        return Err(Error::IncorrectSocketAddrHeader {
+            position: from as Offset,
+            value: header,
+        });
+    }
+
+    let padding_start = from + SOCKET_ADDR_HEADER_SIZE + IPV4_SIZE;
+    let padding = &buffer[padding_start..padding_start + SIZE_DIFF];
+    if header == IPV4_HEADER && padding != [0_u8; SIZE_DIFF] {
+        let mut value = [0_u8; SIZE_DIFF];
+        value.copy_from_slice(padding);
+        return Err(Error::IncorrectSocketAddrPadding {
+            position: padding_start as Offset,
+            value,
+        });
+    }
+    Ok(())
+}
+
+impl<'a> Field<'a> for IpAddr {
+    fn field_size() -> Offset {
+        IP_ADDR_SIZE as Offset
+    }
+
+    unsafe fn read(buffer: &'a [u8], from: Offset, _to: Offset) -> Self {
+        read_ip_addr(buffer, from as usize)
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>, from: Offset, _to: Offset) {
+        write_ip_addr(self, buffer, from as usize);
+    }
+
+    fn check(
+        buffer: &'a [u8],
+        from: CheckedOffset,
+        to: CheckedOffset,
+        latest_segment: CheckedOffset,
+    ) -> Result {
+        debug_assert_eq!((to - from)?.unchecked_offset(), Self::field_size());
+        check_ip_addr(buffer, from.unchecked_offset() as usize)?;
+        Ok(latest_segment)
+    }
+}
+
 impl<'a> Field<'a> for SocketAddr {
     fn field_size() -> Offset {
-        (SOCKET_ADDR_HEADER_SIZE + IPV6_SIZE + PORT_SIZE) as Offset
+        (IP_ADDR_SIZE + PORT_SIZE) as Offset
     }
 
     unsafe fn read(buffer: &'a [u8], from: Offset, to: Offset) -> Self {
-        let addr_start = from as usize + SOCKET_ADDR_HEADER_SIZE;
-        let ip = match buffer[from as usize] {
-            IPV4_HEADER => {
-                let mut octets: [u8; IPV4_SIZE] = mem::uninitialized();
-                octets.copy_from_slice(&buffer[addr_start..addr_start + IPV4_SIZE]);
-                IpAddr::V4(Ipv4Addr::from(octets))
-            }
-            IPV6_HEADER => {
-                let mut octets: [u8; IPV6_SIZE] = mem::uninitialized();
-                octets.copy_from_slice(&buffer[addr_start..addr_start + IPV6_SIZE]);
-                IpAddr::V6(Ipv6Addr::from(octets))
-            }
-            header => panic!("Unknown header `{:X}` for SocketAddr", header),
-        };
+        let ip = read_ip_addr(buffer, from as usize);
         let port = LittleEndian::read_u16(&buffer[to as usize - PORT_SIZE..to as usize]);
         Self::new(ip, port)
     }
 
     fn write(&self, buffer: &mut Vec<u8>, from: Offset, to: Offset) {
-        match *self {
-            SocketAddr::V4(ref addr) => {
-                buffer[from as usize] = IPV4_HEADER;
-                buffer
-                    [from as usize + SOCKET_ADDR_HEADER_SIZE..to as usize - SIZE_DIFF - PORT_SIZE]
-                    .copy_from_slice(&addr.ip().octets());
-                // Padding.
-                buffer[to as usize - SIZE_DIFF - PORT_SIZE..to as usize - PORT_SIZE]
-                    .copy_from_slice(&[0_u8; SIZE_DIFF]);
-            }
-            SocketAddr::V6(ref addr) => {
-                buffer[from as usize] = IPV6_HEADER;
-                buffer[from as usize + SOCKET_ADDR_HEADER_SIZE..to as usize - PORT_SIZE]
-                    .copy_from_slice(&addr.ip().octets());
-            }
-        }
+        write_ip_addr(&self.ip(), buffer, from as usize);
         LittleEndian::write_u16(
             &mut buffer[to as usize - PORT_SIZE..to as usize],
             self.port(),
@@ -435,28 +544,7 @@ impl<'a> Field<'a> for SocketAddr {
         latest_segment: CheckedOffset,
     ) -> Result {
         debug_assert_eq!((to - from)?.unchecked_offset(), Self::field_size());
-
-        let from_unchecked = from.unchecked_offset() as usize;
-        let to_unchecked = to.unchecked_offset() as usize;
-
-        if buffer[from_unchecked] != IPV4_HEADER && buffer[from_unchecked] != IPV6_HEADER {
-            return Err(Error::IncorrectSocketAddrHeader {
-                position: from.unchecked_offset(),
-                value: buffer[from_unchecked],
-            });
-        }
-
-        if buffer[from_unchecked] == IPV4_HEADER
-            && buffer[to_unchecked - SIZE_DIFF - PORT_SIZE..to_unchecked - PORT_SIZE]
-                != [0_u8; SIZE_DIFF]
-        {
-            let mut value: [u8; SIZE_DIFF] = unsafe { mem::uninitialized() };
-            value.copy_from_slice(&buffer[to_unchecked - SIZE_DIFF..to_unchecked]);
-            return Err(Error::IncorrectSocketAddrPadding {
-                position: (to_unchecked - SIZE_DIFF) as Offset,
-                value,
-            });
-        }
+        check_ip_addr(buffer, from.unchecked_offset() as usize)?;
         Ok(latest_segment)
     }
 }
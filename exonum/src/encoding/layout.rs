@@ -0,0 +1,186 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Machine-readable description of the binary layout of the `encoding_struct!` types.
+//!
+//! The layout of the core structures can be compared with a committed snapshot
+//! to detect unintentional changes of the wire format.
+
+use failure;
+
+use super::Offset;
+use blockchain::{Block, TxLocation};
+use messages::{
+    BlockRequest, BlockResponse, Connect, PeersRequest, Ping, Pong, Precommit, Prevote,
+    PrevotesRequest, Propose, ProposeRequest, ProtocolMessage, Status, TransactionsRequest,
+    TransactionsResponse,
+};
+
+/// Layout of a single field in the header of a struct.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldLayout {
+    /// Name of the field.
+    pub name: String,
+    /// Start of the field in the header.
+    pub from: Offset,
+    /// End of the field in the header.
+    pub to: Offset,
+    /// `true` if the header contains only a pointer to the segment with the field data.
+    pub segment: bool,
+}
+
+/// Layout of a struct defined with the `encoding_struct!` macro.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructLayout {
+    /// Name of the struct.
+    pub name: String,
+    /// Class and type identifiers if the struct is a payload of a protocol message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_type: Option<(u8, u8)>,
+    /// Size of the struct header.
+    pub header_size: Offset,
+    /// Layouts of the struct fields.
+    pub fields: Vec<FieldLayout>,
+}
+
+impl StructLayout {
+    /// Creates a layout of the struct which is not a protocol message.
+    pub fn new(name: &str, header_size: Offset, fields: Vec<FieldLayout>) -> Self {
+        Self {
+            name: name.to_owned(),
+            message_type: None,
+            header_size,
+            fields,
+        }
+    }
+}
+
+/// Describes the binary layout of a type. Implemented by the `encoding_struct!` macro.
+pub trait DescribeLayout {
+    /// Returns the layout of the type.
+    fn describe_layout() -> StructLayout;
+}
+
+fn message_layout<T: ProtocolMessage + DescribeLayout>() -> StructLayout {
+    StructLayout {
+        message_type: Some(T::message_type()),
+        ..T::describe_layout()
+    }
+}
+
+/// Returns layouts of the protocol messages and the core structures stored in the blockchain.
+pub fn core_layouts() -> Vec<StructLayout> {
+    vec![
+        message_layout::<Connect>(),
+        message_layout::<Status>(),
+        message_layout::<Precommit>(),
+        message_layout::<Propose>(),
+        message_layout::<Prevote>(),
+        message_layout::<TransactionsResponse>(),
+        message_layout::<BlockResponse>(),
+        message_layout::<Pong>(),
+        message_layout::<ProposeRequest>(),
+        message_layout::<TransactionsRequest>(),
+        message_layout::<PrevotesRequest>(),
+        message_layout::<PeersRequest>(),
+        message_layout::<BlockRequest>(),
+        message_layout::<Ping>(),
+        Block::describe_layout(),
+        TxLocation::describe_layout(),
+    ]
+}
+
+/// Compares the `actual` layouts with the `expected` ones.
+///
+/// Returns an error listing all the structs whose layout differs.
+pub fn check_layouts(
+    expected: &[StructLayout],
+    actual: &[StructLayout],
+) -> Result<(), failure::Error> {
+    let mut errors = Vec::new();
+    for layout in expected {
+        match actual.iter().find(|other| other.name == layout.name) {
+            None => errors.push(format!("`{}` is removed", layout.name)),
+            Some(other) if other != layout => errors.push(format!(
+                "`{}` is changed: expected {:?}, found {:?}",
+                layout.name, layout, other
+            )),
+            _ => {}
+        }
+    }
+    for layout in actual {
+        if !expected.iter().any(|other| other.name == layout.name) {
+            errors.push(format!("`{}` is added", layout.name));
+        }
+    }
+
+    ensure!(
+        errors.is_empty(),
+        "Binary format has changed:\n{}",
+        errors.join("\n")
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_layout() {
+        let layout = message_layout::<Status>();
+        assert_eq!(layout.name, "Status");
+        assert_eq!(layout.message_type, Some((0, 2)));
+        assert_eq!(layout.header_size, 40);
+        assert_eq!(
+            layout.fields,
+            vec![
+                FieldLayout {
+                    name: "height".to_owned(),
+                    from: 0,
+                    to: 8,
+                    segment: false,
+                },
+                FieldLayout {
+                    name: "last_hash".to_owned(),
+                    from: 8,
+                    to: 40,
+                    segment: false,
+                },
+            ]
+        );
+
+        let layout = TransactionsRequest::describe_layout();
+        assert_eq!(layout.message_type, None);
+        assert!(layout.fields[1].segment);
+    }
+
+    #[test]
+    fn test_check_layouts() {
+        let expected = core_layouts();
+        assert!(check_layouts(&expected, &expected).is_ok());
+
+        let mut actual = expected.clone();
+        actual[0].fields[0].segment = true;
+        actual.pop();
+        let err = check_layouts(&expected, &actual).unwrap_err().to_string();
+        assert!(err.contains("`Connect` is changed"));
+        assert!(err.contains("`TxLocation` is removed"));
+
+        let err = check_layouts(&expected[1..], &expected)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("`Connect` is added"));
+    }
+}
@@ -78,6 +78,9 @@
 //! | `i32`    | 4    | 32-bit signed integer stored in little endian  |
 //! | `u64`    | 8    | Long unsigned integer stored in little endian  |
 //! | `i64`    | 8    | Long signed integer stored in little endian  |
+//! | `u128`   | 16   | 128-bit unsigned integer stored in little endian  |
+//! | `i128`   | 16   | 128-bit signed integer stored in little endian  |
+//! | `BigDecimal` | 17 | 128-bit signed mantissa stored in little endian followed by the scale byte \[4\] |
 //! | `F32`    | 4    | 32-bit floating point type stored in little endian \[1\]\[2\] |
 //! | `F64`    | 8    | 64-bit floating point type stored in little endian \[1\]\[2\] |
 //! | `bool`   | 1    | Stored as a byte, with `0x01` denoting true and `0x00` false \[3\] |
@@ -92,6 +95,9 @@
 //! \[3\]
 //! Trying to represent other values as `bool` leads to undefined behavior.
 //!
+//! \[4\]
+//! `BigDecimal` is hidden behind the `big_decimal` feature gate.
+//!
 //! ## Segment fields
 //!
 //! All segment types take 8 bytes in the header: 4 for position in the buffer,
@@ -105,9 +111,16 @@
 //!
 //! [`field_size()`]: ./trait.Field.html#tymethod.field_size
 
+#[cfg(feature = "big_decimal")]
+pub use self::big_decimal::BigDecimal;
 #[cfg(feature = "float_serialize")]
 pub use self::float::{F32, F64};
-pub use self::{error::Error, fields::Field, segments::SegmentField};
+pub use self::{
+    error::Error,
+    fields::Field,
+    layout::{check_layouts, core_layouts, DescribeLayout, FieldLayout, StructLayout},
+    segments::SegmentField,
+};
 
 #[macro_use]
 pub mod serialize;
@@ -117,9 +130,12 @@ use std::{
     ops::{Add, Div, Mul, Sub},
 };
 
+#[cfg(feature = "big_decimal")]
+mod big_decimal;
 mod error;
 #[macro_use]
 mod fields;
+mod layout;
 mod segments;
 #[macro_use]
 mod spec;
@@ -109,6 +109,10 @@ where
 
         Self::check_data(buffer, segment_start, count, latest_segment)
     }
+
+    fn is_segment() -> bool {
+        true
+    }
 }
 
 impl<'a> SegmentField<'a> for &'a str {
@@ -13,18 +13,21 @@
 // limitations under the License.
 
 use bit_vec::BitVec;
-use chrono::{DateTime, Duration, TimeZone, Utc};
+use chrono::{DateTime, Duration, Utc};
 use hex::FromHex;
 use rust_decimal::Decimal;
 /// trait `ExonumSerializeJson` implemented for all field that allows serializing in json format.
 use serde_json::{self, value::Value};
 use uuid::Uuid;
 
-use std::{error::Error, net::SocketAddr};
+use std::{
+    error::Error,
+    net::{IpAddr, SocketAddr},
+};
 
 use super::WriteBufferWrapper;
 use crypto::{Hash, PublicKey, Signature};
-use encoding::{Field, Offset};
+use encoding::{fields::date_time_from_timestamp, Field, Offset};
 use helpers::{Height, Round, ValidatorId};
 
 // TODO: Should we implement serialize for: `SecretKey`, `Seed`. (ECR-156)
@@ -128,8 +131,22 @@ macro_rules! impl_deserialize_bigint {
     ($($name:ty);*) => ($(impl_deserialize_bigint!{@impl $name})*);
 }
 
-macro_rules! impl_deserialize_hex_segment {
+// 128-bit integers are not supported by `serde_json::Value`, so they are parsed
+// from strings directly.
+macro_rules! impl_deserialize_owned_bigint {
     (@impl $typename:ty) => {
+        impl ExonumJsonDeserialize for $typename {
+            fn deserialize(value: &Value) -> Result<Self, Box<dyn Error>> {
+                let string = value.as_str().ok_or("Can't cast json as string")?;
+                Ok(string.parse()?)
+            }
+        }
+    };
+    ($($name:ty);*) => ($(impl_deserialize_owned_bigint!{@impl $name})*);
+}
+
+macro_rules! impl_deserialize_hex_segment {
+    (@impl $typename:ty, $($parse:tt)+) => {
         impl<'a> ExonumJson for &'a $typename {
             fn deserialize_field<B: WriteBufferWrapper>(value: &Value,
                                                         buffer: & mut B,
@@ -138,7 +155,7 @@ macro_rules! impl_deserialize_hex_segment {
                 -> Result<(), Box<dyn Error>>
             {
                 let string = value.as_str().ok_or("Can't cast json as string")?;
-                let val = <$typename as FromHex>:: from_hex(string)?;
+                let val: $typename = $($parse)+(string)?;
                 buffer.write(from, to, &val);
                 Ok(())
             }
@@ -149,14 +166,21 @@ macro_rules! impl_deserialize_hex_segment {
             }
         }
     };
-    ($($name:ty);*) => ($(impl_deserialize_hex_segment!{@impl $name})*);
+    // Types implementing `FromStr` may accept representations other than hex.
+    (FromStr: $($name:ty);*) => (
+        $(impl_deserialize_hex_segment!{@impl $name, <$name as ::std::str::FromStr>::from_str})*
+    );
+    ($($name:ty);*) => ($(impl_deserialize_hex_segment!{@impl $name, <$name as FromHex>::from_hex})*);
 }
 
 impl_deserialize_int!{u8; u16; u32; i8; i16; i32}
-impl_deserialize_bigint!{u64; i64}
-impl_deserialize_hex_segment!{Hash; PublicKey; Signature}
+impl_deserialize_bigint!{u64; i64; u128; i128}
+impl_deserialize_hex_segment!{FromStr: Hash; PublicKey}
+impl_deserialize_hex_segment!{Signature}
 impl_default_deserialize_owned!{u8; u16; u32; i8; i16; i32; u64; i64}
+impl_deserialize_owned_bigint!{u128; i128}
 impl_default_deserialize_owned!{Hash; PublicKey; Signature; bool}
+impl_default_deserialize_owned!{SocketAddr; IpAddr}
 
 impl ExonumJson for bool {
     fn deserialize_field<B: WriteBufferWrapper>(
@@ -199,9 +223,7 @@ impl ExonumJson for DateTime<Utc> {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<dyn Error>> {
-        let helper: TimestampHelper = serde_json::from_value(value.clone())?;
-        let date_time = Utc.timestamp(helper.secs.parse()?, helper.nanos);
-        buffer.write(from, to, date_time);
+        buffer.write(from, to, Self::deserialize(value)?);
         Ok(())
     }
 
@@ -214,6 +236,20 @@ impl ExonumJson for DateTime<Utc> {
     }
 }
 
+impl ExonumJsonDeserialize for DateTime<Utc> {
+    fn deserialize(value: &Value) -> Result<Self, Box<dyn Error>> {
+        let helper: TimestampHelper = serde_json::from_value(value.clone())?;
+        let secs = helper.secs.parse()?;
+        let date_time = date_time_from_timestamp(secs, helper.nanos).ok_or_else(|| {
+            format!(
+                "Can't deserialize DateTime: {} secs, {} nanos",
+                secs, helper.nanos
+            )
+        })?;
+        Ok(date_time)
+    }
+}
+
 impl ExonumJson for Duration {
     fn deserialize_field<B: WriteBufferWrapper>(
         value: &Value,
@@ -271,6 +307,23 @@ impl ExonumJson for SocketAddr {
     }
 }
 
+impl ExonumJson for IpAddr {
+    fn deserialize_field<B: WriteBufferWrapper>(
+        value: &Value,
+        buffer: &mut B,
+        from: Offset,
+        to: Offset,
+    ) -> Result<(), Box<dyn Error>> {
+        let addr: Self = serde_json::from_value(value.clone())?;
+        buffer.write(from, to, addr);
+        Ok(())
+    }
+
+    fn serialize_field(&self) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        Ok(serde_json::to_value(&self)?)
+    }
+}
+
 impl<'a> ExonumJson for &'a [Hash] {
     fn deserialize_field<B: WriteBufferWrapper>(
         value: &Value,
@@ -544,8 +597,33 @@ mod tests {
     #![allow(unsafe_code)]
 
     use super::*;
+    use crypto::{self, Address};
     use encoding::CheckedOffset;
 
+    #[test]
+    fn exonum_json_for_checksummed_public_key() {
+        let (public_key, _) = crypto::gen_keypair();
+        let mut buffer = vec![0; <&PublicKey>::field_size() as usize];
+        let to = <&PublicKey>::field_size();
+
+        let address = Value::String(public_key.to_address());
+        <&PublicKey>::deserialize_field(&address, &mut buffer, 0, to).unwrap();
+        assert_eq!(unsafe { <&PublicKey>::read(&buffer, 0, to) }, &public_key);
+        // Keys are still serialized as hex.
+        assert_eq!(
+            (&public_key).serialize_field().unwrap(),
+            Value::String(public_key.to_hex())
+        );
+
+        // A typo in the address is detected.
+        let mut typo = public_key.to_address();
+        let last = if typo.ends_with('q') { "p" } else { "q" };
+        typo.pop();
+        typo.push_str(last);
+        let typo = Value::String(typo);
+        assert!(<&PublicKey>::deserialize_field(&typo, &mut buffer, 0, to).is_err());
+    }
+
     #[test]
     fn exonum_json_for_duration_round_trip() {
         let durations = [
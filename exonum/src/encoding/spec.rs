@@ -133,6 +133,10 @@ macro_rules! encoding_struct {
 
                 8 as $crate::encoding::Offset
             }
+
+            fn is_segment() -> bool {
+                true
+            }
         }
 
         impl $crate::crypto::CryptoHash for $name {
@@ -178,6 +182,22 @@ macro_rules! encoding_struct {
             }
         }
 
+        impl $crate::encoding::DescribeLayout for $name {
+            #[allow(unused_mut)]
+            fn describe_layout() -> $crate::encoding::StructLayout {
+                let mut fields = Vec::new();
+                __ex_for_each_field!(
+                    __ex_struct_describe_field, (fields),
+                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
+                );
+                $crate::encoding::StructLayout::new(
+                    stringify!($name),
+                    $name::__ex_header_size(),
+                    fields,
+                )
+            }
+        }
+
         impl ::std::fmt::Debug for $name {
             fn fmt(&self, fmt: &mut ::std::fmt::Formatter)
                 -> Result<(), ::std::fmt::Error> {
@@ -445,6 +465,26 @@ macro_rules! __ex_struct_mk_field {
     }
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ex_struct_describe_field {
+    (
+        ($fields:ident),
+        $(#[$field_attr:meta])*,
+        $field_name:ident,
+        $field_type:ty,
+        $from:expr,
+        $to:expr
+    ) => {
+        $fields.push($crate::encoding::FieldLayout {
+            name: stringify!($field_name).to_owned(),
+            from: $from,
+            to: $to,
+            segment: <$field_type as $crate::encoding::Field>::is_segment(),
+        });
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __ex_deserialize_field {
@@ -16,15 +16,25 @@
 
 use bit_vec::BitVec;
 use byteorder::{ByteOrder, LittleEndian};
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use proptest::{
+    array::{uniform16, uniform32},
+    collection::vec,
+    option,
+    prelude::*,
+};
 use rust_decimal::Decimal;
 use uuid::Uuid;
 
-use std::str::FromStr;
+use std::{
+    fmt::Debug,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    str::FromStr,
+};
 
 use super::{CheckedOffset, Field, Offset};
 use blockchain::Block;
-use crypto::{gen_keypair, hash};
+use crypto::{gen_keypair, hash, Hash, PublicKey};
 use helpers::{user_agent, Height, Round, ValidatorId};
 use messages::{
     BlockRequest, BlockResponse, Connect, Message, Precommit, Prevote, Propose, Status,
@@ -340,6 +350,7 @@ fn test_segments_of_status_messages() {
 }
 
 fn test_connect(addr: &str) {
+    let addr: SocketAddr = addr.parse().unwrap();
     let time = Utc::now();
     let (public_key, secret_key) = gen_keypair();
 
@@ -362,6 +373,54 @@ fn test_connect_ipv6() {
     test_connect("[::1]:7777");
 }
 
+#[test]
+fn test_ip_addr_segment() {
+    let addrs: [IpAddr; 3] = [
+        Ipv4Addr::new(18, 34, 3, 4).into(),
+        Ipv6Addr::LOCALHOST.into(),
+        Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1).into(),
+    ];
+    for addr in &addrs {
+        assert_write_check_read(*addr, 17);
+    }
+}
+
+#[test]
+fn test_check_invalid_ip_addr() {
+    use encoding::Error;
+
+    let mut buffer = vec![0; 17];
+    Field::write(&IpAddr::from(Ipv4Addr::LOCALHOST), &mut buffer, 0, 17);
+
+    let mut invalid_header = buffer.clone();
+    invalid_header[0] = 2;
+    match <IpAddr as Field>::check(&invalid_header, 0.into(), 17.into(), 17.into()) {
+        Err(Error::IncorrectSocketAddrHeader { position, value }) => {
+            assert_eq!((position, value), (0, 2))
+        }
+        other => panic!("Unexpected check result: {:?}", other),
+    }
+
+    let mut invalid_padding = buffer.clone();
+    invalid_padding[16] = 1;
+    match <IpAddr as Field>::check(&invalid_padding, 0.into(), 17.into(), 17.into()) {
+        Err(Error::IncorrectSocketAddrPadding { position, value }) => {
+            assert_eq!(position, 5);
+            assert_eq!(value[11], 1);
+        }
+        other => panic!("Unexpected check result: {:?}", other),
+    }
+
+    // The port of a socket address is not a part of the padding.
+    let addr = SocketAddr::from(([127, 0, 0, 1], 7777));
+    let mut socket_addr = vec![0; 19];
+    Field::write(&addr, &mut socket_addr, 0, 19);
+    socket_addr[18] = 0xff;
+    assert!(<SocketAddr as Field>::check(&socket_addr, 0.into(), 19.into(), 19.into()).is_ok());
+    socket_addr[6] = 1;
+    assert!(<SocketAddr as Field>::check(&socket_addr, 0.into(), 19.into(), 19.into()).is_err());
+}
+
 #[test]
 fn test_propose() {
     let prev_hash = hash(&[1, 2, 3]);
@@ -541,3 +600,410 @@ fn test_option_serialization_roundtrip() {
         assert_eq!(*x, foo);
     }
 }
+
+#[test]
+fn test_date_time_segment() {
+    // Size of date time is sizeof(i64) + sizeof(u32).
+    let header_size = 12;
+
+    let times = [
+        Utc.timestamp(0, 0),
+        Utc.timestamp(1_500_000_000, 999_999_999),
+        Utc.timestamp(-1, 1),
+        Utc.timestamp(-100_000_000_000, 0),
+        // Leap second in the last second of a minute.
+        Utc.timestamp(1_483_228_799, 1_500_000_000),
+        Utc.timestamp(-1, 1_000_000_000),
+    ];
+    for time in &times {
+        assert_write_check_read(*time, header_size);
+    }
+}
+
+#[test]
+fn test_check_invalid_date_time() {
+    // Nanoseconds exceed the range allowed even for leap seconds.
+    expect_date_time_check_error(0, 2_000_000_000);
+    expect_date_time_check_error(0, u32::max_value());
+    // Leap seconds are only allowed in the last second of a minute.
+    expect_date_time_check_error(0, 1_000_000_000);
+    expect_date_time_check_error(-2, 1_000_000_000);
+    // Seconds are out of the range supported by `DateTime<Utc>`.
+    expect_date_time_check_error(i64::max_value(), 0);
+    expect_date_time_check_error(i64::min_value(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Got DateTime object with incorrect representation")]
+fn test_write_invalid_date_time() {
+    // Leap second outside of the last second of a minute.
+    let time = Utc.timestamp(0, 1_000_000_000);
+    let mut buffer = vec![0; 12];
+    <DateTime<Utc> as Field>::write(&time, &mut buffer, 0, 12);
+}
+
+fn expect_date_time_check_error(secs: i64, nanos: u32) {
+    // Size of date time is sizeof(i64) + sizeof(u32).
+    let header_size = 12;
+
+    let mut raw_time: Vec<u8> = vec![0; header_size];
+
+    LittleEndian::write_i64(&mut raw_time[0..8], secs);
+    LittleEndian::write_u32(&mut raw_time[8..header_size], nanos);
+
+    let start_offset = CheckedOffset::new(0 as Offset);
+    let end_offset = CheckedOffset::new(header_size as Offset);
+    <DateTime<Utc> as Field>::check(&raw_time, start_offset, end_offset, end_offset)
+        .expect_err("Check should return IncorrectDateTime error for incorrect buffer");
+}
+
+#[test]
+fn test_date_time_json_roundtrip() {
+    use storage::StorageValue;
+
+    encoding_struct! {
+        struct Event {
+            time: DateTime<Utc>,
+            deadline: Option<DateTime<Utc>>,
+        }
+    }
+
+    let events = vec![
+        Event::new(Utc.timestamp(1_500_000_000, 15), None),
+        Event::new(
+            Utc.timestamp(-1, 999_999_999),
+            Some(Utc.timestamp(1_483_228_799, 1_000_000_001)),
+        ),
+    ];
+    for event in events {
+        let json = ::serde_json::to_value(&event).unwrap();
+        let restored: Event = ::serde_json::from_value(json).unwrap();
+        assert_eq!(restored, event);
+        // JSON must preserve the exact bytes used for signing and hashing.
+        assert_eq!(restored.into_bytes(), event.into_bytes());
+    }
+
+    let invalid = vec![
+        json!({"time": {"secs": "0", "nanos": 2_000_000_000}, "deadline": null}),
+        json!({"time": {"secs": "0", "nanos": 1_000_000_000}, "deadline": null}),
+        json!({"time": {"secs": "9223372036854775807", "nanos": 0}, "deadline": null}),
+        json!({
+            "time": {"secs": "0", "nanos": 0},
+            "deadline": {"secs": "1", "nanos": 1_000_000_000}
+        }),
+    ];
+    for json in invalid {
+        assert!(::serde_json::from_value::<Event>(json).is_err());
+    }
+}
+
+// Property-based tests checking write-check-read roundtrips for all `Field` implementations
+// and that `check` returns an error instead of panicking on corrupted buffers.
+
+#[allow(dead_code)]
+mod prop_structs {
+    use bit_vec::BitVec;
+    use chrono::{DateTime, Duration, Utc};
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    use std::net::SocketAddr;
+
+    use crypto::{Hash, PublicKey};
+    use helpers::{Height, Round, ValidatorId};
+
+    encoding_struct! {
+        struct Inner {
+            hash: &Hash,
+            name: &str,
+            flag: bool,
+            value: i64,
+        }
+    }
+
+    encoding_struct! {
+        struct Numbers {
+            a: u8,
+            b: i8,
+            c: u16,
+            d: i16,
+            e: u32,
+            f: i32,
+            g: u64,
+            height: Height,
+            round: Round,
+            validator: ValidatorId,
+        }
+    }
+
+    encoding_struct! {
+        struct Outer {
+            key: &PublicKey,
+            numbers: Numbers,
+            inner: Inner,
+            optional: Option<Inner>,
+            nested: Vec<Inner>,
+            bytes: &[u8],
+            hashes: &[Hash],
+            bits: BitVec,
+            time: DateTime<Utc>,
+            duration: Duration,
+            addr: SocketAddr,
+            uuid: Uuid,
+            decimal: Decimal,
+        }
+    }
+}
+
+use self::prop_structs::{Inner, Numbers, Outer};
+
+/// Byte substitutions applied to a serialized buffer.
+fn mutations() -> impl Strategy<Value = Vec<(usize, u8)>> {
+    vec((any::<usize>(), any::<u8>()), 1..16)
+}
+
+fn mutate(buffer: &mut [u8], mutations: &[(usize, u8)]) {
+    let len = buffer.len();
+    for &(index, value) in mutations {
+        buffer[index % len] = value;
+    }
+}
+
+fn assert_roundtrip<T>(input: T, mutations: &[(usize, u8)])
+where
+    T: for<'r> Field<'r> + PartialEq + Debug + Clone,
+{
+    let header_size = <T as Field>::field_size();
+    assert_write_check_read(input.clone(), header_size);
+
+    let mut buffer = vec![0; header_size as usize];
+    input.write(&mut buffer, 0, header_size);
+    mutate(&mut buffer, mutations);
+    // The result is not important, `check` must just not panic.
+    let _ = <T as Field>::check(&buffer, 0.into(), header_size.into(), header_size.into());
+}
+
+fn date_time() -> impl Strategy<Value = DateTime<Utc>> {
+    (-100_000_000_000_i64..100_000_000_000, 0..1_000_000_000_u32)
+        .prop_map(|(secs, nanos)| Utc.timestamp(secs, nanos))
+}
+
+fn duration() -> impl Strategy<Value = Duration> {
+    let secs = -1_000_000_000_000_i64..1_000_000_000_000;
+    (secs, 0..1_000_000_000_i64).prop_map(|(secs, nanos)| {
+        // Seconds and nanoseconds must have the same sign.
+        let nanos = if secs < 0 { -nanos } else { nanos };
+        Duration::seconds(secs) + Duration::nanoseconds(nanos)
+    })
+}
+
+fn socket_addr() -> impl Strategy<Value = SocketAddr> {
+    prop_oneof![
+        (any::<u32>(), any::<u16>())
+            .prop_map(|(ip, port)| SocketAddr::new(IpAddr::V4(Ipv4Addr::from(ip)), port)),
+        (uniform16(any::<u8>()), any::<u16>())
+            .prop_map(|(ip, port)| SocketAddr::new(IpAddr::V6(Ipv6Addr::from(ip)), port)),
+    ]
+}
+
+fn uuid() -> impl Strategy<Value = Uuid> {
+    uniform16(any::<u8>()).prop_map(|bytes| Uuid::from_slice(&bytes).unwrap())
+}
+
+fn decimal() -> impl Strategy<Value = Decimal> {
+    (any::<i64>(), 0..29_u32).prop_map(|(num, scale)| Decimal::new(num, scale))
+}
+
+fn bit_vec() -> impl Strategy<Value = BitVec> {
+    // `BitVec` is serialized by bytes, so its length must be a multiple of 8.
+    vec(any::<u8>(), 0..16).prop_map(|bytes| BitVec::from_bytes(&bytes))
+}
+
+fn hash_value() -> impl Strategy<Value = Hash> {
+    uniform32(any::<u8>()).prop_map(Hash::new)
+}
+
+fn inner() -> impl Strategy<Value = Inner> {
+    (hash_value(), "\\PC{0,16}", any::<bool>(), any::<i64>())
+        .prop_map(|(hash, name, flag, value)| Inner::new(&hash, &name, flag, value))
+}
+
+fn numbers() -> impl Strategy<Value = Numbers> {
+    (
+        any::<u8>(),
+        any::<i8>(),
+        any::<u16>(),
+        any::<i16>(),
+        any::<u32>(),
+        any::<i32>(),
+        any::<u64>(),
+        any::<u64>(),
+        any::<u32>(),
+        any::<u16>(),
+    )
+        .prop_map(|(a, b, c, d, e, f, g, height, round, validator)| {
+            Numbers::new(
+                a,
+                b,
+                c,
+                d,
+                e,
+                f,
+                g,
+                Height(height),
+                Round(round),
+                ValidatorId(validator),
+            )
+        })
+}
+
+fn outer() -> impl Strategy<Value = Outer> {
+    (
+        (
+            uniform32(any::<u8>()).prop_map(PublicKey::new),
+            numbers(),
+            inner(),
+            option::of(inner()),
+            vec(inner(), 0..4),
+            vec(any::<u8>(), 0..32),
+            vec(hash_value(), 0..4),
+        ),
+        (
+            bit_vec(),
+            date_time(),
+            duration(),
+            socket_addr(),
+            uuid(),
+            decimal(),
+        ),
+    )
+        .prop_map(
+            |(
+                (key, numbers, inner, optional, nested, bytes, hashes),
+                (bits, time, duration, addr, uuid, decimal),
+            )| {
+                Outer::new(
+                    &key, numbers, inner, optional, nested, &bytes, &hashes, bits, time, duration,
+                    addr, uuid, decimal,
+                )
+            },
+        )
+}
+
+proptest! {
+    #[test]
+    fn proptest_bool(value in any::<bool>(), ref mutations in mutations()) {
+        assert_roundtrip(value, mutations);
+    }
+
+    #[test]
+    fn proptest_integers(
+        a in any::<u8>(),
+        b in any::<i8>(),
+        c in any::<u16>(),
+        d in any::<i16>(),
+        e in any::<u32>(),
+        f in any::<i32>(),
+        g in any::<u64>(),
+        h in any::<i64>(),
+        ref mutations in mutations()
+    ) {
+        assert_roundtrip(a, mutations);
+        assert_roundtrip(b, mutations);
+        assert_roundtrip(c, mutations);
+        assert_roundtrip(d, mutations);
+        assert_roundtrip(e, mutations);
+        assert_roundtrip(f, mutations);
+        assert_roundtrip(g, mutations);
+        assert_roundtrip(h, mutations);
+    }
+
+    #[test]
+    fn proptest_typedefs(
+        height in any::<u64>(),
+        round in any::<u32>(),
+        validator in any::<u16>(),
+        ref mutations in mutations()
+    ) {
+        assert_roundtrip(Height(height), mutations);
+        assert_roundtrip(Round(round), mutations);
+        assert_roundtrip(ValidatorId(validator), mutations);
+    }
+
+    #[test]
+    fn proptest_date_time(value in date_time(), ref mutations in mutations()) {
+        assert_roundtrip(value, mutations);
+    }
+
+    #[test]
+    fn proptest_duration(value in duration(), ref mutations in mutations()) {
+        assert_roundtrip(value, mutations);
+    }
+
+    #[test]
+    fn proptest_socket_addr(value in socket_addr(), ref mutations in mutations()) {
+        assert_roundtrip(value, mutations);
+    }
+
+    #[test]
+    fn proptest_uuid(value in uuid(), ref mutations in mutations()) {
+        assert_roundtrip(value, mutations);
+    }
+
+    #[test]
+    fn proptest_decimal(value in decimal(), ref mutations in mutations()) {
+        assert_roundtrip(value, mutations);
+    }
+
+    #[test]
+    fn proptest_bit_vec(value in bit_vec(), ref mutations in mutations()) {
+        assert_roundtrip(value, mutations);
+    }
+
+    #[test]
+    fn proptest_segments(
+        ref bytes in vec(any::<u8>(), 0..32),
+        ref nested in vec(vec(any::<u8>(), 0..8), 0..8),
+        ref numbers in vec(any::<u32>(), 0..16),
+        optional in option::of(any::<u64>()),
+        ref mutations in mutations()
+    ) {
+        assert_roundtrip(bytes.clone(), mutations);
+        assert_roundtrip(nested.clone(), mutations);
+        assert_roundtrip(numbers.clone(), mutations);
+        assert_roundtrip(optional, mutations);
+    }
+
+    #[test]
+    fn proptest_str(ref value in "\\PC*", ref mutations in mutations()) {
+        let mut buffer = vec![0; 8];
+        Field::write(&value.as_str(), &mut buffer, 0, 8);
+        <&str as Field>::check(&buffer, 0.into(), 8.into(), 8.into()).unwrap();
+        let output: &str = unsafe { Field::read(&buffer, 0, 8) };
+        prop_assert_eq!(output, value.as_str());
+
+        mutate(&mut buffer, mutations);
+        let _ = <&str as Field>::check(&buffer, 0.into(), 8.into(), 8.into());
+    }
+
+    #[test]
+    fn proptest_hashes(ref value in vec(hash_value(), 0..8), ref mutations in mutations()) {
+        let mut buffer = vec![0; 8];
+        Field::write(&value.as_slice(), &mut buffer, 0, 8);
+        <&[Hash] as Field>::check(&buffer, 0.into(), 8.into(), 8.into()).unwrap();
+        let output: &[Hash] = unsafe { Field::read(&buffer, 0, 8) };
+        prop_assert_eq!(output, value.as_slice());
+
+        mutate(&mut buffer, mutations);
+        let _ = <&[Hash] as Field>::check(&buffer, 0.into(), 8.into(), 8.into());
+    }
+
+    #[test]
+    fn proptest_encoding_struct(value in outer(), ref mutations in mutations()) {
+        assert_roundtrip(value.clone(), mutations);
+        assert_roundtrip(value.inner(), mutations);
+        assert_roundtrip(value.numbers(), mutations);
+        assert_roundtrip(value.nested(), mutations);
+        assert_roundtrip(value.optional(), mutations);
+    }
+}
@@ -29,10 +29,14 @@ use futures::{
     Async, Future, Poll, Stream,
 };
 
-use std::{cmp::Ordering, time::SystemTime};
+use std::{
+    cmp::Ordering,
+    collections::VecDeque,
+    time::{Instant, SystemTime},
+};
 
 use helpers::{Height, Round};
-use messages::Message;
+use messages::{Message, Service};
 use node::{ExternalMessage, NodeTimeout};
 
 #[cfg(all(test, feature = "long_benchmarks"))]
@@ -145,8 +149,63 @@ impl Into<Event> for InternalEvent {
     }
 }
 
+/// Priority lane of the node event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventLane {
+    /// Consensus messages, timeouts and other events critical for the consensus progress.
+    Consensus,
+    /// Transactions received from the peers or the API.
+    Transactions,
+}
+
+impl EventLane {
+    fn index(self) -> usize {
+        match self {
+            EventLane::Consensus => 0,
+            EventLane::Transactions => 1,
+        }
+    }
+
+    fn depth_metric(self) -> &'static str {
+        match self {
+            EventLane::Consensus => "node.events_consensus_queue",
+            EventLane::Transactions => "node.events_transactions_queue",
+        }
+    }
+
+    fn wait_time_metric(self) -> &'static str {
+        match self {
+            EventLane::Consensus => "node.events_consensus_wait_us",
+            EventLane::Transactions => "node.events_transactions_wait_us",
+        }
+    }
+}
+
+impl Event {
+    /// Returns the priority lane of the event.
+    pub fn lane(&self) -> EventLane {
+        match self {
+            Event::Internal(InternalEvent::MessageVerified(Message::Service(
+                Service::RawTransaction(_),
+            )))
+            | Event::Api(ExternalMessage::Transaction(_)) => EventLane::Transactions,
+            _ => EventLane::Consensus,
+        }
+    }
+}
+
+/// Maximum number of consensus events processed in a row while there are pending transactions.
+const CONSENSUS_EVENTS_BURST: usize = 16;
+/// Maximum number of events buffered in the lanes of the aggregator.
+const MAX_BUFFERED_EVENTS: usize = 1024;
+
 /// Receives timeout, network and api events and invokes `handle_event` method of handler.
 /// If one of these streams closes, the aggregator stream completes immediately.
+///
+/// Received events are sorted into priority lanes, so that consensus messages are not starved
+/// by transactions. Up to `CONSENSUS_EVENTS_BURST` consensus events are yielded in a row
+/// if there are pending transactions, then a transaction is yielded to keep their processing
+/// going.
 #[derive(Debug)]
 pub struct EventsAggregator<S1, S2, S3>
 where
@@ -158,6 +217,8 @@ where
     internal: S1,
     network: S2,
     api: S3,
+    lanes: [VecDeque<(Event, Instant)>; 2],
+    consensus_streak: usize,
 }
 
 impl<S1, S2, S3> EventsAggregator<S1, S2, S3>
@@ -172,8 +233,74 @@ where
             network,
             internal,
             api,
+            lanes: [VecDeque::new(), VecDeque::new()],
+            consensus_streak: 0,
         }
     }
+
+    fn buffered(&self) -> usize {
+        self.lanes.iter().map(VecDeque::len).sum()
+    }
+
+    fn push(&mut self, event: Event) {
+        let lane = event.lane();
+        let queue = &mut self.lanes[lane.index()];
+        queue.push_back((event, Instant::now()));
+        metric!(lane.depth_metric(), queue.len());
+    }
+
+    fn pop(&mut self) -> Option<Event> {
+        let consensus_pending = !self.lanes[EventLane::Consensus.index()].is_empty();
+        let transactions_pending = !self.lanes[EventLane::Transactions.index()].is_empty();
+        let lane = if consensus_pending
+            && (!transactions_pending || self.consensus_streak < CONSENSUS_EVENTS_BURST)
+        {
+            self.consensus_streak += 1;
+            EventLane::Consensus
+        } else {
+            self.consensus_streak = 0;
+            EventLane::Transactions
+        };
+
+        let (event, received_at) = self.lanes[lane.index()].pop_front()?;
+        let wait_time = received_at.elapsed();
+        metric!(
+            lane.wait_time_metric(),
+            wait_time.as_secs() * 1_000_000 + u64::from(wait_time.subsec_micros())
+        );
+        Some(event)
+    }
+}
+
+impl<S1, S2, S3> EventsAggregator<S1, S2, S3>
+where
+    S1: Stream<Item = InternalEvent>,
+    S2: Stream<Item = NetworkEvent, Error = S1::Error>,
+    S3: Stream<Item = ExternalMessage, Error = S1::Error>,
+{
+    /// Moves ready events from the sources to the lanes. Returns `false` if one
+    /// of the sources is closed or the shutdown is requested.
+    fn receive(&mut self) -> Result<bool, S1::Error> {
+        while self.buffered() < MAX_BUFFERED_EVENTS {
+            let event = match self.internal.poll()? {
+                Async::Ready(None) | Async::Ready(Some(InternalEvent::Shutdown)) => {
+                    return Ok(false);
+                }
+                Async::Ready(Some(item)) => Event::Internal(item),
+                Async::NotReady => match self.network.poll()? {
+                    Async::Ready(Some(item)) => Event::Network(item),
+                    Async::Ready(None) => return Ok(false),
+                    Async::NotReady => match self.api.poll()? {
+                        Async::Ready(Some(item)) => Event::Api(item),
+                        Async::Ready(None) => return Ok(false),
+                        Async::NotReady => break,
+                    },
+                },
+            };
+            self.push(event);
+        }
+        Ok(true)
+    }
 }
 
 impl<S1, S2, S3> Stream for EventsAggregator<S1, S2, S3>
@@ -187,40 +314,16 @@ where
 
     fn poll(&mut self) -> Poll<Option<Event>, Self::Error> {
         if self.done {
-            Ok(Async::Ready(None))
-        } else {
-            match self.internal.poll()? {
-                Async::Ready(None) | Async::Ready(Some(InternalEvent::Shutdown)) => {
-                    self.done = true;
-                    return Ok(Async::Ready(None));
-                }
-                Async::Ready(Some(item)) => {
-                    return Ok(Async::Ready(Some(Event::Internal(item))));
-                }
-                Async::NotReady => {}
-            };
-            match self.network.poll()? {
-                Async::Ready(Some(item)) => {
-                    return Ok(Async::Ready(Some(Event::Network(item))));
-                }
-                Async::Ready(None) => {
-                    self.done = true;
-                    return Ok(Async::Ready(None));
-                }
-                Async::NotReady => {}
-            };
-            match self.api.poll()? {
-                Async::Ready(None) => {
-                    self.done = true;
-                    return Ok(Async::Ready(None));
-                }
-                Async::Ready(Some(item)) => {
-                    return Ok(Async::Ready(Some(Event::Api(item))));
-                }
-                Async::NotReady => {}
-            };
+            return Ok(Async::Ready(None));
+        }
+        if !self.receive()? {
+            self.done = true;
+            return Ok(Async::Ready(None));
+        }
 
-            Ok(Async::NotReady)
+        match self.pop() {
+            Some(event) => Ok(Async::Ready(Some(event))),
+            None => Ok(Async::NotReady),
         }
     }
 }
@@ -29,10 +29,12 @@ use events::{
     error::log_error,
     network::{NetworkConfiguration, NetworkPart},
     noise::HandshakeParams,
-    NetworkEvent, NetworkRequest,
+    Event, EventLane, EventsAggregator, InternalEvent, NetworkEvent, NetworkRequest,
 };
 use helpers::user_agent;
-use messages::{Connect, Message, Signed, SignedMessage};
+use messages::{
+    Connect, Message, RawTransaction, Service, ServiceTransaction, Signed, SignedMessage,
+};
 use node::{state::SharedConnectList, ConnectInfo, ConnectList, EventsPoolCapacity, NodeChannel};
 
 #[derive(Debug)]
@@ -197,7 +199,7 @@ pub fn connect_message(
 ) -> Signed<Connect> {
     let time = time::UNIX_EPOCH;
     Message::concrete(
-        Connect::new(&addr.to_string(), time.into(), &user_agent::get()),
+        Connect::new(addr, time.into(), &user_agent::get()),
         *public_key,
         secret_key,
     )
@@ -224,7 +226,7 @@ impl HandshakeParams {
     #[doc(hidden)]
     pub fn with_default_params() -> Self {
         let (public_key, secret_key) = gen_keypair_from_seed(&Seed::new([1; SEED_LENGTH]));
-        let address = "127.0.0.1:8000";
+        let address = "127.0.0.1:8000".parse().unwrap();
 
         let connect = Message::concrete(
             Connect::new(address, SystemTime::now().into(), &user_agent::get()),
@@ -594,3 +596,50 @@ fn test_connect_list_ignore_when_listening() {
     e1.wait_for_connect();
     e2.wait_for_connect();
 }
+
+#[test]
+fn test_events_aggregator_lanes() {
+    let (internal_tx, internal_rx) = mpsc::channel(32);
+    let (network_tx, network_rx) = mpsc::channel(32);
+    let (api_tx, api_rx) = mpsc::channel::<::node::ExternalMessage>(32);
+
+    let (public_key, secret_key) = gen_keypair();
+    let transaction = Message::concrete(
+        RawTransaction::new(0, ServiceTransaction::from_raw_unchecked(0, vec![])),
+        public_key,
+        &secret_key,
+    );
+    let verified_tx = || {
+        InternalEvent::MessageVerified(Message::Service(Service::RawTransaction(
+            transaction.clone(),
+        )))
+    };
+
+    let internal_tx = internal_tx
+        .send_all(::futures::stream::iter_ok((0..20).map(|_| verified_tx())))
+        .wait()
+        .unwrap()
+        .0;
+    let network_tx = network_tx
+        .send_all(::futures::stream::iter_ok(
+            (0..20).map(|_| NetworkEvent::MessageReceived(vec![])),
+        )).wait()
+        .unwrap()
+        .0;
+
+    let lanes = EventsAggregator::new(internal_rx, network_rx, api_rx)
+        .take(40)
+        .map(|event: Event| event.lane())
+        .collect()
+        .wait()
+        .unwrap();
+
+    // Consensus events are processed first, but transactions are not starved.
+    let mut expected = vec![EventLane::Consensus; 16];
+    expected.push(EventLane::Transactions);
+    expected.extend(vec![EventLane::Consensus; 4]);
+    expected.extend(vec![EventLane::Transactions; 19]);
+    assert_eq!(lanes, expected);
+
+    drop((internal_tx, network_tx, api_tx));
+}
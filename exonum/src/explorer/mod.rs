@@ -17,6 +17,9 @@
 //!
 //! See the `explorer` example in the crate for examples of usage.
 
+use byteorder::{BigEndian, ByteOrder};
+use failure;
+use hex;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use std::{
@@ -25,6 +28,7 @@ use std::{
     fmt,
     ops::{Index, Range, RangeFrom, RangeFull, RangeTo},
     slice,
+    str::FromStr,
 };
 
 use blockchain::{
@@ -33,7 +37,7 @@ use blockchain::{
 };
 use crypto::{CryptoHash, Hash};
 use encoding;
-use helpers::Height;
+use helpers::{Height, ValidatorId};
 use messages::{Precommit, RawTransaction, Signed};
 use storage::{ListProof, Snapshot};
 
@@ -91,6 +95,85 @@ impl HeightRange {
     }
 }
 
+/// Opaque position in the blockchain used to page through explorer listings.
+///
+/// The cursor encodes the height of a block and the zero-based index of an entry
+/// (e.g., a transaction) within that block. Committed blocks never change, so
+/// a listing resumed from a cursor neither skips nor repeats entries, regardless of the
+/// blocks committed in between the requests.
+///
+/// # JSON presentation
+///
+/// The cursor is serialized as an opaque hex string. Clients should not rely on its format
+/// and should only pass the values obtained from the previous responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cursor {
+    /// Height of the block.
+    pub height: Height,
+    /// Zero-based index of the entry within the block.
+    pub index: u64,
+}
+
+impl Cursor {
+    /// Length of the binary cursor representation.
+    const LEN: usize = 16;
+
+    /// Creates a cursor pointing to the given entry of the block at the given height.
+    pub fn new(height: Height, index: u64) -> Self {
+        Cursor { height, index }
+    }
+
+    /// Creates a cursor pointing to the block at the given height.
+    pub fn at_height(height: Height) -> Self {
+        Self::new(height, 0)
+    }
+}
+
+impl From<TxLocation> for Cursor {
+    fn from(location: TxLocation) -> Self {
+        Self::new(location.block_height(), location.position_in_block())
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut bytes = [0_u8; Self::LEN];
+        BigEndian::write_u64(&mut bytes[..8], self.height.0);
+        BigEndian::write_u64(&mut bytes[8..], self.index);
+        f.write_str(&hex::encode(&bytes[..]))
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s).map_err(|_| format_err!("Malformed cursor: {}", s))?;
+        if bytes.len() != Self::LEN {
+            bail!("Malformed cursor: {}", s);
+        }
+        Ok(Self::new(
+            Height(BigEndian::read_u64(&bytes[..8])),
+            BigEndian::read_u64(&bytes[8..]),
+        ))
+    }
+}
+
+impl Serialize for Cursor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Cursor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(D::Error::custom)
+    }
+}
+
 /// Information about a block in the blockchain.
 ///
 /// # JSON presentation
@@ -164,7 +247,7 @@ impl<'a> BlockInfo<'a> {
         self.len() == 0
     }
 
-    /// Returns a list of precommits for this block.
+    /// Returns a list of precommits for this block. The list is empty if the block is pruned.
     pub fn precommits(&self) -> Ref<[Signed<Precommit>]> {
         if self.precommits.borrow().is_none() {
             let precommits = self.explorer.precommits(&self.header);
@@ -186,14 +269,33 @@ impl<'a> BlockInfo<'a> {
         Ref::map(self.txs.borrow(), |cache| cache.as_ref().unwrap().as_ref())
     }
 
-    /// Returns a transaction with the specified index in the block.
+    /// Returns a transaction with the specified index in the block, or `None` if there is
+    /// no such transaction or the block is pruned.
     pub fn transaction(&self, index: usize) -> Option<CommittedTransaction> {
+        if self.explorer.is_pruned(self.height()) {
+            return None;
+        }
         self.transaction_hashes()
             .get(index)
             .map(|hash| self.explorer.committed_transaction(hash, None))
     }
 
-    /// Iterates over transactions in the block.
+    /// Returns a single proof of inclusion of the hashes of the transactions with indexes
+    /// in `from..to` into this block. The proof is more compact than the separate proofs
+    /// of the individual transactions and is checked against the `tx_hash` of the block header.
+    /// The proof is available even if the block is pruned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is empty or out of bounds.
+    pub fn transaction_range_proof(&self, from: u64, to: u64) -> ListProof<Hash> {
+        let schema = Schema::new(&self.explorer.snapshot);
+        schema
+            .block_transactions(self.height())
+            .get_range_proof(from, to)
+    }
+
+    /// Iterates over transactions in the block. The iterator is empty if the block is pruned.
     pub fn iter(&self) -> Transactions {
         Transactions {
             block: self,
@@ -203,6 +305,10 @@ impl<'a> BlockInfo<'a> {
     }
 
     /// Loads transactions and precommits for the block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block is pruned.
     pub fn with_transactions(self) -> BlockWithTransactions {
         let (explorer, header, precommits, transactions) =
             (self.explorer, self.header, self.precommits, self.txs);
@@ -435,19 +541,22 @@ pub struct CommittedTransaction {
     location_proof: ListProof<Hash>,
     #[serde(with = "TxStatus")]
     status: TransactionResult,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    emitted_by: Option<ValidatorId>,
 }
 
 /// Transaction execution status. Simplified version of `TransactionResult`.
 #[serde(tag = "type", rename_all = "kebab-case")]
 #[derive(Debug, Serialize, Deserialize)]
-enum TxStatus<'a> {
+pub(crate) enum TxStatus<'a> {
     Success,
     Panic { description: &'a str },
     Error { code: u8, description: &'a str },
+    UnmetDependency { description: &'a str },
 }
 
 impl<'a> TxStatus<'a> {
-    fn serialize<S>(result: &TransactionResult, serializer: S) -> Result<S::Ok, S::Error>
+    pub(crate) fn serialize<S>(result: &TransactionResult, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
@@ -455,7 +564,7 @@ impl<'a> TxStatus<'a> {
         status.serialize(serializer)
     }
 
-    fn deserialize<D>(deserializer: D) -> Result<TransactionResult, D::Error>
+    pub(crate) fn deserialize<D>(deserializer: D) -> Result<TransactionResult, D::Error>
     where
         D: Deserializer<'a>,
     {
@@ -475,6 +584,7 @@ impl<'a> From<&'a TransactionResult> for TxStatus<'a> {
                 match e.error_type() {
                     Panic => TxStatus::Panic { description },
                     Code(code) => TxStatus::Error { code, description },
+                    UnmetDependency => TxStatus::UnmetDependency { description },
                 }
             }
         }
@@ -497,6 +607,9 @@ impl<'a> From<TxStatus<'a>> for TransactionResult {
             TxStatus::Error { code, description } => {
                 Err(TransactionError::code(code, to_option(description)))
             }
+            TxStatus::UnmetDependency { description } => {
+                Err(TransactionError::unmet_dependency(to_option(description)))
+            }
         })
     }
 }
@@ -513,10 +626,22 @@ impl CommittedTransaction {
     }
 
     /// Returns a proof that transaction is recorded in the blockchain.
+    ///
+    /// The proof can be validated against the `tx_hash` and `tx_count` fields of the header
+    /// of the block at [`location().block_height()`](#method.location).
     pub fn location_proof(&self) -> &ListProof<Hash> {
         &self.location_proof
     }
 
+    /// Returns the identifier of the validator which has emitted the transaction, if the
+    /// transaction is signed by the service key of a validator.
+    /// See [`ServiceContext::emit_transaction`] for the details.
+    ///
+    /// [`ServiceContext::emit_transaction`]: ../blockchain/struct.ServiceContext.html#method.emit_transaction
+    pub fn emitted_by(&self) -> Option<ValidatorId> {
+        self.emitted_by
+    }
+
     /// Returns the status of the transaction execution.
     pub fn status(&self) -> Result<(), &TransactionError> {
         self.status.0.as_ref().map(|_| ())
@@ -660,6 +785,20 @@ impl<'a> fmt::Debug for BlockchainExplorer<'a> {
     }
 }
 
+impl BlockchainExplorer<'static> {
+    /// Creates a new `BlockchainExplorer` instance which owns a handle to the blockchain,
+    /// so that the explorer may be stored independently of the blockchain.
+    pub fn owned(blockchain: Blockchain) -> Self {
+        BlockchainExplorer {
+            snapshot: blockchain.snapshot(),
+            transaction_parser: Box::new(move |raw| {
+                let tx = blockchain.tx_from_raw(raw.payload().clone())?;
+                Ok(TransactionMessage::new(raw, tx))
+            }),
+        }
+    }
+}
+
 impl<'a> BlockchainExplorer<'a> {
     /// Creates a new `BlockchainExplorer` instance.
     pub fn new(blockchain: &'a Blockchain) -> Self {
@@ -672,6 +811,11 @@ impl<'a> BlockchainExplorer<'a> {
         }
     }
 
+    /// Returns the snapshot of the blockchain state wrapped by the explorer.
+    pub fn snapshot(&self) -> &dyn Snapshot {
+        &*self.snapshot
+    }
+
     /// Returns information about the transaction identified by the hash.
     pub fn transaction(&self, tx_hash: &Hash) -> Option<TransactionInfo> {
         let schema = Schema::new(&self.snapshot);
@@ -734,15 +878,24 @@ impl<'a> BlockchainExplorer<'a> {
         // Unwrap is OK here, because we already know that transaction is committed.
         let status = schema.transaction_results().get(tx_hash).unwrap();
 
-        CommittedTransaction {
-            content: maybe_content.unwrap_or_else(|| {
-                let raw_tx = schema.transactions().get(tx_hash).unwrap();
-                (self.transaction_parser)(raw_tx).unwrap()
-            }),
+        let content = maybe_content.unwrap_or_else(|| {
+            let raw_tx = schema.transactions().get(tx_hash).unwrap();
+            (self.transaction_parser)(raw_tx).unwrap()
+        });
+        let author = content.message().author();
+        let emitted_by = schema
+            .configuration_by_height(location.block_height())
+            .validator_keys
+            .iter()
+            .position(|keys| keys.service_key == author)
+            .map(|id| ValidatorId(id as u16));
 
+        CommittedTransaction {
+            content,
             location,
             location_proof,
             status,
+            emitted_by,
         }
     }
 
@@ -762,8 +915,11 @@ impl<'a> BlockchainExplorer<'a> {
     }
 
     /// Returns block together with its transactions for the specified height, or `None`
-    /// if there is no such block.
+    /// if there is no such block or the block is pruned.
     pub fn block_with_txs(&self, height: Height) -> Option<BlockWithTransactions> {
+        if self.is_pruned(height) {
+            return None;
+        }
         let schema = Schema::new(&self.snapshot);
         let txs_table = schema.block_transactions(height);
         let block_proof = schema.block_and_precommits(height);
@@ -778,6 +934,32 @@ impl<'a> BlockchainExplorer<'a> {
         })
     }
 
+    /// Returns the height below which the transaction bodies and the precommits of the blocks
+    /// are pruned. See [`PruningConfig`] for details.
+    ///
+    /// [`PruningConfig`]: ../node/struct.PruningConfig.html
+    pub fn pruned_height(&self) -> Height {
+        Schema::new(&self.snapshot).pruned_height()
+    }
+
+    /// Returns `true` if the transaction bodies and the precommits of the block
+    /// at the given height are pruned.
+    pub fn is_pruned(&self, height: Height) -> bool {
+        height < self.pruned_height()
+    }
+
+    /// Returns `true` if the transaction is committed in a pruned block, so that
+    /// its body is removed.
+    pub fn is_transaction_pruned(&self, tx_hash: &Hash) -> bool {
+        let schema = Schema::new(&self.snapshot);
+        schema
+            .transactions_locations()
+            .get(tx_hash)
+            .map_or(false, |location| {
+                location.block_height() < schema.pruned_height()
+            })
+    }
+
     /// Iterates over blocks in the blockchain.
     pub fn blocks<R: Into<HeightRange>>(&self, heights: R) -> Blocks {
         use std::cmp::max;
@@ -793,6 +975,34 @@ impl<'a> BlockchainExplorer<'a> {
             back: max(ptr, heights.end_height(max_height)),
         }
     }
+
+    /// Reads the latest `count` blocks together with their precommits and transactions,
+    /// so that the following queries of these blocks may be served from the database caches.
+    /// Nothing is cached by the explorer itself, so the effect of the prefetch depends
+    /// on the database and its cache size.
+    /// `progress` is invoked with the number of read blocks after reading each block.
+    ///
+    /// Returns the height of the earliest read block. Pruned blocks are not read.
+    pub fn prefetch<F: FnMut(u64)>(&self, count: u64, mut progress: F) -> Height {
+        let from = Height((self.height().0 + 1).saturating_sub(count));
+        let from = ::std::cmp::max(from, self.pruned_height());
+        for (loaded, block) in self.blocks(from..).enumerate() {
+            block.with_transactions();
+            progress(loaded as u64 + 1);
+        }
+        from
+    }
+}
+
+/// Progress of the prefetch phase reading the latest blocks on the node start.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PrefetchProgress {
+    /// The number of blocks to preload.
+    pub total_blocks: u64,
+    /// The number of already preloaded blocks.
+    pub loaded_blocks: u64,
+    /// `true` if the prefetch is finished, including the preloading of the service data.
+    pub completed: bool,
 }
 
 /// Iterator over blocks in the blockchain.
@@ -812,6 +1022,12 @@ impl<'a> fmt::Debug for Blocks<'a> {
     }
 }
 
+impl<'a> Blocks<'a> {
+    fn len(&self) -> usize {
+        self.ptr.distance_to(self.back).unwrap_or(0) as usize
+    }
+}
+
 impl<'a> Iterator for Blocks<'a> {
     type Item = BlockInfo<'a>;
 
@@ -826,23 +1042,25 @@ impl<'a> Iterator for Blocks<'a> {
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let exact = (self.back.0 - self.ptr.0) as usize;
+        let exact = self.len();
         (exact, Some(exact))
     }
 
     fn count(self) -> usize {
-        (self.back.0 - self.ptr.0) as usize
+        self.len()
     }
 
     fn nth(&mut self, n: usize) -> Option<BlockInfo<'a>> {
-        if self.ptr.0 + n as u64 >= self.back.0 {
-            self.ptr = self.back;
-            None
-        } else {
-            self.ptr = Height(self.ptr.0 + n as u64);
-            let block = BlockInfo::new(self.explorer, self.ptr);
-            self.ptr = self.ptr.next();
-            Some(block)
+        match self.ptr.checked_add(n as u64) {
+            Some(ptr) if ptr < self.back => {
+                let block = BlockInfo::new(self.explorer, ptr);
+                self.ptr = ptr.next();
+                Some(block)
+            }
+            _ => {
+                self.ptr = self.back;
+                None
+            }
         }
     }
 }
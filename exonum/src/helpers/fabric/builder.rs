@@ -17,19 +17,29 @@ use std::{
     ffi::OsString,
     fmt,
     panic::{self, PanicInfo},
+    thread,
+    time::Duration,
 };
 
 use super::{
+    chains::RunChains,
     clap_backend::ClapBackend,
     details::{Finalize, GenerateCommonConfig, GenerateNodeConfig, GenerateTestnet, Run, RunDev},
+    devnet::Devnet,
     info::Info,
     internal::{CollectedCommand, Command, Feedback},
     keys,
-    maintenance::Maintenance,
-    CommandName, ServiceFactory,
+    maintenance::{
+        CheckDb, CheckNetwork, DiffDb, Explore, ExportArchive, ExportBackup, Maintenance,
+        RestoreBackup,
+    },
+    test_vectors::GenerateTestVectors,
+    wire_format::DescribeFormat,
+    CommandName, Context, ServiceFactory,
 };
 use blockchain::Service;
 use node::Node;
+use storage::{self, Database, MemoryDB, TieredDB};
 
 /// `NodeBuilder` is a high level object,
 /// usable for fast prototyping and creating app from services list.
@@ -70,26 +80,79 @@ impl NodeBuilder {
     }
 
     /// Parse cmd args, return `Node`, if run command found
-    pub fn parse_cmd(self) -> Option<Node> {
+    pub fn parse_cmd(mut self) -> Option<Node> {
         match ClapBackend::execute(&self.commands) {
-            Feedback::RunNode(ref ctx) => {
-                let config_file_path = ctx.get(keys::NODE_CONFIG_PATH).ok();
-                let config = ctx
-                    .get(keys::NODE_CONFIG)
-                    .expect("could not find node_config");
-                let db = Run::db_helper(ctx, &config.database);
-                let services: Vec<Box<dyn Service>> = self
-                    .service_factories
-                    .into_iter()
-                    .map(|mut factory| factory.make_service(ctx))
-                    .collect();
-                let node = Node::new(db, services, config, config_file_path);
-                Some(node)
-            }
+            Feedback::RunNode(ref ctx) => Some(self.create_node(ctx)),
             _ => None,
         }
     }
 
+    /// Parses cmd args and returns the nodes to run together with the names of their chains.
+    fn parse_nodes(mut self) -> Vec<(String, Node)> {
+        match ClapBackend::execute(&self.commands) {
+            Feedback::RunNode(ref ctx) => vec![(String::new(), self.create_node(ctx))],
+            Feedback::RunChains(ref contexts) => contexts
+                .iter()
+                .map(|ctx| {
+                    let name = ctx
+                        .get(keys::CHAIN_NAME)
+                        .expect("could not find chain_name");
+                    (name, self.create_node(ctx))
+                }).collect(),
+            Feedback::None => Vec::new(),
+        }
+    }
+
+    fn create_node(&mut self, ctx: &Context) -> Node {
+        let config_file_path = ctx.get(keys::NODE_CONFIG_PATH).ok();
+        let config = ctx
+            .get(keys::NODE_CONFIG)
+            .expect("could not find node_config");
+        let mut db: Box<dyn Database> = match config.memory_db {
+            Some(ref memory_db) => {
+                let mut db =
+                    MemoryDB::open(&memory_db.path).expect("Can't load in-memory database file");
+                if let Some(interval) = memory_db.flush_interval {
+                    db = db.with_flush_interval(Duration::from_millis(interval));
+                }
+                Box::new(db)
+            }
+            None => Run::db_helper(ctx, &config.database),
+        };
+        if let Some(ref cold_storage) = config.cold_storage {
+            let cold_db = storage::open_database(&cold_storage.path, &config.database)
+                .expect("Can't load cold storage database");
+            db = Box::new(TieredDB::new(db, cold_db));
+        }
+        let services: Vec<Box<dyn Service>> = self
+            .service_factories
+            .iter_mut()
+            .map(|factory| factory.make_service(ctx))
+            .collect();
+        Node::new(db, services, config, config_file_path)
+    }
+
+    /// Runs each node in a separate thread and waits until all of them exit.
+    fn run_chains(nodes: Vec<(String, Node)>) {
+        let handles: Vec<_> = nodes
+            .into_iter()
+            .map(|(name, node)| {
+                let thread_name = format!("chain-{}", name);
+                let handle = thread::Builder::new()
+                    .name(thread_name)
+                    .spawn(move || node.run())
+                    .expect("Can't spawn chain thread");
+                (name, handle)
+            }).collect();
+        for (name, handle) in handles {
+            match handle.join() {
+                Ok(Ok(())) => info!("Chain {} is stopped", name),
+                Ok(Err(e)) => error!("Chain {} returned error: {}", name, e),
+                Err(_) => error!("Chain {} panicked", name),
+            }
+        }
+    }
+
     // handle error, and print it.
     fn panic_hook(info: &PanicInfo) {
         let msg = match info.payload().downcast_ref::<&'static str>() {
@@ -118,10 +181,12 @@ impl NodeBuilder {
 
         let old_hook = panic::take_hook();
         panic::set_hook(Box::new(Self::panic_hook));
-        let feedback = self.parse_cmd();
+        let mut nodes = self.parse_nodes();
         panic::set_hook(old_hook);
 
-        if let Some(node) = feedback {
+        if nodes.len() > 1 {
+            Self::run_chains(nodes);
+        } else if let Some((_, node)) = nodes.pop() {
             node.run().expect("Node return error")
         }
     }
@@ -131,10 +196,21 @@ impl NodeBuilder {
             Box::new(GenerateTestnet) as Box<dyn Command>,
             Box::new(Run),
             Box::new(RunDev),
+            Box::new(RunChains),
+            Box::new(Devnet),
             Box::new(GenerateNodeConfig),
             Box::new(GenerateCommonConfig),
             Box::new(Finalize),
             Box::new(Maintenance),
+            Box::new(CheckDb),
+            Box::new(CheckNetwork),
+            Box::new(DiffDb),
+            Box::new(ExportArchive),
+            Box::new(ExportBackup),
+            Box::new(RestoreBackup),
+            Box::new(Explore),
+            Box::new(GenerateTestVectors),
+            Box::new(DescribeFormat),
         ].into_iter()
         .map(|c| (c.name(), CollectedCommand::new(c)))
         .collect()
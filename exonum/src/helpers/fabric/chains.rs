@@ -0,0 +1,256 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module implements running several independent blockchains in one process.
+
+use failure;
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use super::{
+    internal::{CollectedCommand, Command, Feedback},
+    keys, Argument, CommandName, Context,
+};
+use helpers::config::ConfigFile;
+use node::NodeConfig;
+
+// Context entry for the path to the chains configuration.
+const CHAINS_CONFIG_PATH: &str = "CHAINS_CONFIG_PATH";
+// Context entry for the path to the database. The same entry is used by the `run` command,
+// so the contexts of the chains can be handled in the same way as the context of a node.
+const DATABASE_PATH: &str = "DATABASE_PATH";
+
+/// Blockchain run by the `run-chains` command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainConfig {
+    /// Name of the chain. The database of the chain is stored in the subdirectory
+    /// with this name.
+    pub name: String,
+    /// Path to the node configuration of the chain. Relative paths are resolved against
+    /// the directory of the chains configuration file.
+    pub node_config: PathBuf,
+}
+
+/// Configuration of the blockchains run in one process by the `run-chains` command.
+///
+/// # Examples
+///
+/// ```toml
+/// [[chains]]
+/// name = "stage"
+/// node_config = "stage/node.toml"
+///
+/// [[chains]]
+/// name = "prod"
+/// node_config = "prod/node.toml"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChainsConfig {
+    /// Blockchains to run.
+    pub chains: Vec<ChainConfig>,
+}
+
+impl ChainsConfig {
+    /// Returns the paths to the node configurations of the chains, resolving the relative
+    /// paths against the given directory.
+    pub fn node_config_paths(&self, base_dir: &Path) -> Vec<PathBuf> {
+        self.chains
+            .iter()
+            .map(|chain| base_dir.join(&chain.node_config))
+            .collect()
+    }
+
+    /// Checks that the chains can be run in one process: their names are unique and
+    /// can be used as directory names, and the chains do not share the node keys,
+    /// the listen addresses or the API addresses. Node configurations are given
+    /// in the order of the chains.
+    pub fn check(&self, configs: &[NodeConfig]) -> Result<(), failure::Error> {
+        ensure!(!self.chains.is_empty(), "No chains are configured");
+        ensure!(
+            self.chains.len() == configs.len(),
+            "Expected {} node configurations, got {}",
+            self.chains.len(),
+            configs.len()
+        );
+
+        let mut names = HashSet::new();
+        let mut consensus_keys = HashMap::new();
+        let mut addresses = HashMap::new();
+        for (chain, config) in self.chains.iter().zip(configs) {
+            let name = &chain.name;
+            ensure!(is_valid_name(name), "Invalid chain name {:?}", name);
+            ensure!(names.insert(name), "Duplicate chain name {}", name);
+
+            // Peers identify the node by its consensus key, so the chains sharing the key
+            // could be connected with each other.
+            if let Some(other) = consensus_keys.insert(config.consensus_public_key, name) {
+                bail!("Chains {} and {} use the same consensus key", other, name);
+            }
+            let api = &config.api;
+            let chain_addresses = Some(config.listen_address)
+                .into_iter()
+                .chain(api.public_api_address)
+                .chain(api.private_api_address);
+            for address in chain_addresses {
+                if let Some(other) = addresses.insert(address, name) {
+                    bail!(
+                        "Chains {} and {} use the same address {}",
+                        other,
+                        name,
+                        address
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Command for running several independent blockchains in one process.
+///
+/// The chains are listed in the [configuration file](struct.ChainsConfig.html); each chain
+/// has its own node configuration, and thus its own genesis configuration, validators,
+/// listen address and API addresses. The database of each chain is stored in a separate
+/// subdirectory of the given database directory named after the chain. The command line
+/// extensions of the services are applied to each chain separately, and the name
+/// of the chain is available to them under the [`CHAIN_NAME`] key.
+///
+/// [`CHAIN_NAME`]: keys/constant.CHAIN_NAME.html
+#[derive(Debug)]
+pub struct RunChains;
+
+impl RunChains {
+    fn load(path: &Path) -> Result<(ChainsConfig, Vec<PathBuf>, Vec<NodeConfig>), failure::Error> {
+        let chains: ChainsConfig = ConfigFile::load(path)?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let paths = chains.node_config_paths(base_dir);
+        let configs = paths
+            .iter()
+            .map(|path| {
+                ConfigFile::load(path)
+                    .map_err(|e| format_err!("Cannot load node config {}: {}", path.display(), e))
+            }).collect::<Result<Vec<NodeConfig>, _>>()?;
+        chains.check(&configs)?;
+        Ok((chains, paths, configs))
+    }
+}
+
+impl Command for RunChains {
+    fn args(&self) -> Vec<Argument> {
+        vec![
+            Argument::new_named(
+                CHAINS_CONFIG_PATH,
+                true,
+                "Path to the chains configuration file.",
+                "c",
+                "chains-config",
+                false,
+            ),
+            Argument::new_named(
+                DATABASE_PATH,
+                true,
+                "Directory with the databases of the chains.",
+                "d",
+                "db-path",
+                false,
+            ),
+        ]
+    }
+
+    fn name(&self) -> CommandName {
+        "run-chains"
+    }
+
+    fn about(&self) -> &str {
+        "Runs several independent blockchains in one process."
+    }
+
+    fn execute(
+        &self,
+        _commands: &HashMap<CommandName, CollectedCommand>,
+        context: Context,
+        exts: &dyn Fn(Context) -> Context,
+    ) -> Feedback {
+        let path = context
+            .arg::<String>(CHAINS_CONFIG_PATH)
+            .unwrap_or_else(|_| panic!("{} not found.", CHAINS_CONFIG_PATH));
+        let db_dir = context
+            .arg::<String>(DATABASE_PATH)
+            .unwrap_or_else(|_| panic!("{} not found.", DATABASE_PATH));
+        let (chains, paths, configs) =
+            Self::load(Path::new(&path)).unwrap_or_else(|e| panic!("Invalid chains config: {}", e));
+
+        let contexts = chains
+            .chains
+            .into_iter()
+            .zip(paths)
+            .zip(configs)
+            .map(|((chain, path), config)| {
+                let db_path = Path::new(&db_dir).join(&chain.name);
+                let mut context = context.clone();
+                context.set_arg(DATABASE_PATH, db_path.to_string_lossy().into_owned());
+                context.set(keys::NODE_CONFIG, config);
+                context.set(keys::NODE_CONFIG_PATH, path.to_string_lossy().into_owned());
+                context.set(keys::CHAIN_NAME, chain.name);
+                exts(context)
+            }).collect();
+        Feedback::RunChains(contexts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChainConfig, ChainsConfig};
+    use helpers::generate_testnet_config;
+
+    fn chains(names: &[&str]) -> ChainsConfig {
+        let chains = names
+            .iter()
+            .map(|name| ChainConfig {
+                name: name.to_string(),
+                node_config: format!("{}/node.toml", name).into(),
+            }).collect();
+        ChainsConfig { chains }
+    }
+
+    #[test]
+    fn check_chains() {
+        let configs = vec![
+            generate_testnet_config(1, 6000).remove(0),
+            generate_testnet_config(1, 7000).remove(0),
+        ];
+        assert!(chains(&["stage", "prod"]).check(&configs).is_ok());
+        assert!(chains(&[]).check(&[]).is_err());
+        assert!(chains(&["stage"]).check(&configs).is_err());
+        assert!(chains(&["prod", "prod"]).check(&configs).is_err());
+        assert!(chains(&["stage", "../prod"]).check(&configs).is_err());
+
+        let same_key = vec![configs[0].clone(), configs[0].clone()];
+        assert!(chains(&["stage", "prod"]).check(&same_key).is_err());
+
+        let mut same_address = configs.clone();
+        same_address[1].listen_address = same_address[0].listen_address;
+        assert!(chains(&["stage", "prod"]).check(&same_address).is_err());
+    }
+}
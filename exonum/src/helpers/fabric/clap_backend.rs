@@ -97,6 +97,13 @@ impl ClapBackend {
                         }
                         clap_arg.multiple(detail.multiple).takes_value(true)
                     }
+                    ArgumentType::Flag(detail) => {
+                        let mut clap_arg = clap_arg.long(detail.long_name);
+                        if let Some(short) = detail.short_name {
+                            clap_arg = clap_arg.short(short);
+                        }
+                        clap_arg
+                    }
                 };
                 clap_arg.help(arg.help).required(arg.required)
             }).collect();
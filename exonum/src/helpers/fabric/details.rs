@@ -39,7 +39,7 @@ use api::backends::actix::AllowOrigin;
 use blockchain::{config::ValidatorKeys, GenesisConfig};
 use helpers::{config::ConfigFile, generate_testnet_config};
 use node::{ConnectListConfig, NodeApiConfig, NodeConfig};
-use storage::{Database, DbOptions, RocksDB};
+use storage::{self, Database, DbOptions};
 
 const DATABASE_PATH: &str = "DATABASE_PATH";
 const OUTPUT_DIR: &str = "OUTPUT_DIR";
@@ -50,6 +50,7 @@ const PUBLIC_API_ADDRESS: &str = "PUBLIC_API_ADDRESS";
 const PRIVATE_API_ADDRESS: &str = "PRIVATE_API_ADDRESS";
 const PUBLIC_ALLOW_ORIGIN: &str = "PUBLIC_ALLOW_ORIGIN";
 const PRIVATE_ALLOW_ORIGIN: &str = "PRIVATE_ALLOW_ORIGIN";
+const PARANOID: &str = "PARANOID";
 
 /// Run command.
 pub struct Run;
@@ -60,7 +61,7 @@ impl Run {
         let path = ctx
             .arg::<String>(DATABASE_PATH)
             .unwrap_or_else(|_| panic!("{} not found.", DATABASE_PATH));
-        Box::new(RocksDB::open(Path::new(&path), options).expect("Can't load database file"))
+        storage::open_database(Path::new(&path), options).expect("Can't load database file")
     }
 
     fn node_config_path(ctx: &Context) -> String {
@@ -79,6 +80,10 @@ impl Run {
     fn private_api_address(ctx: &Context) -> Option<SocketAddr> {
         ctx.arg(PRIVATE_API_ADDRESS).ok()
     }
+
+    fn paranoid(ctx: &Context) -> bool {
+        ctx.arg(PARANOID).unwrap_or(false)
+    }
 }
 
 impl Command for Run {
@@ -116,6 +121,12 @@ impl Command for Run {
                 "private-api-address",
                 false,
             ),
+            Argument::new_flag(
+                PARANOID,
+                "Re-verify the blockchain data on each block and on restart.",
+                None,
+                "paranoid",
+            ),
         ]
     }
 
@@ -138,6 +149,7 @@ impl Command for Run {
         let config = Self::node_config(config_path.clone());
         let public_addr = Self::public_api_address(&context);
         let private_addr = Self::private_api_address(&context);
+        let paranoid = Self::paranoid(&context);
 
         context.set(keys::NODE_CONFIG, config);
         context.set(keys::NODE_CONFIG_PATH, config_path);
@@ -154,6 +166,10 @@ impl Command for Run {
             config.api.private_api_address = Some(private_api_address);
         }
 
+        if paranoid {
+            config.paranoid = true;
+        }
+
         new_context.set(keys::NODE_CONFIG, config);
 
         Feedback::RunNode(new_context)
@@ -680,7 +696,22 @@ impl Command for Finalize {
                 services_configs: Default::default(),
                 database: Default::default(),
                 connect_list,
-                thread_pool_size: Default::default(),
+                threads: Default::default(),
+                plugins: Default::default(),
+                propose_precheck: false,
+                compaction: None,
+                status_file: None,
+                consensus_archive: None,
+                ping_interval: None,
+                max_clock_skew: None,
+                propose_ahead: false,
+                cold_storage: None,
+                memory_db: None,
+                pruning: None,
+                alerting: None,
+                paranoid: false,
+                replica: None,
+                forensics: None,
             }
         };
 
@@ -0,0 +1,340 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module implements the local development network launcher.
+
+use failure;
+use serde_json;
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpStream},
+    path::{Path, PathBuf},
+    process::{Child, Command as Process, Stdio},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use super::{
+    internal::{CollectedCommand, Command, Feedback},
+    keys, Argument, CommandName, Context, GenerateTestnet, DEFAULT_EXONUM_LISTEN_PORT,
+};
+use api::node::public::explorer::{TransactionHex, TransactionResponse};
+use explorer::TransactionInfo;
+
+// Context entry for the directory with the network data.
+const OUTPUT_DIR: &str = "OUTPUT_DIR";
+// Context entry for the number of validators.
+const COUNT: &str = "COUNT";
+// Context entry for the first port of the validators.
+const START_PORT: &str = "START_PORT";
+// Context entry for the first port of the public API of the validators.
+const PUBLIC_API_PORT: &str = "PUBLIC_API_PORT";
+// Context entry for the first port of the private API of the validators.
+const PRIVATE_API_PORT: &str = "PRIVATE_API_PORT";
+
+/// Default directory with the network data.
+const DEFAULT_DEVNET_DIR: &str = ".exonum-devnet";
+/// Default first port of the public API of the validators.
+const DEFAULT_PUBLIC_API_PORT: u16 = 8200;
+/// Default first port of the private API of the validators.
+const DEFAULT_PRIVATE_API_PORT: u16 = 8091;
+/// Timeout for the public API of the first validator to become available
+/// and for the transactions of a batch to be committed.
+const API_TIMEOUT: Duration = Duration::from_secs(60);
+/// Interval of polling the public API.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Path of the endpoint for sending and querying the transactions.
+const TRANSACTIONS_PATH: &str = "/api/explorer/v1/transactions";
+
+/// Command for launching a local development network.
+///
+/// The command generates the configurations of the given number of validators
+/// with `generate-testnet`, runs each validator as a child process of the current
+/// executable, and prints the combined logs of the nodes prefixed by the validator
+/// index until all the nodes exit. Validator `i` listens to the peers on the port
+/// `start + i`, and serves the public and the private API on the ports
+/// `public-api-port + i` and `private-api-port + i` respectively.
+///
+/// Services can extend the command to prepare the network for testing, e.g., to fund
+/// test wallets. The batches of transactions put by the extensions into the context
+/// under the [`DEVNET_TRANSACTIONS`] key are sent to the first validator once it is
+/// started; each batch is sent after the previous one is committed.
+///
+/// [`DEVNET_TRANSACTIONS`]: keys/constant.DEVNET_TRANSACTIONS.html
+#[derive(Debug)]
+pub struct Devnet;
+
+impl Devnet {
+    fn output_dir(ctx: &Context) -> PathBuf {
+        let dir = ctx
+            .arg::<String>(OUTPUT_DIR)
+            .unwrap_or_else(|_| DEFAULT_DEVNET_DIR.into());
+        PathBuf::from(dir)
+    }
+
+    fn local_address(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    fn spawn_node(
+        dir: &Path,
+        idx: u16,
+        public_api: SocketAddr,
+        private_api: SocketAddr,
+    ) -> io::Result<Child> {
+        let config_path = dir.join("validators").join(format!("{}.toml", idx));
+        let db_path = dir.join("db").join(idx.to_string());
+        Process::new(env::current_exe()?)
+            .arg("run")
+            .arg("--node-config")
+            .arg(config_path)
+            .arg("--db-path")
+            .arg(db_path)
+            .arg("--public-api-address")
+            .arg(public_api.to_string())
+            .arg("--private-api-address")
+            .arg(private_api.to_string())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+    }
+
+    /// Prints the lines read from the stream with the prefix of the validator.
+    fn tail<R: Read + Send + 'static>(idx: u16, stream: R) -> JoinHandle<()> {
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines() {
+                match line {
+                    Ok(line) => println!("[{}] {}", idx, line),
+                    Err(_) => break,
+                }
+            }
+        })
+    }
+
+    /// Sends the batches of transactions to the public API once it becomes available.
+    /// Each batch is sent after all the transactions of the previous one are committed.
+    fn send_transactions(
+        address: SocketAddr,
+        batches: &[Vec<String>],
+    ) -> Result<usize, failure::Error> {
+        let started = Instant::now();
+        while TcpStream::connect(address).is_err() {
+            ensure!(
+                started.elapsed() < API_TIMEOUT,
+                "Public API at {} is not available",
+                address
+            );
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        let mut sent = 0;
+        for (idx, batch) in batches.iter().enumerate() {
+            let mut tx_hashes = Vec::new();
+            for tx_body in batch {
+                let body = serde_json::to_vec(&TransactionHex {
+                    tx_body: tx_body.clone(),
+                })?;
+                let response = Self::request(address, "POST", TRANSACTIONS_PATH, &body)?;
+                let response: TransactionResponse = serde_json::from_str(&response)?;
+                tx_hashes.push(response.tx_hash);
+                sent += 1;
+            }
+            if idx + 1 == batches.len() {
+                break;
+            }
+
+            let started = Instant::now();
+            for tx_hash in tx_hashes {
+                let path = format!("{}?hash={}", TRANSACTIONS_PATH, tx_hash.to_hex());
+                loop {
+                    let info: TransactionInfo =
+                        serde_json::from_str(&Self::request(address, "GET", &path, &[])?)?;
+                    if info.is_committed() {
+                        break;
+                    }
+                    ensure!(
+                        started.elapsed() < API_TIMEOUT,
+                        "Transaction {} is not committed",
+                        tx_hash.to_hex()
+                    );
+                    thread::sleep(POLL_INTERVAL);
+                }
+            }
+        }
+        Ok(sent)
+    }
+
+    /// Performs an HTTP request and returns the body of the successful response.
+    fn request(
+        address: SocketAddr,
+        method: &str,
+        path: &str,
+        body: &[u8],
+    ) -> Result<String, failure::Error> {
+        let mut stream = TcpStream::connect(address)?;
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n",
+            method,
+            path,
+            address,
+            body.len()
+        )?;
+        stream.write_all(body)?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        let (head, body) = match response.find("\r\n\r\n") {
+            Some(idx) => (&response[..idx], &response[idx + 4..]),
+            None => bail!("Malformed response to {} {}", method, path),
+        };
+        let status_line = head.lines().next().unwrap_or_default();
+        ensure!(
+            status_line
+                .split_whitespace()
+                .nth(1)
+                .map_or(false, |code| code.starts_with('2')),
+            "Request {} {} failed with `{}`: {}",
+            method,
+            path,
+            status_line,
+            body
+        );
+        Ok(body.to_owned())
+    }
+}
+
+impl Command for Devnet {
+    fn args(&self) -> Vec<Argument> {
+        vec![
+            Argument::new_named(
+                OUTPUT_DIR,
+                false,
+                "Path to directory where to save configs and databases.",
+                "o",
+                "output-dir",
+                false,
+            ),
+            Argument::new_named(
+                START_PORT,
+                false,
+                "Port number started from which should validators listen.",
+                "p",
+                "start",
+                false,
+            ),
+            Argument::new_named(
+                PUBLIC_API_PORT,
+                false,
+                "Port number started from which should validators serve public API.",
+                None,
+                "public-api-port",
+                false,
+            ),
+            Argument::new_named(
+                PRIVATE_API_PORT,
+                false,
+                "Port number started from which should validators serve private API.",
+                None,
+                "private-api-port",
+                false,
+            ),
+            Argument::new_positional(COUNT, true, "Count of validators in devnet."),
+        ]
+    }
+
+    fn name(&self) -> CommandName {
+        "devnet"
+    }
+
+    fn about(&self) -> &str {
+        "Runs local development network with the given number of validators."
+    }
+
+    fn execute(
+        &self,
+        commands: &HashMap<CommandName, CollectedCommand>,
+        mut context: Context,
+        exts: &dyn Fn(Context) -> Context,
+    ) -> Feedback {
+        let dir = Self::output_dir(&context);
+        let count: u16 = context.arg(COUNT).expect("count as int");
+        let public_api_port = context
+            .arg::<u16>(PUBLIC_API_PORT)
+            .unwrap_or(DEFAULT_PUBLIC_API_PORT);
+        let private_api_port = context
+            .arg::<u16>(PRIVATE_API_PORT)
+            .unwrap_or(DEFAULT_PRIVATE_API_PORT);
+        let start_port = context
+            .arg::<u16>(START_PORT)
+            .unwrap_or(DEFAULT_EXONUM_LISTEN_PORT);
+
+        // The keys of the validators are generated anew, so the old data is useless.
+        if dir.exists() {
+            fs::remove_dir_all(&dir).expect("Expected devnet directory being removable.");
+        }
+        context.set_arg(OUTPUT_DIR, dir.to_string_lossy().into_owned());
+        context.set_arg(START_PORT, start_port.to_string());
+        commands
+            .get(GenerateTestnet.name())
+            .expect("Expected GenerateTestnet in the commands list.")
+            .execute(commands, context.clone());
+
+        let context = exts(context);
+        let transactions = context.get(keys::DEVNET_TRANSACTIONS).unwrap_or_default();
+
+        let mut nodes = Vec::new();
+        let mut tails = Vec::new();
+        for idx in 0..count {
+            let public_api = Self::local_address(public_api_port + idx);
+            let private_api = Self::local_address(private_api_port + idx);
+            let mut node = Self::spawn_node(&dir, idx, public_api, private_api)
+                .unwrap_or_else(|e| panic!("Cannot start validator {}: {}", idx, e));
+            tails.push(Self::tail(
+                idx,
+                node.stdout.take().expect("stdout is piped"),
+            ));
+            tails.push(Self::tail(
+                idx,
+                node.stderr.take().expect("stderr is piped"),
+            ));
+            nodes.push(node);
+        }
+
+        if !transactions.is_empty() {
+            let public_api = Self::local_address(public_api_port);
+            match Self::send_transactions(public_api, &transactions) {
+                Ok(sent) => println!("Sent {} transactions to the devnet", sent),
+                Err(e) => println!("Cannot send transactions to the devnet: {}", e),
+            }
+        }
+
+        for (idx, mut node) in nodes.into_iter().enumerate() {
+            match node.wait() {
+                Ok(status) => println!("Validator {} exited with {}", idx, status),
+                Err(e) => println!("Cannot wait for validator {}: {}", idx, e),
+            }
+        }
+        for tail in tails {
+            let _ = tail.join();
+        }
+
+        Feedback::None
+    }
+}
@@ -23,6 +23,8 @@ use super::{Argument, CommandExtension, CommandName, Context};
 pub enum Feedback {
     /// Run node with current context.
     RunNode(Context),
+    /// Run several nodes, one per chain, with the given contexts.
+    RunChains(Vec<Context>),
     /// Do nothing
     None,
 }
@@ -14,21 +14,57 @@
 
 //! This module implements node maintenance actions.
 
-use std::{collections::HashMap, path::Path};
+use failure;
+use futures::sync::mpsc;
+use hex;
+
+use std::{
+    collections::HashMap,
+    env,
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    process, thread,
+    time::Duration,
+};
 
 use super::{
     internal::{CollectedCommand, Command, Feedback},
     Argument, CommandName, Context,
 };
-use blockchain::Schema;
-use helpers::config::ConfigFile;
-use node::NodeConfig;
-use storage::{Database, DbOptions, RocksDB};
+use api::{
+    backends::actix::{ApiRuntimeConfig, SystemRuntime, SystemRuntimeConfig},
+    ApiAccess, ApiAggregator,
+};
+use blockchain::{archive, invariants, Blockchain, Schema, SharedNodeState};
+use crypto;
+use helpers::{config::ConfigFile, Height};
+use node::{network_check, ApiSender, NodeConfig};
+use storage::{
+    self,
+    backup::{self, BackupInfo},
+    diff, Database, DbOptions, ReadOnlyDB, Snapshot,
+};
 
 // Context entry for the path to the node config.
 const NODE_CONFIG_PATH: &str = "NODE_CONFIG_PATH";
 // Context entry for the path to the database.
 const DATABASE_PATH: &str = "DATABASE_PATH";
+// Context entry for the path to the database compared with the first one.
+const OTHER_DATABASE_PATH: &str = "OTHER_DATABASE_PATH";
+// Context entry for the maximal number of the printed differing keys per table.
+const MAX_KEYS: &str = "MAX_KEYS";
+// Context entry for the directory with the block archive.
+const ARCHIVE_DIR: &str = "ARCHIVE_DIR";
+// Context entry for the path to the backup file.
+const BACKUP_PATH: &str = "BACKUP_PATH";
+// Context entry for the timeout of the handshake with a single peer.
+const HANDSHAKE_TIMEOUT: &str = "HANDSHAKE_TIMEOUT";
+// Context entry for the listen address of the explorer API.
+const LISTEN_ADDRESS: &str = "LISTEN_ADDRESS";
+// Context entry for the directory of the working copy of the explored database.
+const WORK_DIR: &str = "WORK_DIR";
 // Context entry for the type of action to be performed.
 const MAINTENANCE_ACTION_PATH: &str = "MAINTENANCE_ACTION_PATH";
 
@@ -50,7 +86,7 @@ impl Maintenance {
         let path = ctx
             .arg::<String>(DATABASE_PATH)
             .unwrap_or_else(|_| panic!("{} not found.", DATABASE_PATH));
-        Box::new(RocksDB::open(Path::new(&path), options).expect("Can't load database file"))
+        storage::open_database(Path::new(&path), options).expect("Can't load database file")
     }
 
     fn clear_cache(context: &Context) {
@@ -58,10 +94,13 @@ impl Maintenance {
 
         let config = Self::node_config(context);
         let db = Self::database(context, &config.database);
+        let snapshot = db.snapshot();
         let mut fork = db.fork();
         {
             let mut schema = Schema::new(&mut fork);
             schema.consensus_messages_cache_mut().clear();
+            let height = Height(schema.block_hashes_by_height().len().saturating_sub(1));
+            schema.update_storage_stats(&*snapshot, height);
         }
         db.merge_sync(fork.into_patch()).expect("Can't clear cache");
 
@@ -126,3 +165,861 @@ impl Command for Maintenance {
         Feedback::None
     }
 }
+
+/// Database consistency check command.
+///
+/// The command walks over the committed blocks and checks that:
+///
+/// - blocks are linked with each other via `prev_hash`;
+/// - the root hashes of the block transactions and of the core tables are equal to
+///   the ones recomputed from the stored entries;
+/// - every block (except the genesis one) is signed by the +2/3 majority of the validators
+///   with valid precommit signatures.
+///
+/// The database is opened through [`ReadOnlyDB`] and is never created or modified;
+/// the command fails if there is no database at the given path. The first found
+/// inconsistency is reported, and the command exits with a non-zero status.
+/// Note that service tables are not checked, since the command has no access
+/// to the service implementations.
+///
+/// [`ReadOnlyDB`]: ../../storage/struct.ReadOnlyDB.html
+#[derive(Debug)]
+pub struct CheckDb;
+
+impl CheckDb {
+    /// Checks the database at the given path and returns the height of the last
+    /// consistent block.
+    pub fn check(db_path: &Path, options: &DbOptions) -> Result<Height, failure::Error> {
+        ensure!(
+            db_path.is_dir(),
+            "Database path {} is not a directory",
+            db_path.display()
+        );
+        let options = DbOptions {
+            create_if_missing: false,
+            ..*options
+        };
+        let db = ReadOnlyDB::new(storage::open_database(db_path, &options)?);
+        invariants::check_chain(db.snapshot())
+    }
+}
+
+impl Command for CheckDb {
+    fn args(&self) -> Vec<Argument> {
+        vec![
+            Argument::new_named(
+                NODE_CONFIG_PATH,
+                true,
+                "Path to node configuration file.",
+                "c",
+                "node-config",
+                false,
+            ),
+            Argument::new_named(
+                DATABASE_PATH,
+                true,
+                "Use database with the given path.",
+                "d",
+                "db-path",
+                false,
+            ),
+        ]
+    }
+
+    fn name(&self) -> CommandName {
+        "check-db"
+    }
+
+    fn about(&self) -> &str {
+        "Checks consistency of the blockchain data in the database."
+    }
+
+    fn execute(
+        &self,
+        _commands: &HashMap<CommandName, CollectedCommand>,
+        context: Context,
+        _: &dyn Fn(Context) -> Context,
+    ) -> Feedback {
+        let config = Maintenance::node_config(&context);
+        let db_path = context
+            .arg::<String>(DATABASE_PATH)
+            .unwrap_or_else(|_| panic!("{} not found.", DATABASE_PATH));
+
+        match Self::check(Path::new(&db_path), &config.database) {
+            Ok(height) => println!("Database is consistent up to height {}", height),
+            Err(e) => {
+                eprintln!("Database check failed: {}", e);
+                process::exit(1);
+            }
+        }
+
+        Feedback::None
+    }
+}
+
+/// Network check command.
+///
+/// The command loads the node configuration and performs the handshakes with all
+/// the peers from the connect list, as the node does on startup. For each peer,
+/// the command reports whether it is reachable, whether its consensus key matches
+/// the connect list, and whether it runs the same Exonum version. Then it exits
+/// without starting the node, so it can be used to validate firewall and configuration
+/// changes before restarting a validator. See [`network_check`] for details.
+///
+/// [`network_check`]: ../../node/network_check/index.html
+#[derive(Debug)]
+pub struct CheckNetwork;
+
+impl CheckNetwork {
+    /// Default timeout of the handshake with a single peer in milliseconds.
+    const DEFAULT_TIMEOUT: u64 = 5_000;
+}
+
+impl Command for CheckNetwork {
+    fn args(&self) -> Vec<Argument> {
+        vec![
+            Argument::new_named(
+                NODE_CONFIG_PATH,
+                true,
+                "Path to node configuration file.",
+                "c",
+                "node-config",
+                false,
+            ),
+            Argument::new_named(
+                HANDSHAKE_TIMEOUT,
+                false,
+                "Timeout of the handshake with a single peer in milliseconds (5000 by default).",
+                "t",
+                "timeout",
+                false,
+            ),
+        ]
+    }
+
+    fn name(&self) -> CommandName {
+        "check-network"
+    }
+
+    fn about(&self) -> &str {
+        "Performs handshakes with the peers from the connect list and reports the results."
+    }
+
+    fn execute(
+        &self,
+        _commands: &HashMap<CommandName, CollectedCommand>,
+        context: Context,
+        _: &dyn Fn(Context) -> Context,
+    ) -> Feedback {
+        let config = Maintenance::node_config(&context);
+        let timeout = context
+            .arg::<u64>(HANDSHAKE_TIMEOUT)
+            .unwrap_or(Self::DEFAULT_TIMEOUT);
+
+        let reports = network_check::check_network(&config, Duration::from_millis(timeout))
+            .unwrap_or_else(|e| panic!("Cannot check the network: {}", e));
+        for report in &reports {
+            println!(
+                "{} ({}): {}",
+                report.address, report.public_key, report.status
+            );
+        }
+
+        let failed = reports
+            .iter()
+            .filter(|report| !report.status.is_ok())
+            .count();
+        if failed > 0 {
+            panic!("{} of {} peers failed the check", failed, reports.len());
+        }
+        println!("All {} peers passed the check", reports.len());
+
+        Feedback::None
+    }
+}
+
+/// Block archive export command.
+///
+/// The command writes each complete segment of the blockchain history into a separate
+/// file in the [block archive format] within the given directory. Segments already present
+/// in the directory are skipped, so the command can be run periodically to keep
+/// the archive up to date. Pruned segments are skipped as well.
+///
+/// [block archive format]: ../../blockchain/archive/index.html
+#[derive(Debug)]
+pub struct ExportArchive;
+
+impl ExportArchive {
+    /// Returns the name of the file containing the given segment.
+    pub fn segment_file_name(segment: u64) -> String {
+        format!("{:08}.archive", segment)
+    }
+
+    fn export(db: &dyn Database, dir: &Path) -> Result<u64, failure::Error> {
+        fs::create_dir_all(dir)?;
+        let snapshot = db.snapshot();
+        let mut exported = 0;
+        for segment in 0..archive::complete_segments(&snapshot) {
+            let path = dir.join(Self::segment_file_name(segment));
+            if path.exists() || archive::is_pruned_segment(&snapshot, segment) {
+                continue;
+            }
+            // The segment is written into a temporary file first, so that an interrupted
+            // export does not leave an incomplete segment behind.
+            let tmp_path = path.with_extension("tmp");
+            let writer = BufWriter::new(File::create(&tmp_path)?);
+            archive::write_segment(&snapshot, segment, writer)?;
+            fs::rename(&tmp_path, &path)?;
+            exported += 1;
+        }
+        Ok(exported)
+    }
+}
+
+impl Command for ExportArchive {
+    fn args(&self) -> Vec<Argument> {
+        vec![
+            Argument::new_named(
+                NODE_CONFIG_PATH,
+                true,
+                "Path to node configuration file.",
+                "c",
+                "node-config",
+                false,
+            ),
+            Argument::new_named(
+                DATABASE_PATH,
+                true,
+                "Use database with the given path.",
+                "d",
+                "db-path",
+                false,
+            ),
+            Argument::new_named(
+                ARCHIVE_DIR,
+                true,
+                "Directory to write the archive segments to.",
+                "o",
+                "output-dir",
+                false,
+            ),
+        ]
+    }
+
+    fn name(&self) -> CommandName {
+        "archive"
+    }
+
+    fn about(&self) -> &str {
+        "Exports the blockchain history into the block archive files."
+    }
+
+    fn execute(
+        &self,
+        _commands: &HashMap<CommandName, CollectedCommand>,
+        context: Context,
+        _: &dyn Fn(Context) -> Context,
+    ) -> Feedback {
+        let config = Maintenance::node_config(&context);
+        let db = Maintenance::database(&context, &config.database);
+        let dir = context
+            .arg::<String>(ARCHIVE_DIR)
+            .unwrap_or_else(|_| panic!("{} not found.", ARCHIVE_DIR));
+
+        match Self::export(db.as_ref(), Path::new(&dir)) {
+            Ok(exported) => println!("Exported {} archive segments", exported),
+            Err(e) => panic!("Cannot export the block archive: {}", e),
+        }
+
+        Feedback::None
+    }
+}
+
+/// Database comparison command.
+///
+/// The command compares the committed blocks of two databases first and reports
+/// the first height at which the block hashes (and thus the state hashes) diverge.
+/// Then the databases are compared table by table, drilling only into the differing
+/// key ranges (see [`storage::diff`](../../storage/diff/index.html)), and the divergent
+/// keys are printed.
+///
+/// Both databases are opened through [`ReadOnlyDB`] and are never created or modified;
+/// the command fails if there is no database at any of the given paths.
+///
+/// The command is useful to check a restored backup or to find out why two validators
+/// report different state hashes.
+///
+/// [`ReadOnlyDB`]: ../../storage/struct.ReadOnlyDB.html
+#[derive(Debug)]
+pub struct DiffDb;
+
+impl DiffDb {
+    /// Default maximal number of the printed differing keys per table.
+    const DEFAULT_MAX_KEYS: usize = 20;
+
+    /// Opens the existing database at the given path for reading.
+    fn open(db_path: &Path, options: &DbOptions) -> Result<ReadOnlyDB, failure::Error> {
+        ensure!(
+            db_path.is_dir(),
+            "Database path {} is not a directory",
+            db_path.display()
+        );
+        let options = DbOptions {
+            create_if_missing: false,
+            ..*options
+        };
+        Ok(ReadOnlyDB::new(storage::open_database(db_path, &options)?))
+    }
+
+    /// Returns the first height at which the block hashes of the snapshots differ,
+    /// or `None` if the blocks are equal up to the height of the shorter chain.
+    fn first_divergent_block(first: &dyn Snapshot, second: &dyn Snapshot) -> Option<Height> {
+        let first = Schema::new(first);
+        let second = Schema::new(second);
+        let first_hashes = first.block_hashes_by_height();
+        let second_hashes = second.block_hashes_by_height();
+        let common_len = ::std::cmp::min(first_hashes.len(), second_hashes.len());
+
+        // The blocks are linked with each other, so the hashes are equal up to
+        // the first divergent block and differ afterwards.
+        if common_len == 0 {
+            return None;
+        }
+        if first_hashes.get(0) != second_hashes.get(0) {
+            return Some(Height(0));
+        }
+        let (mut equal, mut divergent) = (0, common_len);
+        while equal + 1 < divergent {
+            let middle = equal + (divergent - equal) / 2;
+            if first_hashes.get(middle) == second_hashes.get(middle) {
+                equal = middle;
+            } else {
+                divergent = middle;
+            }
+        }
+        if divergent == common_len {
+            None
+        } else {
+            Some(Height(divergent))
+        }
+    }
+
+    fn print_chain(name: &str, snapshot: &dyn Snapshot) {
+        let schema = Schema::new(snapshot);
+        if schema.block_hashes_by_height().is_empty() {
+            println!("{}: no blocks", name);
+        } else {
+            let block = schema.last_block();
+            println!(
+                "{}: height {}, state hash {:?}",
+                name,
+                block.height(),
+                block.state_hash()
+            );
+        }
+    }
+}
+
+impl Command for DiffDb {
+    fn args(&self) -> Vec<Argument> {
+        vec![
+            Argument::new_named(
+                NODE_CONFIG_PATH,
+                true,
+                "Path to node configuration file.",
+                "c",
+                "node-config",
+                false,
+            ),
+            Argument::new_named(
+                DATABASE_PATH,
+                true,
+                "Use database with the given path.",
+                "d",
+                "db-path",
+                false,
+            ),
+            Argument::new_named(
+                OTHER_DATABASE_PATH,
+                true,
+                "Path to the database compared with the first one.",
+                "o",
+                "other-db-path",
+                false,
+            ),
+            Argument::new_named(
+                MAX_KEYS,
+                false,
+                "Maximal number of the printed differing keys per table (20 by default).",
+                None,
+                "max-keys",
+                false,
+            ),
+        ]
+    }
+
+    fn name(&self) -> CommandName {
+        "diff-db"
+    }
+
+    fn about(&self) -> &str {
+        "Compares the contents of two databases and prints the divergent keys."
+    }
+
+    fn execute(
+        &self,
+        _commands: &HashMap<CommandName, CollectedCommand>,
+        context: Context,
+        _: &dyn Fn(Context) -> Context,
+    ) -> Feedback {
+        let config = Maintenance::node_config(&context);
+        let open = |arg: &str| {
+            let path = context
+                .arg::<String>(arg)
+                .unwrap_or_else(|_| panic!("{} not found.", arg));
+            Self::open(Path::new(&path), &config.database).unwrap_or_else(|e| {
+                eprintln!("Can't open database {}: {}", path, e);
+                process::exit(1);
+            })
+        };
+        let first_db = open(DATABASE_PATH);
+        let second_db = open(OTHER_DATABASE_PATH);
+        let max_keys = context
+            .arg::<usize>(MAX_KEYS)
+            .unwrap_or(Self::DEFAULT_MAX_KEYS);
+
+        let first = first_db.snapshot();
+        let second = second_db.snapshot();
+        Self::print_chain("First database", &*first);
+        Self::print_chain("Second database", &*second);
+        match Self::first_divergent_block(&*first, &*second) {
+            Some(height) => println!("Blocks diverge starting from height {}", height),
+            None => println!("Blocks are equal up to the height of the shorter chain"),
+        }
+
+        let tables = diff::diff_snapshots(&*first, &*second);
+        if tables.is_empty() {
+            println!("Databases are equal");
+            return Feedback::None;
+        }
+        for table in tables {
+            println!("Table {}: {} divergent keys", table.name, table.keys.len());
+            for key_diff in table.keys.iter().take(max_keys) {
+                let status = match *key_diff {
+                    diff::KeyDiff::OnlyInFirst(_) => "only in first",
+                    diff::KeyDiff::OnlyInSecond(_) => "only in second",
+                    diff::KeyDiff::Changed(_) => "changed",
+                };
+                println!("  {} ({})", hex::encode(key_diff.key()), status);
+            }
+            if table.keys.len() > max_keys {
+                println!("  ... and {} more", table.keys.len() - max_keys);
+            }
+        }
+
+        Feedback::None
+    }
+}
+
+/// Database backup command.
+///
+/// The command writes all the tables of the database into a single file
+/// in the [backup format]. The backup is read from a snapshot of the database.
+///
+/// [backup format]: ../../storage/backup/index.html
+#[derive(Debug)]
+pub struct ExportBackup;
+
+impl ExportBackup {
+    fn export(db: &dyn Database, path: &Path) -> Result<BackupInfo, failure::Error> {
+        ensure!(!path.exists(), "File {} already exists", path.display());
+        // The backup is written into a temporary file first, so that an interrupted
+        // export does not leave an incomplete backup behind.
+        let tmp_path = path.with_extension("tmp");
+        let writer = BufWriter::new(File::create(&tmp_path)?);
+        let info = backup::export(&*db.snapshot(), writer)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(info)
+    }
+}
+
+impl Command for ExportBackup {
+    fn args(&self) -> Vec<Argument> {
+        vec![
+            Argument::new_named(
+                NODE_CONFIG_PATH,
+                true,
+                "Path to node configuration file.",
+                "c",
+                "node-config",
+                false,
+            ),
+            Argument::new_named(
+                DATABASE_PATH,
+                true,
+                "Use database with the given path.",
+                "d",
+                "db-path",
+                false,
+            ),
+            Argument::new_named(
+                BACKUP_PATH,
+                true,
+                "Path to the backup file.",
+                "b",
+                "backup",
+                false,
+            ),
+        ]
+    }
+
+    fn name(&self) -> CommandName {
+        "backup"
+    }
+
+    fn about(&self) -> &str {
+        "Writes the contents of the database into a backup file."
+    }
+
+    fn execute(
+        &self,
+        _commands: &HashMap<CommandName, CollectedCommand>,
+        context: Context,
+        _: &dyn Fn(Context) -> Context,
+    ) -> Feedback {
+        let config = Maintenance::node_config(&context);
+        let db = Maintenance::database(&context, &config.database);
+        let path = context
+            .arg::<String>(BACKUP_PATH)
+            .unwrap_or_else(|_| panic!("{} not found.", BACKUP_PATH));
+
+        match Self::export(db.as_ref(), Path::new(&path)) {
+            Ok(info) => println!(
+                "Backed up {} tables with {} entries, checksum {:?}",
+                info.tables, info.entries, info.checksum
+            ),
+            Err(e) => panic!("Cannot write the backup: {}", e),
+        }
+
+        Feedback::None
+    }
+}
+
+/// Database restore command.
+///
+/// The command restores a backup written by the `backup` command into a new database.
+/// The restored database is checked in the same way as by the `check-db` command,
+/// i.e., the blocks and the core tables must match the stored block hashes.
+/// The database is written to a temporary location next to the given path and is moved
+/// to this path only if the checks pass.
+#[derive(Debug)]
+pub struct RestoreBackup;
+
+impl RestoreBackup {
+    fn restore(
+        backup_path: &Path,
+        db_path: &Path,
+        options: &DbOptions,
+    ) -> Result<(BackupInfo, Height), failure::Error> {
+        ensure!(
+            !db_path.exists(),
+            "Database path {} already exists",
+            db_path.display()
+        );
+        let tmp_path = db_path.with_extension("restore");
+        ensure!(
+            !tmp_path.exists(),
+            "Temporary path {} already exists",
+            tmp_path.display()
+        );
+
+        let result = Self::restore_into(backup_path, &tmp_path, options);
+        if result.is_ok() {
+            fs::rename(&tmp_path, db_path)?;
+        } else if tmp_path.exists() {
+            fs::remove_dir_all(&tmp_path)?;
+        }
+        result
+    }
+
+    fn restore_into(
+        backup_path: &Path,
+        db_path: &Path,
+        options: &DbOptions,
+    ) -> Result<(BackupInfo, Height), failure::Error> {
+        let options = DbOptions {
+            create_if_missing: true,
+            ..*options
+        };
+        // The database is dropped before it is moved to the final location.
+        let db = storage::open_database(db_path, &options)?;
+        let reader = BufReader::new(File::open(backup_path)?);
+        let info = backup::restore(reader, db.as_ref())?;
+        let height = invariants::check_chain(db.snapshot())?;
+        Ok((info, height))
+    }
+}
+
+impl Command for RestoreBackup {
+    fn args(&self) -> Vec<Argument> {
+        vec![
+            Argument::new_named(
+                NODE_CONFIG_PATH,
+                true,
+                "Path to node configuration file.",
+                "c",
+                "node-config",
+                false,
+            ),
+            Argument::new_named(
+                DATABASE_PATH,
+                true,
+                "Path to the restored database, which must not exist.",
+                "d",
+                "db-path",
+                false,
+            ),
+            Argument::new_named(
+                BACKUP_PATH,
+                true,
+                "Path to the backup file.",
+                "b",
+                "backup",
+                false,
+            ),
+        ]
+    }
+
+    fn name(&self) -> CommandName {
+        "restore"
+    }
+
+    fn about(&self) -> &str {
+        "Restores the database from a backup file and checks its consistency."
+    }
+
+    fn execute(
+        &self,
+        _commands: &HashMap<CommandName, CollectedCommand>,
+        context: Context,
+        _: &dyn Fn(Context) -> Context,
+    ) -> Feedback {
+        let config = Maintenance::node_config(&context);
+        let backup_path = context
+            .arg::<String>(BACKUP_PATH)
+            .unwrap_or_else(|_| panic!("{} not found.", BACKUP_PATH));
+        let db_path = context
+            .arg::<String>(DATABASE_PATH)
+            .unwrap_or_else(|_| panic!("{} not found.", DATABASE_PATH));
+
+        match Self::restore(
+            Path::new(&backup_path),
+            Path::new(&db_path),
+            &config.database,
+        ) {
+            Ok((info, height)) => println!(
+                "Restored {} tables with {} entries, database is consistent up to height {}",
+                info.tables, info.entries, height
+            ),
+            Err(e) => panic!("Cannot restore the backup: {}", e),
+        }
+
+        Feedback::None
+    }
+}
+
+/// Database explorer command.
+///
+/// The command serves the public explorer API over a database without running a node,
+/// e.g., to browse a backup or a seized copy of the database. The original database
+/// is never written: it is copied into a working directory first (skipping the lock file,
+/// so the database of a running node can be explored as well), and the copy is opened
+/// through [`ReadOnlyDB`], which rejects all changes. Note that a copy of the database
+/// of a running node may miss the latest writes of the node.
+///
+/// The node configuration is optional and is only used for the database options.
+/// Only the explorer API is served; the system and service APIs are not available,
+/// and the submitted transactions are rejected.
+///
+/// [`ReadOnlyDB`]: ../../storage/struct.ReadOnlyDB.html
+#[derive(Debug)]
+pub struct Explore;
+
+impl Explore {
+    /// Default listen address of the explorer API.
+    const DEFAULT_LISTEN_ADDRESS: &'static str = "127.0.0.1:8080";
+    /// Timeout of the node state updates, in milliseconds. There is no node to update
+    /// the state, so the value only affects the defaults of the API.
+    const STATE_UPDATE_TIMEOUT: u64 = 10_000;
+
+    /// Copies the database directory, skipping the `RocksDB` lock file held by the node
+    /// using the database.
+    fn copy_database(from: &Path, to: &Path) -> Result<(), failure::Error> {
+        ensure!(
+            !to.exists(),
+            "Working copy path {} already exists",
+            to.display()
+        );
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            if name == "LOCK" {
+                continue;
+            }
+            if entry.file_type()?.is_dir() {
+                Self::copy_database(&entry.path(), &to.join(&name))?;
+            } else {
+                fs::copy(entry.path(), to.join(&name))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn serve(
+        db_path: &Path,
+        work_path: &Path,
+        options: &DbOptions,
+        listen_address: SocketAddr,
+    ) -> Result<SystemRuntime, failure::Error> {
+        ensure!(
+            db_path.is_dir(),
+            "Database path {} is not a directory",
+            db_path.display()
+        );
+        Self::copy_database(db_path, work_path)?;
+        let options = DbOptions {
+            create_if_missing: false,
+            ..*options
+        };
+        let db: Box<dyn Database> = Box::new(ReadOnlyDB::new(storage::open_database(
+            work_path, &options,
+        )?));
+
+        // The receiver is dropped, so that the transactions sent to the API are rejected.
+        let (api_sender, _) = mpsc::channel(0);
+        let (public_key, secret_key) = crypto::gen_keypair();
+        let blockchain = Blockchain::new(
+            db,
+            Vec::new(),
+            public_key,
+            secret_key,
+            ApiSender::new(api_sender),
+        );
+        let node_state = SharedNodeState::new(Self::STATE_UPDATE_TIMEOUT);
+
+        SystemRuntimeConfig {
+            api_runtimes: vec![ApiRuntimeConfig::new(listen_address, ApiAccess::Public)],
+            api_aggregator: ApiAggregator::explorer_only(blockchain, node_state),
+        }.start()
+    }
+}
+
+impl Command for Explore {
+    fn args(&self) -> Vec<Argument> {
+        vec![
+            Argument::new_named(
+                NODE_CONFIG_PATH,
+                false,
+                "Path to node configuration file with the database options.",
+                "c",
+                "node-config",
+                false,
+            ),
+            Argument::new_named(
+                DATABASE_PATH,
+                true,
+                "Path to the explored database, which is not modified.",
+                "d",
+                "db-path",
+                false,
+            ),
+            Argument::new_named(
+                LISTEN_ADDRESS,
+                false,
+                "Listen address of the explorer API (127.0.0.1:8080 by default).",
+                "l",
+                "listen-address",
+                false,
+            ),
+            Argument::new_named(
+                WORK_DIR,
+                false,
+                "Path to the working copy of the database, which must not exist \
+                 (a new directory in the system temporary directory by default).",
+                "w",
+                "work-dir",
+                false,
+            ),
+        ]
+    }
+
+    fn name(&self) -> CommandName {
+        "explore"
+    }
+
+    fn about(&self) -> &str {
+        "Serves the explorer API over a read-only copy of the database."
+    }
+
+    fn execute(
+        &self,
+        _commands: &HashMap<CommandName, CollectedCommand>,
+        context: Context,
+        _: &dyn Fn(Context) -> Context,
+    ) -> Feedback {
+        let options = context
+            .arg::<String>(NODE_CONFIG_PATH)
+            .ok()
+            .map(|path| {
+                let config: NodeConfig =
+                    ConfigFile::load(path).expect("Can't load node config file");
+                config.database
+            }).unwrap_or_default();
+        let db_path = context
+            .arg::<String>(DATABASE_PATH)
+            .unwrap_or_else(|_| panic!("{} not found.", DATABASE_PATH));
+        let listen_address = context
+            .arg::<SocketAddr>(LISTEN_ADDRESS)
+            .unwrap_or_else(|_| Self::DEFAULT_LISTEN_ADDRESS.parse().unwrap());
+        let work_path = context
+            .arg::<String>(WORK_DIR)
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| env::temp_dir().join(format!("exonum-explore-{}", process::id())));
+
+        let _runtime = Self::serve(Path::new(&db_path), &work_path, &options, listen_address)
+            .unwrap_or_else(|e| panic!("Cannot serve the explorer API: {}", e));
+        println!(
+            "Serving the explorer API on {} over the working copy {}",
+            listen_address,
+            work_path.display()
+        );
+        loop {
+            thread::park();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::{CheckDb, DiffDb};
+    use storage::DbOptions;
+
+    #[test]
+    fn check_db_does_not_create_database() {
+        let dir = TempDir::new("exonum_check_db").unwrap();
+        let db_path = dir.path().join("db");
+        assert!(CheckDb::check(&db_path, &DbOptions::default()).is_err());
+        assert!(!db_path.exists());
+    }
+
+    #[test]
+    fn diff_db_does_not_create_database() {
+        let dir = TempDir::new("exonum_diff_db").unwrap();
+        let db_path = dir.path().join("db");
+        assert!(DiffDb::open(&db_path, &DbOptions::default()).is_err());
+        assert!(!db_path.exists());
+    }
+}
@@ -16,11 +16,18 @@
 
 pub use self::{
     builder::NodeBuilder,
+    chains::{ChainConfig, ChainsConfig, RunChains},
     context_key::ContextKey,
     details::{Finalize, GenerateCommonConfig, GenerateNodeConfig, GenerateTestnet, Run, RunDev},
+    devnet::Devnet,
     internal::Command,
-    maintenance::Maintenance,
+    maintenance::{
+        CheckDb, CheckNetwork, DiffDb, Explore, ExportArchive, ExportBackup, Maintenance,
+        RestoreBackup,
+    },
     shared::{AbstractConfig, CommonConfigTemplate, NodePrivateConfig, NodePublicConfig},
+    test_vectors::{GenerateTestVectors, TestVector, TestVectors},
+    wire_format::DescribeFormat,
 };
 
 use clap;
@@ -33,12 +40,16 @@ use std::{collections::BTreeMap, str::FromStr};
 use blockchain::Service;
 
 mod builder;
+mod chains;
 mod clap_backend;
 mod details;
+mod devnet;
 mod info;
 mod internal;
 mod maintenance;
 mod shared;
+mod test_vectors;
+mod wire_format;
 #[macro_use]
 mod context_key;
 
@@ -66,6 +77,8 @@ pub enum ArgumentType {
     Positional,
     /// Named argument.
     Named(NamedArgument),
+    /// Named argument without a value. Its presence is recorded as `true` in the context.
+    Flag(NamedArgument),
 }
 
 /// Abstraction to represent arguments in command line.
@@ -106,6 +119,28 @@ impl Argument {
         }
     }
 
+    /// Creates a new flag with `long` and optionally `short` names.
+    pub fn new_flag<T>(
+        name: &'static str,
+        help: &'static str,
+        short_name: T,
+        long_name: &'static str,
+    ) -> Self
+    where
+        T: Into<Option<&'static str>>,
+    {
+        Self {
+            argument_type: ArgumentType::Flag(NamedArgument {
+                short_name: short_name.into(),
+                long_name,
+                multiple: false,
+            }),
+            name,
+            help,
+            required: false,
+        }
+    }
+
     /// Creates a new positional argument.
     pub fn new_positional(name: &'static str, required: bool, help: &'static str) -> Self {
         Self {
@@ -162,9 +197,19 @@ pub mod keys {
     pub const PUBLIC_CONFIG_LIST: ContextKey<Vec<NodePublicConfig>> =
         context_key!("public_config_list");
 
+    /// Name of the chain run by the node.
+    /// Set by `run-chains` command.
+    pub const CHAIN_NAME: ContextKey<String> = context_key!("chain_name");
+
     /// Auditor mode.
     /// Set by `finalize` command.
     pub const AUDITOR_MODE: ContextKey<bool> = context_key!("auditor_mode");
+
+    /// Batches of hex-encoded signed transactions sent to the network once it is started.
+    /// Each batch is sent after all the transactions of the previous batch are committed.
+    /// Set by the extensions of `devnet` command.
+    pub const DEVNET_TRANSACTIONS: ContextKey<Vec<Vec<String>>> =
+        context_key!("devnet_transactions");
 }
 
 /// `Context` is a type, used to keep some values from `Command` into
@@ -207,6 +252,12 @@ impl Context {
                         continue;
                     }
                 }
+                ArgumentType::Flag(_) => {
+                    if matches.is_present(&arg.name) {
+                        context.args.insert(arg.name.to_owned(), true.to_string());
+                    }
+                    continue;
+                }
                 _ => (),
             };
 
@@ -0,0 +1,298 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module implements the generator of test vectors for the light clients.
+//!
+//! Test vectors contain canonical serialization, hash and signature of each message type
+//! created from fixed keys and field values, so that the clients implemented in other
+//! languages can check their serializers against the Rust implementation.
+
+use bit_vec::BitVec;
+use chrono::{TimeZone, Utc};
+use hex;
+use serde::Serialize;
+use serde_json::{self, Value};
+
+use std::{collections::HashMap, fs::File};
+
+use super::{
+    internal::{CollectedCommand, Command, Feedback},
+    Argument, CommandName, Context,
+};
+use blockchain::Block;
+use crypto::{
+    gen_keypair_from_seed, hash, CryptoHash, Hash, PublicKey, SecretKey, Seed, SEED_LENGTH,
+    SIGNATURE_LENGTH,
+};
+use helpers::{Height, Round, ValidatorId};
+use messages::{
+    BlockRequest, BlockResponse, Connect, Message, PeersRequest, Ping, Pong, Precommit, Prevote,
+    PrevotesRequest, Propose, ProposeRequest, ProtocolMessage, ServiceTransaction, Signed, Status,
+    TransactionsRequest, TransactionsResponse,
+};
+
+// Context entry for the path to the output file.
+const OUTPUT_PATH: &str = "OUTPUT_PATH";
+
+/// Seed of the keys signing the messages.
+const AUTHOR_SEED: [u8; SEED_LENGTH] = [1; SEED_LENGTH];
+/// Seed of the keys of the request recipient.
+const RECIPIENT_SEED: [u8; SEED_LENGTH] = [2; SEED_LENGTH];
+/// Identifier of the service of the sample transaction.
+const SERVICE_ID: u16 = 128;
+
+/// Serialized form of a single message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestVector {
+    /// Name of the message type.
+    pub name: String,
+    /// JSON representation of the message payload.
+    pub payload: Value,
+    /// Hex-encoded serialized message including the signature.
+    pub bytes: String,
+    /// Hash of the serialized message.
+    pub hash: Hash,
+    /// Hex-encoded signature of the message.
+    pub signature: String,
+}
+
+/// Test vectors for all the message types.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestVectors {
+    /// Hex-encoded seed of the author keys.
+    pub seed: String,
+    /// Public key of the messages author.
+    pub public_key: PublicKey,
+    /// Secret key of the messages author.
+    pub secret_key: SecretKey,
+    /// Public key of the requests recipient.
+    pub recipient: PublicKey,
+    /// Test vectors of the messages.
+    pub vectors: Vec<TestVector>,
+}
+
+impl TestVectors {
+    /// Generates test vectors. The result does not depend on the environment.
+    pub fn generate() -> Self {
+        let (public_key, secret_key) = gen_keypair_from_seed(&Seed::new(AUTHOR_SEED));
+        let (recipient, _) = gen_keypair_from_seed(&Seed::new(RECIPIENT_SEED));
+        let keys = (public_key, secret_key.clone());
+
+        let time = Utc.timestamp(1_500_000_000, 0);
+        let prev_hash = hash(b"prev_hash");
+        let state_hash = hash(b"state_hash");
+
+        let tx = Message::sign_transaction(
+            ServiceTransaction::from_raw_unchecked(0, vec![1, 2, 3]),
+            SERVICE_ID,
+            public_key,
+            &secret_key,
+        );
+        let tx_hashes = [tx.hash()];
+        let propose = sign(
+            Propose::new(ValidatorId(0), Height(1), Round(1), &prev_hash, &tx_hashes),
+            &keys,
+        );
+        let propose_hash = propose.hash();
+        let block = Block::new(
+            ValidatorId(0),
+            Height(1),
+            1,
+            &prev_hash,
+            &hash(b"tx_hash"),
+            &state_hash,
+        );
+        let precommit = sign(
+            Precommit::new(
+                ValidatorId(0),
+                Height(1),
+                Round(1),
+                &propose_hash,
+                &block.hash(),
+                time,
+            ),
+            &keys,
+        );
+
+        let vectors = vec![
+            test_vector("RawTransaction", &tx),
+            test_vector(
+                "Connect",
+                &sign(
+                    Connect::new(
+                        "127.0.0.1:6333".parse().unwrap(),
+                        time,
+                        "exonum 0.9.0/rustc 1.29.0",
+                    ),
+                    &keys,
+                ),
+            ),
+            test_vector("Status", &sign(Status::new(Height(1), &prev_hash), &keys)),
+            test_vector("Propose", &propose),
+            test_vector(
+                "Prevote",
+                &sign(
+                    Prevote::new(ValidatorId(0), Height(1), Round(1), &propose_hash, Round(0)),
+                    &keys,
+                ),
+            ),
+            test_vector("Precommit", &precommit),
+            test_vector(
+                "BlockResponse",
+                &sign(
+                    BlockResponse::new(
+                        &recipient,
+                        block,
+                        vec![precommit.signed_message().raw().to_vec()],
+                        &tx_hashes,
+                    ),
+                    &keys,
+                ),
+            ),
+            test_vector(
+                "TransactionsResponse",
+                &sign(
+                    TransactionsResponse::new(&recipient, vec![tx.signed_message().raw().to_vec()]),
+                    &keys,
+                ),
+            ),
+            test_vector(
+                "ProposeRequest",
+                &sign(
+                    ProposeRequest::new(&recipient, Height(1), &propose_hash),
+                    &keys,
+                ),
+            ),
+            test_vector(
+                "TransactionsRequest",
+                &sign(TransactionsRequest::new(&recipient, &tx_hashes), &keys),
+            ),
+            test_vector(
+                "PrevotesRequest",
+                &sign(
+                    PrevotesRequest::new(
+                        &recipient,
+                        Height(1),
+                        Round(1),
+                        &propose_hash,
+                        BitVec::from_elem(4, true),
+                    ),
+                    &keys,
+                ),
+            ),
+            test_vector("PeersRequest", &sign(PeersRequest::new(&recipient), &keys)),
+            test_vector(
+                "BlockRequest",
+                &sign(BlockRequest::new(&recipient, Height(1)), &keys),
+            ),
+            test_vector("Ping", &sign(Ping::new(&recipient, time), &keys)),
+            test_vector("Pong", &sign(Pong::new(&recipient, time), &keys)),
+        ];
+
+        Self {
+            seed: hex::encode(&AUTHOR_SEED[..]),
+            public_key,
+            secret_key,
+            recipient,
+            vectors,
+        }
+    }
+}
+
+/// Signs the message with the given keys.
+fn sign<T: ProtocolMessage>(message: T, keys: &(PublicKey, SecretKey)) -> Signed<T> {
+    Message::concrete(message, keys.0, &keys.1)
+}
+
+/// Converts the signed message into the test vector.
+fn test_vector<T: ProtocolMessage + Serialize>(name: &str, signed: &Signed<T>) -> TestVector {
+    let raw = signed.signed_message().raw();
+    TestVector {
+        name: name.to_owned(),
+        payload: serde_json::to_value(signed.payload()).expect("Cannot serialize payload"),
+        bytes: hex::encode(raw),
+        hash: signed.hash(),
+        signature: hex::encode(&raw[raw.len() - SIGNATURE_LENGTH..]),
+    }
+}
+
+/// Command generating test vectors for the light clients.
+#[derive(Debug)]
+pub struct GenerateTestVectors;
+
+impl Command for GenerateTestVectors {
+    fn args(&self) -> Vec<Argument> {
+        vec![Argument::new_positional(
+            OUTPUT_PATH,
+            true,
+            "Path to the output JSON file.",
+        )]
+    }
+
+    fn name(&self) -> CommandName {
+        "gen-test-vectors"
+    }
+
+    fn about(&self) -> &str {
+        "Generates serialized messages with hashes and signatures for testing light clients."
+    }
+
+    fn execute(
+        &self,
+        _commands: &HashMap<CommandName, CollectedCommand>,
+        context: Context,
+        _: &dyn Fn(Context) -> Context,
+    ) -> Feedback {
+        let path = context
+            .arg::<String>(OUTPUT_PATH)
+            .unwrap_or_else(|_| panic!("{} not found.", OUTPUT_PATH));
+        let file = File::create(&path).expect("Can't create test vectors file");
+        serde_json::to_writer_pretty(file, &TestVectors::generate())
+            .expect("Can't write test vectors file");
+        Feedback::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::{self, Signature};
+
+    #[test]
+    fn test_vectors_are_deterministic() {
+        assert_eq!(TestVectors::generate(), TestVectors::generate());
+    }
+
+    #[test]
+    fn test_vectors_are_consistent() {
+        let vectors = TestVectors::generate();
+        for vector in &vectors.vectors {
+            let bytes = hex::decode(&vector.bytes).unwrap();
+            assert_eq!(crypto::hash(&bytes), vector.hash, "{}", vector.name);
+
+            let (data, signature) = bytes.split_at(bytes.len() - SIGNATURE_LENGTH);
+            let signature = Signature::from_slice(signature).unwrap();
+            assert!(
+                crypto::verify(&signature, data, &vectors.public_key),
+                "{}",
+                vector.name
+            );
+            assert!(
+                Message::from_raw_buffer(bytes.clone()).is_ok(),
+                "{}",
+                vector.name
+            );
+        }
+    }
+}
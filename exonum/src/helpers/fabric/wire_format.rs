@@ -0,0 +1,92 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module implements the command describing the binary format of the core structures.
+
+use serde_json;
+
+use std::{collections::HashMap, fs::File};
+
+use super::{
+    internal::{CollectedCommand, Command, Feedback},
+    Argument, CommandName, Context,
+};
+use encoding::{check_layouts, core_layouts, StructLayout};
+
+// Context entry for the path to the output file.
+const OUTPUT_PATH: &str = "OUTPUT_PATH";
+// Context entry for the path to the snapshot to check against.
+const SNAPSHOT_PATH: &str = "SNAPSHOT_PATH";
+
+/// Command printing the layouts of the protocol messages and the core structures,
+/// or checking them against a previously saved snapshot.
+#[derive(Debug)]
+pub struct DescribeFormat;
+
+impl Command for DescribeFormat {
+    fn args(&self) -> Vec<Argument> {
+        vec![
+            Argument::new_named(
+                OUTPUT_PATH,
+                false,
+                "Path to the output JSON file. The layouts are printed to stdout if not set.",
+                "o",
+                "output",
+                false,
+            ),
+            Argument::new_named(
+                SNAPSHOT_PATH,
+                false,
+                "Path to the snapshot JSON file to check the layouts against.",
+                "c",
+                "check",
+                false,
+            ),
+        ]
+    }
+
+    fn name(&self) -> CommandName {
+        "describe-format"
+    }
+
+    fn about(&self) -> &str {
+        "Describes the binary format of the protocol messages and the core structures."
+    }
+
+    fn execute(
+        &self,
+        _commands: &HashMap<CommandName, CollectedCommand>,
+        context: Context,
+        _: &dyn Fn(Context) -> Context,
+    ) -> Feedback {
+        let layouts = core_layouts();
+
+        if let Ok(path) = context.arg::<String>(SNAPSHOT_PATH) {
+            let file = File::open(&path).expect("Can't open snapshot file");
+            let expected: Vec<StructLayout> =
+                serde_json::from_reader(file).expect("Can't parse snapshot file");
+            match check_layouts(&expected, &layouts) {
+                Ok(()) => println!("Binary format matches the snapshot"),
+                Err(e) => panic!("{}", e),
+            }
+        } else if let Ok(path) = context.arg::<String>(OUTPUT_PATH) {
+            let file = File::create(&path).expect("Can't create output file");
+            serde_json::to_writer_pretty(file, &layouts).expect("Can't write output file");
+        } else {
+            let output = serde_json::to_string_pretty(&layouts).expect("Can't serialize layouts");
+            println!("{}", output);
+        }
+        Feedback::None
+    }
+}
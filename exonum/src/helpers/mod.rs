@@ -14,7 +14,7 @@
 
 //! Different assorted utilities.
 
-pub use self::types::{Height, Milliseconds, Round, ValidatorId};
+pub use self::types::{Height, HeightRangeIter, Milliseconds, Round, RoundRangeIter, ValidatorId};
 
 pub mod config;
 pub mod fabric;
@@ -74,6 +74,21 @@ pub fn generate_testnet_config(count: u16, start_port: u16) -> Vec<NodeConfig> {
             mempool: Default::default(),
             services_configs: Default::default(),
             database: Default::default(),
-            thread_pool_size: Default::default(),
+            threads: Default::default(),
+            plugins: Default::default(),
+            propose_precheck: false,
+            compaction: None,
+            status_file: None,
+            consensus_archive: None,
+            ping_interval: None,
+            max_clock_skew: None,
+            propose_ahead: false,
+            cold_storage: None,
+            memory_db: None,
+            pruning: None,
+            alerting: None,
+            paranoid: false,
+            replica: None,
+            forensics: None,
         }).collect::<Vec<_>>()
 }
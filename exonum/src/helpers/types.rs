@@ -110,6 +110,124 @@ impl Height {
         assert_ne!(0, self.0);
         self.0 -= 1;
     }
+
+    /// Returns next value of the height or `None` if the height is equal to `u64::max_value()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::helpers::Height;
+    ///
+    /// assert_eq!(Some(Height(11)), Height(10).checked_next());
+    /// assert_eq!(None, Height(u64::max_value()).checked_next());
+    /// ```
+    pub fn checked_next(self) -> Option<Self> {
+        self.checked_add(1)
+    }
+
+    /// Returns previous value of the height or `None` if the height is equal to zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::helpers::Height;
+    ///
+    /// assert_eq!(Some(Height(9)), Height(10).checked_previous());
+    /// assert_eq!(None, Height::zero().checked_previous());
+    /// ```
+    pub fn checked_previous(self) -> Option<Self> {
+        self.checked_sub(1)
+    }
+
+    /// Adds `count` blocks to the height, returning `None` on overflow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::helpers::Height;
+    ///
+    /// assert_eq!(Some(Height(15)), Height(10).checked_add(5));
+    /// assert_eq!(None, Height(u64::max_value()).checked_add(1));
+    /// ```
+    pub fn checked_add(self, count: u64) -> Option<Self> {
+        self.0.checked_add(count).map(Height)
+    }
+
+    /// Subtracts `count` blocks from the height, returning `None` on underflow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::helpers::Height;
+    ///
+    /// assert_eq!(Some(Height(5)), Height(10).checked_sub(5));
+    /// assert_eq!(None, Height(10).checked_sub(11));
+    /// ```
+    pub fn checked_sub(self, count: u64) -> Option<Self> {
+        self.0.checked_sub(count).map(Height)
+    }
+
+    /// Adds `count` blocks to the height, saturating at `u64::max_value()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::helpers::Height;
+    ///
+    /// assert_eq!(Height(15), Height(10).saturating_add(5));
+    /// assert_eq!(Height(u64::max_value()), Height(u64::max_value()).saturating_add(1));
+    /// ```
+    pub fn saturating_add(self, count: u64) -> Self {
+        Height(self.0.saturating_add(count))
+    }
+
+    /// Subtracts `count` blocks from the height, saturating at zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::helpers::Height;
+    ///
+    /// assert_eq!(Height(5), Height(10).saturating_sub(5));
+    /// assert_eq!(Height::zero(), Height(10).saturating_sub(11));
+    /// ```
+    pub fn saturating_sub(self, count: u64) -> Self {
+        Height(self.0.saturating_sub(count))
+    }
+
+    /// Returns the number of blocks between `self` and `to`, or `None` if `to`
+    /// is less than `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::helpers::Height;
+    ///
+    /// assert_eq!(Some(5), Height(10).distance_to(Height(15)));
+    /// assert_eq!(None, Height(10).distance_to(Height(5)));
+    /// ```
+    pub fn distance_to(self, to: Self) -> Option<u64> {
+        to.0.checked_sub(self.0)
+    }
+
+    /// Returns the iterator over heights in the range from `self` to `to - 1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::helpers::Height;
+    ///
+    /// let heights: Vec<_> = Height(1).iter_to(Height(4)).collect();
+    /// assert_eq!(heights, vec![Height(1), Height(2), Height(3)]);
+    /// let heights: Vec<_> = Height(1).iter_to(Height(4)).rev().collect();
+    /// assert_eq!(heights, vec![Height(3), Height(2), Height(1)]);
+    /// ```
+    pub fn iter_to(self, to: Self) -> HeightRangeIter {
+        HeightRangeIter {
+            next: self,
+            last: to,
+        }
+    }
 }
 
 /// Consensus round index.
@@ -215,6 +333,90 @@ impl Round {
         self.0 -= 1;
     }
 
+    /// Returns next value of the round or `None` if the round is equal to `u32::max_value()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::helpers::Round;
+    ///
+    /// assert_eq!(Some(Round(21)), Round(20).checked_next());
+    /// assert_eq!(None, Round(u32::max_value()).checked_next());
+    /// ```
+    pub fn checked_next(self) -> Option<Self> {
+        self.checked_add(1)
+    }
+
+    /// Returns previous value of the round or `None` if the round is equal to zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::helpers::Round;
+    ///
+    /// assert_eq!(Some(Round(9)), Round(10).checked_previous());
+    /// assert_eq!(None, Round::zero().checked_previous());
+    /// ```
+    pub fn checked_previous(self) -> Option<Self> {
+        self.checked_sub(1)
+    }
+
+    /// Adds `count` rounds to the round, returning `None` on overflow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::helpers::Round;
+    ///
+    /// assert_eq!(Some(Round(15)), Round(10).checked_add(5));
+    /// assert_eq!(None, Round(u32::max_value()).checked_add(1));
+    /// ```
+    pub fn checked_add(self, count: u32) -> Option<Self> {
+        self.0.checked_add(count).map(Round)
+    }
+
+    /// Subtracts `count` rounds from the round, returning `None` on underflow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::helpers::Round;
+    ///
+    /// assert_eq!(Some(Round(5)), Round(10).checked_sub(5));
+    /// assert_eq!(None, Round(10).checked_sub(11));
+    /// ```
+    pub fn checked_sub(self, count: u32) -> Option<Self> {
+        self.0.checked_sub(count).map(Round)
+    }
+
+    /// Adds `count` rounds to the round, saturating at `u32::max_value()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::helpers::Round;
+    ///
+    /// assert_eq!(Round(15), Round(10).saturating_add(5));
+    /// assert_eq!(Round(u32::max_value()), Round(u32::max_value()).saturating_add(1));
+    /// ```
+    pub fn saturating_add(self, count: u32) -> Self {
+        Round(self.0.saturating_add(count))
+    }
+
+    /// Subtracts `count` rounds from the round, saturating at zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::helpers::Round;
+    ///
+    /// assert_eq!(Round(5), Round(10).saturating_sub(5));
+    /// assert_eq!(Round::zero(), Round(10).saturating_sub(11));
+    /// ```
+    pub fn saturating_sub(self, count: u32) -> Self {
+        Round(self.0.saturating_sub(count))
+    }
+
     /// Returns the iterator over rounds in the range from `self` to `to - 1`.
     ///
     /// # Examples
@@ -268,6 +470,12 @@ impl From<Height> for u64 {
     }
 }
 
+impl From<u64> for Height {
+    fn from(val: u64) -> Self {
+        Height(val)
+    }
+}
+
 impl fmt::Display for Round {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -292,6 +500,12 @@ impl From<Round> for u64 {
     }
 }
 
+impl From<u32> for Round {
+    fn from(val: u32) -> Self {
+        Round(val)
+    }
+}
+
 impl fmt::Display for ValidatorId {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -359,3 +573,51 @@ impl Iterator for RoundRangeIter {
         }
     }
 }
+
+impl DoubleEndedIterator for RoundRangeIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.next < self.last {
+            self.last.decrement();
+            Some(self.last)
+        } else {
+            None
+        }
+    }
+}
+
+/// Iterator over heights range.
+#[derive(Debug)]
+pub struct HeightRangeIter {
+    next: Height,
+    last: Height,
+}
+
+impl Iterator for HeightRangeIter {
+    type Item = Height;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next < self.last {
+            let res = Some(self.next);
+            self.next.increment();
+            res
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.next.distance_to(self.last).unwrap_or(0) as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for HeightRangeIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.next < self.last {
+            self.last.decrement();
+            Some(self.last)
+        } else {
+            None
+        }
+    }
+}
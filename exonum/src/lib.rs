@@ -55,9 +55,11 @@ extern crate bytes;
 extern crate chrono;
 #[macro_use(crate_version, crate_authors)]
 extern crate clap;
+extern crate core_affinity;
 extern crate env_logger;
 extern crate erased_serde;
 pub extern crate exonum_crypto as crypto;
+#[cfg(feature = "rocksdb")]
 extern crate exonum_rocksdb as rocksdb;
 #[cfg(feature = "sodiumoxide-crypto")]
 extern crate exonum_sodiumoxide as sodiumoxide;
@@ -65,16 +67,26 @@ extern crate exonum_sodiumoxide as sodiumoxide;
 extern crate failure;
 extern crate futures;
 extern crate hex;
+#[cfg(feature = "graphql")]
+#[macro_use]
+extern crate juniper;
+#[cfg(unix)]
+extern crate libc;
+#[cfg(feature = "plugins")]
+extern crate libloading;
 #[macro_use]
 extern crate log;
+extern crate num_cpus;
 extern crate os_info;
 extern crate rand;
+extern crate rmp_serde;
 extern crate rust_decimal;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
 #[macro_use]
 extern crate serde_json;
+extern crate serde_cbor;
 extern crate snow;
 extern crate tokio;
 extern crate tokio_codec;
@@ -93,6 +105,9 @@ extern crate vec_map;
 #[macro_use]
 extern crate lazy_static;
 #[cfg(test)]
+#[macro_use]
+extern crate proptest;
+#[cfg(test)]
 extern crate tempdir;
 #[cfg(all(test, feature = "long_benchmarks"))]
 extern crate test;
@@ -111,6 +126,7 @@ pub mod events;
 pub mod explorer;
 pub mod node;
 pub mod storage;
+pub mod verify;
 
 #[cfg(test)]
 mod sandbox;
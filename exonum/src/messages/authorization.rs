@@ -36,11 +36,7 @@ impl SignedMessage {
         author: PublicKey,
         secret_key: &SecretKey,
     ) -> SignedMessage {
-        let mut buffer = Vec::with_capacity(2 + value.len() + PUBLIC_KEY_LENGTH + SIGNATURE_LENGTH);
-        buffer.extend_from_slice(author.as_ref());
-        buffer.push(class);
-        buffer.push(tag);
-        buffer.extend_from_slice(value);
+        let mut buffer = Self::signing_data(class, tag, value, author);
         let signature = Self::sign(&buffer, secret_key).expect("Couldn't form signature");
         buffer.extend_from_slice(signature.as_ref());
         SignedMessage { raw: buffer }
@@ -55,13 +51,25 @@ impl SignedMessage {
         author: PublicKey,
         signature: Signature,
     ) -> SignedMessage {
+        let mut buffer = Self::signing_data(class, tag, value, author);
+        buffer.extend_from_slice(signature.as_ref());
+        SignedMessage { raw: buffer }
+    }
+
+    /// Returns the part of the message buffer covered by the signature,
+    /// i.e., everything except the signature itself.
+    pub(in messages) fn signing_data(
+        class: u8,
+        tag: u8,
+        value: &[u8],
+        author: PublicKey,
+    ) -> Vec<u8> {
         let mut buffer = Vec::with_capacity(2 + value.len() + PUBLIC_KEY_LENGTH + SIGNATURE_LENGTH);
         buffer.extend_from_slice(author.as_ref());
         buffer.push(class);
         buffer.push(tag);
         buffer.extend_from_slice(value);
-        buffer.extend_from_slice(signature.as_ref());
-        SignedMessage { raw: buffer }
+        buffer
     }
 
     /// Creates `SignedMessage` wrapper from the raw buffer.
@@ -82,6 +90,27 @@ impl SignedMessage {
         Ok(signed)
     }
 
+    /// Creates `SignedMessage` wrapper from the raw buffer without rejecting messages
+    /// with an invalid signature. Checks binary format and returns the message together
+    /// with the result of the signature verification.
+    ///
+    /// Intended for diagnostic purposes only, since the returned message breaks
+    /// the guarantee of having a correct signature.
+    pub(crate) fn from_raw_buffer_unverified(buffer: Vec<u8>) -> Result<(Self, bool), Error> {
+        ensure!(
+            buffer.len() > EMPTY_SIGNED_MESSAGE_SIZE,
+            "Message too short message_len = {}",
+            buffer.len()
+        );
+        let signed = SignedMessage { raw: buffer };
+
+        let pk = signed.author();
+        let signature = signed.signature();
+        let is_valid = Self::verify(signed.data_without_signature(), &signature, &pk).is_ok();
+
+        Ok((signed, is_valid))
+    }
+
     fn data_without_signature(&self) -> &[u8] {
         debug_assert!(self.raw.len() > EMPTY_SIGNED_MESSAGE_SIZE);
         let sign_idx = self.raw.len() - SIGNATURE_LENGTH;
@@ -0,0 +1,115 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Building transactions that are signed outside of the Rust process.
+
+use failure::Error;
+
+use super::{
+    BinaryForm, ProtocolMessage, RawTransaction, ServiceTransaction, Signed, SignedMessage,
+};
+use crypto::{self, PublicKey, SecretKey, Signature};
+
+/// Builder of `Signed<RawTransaction>` messages.
+///
+/// The builder allows to split the creation of a transaction into separate steps:
+/// the payload is fixed first, then the exact bytes to be signed are
+/// obtained with `signing_bytes`, and finally the externally produced signature
+/// is attached with `with_signature`. This way hardware security modules and
+/// mobile clients can sign transactions without passing the secret key to the node.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate exonum;
+/// # use exonum::crypto;
+/// # use exonum::messages::{ServiceTransaction, TransactionBuilder};
+/// # fn main() {
+/// let (public_key, secret_key) = crypto::gen_keypair();
+/// let transaction = ServiceTransaction::from_raw_unchecked(0, vec![1, 2, 3]);
+/// let builder = TransactionBuilder::new(transaction, 128, public_key);
+///
+/// // The signature may be produced by an external device.
+/// let signature = crypto::sign(&builder.signing_bytes(), &secret_key);
+/// let signed = builder.with_signature(signature).unwrap();
+/// assert_eq!(signed.author(), public_key);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionBuilder {
+    author: PublicKey,
+    transaction: RawTransaction,
+    payload: Vec<u8>,
+}
+
+impl TransactionBuilder {
+    /// Creates a builder for a transaction of the specified service, which will be
+    /// signed by the owner of `author` key.
+    ///
+    /// # Panics
+    ///
+    /// This method can panic on serialization failure.
+    pub fn new<T>(transaction: T, service_id: u16, author: PublicKey) -> Self
+    where
+        T: Into<ServiceTransaction>,
+    {
+        let transaction = RawTransaction::new(service_id, transaction.into());
+        let payload = transaction.encode().expect("Couldn't serialize data.");
+        TransactionBuilder {
+            author,
+            transaction,
+            payload,
+        }
+    }
+
+    /// Returns public key of the transaction author.
+    pub fn author(&self) -> PublicKey {
+        self.author
+    }
+
+    /// Returns the unsigned transaction.
+    pub fn transaction(&self) -> &RawTransaction {
+        &self.transaction
+    }
+
+    /// Returns the exact bytes that must be signed by the transaction author.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let (class, tag) = RawTransaction::message_type();
+        SignedMessage::signing_data(class, tag, &self.payload, self.author)
+    }
+
+    /// Checks whether `signature` is a valid signature of the transaction by its author.
+    pub fn verify(&self, signature: &Signature) -> bool {
+        crypto::verify(signature, &self.signing_bytes(), &self.author)
+    }
+
+    /// Attaches an externally produced signature to the transaction.
+    ///
+    /// Returns an error if the signature does not match the transaction and its author.
+    pub fn with_signature(self, signature: Signature) -> Result<Signed<RawTransaction>, Error> {
+        let mut buffer = self.signing_bytes();
+        buffer.extend_from_slice(signature.as_ref());
+        let signed = SignedMessage::from_raw_buffer(buffer)?;
+        Ok(self.transaction.into_message_from_parts(signed))
+    }
+
+    /// Signs the transaction with the given secret key.
+    ///
+    /// This is equivalent to `Message::sign_transaction`.
+    pub fn sign(self, secret_key: &SecretKey) -> Signed<RawTransaction> {
+        let signature = crypto::sign(&self.signing_bytes(), secret_key);
+        self.with_signature(signature)
+            .expect("Secret key does not correspond to the transaction author")
+    }
+}
@@ -43,6 +43,7 @@ use storage::StorageValue;
 
 pub(crate) use self::{authorization::SignedMessage, helpers::HexStringRepresentation};
 pub use self::{
+    builder::TransactionBuilder,
     helpers::{to_hex_string, BinaryForm},
     protocol::*,
 };
@@ -50,13 +51,14 @@ pub use self::{
 #[macro_use]
 mod compatibility;
 mod authorization;
+mod builder;
 mod helpers;
 mod protocol;
 #[cfg(test)]
 mod tests;
 
 /// Version of the protocol. Different versions are incompatible.
-pub const PROTOCOL_MAJOR_VERSION: u8 = 1;
+pub const PROTOCOL_MAJOR_VERSION: u8 = 3;
 pub(crate) const RAW_TRANSACTION_HEADER: usize = mem::size_of::<u16>() * 2;
 
 /// Transaction raw buffer.
@@ -30,7 +30,7 @@ use bit_vec::BitVec;
 use chrono::{DateTime, Utc};
 use failure;
 
-use std::{borrow::Cow, fmt::Debug, mem};
+use std::{borrow::Cow, fmt::Debug, mem, net::SocketAddr};
 
 use super::{BinaryForm, RawTransaction, ServiceTransaction, Signed, SignedMessage};
 use blockchain;
@@ -68,7 +68,7 @@ encoding_struct! {
     /// message after receiving `node::Event::Connected`.
     struct Connect {
         /// The node's address.
-        pub_addr: &str,
+        pub_addr: SocketAddr,
         /// Time when the message was created.
         time: DateTime<Utc>,
         /// String containing information about this node including Exonum, Rust and OS versions.
@@ -342,6 +342,44 @@ encoding_struct! {
         height: Height,
     }
 }
+encoding_struct! {
+    /// Request measuring the round-trip time to a peer.
+    ///
+    /// ### Validation
+    /// The message is ignored if it is addressed to another peer.
+    ///
+    /// ### Processing
+    /// `Pong` message with the same `time` is sent as the response.
+    ///
+    /// ### Generation
+    /// `Ping` message is sent to every connected peer regularly if the latency measurement
+    /// is enabled with `NodeConfig::ping_interval`.
+    struct Ping {
+        /// Public key of the recipient.
+        to: &PublicKey,
+        /// Time when the message was created.
+        time: DateTime<Utc>,
+    }
+}
+encoding_struct! {
+    /// Response to the `Ping` message.
+    ///
+    /// ### Validation
+    /// The message is ignored if it is addressed to another peer.
+    ///
+    /// ### Processing
+    /// The round-trip time to the peer is computed as the difference between
+    /// the current time and `time`.
+    ///
+    /// ### Generation
+    /// A node sends `Pong` as the response to `Ping`.
+    struct Pong {
+        /// Public key of the recipient.
+        to: &PublicKey,
+        /// Time copied from the `Ping` message.
+        time: DateTime<Utc>,
+    }
+}
 
 impl BlockResponse {
     /// Verify Merkle root of transactions in the block.
@@ -525,6 +563,8 @@ impl_protocol! {
             TransactionsResponse = 0,
             /// Information about block, that sent as response to `BlockRequest`.
             BlockResponse = 1,
+            /// Response to `Ping`.
+            Pong = 2,
         },
         /// Exonum node requests.
         3 => Requests {
@@ -538,6 +578,8 @@ impl_protocol! {
             PeersRequest = 3,
             /// Request of some future block.
             BlockRequest = 4,
+            /// Request measuring the round-trip time.
+            Ping = 5,
         },
 
     }
@@ -609,6 +651,7 @@ impl Requests {
             Requests::PrevotesRequest(ref msg) => msg.to(),
             Requests::PeersRequest(ref msg) => msg.to(),
             Requests::BlockRequest(ref msg) => msg.to(),
+            Requests::Ping(ref msg) => msg.to(),
         }
     }
 
@@ -620,6 +663,7 @@ impl Requests {
             Requests::PrevotesRequest(ref msg) => msg.author(),
             Requests::PeersRequest(ref msg) => msg.author(),
             Requests::BlockRequest(ref msg) => msg.author(),
+            Requests::Ping(ref msg) => msg.author(),
         }
     }
 }
@@ -660,6 +704,15 @@ impl Consensus {
             Consensus::Precommit(ref msg) => msg.round(),
         }
     }
+
+    /// Returns size of the serialized message in bytes.
+    pub fn size(&self) -> usize {
+        match *self {
+            Consensus::Propose(ref msg) => msg.signed_message().raw().len(),
+            Consensus::Prevote(ref msg) => msg.signed_message().raw().len(),
+            Consensus::Precommit(ref msg) => msg.signed_message().raw().len(),
+        }
+    }
 }
 
 impl<T: ProtocolMessage> From<Signed<T>> for Message {
@@ -3,11 +3,11 @@ use hex::{self, FromHex};
 
 use super::{
     BinaryForm, BlockResponse, Message, Precommit, ProtocolMessage, RawTransaction,
-    ServiceTransaction, Signed, SignedMessage, Status, TransactionsResponse,
+    ServiceTransaction, Signed, SignedMessage, Status, TransactionBuilder, TransactionsResponse,
     RAW_TRANSACTION_EMPTY_SIZE, TRANSACTION_RESPONSE_EMPTY_SIZE,
 };
 use blockchain::{Block, BlockProof};
-use crypto::{gen_keypair, hash, PublicKey, SecretKey};
+use crypto::{self, gen_keypair, hash, PublicKey, SecretKey};
 use helpers::{Height, Round, ValidatorId};
 
 #[test]
@@ -165,3 +165,35 @@ fn test_raw_transaction_small_size() {
     assert_eq!(tx.service_id, 0);
     assert_eq!(tx.service_transaction.transaction_id, 0);
 }
+
+#[test]
+fn test_transaction_builder_external_signature() {
+    let (public_key, secret_key) = gen_keypair();
+    let transaction = ServiceTransaction::from_raw_unchecked(2, vec![1, 2, 3, 4]);
+    let builder = TransactionBuilder::new(transaction.clone(), 5, public_key);
+
+    let signature = crypto::sign(&builder.signing_bytes(), &secret_key);
+    assert!(builder.verify(&signature));
+    let signed = builder.clone().with_signature(signature).unwrap();
+
+    let expected = Message::sign_transaction(transaction, 5, public_key, &secret_key);
+    assert_eq!(signed, expected);
+    assert_eq!(signed.author(), public_key);
+    assert_eq!(builder.sign(&secret_key), expected);
+
+    let raw = signed.signed_message().raw().to_vec();
+    let message = Message::from_raw_buffer(raw).unwrap();
+    assert_eq!(RawTransaction::try_from(message).unwrap(), expected);
+}
+
+#[test]
+fn test_transaction_builder_invalid_signature() {
+    let (public_key, _) = gen_keypair();
+    let (_, other_secret_key) = gen_keypair();
+    let transaction = ServiceTransaction::from_raw_unchecked(0, vec![1, 2, 3]);
+    let builder = TransactionBuilder::new(transaction, 1, public_key);
+
+    let signature = crypto::sign(&builder.signing_bytes(), &other_secret_key);
+    assert!(!builder.verify(&signature));
+    assert!(builder.with_signature(signature).is_err());
+}
@@ -0,0 +1,618 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Alerting on the node health conditions.
+//!
+//! The node evaluates the configured conditions after each block commit and each time
+//! the API state is updated (see `NodeApiConfig::state_update_timeout`). When a condition
+//! starts or stops holding, an alert is dispatched to all configured sinks:
+//!
+//! ```toml
+//! [alerting]
+//! # Alert if the height does not change for more than a minute.
+//! height_stalled_secs = 60
+//! # Alert if the node is connected to less validators than required for the consensus.
+//! peers_below_quorum = true
+//! # Alert if the memory pool contains more than 10000 transactions.
+//! max_mempool_size = 10000
+//!
+//! # Alert if there is less than 1 GB free on the database disk.
+//! [alerting.free_disk_space]
+//! path = "/var/lib/exonum"
+//! min_bytes = 1073741824
+//!
+//! [[alerting.sinks]]
+//! type = "log"
+//!
+//! # The alert is sent as a JSON body of the POST request.
+//! [[alerting.sinks]]
+//! type = "webhook"
+//! url = "http://127.0.0.1:9000/alerts"
+//!
+//! # The alert is sent with the local `sendmail` binary.
+//! [[alerting.sinks]]
+//! type = "email"
+//! to = ["ops@example.com"]
+//! ```
+//!
+//! Webhook and email notifications are sent from a separate thread, so that slow sinks
+//! do not affect the consensus.
+
+use chrono::{DateTime, Utc};
+use serde_json;
+
+use std::{
+    collections::HashSet,
+    fmt,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use helpers::Height;
+
+/// Timeout for the network operations of the alert sinks.
+const SINK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Path to the `sendmail` binary used by default by the email sink.
+const DEFAULT_SENDMAIL: &str = "/usr/sbin/sendmail";
+
+/// Configuration of the alerting. All conditions are disabled by default.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AlertingConfig {
+    /// Maximum number of seconds the height of the blockchain may stay unchanged.
+    #[serde(default)]
+    pub height_stalled_secs: Option<u64>,
+    /// If true, an alert is raised when the node is connected to less validators than
+    /// required to achieve the consensus.
+    #[serde(default)]
+    pub peers_below_quorum: bool,
+    /// Maximum number of transactions in the memory pool.
+    #[serde(default)]
+    pub max_mempool_size: Option<u64>,
+    /// Minimum free disk space.
+    #[serde(default)]
+    pub free_disk_space: Option<DiskSpaceCondition>,
+    /// Sinks the alerts are dispatched to.
+    #[serde(default)]
+    pub sinks: Vec<AlertSink>,
+}
+
+/// Minimum free space on the disk containing the given path.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DiskSpaceCondition {
+    /// Path on the checked disk, e.g., the database directory.
+    pub path: PathBuf,
+    /// Minimum number of bytes available on the disk.
+    pub min_bytes: u64,
+}
+
+impl DiskSpaceCondition {
+    /// Returns the free space on the disk, or `None` if it cannot be determined.
+    pub fn free_space(&self) -> Option<u64> {
+        free_disk_space(&self.path)
+            .map_err(|e| {
+                error!(
+                    "Unable to determine free disk space at {}: {}",
+                    self.path.display(),
+                    e
+                )
+            })
+            .ok()
+    }
+}
+
+/// Destination of the alerts.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertSink {
+    /// Writes alerts to the node log.
+    Log,
+    /// Sends alerts as a JSON body of the POST request to the given `http://` URL.
+    Webhook {
+        /// URL of the webhook.
+        url: String,
+    },
+    /// Sends alerts by email with the `sendmail` binary.
+    Email {
+        /// Recipients of the email.
+        to: Vec<String>,
+        /// Sender of the email. The `sendmail` default is used if not set.
+        #[serde(default)]
+        from: Option<String>,
+        /// Path to the `sendmail` binary, `/usr/sbin/sendmail` by default.
+        #[serde(default)]
+        sendmail: Option<PathBuf>,
+    },
+}
+
+/// Condition the alert is raised for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// The height of the blockchain does not change.
+    HeightStalled,
+    /// The node is connected to less validators than required for the consensus.
+    QuorumLost,
+    /// The memory pool contains too many transactions.
+    MempoolOverflow,
+    /// There is not enough free disk space.
+    LowDiskSpace,
+}
+
+impl fmt::Display for AlertKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            AlertKind::HeightStalled => "height stalled",
+            AlertKind::QuorumLost => "quorum lost",
+            AlertKind::MempoolOverflow => "memory pool overflow",
+            AlertKind::LowDiskSpace => "low disk space",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Notification about a raised or resolved condition.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    /// Condition of the alert.
+    pub kind: AlertKind,
+    /// `true` if the condition no longer holds.
+    pub resolved: bool,
+    /// Human-readable description of the node state.
+    pub message: String,
+    /// Height of the latest committed block.
+    pub height: Height,
+    /// Time of the alert.
+    pub time: DateTime<Utc>,
+}
+
+impl Alert {
+    /// Returns a short summary of the alert, e.g., for the email subject.
+    pub fn summary(&self) -> String {
+        let state = if self.resolved { "RESOLVED" } else { "ALERT" };
+        format!("[{}] Exonum node: {}", state, self.kind)
+    }
+}
+
+/// Node metrics the alerting conditions are evaluated on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeMetrics {
+    /// Height of the latest committed block.
+    pub height: Height,
+    /// `true` if the node is connected to enough validators to achieve the consensus.
+    pub has_quorum: bool,
+    /// Number of transactions in the memory pool.
+    pub mempool_size: u64,
+    /// Free disk space in bytes, if it is checked.
+    pub free_disk_space: Option<u64>,
+}
+
+/// Evaluates the alerting conditions and dispatches the alerts.
+#[derive(Debug)]
+pub struct Alerting {
+    config: AlertingConfig,
+    active: HashSet<AlertKind>,
+    last_height: Option<Height>,
+    last_height_change: SystemTime,
+}
+
+impl Alerting {
+    /// Creates alerting with the given configuration.
+    pub fn new(config: AlertingConfig) -> Self {
+        Self {
+            config,
+            active: HashSet::new(),
+            last_height: None,
+            last_height_change: SystemTime::now(),
+        }
+    }
+
+    /// Returns the alerting configuration.
+    pub fn config(&self) -> &AlertingConfig {
+        &self.config
+    }
+
+    /// Evaluates the conditions and returns alerts for the conditions which have been
+    /// raised or resolved since the previous evaluation.
+    pub fn evaluate(&mut self, metrics: &NodeMetrics, now: SystemTime) -> Vec<Alert> {
+        if self.last_height != Some(metrics.height) {
+            self.last_height = Some(metrics.height);
+            self.last_height_change = now;
+        }
+
+        let mut conditions = Vec::new();
+        if let Some(max_secs) = self.config.height_stalled_secs {
+            let stalled = now
+                .duration_since(self.last_height_change)
+                .unwrap_or_else(|_| Duration::from_secs(0))
+                .as_secs();
+            conditions.push((
+                AlertKind::HeightStalled,
+                stalled > max_secs,
+                format!(
+                    "Height {} has not changed for {} seconds",
+                    metrics.height, stalled
+                ),
+            ));
+        }
+        if self.config.peers_below_quorum {
+            let message = if metrics.has_quorum {
+                "The node is connected to enough validators to achieve the consensus"
+            } else {
+                "The node is connected to less validators than required for the consensus"
+            };
+            conditions.push((
+                AlertKind::QuorumLost,
+                !metrics.has_quorum,
+                message.to_owned(),
+            ));
+        }
+        if let Some(max_size) = self.config.max_mempool_size {
+            conditions.push((
+                AlertKind::MempoolOverflow,
+                metrics.mempool_size > max_size,
+                format!(
+                    "Memory pool contains {} transactions, the limit is {}",
+                    metrics.mempool_size, max_size
+                ),
+            ));
+        }
+        if let (Some(condition), Some(free)) = (
+            self.config.free_disk_space.as_ref(),
+            metrics.free_disk_space,
+        ) {
+            conditions.push((
+                AlertKind::LowDiskSpace,
+                free < condition.min_bytes,
+                format!(
+                    "{} bytes are available at {}, the limit is {}",
+                    free,
+                    condition.path.display(),
+                    condition.min_bytes
+                ),
+            ));
+        }
+
+        let mut alerts = Vec::new();
+        for (kind, holds, message) in conditions {
+            let changed = if holds {
+                self.active.insert(kind)
+            } else {
+                self.active.remove(&kind)
+            };
+            if changed {
+                alerts.push(Alert {
+                    kind,
+                    resolved: !holds,
+                    message,
+                    height: metrics.height,
+                    time: now.into(),
+                });
+            }
+        }
+        alerts
+    }
+
+    /// Dispatches the alerts to the configured sinks.
+    pub fn dispatch(&self, alerts: Vec<Alert>) {
+        if alerts.is_empty() {
+            return;
+        }
+
+        let mut remote_sinks = Vec::new();
+        for sink in &self.config.sinks {
+            match *sink {
+                AlertSink::Log => {
+                    for alert in &alerts {
+                        if alert.resolved {
+                            info!("{}: {}", alert.summary(), alert.message);
+                        } else {
+                            warn!("{}: {}", alert.summary(), alert.message);
+                        }
+                    }
+                }
+                _ => remote_sinks.push(sink.clone()),
+            }
+        }
+
+        if !remote_sinks.is_empty() {
+            thread::spawn(move || {
+                for sink in &remote_sinks {
+                    for alert in &alerts {
+                        if let Err(e) = send_alert(sink, alert) {
+                            error!("Unable to send alert to {:?}: {}", sink, e);
+                        }
+                    }
+                }
+            });
+        }
+    }
+}
+
+fn send_alert(sink: &AlertSink, alert: &Alert) -> io::Result<()> {
+    match *sink {
+        AlertSink::Log => Ok(()),
+        AlertSink::Webhook { ref url } => {
+            let body = serde_json::to_vec(alert)?;
+            post_webhook(url, &body)
+        }
+        AlertSink::Email {
+            ref to,
+            ref from,
+            ref sendmail,
+        } => {
+            let sendmail = sendmail
+                .as_ref()
+                .map_or_else(|| Path::new(DEFAULT_SENDMAIL), PathBuf::as_path);
+            send_email(sendmail, to, from.as_ref().map(String::as_str), alert)
+        }
+    }
+}
+
+/// Sends a minimal HTTP/1.1 POST request with the JSON body.
+fn post_webhook(url: &str, body: &[u8]) -> io::Result<()> {
+    if !url.starts_with("http://") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "only `http://` webhook URLs are supported",
+        ));
+    }
+    let rest = &url["http://".len()..];
+    let (host, path) = match rest.find('/') {
+        Some(idx) => (&rest[..idx], &rest[idx..]),
+        None => (rest, "/"),
+    };
+    let address = if host.rfind(':') > host.rfind(']') {
+        host.to_owned()
+    } else {
+        format!("{}:80", host)
+    };
+    let address = address.to_socket_addrs()?.next().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, "webhook host cannot be resolved")
+    })?;
+
+    let mut stream = TcpStream::connect_timeout(&address, SINK_TIMEOUT)?;
+    stream.set_read_timeout(Some(SINK_TIMEOUT))?;
+    stream.set_write_timeout(Some(SINK_TIMEOUT))?;
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n",
+        path,
+        host,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line)?;
+    let is_success = status_line
+        .split_whitespace()
+        .nth(1)
+        .map_or(false, |code| code.starts_with('2'));
+    if is_success {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("webhook responded with `{}`", status_line.trim()),
+        ))
+    }
+}
+
+fn send_email(sendmail: &Path, to: &[String], from: Option<&str>, alert: &Alert) -> io::Result<()> {
+    let mut child = Command::new(sendmail)
+        .arg("-t")
+        .arg("-i")
+        .stdin(Stdio::piped())
+        .spawn()?;
+    {
+        let stdin = child.stdin.as_mut().expect("stdin is piped");
+        if let Some(from) = from {
+            write!(stdin, "From: {}\r\n", from)?;
+        }
+        write!(
+            stdin,
+            "To: {}\r\nSubject: {}\r\n\r\n{}\r\nHeight: {}\r\nTime: {}\r\n",
+            to.join(", "),
+            alert.summary(),
+            alert.message,
+            alert.height,
+            alert.time.to_rfc3339()
+        )?;
+    }
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("{} exited with {}", sendmail.display(), status),
+        ))
+    }
+}
+
+/// Returns the number of bytes available to unprivileged users on the disk
+/// containing the given path.
+#[cfg(unix)]
+#[allow(unsafe_code)]
+pub fn free_disk_space(path: &Path) -> io::Result<u64> {
+    use libc;
+    use std::{ffi::CString, mem, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut stat: libc::statvfs = unsafe { mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Returns the number of bytes available to unprivileged users on the disk
+/// containing the given path.
+#[cfg(not(unix))]
+pub fn free_disk_space(_path: &Path) -> io::Result<u64> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "free disk space cannot be determined on this platform",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use toml;
+
+    use super::*;
+
+    fn metrics(height: u64) -> NodeMetrics {
+        NodeMetrics {
+            height: Height(height),
+            has_quorum: true,
+            mempool_size: 0,
+            free_disk_space: None,
+        }
+    }
+
+    #[test]
+    fn test_config_deserialization() {
+        let config: AlertingConfig = toml::from_str(
+            r#"
+            height_stalled_secs = 60
+            peers_below_quorum = true
+
+            [free_disk_space]
+            path = "/var/lib/exonum"
+            min_bytes = 1024
+
+            [[sinks]]
+            type = "log"
+
+            [[sinks]]
+            type = "webhook"
+            url = "http://127.0.0.1:9000/alerts"
+
+            [[sinks]]
+            type = "email"
+            to = ["ops@example.com"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.height_stalled_secs, Some(60));
+        assert!(config.peers_below_quorum);
+        assert_eq!(config.max_mempool_size, None);
+        assert_eq!(
+            config.free_disk_space,
+            Some(DiskSpaceCondition {
+                path: PathBuf::from("/var/lib/exonum"),
+                min_bytes: 1024,
+            })
+        );
+        assert_eq!(
+            config.sinks,
+            vec![
+                AlertSink::Log,
+                AlertSink::Webhook {
+                    url: "http://127.0.0.1:9000/alerts".to_owned(),
+                },
+                AlertSink::Email {
+                    to: vec!["ops@example.com".to_owned()],
+                    from: None,
+                    sendmail: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_height_stalled() {
+        let mut alerting = Alerting::new(AlertingConfig {
+            height_stalled_secs: Some(30),
+            ..AlertingConfig::default()
+        });
+        let start = SystemTime::now();
+        let after = |secs| start + Duration::from_secs(secs);
+
+        assert!(alerting.evaluate(&metrics(1), start).is_empty());
+        assert!(alerting.evaluate(&metrics(1), after(30)).is_empty());
+
+        let alerts = alerting.evaluate(&metrics(1), after(31));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, AlertKind::HeightStalled);
+        assert!(!alerts[0].resolved);
+        // The alert is not repeated while the condition holds.
+        assert!(alerting.evaluate(&metrics(1), after(60)).is_empty());
+
+        let alerts = alerting.evaluate(&metrics(2), after(61));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, AlertKind::HeightStalled);
+        assert!(alerts[0].resolved);
+    }
+
+    #[test]
+    fn test_quorum_mempool_and_disk() {
+        let mut alerting = Alerting::new(AlertingConfig {
+            peers_below_quorum: true,
+            max_mempool_size: Some(100),
+            free_disk_space: Some(DiskSpaceCondition {
+                path: PathBuf::from("/"),
+                min_bytes: 1000,
+            }),
+            ..AlertingConfig::default()
+        });
+        let now = SystemTime::now();
+        let mut state = NodeMetrics {
+            free_disk_space: Some(5000),
+            ..metrics(1)
+        };
+        assert!(alerting.evaluate(&state, now).is_empty());
+
+        state.has_quorum = false;
+        state.mempool_size = 101;
+        state.free_disk_space = Some(999);
+        let kinds = alerting
+            .evaluate(&state, now)
+            .into_iter()
+            .map(|alert| (alert.kind, alert.resolved))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                (AlertKind::QuorumLost, false),
+                (AlertKind::MempoolOverflow, false),
+                (AlertKind::LowDiskSpace, false),
+            ]
+        );
+
+        state.mempool_size = 100;
+        let kinds = alerting
+            .evaluate(&state, now)
+            .into_iter()
+            .map(|alert| (alert.kind, alert.resolved))
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, vec![(AlertKind::MempoolOverflow, true)]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_free_disk_space() {
+        assert!(free_disk_space(Path::new("/")).unwrap() > 0);
+        assert!(free_disk_space(Path::new("/nonexistent/path")).is_err());
+    }
+}
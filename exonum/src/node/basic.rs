@@ -12,14 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use chrono::{DateTime, Utc};
 use rand::{self, Rng};
 
-use super::{NodeHandler, NodeRole, RequestData};
+use std::{collections::HashSet, net::SocketAddr};
+
+use super::{NodeHandler, NodeRole, PeerStatus, RequestData};
+use blockchain::Schema;
 use crypto::PublicKey;
 use events::error::LogError;
 use events::network::ConnectedPeerAddr;
 use helpers::Height;
-use messages::{Connect, Message, PeersRequest, Responses, Service, Signed, Status};
+use messages::{
+    Connect, Consensus as ConsensusMessage, Message, PeersRequest, Ping, Pong, Responses, Service,
+    Signed, Status,
+};
 
 impl NodeHandler {
     /// Redirects message to the corresponding `handle_...` function.
@@ -38,6 +45,7 @@ impl NodeHandler {
             Message::Responses(Responses::TransactionsResponse(msg)) => {
                 self.handle_txs_batch(&msg).log_error()
             }
+            Message::Responses(Responses::Pong(msg)) => self.handle_pong(&msg),
         }
     }
 
@@ -51,7 +59,7 @@ impl NodeHandler {
     }
 
     /// Handles the `Disconnected` event. Node will try to connect to that address again if it was
-    /// in the validators list.
+    /// in the validators list or among the upstreams of the replica.
     pub fn handle_disconnected(&mut self, key: PublicKey) {
         info!("Disconnected from: {}", key);
         self.remove_peer_with_addr(key);
@@ -69,18 +77,60 @@ impl NodeHandler {
     fn remove_peer_with_addr(&mut self, key: PublicKey) {
         self.state.remove_peer_with_pubkey(&key);
         self.blockchain.remove_peer_with_pubkey(&key);
+        self.api_state.remove_peer_status(&key);
         let is_validator = self.state.peer_is_validator(&key);
         let in_connect_list = self.state.peer_in_connect_list(&key);
-        if is_validator && in_connect_list {
+        let is_blocked = self.is_peer_blocked(&key);
+        if (is_validator || self.is_upstream(&key)) && in_connect_list && !is_blocked {
             self.connect(key);
         }
     }
 
+    /// Returns `true` if the connections with the peer are refused because the evidence
+    /// of its equivocation is committed to the blockchain.
+    pub fn is_peer_blocked(&self, key: &PublicKey) -> bool {
+        self.blocked_peers.contains(key)
+    }
+
+    /// Updates the set of the blocked peers according to the evidence of equivocation
+    /// committed to the blockchain, if the `block_byzantine_peers` flag of the consensus
+    /// configuration is set. Drops the connections with the newly blocked peers and
+    /// reconnects to the reinstated validators.
+    pub(crate) fn update_blocked_peers(&mut self) {
+        let blocked: HashSet<PublicKey> = if self.state.consensus_config().block_byzantine_peers {
+            let snapshot = self.blockchain.snapshot();
+            Schema::new(&snapshot)
+                .actual_byzantine_validators()
+                .into_iter()
+                .collect()
+        } else {
+            HashSet::new()
+        };
+
+        let added: Vec<_> = blocked.difference(&self.blocked_peers).cloned().collect();
+        let removed: Vec<_> = self.blocked_peers.difference(&blocked).cloned().collect();
+        self.blocked_peers = blocked;
+
+        for key in added {
+            warn!("Dropping connection with the Byzantine validator {}", key);
+            self.state.remove_peer_with_pubkey(&key);
+            self.blockchain.remove_peer_with_pubkey(&key);
+            self.api_state.remove_peer_status(&key);
+            self.disconnect(key);
+        }
+        for key in removed {
+            info!("Validator {} is reinstated", key);
+            if self.state.peer_is_validator(&key) && self.state.peer_in_connect_list(&key) {
+                self.connect(key);
+            }
+        }
+    }
+
     /// Handles the `Connect` message and connects to a peer as result.
     pub fn handle_connect(&mut self, message: Signed<Connect>) {
         // TODO Add spam protection (ECR-170)
         // TODO: drop connection if checks have failed. (ECR-1837)
-        let address = message.pub_addr().to_owned();
+        let address = message.pub_addr();
         if address == self.state.our_connect_message().pub_addr() {
             trace!("Received Connect with same address as our external_address.");
             return;
@@ -100,6 +150,15 @@ impl NodeHandler {
             return;
         }
 
+        if self.is_peer_blocked(&public_key) {
+            warn!(
+                "Refusing the connection with the Byzantine validator {}",
+                public_key
+            );
+            self.disconnect(public_key);
+            return;
+        }
+
         // Check if we have another connect message from peer with the given public_key.
         let mut need_connect = true;
         if let Some(saved_message) = self.state.peers().get(&public_key) {
@@ -115,14 +174,19 @@ impl NodeHandler {
                 return;
             }
             if saved_message.pub_addr() != message.pub_addr() {
-                info!(
-                    "Updating connect list for peer: {} with new addr: {}",
-                    public_key,
-                    message.pub_addr()
-                );
-                self.state
-                    .connect_list()
-                    .update_peer(&public_key, message.pub_addr().to_string())
+                let mut connect_list = self.state.connect_list();
+                // Configured hostnames are kept, since they are resolved on each connection.
+                let is_hostname = connect_list
+                    .find_address_by_key(&public_key)
+                    .map_or(false, |peer| peer.address.parse::<SocketAddr>().is_err());
+                if !is_hostname {
+                    info!(
+                        "Updating connect list for peer: {} with new addr: {}",
+                        public_key,
+                        message.pub_addr()
+                    );
+                    connect_list.update_peer(&public_key, message.pub_addr().to_string())
+                }
             }
         }
         self.state.add_peer(public_key, message.clone());
@@ -157,6 +221,18 @@ impl NodeHandler {
             return;
         }
 
+        self.api_state.update_peer_status(PeerStatus {
+            public_key: msg.author(),
+            validator_id: self.state.find_validator(msg.author()),
+            height: msg.height(),
+            time: self.system_state.current_time().into(),
+        });
+
+        // Replica follows the block stream of its upstreams only
+        if self.is_replica() && !self.is_upstream(&msg.author()) {
+            return;
+        }
+
         // Handle message from future height
         if msg.height() > height {
             let peer = msg.author();
@@ -167,6 +243,8 @@ impl NodeHandler {
                 self.state.set_node_height(peer, msg.height());
             }
 
+            self.update_replica_status(None);
+
             // Request block
             self.request(RequestData::Block(height), peer);
         }
@@ -219,11 +297,102 @@ impl NodeHandler {
         }
         self.add_peer_exchange_timeout();
     }
+    /// Handles `NodeTimeout::Ping`. Node sends the `Ping` message to every connected peer.
+    pub fn handle_ping_timeout(&mut self) {
+        let time: DateTime<Utc> = self.system_state.current_time().into();
+        let peers: Vec<_> = self.state.peers().keys().cloned().collect();
+        for peer in peers {
+            let message = self.sign_message(Ping::new(&peer, time));
+            self.send_to_peer(peer, message);
+        }
+        self.add_ping_timeout();
+    }
+
+    /// Handles the `Pong` message. Node records the round-trip time to the peer.
+    pub fn handle_pong(&mut self, msg: &Signed<Pong>) {
+        if msg.to() != self.state.consensus_public_key() {
+            error!("Received message addressed to other peer = {:?}.", msg.to());
+            return;
+        }
+
+        let now: DateTime<Utc> = self.system_state.current_time().into();
+        match now.signed_duration_since(msg.time()).num_microseconds() {
+            Some(rtt) if rtt >= 0 => {
+                let validator_id = self.state.find_validator(msg.author());
+                self.api_state
+                    .add_peer_latency(msg.author(), validator_id, rtt as u64);
+            }
+            _ => warn!(
+                "Received Pong with incorrect time from peer = {:?}.",
+                msg.author()
+            ),
+        }
+    }
+
+    /// Estimates the clock skew of a validator from the time of its `Precommit` message
+    /// received from the network and logs a warning if the average skew exceeds
+    /// the threshold. See [`PeerClockSkew`] for details.
+    ///
+    /// Only the precommits for the current height are taken into account, since the ones
+    /// for the other heights may be delayed or retransmitted.
+    ///
+    /// [`PeerClockSkew`]: struct.PeerClockSkew.html
+    pub fn estimate_clock_skew(&mut self, msg: &Message) {
+        let precommit = match *msg {
+            Message::Consensus(ConsensusMessage::Precommit(ref precommit)) => precommit,
+            _ => return,
+        };
+        if precommit.height() != self.state.height() {
+            return;
+        }
+
+        let peer = precommit.author();
+        let now: DateTime<Utc> = self.system_state.current_time().into();
+        // The message was sent about half of the round-trip time ago.
+        let transmission_delay = self
+            .api_state
+            .peer_latency(&peer)
+            .map_or(0, |latency| latency.average_rtt / 2 / 1_000);
+        let skew = precommit
+            .time()
+            .signed_duration_since(now)
+            .num_milliseconds()
+            .saturating_add(transmission_delay as i64);
+
+        let threshold = self.max_clock_skew as i64;
+        let was_skewed = self
+            .api_state
+            .peer_clock_skew(&peer)
+            .map_or(false, |clock_skew| {
+                clock_skew.average_skew.abs() > threshold
+            });
+        let clock_skew =
+            self.api_state
+                .add_peer_clock_skew(peer, Some(precommit.validator()), skew);
+        let is_skewed = clock_skew.average_skew.abs() > threshold;
+        if is_skewed && !was_skewed {
+            warn!(
+                "Clock of validator {} is skewed by {} ms relative to the local clock",
+                precommit.validator(),
+                clock_skew.average_skew
+            );
+        } else if was_skewed && !is_skewed {
+            info!(
+                "Clock skew of validator {} is back within {} ms",
+                precommit.validator(),
+                threshold
+            );
+        }
+    }
+
     /// Handles `NodeTimeout::UpdateApiState`.
     /// Node update internal `ApiState` and `NodeRole`.
     pub fn handle_update_api_state_timeout(&mut self) {
         self.api_state.update_node_state(&self.state);
+        self.api_state
+            .update_quorum_status(self.system_state.current_time().into());
         self.node_role = NodeRole::new(self.state.validator_id());
+        self.check_alerts();
         self.add_update_api_state_timeout();
     }
 
@@ -0,0 +1,107 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Chaos testing hooks injecting faults into a running node.
+//!
+//! The hooks are compiled only into debug builds. They are controlled with the
+//! `v1/chaos` endpoint of the private API, so that the resilience of a real network
+//! can be tested by scripted fault scenarios:
+//!
+//! - a share of the messages sent to the given peers is dropped;
+//! - each block commit is delayed by the given number of milliseconds;
+//! - the node does not send proposals while it is the round leader.
+
+use rand::{self, Rng};
+
+use std::{thread, time::Duration};
+
+use super::NodeHandler;
+use crypto::PublicKey;
+use helpers::Milliseconds;
+
+/// Dropping of the messages sent to a peer.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct MessageDrop {
+    /// Consensus public key of the peer.
+    pub peer: PublicKey,
+    /// Percentage of the dropped messages, from 0 to 100.
+    pub percent: u8,
+}
+
+/// Faults injected into the node.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ChaosConfig {
+    /// Messages dropped for the peers.
+    pub message_drops: Vec<MessageDrop>,
+    /// Delay of each block commit in milliseconds.
+    pub commit_delay: Milliseconds,
+    /// If true, the node does not send proposals.
+    pub pause_proposer: bool,
+}
+
+impl ChaosConfig {
+    /// Checks that the percentages of the dropped messages are valid.
+    pub fn validate(&self) -> Result<(), String> {
+        match self.message_drops.iter().find(|drop| drop.percent > 100) {
+            Some(drop) => Err(format!(
+                "Invalid percentage of dropped messages for peer {:?}: {}",
+                drop.peer, drop.percent
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Decides whether the next message to the given peer should be dropped.
+    fn drops_message_to(&self, peer: &PublicKey) -> bool {
+        self.message_drops
+            .iter()
+            .filter(|drop| drop.peer == *peer)
+            .any(|drop| rand::thread_rng().gen_range(0, 100) < drop.percent)
+    }
+}
+
+impl NodeHandler {
+    /// Replaces the injected faults.
+    pub(crate) fn set_chaos(&mut self, config: ChaosConfig) {
+        warn!("Chaos testing hooks are updated: {:?}", config);
+        self.api_state().set_chaos(config.clone());
+        self.chaos = config;
+    }
+
+    /// Returns `true` if the message to the given peer should be dropped.
+    pub(crate) fn chaos_drops_message_to(&self, peer: &PublicKey) -> bool {
+        let dropped = self.chaos.drops_message_to(peer);
+        if dropped {
+            trace!("Chaos: dropped message to {:?}", peer);
+        }
+        dropped
+    }
+
+    /// Returns `true` if the node should not send proposals.
+    pub(crate) fn chaos_pauses_proposer(&self) -> bool {
+        if self.chaos.pause_proposer {
+            info!("Chaos: proposer is paused");
+        }
+        self.chaos.pause_proposer
+    }
+
+    /// Blocks the node for the configured commit delay.
+    pub(crate) fn chaos_delay_commit(&self) {
+        if self.chaos.commit_delay > 0 {
+            trace!("Chaos: delaying commit by {} ms", self.chaos.commit_delay);
+            thread::sleep(Duration::from_millis(self.chaos.commit_delay));
+        }
+    }
+}
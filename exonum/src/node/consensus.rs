@@ -14,7 +14,7 @@
 
 use std::collections::HashSet;
 
-use blockchain::Schema;
+use blockchain::{invariants, Schema};
 use crypto::{CryptoHash, Hash, PublicKey};
 use events::InternalRequest;
 use failure;
@@ -24,8 +24,8 @@ use messages::{
     PrevotesRequest, Propose, ProposeRequest, RawTransaction, Signed, SignedMessage,
     TransactionsRequest, TransactionsResponse,
 };
-use node::{NodeHandler, RequestData};
-use storage::Patch;
+use node::{forensics::PeerBlock, CommitEvent, NodeHandler, RequestData};
+use storage::{Patch, Snapshot};
 
 // TODO Reduce view invocations. (ECR-171)
 impl NodeHandler {
@@ -39,9 +39,14 @@ impl NodeHandler {
             return;
         }
 
+        if self.is_replica() {
+            trace!("Ignoring a consensus message {:?} in the replica mode", msg);
+            return;
+        }
+
         // Warning for messages from previous and future height
-        if msg.height() < self.state.height().previous()
-            || msg.height() > self.state.height().next()
+        if msg.height() < self.state.height().saturating_sub(1)
+            || msg.height() > self.state.height().saturating_add(1)
         {
             warn!(
                 "Received consensus message from other height: msg.height={}, self.height={}",
@@ -51,13 +56,16 @@ impl NodeHandler {
         }
 
         // Ignore messages from previous and future height
-        if msg.height() < self.state.height() || msg.height() > self.state.height().next() {
+        if msg.height() < self.state.height()
+            || msg.height() > self.state.height().saturating_add(1)
+        {
             return;
         }
 
         // Queued messages from next height or round
         // TODO: Should we ignore messages from far rounds? (ECR-171)
-        if msg.height() == self.state.height().next() || msg.round() > self.state.round() {
+        if msg.height() == self.state.height().saturating_add(1) || msg.round() > self.state.round()
+        {
             trace!(
                 "Received consensus message from future round: msg.height={}, msg.round={}, \
                  self.height={}, self.round={}",
@@ -67,8 +75,15 @@ impl NodeHandler {
                 self.state.round()
             );
             let validator = msg.validator();
+            let msg_height = msg.height();
             let round = msg.round();
-            self.state.add_queued(msg);
+            if !self.state.add_queued(msg) {
+                warn!(
+                    "In-flight messages size limit is reached, dropping message from \
+                     future round: msg.height={}, msg.round={}",
+                    msg_height, round
+                );
+            }
             trace!("Trying to reach actual round.");
             if let Some(r) = self.state.update_validator_round(validator, round) {
                 trace!("Scheduling jump to round.");
@@ -120,6 +135,7 @@ impl NodeHandler {
             msg.clone(),
             &schema.transactions(),
             &schema.transactions_pool(),
+            &schema.transactions_locations(),
         ) {
             Ok(state) => state.has_unknown_txs(),
             Err(err) => {
@@ -127,6 +143,7 @@ impl NodeHandler {
                 return;
             }
         };
+        self.archive_consensus_message(msg);
 
         let hash = msg.hash();
 
@@ -164,6 +181,10 @@ impl NodeHandler {
         let block = msg.block();
         let block_hash = block.hash();
 
+        if block.height() < self.state.height() {
+            self.check_finality(msg);
+        }
+
         // TODO: Add block with greater height to queue. (ECR-171)
         if self.state.height() != block.height() {
             bail!("Received block has another height, msg={:?}", msg);
@@ -209,8 +230,12 @@ impl NodeHandler {
             let schema = Schema::new(snapshot);
             let has_unknown_txs = self
                 .state
-                .create_incomplete_block(&msg, &schema.transactions(), &schema.transactions_pool())
-                .has_unknown_txs();
+                .create_incomplete_block(
+                    &msg,
+                    &schema.transactions(),
+                    &schema.transactions_pool(),
+                    &schema.transactions_locations(),
+                ).has_unknown_txs();
 
             let known_nodes = self.remove_request(&RequestData::Block(block.height()));
 
@@ -232,17 +257,43 @@ impl NodeHandler {
                 .collect();
 
             self.commit(block_hash, precommits?.into_iter(), None);
+            self.update_replica_status(Some(msg.author()));
             self.request_next_block();
         }
         Ok(())
     }
 
+    /// Halts the node if the received block conflicts with the committed block at the same
+    /// height and is precommitted by the majority of validators, since the finality
+    /// of the committed blocks is broken in this case and the node cannot continue safely.
+    fn check_finality(&self, msg: &Signed<BlockResponse>) {
+        let precommits: Result<Vec<_>, _> = msg
+            .precommits()
+            .into_iter()
+            .map(Precommit::verify_precommit)
+            .collect();
+        let precommits = match precommits {
+            Ok(precommits) => precommits,
+            Err(_) => return,
+        };
+        let snapshot = self.blockchain.snapshot();
+        if let Err(e) = invariants::check_conflicting_block(&*snapshot, &msg.block(), &precommits) {
+            error!("{}, received from {}", e, msg.author().to_hex());
+            panic!("Halting the node: {}", e);
+        }
+    }
+
     /// Executes and commits block. This function is called when node has full propose information.
     pub fn handle_full_propose(&mut self, hash: Hash, propose_round: Round) {
         // Send prevote
         if self.state.locked_round() == Round::zero() {
             if self.state.is_validator() && !self.state.have_prevote(propose_round) {
-                self.broadcast_prevote(propose_round, &hash);
+                match self.precheck_propose(&hash) {
+                    Ok(()) => {
+                        self.broadcast_prevote(propose_round, &hash);
+                    }
+                    Err(e) => warn!("Not voting for propose {:?}: {}", hash, e),
+                }
             } else {
                 // TODO: what if we HAVE prevote for the propose round? (ECR-171)
             }
@@ -250,8 +301,9 @@ impl NodeHandler {
 
         // Lock to propose
         // TODO: avoid loop here (ECR-171).
-        let start_round = ::std::cmp::max(self.state.locked_round().next(), propose_round);
-        for round in start_round.iter_to(self.state.round().next()) {
+        let start_round =
+            ::std::cmp::max(self.state.locked_round().saturating_add(1), propose_round);
+        for round in start_round.iter_to(self.state.round().saturating_add(1)) {
             if self.state.has_majority_prevotes(round, hash) {
                 self.handle_majority_prevotes(round, &hash);
             }
@@ -263,6 +315,10 @@ impl NodeHandler {
             let our_block_hash = self.execute(&hash);
 
             if our_block_hash != block_hash {
+                if self.is_forensics_enabled() {
+                    self.capture_executed_divergence(&our_block_hash, block_hash);
+                    return;
+                }
                 panic!(
                     "Full propose: wrong state hash. Either a node's implementation is \
                      incorrect or validators majority works incorrectly"
@@ -278,15 +334,27 @@ impl NodeHandler {
     ///
     /// # Panics
     ///
-    /// Panics if the received block has incorrect `block_hash`.
+    /// Panics if the received block has incorrect `block_hash`, unless the forensic mode
+    /// is enabled.
     pub fn handle_full_block(&mut self, msg: &Signed<BlockResponse>) -> Result<(), failure::Error> {
         let block = msg.block();
         let block_hash = block.hash();
 
         if self.state.block(&block_hash).is_none() {
+            if self.is_divergence_captured(block.height()) {
+                return Ok(());
+            }
             let (computed_block_hash, patch) =
                 self.create_block(block.proposer_id(), block.height(), msg.transactions());
             // Verify block_hash.
+            if computed_block_hash != block_hash && self.is_forensics_enabled() {
+                let peer_block = PeerBlock {
+                    author: msg.author(),
+                    block: block.clone(),
+                };
+                self.capture_divergence(&patch, block_hash, Some(peer_block));
+                return Ok(());
+            }
             assert!(
                 computed_block_hash == block_hash,
                 "Block_hash incorrect in the received block={:?}. Either a node's \
@@ -308,6 +376,7 @@ impl NodeHandler {
             .collect();
 
         self.commit(block_hash, precommits?.into_iter(), None);
+        self.update_replica_status(Some(msg.author()));
         self.request_next_block();
         Ok(())
     }
@@ -322,6 +391,7 @@ impl NodeHandler {
         );
 
         // Add prevote
+        self.archive_consensus_message(msg);
         let has_consensus = self.state.add_prevote(msg.clone());
 
         // Request propose or transactions
@@ -386,6 +456,10 @@ impl NodeHandler {
 
         // Execute block and get state hash
         let our_block_hash = self.execute(propose_hash);
+        if our_block_hash != *block_hash && self.is_forensics_enabled() {
+            self.capture_executed_divergence(&our_block_hash, *block_hash);
+            return;
+        }
         assert_eq!(
             &our_block_hash, block_hash,
             "Our block_hash different from precommits one."
@@ -399,7 +473,7 @@ impl NodeHandler {
     /// Locks node to the specified round, so pre-votes for the lower round will be ignored.
     pub fn lock(&mut self, prevote_round: Round, propose_hash: Hash) {
         trace!("MAKE LOCK {:?} {:?}", prevote_round, propose_hash);
-        for round in prevote_round.iter_to(self.state.round().next()) {
+        for round in prevote_round.iter_to(self.state.round().saturating_add(1)) {
             // Send prevotes
             if self.state.is_validator() && !self.state.have_prevote(round) {
                 self.broadcast_prevote(round, &propose_hash);
@@ -423,6 +497,7 @@ impl NodeHandler {
                     // Execute block and get state hash
                     let block_hash = self.execute(&propose_hash);
                     self.broadcast_precommit(round, &propose_hash, &block_hash);
+                    self.prepare_next_propose(&propose_hash);
                     // Commit if has consensus
                     if self.state.has_majority_precommits(round, block_hash) {
                         self.handle_majority_precommits(round, &propose_hash, &block_hash);
@@ -445,6 +520,7 @@ impl NodeHandler {
         );
 
         // Add precommit
+        self.archive_consensus_message(msg);
         let has_consensus = self.state.add_precommit(msg.clone());
 
         // Request propose
@@ -476,13 +552,16 @@ impl NodeHandler {
         round: Option<Round>,
     ) {
         trace!("COMMIT {:?}", block_hash);
+        #[cfg(debug_assertions)]
+        self.chaos_delay_commit();
 
         // Merge changes into storage
         let (committed_txs, proposer) = {
             // FIXME: Avoid of clone here. (ECR-171)
             let block_state = self.state.block(&block_hash).unwrap().clone();
+            let archive = self.take_archived_messages();
             self.blockchain
-                .commit(block_state.patch(), block_hash, precommits)
+                .commit_with_archive(block_state.patch(), block_hash, precommits, archive)
                 .unwrap();
             // Update node state.
             self.state
@@ -500,7 +579,19 @@ impl NodeHandler {
         let schema = Schema::new(&snapshot);
         let pool_len = schema.transactions_pool_len();
 
+        let transactions = schema.transactions();
+        let committed_bytes = schema
+            .block_transactions(schema.height())
+            .iter()
+            .filter_map(|hash| transactions.get(&hash))
+            .map(|tx| tx.signed_message().raw().len() as u64)
+            .sum();
+        self.state.remove_tx_pool_bytes(committed_bytes);
+        let memory_usage = self.state.memory_usage();
+
         metric!("node.mempool", pool_len);
+        metric!("node.mempool_bytes", memory_usage.tx_pool_bytes);
+        metric!("node.in_flight_bytes", memory_usage.in_flight_bytes);
 
         let height = self.state.height();
         info!(
@@ -513,13 +604,34 @@ impl NodeHandler {
             block_hash.to_hex(),
         );
 
+        self.write_status_file();
+        self.record_table_roots();
+        self.update_blocked_peers();
+        self.check_alerts();
+        self.archive_old_blocks();
+        self.prune_old_blocks();
+        self.expire_unmet_dependencies();
+
+        if !self.commit_observers.is_empty() {
+            let block = schema.last_block();
+            let event = CommitEvent {
+                height: block.height(),
+                block_hash,
+                tx_hashes: schema.block_transactions(block.height()).iter().collect(),
+                state_hash: *block.state_hash(),
+            };
+            for observer in &self.commit_observers {
+                observer.on_commit(&event);
+            }
+        }
+
         self.broadcast_status();
         self.add_status_timeout();
 
         // Add timeout for first round
         self.add_round_timeout();
         // Send propose we is leader
-        if self.state.is_leader() {
+        if self.state.is_leader() && !self.send_prepared_propose() {
             self.add_propose_timeout();
         }
 
@@ -535,13 +647,44 @@ impl NodeHandler {
         let hash = msg.hash();
 
         let snapshot = self.blockchain.snapshot();
-        if Schema::new(&snapshot).transactions().contains(&hash) {
+        if Schema::new(&snapshot).is_known_transaction(&hash) {
             bail!("Received already processed transaction, hash {:?}", hash)
         }
+        if self.is_replica() && !self.state.is_tx_awaited(&hash) {
+            bail!(
+                "Replica does not keep the memory pool, ignoring transaction {:?}",
+                hash
+            )
+        }
+
+        let dependency = match self.blockchain.tx_from_raw(msg.payload().clone()) {
+            Ok(ref tx) if self.propose_precheck && !tx.verify() => {
+                bail!("Received transaction failing verification, hash {:?}", hash)
+            }
+            Ok(tx) => tx.depends_on(),
+            Err(e) => {
+                error!("Received invalid transaction {:?}, result: {}", msg, e);
+                bail!("Received malicious transaction.")
+            }
+        };
 
-        if let Err(e) = self.blockchain.tx_from_raw(msg.payload().clone()) {
-            error!("Received invalid transaction {:?}, result: {}", msg, e);
-            bail!("Received malicious transaction.")
+        let tx_size = msg.signed_message().raw().len() as u64;
+        if !self.state.is_tx_awaited(&hash) {
+            let schema = Schema::new(&snapshot);
+            if let Some(ref dependency) = dependency {
+                // A transaction could never be committed if its dependency is unknown.
+                if !schema.is_known_transaction(dependency) {
+                    bail!(
+                        "Rejected transaction {:?}: unknown dependency {:?}",
+                        hash,
+                        dependency
+                    )
+                }
+            }
+            let pool_len = schema.transactions_pool_len();
+            if let Err(e) = self.state.check_tx_pool_limits(pool_len, tx_size) {
+                bail!("Rejected transaction {:?}: {}", hash, e)
+            }
         }
 
         let mut fork = self.blockchain.fork();
@@ -552,6 +695,10 @@ impl NodeHandler {
         self.blockchain
             .merge(fork.into_patch())
             .expect("Unable to save transaction to persistent pool.");
+        self.state.add_tx_pool_bytes(tx_size);
+        if let Some(dependency) = dependency {
+            self.state.add_tx_dependency(hash, dependency);
+        }
 
         if self.state.is_leader() && self.state.round() != Round::zero() {
             self.maybe_add_propose_timeout();
@@ -573,6 +720,33 @@ impl NodeHandler {
         Ok(())
     }
 
+    /// Checks that all transactions of the propose belong to the known services and pass
+    /// `Transaction::verify`. The check is performed only if `propose_precheck` is enabled
+    /// in the node configuration.
+    fn precheck_propose(&self, propose_hash: &Hash) -> Result<(), failure::Error> {
+        if !self.propose_precheck {
+            return Ok(());
+        }
+
+        let propose_state = self
+            .state
+            .propose(propose_hash)
+            .ok_or_else(|| format_err!("Unknown propose"))?;
+        let snapshot = self.blockchain.snapshot();
+        let transactions = Schema::new(&snapshot).transactions();
+        for tx_hash in propose_state.message().transactions() {
+            let raw = transactions
+                .get(tx_hash)
+                .ok_or_else(|| format_err!("Unknown transaction {:?}", tx_hash))?;
+            let tx = self
+                .blockchain
+                .tx_from_raw(raw.payload().clone())
+                .map_err(|e| format_err!("Malformed transaction {:?}: {}", tx_hash, e))?;
+            ensure!(tx.verify(), "Transaction {:?} fails verification", tx_hash);
+        }
+        Ok(())
+    }
+
     /// Handles raw transactions.
     pub fn handle_txs_batch(
         &mut self,
@@ -592,20 +766,30 @@ impl NodeHandler {
                 msg.author().to_hex()
             )
         }
+        let snapshot = self.blockchain.snapshot();
+        let schema = Schema::new(&snapshot);
         for tx in msg.transactions() {
+            // Committed transactions are skipped, including the ones from pruned blocks.
+            if schema.is_known_transaction(&tx.hash()) {
+                continue;
+            }
             self.execute_later(InternalRequest::VerifyMessage(tx));
         }
         Ok(())
     }
 
     /// Handles external boxed transaction. Additionally transaction will be broadcast to the
-    /// Node's peers.
+    /// Node's peers. In the replica mode, the transaction is forwarded to the upstreams instead.
     #[cfg_attr(
         feature = "cargo-clippy",
         allow(clippy::needless_pass_by_value)
     )]
     pub fn handle_incoming_tx(&mut self, msg: Signed<RawTransaction>) {
         trace!("Handle incoming transaction");
+        if self.is_replica() {
+            self.forward_to_upstreams(&msg);
+            return;
+        }
         match self.handle_tx(msg.clone()) {
             Ok(_) => self.broadcast(msg),
             Err(e) => error!("{}", e),
@@ -683,45 +867,211 @@ impl NodeHandler {
         if self.state.locked_propose().is_some() {
             return;
         }
+        #[cfg(debug_assertions)]
+        {
+            if self.chaos_pauses_proposer() {
+                return;
+            }
+        }
         if let Some(validator_id) = self.state.validator_id() {
             if self.state.have_prevote(round) {
                 return;
             }
             let snapshot = self.blockchain.snapshot();
             let schema = Schema::new(&snapshot);
-            let pool = schema.transactions_pool();
             let pool_len = schema.transactions_pool_len();
 
             info!("LEADER: pool = {}", pool_len);
 
             let round = self.state.round();
-            let max_count = ::std::cmp::min(u64::from(self.txs_block_limit()), pool_len);
+            let txs = self.select_propose_txs(&schema, &HashSet::new());
+            self.send_propose(validator_id, &txs);
+        }
+    }
 
-            let txs: Vec<Hash> = pool.iter().take(max_count as usize).collect();
-            let propose = self.sign_message(Propose::new(
-                validator_id,
-                self.state.height(),
-                round,
-                self.state.last_hash(),
-                &txs,
-            ));
-            // Put our propose to the consensus messages cache
-            self.blockchain.save_message(round, propose.clone());
+    /// Broadcasts our `Propose` with the given transactions for the current round
+    /// and votes for it.
+    fn send_propose(&mut self, validator_id: ValidatorId, txs: &[Hash]) {
+        let round = self.state.round();
+        let propose = self.sign_message(Propose::new(
+            validator_id,
+            self.state.height(),
+            round,
+            self.state.last_hash(),
+            txs,
+        ));
+        // Put our propose to the consensus messages cache
+        self.blockchain.save_message(round, propose.clone());
 
-            trace!("Broadcast propose: {:?}", propose);
-            self.broadcast(propose.clone());
+        trace!("Broadcast propose: {:?}", propose);
+        self.broadcast(propose.clone());
 
-            self.allow_expedited_propose = true;
+        self.allow_expedited_propose = true;
 
-            // Save our propose into state
-            let hash = self.state.add_self_propose(propose);
+        // Save our propose into state
+        let hash = self.state.add_self_propose(propose);
 
-            // Send prevote
-            let has_majority_prevotes = self.broadcast_prevote(round, &hash);
-            if has_majority_prevotes {
-                self.handle_majority_prevotes(round, &hash);
+        // Execute our own proposal in advance, so that the block is ready by the time
+        // the other validators vote for it.
+        if self.propose_ahead {
+            self.execute(&hash);
+        }
+
+        // Send prevote
+        let has_majority_prevotes = self.broadcast_prevote(round, &hash);
+        if has_majority_prevotes {
+            self.handle_majority_prevotes(round, &hash);
+        }
+    }
+
+    /// Selects the transactions for our proposal at the next height if `propose_ahead`
+    /// is enabled and the node is the leader of its first round. Transactions of the locked
+    /// proposal are skipped, as they are going to be committed at the current height.
+    fn prepare_next_propose(&mut self, locked_propose: &Hash) {
+        self.prepared_propose = None;
+        if !self.propose_ahead {
+            return;
+        }
+        let next_height = match self.state.height().checked_next() {
+            Some(height) => height,
+            None => return,
+        };
+        if self.state.validator_id() != Some(self.state.leader_at(next_height, Round::first())) {
+            return;
+        }
+
+        let txs = {
+            let locked_txs: HashSet<Hash> = match self.state.propose(locked_propose) {
+                Some(state) => state.message().transactions().iter().cloned().collect(),
+                None => return,
+            };
+            let snapshot = self.blockchain.snapshot();
+            let schema = Schema::new(&snapshot);
+            self.select_propose_txs(&schema, &locked_txs)
+        };
+        trace!("Prepared {} transactions for the next propose", txs.len());
+        self.prepared_propose = Some((next_height, txs));
+    }
+
+    /// Selects transactions from the pool for a new proposal, skipping the `pending` ones,
+    /// which are going to be committed before the proposal. The transactions of the priority
+    /// senders are selected first, up to the number reserved for them in the block.
+    fn select_propose_txs<T>(&self, schema: &Schema<T>, pending: &HashSet<Hash>) -> Vec<Hash>
+    where
+        T: AsRef<dyn Snapshot>,
+    {
+        let limit = self.txs_block_limit();
+        let priority_senders = &self.state.consensus_config().priority_senders;
+        let pool = schema.transactions_pool();
+        let transactions = schema.transactions();
+        let is_candidate = |tx_hash: &Hash| {
+            !pending.contains(tx_hash) && self.is_dependency_met(schema, tx_hash, pending)
+        };
+        let is_priority = |tx_hash: &Hash| {
+            transactions
+                .get(tx_hash)
+                .map_or(false, |tx| priority_senders.keys.contains(&tx.author()))
+        };
+
+        let reserved = ::std::cmp::min(priority_senders.reserved_txs(limit), limit as usize);
+        let limit = limit as usize;
+        let (mut priority, mut regular) = (Vec::new(), Vec::new());
+        // The pool is scanned once, until both the reserved part and the rest
+        // of the block are filled.
+        for tx_hash in pool.iter() {
+            if priority.len() == reserved && priority.len() + regular.len() >= limit {
+                break;
             }
+            if !is_candidate(&tx_hash) {
+                continue;
+            }
+            if priority.len() < reserved && is_priority(&tx_hash) {
+                priority.push(tx_hash);
+            } else if regular.len() < limit {
+                regular.push(tx_hash);
+            }
+        }
+        regular.truncate(limit - priority.len());
+        priority.extend(regular);
+        priority
+    }
+
+    /// Checks whether the dependency of the pool transaction, if any, is committed
+    /// or is going to be committed by one of the `pending` transactions.
+    fn is_dependency_met<T>(
+        &self,
+        schema: &Schema<T>,
+        tx_hash: &Hash,
+        pending: &HashSet<Hash>,
+    ) -> bool
+    where
+        T: AsRef<dyn Snapshot>,
+    {
+        match self.state.tx_dependency(tx_hash) {
+            Some(dependency) => {
+                pending.contains(dependency) || schema.transactions_locations().contains(dependency)
+            }
+            None => true,
+        }
+    }
+
+    /// Drops from the pool the transactions whose dependency is neither committed
+    /// nor in the pool anymore, since such transactions could never be committed.
+    fn expire_unmet_dependencies(&mut self) {
+        let expired: Vec<Hash> = {
+            let snapshot = self.blockchain.snapshot();
+            let schema = Schema::new(&snapshot);
+            let pool = schema.transactions_pool();
+            self.state
+                .retain_tx_dependencies(|tx_hash, _| pool.contains(tx_hash));
+            self.state
+                .tx_dependencies()
+                .iter()
+                .filter(|&(_, dependency)| !schema.is_known_transaction(dependency))
+                .map(|(tx_hash, _)| *tx_hash)
+                .collect()
+        };
+        for tx_hash in expired {
+            info!(
+                "Dependency of transaction {:?} is not known anymore, dropping it",
+                tx_hash
+            );
+            self.handle_evict_tx(&tx_hash);
+        }
+    }
+
+    /// Sends the proposal prepared by `prepare_next_propose` for the current height.
+    /// Returns `false` if there is no such proposal, in which case the node should wait
+    /// for the propose timeout as usual.
+    fn send_prepared_propose(&mut self) -> bool {
+        let txs = match self.prepared_propose.take() {
+            Some((height, txs)) if height == self.state.height() => txs,
+            _ => return false,
+        };
+        let validator_id = match self.state.validator_id() {
+            Some(validator_id) => validator_id,
+            None => return false,
+        };
+        #[cfg(debug_assertions)]
+        {
+            if self.chaos_pauses_proposer() {
+                return false;
+            }
+        }
+        // Some of the selected transactions could be committed in the meantime.
+        let txs: Vec<Hash> = {
+            let snapshot = self.blockchain.snapshot();
+            let schema = Schema::new(&snapshot);
+            let pool = schema.transactions_pool();
+            txs.into_iter().filter(|hash| pool.contains(hash)).collect()
+        };
+        // Empty blocks are not hurried; the propose timeout handles them.
+        if txs.is_empty() {
+            return false;
         }
+        info!("LEADER: prepared propose, txs = {}", txs.len());
+        self.send_propose(validator_id, &txs);
+        true
     }
 
     /// Handles request timeout by sending the corresponding request message to a peer.
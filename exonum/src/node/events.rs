@@ -14,6 +14,7 @@
 
 use super::{ConnectListConfig, ExternalMessage, NodeHandler, NodeTimeout};
 use blockchain::Schema;
+use crypto::Hash;
 use events::{error::LogError, Event, EventHandler, InternalEvent, InternalRequest, NetworkEvent};
 
 impl EventHandler for NodeHandler {
@@ -38,7 +39,10 @@ impl NodeHandler {
             InternalEvent::Timeout(timeout) => self.handle_timeout(timeout),
             InternalEvent::JumpToRound(height, round) => self.handle_new_round(height, round),
             InternalEvent::Shutdown => panic!("Shutdown should be processed in the event loop"),
-            InternalEvent::MessageVerified(msg) => self.handle_message(msg),
+            InternalEvent::MessageVerified(msg) => {
+                self.estimate_clock_skew(&msg);
+                self.handle_message(msg)
+            }
         }
     }
 
@@ -89,6 +93,9 @@ impl NodeHandler {
             }
             ExternalMessage::Shutdown => self.execute_later(InternalRequest::Shutdown),
             ExternalMessage::Rebroadcast => self.handle_rebroadcast(),
+            ExternalMessage::EvictTransaction(tx_hash) => self.handle_evict_tx(&tx_hash),
+            #[cfg(debug_assertions)]
+            ExternalMessage::Chaos(config) => self.set_chaos(config),
         }
     }
 
@@ -101,10 +108,12 @@ impl NodeHandler {
             return;
         }
         match timeout {
+            NodeTimeout::Round(..) | NodeTimeout::Propose(..) if self.is_replica() => {}
             NodeTimeout::Round(height, round) => self.handle_round_timeout(height, round),
             NodeTimeout::Request(data, peer) => self.handle_request_timeout(&data, peer),
             NodeTimeout::Status(height) => self.handle_status_timeout(height),
             NodeTimeout::PeerExchange => self.handle_peer_exchange_timeout(),
+            NodeTimeout::Ping => self.handle_ping_timeout(),
             NodeTimeout::UpdateApiState => self.handle_update_api_state_timeout(),
             NodeTimeout::Propose(height, round) => self.handle_propose_timeout(height, round),
         }
@@ -129,4 +138,35 @@ impl NodeHandler {
             )
         }
     }
+
+    /// Removes the transaction from the pool, unless it is included into a known proposal.
+    pub(crate) fn handle_evict_tx(&mut self, tx_hash: &Hash) {
+        if self.state.is_tx_proposed(tx_hash) {
+            warn!(
+                "Transaction {:?} is included into a proposal and cannot be evicted",
+                tx_hash
+            );
+            return;
+        }
+
+        let mut fork = self.blockchain.fork();
+        let tx_size = {
+            let mut schema = Schema::new(&mut fork);
+            let tx_size = schema
+                .transactions()
+                .get(tx_hash)
+                .map(|tx| tx.signed_message().raw().len() as u64);
+            if schema.reject_transaction(tx_hash).is_err() {
+                warn!("Transaction {:?} is not in the pool", tx_hash);
+                return;
+            }
+            tx_size.unwrap_or(0)
+        };
+        self.blockchain
+            .merge(fork.into_patch())
+            .expect("Unable to evict transaction from the persistent pool.");
+        self.state.remove_tx_pool_bytes(tx_size);
+        self.state.retain_tx_dependencies(|hash, _| hash != tx_hash);
+        info!("Evicted transaction {:?} from the pool", tx_hash);
+    }
 }
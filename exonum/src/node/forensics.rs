@@ -0,0 +1,305 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Forensic mode of the node.
+//!
+//! If the block hash computed by the node differs from the one agreed on by the validators,
+//! the node cannot continue and normally panics. In the forensic mode, the node stops
+//! processing the divergent height instead and captures a [`DivergenceReport`] containing
+//! the block computed by the node, the results of its transactions and the root hashes
+//! of the tables aggregated into the state hash. If a peer sends the committed block,
+//! the block is added to the report as well.
+//!
+//! In the forensic mode, the node also keeps the table root hashes for a number of
+//! the latest heights, so that a healthy node can provide them for the comparison:
+//!
+//! ```toml
+//! [forensics]
+//! # Number of the latest heights for which the table root hashes are kept.
+//! depth = 100
+//! ```
+//!
+//! The report, the table root hashes and their comparison are available via the private
+//! system API at `v1/forensics/report`, `v1/forensics/table_roots` and
+//! `v1/forensics/compare` respectively.
+//!
+//! [`DivergenceReport`]: struct.DivergenceReport.html
+
+use std::collections::BTreeMap;
+
+use blockchain::{Block, Schema, TableRoot, TransactionResult};
+use crypto::{CryptoHash, Hash, PublicKey};
+use explorer::TxStatus;
+use helpers::Height;
+use node::NodeHandler;
+use storage::Patch;
+
+/// Configuration of the forensic mode.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ForensicsConfig {
+    /// Number of the latest heights for which the table root hashes are kept.
+    pub depth: usize,
+}
+
+/// Root hashes of the tables at a certain height.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TableRoots {
+    /// Height of the block.
+    pub height: Height,
+    /// Root hashes of the core and service tables.
+    pub roots: Vec<TableRoot>,
+}
+
+/// Transaction of the divergent block with the result of its execution by the node.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExecutedTransaction {
+    /// Hash of the transaction.
+    pub tx_hash: Hash,
+    /// Result of the transaction execution.
+    #[serde(with = "TxStatus")]
+    pub status: TransactionResult,
+}
+
+/// Committed block received from a peer.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PeerBlock {
+    /// Peer which has sent the block.
+    pub author: PublicKey,
+    /// The block itself.
+    pub block: Block,
+}
+
+/// Diagnostic bundle captured when the block computed by the node differs from
+/// the committed one.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DivergenceReport {
+    /// Height of the divergent block.
+    pub height: Height,
+    /// Hash of the block agreed on by the validators.
+    pub expected_block_hash: Hash,
+    /// Block computed by the node.
+    pub block: Block,
+    /// Transactions of the block computed by the node with the results of their execution.
+    pub transactions: Vec<ExecutedTransaction>,
+    /// Root hashes of the tables after the execution of the block by the node.
+    pub table_roots: Vec<TableRoot>,
+    /// Committed block received from a peer, if any.
+    pub peer_block: Option<PeerBlock>,
+}
+
+/// Difference of the root hashes of a table.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TableRootDiff {
+    /// Identifier of the service owning the table.
+    pub service_id: u16,
+    /// Index of the table within the service.
+    pub table_idx: usize,
+    /// Root hash of the table computed by the node.
+    pub ours: Option<Hash>,
+    /// Root hash of the table provided by the peer.
+    pub peer: Option<Hash>,
+}
+
+impl DivergenceReport {
+    /// Compares the table root hashes of the report with the ones provided by a peer
+    /// and returns the tables which root hashes differ.
+    pub fn compare_roots(&self, peer_roots: &[TableRoot]) -> Vec<TableRootDiff> {
+        let mut tables: BTreeMap<(u16, usize), (Option<Hash>, Option<Hash>)> = BTreeMap::new();
+        for table in &self.table_roots {
+            tables
+                .entry((table.service_id, table.table_idx))
+                .or_insert((None, None))
+                .0 = Some(table.root);
+        }
+        for table in peer_roots {
+            tables
+                .entry((table.service_id, table.table_idx))
+                .or_insert((None, None))
+                .1 = Some(table.root);
+        }
+        tables
+            .into_iter()
+            .filter(|&(_, (ours, peer))| ours != peer)
+            .map(|((service_id, table_idx), (ours, peer))| TableRootDiff {
+                service_id,
+                table_idx,
+                ours,
+                peer,
+            }).collect()
+    }
+}
+
+impl NodeHandler {
+    /// Enables the forensic mode.
+    /// See [`forensics`](forensics/index.html) module for details.
+    pub fn set_forensics(&mut self, config: ForensicsConfig) {
+        self.forensics = Some(config);
+    }
+
+    /// Returns `true` if the forensic mode is enabled.
+    pub(crate) fn is_forensics_enabled(&self) -> bool {
+        self.forensics.is_some()
+    }
+
+    /// Returns `true` if the divergence at the given height has already been captured
+    /// along with the committed block received from a peer.
+    pub(crate) fn is_divergence_captured(&self, height: Height) -> bool {
+        self.api_state.divergence_report().map_or(false, |report| {
+            report.height == height && report.peer_block.is_some()
+        })
+    }
+
+    /// Saves the table root hashes of the latest committed block, if the forensic mode
+    /// is enabled.
+    pub(crate) fn record_table_roots(&self) {
+        let depth = match self.forensics {
+            Some(config) => config.depth,
+            None => return,
+        };
+        let snapshot = self.blockchain.snapshot();
+        let roots = TableRoots {
+            height: Schema::new(&snapshot).height(),
+            roots: self.blockchain.table_roots(&*snapshot),
+        };
+        self.api_state.add_table_roots(roots, depth);
+    }
+
+    /// Captures the divergence report for the block computed by the node with the given
+    /// `patch`, which hash differs from `expected_block_hash`. If the report for the same
+    /// height is already captured, only the peer block is added to it.
+    pub(crate) fn capture_divergence(
+        &self,
+        patch: &Patch,
+        expected_block_hash: Hash,
+        peer_block: Option<PeerBlock>,
+    ) {
+        let mut fork = self.blockchain.fork();
+        fork.merge(patch.clone());
+        let schema = Schema::new(&fork);
+        let block = schema.last_block();
+        error!(
+            "State divergence at height {}: computed block {:?}, expected block {:?}",
+            block.height(),
+            block.hash(),
+            expected_block_hash
+        );
+
+        if let Some(mut report) = self.api_state.divergence_report() {
+            if report.height == block.height() {
+                if report.peer_block.is_none() {
+                    report.peer_block = peer_block;
+                    self.api_state.set_divergence_report(report);
+                }
+                return;
+            }
+        }
+
+        let results = schema.transaction_results();
+        let transactions = schema
+            .block_transactions(block.height())
+            .iter()
+            .map(|tx_hash| ExecutedTransaction {
+                tx_hash,
+                status: results
+                    .get(&tx_hash)
+                    .expect("BUG: Cannot find result of the executed transaction"),
+            }).collect();
+        let report = DivergenceReport {
+            height: block.height(),
+            expected_block_hash,
+            table_roots: self.blockchain.table_roots(&fork),
+            block,
+            transactions,
+            peer_block,
+        };
+        self.api_state.set_divergence_report(report);
+    }
+
+    /// Captures the divergence report for the block with `our_block_hash` executed
+    /// by the node during the consensus.
+    pub(crate) fn capture_executed_divergence(
+        &self,
+        our_block_hash: &Hash,
+        expected_block_hash: Hash,
+    ) {
+        let patch = self
+            .state
+            .block(our_block_hash)
+            .expect("BUG: Cannot find the executed block")
+            .patch();
+        self.capture_divergence(patch, expected_block_hash, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DivergenceReport, TableRootDiff};
+    use blockchain::{Block, TableRoot};
+    use crypto::{hash, Hash};
+    use helpers::{Height, ValidatorId};
+
+    fn root(service_id: u16, table_idx: usize, data: &[u8]) -> TableRoot {
+        TableRoot {
+            service_id,
+            table_idx,
+            root: hash(data),
+        }
+    }
+
+    #[test]
+    fn compare_table_roots() {
+        let block = Block::new(
+            ValidatorId(0),
+            Height(1),
+            0,
+            &Hash::zero(),
+            &Hash::zero(),
+            &Hash::zero(),
+        );
+        let report = DivergenceReport {
+            height: Height(1),
+            expected_block_hash: Hash::zero(),
+            block,
+            transactions: Vec::new(),
+            table_roots: vec![root(0, 0, b"a"), root(0, 1, b"b"), root(1, 0, b"c")],
+            peer_block: None,
+        };
+
+        let peer_roots = vec![root(0, 0, b"a"), root(0, 1, b"x"), root(2, 0, b"d")];
+        assert_eq!(
+            report.compare_roots(&peer_roots),
+            vec![
+                TableRootDiff {
+                    service_id: 0,
+                    table_idx: 1,
+                    ours: Some(hash(b"b")),
+                    peer: Some(hash(b"x")),
+                },
+                TableRootDiff {
+                    service_id: 1,
+                    table_idx: 0,
+                    ours: Some(hash(b"c")),
+                    peer: None,
+                },
+                TableRootDiff {
+                    service_id: 2,
+                    table_idx: 0,
+                    ours: None,
+                    peer: Some(hash(b"d")),
+                },
+            ]
+        );
+        assert!(report.compare_roots(&report.table_roots).is_empty());
+    }
+}
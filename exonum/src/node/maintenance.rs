@@ -0,0 +1,128 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage maintenance performed by the node in the background.
+
+use chrono::{Timelike, Utc};
+use futures::sync::oneshot;
+
+use std::{
+    sync::mpsc::{self, Sender},
+    thread,
+    time::Duration,
+};
+
+use blockchain::Blockchain;
+use helpers::Height;
+use storage::{self, CompactionSchedule};
+
+/// Interval between the checks of the maintenance window, in seconds.
+const CHECK_INTERVAL_SECS: u64 = 60;
+
+/// Maximum number of blocks moved to the cold storage at once. The blocks of an existing
+/// database are moved in several batches once the cold storage is enabled.
+const MAX_ARCHIVED_BLOCKS_PER_BATCH: u64 = 16;
+
+/// Spawns a thread compacting the storage once per each occurrence of the maintenance
+/// window. The compaction is performed in a separate thread, so that it does not block
+/// the consensus.
+pub(crate) fn spawn_compaction_scheduler(blockchain: Blockchain, schedule: CompactionSchedule) {
+    thread::Builder::new()
+        .name("compaction-scheduler".to_owned())
+        .spawn(move || {
+            let mut compacted = false;
+            loop {
+                let hour = Utc::now().hour() as u8;
+                if !schedule.contains_hour(hour) {
+                    compacted = false;
+                } else if !compacted {
+                    info!("Starting scheduled storage compaction");
+                    match blockchain.compact(&schedule.prefixes) {
+                        Ok(()) => info!("Scheduled storage compaction finished"),
+                        Err(e) => error!("Scheduled storage compaction failed: {}", e),
+                    }
+                    compacted = true;
+                }
+                thread::sleep(Duration::from_secs(CHECK_INTERVAL_SECS));
+            }
+        }).expect("Unable to spawn compaction scheduler thread");
+}
+
+/// Compacts the storage in a separate thread, so that the caller is not blocked for
+/// the duration of the compaction. The returned receiver gets the result of the compaction.
+pub(crate) fn spawn_compaction(
+    blockchain: Blockchain,
+    prefixes: Vec<String>,
+) -> oneshot::Receiver<Result<(), storage::Error>> {
+    let (sender, receiver) = oneshot::channel();
+    thread::Builder::new()
+        .name("manual-compaction".to_owned())
+        .spawn(move || {
+            info!("Starting manual storage compaction");
+            let result = blockchain.compact(&prefixes);
+            match result {
+                Ok(()) => info!("Manual storage compaction finished"),
+                Err(ref e) => error!("Manual storage compaction failed: {}", e),
+            }
+            // The requester may have gone away; the result is logged anyway.
+            let _ = sender.send(result);
+        }).expect("Unable to spawn compaction thread");
+    receiver
+}
+
+/// Handle to the thread moving the old blocks to the cold storage. The blocks are moved
+/// in the background, so that the writes to the cold database do not block the consensus.
+#[derive(Debug)]
+pub(crate) struct ColdStorageArchiver {
+    depth: u64,
+    heights: Sender<Height>,
+}
+
+impl ColdStorageArchiver {
+    /// Spawns a thread keeping the data of the latest `depth` blocks in the hot storage.
+    pub fn spawn(blockchain: Blockchain, depth: u64) -> Self {
+        let (heights, receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name("cold-storage-archiver".to_owned())
+            .spawn(move || {
+                let mut blockchain = blockchain;
+                while let Ok(mut height) = receiver.recv() {
+                    // Only the latest request matters if the archiver lags behind.
+                    while let Ok(next) = receiver.try_recv() {
+                        height = next;
+                    }
+                    loop {
+                        match blockchain.archive_blocks(height, MAX_ARCHIVED_BLOCKS_PER_BATCH) {
+                            Ok(0) => break,
+                            Ok(_) => {}
+                            Err(e) => {
+                                error!("Unable to move the old blocks to the cold storage: {}", e);
+                                break;
+                            }
+                        }
+                    }
+                }
+            }).expect("Unable to spawn cold storage archiver thread");
+        Self { depth, heights }
+    }
+
+    /// Requests moving the blocks which became old after the block at `height` is committed.
+    /// Does not wait for the blocks to be moved.
+    pub fn notify(&self, height: Height) {
+        let height = Height(height.0.saturating_sub(self.depth));
+        if self.heights.send(height).is_err() {
+            error!("Cold storage archiver thread has stopped");
+        }
+    }
+}
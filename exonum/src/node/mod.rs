@@ -15,37 +15,74 @@
 //! Exonum node that performs consensus algorithm.
 //!
 //! For details about consensus message handling see messages module documentation.
+//!
+//! # Event loop
+//!
+//! The node is built around asynchronous event loops:
+//!
+//! - The handler thread runs a reactor polling network events, API requests and internal
+//!   events (timeouts and verified messages) and passes them to the handler one by one.
+//!   Internal events are polled first, so timeouts and shutdown are not delayed
+//!   by the incoming traffic.
+//! - The network thread runs a reactor serving peer connections via the [`Network`]
+//!   transport and firing the timeouts scheduled by the handler.
+//! - Messages are verified in a separate thread pool, see [`ThreadsConfig`].
+//!
+//! These parts communicate via bounded channels with capacities set
+//! in [`EventsPoolCapacity`], so a slow consumer applies backpressure to the producers.
+//!
+//! [`Network`]: trait.Network.html
+//! [`ThreadsConfig`]: struct.ThreadsConfig.html
+//! [`EventsPoolCapacity`]: struct.EventsPoolCapacity.html
 // spell-checker:ignore cors
 
 pub use self::{
+    alerting::{Alerting, AlertingConfig, DiskSpaceCondition, NodeMetrics},
     connect_list::{ConnectList, PeerAddress},
-    state::{RequestData, State, ValidatorState},
+    forensics::{DivergenceReport, ForensicsConfig, TableRoots},
+    network::{MemoryNetwork, Network, TcpNetwork},
+    plugins::{CommitEvent, CommitObserver, PluginConfig},
+    replica::{ReplicaConfig, ReplicaStatus},
+    state::{MemoryUsage, RequestData, State, ValidatorState},
+    status_file::NodeStatus,
 };
 
 // TODO: Temporary solution to get access to WAIT constants. (ECR-167)
 pub mod state;
 
+use chrono::{DateTime, Utc};
+use core_affinity;
 use failure::{self, Error};
 use futures::{sync::mpsc, Future, Sink};
+use num_cpus;
+use serde::{Deserialize, Deserializer};
 use tokio_core::reactor::Core;
 use tokio_threadpool::Builder as ThreadPoolBuilder;
 use toml::Value;
 
 use std::{
+    cmp,
     collections::{BTreeMap, HashSet},
-    fmt,
-    net::SocketAddr,
-    sync::Arc,
+    fmt, mem,
+    net::{SocketAddr, ToSocketAddrs},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     thread,
     time::{Duration, SystemTime},
 };
 
 use api::{
+    auth::{ApiAccessControl, ApiToken},
     backends::actix::{AllowOrigin, ApiRuntimeConfig, App, AppConfig, Cors, SystemRuntimeConfig},
+    consumers::{ApiConsumers, ConsumersConfig},
     ApiAccess, ApiAggregator,
 };
 use blockchain::{
-    Blockchain, ConsensusConfig, GenesisConfig, Schema, Service, SharedNodeState, ValidatorKeys,
+    offchain::ContentFetcher, Blockchain, ConsensusConfig, GenesisConfig, Schema, Service,
+    SharedNodeState, ValidatorKeys,
 };
 use crypto::{self, CryptoHash, Hash, PublicKey, SecretKey};
 use events::{
@@ -54,6 +91,7 @@ use events::{
     HandlerPart, InternalEvent, InternalPart, InternalRequest, NetworkConfiguration, NetworkEvent,
     NetworkPart, NetworkRequest, SyncSender, TimeoutRequest,
 };
+use explorer::{BlockchainExplorer, PrefetchProgress};
 use helpers::{
     config::ConfigManager,
     fabric::{NodePrivateConfig, NodePublicConfig},
@@ -61,13 +99,31 @@ use helpers::{
 };
 use messages::{Connect, Message, ProtocolMessage, RawTransaction, Signed, SignedMessage};
 use node::state::SharedConnectList;
-use storage::{Database, DbOptions};
+use storage::{CompactionSchedule, Database, DbOptions};
+
+#[cfg(debug_assertions)]
+use self::chaos::ChaosConfig;
+use self::maintenance::ColdStorageArchiver;
 
+pub mod alerting;
 mod basic;
+#[cfg(debug_assertions)]
+pub mod chaos;
 mod connect_list;
 mod consensus;
 mod events;
+pub mod forensics;
+pub(crate) mod maintenance;
+mod network;
+pub mod network_check;
+pub mod plugins;
+mod replica;
 mod requests;
+pub mod status_file;
+
+/// Maximum number of blocks pruned after each commit. The blocks of an existing database
+/// are thus pruned gradually once the pruning is enabled.
+const MAX_PRUNED_BLOCKS_PER_COMMIT: u64 = 16;
 
 /// External messages.
 #[derive(Debug)]
@@ -82,6 +138,11 @@ pub enum ExternalMessage {
     Shutdown,
     /// Rebroadcast transactions from the pool.
     Rebroadcast,
+    /// Remove the transaction from the pool.
+    EvictTransaction(Hash),
+    /// Replace the faults injected by the chaos testing hooks.
+    #[cfg(debug_assertions)]
+    Chaos(ChaosConfig),
 }
 
 /// Node timeout types.
@@ -99,6 +160,8 @@ pub enum NodeTimeout {
     UpdateApiState,
     /// Exchange peers timeout.
     PeerExchange,
+    /// Measure latency to the peers timeout.
+    Ping,
 }
 
 /// A helper trait that provides the node with information about the state of the system such
@@ -136,6 +199,43 @@ pub struct NodeHandler {
     config_manager: Option<ConfigManager>,
     /// Can we speed up Propose with transaction pressure?
     allow_expedited_propose: bool,
+    /// Observers notified about the committed blocks.
+    commit_observers: Vec<Box<dyn CommitObserver>>,
+    /// Should the transactions of proposals be checked before voting?
+    propose_precheck: bool,
+    /// Path to the status file updated after each block commit.
+    status_file: Option<PathBuf>,
+    /// Configuration of the consensus messages archive.
+    consensus_archive: Option<ConsensusArchiveConfig>,
+    /// Consensus messages accepted at the current height, which are archived
+    /// together with the block.
+    archived_messages: Vec<Message>,
+    /// Hashes of the `archived_messages`.
+    archived_hashes: HashSet<Hash>,
+    /// Interval between the latency measurements.
+    ping_interval: Option<Milliseconds>,
+    /// Threshold of the estimated clock skew of a validator above which a warning is logged.
+    max_clock_skew: Milliseconds,
+    /// Should the leader prepare the next proposal while the current block is committed?
+    propose_ahead: bool,
+    /// Transactions selected in advance for the proposal at the given height.
+    prepared_propose: Option<(Height, Vec<Hash>)>,
+    /// Thread moving the old blocks to the cold storage.
+    cold_storage: Option<ColdStorageArchiver>,
+    /// Number of the latest blocks which data is not pruned.
+    pruning_depth: Option<u64>,
+    /// Alerting on the node health conditions.
+    alerting: Option<Alerting>,
+    /// Configuration of the replica mode.
+    replica: Option<ReplicaConfig>,
+    /// Configuration of the forensic mode.
+    forensics: Option<ForensicsConfig>,
+    /// Validators with the committed evidence of equivocation, connections with which
+    /// are refused.
+    blocked_peers: HashSet<PublicKey>,
+    /// Faults injected by the chaos testing hooks.
+    #[cfg(debug_assertions)]
+    chaos: ChaosConfig,
 }
 
 /// Service configuration.
@@ -179,6 +279,42 @@ pub struct NodeApiConfig {
     ///
     /// [cors]: https://developer.mozilla.org/en-US/docs/Web/HTTP/CORS
     pub private_allow_origin: Option<AllowOrigin>,
+    /// Tokens granting access to private API endpoints. If neither tokens nor
+    /// the tokens file are specified, the private API is available without authorization.
+    #[serde(default)]
+    pub private_api_tokens: Vec<ApiToken>,
+    /// Path to a TOML file with additional tokens for the private API.
+    #[serde(default)]
+    pub private_api_tokens_file: Option<String>,
+    /// Resource accounting and quotas per API consumer.
+    #[serde(default)]
+    pub consumers: ConsumersConfig,
+    /// The number of the latest blocks read on the node start before the API starts
+    /// accepting requests. The prefetch is best-effort: no data is cached by the node
+    /// itself, so the following requests are faster only as long as the data is kept
+    /// by the database caches, e.g., the RocksDB block cache. The prefetch has no effect
+    /// for the in-memory database. If not specified, the prefetch phase is skipped.
+    #[serde(default)]
+    pub prefetch_blocks: Option<u64>,
+    /// If true, the public API rejects new transactions while the node is connected
+    /// to less validators than required to achieve the consensus.
+    #[serde(default)]
+    pub read_only_on_quorum_loss: bool,
+}
+
+impl NodeApiConfig {
+    /// Returns access control for the private API, or `None` if no tokens are configured.
+    pub fn private_access_control(&self) -> Result<Option<ApiAccessControl>, Error> {
+        let access_control = ApiAccessControl::load(
+            self.private_api_tokens.clone(),
+            self.private_api_tokens_file.as_ref(),
+        )?;
+        if access_control.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(access_control))
+        }
+    }
 }
 
 impl Default for NodeApiConfig {
@@ -189,6 +325,11 @@ impl Default for NodeApiConfig {
             private_api_address: None,
             public_allow_origin: None,
             private_allow_origin: None,
+            private_api_tokens: Vec::new(),
+            private_api_tokens_file: None,
+            consumers: ConsumersConfig::default(),
+            prefetch_blocks: None,
+            read_only_on_quorum_loss: false,
         }
     }
 }
@@ -220,8 +361,21 @@ impl Default for EventsPoolCapacity {
 /// Memory pool configuration parameters.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MemoryPoolConfig {
-    /// Maximum number of uncommitted transactions.
+    /// Maximum number of uncommitted transactions. New transactions are rejected
+    /// once the limit is reached, except for the transactions required by the consensus.
     pub tx_pool_capacity: usize,
+    /// Maximum total size of uncommitted transactions, in bytes. New transactions are
+    /// rejected once the limit is reached, except for the transactions required
+    /// by the consensus. Defaults to `None`, meaning that only `tx_pool_capacity`
+    /// limits the pool.
+    #[serde(default)]
+    pub tx_pool_bytes: Option<u64>,
+    /// Maximum total size of the buffered consensus messages from the future rounds
+    /// and heights, in bytes. Messages exceeding the limit are dropped; the node
+    /// catches up with the other validators using requests.
+    /// Defaults to `None`, meaning that the size is not limited.
+    #[serde(default)]
+    pub in_flight_bytes: Option<u64>,
     /// Sets the maximum number of messages that can be buffered on the event loop's
     /// notification channel before a send will fail.
     pub events_pool_capacity: EventsPoolCapacity,
@@ -231,6 +385,8 @@ impl Default for MemoryPoolConfig {
     fn default() -> Self {
         Self {
             tx_pool_capacity: 100_000,
+            tx_pool_bytes: None,
+            in_flight_bytes: None,
             events_pool_capacity: EventsPoolCapacity::default(),
         }
     }
@@ -267,8 +423,277 @@ pub struct NodeConfig {
     pub database: DbOptions,
     /// Node's ConnectList.
     pub connect_list: ConnectListConfig,
-    /// Transaction Verification Thread Pool size.
-    pub thread_pool_size: Option<u8>,
+    /// Configuration of the node threads. The legacy `thread_pool_size` value
+    /// is accepted as the size of the verification thread pool.
+    #[serde(
+        default,
+        alias = "thread_pool_size",
+        deserialize_with = "ThreadsConfig::deserialize_compat"
+    )]
+    pub threads: ThreadsConfig,
+    /// Plugins observing the block commits.
+    #[serde(default)]
+    pub plugins: Vec<PluginConfig>,
+    /// If true, the node checks the transactions of the received proposals before voting
+    /// for them and does not accept transactions failing `Transaction::verify`.
+    #[serde(default)]
+    pub propose_precheck: bool,
+    /// Optional schedule of the storage compaction.
+    #[serde(default)]
+    pub compaction: Option<CompactionSchedule>,
+    /// Optional path to the status file updated after each block commit,
+    /// e.g., for the liveness probes.
+    #[serde(default)]
+    pub status_file: Option<PathBuf>,
+    /// Optional configuration of the archive of the received consensus messages.
+    #[serde(default)]
+    pub consensus_archive: Option<ConsensusArchiveConfig>,
+    /// Optional interval in milliseconds between the measurements of the round-trip time
+    /// to the connected peers. The latency is not measured if not set.
+    #[serde(default)]
+    pub ping_interval: Option<Milliseconds>,
+    /// Optional threshold in milliseconds of the estimated clock skew of a validator,
+    /// above which a warning is logged (1000 by default). See [`PeerClockSkew`] for details.
+    ///
+    /// [`PeerClockSkew`]: struct.PeerClockSkew.html
+    #[serde(default)]
+    pub max_clock_skew: Option<Milliseconds>,
+    /// If true, the node selects the transactions for its next proposal as soon as it locks
+    /// on the current one, and proposes right after the commit if it is the leader of the
+    /// first round of the next height. Reduces the delay between blocks under load.
+    #[serde(default)]
+    pub propose_ahead: bool,
+    /// Optional configuration of the cold storage for the old blocks.
+    #[serde(default)]
+    pub cold_storage: Option<ColdStorageConfig>,
+    /// Optional configuration of the in-memory database backed by a file, which is used
+    /// instead of the persistent database.
+    #[serde(default)]
+    pub memory_db: Option<MemoryDbConfig>,
+    /// Optional configuration of the pruning of the old blocks.
+    #[serde(default)]
+    pub pruning: Option<PruningConfig>,
+    /// Optional configuration of the alerting on the node health conditions.
+    /// See [`alerting`](alerting/index.html) module for details.
+    #[serde(default)]
+    pub alerting: Option<AlertingConfig>,
+    /// If true, the node runs in the paranoid mode intended for long-running test networks.
+    /// See [`Blockchain::set_paranoid`] for details.
+    ///
+    /// [`Blockchain::set_paranoid`]: ../blockchain/struct.Blockchain.html#method.set_paranoid
+    #[serde(default)]
+    pub paranoid: bool,
+    /// Optional configuration of the replica mode, in which the node follows the block
+    /// stream of the upstream nodes instead of participating in the consensus.
+    /// See [`replica`](replica/index.html) module for details.
+    #[serde(default)]
+    pub replica: Option<ReplicaConfig>,
+    /// Optional configuration of the forensic mode, in which the node captures a diagnostic
+    /// report instead of panicking if its state diverges from the committed one.
+    /// See [`forensics`](forensics/index.html) module for details.
+    #[serde(default)]
+    pub forensics: Option<ForensicsConfig>,
+}
+
+/// Configuration of the archive of the received consensus messages.
+///
+/// If enabled, all `Propose`, `Prevote` and `Precommit` messages received by the node
+/// are persisted, so that they can be inspected with the private API afterwards.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConsensusArchiveConfig {
+    /// Number of the last heights for which the messages are kept.
+    pub depth: u64,
+}
+
+/// Configuration of the cold storage for the old blocks.
+///
+/// The data of the blocks older than `depth` heights, including the bodies of their
+/// transactions, is moved to a separate database, which can be placed on a slower
+/// and cheaper disk. The moved data remains accessible as usual.
+/// See [`TieredDB`](../storage/struct.TieredDB.html) for details.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ColdStorageConfig {
+    /// Path to the cold storage database.
+    pub path: PathBuf,
+    /// Number of the latest blocks kept in the primary database.
+    pub depth: u64,
+}
+
+/// Configuration of the in-memory database backed by a file.
+///
+/// If enabled, the node keeps its state in [`MemoryDB`] loaded from the file on start.
+/// The state is saved to the file on the `v1/flush` private API call and, optionally,
+/// periodically when the blocks are committed. Intended for small demo deployments.
+///
+/// [`MemoryDB`]: ../storage/struct.MemoryDB.html
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MemoryDbConfig {
+    /// Path to the file with the database state.
+    pub path: PathBuf,
+    /// Optional minimal interval in milliseconds between the periodic saves of the state.
+    /// The state is saved only on flush if not set.
+    #[serde(default)]
+    pub flush_interval: Option<Milliseconds>,
+}
+
+/// Configuration of the pruning of the old blocks.
+///
+/// The transaction bodies and the precommits of the blocks older than `keep_last_n_blocks`
+/// heights are removed from the storage. The block headers, the lists of the transaction
+/// hashes, the transaction results and locations, and the current state are kept intact.
+/// The explorer API reports the removed data as pruned, and the node does not serve
+/// the pruned blocks to the lagging peers.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PruningConfig {
+    /// Number of the latest blocks which data is kept. Must be positive.
+    pub keep_last_n_blocks: u64,
+}
+
+/// Configuration of the threads used by the node.
+///
+/// Besides the pools configured here, the node always runs a thread handling consensus
+/// messages and a thread handling network connections and timeouts.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThreadsConfig {
+    /// Size of the transaction verification thread pool. Defaults to the number of CPU cores
+    /// minus two cores reserved for the handler and network threads, but at least one.
+    #[serde(default)]
+    pub verification_threads: Option<usize>,
+    /// Number of worker threads of each API server. Defaults to a quarter of the CPU cores,
+    /// but at least one and at most eight.
+    #[serde(default)]
+    pub api_threads: Option<usize>,
+    /// Size of the thread pool executing the independent transactions of a block
+    /// in parallel. Transactions are executed serially on the handler thread if not set.
+    #[serde(default)]
+    pub execution_threads: Option<usize>,
+    /// Optional pinning of the node threads to CPU cores.
+    #[serde(default)]
+    pub core_pinning: Option<CorePinningConfig>,
+}
+
+/// CPU cores the node threads are pinned to. Cores are identified by zero-based indexes.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CorePinningConfig {
+    /// Core for the thread handling consensus messages.
+    #[serde(default)]
+    pub handler: Option<usize>,
+    /// Core for the network thread.
+    #[serde(default)]
+    pub network: Option<usize>,
+    /// Cores for the transaction verification threads. The threads are assigned
+    /// to the cores in the round-robin fashion.
+    #[serde(default)]
+    pub verification: Vec<usize>,
+}
+
+impl ThreadsConfig {
+    /// Maximum number of API worker threads used by default.
+    const MAX_DEFAULT_API_THREADS: usize = 8;
+
+    /// Deserializes either the `threads` section or the legacy `thread_pool_size` value.
+    fn deserialize_compat<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Compat {
+            ThreadPoolSize(usize),
+            Threads(ThreadsConfig),
+        }
+
+        Ok(match Compat::deserialize(deserializer)? {
+            Compat::ThreadPoolSize(size) => ThreadsConfig {
+                verification_threads: Some(size),
+                ..ThreadsConfig::default()
+            },
+            Compat::Threads(threads) => threads,
+        })
+    }
+
+    /// Returns the size of the transaction verification thread pool.
+    pub fn verification_threads(&self) -> usize {
+        self.verification_threads
+            .unwrap_or_else(|| cmp::max(1, num_cpus::get().saturating_sub(2)))
+    }
+
+    /// Returns the number of worker threads of each API server.
+    pub fn api_threads(&self) -> usize {
+        self.api_threads.unwrap_or_else(|| {
+            cmp::min(
+                cmp::max(1, num_cpus::get() / 4),
+                Self::MAX_DEFAULT_API_THREADS,
+            )
+        })
+    }
+
+    /// Checks the configuration against the number of available CPU cores.
+    pub fn validate(&self) -> Result<(), Error> {
+        let cores = num_cpus::get();
+        ensure!(
+            self.verification_threads != Some(0),
+            "Verification thread pool cannot be empty"
+        );
+        ensure!(
+            self.api_threads != Some(0),
+            "API servers should have at least one worker thread"
+        );
+        if let Some(ref pinning) = self.core_pinning {
+            let pinned_cores = pinning
+                .handler
+                .iter()
+                .chain(&pinning.network)
+                .chain(&pinning.verification);
+            for &core in pinned_cores {
+                ensure!(
+                    core < cores,
+                    "Unable to pin thread to CPU core {}, only {} cores are available",
+                    core,
+                    cores
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn warn_if_oversubscribed(&self) {
+        let cores = num_cpus::get();
+        let total_threads = 2
+            + self.verification_threads()
+            + 2 * self.api_threads()
+            + self.execution_threads.unwrap_or(0);
+        if total_threads > 2 * cores {
+            warn!(
+                "Node is configured to run {} threads on {} CPU cores, \
+                 consider decreasing thread pool sizes",
+                total_threads, cores
+            );
+        }
+    }
+
+    fn handler_core(&self) -> Option<usize> {
+        self.core_pinning.as_ref().and_then(|pinning| pinning.handler)
+    }
+
+    fn network_core(&self) -> Option<usize> {
+        self.core_pinning.as_ref().and_then(|pinning| pinning.network)
+    }
+
+    fn verification_cores(&self) -> Vec<usize> {
+        self.core_pinning
+            .as_ref()
+            .map(|pinning| pinning.verification.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Pins the current thread to the CPU core with the given index.
+fn pin_current_thread(core: usize) {
+    match core_affinity::get_core_ids().and_then(|ids| ids.into_iter().nth(core)) {
+        Some(core_id) => core_affinity::set_for_current(core_id),
+        None => warn!("Unable to pin thread to CPU core {}", core),
+    }
 }
 
 /// Configuration for the `NodeHandler`.
@@ -284,6 +709,8 @@ pub struct Configuration {
     pub peer_discovery: Vec<String>,
     /// Memory pool configuration.
     pub mempool: MemoryPoolConfig,
+    /// Check transactions of the received proposals before voting for them.
+    pub propose_precheck: bool,
 }
 
 /// Channel for messages, timeouts and api requests.
@@ -385,6 +812,15 @@ impl ConnectListConfig {
     }
 }
 
+/// Resolves the external address of the node, which may contain a hostname,
+/// to the socket address announced to the peers in the `Connect` message.
+fn resolve_external_address(address: &str) -> Result<SocketAddr, failure::Error> {
+    address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| format_err!("No socket addresses for external address `{}`", address))
+}
+
 impl NodeHandler {
     /// Creates `NodeHandler` using specified `Configuration`.
     pub fn new(
@@ -414,7 +850,16 @@ impl NodeHandler {
         info!("Validator id = '{:?}'", validator_id);
         let connect = Message::concrete(
             Connect::new(
-                external_address,
+                resolve_external_address(external_address).unwrap_or_else(|e| {
+                    // The node is still able to connect to its peers, though they
+                    // cannot connect back until the address is fixed in the config.
+                    error!(
+                        "Unable to resolve external address `{}`: {}; announcing \
+                         the listen address instead",
+                        external_address, e
+                    );
+                    config.listener.address
+                }),
                 system_state.current_time().into(),
                 &user_agent::get(),
             ),
@@ -423,13 +868,14 @@ impl NodeHandler {
         );
 
         let connect_list = config.listener.connect_list;
-        let state = State::new(
+        let propose_precheck = config.propose_precheck;
+        let mut state = State::new(
             validator_id,
             config.listener.consensus_public_key,
             config.listener.consensus_secret_key,
             config.service.service_public_key,
             config.service.service_secret_key,
-            config.mempool.tx_pool_capacity,
+            &config.mempool,
             connect_list,
             stored,
             connect,
@@ -439,6 +885,11 @@ impl NodeHandler {
             system_state.current_time(),
         );
 
+        state.add_tx_pool_bytes(Self::tx_pool_bytes(&blockchain));
+        for (tx_hash, dependency) in Self::tx_pool_dependencies(&blockchain) {
+            state.add_tx_dependency(tx_hash, dependency);
+        }
+
         let node_role = NodeRole::new(validator_id);
         let is_enabled = api_state.is_enabled();
         api_state.set_node_role(node_role);
@@ -459,7 +910,214 @@ impl NodeHandler {
             node_role,
             config_manager,
             allow_expedited_propose: true,
+            commit_observers: Vec::new(),
+            propose_precheck,
+            status_file: None,
+            consensus_archive: None,
+            archived_messages: Vec::new(),
+            archived_hashes: HashSet::new(),
+            ping_interval: None,
+            max_clock_skew: PeerClockSkew::DEFAULT_THRESHOLD,
+            propose_ahead: false,
+            prepared_propose: None,
+            cold_storage: None,
+            pruning_depth: None,
+            alerting: None,
+            replica: None,
+            forensics: None,
+            blocked_peers: HashSet::new(),
+            #[cfg(debug_assertions)]
+            chaos: ChaosConfig::default(),
+        }
+    }
+
+    /// Adds an observer notified after each block commit.
+    pub fn add_commit_observer(&mut self, observer: Box<dyn CommitObserver>) {
+        self.commit_observers.push(observer);
+    }
+
+    /// Sets the path to the status file updated after each block commit.
+    /// See [`status_file`](status_file/index.html) module for details.
+    pub fn set_status_file(&mut self, path: PathBuf) {
+        self.status_file = Some(path);
+    }
+
+    /// Enables the archive of the received consensus messages.
+    pub fn set_consensus_archive(&mut self, config: ConsensusArchiveConfig) {
+        self.consensus_archive = Some(config);
+    }
+
+    /// Enables the latency measurement with the given interval between the `Ping` messages.
+    pub fn set_ping_interval(&mut self, interval: Milliseconds) {
+        self.ping_interval = Some(interval);
+    }
+
+    /// Sets the threshold of the estimated clock skew of a validator above which
+    /// a warning is logged.
+    pub fn set_max_clock_skew(&mut self, threshold: Milliseconds) {
+        self.max_clock_skew = threshold;
+    }
+
+    /// Enables or disables preparing the next proposal while the current block is committed.
+    pub fn set_propose_ahead(&mut self, enabled: bool) {
+        self.propose_ahead = enabled;
+        if !enabled {
+            self.prepared_propose = None;
+        }
+    }
+
+    /// Enables moving the old blocks to the cold storage by the given archiver.
+    pub(crate) fn set_cold_storage(&mut self, archiver: ColdStorageArchiver) {
+        self.cold_storage = Some(archiver);
+    }
+
+    /// Enables pruning of the transaction bodies and the precommits of the blocks older
+    /// than `depth` heights. See [`PruningConfig`](struct.PruningConfig.html) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `depth` is zero, since the latest block must be kept intact.
+    pub fn set_pruning_depth(&mut self, depth: u64) {
+        assert!(depth > 0, "At least one latest block must be kept intact");
+        self.pruning_depth = Some(depth);
+    }
+
+    /// Enables the alerting on the node health conditions.
+    /// See [`alerting`](alerting/index.html) module for details.
+    pub fn set_alerting(&mut self, config: AlertingConfig) {
+        self.alerting = Some(Alerting::new(config));
+    }
+
+    /// Switches the node to the replica mode following the block stream of the upstreams.
+    /// See [`replica`](replica/index.html) module for details.
+    pub fn set_replica(&mut self, config: ReplicaConfig) {
+        if self.state.is_validator() {
+            warn!("The validator node does not take part in the consensus in the replica mode");
         }
+        self.replica = Some(config);
+        self.update_replica_status(None);
+    }
+
+    /// Remembers the accepted consensus message to archive it together with the block,
+    /// if the archive is enabled.
+    fn archive_consensus_message<T: ProtocolMessage>(&mut self, msg: &Signed<T>) {
+        if self.consensus_archive.is_some() && self.archived_hashes.insert(msg.hash()) {
+            self.archived_messages.push(msg.clone().into());
+        }
+    }
+
+    /// Takes the consensus messages accepted at the current height together with
+    /// the lowest height for which the archived messages are kept, or returns `None`
+    /// if the archive is disabled.
+    fn take_archived_messages(&mut self) -> Option<(Vec<Message>, Height)> {
+        self.archived_hashes.clear();
+        let messages = mem::replace(&mut self.archived_messages, Vec::new());
+        let height = self.state.height();
+        self.consensus_archive.map(|config| {
+            let start = height.saturating_add(1).saturating_sub(config.depth);
+            (messages, start)
+        })
+    }
+
+    /// Requests moving the old blocks to the cold storage, if it is configured.
+    fn archive_old_blocks(&self) {
+        if let Some(ref archiver) = self.cold_storage {
+            archiver.notify(self.state.height());
+        }
+    }
+
+    /// Prunes the data of the old blocks, if the pruning is enabled.
+    fn prune_old_blocks(&mut self) {
+        let depth = match self.pruning_depth {
+            Some(depth) => depth,
+            None => return,
+        };
+        let height = Height(self.state.height().0.saturating_sub(depth));
+        if let Err(e) = self
+            .blockchain
+            .prune_blocks(height, MAX_PRUNED_BLOCKS_PER_COMMIT)
+        {
+            error!("Unable to prune the old blocks: {}", e);
+        }
+    }
+
+    /// Writes the node status to the status file, if it is configured.
+    fn write_status_file(&self) {
+        let path = match self.status_file {
+            Some(ref path) => path,
+            None => return,
+        };
+        let validator_id = self.state.validator_id();
+        let status = NodeStatus {
+            height: self.blockchain.last_block().height(),
+            last_commit_time: self.system_state.current_time().into(),
+            role: if validator_id.is_some() {
+                "validator".to_owned()
+            } else {
+                "auditor".to_owned()
+            },
+            validator_id,
+            healthy: self.api_state.is_enabled() && self.api_state.consensus_status(),
+        };
+        if let Err(e) = status.write_to(path) {
+            error!("Unable to write status file {}: {}", path.display(), e);
+        }
+    }
+
+    /// Evaluates the alerting conditions and dispatches the alerts, if the alerting
+    /// is configured.
+    fn check_alerts(&mut self) {
+        if self.alerting.is_none() {
+            return;
+        }
+        let mempool_size = Schema::new(&self.blockchain.snapshot()).transactions_pool_len();
+        let has_quorum = self.api_state.consensus_status();
+        let height = self.blockchain.last_block().height();
+        let now = self.system_state.current_time();
+
+        if let Some(ref mut alerting) = self.alerting {
+            let free_disk_space = alerting
+                .config()
+                .free_disk_space
+                .as_ref()
+                .and_then(DiskSpaceCondition::free_space);
+            let metrics = NodeMetrics {
+                height,
+                has_quorum,
+                mempool_size,
+                free_disk_space,
+            };
+            let alerts = alerting.evaluate(&metrics, now);
+            alerting.dispatch(alerts);
+        }
+    }
+
+    /// Returns the total size of the transactions in the persistent pool.
+    fn tx_pool_bytes(blockchain: &Blockchain) -> u64 {
+        let snapshot = blockchain.snapshot();
+        let schema = Schema::new(&snapshot);
+        let transactions = schema.transactions();
+        schema
+            .transactions_pool()
+            .iter()
+            .filter_map(|hash| transactions.get(&hash))
+            .map(|tx| tx.signed_message().raw().len() as u64)
+            .sum()
+    }
+
+    /// Returns the dependencies of the transactions in the persistent pool.
+    fn tx_pool_dependencies(blockchain: &Blockchain) -> Vec<(Hash, Hash)> {
+        let snapshot = blockchain.snapshot();
+        let schema = Schema::new(&snapshot);
+        let transactions = schema.transactions();
+        schema
+            .transactions_pool()
+            .iter()
+            .filter_map(|hash| {
+                let raw = transactions.get(&hash)?;
+                let tx = blockchain.tx_from_raw(raw.payload().clone()).ok()?;
+                tx.depends_on().map(|dependency| (hash, dependency))
+            }).collect()
     }
 
     fn sign_message<T: ProtocolMessage>(&self, message: T) -> Signed<T> {
@@ -517,6 +1175,11 @@ impl NodeHandler {
         self.state().consensus_config().propose_timeout_threshold
     }
 
+    /// Returns `true` if transactions of the received proposals are checked before voting.
+    pub fn propose_precheck(&self) -> bool {
+        self.propose_precheck
+    }
+
     /// Returns `State` of the node.
     pub fn state(&self) -> &State {
         &self.state
@@ -527,6 +1190,7 @@ impl NodeHandler {
         let listen_address = self.system_state.listen_address();
         info!("Start listening address={}", listen_address);
 
+        self.update_blocked_peers();
         let peers: HashSet<_> = {
             let it = self.state.peers().values().map(|p| p.author());
             let it = it.chain(
@@ -537,6 +1201,7 @@ impl NodeHandler {
                     .map(|i| i.public_key),
             );
             let it = it.filter(|address| address != &self.state.our_connect_message().author());
+            let it = it.filter(|key| !self.blocked_peers.contains(key));
             it.collect()
         };
 
@@ -569,10 +1234,17 @@ impl NodeHandler {
         self.add_status_timeout();
         self.add_peer_exchange_timeout();
         self.add_update_api_state_timeout();
+        self.add_ping_timeout();
     }
 
     /// Sends the given message to a peer by its public key.
     pub fn send_to_peer<T: Into<SignedMessage>>(&mut self, public_key: PublicKey, message: T) {
+        #[cfg(debug_assertions)]
+        {
+            if self.chaos_drops_message_to(&public_key) {
+                return;
+            }
+        }
         let message = message.into();
         let request = NetworkRequest::SendMessage(public_key, message);
         self.channel.network_requests.send(request).log_error();
@@ -603,6 +1275,12 @@ impl NodeHandler {
         self.send_to_peer(key, connect.clone());
     }
 
+    /// Drops the connection with the peer.
+    pub fn disconnect(&mut self, key: PublicKey) {
+        let request = NetworkRequest::DisconnectWithPeer(key);
+        self.channel.network_requests.send(request).log_error();
+    }
+
     /// Add timeout request.
     pub fn add_timeout(&mut self, timeout: NodeTimeout, time: SystemTime) {
         let request = TimeoutRequest(time, timeout);
@@ -688,6 +1366,14 @@ impl NodeHandler {
         self.add_timeout(NodeTimeout::PeerExchange, time);
     }
 
+    /// Adds `NodeTimeout::Ping` timeout to the channel if the latency measurement is enabled.
+    pub fn add_ping_timeout(&mut self) {
+        if let Some(interval) = self.ping_interval {
+            let time = self.system_state.current_time() + Duration::from_millis(interval);
+            self.add_timeout(NodeTimeout::Ping, time);
+        }
+    }
+
     /// Adds `NodeTimeout::UpdateApiState` timeout to the channel.
     pub fn add_update_api_state_timeout(&mut self) {
         let time = self.system_state.current_time()
@@ -768,6 +1454,107 @@ pub struct ConnectInfo {
     pub public_key: PublicKey,
 }
 
+/// Information about the last `Status` message received from a peer.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PeerStatus {
+    /// Peer public key.
+    pub public_key: PublicKey,
+    /// Identifier of the peer if it is a validator.
+    pub validator_id: Option<ValidatorId>,
+    /// Height reported by the peer.
+    pub height: Height,
+    /// Time when the `Status` message was received.
+    pub time: DateTime<Utc>,
+}
+
+/// Round-trip time to a connected peer measured with the `Ping` messages.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PeerLatency {
+    /// Peer public key.
+    pub public_key: PublicKey,
+    /// Identifier of the peer if it is a validator.
+    pub validator_id: Option<ValidatorId>,
+    /// The last measured round-trip time in microseconds.
+    pub last_rtt: u64,
+    /// Exponential moving average of the round-trip time in microseconds.
+    pub average_rtt: u64,
+    /// Number of the measurements.
+    pub samples: u64,
+}
+
+impl PeerLatency {
+    /// Weight of the previous average in the moving average of the round-trip time.
+    const AVERAGE_WEIGHT: u64 = 7;
+
+    /// Creates a new instance with the single measurement.
+    pub fn new(public_key: PublicKey, validator_id: Option<ValidatorId>, rtt: u64) -> Self {
+        Self {
+            public_key,
+            validator_id,
+            last_rtt: rtt,
+            average_rtt: rtt,
+            samples: 1,
+        }
+    }
+
+    /// Adds a new measurement of the round-trip time.
+    pub fn update(&mut self, rtt: u64) {
+        self.last_rtt = rtt;
+        self.average_rtt =
+            (self.average_rtt * Self::AVERAGE_WEIGHT + rtt) / (Self::AVERAGE_WEIGHT + 1);
+        self.samples += 1;
+    }
+}
+
+/// Estimated clock skew of a validator relative to the local clock.
+///
+/// The skew is estimated from the times of the `Precommit` messages of the validator
+/// for the current height, which are set from the local clock of the validator when
+/// the messages are created. The transmission delay is compensated with half of
+/// the average round-trip time to the validator if the latency measurement is enabled
+/// (see `NodeConfig::ping_interval`); otherwise, the skew of a validator whose clock is
+/// behind the local one is overestimated by the transmission delay.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PeerClockSkew {
+    /// Peer public key.
+    pub public_key: PublicKey,
+    /// Identifier of the peer if it is a validator.
+    pub validator_id: Option<ValidatorId>,
+    /// The last estimated skew in milliseconds. Positive values mean that the clock
+    /// of the peer is ahead of the local one.
+    pub last_skew: i64,
+    /// Exponential moving average of the estimated skew in milliseconds.
+    pub average_skew: i64,
+    /// Number of the estimations.
+    pub samples: u64,
+}
+
+impl PeerClockSkew {
+    /// Default threshold of the clock skew in milliseconds above which a warning is logged.
+    pub const DEFAULT_THRESHOLD: Milliseconds = 1_000;
+    /// Weight of the previous average in the moving average of the skew.
+    const AVERAGE_WEIGHT: i64 = 7;
+
+    /// Creates a new instance with the single estimation.
+    pub fn new(public_key: PublicKey, validator_id: Option<ValidatorId>, skew: i64) -> Self {
+        Self {
+            public_key,
+            validator_id,
+            last_skew: skew,
+            average_skew: skew,
+            samples: 1,
+        }
+    }
+
+    /// Adds a new estimation of the skew.
+    pub fn update(&mut self, skew: i64) {
+        self.last_skew = skew;
+        self.average_skew =
+            (self.average_skew * Self::AVERAGE_WEIGHT + skew) / (Self::AVERAGE_WEIGHT + 1);
+        self.samples += 1;
+    }
+}
+
 impl fmt::Display for ConnectInfo {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.address)
@@ -817,7 +1604,11 @@ pub struct Node {
     handler: NodeHandler,
     channel: NodeChannel,
     max_message_len: u32,
-    thread_pool_size: Option<u8>,
+    threads: ThreadsConfig,
+    network: Arc<dyn Network>,
+    plugins: Vec<PluginConfig>,
+    compaction: Option<CompactionSchedule>,
+    cold_storage: Option<ColdStorageConfig>,
 }
 
 impl NodeChannel {
@@ -860,6 +1651,11 @@ impl Node {
             node_cfg.service_secret_key.clone(),
             ApiSender::new(channel.api_requests.0.clone()),
         );
+        blockchain.set_commit_sync_policy(node_cfg.database.commit_sync);
+        if let Some(threads) = node_cfg.threads.execution_threads {
+            blockchain.set_execution_threads(threads);
+        }
+        blockchain.set_paranoid(node_cfg.paranoid);
         blockchain.initialize(node_cfg.genesis.clone()).unwrap();
 
         let peers = node_cfg.connect_list.addresses();
@@ -878,12 +1674,14 @@ impl Node {
             mempool: node_cfg.mempool,
             network: node_cfg.network,
             peer_discovery: peers,
+            propose_precheck: node_cfg.propose_precheck,
         };
 
         let api_state = SharedNodeState::new(node_cfg.api.state_update_timeout as u64);
+        api_state.set_read_only_on_quorum_loss(node_cfg.api.read_only_on_quorum_loss);
         let system_state = Box::new(DefaultSystemState(node_cfg.listen_address));
         let network_config = config.network;
-        let handler = NodeHandler::new(
+        let mut handler = NodeHandler::new(
             blockchain,
             &node_cfg.external_address,
             channel.node_sender(),
@@ -892,44 +1690,128 @@ impl Node {
             api_state,
             config_file_path,
         );
+        if let Some(path) = node_cfg.status_file {
+            handler.set_status_file(path);
+        }
+        if let Some(archive) = node_cfg.consensus_archive {
+            handler.set_consensus_archive(archive);
+        }
+        if let Some(interval) = node_cfg.ping_interval {
+            handler.set_ping_interval(interval);
+        }
+        if let Some(threshold) = node_cfg.max_clock_skew {
+            handler.set_max_clock_skew(threshold);
+        }
+        handler.set_propose_ahead(node_cfg.propose_ahead);
+        if let Some(pruning) = node_cfg.pruning {
+            handler.set_pruning_depth(pruning.keep_last_n_blocks);
+        }
+        if let Some(alerting) = node_cfg.alerting {
+            handler.set_alerting(alerting);
+        }
+        if let Some(replica) = node_cfg.replica {
+            handler.set_replica(replica);
+        }
+        if let Some(forensics) = node_cfg.forensics {
+            handler.set_forensics(forensics);
+        }
         Self {
             api_options: node_cfg.api,
             handler,
             channel,
             network_config,
             max_message_len: node_cfg.genesis.consensus.max_message_len,
-            thread_pool_size: node_cfg.thread_pool_size,
+            threads: node_cfg.threads,
+            network: Arc::new(TcpNetwork),
+            plugins: node_cfg.plugins,
+            compaction: node_cfg.compaction,
+            cold_storage: node_cfg.cold_storage,
         }
     }
 
+    /// Replaces the transport used to communicate with other peers. By default,
+    /// the node uses `TcpNetwork`.
+    pub fn with_network<N: Network + 'static>(mut self, network: N) -> Self {
+        self.network = Arc::new(network);
+        self
+    }
+
+    /// Adds an observer notified after each block commit. Observers from the `plugins`
+    /// section of the node configuration are added when the node is started.
+    pub fn with_commit_observer<O: CommitObserver + 'static>(mut self, observer: O) -> Self {
+        self.handler.add_commit_observer(Box::new(observer));
+        self
+    }
+
+    /// Sets the fetcher of the off-chain content referenced by the transactions.
+    /// See the [`offchain`](../blockchain/offchain/index.html) module for details.
+    pub fn with_offchain_fetcher<F: ContentFetcher + 'static>(mut self, fetcher: F) -> Self {
+        self.handler
+            .blockchain
+            .set_offchain_fetcher(Arc::new(fetcher));
+        self
+    }
+
     /// Launches only consensus messages handler.
     /// This may be used if you want to customize api with the `ApiContext`.
     pub fn run_handler(mut self, handshake_params: &HandshakeParams) -> Result<(), Error> {
+        self.threads.validate()?;
+        self.threads.warn_if_oversubscribed();
+        for plugin in &self.plugins {
+            self.handler.add_commit_observer(plugins::load_plugin(plugin)?);
+        }
+        if let Some(schedule) = self.compaction.take() {
+            schedule.validate()?;
+            maintenance::spawn_compaction_scheduler(self.handler.blockchain.clone(), schedule);
+        }
+        if let Some(cold_storage) = self.cold_storage.take() {
+            if self.handler.blockchain.supports_archive() {
+                let blockchain = self.handler.blockchain.clone();
+                let archiver = ColdStorageArchiver::spawn(blockchain, cold_storage.depth);
+                self.handler.set_cold_storage(archiver);
+            } else {
+                warn!("The database has no cold storage, old blocks are kept in place");
+            }
+        }
         self.handler.initialize();
 
-        let pool_size = self.thread_pool_size;
+        let threads = self.threads.clone();
+        let network = Arc::clone(&self.network);
         let (handler_part, network_part, internal_part) = self.into_reactor();
         let handshake_params = handshake_params.clone();
 
+        let handler_core = threads.handler_core();
         let network_thread = thread::spawn(move || {
+            if let Some(core) = threads.network_core() {
+                pin_current_thread(core);
+            }
             let mut core = Core::new().map_err(into_failure)?;
             let handle = core.handle();
 
             let mut pool_builder = ThreadPoolBuilder::new();
-            if let Some(pool_size) = pool_size {
-                pool_builder.pool_size(pool_size as usize);
+            pool_builder.pool_size(threads.verification_threads());
+            let verification_cores = threads.verification_cores();
+            if !verification_cores.is_empty() {
+                let next_core = AtomicUsize::new(0);
+                pool_builder.after_start(move || {
+                    let index = next_core.fetch_add(1, Ordering::SeqCst);
+                    pin_current_thread(verification_cores[index % verification_cores.len()]);
+                });
             }
             let thread_pool = pool_builder.build();
             let executor = thread_pool.sender().clone();
 
             core.handle().spawn(internal_part.run(handle, executor));
 
-            let network_handler = network_part.run(&core.handle(), &handshake_params);
+            let network_handler = network.run(network_part, &core.handle(), &handshake_params);
             core.run(network_handler)
                 .map(drop)
                 .map_err(|e| format_err!("An error in the `Network` thread occurred: {}", e))
         });
 
+        if let Some(core) = handler_core {
+            pin_current_thread(core);
+        }
         let mut core = Core::new().map_err(into_failure)?;
         core.run(handler_part.run())
             .map_err(|_| format_err!("An error in the `Handler` thread occurred"))?;
@@ -943,6 +1825,13 @@ impl Node {
     /// Private api prefix is `/api/services/{service_name}`
     pub fn run(self) -> Result<(), failure::Error> {
         trace!("Running node.");
+        self.threads.validate()?;
+        let api_threads = self.threads.api_threads();
+        let private_access_control = self.api_options.private_access_control()?;
+        let consumers = ApiConsumers::new(self.api_options.consumers.clone());
+        if let Some(blocks) = self.api_options.prefetch_blocks {
+            self.prefetch(blocks);
+        }
         // Runs actix-web api.
         let actix_api_runtime = SystemRuntimeConfig {
             api_runtimes: {
@@ -965,6 +1854,9 @@ impl Node {
                             .public_allow_origin
                             .clone()
                             .map(into_app_config),
+                        access_control: None,
+                        consumers: Some(consumers.clone()),
+                        workers: Some(api_threads),
                     }).into_iter();
                 let private_api_handler = self
                     .api_options
@@ -977,6 +1869,9 @@ impl Node {
                             .private_allow_origin
                             .clone()
                             .map(into_app_config),
+                        access_control: private_access_control,
+                        consumers: Some(consumers.clone()),
+                        workers: Some(api_threads),
                     }).into_iter();
                 // Collects API handlers.
                 public_api_handler
@@ -1006,6 +1901,34 @@ impl Node {
         Ok(())
     }
 
+    /// Preloads the latest `count` blocks and the service data touched by them,
+    /// reporting the progress to the shared node state.
+    fn prefetch(&self, count: u64) {
+        let blockchain = &self.handler.blockchain;
+        let api_state = &self.handler.api_state;
+        let explorer = BlockchainExplorer::new(blockchain);
+        let mut progress = PrefetchProgress {
+            total_blocks: cmp::min(count, explorer.height().0 + 1),
+            ..PrefetchProgress::default()
+        };
+        api_state.set_prefetch_progress(progress);
+        info!("Prefetching {} latest blocks", progress.total_blocks);
+
+        let start = SystemTime::now();
+        let from = explorer.prefetch(count, |loaded_blocks| {
+            progress.loaded_blocks = loaded_blocks;
+            api_state.set_prefetch_progress(progress);
+        });
+        let snapshot = blockchain.snapshot();
+        for service in blockchain.service_map().values() {
+            service.prefetch(snapshot.as_ref(), from);
+        }
+
+        progress.completed = true;
+        api_state.set_prefetch_progress(progress);
+        info!("Prefetch finished in {:?}", start.elapsed().unwrap_or_default());
+    }
+
     fn into_reactor(self) -> (HandlerPart<NodeHandler>, NetworkPart, InternalPart) {
         let connect_message = self.state().our_connect_message().clone();
         let connect_list = self.state().connect_list().clone();
@@ -1168,4 +2091,43 @@ mod tests {
         let schema = Schema::new(&snapshot);
         assert_eq!(schema.transactions_pool_len(), 0);
     }
+
+    #[test]
+    fn test_threads_config() {
+        let threads = ThreadsConfig::default();
+        assert!(threads.validate().is_ok());
+        assert!(threads.verification_threads() >= 1);
+        assert!(threads.api_threads() >= 1);
+        assert!(threads.api_threads() <= ThreadsConfig::MAX_DEFAULT_API_THREADS);
+
+        let threads = ThreadsConfig {
+            verification_threads: Some(0),
+            ..ThreadsConfig::default()
+        };
+        assert!(threads.validate().is_err());
+
+        let threads = ThreadsConfig {
+            core_pinning: Some(CorePinningConfig {
+                network: Some(num_cpus::get()),
+                ..CorePinningConfig::default()
+            }),
+            ..ThreadsConfig::default()
+        };
+        assert!(threads.validate().is_err());
+    }
+
+    #[test]
+    fn test_legacy_thread_pool_size() {
+        let node_cfg = helpers::generate_testnet_config(1, 16_500)[0].clone();
+        let mut value = Value::try_from(&node_cfg).unwrap();
+        {
+            let table = value.as_table_mut().unwrap();
+            table.remove("threads");
+            table.insert("thread_pool_size".to_owned(), Value::Integer(3));
+        }
+
+        let node_cfg: NodeConfig = value.try_into().unwrap();
+        assert_eq!(node_cfg.threads.verification_threads, Some(3));
+        assert_eq!(node_cfg.threads.api_threads, None);
+    }
 }
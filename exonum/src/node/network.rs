@@ -0,0 +1,326 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Transports used by the node to communicate with other peers.
+
+use failure;
+use futures::{
+    sync::mpsc::{self, UnboundedSender},
+    Future, Sink, Stream,
+};
+use rand::{self, Rng};
+use tokio_core::reactor::{Handle, Timeout};
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crypto::PublicKey;
+use events::{
+    network::ConnectedPeerAddr, noise::HandshakeParams, NetworkEvent, NetworkPart, NetworkRequest,
+};
+use messages::{Connect, Signed, SignedMessage};
+use node::state::SharedConnectList;
+
+/// Transport used by the node to exchange messages with other peers.
+///
+/// The transport handles `NetworkRequest`s issued by the node and reports `NetworkEvent`s
+/// back to it.
+pub trait Network: Send + Sync + fmt::Debug {
+    /// Runs the transport on the given event loop. The returned future completes after
+    /// the `NetworkRequest::Shutdown` request is received.
+    fn run(
+        &self,
+        part: NetworkPart,
+        handle: &Handle,
+        handshake_params: &HandshakeParams,
+    ) -> Box<dyn Future<Item = (), Error = failure::Error>>;
+}
+
+/// TCP transport with the encrypted connections between peers. This transport is used
+/// by the node by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcpNetwork;
+
+impl Network for TcpNetwork {
+    fn run(
+        &self,
+        part: NetworkPart,
+        handle: &Handle,
+        handshake_params: &HandshakeParams,
+    ) -> Box<dyn Future<Item = (), Error = failure::Error>> {
+        Box::new(part.run(handle, handshake_params))
+    }
+}
+
+/// In-memory transport connecting the nodes running in the same process.
+///
+/// All the nodes sharing clones of the same `MemoryNetwork` instance can communicate
+/// with each other; no sockets are opened. The transport is intended for tests, so it
+/// allows to simulate the message delivery latency and the packet loss.
+///
+/// # Examples
+///
+/// ```no_run
+/// # extern crate exonum;
+/// # use exonum::helpers;
+/// # use exonum::node::{MemoryNetwork, Node};
+/// # use exonum::storage::MemoryDB;
+/// # use std::{thread, time::Duration};
+/// # fn main() {
+/// let network = MemoryNetwork::new()
+///     .with_latency(Duration::from_millis(10))
+///     .with_packet_loss(0.05);
+/// for node_cfg in helpers::generate_testnet_config(4, 16_500) {
+///     let node = Node::new(MemoryDB::new(), vec![], node_cfg, None)
+///         .with_network(network.clone());
+///     thread::spawn(move || node.run().unwrap());
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryNetwork {
+    peers: Arc<Mutex<HashMap<PublicKey, MemoryPeer>>>,
+    latency: Duration,
+    packet_loss: f64,
+}
+
+#[derive(Debug)]
+struct MemoryPeer {
+    inbox: UnboundedSender<NetworkEvent>,
+    connect: Signed<Connect>,
+    address: SocketAddr,
+    connect_list: SharedConnectList,
+    connections: HashSet<PublicKey>,
+}
+
+impl MemoryNetwork {
+    /// Creates a new network without latency and packet loss.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the delay before a message is delivered to the recipient.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Sets the probability that a message is lost. Connection events are
+    /// never lost.
+    ///
+    /// # Panics
+    ///
+    /// If `packet_loss` is outside of `[0, 1]` range.
+    pub fn with_packet_loss(mut self, packet_loss: f64) -> Self {
+        assert!(
+            packet_loss >= 0.0 && packet_loss <= 1.0,
+            "Packet loss probability should be in [0, 1] range"
+        );
+        self.packet_loss = packet_loss;
+        self
+    }
+
+    fn register(&self, key: PublicKey, peer: MemoryPeer) {
+        let mut peers = self.peers.lock().expect("Expected mutex lock");
+        peers.insert(key, peer);
+    }
+
+    fn unregister(&self, key: &PublicKey) {
+        let mut peers = self.peers.lock().expect("Expected mutex lock");
+        if let Some(peer) = peers.remove(key) {
+            for remote_key in &peer.connections {
+                if let Some(remote) = peers.get_mut(remote_key) {
+                    remote.connections.remove(key);
+                    let _ = remote
+                        .inbox
+                        .unbounded_send(NetworkEvent::PeerDisconnected(*key));
+                }
+            }
+        }
+    }
+
+    fn send_message(
+        &self,
+        handle: &Handle,
+        from: PublicKey,
+        to: PublicKey,
+        message: SignedMessage,
+    ) {
+        let mut peers = self.peers.lock().expect("Expected mutex lock");
+        let connected = peers
+            .get(&from)
+            .map_or(false, |peer| peer.connections.contains(&to));
+
+        if !connected {
+            let connect = match Self::connect(&mut peers, from, to) {
+                Some(connect) => connect,
+                None => {
+                    if let Some(peer) = peers.get(&from) {
+                        let _ = peer
+                            .inbox
+                            .unbounded_send(NetworkEvent::UnableConnectToPeer(to));
+                    }
+                    return;
+                }
+            };
+            // As in the TCP transport, `Connect` is sent during the handshake.
+            if &message == connect.signed_message() {
+                return;
+            }
+        }
+
+        if self.packet_loss > 0.0 && rand::thread_rng().gen_bool(self.packet_loss) {
+            trace!("Message from {} to {} is lost", from, to);
+            return;
+        }
+        if let Some(peer) = peers.get(&to) {
+            let event = NetworkEvent::MessageReceived(message.raw().to_vec());
+            self.deliver(handle, peer.inbox.clone(), event);
+        }
+    }
+
+    /// Connects peers with each other and returns the `Connect` message of the initiator
+    /// or `None` if the connection cannot be established.
+    fn connect(
+        peers: &mut HashMap<PublicKey, MemoryPeer>,
+        from: PublicKey,
+        to: PublicKey,
+    ) -> Option<Signed<Connect>> {
+        let (from_connect, from_address, remote_address) = {
+            let peer = peers.get(&from)?;
+            let remote = peers.get(&to)?;
+            if !remote.connect_list.is_peer_allowed(&from) {
+                return None;
+            }
+            let remote_address = peer.connect_list.find_address_by_key(&to)?.address;
+            (peer.connect.clone(), peer.address, remote_address)
+        };
+
+        let remote_connect = {
+            let remote = peers.get_mut(&to)?;
+            remote.connections.insert(from);
+            let address = ConnectedPeerAddr::In(from_address);
+            let _ = remote
+                .inbox
+                .unbounded_send(NetworkEvent::PeerConnected(address, from_connect.clone()));
+            (remote.connect.clone(), remote.address)
+        };
+
+        let peer = peers.get_mut(&from)?;
+        peer.connections.insert(to);
+        let address = ConnectedPeerAddr::Out(remote_address, remote_connect.1);
+        let _ = peer
+            .inbox
+            .unbounded_send(NetworkEvent::PeerConnected(address, remote_connect.0));
+        Some(from_connect)
+    }
+
+    fn disconnect(&self, from: PublicKey, to: PublicKey) {
+        let mut peers = self.peers.lock().expect("Expected mutex lock");
+        let was_connected = peers
+            .get_mut(&from)
+            .map_or(false, |peer| peer.connections.remove(&to));
+        if !was_connected {
+            return;
+        }
+
+        if let Some(peer) = peers.get(&from) {
+            let _ = peer
+                .inbox
+                .unbounded_send(NetworkEvent::PeerDisconnected(to));
+        }
+        if let Some(remote) = peers.get_mut(&to) {
+            remote.connections.remove(&from);
+            let _ = remote
+                .inbox
+                .unbounded_send(NetworkEvent::PeerDisconnected(from));
+        }
+    }
+
+    fn deliver(&self, handle: &Handle, inbox: UnboundedSender<NetworkEvent>, event: NetworkEvent) {
+        if self.latency == Duration::default() {
+            let _ = inbox.unbounded_send(event);
+            return;
+        }
+
+        match Timeout::new(self.latency, handle) {
+            Ok(timeout) => handle.spawn(timeout.map_err(drop).map(move |_| {
+                let _ = inbox.unbounded_send(event);
+            })),
+            Err(e) => error!("Unable to set up message delivery timeout: {}", e),
+        }
+    }
+}
+
+impl Network for MemoryNetwork {
+    fn run(
+        &self,
+        part: NetworkPart,
+        handle: &Handle,
+        _handshake_params: &HandshakeParams,
+    ) -> Box<dyn Future<Item = (), Error = failure::Error>> {
+        let key = part.our_connect_message.author();
+        let (inbox, inbox_rx) = mpsc::unbounded();
+        self.register(
+            key,
+            MemoryPeer {
+                inbox,
+                connect: part.our_connect_message,
+                address: part.listen_address,
+                connect_list: part.connect_list,
+                connections: HashSet::new(),
+            },
+        );
+
+        // Events are passed to the node via the inbox to keep them in order.
+        let forward = part
+            .network_tx
+            .sink_map_err(drop)
+            .send_all(inbox_rx)
+            .map(drop);
+        handle.spawn(forward);
+
+        let network = self.clone();
+        let handle = handle.clone();
+        let requests = part
+            .network_requests
+            .1
+            .map_err(|_| format_err!("Error while processing outgoing Network Requests"))
+            .take_while(|request| match request {
+                NetworkRequest::Shutdown => Ok(false),
+                _ => Ok(true),
+            })
+            .for_each(move |request| {
+                match request {
+                    NetworkRequest::SendMessage(to, message) => {
+                        network.send_message(&handle, key, to, message)
+                    }
+                    NetworkRequest::DisconnectWithPeer(to) => network.disconnect(key, to),
+                    NetworkRequest::Shutdown => unreachable!(),
+                }
+                Ok(())
+            });
+
+        let network = self.clone();
+        Box::new(requests.then(move |res| {
+            network.unregister(&key);
+            res
+        }))
+    }
+}
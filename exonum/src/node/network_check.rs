@@ -0,0 +1,405 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dry run of the network handshakes with the peers from the connect list.
+//!
+//! The check loads the node configuration, performs the Noise handshake and exchanges
+//! `Connect` messages with every configured peer in the same way as the node does
+//! on startup, and reports the outcome for each peer. No blocks or consensus messages
+//! are exchanged, so the check can be run next to a working node, e.g., to validate
+//! firewall or connect list changes before restarting a production validator.
+//!
+//! Note that the handshake succeeds on the initiator side even if the peer does not
+//! have this node in its own connect list; such a peer drops the connection right
+//! after the handshake, which is not detected by the check.
+
+use failure;
+use futures::{future, Future};
+use tokio_core::reactor::{Core, Handle, Timeout};
+use tokio_dns;
+
+use std::{
+    fmt,
+    time::{Duration, SystemTime},
+};
+
+use super::{resolve_external_address, ConnectInfo, ConnectList, NodeConfig};
+use crypto::PublicKey;
+use events::noise::{Handshake, HandshakeParams, NoiseHandshake};
+use helpers::user_agent;
+use messages::{Connect, Message, Service, Signed};
+use node::state::SharedConnectList;
+
+/// Outcome of the handshake with a single peer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PeerStatus {
+    /// The handshake succeeded and the peer runs the same Exonum version.
+    Reachable {
+        /// User agent reported by the peer.
+        user_agent: String,
+    },
+    /// The TCP connection with the peer could not be established.
+    Unreachable(String),
+    /// The connection was established, but the handshake failed. The most common reason
+    /// is that the peer uses a consensus key different from the one in the connect list.
+    HandshakeFailed(String),
+    /// The `Connect` message of the peer is signed by an unexpected key.
+    KeyMismatch {
+        /// Actual consensus key of the peer.
+        actual: PublicKey,
+    },
+    /// The handshake succeeded, but the peer runs a different Exonum version.
+    VersionMismatch {
+        /// User agent reported by the peer.
+        user_agent: String,
+    },
+}
+
+impl PeerStatus {
+    /// Returns `true` if the node will be able to communicate with the peer.
+    ///
+    /// Version mismatches are reported, but are not considered fatal.
+    pub fn is_ok(&self) -> bool {
+        match *self {
+            PeerStatus::Reachable { .. } | PeerStatus::VersionMismatch { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for PeerStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PeerStatus::Reachable { ref user_agent } => write!(f, "ok ({})", user_agent),
+            PeerStatus::Unreachable(ref e) => write!(f, "unreachable: {}", e),
+            PeerStatus::HandshakeFailed(ref e) => write!(
+                f,
+                "handshake failed, the peer may use another consensus key: {}",
+                e
+            ),
+            PeerStatus::KeyMismatch { ref actual } => {
+                write!(f, "key mismatch: the peer uses key {}", actual)
+            }
+            PeerStatus::VersionMismatch { ref user_agent } => write!(
+                f,
+                "version mismatch: the peer runs {}, this node runs {}",
+                exonum_version(user_agent),
+                exonum_version(&user_agent::get())
+            ),
+        }
+    }
+}
+
+/// Outcome of the check for a single peer from the connect list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerReport {
+    /// Consensus key of the peer from the connect list.
+    pub public_key: PublicKey,
+    /// Address of the peer from the connect list.
+    pub address: String,
+    /// Outcome of the handshake.
+    pub status: PeerStatus,
+}
+
+/// Performs handshakes with all the peers from the connect list of the node
+/// and returns the reports in the order of the connect list.
+///
+/// Each peer is given `timeout` to establish the connection and complete the handshake;
+/// the peers are checked concurrently.
+pub fn check_network(
+    config: &NodeConfig,
+    timeout: Duration,
+) -> Result<Vec<PeerReport>, failure::Error> {
+    let mut core = Core::new()?;
+    let params = handshake_params(config)?;
+    check_peers(&mut core, &params, &config.connect_list.peers, timeout)
+}
+
+fn handshake_params(config: &NodeConfig) -> Result<HandshakeParams, failure::Error> {
+    let connect = Message::concrete(
+        Connect::new(
+            resolve_external_address(&config.external_address)?,
+            SystemTime::now().into(),
+            &user_agent::get(),
+        ),
+        config.consensus_public_key,
+        &config.consensus_secret_key,
+    );
+    let connect_list = ConnectList::from_config(config.connect_list.clone());
+    Ok(HandshakeParams::new(
+        config.consensus_public_key,
+        config.consensus_secret_key.clone(),
+        SharedConnectList::from_connect_list(connect_list),
+        connect,
+        config.genesis.consensus.max_message_len,
+    ))
+}
+
+fn check_peers(
+    core: &mut Core,
+    params: &HandshakeParams,
+    peers: &[ConnectInfo],
+    timeout: Duration,
+) -> Result<Vec<PeerReport>, failure::Error> {
+    let handle = core.handle();
+    let checks = peers
+        .iter()
+        .map(|peer| {
+            let public_key = peer.public_key;
+            let address = peer.address.clone();
+            check_peer(&handle, params, peer, timeout).map(move |status| PeerReport {
+                public_key,
+                address,
+                status,
+            })
+        }).collect::<Vec<_>>();
+    core.run(future::join_all(checks))
+        .map_err(|()| format_err!("Network check was interrupted"))
+}
+
+fn check_peer(
+    handle: &Handle,
+    params: &HandshakeParams,
+    peer: &ConnectInfo,
+    timeout: Duration,
+) -> Box<dyn Future<Item = PeerStatus, Error = ()>> {
+    let mut params = params.clone();
+    params.set_remote_key(peer.public_key);
+    let expected_key = peer.public_key;
+
+    let handshake = tokio_dns::TcpStream::connect(peer.address.as_str())
+        .map_err(|e| PeerStatus::Unreachable(e.to_string()))
+        .and_then(move |stream| {
+            let peer_address = stream
+                .peer_addr()
+                .map_err(|e| PeerStatus::Unreachable(e.to_string()))?;
+            Ok((stream, peer_address))
+        }).and_then(move |(stream, peer_address)| {
+            NoiseHandshake::initiator(&params, &peer_address)
+                .send(stream)
+                .map_err(|e| PeerStatus::HandshakeFailed(e.to_string()))
+        }).and_then(move |(_, raw)| {
+            let connect = parse_connect(raw).map_err(PeerStatus::HandshakeFailed)?;
+            Ok(connect_status(&connect, &expected_key))
+        }).then(|result| match result {
+            Ok(status) | Err(status) => Ok::<_, ()>(status),
+        });
+
+    let deadline = match Timeout::new(timeout, handle) {
+        Ok(deadline) => deadline.then(move |_| {
+            Ok::<_, ()>(PeerStatus::Unreachable(format!(
+                "no response in {} ms",
+                timeout.as_secs() * 1000 + u64::from(timeout.subsec_millis())
+            )))
+        }),
+        Err(e) => return Box::new(future::ok(PeerStatus::Unreachable(e.to_string()))),
+    };
+
+    Box::new(
+        handshake
+            .select(deadline)
+            .map(|(status, _)| status)
+            .map_err(|_| ()),
+    )
+}
+
+fn parse_connect(raw: Vec<u8>) -> Result<Signed<Connect>, String> {
+    match Message::from_raw_buffer(raw).map_err(|e| e.to_string())? {
+        Message::Service(Service::Connect(connect)) => Ok(connect),
+        other => Err(format!(
+            "first message from the peer is not Connect, got={:?}",
+            other
+        )),
+    }
+}
+
+fn connect_status(connect: &Signed<Connect>, expected_key: &PublicKey) -> PeerStatus {
+    let user_agent = connect.user_agent().to_owned();
+    if connect.author() != *expected_key {
+        PeerStatus::KeyMismatch {
+            actual: connect.author(),
+        }
+    } else if exonum_version(&user_agent) != exonum_version(&user_agent::get()) {
+        PeerStatus::VersionMismatch { user_agent }
+    } else {
+        PeerStatus::Reachable { user_agent }
+    }
+}
+
+/// Extracts the Exonum version (e.g., `exonum 0.9.0`) from the user agent string.
+fn exonum_version(user_agent: &str) -> &str {
+    user_agent.split('/').next().unwrap_or("").trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::Stream;
+    use tokio::net::TcpListener;
+
+    use std::net::SocketAddr;
+
+    use super::*;
+    use crypto::{gen_keypair, SecretKey};
+    use events::error::into_failure;
+
+    fn handshake_params(
+        public_key: PublicKey,
+        secret_key: SecretKey,
+        peers: &[ConnectInfo],
+    ) -> HandshakeParams {
+        let address = "127.0.0.1:0".parse().unwrap();
+        let connect = Message::concrete(
+            Connect::new(address, SystemTime::now().into(), &user_agent::get()),
+            public_key,
+            &secret_key,
+        );
+        let mut connect_list = ConnectList::default();
+        for peer in peers {
+            connect_list.add(peer.clone());
+        }
+        HandshakeParams::new(
+            public_key,
+            secret_key,
+            SharedConnectList::from_connect_list(connect_list),
+            connect,
+            1024 * 1024,
+        )
+    }
+
+    /// Spawns a peer accepting a single connection and returns its address.
+    fn spawn_peer(handle: &Handle, params: HandshakeParams) -> SocketAddr {
+        let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let address = listener.local_addr().unwrap();
+        let peer = listener
+            .incoming()
+            .take(1)
+            .map_err(into_failure)
+            .for_each(move |stream| {
+                let address = stream.peer_addr().unwrap();
+                NoiseHandshake::responder(&params, &address)
+                    .listen(stream)
+                    .map(drop)
+            }).map_err(drop);
+        handle.spawn(peer);
+        address
+    }
+
+    #[test]
+    fn check_reachable_peer() {
+        let (our_key, our_secret_key) = gen_keypair();
+        let (peer_key, peer_secret_key) = gen_keypair();
+        let mut core = Core::new().unwrap();
+
+        let us = ConnectInfo {
+            public_key: our_key,
+            address: "127.0.0.1:0".to_owned(),
+        };
+        let address = spawn_peer(
+            &core.handle(),
+            handshake_params(peer_key, peer_secret_key, &[us]),
+        );
+        let peer = ConnectInfo {
+            public_key: peer_key,
+            address: address.to_string(),
+        };
+        let params = handshake_params(our_key, our_secret_key, &[peer.clone()]);
+
+        let reports = check_peers(&mut core, &params, &[peer], Duration::from_secs(5)).unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].public_key, peer_key);
+        assert_eq!(
+            reports[0].status,
+            PeerStatus::Reachable {
+                user_agent: user_agent::get(),
+            }
+        );
+    }
+
+    #[test]
+    fn check_peer_with_another_key() {
+        let (our_key, our_secret_key) = gen_keypair();
+        let (peer_key, peer_secret_key) = gen_keypair();
+        let (expected_key, _) = gen_keypair();
+        let mut core = Core::new().unwrap();
+
+        let us = ConnectInfo {
+            public_key: our_key,
+            address: "127.0.0.1:0".to_owned(),
+        };
+        let address = spawn_peer(
+            &core.handle(),
+            handshake_params(peer_key, peer_secret_key, &[us]),
+        );
+        let peer = ConnectInfo {
+            public_key: expected_key,
+            address: address.to_string(),
+        };
+        let params = handshake_params(our_key, our_secret_key, &[peer.clone()]);
+
+        let reports = check_peers(&mut core, &params, &[peer], Duration::from_secs(5)).unwrap();
+        match reports[0].status {
+            PeerStatus::HandshakeFailed(_) => {}
+            ref status => panic!("Unexpected status: {}", status),
+        }
+        assert!(!reports[0].status.is_ok());
+    }
+
+    #[test]
+    fn check_unreachable_peer() {
+        let (our_key, our_secret_key) = gen_keypair();
+        let (peer_key, _) = gen_keypair();
+        let mut core = Core::new().unwrap();
+
+        // Nobody listens on the address after the listener is dropped.
+        let address = {
+            let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+            listener.local_addr().unwrap()
+        };
+        let peer = ConnectInfo {
+            public_key: peer_key,
+            address: address.to_string(),
+        };
+        let params = handshake_params(our_key, our_secret_key, &[peer.clone()]);
+
+        let reports = check_peers(&mut core, &params, &[peer], Duration::from_secs(5)).unwrap();
+        match reports[0].status {
+            PeerStatus::Unreachable(_) => {}
+            ref status => panic!("Unexpected status: {}", status),
+        }
+    }
+
+    #[test]
+    fn version_mismatch() {
+        let (key, secret_key) = gen_keypair();
+        let address = "127.0.0.1:0".parse().unwrap();
+        let user_agent = "exonum 0.1.0/rustc 1.26.0/linux";
+        let connect = Message::concrete(
+            Connect::new(address, SystemTime::now().into(), user_agent),
+            key,
+            &secret_key,
+        );
+        assert_eq!(
+            connect_status(&connect, &key),
+            PeerStatus::VersionMismatch {
+                user_agent: user_agent.to_owned(),
+            }
+        );
+
+        let (other_key, _) = gen_keypair();
+        assert_eq!(
+            connect_status(&connect, &other_key),
+            PeerStatus::KeyMismatch { actual: key }
+        );
+    }
+}
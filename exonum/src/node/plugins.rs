@@ -0,0 +1,238 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hooks notifying external code about the committed blocks.
+//!
+//! Observers implementing the [`CommitObserver`] trait are called by the node after
+//! each block commit. Besides the observers added in Rust code, the node can load
+//! observers from shared libraries listed in the `plugins` section of the node
+//! configuration. Loading shared libraries requires the `plugins` feature.
+//!
+//! # C ABI
+//!
+//! A plugin library exports the following symbols:
+//!
+//! ```c
+//! typedef struct {
+//!     uint64_t height;
+//!     const uint8_t *block_hash;  /* 32 bytes */
+//!     const uint8_t *state_hash;  /* 32 bytes */
+//!     const uint8_t *tx_hashes;   /* 32 * tx_count bytes */
+//!     uint64_t tx_count;
+//! } exonum_commit_info;
+//!
+//! /* Optional; a non-zero return value aborts the node start. */
+//! int exonum_commit_observer_init(const char *config);
+//!
+//! void exonum_commit_observer_on_commit(const exonum_commit_info *info);
+//! ```
+//!
+//! `config` is the `config` string from the plugin configuration or `NULL` if
+//! it is not specified. The pointers in `exonum_commit_info` are valid only during
+//! the `exonum_commit_observer_on_commit` call. The hook is called from the thread
+//! handling consensus messages, so it should return quickly.
+//!
+//! [`CommitObserver`]: trait.CommitObserver.html
+
+use failure;
+
+use std::{fmt, path::PathBuf};
+
+use crypto::Hash;
+use helpers::Height;
+
+/// Information about the committed block passed to the observers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitEvent {
+    /// Height of the committed block.
+    pub height: Height,
+    /// Hash of the committed block.
+    pub block_hash: Hash,
+    /// Hashes of the transactions in the block in the order of their execution.
+    pub tx_hashes: Vec<Hash>,
+    /// Hash of the blockchain state after the block commit.
+    pub state_hash: Hash,
+}
+
+/// Observer of the block commits.
+pub trait CommitObserver: Send + fmt::Debug {
+    /// Called by the node after the block is committed.
+    fn on_commit(&self, event: &CommitEvent);
+}
+
+/// Configuration of a plugin loaded from a shared library.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PluginConfig {
+    /// Path to the shared library.
+    pub path: PathBuf,
+    /// Configuration string passed to the plugin initialization function.
+    #[serde(default)]
+    pub config: Option<String>,
+}
+
+/// Block commit information in the layout used by the plugin C ABI.
+#[repr(C)]
+#[derive(Debug)]
+pub struct CommitInfo {
+    /// Height of the committed block.
+    pub height: u64,
+    /// Pointer to the 32-byte hash of the committed block.
+    pub block_hash: *const u8,
+    /// Pointer to the 32-byte hash of the blockchain state.
+    pub state_hash: *const u8,
+    /// Pointer to the concatenated 32-byte hashes of the block transactions.
+    pub tx_hashes: *const u8,
+    /// Number of transactions in the block.
+    pub tx_count: u64,
+}
+
+/// Loads the commit observer from the shared library.
+#[cfg(feature = "plugins")]
+pub fn load_plugin(config: &PluginConfig) -> Result<Box<dyn CommitObserver>, failure::Error> {
+    DynamicPlugin::load(config).map(|plugin| Box::new(plugin) as Box<dyn CommitObserver>)
+}
+
+/// Loads the commit observer from the shared library.
+///
+/// Always fails, since the crate is built without the `plugins` feature.
+#[cfg(not(feature = "plugins"))]
+pub fn load_plugin(config: &PluginConfig) -> Result<Box<dyn CommitObserver>, failure::Error> {
+    bail!(
+        "Unable to load plugin {}: Exonum is built without the `plugins` feature",
+        config.path.display()
+    )
+}
+
+#[cfg(feature = "plugins")]
+pub use self::dynamic::DynamicPlugin;
+
+#[cfg(feature = "plugins")]
+mod dynamic {
+    use failure;
+    use libloading::{Library, Symbol};
+
+    use std::{ffi::CString, fmt, os::raw::c_char, os::raw::c_int, path::PathBuf, ptr};
+
+    use super::{CommitEvent, CommitInfo, CommitObserver, PluginConfig};
+
+    type InitFn = unsafe extern "C" fn(*const c_char) -> c_int;
+    type OnCommitFn = unsafe extern "C" fn(*const CommitInfo);
+
+    const INIT_SYMBOL: &[u8] = b"exonum_commit_observer_init\0";
+    const ON_COMMIT_SYMBOL: &[u8] = b"exonum_commit_observer_on_commit\0";
+
+    /// Commit observer implemented in a shared library.
+    pub struct DynamicPlugin {
+        path: PathBuf,
+        on_commit: OnCommitFn,
+        // The library should outlive the function pointer above.
+        _library: Library,
+    }
+
+    impl DynamicPlugin {
+        /// Loads the library and calls its initialization function, if any.
+        pub fn load(config: &PluginConfig) -> Result<Self, failure::Error> {
+            let path = config.path.display();
+            let library = Library::new(&config.path)
+                .map_err(|e| format_err!("Unable to load plugin {}: {}", path, e))?;
+
+            let on_commit = unsafe {
+                let on_commit: Symbol<OnCommitFn> = library
+                    .get(ON_COMMIT_SYMBOL)
+                    .map_err(|e| format_err!("Plugin {} has no commit observer: {}", path, e))?;
+                *on_commit
+            };
+
+            let init = unsafe { library.get::<InitFn>(INIT_SYMBOL).ok().map(|init| *init) };
+            if let Some(init) = init {
+                let plugin_config = match config.config {
+                    Some(ref config) => Some(CString::new(config.as_str()).map_err(|_| {
+                        format_err!("Configuration of plugin {} contains a zero byte", path)
+                    })?),
+                    None => None,
+                };
+                let config_ptr = plugin_config.as_ref().map_or(ptr::null(), |c| c.as_ptr());
+                let code = unsafe { init(config_ptr) };
+                ensure!(
+                    code == 0,
+                    "Plugin {} failed to initialize, code {}",
+                    path,
+                    code
+                );
+            }
+
+            info!("Loaded plugin {}", path);
+            Ok(Self {
+                path: config.path.clone(),
+                on_commit,
+                _library: library,
+            })
+        }
+    }
+
+    impl CommitObserver for DynamicPlugin {
+        fn on_commit(&self, event: &CommitEvent) {
+            let tx_hashes: Vec<u8> = event
+                .tx_hashes
+                .iter()
+                .flat_map(|hash| hash.as_ref().iter().cloned())
+                .collect();
+            let info = CommitInfo {
+                height: event.height.0,
+                block_hash: event.block_hash.as_ref().as_ptr(),
+                state_hash: event.state_hash.as_ref().as_ptr(),
+                tx_hashes: tx_hashes.as_ptr(),
+                tx_count: event.tx_hashes.len() as u64,
+            };
+            unsafe { (self.on_commit)(&info) }
+        }
+    }
+
+    impl fmt::Debug for DynamicPlugin {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.debug_struct("DynamicPlugin")
+                .field("path", &self.path)
+                .finish()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plugin_config_deserialization() {
+        let config: PluginConfig = ::toml::from_str(
+            r#"
+            path = "/usr/lib/libobserver.so"
+            config = "verbose"
+            "#,
+        ).unwrap();
+        assert_eq!(config.path, PathBuf::from("/usr/lib/libobserver.so"));
+        assert_eq!(config.config, Some("verbose".to_owned()));
+
+        let config: PluginConfig = ::toml::from_str(r#"path = "libobserver.so""#).unwrap();
+        assert_eq!(config.config, None);
+    }
+
+    #[test]
+    fn test_load_missing_plugin() {
+        let config = PluginConfig {
+            path: PathBuf::from("/nonexistent/libobserver.so"),
+            config: None,
+        };
+        assert!(load_plugin(&config).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Replica mode of the node.
+//!
+//! A replica does not participate in the consensus and does not keep a memory pool.
+//! It only follows the block stream of the designated upstream nodes, which makes it
+//! suitable for serving heavy explorer and API traffic without affecting the validators:
+//!
+//! ```toml
+//! [replica]
+//! # Consensus public keys of the upstream nodes.
+//! upstreams = [
+//!     "2b10ab5b5ba2ea1d3bb06a1a2a6cd2a48e1c3f19e5f2e0fb1f4c5f8f0a1d1a0c",
+//!     "5e5b2a8f2e9e0e2d8e9b9c5aa2b5f0b3a7d2cd0d2fa8e4d1cda51c0ea7a28c3b",
+//! ]
+//! ```
+//!
+//! The upstreams must be present in the connect list of the replica. Blocks are requested
+//! from any upstream reporting a greater height; if an upstream does not respond in time,
+//! the request is retried with another one. Transactions submitted to the replica API
+//! are forwarded to the connected upstreams, so the upstreams should be validators
+//! or nodes relaying the transactions further.
+//!
+//! The lag of the replica behind its upstreams is reported in the `replica` field
+//! of the healthcheck endpoint of the public system API.
+
+use crypto::PublicKey;
+use helpers::Height;
+use messages::{RawTransaction, Signed};
+use node::NodeHandler;
+
+/// Configuration of the replica mode.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplicaConfig {
+    /// Consensus public keys of the nodes whose block stream is followed by the replica.
+    pub upstreams: Vec<PublicKey>,
+}
+
+/// Synchronization status of the replica.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplicaStatus {
+    /// Current height of the replica.
+    pub height: Height,
+    /// Maximal height reported by the upstreams.
+    pub upstream_height: Height,
+    /// Number of blocks the replica lags behind the upstreams.
+    pub lag: u64,
+    /// Upstream which has provided the latest committed block, if any.
+    pub upstream: Option<PublicKey>,
+}
+
+impl ReplicaStatus {
+    /// Creates the status for the given heights of the replica and its upstreams.
+    pub fn new(height: Height, upstream_height: Height, upstream: Option<PublicKey>) -> Self {
+        Self {
+            height,
+            upstream_height,
+            lag: upstream_height.0.saturating_sub(height.0),
+            upstream,
+        }
+    }
+}
+
+impl NodeHandler {
+    /// Returns `true` if the node runs in the replica mode.
+    pub(crate) fn is_replica(&self) -> bool {
+        self.replica.is_some()
+    }
+
+    /// Returns `true` if the node runs in the replica mode and the given peer
+    /// is one of its upstreams.
+    pub(crate) fn is_upstream(&self, key: &PublicKey) -> bool {
+        self.replica
+            .as_ref()
+            .map_or(false, |config| config.upstreams.contains(key))
+    }
+
+    /// Sends the transaction submitted to the replica to all connected upstreams.
+    pub(crate) fn forward_to_upstreams(&mut self, tx: &Signed<RawTransaction>) {
+        let upstreams: Vec<PublicKey> = match self.replica {
+            Some(ref config) => config
+                .upstreams
+                .iter()
+                .filter(|key| self.state.peers().contains_key(*key))
+                .cloned()
+                .collect(),
+            None => return,
+        };
+        if upstreams.is_empty() {
+            warn!(
+                "Transaction {:?} is not forwarded: no upstreams are connected",
+                tx.hash()
+            );
+        }
+        for upstream in upstreams {
+            self.send_to_peer(upstream, tx.clone());
+        }
+    }
+
+    /// Updates the replica status in the API state. `upstream` is the peer which has
+    /// provided the latest committed block; the previously known one is kept if `None`.
+    pub(crate) fn update_replica_status(&self, upstream: Option<PublicKey>) {
+        let upstream_height = match self.replica {
+            Some(ref config) => config
+                .upstreams
+                .iter()
+                .map(|key| self.state.node_height(key))
+                .max()
+                .unwrap_or_else(Height::zero),
+            None => return,
+        };
+        let upstream = upstream.or_else(|| {
+            self.api_state
+                .replica_status()
+                .and_then(|status| status.upstream)
+        });
+        self.api_state.set_replica_status(ReplicaStatus::new(
+            self.state.height(),
+            upstream_height,
+            upstream,
+        ));
+    }
+}
@@ -15,7 +15,7 @@
 use super::NodeHandler;
 use blockchain::Schema;
 use messages::{
-    BlockRequest, BlockResponse, PrevotesRequest, ProposeRequest, Requests, Signed,
+    BlockRequest, BlockResponse, Ping, Pong, PrevotesRequest, ProposeRequest, Requests, Signed,
     TransactionsRequest, TransactionsResponse, RAW_TRANSACTION_HEADER,
     TRANSACTION_RESPONSE_EMPTY_SIZE,
 };
@@ -46,6 +46,7 @@ impl NodeHandler {
             Requests::PrevotesRequest(ref msg) => self.handle_request_prevotes(msg),
             Requests::PeersRequest(ref msg) => self.handle_request_peers(msg),
             Requests::BlockRequest(ref msg) => self.handle_request_block(msg),
+            Requests::Ping(ref msg) => self.handle_request_ping(msg),
         }
     }
 
@@ -141,6 +142,10 @@ impl NodeHandler {
         let schema = Schema::new(&snapshot);
 
         let height = msg.height();
+        if height < schema.pruned_height() {
+            trace!("Requested block at height {} is pruned", height);
+            return;
+        }
         let block_hash = schema.block_hash_by_height(height).unwrap();
 
         let block = schema.blocks().get(&block_hash).unwrap();
@@ -158,4 +163,10 @@ impl NodeHandler {
         ));
         self.send_to_peer(msg.author(), block_msg);
     }
+
+    /// Handles `Ping` message. For details see the message documentation.
+    pub fn handle_request_ping(&mut self, msg: &Signed<Ping>) {
+        let pong = self.sign_message(Pong::new(&msg.author(), msg.time()));
+        self.send_to_peer(msg.author(), pong);
+    }
 }
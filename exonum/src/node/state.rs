@@ -25,7 +25,7 @@ use std::{
     time::{Duration, SystemTime},
 };
 
-use blockchain::{ConsensusConfig, StoredConfiguration, ValidatorKeys};
+use blockchain::{ConsensusConfig, LeaderElection, StoredConfiguration, TxLocation, ValidatorKeys};
 use crypto::{Hash, PublicKey, SecretKey};
 use events::network::ConnectedPeerAddr;
 use helpers::{Height, Milliseconds, Round, ValidatorId};
@@ -35,7 +35,7 @@ use messages::{
 };
 use node::{
     connect_list::{ConnectList, PeerAddress},
-    ConnectInfo,
+    ConnectInfo, MemoryPoolConfig,
 };
 use storage::{KeySetIndex, MapIndex, Patch, Snapshot};
 
@@ -64,6 +64,9 @@ pub struct State {
     config: StoredConfiguration,
     connect_list: SharedConnectList,
     tx_pool_capacity: usize,
+    tx_pool_bytes_limit: Option<u64>,
+    in_flight_bytes_limit: Option<u64>,
+    memory_usage: MemoryUsage,
 
     peers: HashMap<PublicKey, Signed<Connect>>,
     connections: HashMap<PublicKey, ConnectedPeerAddr>,
@@ -95,6 +98,19 @@ pub struct State {
     validators_rounds: BTreeMap<ValidatorId, Round>,
 
     incomplete_block: Option<IncompleteBlock>,
+
+    // Dependencies of the pool transactions, see `Transaction::depends_on`.
+    tx_dependencies: HashMap<Hash, Hash>,
+}
+
+/// Memory used by the node buffers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MemoryUsage {
+    /// Total size of the transactions in the pool, in bytes.
+    pub tx_pool_bytes: u64,
+    /// Total size of the buffered consensus messages from the future rounds
+    /// and heights, in bytes.
+    pub in_flight_bytes: u64,
 }
 
 /// State of a validator-node.
@@ -441,7 +457,7 @@ impl State {
         consensus_secret_key: SecretKey,
         service_public_key: PublicKey,
         service_secret_key: SecretKey,
-        tx_pool_capacity: usize,
+        mempool: &MemoryPoolConfig,
         connect_list: ConnectList,
         stored: StoredConfiguration,
         connect: Signed<Connect>,
@@ -456,7 +472,10 @@ impl State {
             consensus_secret_key,
             service_public_key,
             service_secret_key,
-            tx_pool_capacity,
+            tx_pool_capacity: mempool.tx_pool_capacity,
+            tx_pool_bytes_limit: mempool.tx_pool_bytes,
+            in_flight_bytes_limit: mempool.in_flight_bytes,
+            memory_usage: MemoryUsage::default(),
             connect_list: SharedConnectList::from_connect_list(connect_list),
             peers,
             connections: HashMap::new(),
@@ -487,6 +506,8 @@ impl State {
             config: stored,
 
             incomplete_block: None,
+
+            tx_dependencies: HashMap::new(),
         }
     }
 
@@ -652,9 +673,15 @@ impl State {
 
     /// Returns the leader id for the specified round and current height.
     pub fn leader(&self, round: Round) -> ValidatorId {
-        let height: u64 = self.height().into();
-        let round: u64 = round.into();
-        ValidatorId(((height + round) % (self.validators().len() as u64)) as u16)
+        self.leader_at(self.height(), round)
+    }
+
+    /// Returns the leader id for the specified height and round according to the current
+    /// configuration.
+    pub fn leader_at(&self, height: Height, round: Round) -> ValidatorId {
+        self.consensus_config()
+            .leader_election
+            .leader(height, round, self.validators().len())
     }
 
     /// Updates known round for a validator and returns
@@ -793,7 +820,7 @@ impl State {
 
     /// Increments node's round by one.
     pub fn new_round(&mut self) {
-        self.round.increment();
+        self.round = self.round.checked_next().expect("Round overflow");
     }
 
     /// Return incomplete block.
@@ -803,7 +830,7 @@ impl State {
 
     /// Increments the node height by one and resets previous height data.
     pub fn new_height(&mut self, block_hash: &Hash, height_start_time: SystemTime) {
-        self.height.increment();
+        self.height = self.height.checked_next().expect("Height overflow");
         self.height_start_time = height_start_time;
         self.round = Round::first();
         self.locked_round = Round::zero();
@@ -827,12 +854,104 @@ impl State {
     pub fn queued(&mut self) -> Vec<ConsensusMessage> {
         let mut queued = Vec::new();
         ::std::mem::swap(&mut self.queued, &mut queued);
+        self.memory_usage.in_flight_bytes = 0;
         queued
     }
 
-    /// Add consensus message to the queue.
-    pub fn add_queued(&mut self, msg: ConsensusMessage) {
+    /// Adds consensus message to the queue. Returns `false` if the message is dropped
+    /// because the limit of the in-flight messages size is reached.
+    pub fn add_queued(&mut self, msg: ConsensusMessage) -> bool {
+        let size = msg.size() as u64;
+        let in_flight_bytes = self.memory_usage.in_flight_bytes + size;
+        if self
+            .in_flight_bytes_limit
+            .map_or(false, |limit| in_flight_bytes > limit)
+        {
+            return false;
+        }
+        self.memory_usage.in_flight_bytes = in_flight_bytes;
         self.queued.push(msg);
+        true
+    }
+
+    /// Returns memory used by the node buffers.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.memory_usage
+    }
+
+    /// Checks that a transaction of the given size can be added to the pool containing
+    /// `pool_len` transactions without exceeding the pool limits.
+    pub fn check_tx_pool_limits(&self, pool_len: u64, tx_size: u64) -> Result<(), failure::Error> {
+        ensure!(
+            pool_len < self.tx_pool_capacity as u64,
+            "Transaction pool is full ({} transactions)",
+            pool_len
+        );
+        if let Some(limit) = self.tx_pool_bytes_limit {
+            ensure!(
+                self.memory_usage.tx_pool_bytes + tx_size <= limit,
+                "Transaction pool size limit is reached ({} bytes)",
+                self.memory_usage.tx_pool_bytes
+            );
+        }
+        Ok(())
+    }
+
+    /// Accounts transactions of the given total size added to the pool.
+    pub fn add_tx_pool_bytes(&mut self, size: u64) {
+        self.memory_usage.tx_pool_bytes += size;
+    }
+
+    /// Accounts transactions of the given total size removed from the pool.
+    pub fn remove_tx_pool_bytes(&mut self, size: u64) {
+        self.memory_usage.tx_pool_bytes = self.memory_usage.tx_pool_bytes.saturating_sub(size);
+    }
+
+    /// Records the dependency of the pool transaction.
+    pub fn add_tx_dependency(&mut self, tx_hash: Hash, dependency: Hash) {
+        self.tx_dependencies.insert(tx_hash, dependency);
+    }
+
+    /// Returns the dependency of the pool transaction, if any.
+    pub fn tx_dependency(&self, tx_hash: &Hash) -> Option<&Hash> {
+        self.tx_dependencies.get(tx_hash)
+    }
+
+    /// Returns the dependencies of the pool transactions.
+    pub fn tx_dependencies(&self) -> &HashMap<Hash, Hash> {
+        &self.tx_dependencies
+    }
+
+    /// Forgets the dependencies of the transactions for which `f` returns `false`.
+    pub fn retain_tx_dependencies<F>(&mut self, f: F)
+    where
+        F: FnMut(&Hash, &mut Hash) -> bool,
+    {
+        self.tx_dependencies.retain(f);
+    }
+
+    /// Returns `true` if the transaction is required by a known propose or
+    /// by the incomplete block.
+    pub fn is_tx_awaited(&self, tx_hash: &Hash) -> bool {
+        self.proposes
+            .values()
+            .any(|propose_state| propose_state.unknown_txs.contains(tx_hash))
+            || self
+                .incomplete_block
+                .as_ref()
+                .map_or(false, |block| block.unknown_txs.contains(tx_hash))
+    }
+
+    /// Returns `true` if the transaction is included into a known propose or
+    /// into the incomplete block.
+    pub fn is_tx_proposed(&self, tx_hash: &Hash) -> bool {
+        self.proposes
+            .values()
+            .any(|propose_state| propose_state.message().transactions().contains(tx_hash))
+            || self
+                .incomplete_block
+                .as_ref()
+                .map_or(false, |block| block.msg.transactions().contains(tx_hash))
     }
 
     /// Checks whether some proposes are waiting for this transaction.
@@ -925,14 +1044,27 @@ impl State {
         msg: Signed<Propose>,
         transactions: &MapIndex<S, Hash, Signed<RawTransaction>>,
         transaction_pool: &KeySetIndex<S, Hash>,
+        transaction_locations: &MapIndex<S, Hash, TxLocation>,
     ) -> Result<&ProposeState, failure::Error> {
         let propose_hash = msg.hash();
         match self.proposes.entry(propose_hash) {
             Entry::Occupied(..) => bail!("Propose already found"),
             Entry::Vacant(e) => {
                 let mut unknown_txs = HashSet::new();
+                let mut propose_txs = HashSet::new();
                 for hash in msg.transactions() {
-                    if transactions.get(hash).is_some() {
+                    if !propose_txs.insert(*hash) {
+                        bail!("Received propose with duplicated transaction {:?}", hash)
+                    }
+                    // The bodies of the transactions from pruned blocks are removed,
+                    // so committed transactions are recognized by their locations.
+                    if transaction_locations.contains(hash) {
+                        bail!(
+                            "Received propose with already \
+                             committed transaction"
+                        )
+                    }
+                    if transactions.contains(hash) {
                         if !transaction_pool.contains(hash) {
                             bail!(
                                 "Received propose with already \
@@ -993,12 +1125,13 @@ impl State {
         msg: &Signed<BlockResponse>,
         txs: &MapIndex<S, Hash, Signed<RawTransaction>>,
         txs_pool: &KeySetIndex<S, Hash>,
+        txs_locations: &MapIndex<S, Hash, TxLocation>,
     ) -> &IncompleteBlock {
         assert!(self.incomplete_block().is_none());
 
         let mut unknown_txs = HashSet::new();
         for hash in msg.transactions() {
-            if txs.get(hash).is_some() {
+            if txs.contains(hash) || txs_locations.contains(hash) {
                 if !txs_pool.contains(hash) {
                     panic!(
                         "Received block with already \
@@ -1143,7 +1276,11 @@ impl State {
 
     /// Returns `true` if the node doesn't have proposes different from the locked one.
     pub fn have_incompatible_prevotes(&self) -> bool {
-        for round in self.locked_round.next().iter_to(self.round.next()) {
+        for round in self
+            .locked_round
+            .saturating_add(1)
+            .iter_to(self.round.saturating_add(1))
+        {
             match self.validator_state {
                 Some(ref validator_state) => {
                     if let Some(msg) = validator_state.our_prevotes.get(&round) {
@@ -0,0 +1,75 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Status file of the node.
+//!
+//! The node can write its status to a JSON file after each block commit, so that liveness
+//! probes and scripts can check the node health without querying the HTTP API:
+//!
+//! ```json
+//! {
+//!   "height": 42,
+//!   "last_commit_time": "2018-10-17T12:00:00.000000Z",
+//!   "role": "validator",
+//!   "validator_id": 0,
+//!   "healthy": true
+//! }
+//! ```
+//!
+//! The file is not updated if the node does not commit blocks, so probes should
+//! check `last_commit_time` along with the `healthy` flag.
+
+use chrono::{DateTime, Utc};
+use serde_json;
+
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+};
+
+use helpers::{Height, ValidatorId};
+
+/// Status of the node written to the status file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeStatus {
+    /// Height of the latest committed block.
+    pub height: Height,
+    /// Time of the latest block commit.
+    pub last_commit_time: DateTime<Utc>,
+    /// Role of the node, either `validator` or `auditor`.
+    pub role: String,
+    /// Identifier of the validator, if the node is a validator.
+    pub validator_id: Option<ValidatorId>,
+    /// `true` if the consensus is enabled on the node and the node is connected to enough
+    /// validators to achieve the consensus.
+    pub healthy: bool,
+}
+
+impl NodeStatus {
+    /// Writes the status to the file at the given path.
+    ///
+    /// The status is written to a temporary file which is then renamed, so that readers
+    /// never see a partially written file.
+    pub fn write_to(&self, path: &Path) -> io::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        {
+            let mut file = File::create(&tmp_path)?;
+            serde_json::to_writer_pretty(&mut file, self)?;
+            file.write_all(b"\n")?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp_path, path)
+    }
+}
@@ -16,14 +16,26 @@
 //! related to consensus protocol handling, such as ability of the node
 //! to add block after receiving correct consensus messages.
 
+use chrono::{DateTime, Duration, Utc};
 use rand::{thread_rng, Rng};
+use serde_json;
+use tempdir::TempDir;
 
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::File,
+    sync::{Arc, Mutex},
+};
 
-use blockchain::{Blockchain, Schema, CORE_SERVICE};
-use crypto::{gen_keypair_from_seed, CryptoHash, Hash, Seed, HASH_SIZE, SEED_LENGTH};
-use helpers::{Height, Round, ValidatorId};
-use messages::{Precommit, Signed};
+use blockchain::{Blockchain, EquivocationEvidence, Schema, CORE_SERVICE};
+use crypto::{gen_keypair_from_seed, hash, CryptoHash, Hash, Seed, HASH_SIZE, SEED_LENGTH};
+use explorer::BlockchainExplorer;
+use helpers::{user_agent, Height, Round, ValidatorId};
+use messages::{Message, Ping, Pong, Precommit, ProtocolMessage, Signed, Status};
+use node::{
+    CommitEvent, CommitObserver, ConsensusArchiveConfig, NodeStatus, PeerClockSkew, PeerLatency,
+    PeerStatus,
+};
 use sandbox::{
     sandbox::{self, timestamping_sandbox},
     sandbox_tests_helper::*,
@@ -279,3 +291,337 @@ fn test_store_txs_positions() {
         assert_eq!(committed_height, location.block_height());
     }
 }
+
+#[derive(Debug, Clone, Default)]
+struct CommitRecorder(Arc<Mutex<Vec<CommitEvent>>>);
+
+impl CommitObserver for CommitRecorder {
+    fn on_commit(&self, event: &CommitEvent) {
+        self.0.lock().unwrap().push(event.clone());
+    }
+}
+
+/// Idea of the test is to verify that commit observers are notified about the committed blocks.
+#[test]
+fn test_commit_observer() {
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+    let recorder = CommitRecorder::default();
+    sandbox
+        .node_handler_mut()
+        .add_commit_observer(Box::new(recorder.clone()));
+
+    let tx = gen_timestamping_tx();
+    add_one_height_with_transactions(&sandbox, &sandbox_state, &[tx.clone()]);
+    sandbox.assert_state(Height(2), Round(1));
+
+    let events = recorder.0.lock().unwrap();
+    let block = sandbox.last_block();
+    assert_eq!(
+        *events,
+        vec![CommitEvent {
+            height: Height(1),
+            block_hash: block.hash(),
+            tx_hashes: vec![tx.hash()],
+            state_hash: *block.state_hash(),
+        }]
+    );
+}
+
+/// Idea of the test is to verify that the status file is updated after each block commit.
+#[test]
+fn test_status_file() {
+    let dir = TempDir::new("exonum_status_file").unwrap();
+    let path = dir.path().join("status.json");
+
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+    sandbox.node_handler_mut().set_status_file(path.clone());
+
+    add_one_height(&sandbox, &sandbox_state);
+    let status: NodeStatus = serde_json::from_reader(File::open(&path).unwrap()).unwrap();
+    assert_eq!(status.height, Height(1));
+    assert_eq!(status.role, "validator");
+    assert_eq!(status.validator_id, Some(ValidatorId(0)));
+
+    add_one_height(&sandbox, &sandbox_state);
+    let status: NodeStatus = serde_json::from_reader(File::open(&path).unwrap()).unwrap();
+    assert_eq!(status.height, Height(2));
+}
+
+/// Idea of the test is to verify that the accepted consensus messages are archived
+/// with the block and the archive keeps only the configured number of the last heights.
+#[test]
+fn test_consensus_archive() {
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+    sandbox
+        .node_handler_mut()
+        .set_consensus_archive(ConsensusArchiveConfig { depth: 1 });
+
+    add_one_height(&sandbox, &sandbox_state);
+    {
+        let snapshot = sandbox.blockchain_ref().snapshot();
+        let schema = Schema::new(&snapshot);
+        let archived = schema.consensus_archive(Height(1));
+        assert!(!archived.is_empty());
+        assert!(archived.iter().all(|msg| match msg {
+            Message::Consensus(ref msg) => msg.height() == Height(1),
+            _ => false,
+        }));
+        // Each message is archived once.
+        let hashes: HashSet<_> = archived.iter().map(|msg| msg.hash()).collect();
+        assert_eq!(hashes.len() as u64, archived.len());
+    }
+
+    add_one_height(&sandbox, &sandbox_state);
+    add_one_height(&sandbox, &sandbox_state);
+    let snapshot = sandbox.blockchain_ref().snapshot();
+    let schema = Schema::new(&snapshot);
+    assert!(schema.consensus_archive(Height(1)).is_empty());
+    assert!(!schema.consensus_archive(Height(3)).is_empty());
+    let heights: Vec<_> = schema.consensus_archive_heights().iter().collect();
+    assert_eq!(heights, vec![3]);
+}
+
+/// Idea of the test is to verify that the transaction bodies and the precommits
+/// of the old blocks are pruned, while the block headers and the transaction
+/// locations are kept.
+#[test]
+fn test_block_pruning() {
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+    sandbox.node_handler_mut().set_pruning_depth(2);
+
+    let tx = gen_timestamping_tx();
+    add_one_height_with_transactions(&sandbox, &sandbox_state, &[tx.clone()]);
+    add_one_height(&sandbox, &sandbox_state);
+    add_one_height(&sandbox, &sandbox_state);
+
+    let blockchain = sandbox.blockchain_ref();
+    let explorer = BlockchainExplorer::new(&blockchain);
+    assert_eq!(explorer.pruned_height(), Height(2));
+    assert!(explorer.is_transaction_pruned(&tx.hash()));
+    assert!(explorer.transaction(&tx.hash()).is_none());
+    assert!(explorer.block_with_txs(Height(1)).is_none());
+    assert!(explorer.block_with_txs(Height(2)).is_some());
+
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    let block_hash = schema.block_hash_by_height(Height(1)).unwrap();
+    assert!(schema.blocks().contains(&block_hash));
+    assert!(schema.precommits(&block_hash).is_empty());
+    assert_eq!(schema.block_transactions(Height(1)).len(), 1);
+    assert!(schema.transactions_locations().contains(&tx.hash()));
+}
+
+/// Idea of the test is to verify that the node responds to `Ping` with `Pong`
+/// and records the round-trip time to the peer after receiving `Pong`.
+#[test]
+fn test_ping_pong() {
+    let sandbox = timestamping_sandbox();
+    let time: DateTime<Utc> = sandbox.time().into();
+
+    sandbox.recv(&Message::concrete(
+        Ping::new(&sandbox.p(ValidatorId(0)), time),
+        sandbox.p(ValidatorId(1)),
+        sandbox.s(ValidatorId(1)),
+    ));
+    sandbox.send(
+        sandbox.p(ValidatorId(1)),
+        &Message::concrete(
+            Pong::new(&sandbox.p(ValidatorId(1)), time),
+            sandbox.p(ValidatorId(0)),
+            sandbox.s(ValidatorId(0)),
+        ),
+    );
+
+    sandbox.recv(&Message::concrete(
+        Pong::new(
+            &sandbox.p(ValidatorId(0)),
+            time - Duration::milliseconds(10),
+        ),
+        sandbox.p(ValidatorId(2)),
+        sandbox.s(ValidatorId(2)),
+    ));
+    let latencies = sandbox.node_handler_mut().api_state().peer_latencies();
+    assert_eq!(
+        latencies,
+        vec![PeerLatency::new(
+            sandbox.p(ValidatorId(2)),
+            Some(ValidatorId(2)),
+            10_000
+        )]
+    );
+}
+
+/// Idea of the test is to verify that the node keeps working in the network where some
+/// validators run the previous protocol version and ignore `Ping` messages: only the peers
+/// responding with `Pong` are recorded, and blocks are committed as usual.
+#[test]
+fn test_ping_with_old_validators() {
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+    let time: DateTime<Utc> = sandbox.time().into();
+
+    sandbox.node_handler_mut().handle_ping_timeout();
+    sandbox.process_events();
+    let mut pinged = HashSet::new();
+    while let Some((peer, message)) = sandbox.pop_sent() {
+        let ping = Ping::try_from(message).expect("Ping was expected");
+        assert_eq!(ping.to(), &peer);
+        assert_eq!(ping.time(), time);
+        pinged.insert(peer);
+    }
+    let peers: HashSet<_> = (1..sandbox.n_validators())
+        .map(|i| sandbox.p(ValidatorId(i as u16)))
+        .collect();
+    assert_eq!(pinged, peers);
+
+    // Validators 1 and 2 run the previous protocol version and cannot decode `Ping`,
+    // so only validator 3 responds.
+    sandbox.add_time(Duration::milliseconds(10).to_std().unwrap());
+    sandbox.recv(&Message::concrete(
+        Pong::new(&sandbox.p(ValidatorId(0)), time),
+        sandbox.p(ValidatorId(3)),
+        sandbox.s(ValidatorId(3)),
+    ));
+    let latencies = sandbox.node_handler_mut().api_state().peer_latencies();
+    assert_eq!(
+        latencies,
+        vec![PeerLatency::new(
+            sandbox.p(ValidatorId(3)),
+            Some(ValidatorId(3)),
+            10_000
+        )]
+    );
+
+    add_one_height(&sandbox, &sandbox_state);
+    sandbox.assert_state(Height(2), Round(1));
+}
+
+/// Idea of the test is to verify that the node estimates the clock skews of the validators
+/// from the times of their precommits for the current height.
+#[test]
+fn test_clock_skew() {
+    let sandbox = timestamping_sandbox();
+    let time: DateTime<Utc> = sandbox.time().into();
+
+    sandbox.recv(&sandbox.create_precommit(
+        ValidatorId(1),
+        Height(1),
+        Round(1),
+        &empty_hash(),
+        &empty_hash(),
+        time + Duration::milliseconds(1500),
+        sandbox.s(ValidatorId(1)),
+    ));
+    // Precommits for other heights are not taken into account.
+    sandbox.recv(&sandbox.create_precommit(
+        ValidatorId(2),
+        Height(2),
+        Round(1),
+        &empty_hash(),
+        &empty_hash(),
+        time - Duration::milliseconds(5000),
+        sandbox.s(ValidatorId(2)),
+    ));
+
+    let skews = sandbox.node_handler_mut().api_state().peer_clock_skews();
+    assert_eq!(
+        skews,
+        vec![PeerClockSkew::new(
+            sandbox.p(ValidatorId(1)),
+            Some(ValidatorId(1)),
+            1500
+        )]
+    );
+}
+
+/// Idea of the test is to verify that the node records the heights reported by the peers
+/// and forgets them once the peers disconnect.
+#[test]
+fn test_peer_statuses() {
+    let sandbox = timestamping_sandbox();
+
+    sandbox.recv(&Message::concrete(
+        Status::new(Height(1), &sandbox.last_hash()),
+        sandbox.p(ValidatorId(1)),
+        sandbox.s(ValidatorId(1)),
+    ));
+    let statuses = sandbox.node_handler_mut().api_state().peer_statuses();
+    assert_eq!(
+        statuses,
+        vec![PeerStatus {
+            public_key: sandbox.p(ValidatorId(1)),
+            validator_id: Some(ValidatorId(1)),
+            height: Height(1),
+            time: sandbox.time().into(),
+        }]
+    );
+
+    sandbox
+        .node_handler_mut()
+        .handle_disconnected(sandbox.p(ValidatorId(1)));
+    sandbox.send(sandbox.p(ValidatorId(1)), sandbox.connect().unwrap());
+    let statuses = sandbox.node_handler_mut().api_state().peer_statuses();
+    assert!(statuses.is_empty());
+}
+
+/// Idea of the test is to verify that the node drops and refuses the connections with
+/// the validator once the evidence of its equivocation is committed.
+#[test]
+fn test_byzantine_peer_blocked() {
+    let sandbox = sandbox::timestamping_sandbox_builder()
+        .with_consensus(|config| config.block_byzantine_peers = true)
+        .build();
+    let byzantine = ValidatorId(3);
+    let precommit = |block_hash: &Hash| {
+        sandbox.create_precommit(
+            byzantine,
+            Height(1),
+            Round(1),
+            &Hash::zero(),
+            block_hash,
+            sandbox.time().into(),
+            sandbox.s(byzantine),
+        )
+    };
+    let block_hash = hash(&[1]);
+
+    {
+        let mut fork = sandbox.blockchain_mut().fork();
+        {
+            let mut schema = Schema::new(&mut fork);
+            let duplicate =
+                EquivocationEvidence::new(precommit(&block_hash), precommit(&block_hash));
+            assert!(schema.commit_equivocation_evidence(&duplicate).is_err());
+            let evidence =
+                EquivocationEvidence::new(precommit(&block_hash), precommit(&hash(&[2])));
+            schema.commit_equivocation_evidence(&evidence).unwrap();
+        }
+        sandbox.blockchain_mut().merge(fork.into_patch()).unwrap();
+    }
+    sandbox.node_handler_mut().update_blocked_peers();
+
+    let key = sandbox.p(byzantine);
+    let is_connected = || {
+        sandbox
+            .node_handler_mut()
+            .state()
+            .peers()
+            .contains_key(&key)
+    };
+    assert!(sandbox.node_handler_mut().is_peer_blocked(&key));
+    assert!(!is_connected());
+
+    // The node does not respond to the `Connect` message of the blocked validator.
+    sandbox.recv(&sandbox.create_connect(
+        &key,
+        sandbox.a(byzantine),
+        sandbox.time().into(),
+        &user_agent::get(),
+        sandbox.s(byzantine),
+    ));
+    assert!(!is_connected());
+}
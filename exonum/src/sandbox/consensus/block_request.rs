@@ -18,7 +18,10 @@ use std::time::Duration;
 
 use crypto::CryptoHash;
 use helpers::{Height, Round, ValidatorId};
-use node::state::{BLOCK_REQUEST_TIMEOUT, TRANSACTIONS_REQUEST_TIMEOUT};
+use node::{
+    state::{BLOCK_REQUEST_TIMEOUT, TRANSACTIONS_REQUEST_TIMEOUT},
+    ReplicaConfig, ReplicaStatus,
+};
 use sandbox::{sandbox::timestamping_sandbox, sandbox_tests_helper::*};
 
 /// HANDLE block response
@@ -943,3 +946,131 @@ fn transactions_request_to_multiple_nodes() {
         sandbox.s(ValidatorId(0)),
     ));
 }
+
+/// HANDLE block response in the replica mode
+
+/// - replica should follow the block stream of its upstreams only
+/// idea of test is:
+/// - getting Status from non-upstream node with later height, ignore it
+/// - receive tx A from the network, ignore it as the replica has no memory pool
+/// - getting Status from upstream node with later height, send BlockRequest to it
+/// - receive BlockResponse with unknown tx A, request and receive tx A
+/// - Block should be executed and committed, lag should be reported
+#[test]
+fn replica_follows_upstreams() {
+    let sandbox = timestamping_sandbox();
+    sandbox.node_handler_mut().set_replica(ReplicaConfig {
+        upstreams: vec![sandbox.p(ValidatorId(3))],
+    });
+
+    let tx = gen_timestamping_tx();
+
+    let propose = ProposeBuilder::new(&sandbox).build();
+
+    let block = BlockBuilder::new(&sandbox)
+        .with_tx_hash(&tx.hash())
+        .with_state_hash(&sandbox.compute_state_hash(&[tx.clone()]))
+        .build();
+
+    let precommit_1 = sandbox.create_precommit(
+        ValidatorId(1),
+        Height(1),
+        Round(1),
+        &propose.hash(),
+        &block.hash(),
+        sandbox.time().into(),
+        sandbox.s(ValidatorId(1)),
+    );
+    let precommit_2 = sandbox.create_precommit(
+        ValidatorId(2),
+        Height(1),
+        Round(1),
+        &propose.hash(),
+        &block.hash(),
+        sandbox.time().into(),
+        sandbox.s(ValidatorId(2)),
+    );
+    let precommit_3 = sandbox.create_precommit(
+        ValidatorId(3),
+        Height(1),
+        Round(1),
+        &propose.hash(),
+        &block.hash(),
+        sandbox.time().into(),
+        sandbox.s(ValidatorId(3)),
+    );
+
+    sandbox.recv(&sandbox.create_status(
+        &sandbox.p(ValidatorId(2)),
+        Height(2),
+        &block.hash(),
+        sandbox.s(ValidatorId(2)),
+    ));
+    sandbox.add_time(Duration::from_millis(BLOCK_REQUEST_TIMEOUT));
+    sandbox.recv(&tx);
+
+    sandbox.recv(&sandbox.create_status(
+        &sandbox.p(ValidatorId(3)),
+        Height(2),
+        &block.hash(),
+        sandbox.s(ValidatorId(3)),
+    ));
+    assert_eq!(
+        sandbox.node_handler_mut().api_state().replica_status(),
+        Some(ReplicaStatus::new(Height(1), Height(2), None))
+    );
+
+    sandbox.add_time(Duration::from_millis(BLOCK_REQUEST_TIMEOUT));
+    sandbox.send(
+        sandbox.p(ValidatorId(3)),
+        &sandbox.create_block_request(
+            &sandbox.p(ValidatorId(0)),
+            &sandbox.p(ValidatorId(3)),
+            Height(1),
+            sandbox.s(ValidatorId(0)),
+        ),
+    );
+
+    sandbox.recv(&sandbox.create_block_response(
+        &sandbox.p(ValidatorId(3)),
+        &sandbox.p(ValidatorId(0)),
+        block.clone(),
+        vec![precommit_1, precommit_2, precommit_3],
+        &[tx.hash()],
+        sandbox.s(ValidatorId(3)),
+    ));
+
+    sandbox.add_time(Duration::from_millis(TRANSACTIONS_REQUEST_TIMEOUT));
+    sandbox.send(
+        sandbox.p(ValidatorId(3)),
+        &sandbox.create_transactions_request(
+            &sandbox.p(ValidatorId(0)),
+            &sandbox.p(ValidatorId(3)),
+            &[tx.hash()],
+            sandbox.s(ValidatorId(0)),
+        ),
+    );
+
+    sandbox.recv(&sandbox.create_transactions_response(
+        &sandbox.p(ValidatorId(3)),
+        &sandbox.p(ValidatorId(0)),
+        vec![tx.clone()],
+        sandbox.s(ValidatorId(3)),
+    ));
+
+    sandbox.assert_state(Height(2), Round(1));
+    sandbox.broadcast(&sandbox.create_status(
+        &sandbox.p(ValidatorId(0)),
+        Height(2),
+        &block.hash(),
+        sandbox.s(ValidatorId(0)),
+    ));
+    assert_eq!(
+        sandbox.node_handler_mut().api_state().replica_status(),
+        Some(ReplicaStatus::new(
+            Height(2),
+            Height(2),
+            Some(sandbox.p(ValidatorId(3)))
+        ))
+    );
+}
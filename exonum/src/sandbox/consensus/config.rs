@@ -14,10 +14,11 @@
 
 //! Tests in this module are designed to test configuration change protocol.
 
-use blockchain::Schema;
-use crypto::CryptoHash;
-use helpers::{Height, ValidatorId};
+use blockchain::{Blockchain, Schema, CORE_SERVICE};
+use crypto::{CryptoHash, Hash};
+use helpers::{Height, Round, ValidatorId};
 use sandbox::{config_updater::TxConfig, sandbox::timestamping_sandbox, sandbox_tests_helper::*};
+use storage::{Database, MemoryDB, ProofMapIndex};
 
 /// - exclude validator from consensus
 /// - idea of test is to exclude sandbox validator from consensus
@@ -122,3 +123,168 @@ fn test_schema_config_changes() {
         following_cfg
     );
 }
+
+/// - switch to the weighted leader election and then change the validator set
+/// - idea of the test is to check that leaders follow the election strategy
+///   from the actual configuration
+#[test]
+fn test_weighted_leader_election() {
+    use blockchain::LeaderElectionConfig;
+    use storage::StorageValue;
+
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+
+    add_one_height(&sandbox, &sandbox_state);
+
+    // Validators propose blocks in turns by default.
+    let height: u64 = sandbox.current_height().into();
+    for round in 1..10 {
+        let expected = ValidatorId(((height + u64::from(round)) % 4) as u16);
+        assert_eq!(sandbox.leader(Round(round)), expected);
+    }
+
+    // The weight of the sandbox node outweighs the other validators.
+    let tx_cfg = {
+        let mut consensus_cfg = sandbox.cfg();
+        consensus_cfg.consensus.leader_election = LeaderElectionConfig::Weighted {
+            weights: vec![u64::max_value() - 3, 1, 1, 1],
+        };
+        consensus_cfg.actual_from = sandbox.current_height().next().next();
+        consensus_cfg.previous_cfg_hash = sandbox.cfg().hash();
+
+        TxConfig::create_signed(
+            &sandbox.p(ValidatorId(0)),
+            &consensus_cfg.clone().into_bytes(),
+            consensus_cfg.actual_from,
+            sandbox.s(ValidatorId(0)),
+        )
+    };
+    add_one_height_with_transactions(&sandbox, &sandbox_state, &[tx_cfg.clone()]);
+    add_one_height(&sandbox, &sandbox_state);
+
+    for round in 1..10 {
+        assert_eq!(sandbox.leader(Round(round)), ValidatorId(0));
+    }
+    // The sandbox node is the leader in every round, so it keeps producing blocks.
+    add_one_height(&sandbox, &sandbox_state);
+    sandbox.assert_state(Height(5), Round(1));
+
+    // Weights are changed together with the validator set.
+    let tx_cfg = {
+        let mut consensus_cfg = sandbox.cfg();
+        consensus_cfg.validator_keys.swap_remove(3);
+        consensus_cfg.consensus.leader_election = LeaderElectionConfig::Weighted {
+            weights: vec![1, u64::max_value() - 2, 1],
+        };
+        consensus_cfg.actual_from = sandbox.current_height().next().next();
+        consensus_cfg.previous_cfg_hash = sandbox.cfg().hash();
+
+        TxConfig::create_signed(
+            &sandbox.p(ValidatorId(0)),
+            &consensus_cfg.clone().into_bytes(),
+            consensus_cfg.actual_from,
+            sandbox.s(ValidatorId(0)),
+        )
+    };
+    add_one_height_with_transactions(&sandbox, &sandbox_state, &[tx_cfg.clone()]);
+    add_one_height(&sandbox, &sandbox_state);
+
+    assert_eq!(sandbox.cfg().validator_keys.len(), 3);
+    for round in 1..10 {
+        assert_eq!(sandbox.leader(Round(round)), ValidatorId(1));
+    }
+}
+
+/// Computes the state hash of the latest block in the same way as the versions
+/// aggregating only the first two core tables.
+fn legacy_state_hash(sandbox: &TimestampingSandbox) -> Hash {
+    let blockchain = sandbox.blockchain_ref();
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+
+    let db = MemoryDB::new();
+    let mut fork = db.fork();
+    let mut aggregator = ProofMapIndex::new("aggregator", &mut fork);
+    aggregator.put(
+        &Blockchain::service_table_unique_key(CORE_SERVICE, 0),
+        schema.configs().merkle_root(),
+    );
+    aggregator.put(
+        &Blockchain::service_table_unique_key(CORE_SERVICE, 1),
+        schema.transaction_results().merkle_root(),
+    );
+    for service in blockchain.service_map().values() {
+        for (idx, root) in service.state_hash(&*snapshot).into_iter().enumerate() {
+            aggregator.put(
+                &Blockchain::service_table_unique_key(service.service_id(), idx),
+                root,
+            );
+        }
+    }
+    aggregator.merkle_root()
+}
+
+/// - activate the aggregation of the configuration history into the state hash
+/// - idea of the test is to check that the validators running the earlier versions
+///   compute the same state hash until the activation height
+#[test]
+fn test_configs_history_activation() {
+    use storage::StorageValue;
+
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+
+    add_one_height(&sandbox, &sandbox_state);
+    assert_eq!(
+        *sandbox.last_block().state_hash(),
+        legacy_state_hash(&sandbox)
+    );
+
+    let actual_from = sandbox.current_height().next().next();
+    let activation = actual_from.next();
+    let tx_cfg = {
+        let mut consensus_cfg = sandbox.cfg();
+        consensus_cfg.consensus.configs_history_from = Some(activation);
+        consensus_cfg.actual_from = actual_from;
+        consensus_cfg.previous_cfg_hash = sandbox.cfg().hash();
+
+        TxConfig::create_signed(
+            &sandbox.p(ValidatorId(0)),
+            &consensus_cfg.clone().into_bytes(),
+            consensus_cfg.actual_from,
+            sandbox.s(ValidatorId(0)),
+        )
+    };
+    add_one_height_with_transactions(&sandbox, &sandbox_state, &[tx_cfg.clone()]);
+
+    // Old and upgraded validators agree on the blocks before the activation height,
+    // including the ones with the new configuration actual.
+    while sandbox.current_height() < activation {
+        add_one_height(&sandbox, &sandbox_state);
+        assert_eq!(
+            *sandbox.last_block().state_hash(),
+            legacy_state_hash(&sandbox)
+        );
+        let snapshot = sandbox.blockchain_ref().snapshot();
+        assert!(!Schema::new(&snapshot).is_configs_history_aggregated());
+    }
+    assert_eq!(
+        sandbox.cfg().consensus.configs_history_from,
+        Some(activation)
+    );
+
+    // Starting from the activation height, the history root is aggregated.
+    for _ in 0..2 {
+        add_one_height(&sandbox, &sandbox_state);
+        let snapshot = sandbox.blockchain_ref().snapshot();
+        let schema = Schema::new(&snapshot);
+        assert!(schema.is_configs_history_aggregated());
+        assert_eq!(schema.core_state_hash().len(), 3);
+        assert_ne!(
+            *sandbox.last_block().state_hash(),
+            legacy_state_hash(&sandbox)
+        );
+    }
+    sandbox.assert_state(activation.next().next(), Round(1));
+}
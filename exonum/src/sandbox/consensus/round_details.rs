@@ -26,8 +26,9 @@ use std::time::Duration;
 use crypto::CryptoHash;
 use helpers::{Height, Round, ValidatorId};
 use messages::{PrevotesRequest, ProtocolMessage, TransactionsRequest};
-use node::state::{
-    PREVOTES_REQUEST_TIMEOUT, PROPOSE_REQUEST_TIMEOUT, TRANSACTIONS_REQUEST_TIMEOUT,
+use node::{
+    state::{PREVOTES_REQUEST_TIMEOUT, PROPOSE_REQUEST_TIMEOUT, TRANSACTIONS_REQUEST_TIMEOUT},
+    ForensicsConfig,
 };
 use sandbox::{
     sandbox::{self, timestamping_sandbox},
@@ -625,6 +626,87 @@ fn handle_precommit_different_block_hash() {
     sandbox.recv(&precommit_3);
 }
 
+/// The same scenario as in `handle_precommit_different_block_hash`, but in the forensic mode
+/// the node captures the divergence report instead of panicking.
+#[test]
+fn handle_precommit_different_block_hash_forensics() {
+    let sandbox = timestamping_sandbox();
+    sandbox
+        .node_handler_mut()
+        .set_forensics(ForensicsConfig { depth: 10 });
+
+    let tx = gen_timestamping_tx();
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[tx.hash()])
+        .build();
+    let block = BlockBuilder::new(&sandbox).build();
+
+    let precommits = (1..4)
+        .map(|i| {
+            sandbox.create_precommit(
+                ValidatorId(i),
+                Height(1),
+                Round(1),
+                &propose.hash(),
+                &block.hash(),
+                sandbox.time().into(),
+                sandbox.s(ValidatorId(i)),
+            )
+        }).collect::<Vec<_>>();
+
+    sandbox.recv(&precommits[0]);
+    sandbox.add_time(Duration::from_millis(PROPOSE_REQUEST_TIMEOUT));
+    sandbox.send(
+        sandbox.p(ValidatorId(1)),
+        &make_request_propose_from_precommit(&sandbox, &precommits[0]),
+    );
+    sandbox.send(
+        sandbox.p(ValidatorId(1)),
+        &make_request_prevote_from_precommit(&sandbox, &precommits[0]),
+    );
+    sandbox.recv(&propose);
+    sandbox.recv(&tx);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+    sandbox.recv(&precommits[1]);
+    sandbox.recv(&precommits[2]);
+
+    // The block is not committed.
+    sandbox.assert_state(Height(1), Round(1));
+    let report = sandbox
+        .node_handler_mut()
+        .api_state()
+        .divergence_report()
+        .expect("Divergence report is not captured");
+    assert_eq!(report.height, Height(1));
+    assert_eq!(report.expected_block_hash, block.hash());
+    assert_ne!(report.block.hash(), block.hash());
+    assert_eq!(report.transactions.len(), 1);
+    assert_eq!(report.transactions[0].tx_hash, tx.hash());
+    assert!(report.transactions[0].status.0.is_ok());
+    assert!(!report.table_roots.is_empty());
+    assert!(report.peer_block.is_none());
+}
+
+/// In the forensic mode, the node keeps the table root hashes for the configured number
+/// of the latest heights.
+#[test]
+fn table_roots_are_kept_in_forensics_mode() {
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+    sandbox
+        .node_handler_mut()
+        .set_forensics(ForensicsConfig { depth: 1 });
+
+    add_one_height(&sandbox, &sandbox_state);
+    add_one_height(&sandbox, &sandbox_state);
+
+    let api_state = sandbox.node_handler_mut().api_state().clone();
+    let roots = api_state.table_roots(None).unwrap();
+    assert_eq!(roots.height, Height(2));
+    assert_eq!(api_state.table_roots(Some(Height(2))), Some(roots));
+    assert_eq!(api_state.table_roots(Some(Height(1))), None);
+}
+
 /// scenario: // HANDLE PRECOMMIT positive scenario with commit
 #[test]
 fn handle_precommit_positive_scenario_commit() {
@@ -18,10 +18,35 @@ use std::time::Duration;
 
 use crypto::CryptoHash;
 use helpers::{Height, Round, ValidatorId};
+#[cfg(debug_assertions)]
+use node::chaos::ChaosConfig;
 use node::state::PROPOSE_REQUEST_TIMEOUT;
 
 use sandbox::{sandbox::timestamping_sandbox, sandbox_tests_helper::*};
 
+/// idea of the scenario is to pause the proposer with the chaos testing hooks
+/// then:
+///  - propose timeout is triggered when we are leader
+///  - propose is not sent
+#[cfg(debug_assertions)]
+#[test]
+fn test_chaos_pause_proposer() {
+    let sandbox = timestamping_sandbox();
+    sandbox.recv_chaos(ChaosConfig {
+        pause_proposer: true,
+        ..ChaosConfig::default()
+    });
+
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    sandbox.add_time(Duration::from_millis(
+        sandbox.current_round_timeout() + PROPOSE_TIMEOUT,
+    ));
+
+    sandbox.assert_state(Height(1), Round(3));
+    assert!(sandbox.is_leader());
+    sandbox.add_time(Duration::from_millis(0));
+}
+
 /// HANDLE ROUND TIMEOUT:
 /// - Ignore if height and round are not the same
 /// scenario:
@@ -18,15 +18,16 @@ use bit_vec::BitVec;
 
 use std::time::Duration;
 
+use blockchain::{PrioritySendersConfig, Schema};
 use crypto::{gen_keypair, CryptoHash, Hash};
 use helpers::{Height, Milliseconds, Round, ValidatorId};
-use messages::{RawTransaction, Signed};
+use messages::{Message, RawTransaction, Signed};
 use node::state::TRANSACTIONS_REQUEST_TIMEOUT;
 use sandbox::{
     config_updater::TxConfig,
     sandbox::{timestamping_sandbox, timestamping_sandbox_builder, Sandbox},
     sandbox_tests_helper::*,
-    timestamping::{TimestampingTxGenerator, DATA_SIZE},
+    timestamping::{TimestampTx, TimestampingTxGenerator, DATA_SIZE, TIMESTAMPING_SERVICE},
 };
 
 const MAX_PROPOSE_TIMEOUT: Milliseconds = 200;
@@ -726,3 +727,390 @@ fn expedited_propose_on_transaction_pressure() {
     sandbox.broadcast(&propose);
     sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
 }
+
+/// Idea of the test is to verify that the transactions of the priority senders are
+/// included into the proposal even if the pool has more transactions than fit into a block.
+#[test]
+fn propose_reserves_txs_for_priority_senders() {
+    let priority_keypair = gen_keypair();
+    let priority_key = priority_keypair.0;
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| {
+            config.max_propose_timeout = MAX_PROPOSE_TIMEOUT;
+            config.min_propose_timeout = MIN_PROPOSE_TIMEOUT;
+            config.propose_timeout_threshold = PROPOSE_THRESHOLD;
+            config.txs_block_limit = 2;
+            config.priority_senders = PrioritySendersConfig {
+                keys: vec![priority_key],
+                reserved_percent: 50,
+            };
+        }).build();
+
+    // Wait for us to become the leader.
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+
+    let transactions =
+        send_txs_into_pool(&sandbox, TimestampingTxGenerator::new(64).take(3).collect());
+    let priority_tx = TimestampingTxGenerator::with_keypair(64, priority_keypair)
+        .next()
+        .unwrap();
+    sandbox.recv(&priority_tx);
+
+    sandbox.add_time(Duration::from_millis(MIN_PROPOSE_TIMEOUT));
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[priority_tx.hash(), transactions[0].hash()])
+        .build();
+
+    sandbox.broadcast(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+}
+
+/// Idea of the test is to verify that with `propose_ahead` enabled the node selects
+/// the transactions for its next proposal while locked on the current one and sends
+/// the proposal right after the commit without waiting for the propose timeout.
+#[test]
+fn propose_ahead_after_commit() {
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+    sandbox.node_handler_mut().set_propose_ahead(true);
+
+    // The node is the leader of the first round at the next height.
+    add_one_height(&sandbox, &sandbox_state);
+    sandbox.assert_state(Height(2), Round(1));
+
+    let transactions = TimestampingTxGenerator::new(64).take(2).collect::<Vec<_>>();
+    for tx in &transactions {
+        sandbox.recv(tx);
+    }
+    let (tx_current, tx_next) = (&transactions[0], &transactions[1]);
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_validator(ValidatorId(3))
+        .with_tx_hashes(&[tx_current.hash()])
+        .build();
+    let block = BlockBuilder::new(&sandbox)
+        .with_proposer_id(ValidatorId(3))
+        .with_tx_hash(&tx_current.hash())
+        .with_state_hash(&sandbox.compute_state_hash(&[tx_current.clone()]))
+        .build();
+
+    sandbox.recv(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+    for i in 1..3 {
+        sandbox.recv(&sandbox.create_prevote(
+            ValidatorId(i),
+            Height(2),
+            Round(1),
+            &propose.hash(),
+            NOT_LOCKED,
+            sandbox.s(ValidatorId(i)),
+        ));
+    }
+    sandbox.assert_lock(Round(1), Some(propose.hash()));
+    sandbox.broadcast(&sandbox.create_precommit(
+        ValidatorId(0),
+        Height(2),
+        Round(1),
+        &propose.hash(),
+        &block.hash(),
+        sandbox.time().into(),
+        sandbox.s(ValidatorId(0)),
+    ));
+
+    for i in 1..3 {
+        sandbox.recv(&sandbox.create_precommit(
+            ValidatorId(i),
+            Height(2),
+            Round(1),
+            &propose.hash(),
+            &block.hash(),
+            sandbox.time().into(),
+            sandbox.s(ValidatorId(i)),
+        ));
+    }
+    sandbox.assert_state(Height(3), Round(1));
+    sandbox.check_broadcast_status(Height(3), &block.hash());
+
+    // The prepared proposal is sent immediately.
+    let next_propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[tx_next.hash()])
+        .build();
+    sandbox.broadcast(&next_propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &next_propose));
+}
+
+/// Returns a transaction failing `Transaction::verify`.
+fn gen_invalid_timestamping_tx() -> Signed<RawTransaction> {
+    let (public_key, secret_key) = gen_keypair();
+    Message::sign_transaction(
+        TimestampTx::new(&[]),
+        TIMESTAMPING_SERVICE,
+        public_key,
+        &secret_key,
+    )
+}
+
+/// idea of the test is to verify that with propose pre-check enabled the node does not accept
+/// transactions failing verification
+#[test]
+fn precheck_rejects_invalid_tx() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_propose_precheck()
+        .build();
+
+    let tx = gen_invalid_timestamping_tx();
+    sandbox.recv(&tx);
+
+    let snapshot = sandbox.blockchain_ref().snapshot();
+    assert!(!Schema::new(&snapshot).transactions().contains(&tx.hash()));
+}
+
+/// idea of the test is to verify that with propose pre-check enabled the node does not vote
+/// for a propose containing a transaction failing verification
+#[test]
+fn precheck_propose_with_invalid_tx() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_propose_precheck()
+        .build();
+
+    // The transaction could be saved to the pool before the pre-check was enabled.
+    let tx = gen_invalid_timestamping_tx();
+    {
+        let mut fork = sandbox.blockchain_ref().fork();
+        Schema::new(&mut fork).add_transaction_into_pool(tx.clone());
+        sandbox.blockchain_mut().merge(fork.into_patch()).unwrap();
+    }
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[tx.hash()])
+        .build();
+    sandbox.recv(&propose);
+    // No prevote is broadcast.
+}
+
+/// idea of the test is to verify that without propose pre-check the node votes
+/// for a propose containing a transaction failing verification
+#[test]
+fn propose_with_invalid_tx_without_precheck() {
+    let sandbox = timestamping_sandbox();
+
+    let tx = gen_invalid_timestamping_tx();
+    sandbox.recv(&tx);
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[tx.hash()])
+        .build();
+    sandbox.recv(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+}
+
+/// idea of the test is to verify that the node rejects transactions once the pool limits
+/// are reached
+#[test]
+fn tx_pool_limits() {
+    let tx = gen_timestamping_tx();
+    let tx_size = tx.signed_message().raw().len() as u64;
+
+    let sandbox = timestamping_sandbox_builder()
+        .with_mempool(|config| config.tx_pool_bytes = Some(tx_size))
+        .build();
+    sandbox.recv(&tx);
+    assert_eq!(sandbox.node_state().memory_usage().tx_pool_bytes, tx_size);
+
+    let rejected_tx = gen_timestamping_tx();
+    sandbox.recv(&rejected_tx);
+
+    let snapshot = sandbox.blockchain_ref().snapshot();
+    let schema = Schema::new(&snapshot);
+    assert!(schema.transactions().contains(&tx.hash()));
+    assert!(!schema.transactions().contains(&rejected_tx.hash()));
+    assert_eq!(sandbox.node_state().memory_usage().tx_pool_bytes, tx_size);
+
+    let sandbox = timestamping_sandbox_builder()
+        .with_mempool(|config| config.tx_pool_capacity = 1)
+        .build();
+    sandbox.recv(&tx);
+    sandbox.recv(&rejected_tx);
+
+    let snapshot = sandbox.blockchain_ref().snapshot();
+    assert_eq!(Schema::new(&snapshot).transactions_pool_len(), 1);
+}
+
+/// idea of the test is to verify that the node accepts transactions required by a propose
+/// even if the pool is full
+#[test]
+fn tx_pool_limits_awaited_tx() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_mempool(|config| config.tx_pool_capacity = 1)
+        .build();
+
+    sandbox.recv(&gen_timestamping_tx());
+
+    let tx = gen_timestamping_tx();
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[tx.hash()])
+        .build();
+    sandbox.recv(&propose);
+    sandbox.recv(&tx);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+
+    let snapshot = sandbox.blockchain_ref().snapshot();
+    assert_eq!(Schema::new(&snapshot).transactions_pool_len(), 2);
+}
+
+/// idea of the test is to verify that the node drops messages from the future rounds
+/// once the in-flight messages size limit is reached
+#[test]
+fn in_flight_bytes_limit() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_mempool(|config| config.in_flight_bytes = Some(0))
+        .build();
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_validator(ValidatorId(3))
+        .with_round(Round(2))
+        .build();
+    sandbox.recv(&propose);
+    assert_eq!(sandbox.node_state().memory_usage().in_flight_bytes, 0);
+
+    let sandbox = timestamping_sandbox();
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_validator(ValidatorId(3))
+        .with_round(Round(2))
+        .build();
+    sandbox.recv(&propose);
+    assert_eq!(
+        sandbox.node_state().memory_usage().in_flight_bytes,
+        propose.signed_message().raw().len() as u64
+    );
+}
+
+/// idea of the test is to verify that a transaction from a pruned block is not accepted
+/// into the pool again, neither directly nor in a `TransactionsResponse`, and that a propose
+/// containing it is rejected
+#[test]
+fn replayed_tx_from_pruned_block() {
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+
+    let tx = gen_timestamping_tx();
+    add_one_height_with_transactions(&sandbox, &sandbox_state, &[tx.clone()]);
+    sandbox.assert_state(Height(2), Round(1));
+
+    let pruned = sandbox
+        .blockchain_mut()
+        .prune_blocks(Height(2), u64::max_value())
+        .unwrap();
+    assert_eq!(pruned, 2);
+    {
+        let snapshot = sandbox.blockchain_ref().snapshot();
+        let schema = Schema::new(&snapshot);
+        assert!(!schema.transactions().contains(&tx.hash()));
+        assert!(schema.is_known_transaction(&tx.hash()));
+    }
+
+    sandbox.recv(&tx);
+    sandbox.recv(&sandbox.create_transactions_response(
+        &sandbox.p(ValidatorId(2)),
+        &sandbox.p(ValidatorId(0)),
+        vec![tx.clone()],
+        sandbox.s(ValidatorId(2)),
+    ));
+    sandbox.assert_pool_len(0);
+    {
+        let snapshot = sandbox.blockchain_ref().snapshot();
+        assert!(!Schema::new(&snapshot).transactions().contains(&tx.hash()));
+    }
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[tx.hash()])
+        .build();
+    sandbox.recv(&propose);
+    // No prevote is broadcast and the transaction is not requested.
+    sandbox.add_time(Duration::from_millis(TRANSACTIONS_REQUEST_TIMEOUT));
+}
+
+/// idea of the test is to verify that a transaction with an unknown dependency is not
+/// accepted into the pool, and that it is dropped from the pool once its dependency
+/// is evicted
+#[test]
+fn tx_with_unknown_dependency() {
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+
+    let tx = gen_timestamping_tx();
+    let dependent_tx = gen_dependent_tx(&tx.hash());
+    sandbox.recv(&dependent_tx);
+    sandbox.assert_pool_len(0);
+
+    sandbox.recv(&tx);
+    sandbox.recv(&dependent_tx);
+    sandbox.assert_pool_len(2);
+
+    sandbox.node_handler_mut().handle_evict_tx(&tx.hash());
+    sandbox.assert_pool_len(1);
+    add_one_height(&sandbox, &sandbox_state);
+    sandbox.assert_pool_len(0);
+    let snapshot = sandbox.blockchain_ref().snapshot();
+    assert!(!Schema::new(&snapshot).is_known_transaction(&dependent_tx.hash()));
+}
+
+/// idea of the test is to verify that a transaction evicted from the pool can be received
+/// again and committed in a block proposed by another validator
+#[test]
+fn evicted_tx_committed_by_other_validator() {
+    let sandbox = timestamping_sandbox();
+
+    let tx = gen_timestamping_tx();
+    sandbox.recv(&tx);
+    sandbox.node_handler_mut().handle_evict_tx(&tx.hash());
+    sandbox.assert_pool_len(0);
+    sandbox.recv(&tx);
+    sandbox.assert_pool_len(1);
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[tx.hash()])
+        .build();
+    let block = BlockBuilder::new(&sandbox)
+        .with_proposer_id(propose.validator())
+        .with_tx_hash(&tx.hash())
+        .with_state_hash(&sandbox.compute_state_hash(&[tx.clone()]))
+        .build();
+
+    sandbox.recv(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+    for i in 1..3 {
+        sandbox.recv(&sandbox.create_prevote(
+            ValidatorId(i),
+            Height(1),
+            Round(1),
+            &propose.hash(),
+            NOT_LOCKED,
+            sandbox.s(ValidatorId(i)),
+        ));
+    }
+    sandbox.broadcast(&sandbox.create_precommit(
+        ValidatorId(0),
+        Height(1),
+        Round(1),
+        &propose.hash(),
+        &block.hash(),
+        sandbox.time().into(),
+        sandbox.s(ValidatorId(0)),
+    ));
+    for i in 1..3 {
+        sandbox.recv(&sandbox.create_precommit(
+            ValidatorId(i),
+            Height(1),
+            Round(1),
+            &propose.hash(),
+            &block.hash(),
+            sandbox.time().into(),
+            sandbox.s(ValidatorId(i)),
+        ));
+    }
+    sandbox.assert_state(Height(2), Round(1));
+    sandbox.check_broadcast_status(Height(2), &block.hash());
+    sandbox.assert_pool_len(0);
+}
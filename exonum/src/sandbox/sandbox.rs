@@ -49,10 +49,13 @@ use messages::{
     PrevotesRequest, Propose, ProposeRequest, ProtocolMessage, RawTransaction, Signed,
     SignedMessage, Status, TransactionsRequest, TransactionsResponse,
 };
+#[cfg(debug_assertions)]
+use node::chaos::ChaosConfig;
 use node::ConnectInfo;
 use node::{
     ApiSender, Configuration, ConnectList, ConnectListConfig, ExternalMessage, ListenerConfig,
-    NodeHandler, NodeSender, PeerAddress, ServiceConfig, State, SystemStateProvider,
+    MemoryPoolConfig, NodeHandler, NodeSender, PeerAddress, ServiceConfig, State,
+    SystemStateProvider,
 };
 use storage::{MapProof, MemoryDB};
 
@@ -265,7 +268,11 @@ impl Sandbox {
         secret_key: &SecretKey,
     ) -> Signed<Connect> {
         Message::concrete(
-            Connect::new(&addr, time, user_agent),
+            Connect::new(
+                addr.parse().expect("Expected resolved address"),
+                time,
+                user_agent,
+            ),
             *public_key,
             secret_key,
         )
@@ -468,6 +475,14 @@ impl Sandbox {
             .handle_event(ExternalMessage::Rebroadcast);
     }
 
+    #[cfg(debug_assertions)]
+    pub fn recv_chaos(&self, config: ChaosConfig) {
+        self.check_unexpected_message();
+        self.inner
+            .borrow_mut()
+            .handle_event(ExternalMessage::Chaos(config));
+    }
+
     pub fn process_events(&self) {
         self.inner.borrow_mut().process_events();
     }
@@ -796,7 +811,7 @@ impl Sandbox {
         let connect = self.connect().map(|c| {
             self.create_connect(
                 &c.author(),
-                c.pub_addr().parse().expect("Expected resolved address"),
+                c.pub_addr().to_string(),
                 time.into(),
                 c.user_agent(),
                 self.s(ValidatorId(0)),
@@ -856,6 +871,7 @@ impl Sandbox {
             network: NetworkConfiguration::default(),
             peer_discovery: Vec::new(),
             mempool: Default::default(),
+            propose_precheck: inner.handler.propose_precheck(),
         };
 
         let system_state = SandboxSystemStateProvider {
@@ -944,7 +960,7 @@ impl ConnectList {
     pub fn from_peers(peers: &HashMap<PublicKey, Signed<Connect>>) -> Self {
         let peers: BTreeMap<PublicKey, PeerAddress> = peers
             .iter()
-            .map(|(p, c)| (*p, PeerAddress::new(c.pub_addr().to_owned())))
+            .map(|(p, c)| (*p, PeerAddress::new(c.pub_addr().to_string())))
             .collect();
         ConnectList { peers }
     }
@@ -955,6 +971,8 @@ pub struct SandboxBuilder {
     services: Vec<Box<dyn Service>>,
     validators_count: u8,
     consensus_config: ConsensusConfig,
+    mempool_config: MemoryPoolConfig,
+    propose_precheck: bool,
 }
 
 impl SandboxBuilder {
@@ -972,7 +990,13 @@ impl SandboxBuilder {
                 min_propose_timeout: PROPOSE_TIMEOUT,
                 max_propose_timeout: PROPOSE_TIMEOUT,
                 propose_timeout_threshold: std::u32::MAX,
+                leader_election: Default::default(),
+                priority_senders: Default::default(),
+                block_byzantine_peers: false,
+                configs_history_from: None,
             },
+            mempool_config: MemoryPoolConfig::default(),
+            propose_precheck: false,
         }
     }
 
@@ -991,11 +1015,21 @@ impl SandboxBuilder {
         self
     }
 
+    pub fn with_mempool<F: FnOnce(&mut MemoryPoolConfig)>(mut self, update: F) -> Self {
+        update(&mut self.mempool_config);
+        self
+    }
+
     pub fn with_validators(mut self, n: u8) -> Self {
         self.validators_count = n;
         self
     }
 
+    pub fn with_propose_precheck(mut self) -> Self {
+        self.propose_precheck = true;
+        self
+    }
+
     pub fn build(self) -> Sandbox {
         let _ = env_logger::Builder::from_default_env()
             .target(env_logger::Target::Stdout)
@@ -1005,6 +1039,8 @@ impl SandboxBuilder {
             self.services,
             self.consensus_config,
             self.validators_count,
+            self.mempool_config,
+            self.propose_precheck,
         );
 
         sandbox.inner.borrow_mut().sent.clear(); // To clear initial connect messages.
@@ -1027,6 +1063,8 @@ fn sandbox_with_services_uninitialized(
     services: Vec<Box<dyn Service>>,
     consensus: ConsensusConfig,
     validators_count: u8,
+    mempool: MemoryPoolConfig,
+    propose_precheck: bool,
 ) -> Sandbox {
     let validators = (0..validators_count)
         .map(|i| gen_keypair_from_seed(&Seed::new([i; SEED_LENGTH])))
@@ -1090,7 +1128,8 @@ fn sandbox_with_services_uninitialized(
         },
         network: NetworkConfiguration::default(),
         peer_discovery: Vec::new(),
-        mempool: Default::default(),
+        mempool,
+        propose_precheck,
     };
 
     let system_state = SandboxSystemStateProvider {
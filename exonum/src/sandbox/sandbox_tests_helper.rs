@@ -17,13 +17,13 @@ use bit_vec::BitVec;
 
 use std::{cell::RefCell, collections::BTreeMap, time::Duration};
 
-use super::timestamping::DATA_SIZE;
+use super::timestamping::{DependentTx, DATA_SIZE, TIMESTAMPING_SERVICE};
 use super::{sandbox::Sandbox, timestamping::TimestampingTxGenerator};
 use blockchain::Block;
-use crypto::{CryptoHash, Hash, HASH_SIZE};
+use crypto::{gen_keypair, CryptoHash, Hash, HASH_SIZE};
 use helpers::{Height, Milliseconds, Round, ValidatorId};
 use messages::{
-    Precommit, Prevote, PrevotesRequest, Propose, ProposeRequest, RawTransaction, Signed,
+    Message, Precommit, Prevote, PrevotesRequest, Propose, ProposeRequest, RawTransaction, Signed,
 };
 use storage::Database;
 
@@ -261,6 +261,16 @@ pub fn gen_timestamping_tx() -> Signed<RawTransaction> {
     tx_gen.next().unwrap()
 }
 
+pub fn gen_dependent_tx(dependency: &Hash) -> Signed<RawTransaction> {
+    let (public_key, secret_key) = gen_keypair();
+    Message::sign_transaction(
+        DependentTx::new(dependency),
+        TIMESTAMPING_SERVICE,
+        public_key,
+        &secret_key,
+    )
+}
+
 pub fn add_one_height(sandbox: &TimestampingSandbox, sandbox_state: &SandboxState) {
     // gen some tx
     let tx = gen_timestamping_tx();
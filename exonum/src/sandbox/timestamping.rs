@@ -29,10 +29,28 @@ transactions! {
         struct TimestampTx {
             data: &[u8],
         }
+
+        struct DependentTx {
+            dependency: &Hash,
+        }
     }
 }
 
 impl Transaction for TimestampTx {
+    fn verify(&self) -> bool {
+        !self.data().is_empty()
+    }
+
+    fn execute(&self, _: TransactionContext) -> ExecutionResult {
+        Ok(())
+    }
+}
+
+impl Transaction for DependentTx {
+    fn depends_on(&self) -> Option<Hash> {
+        Some(*self.dependency())
+    }
+
     fn execute(&self, _: TransactionContext) -> ExecutionResult {
         Ok(())
     }
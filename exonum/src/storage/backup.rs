@@ -0,0 +1,324 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Backups of the database.
+//!
+//! A backup is a single file containing all the tables of the database: blocks,
+//! transactions, the state of the services and the indexes metadata. Unlike
+//! [checkpoints](fn.write_checkpoint.html), backups do not depend on the database backend
+//! and can be stored or transferred as ordinary files. A backup is restored into an empty
+//! database, e.g., the database of a fresh node.
+//!
+//! # Format
+//!
+//! All integers are encoded in little-endian byte order. A backup consists of:
+//!
+//! - header: magic bytes `EXNMBKUP` and format version (`u16`);
+//! - records, each one starting with its type (`u8`). A table record (type 1) contains
+//!   the name of a table, and the entry records (type 2) following it contain the keys
+//!   and the values of this table. Names, keys and values are prefixed by their
+//!   length (`u32`);
+//! - footer: the end record (type 0), the number of entries (`u64`) and the SHA-256 hash
+//!   of all the preceding bytes of the backup, used as a checksum.
+//!
+//! The backup is verified against its checksum during restore. The blockchain data
+//! is not interpreted here; the `restore` command of the node additionally checks
+//! the restored blocks and the core tables against the stored block hashes
+//! before accepting the database.
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use std::io::{Read, Write};
+
+use super::{
+    diff::table_names, indexes_metadata::INDEXES_METADATA_TABLE_NAME, Database, Error, Result,
+    Snapshot,
+};
+use crypto::{Hash, HashStream, HASH_SIZE};
+
+/// Current version of the backup format.
+pub const BACKUP_VERSION: u16 = 1;
+
+const MAGIC: &[u8; 8] = b"EXNMBKUP";
+const HEADER_SIZE: usize = 10;
+
+const END_RECORD: u8 = 0;
+const TABLE_RECORD: u8 = 1;
+const ENTRY_RECORD: u8 = 2;
+
+/// Number of the entries written to the restored database in a single merge.
+const BATCH_SIZE: usize = 10_000;
+
+/// Summary of a backup.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BackupInfo {
+    /// Number of the tables in the backup.
+    pub tables: usize,
+    /// Total number of the entries in all the tables.
+    pub entries: u64,
+    /// Checksum of the backup.
+    pub checksum: Hash,
+}
+
+impl BackupInfo {
+    fn new() -> Self {
+        Self {
+            tables: 0,
+            entries: 0,
+            checksum: Hash::zero(),
+        }
+    }
+}
+
+/// Hashes the bytes passing through the wrapped reader or writer.
+#[derive(Debug)]
+struct Checksum<T> {
+    inner: T,
+    stream: Option<HashStream>,
+}
+
+impl<T> Checksum<T> {
+    fn new(inner: T) -> Self {
+        Self {
+            inner,
+            stream: Some(HashStream::new()),
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        self.stream = self.stream.take().map(|stream| stream.update(bytes));
+    }
+
+    fn hash(&mut self) -> Hash {
+        self.stream
+            .take()
+            .expect("BUG: Checksum is already computed")
+            .hash()
+    }
+}
+
+impl<W: Write> Checksum<W> {
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.inner.write_all(bytes)?;
+        self.update(bytes);
+        Ok(())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        let mut len = [0; 4];
+        LittleEndian::write_u32(&mut len, bytes.len() as u32);
+        self.write(&len)?;
+        self.write(bytes)
+    }
+}
+
+impl<R: Read> Checksum<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.inner.read_exact(buf)?;
+        self.update(buf);
+        Ok(())
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        let mut buf = [0; 1];
+        self.read(&mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn read_bytes(&mut self) -> Result<Vec<u8>> {
+        let mut len = [0; 4];
+        self.read(&mut len)?;
+        // The bytes are read up to the declared length, so that a corrupted length
+        // does not cause a huge allocation.
+        let len = u64::from(LittleEndian::read_u32(&len));
+        let mut bytes = Vec::new();
+        (&mut self.inner).take(len).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != len {
+            return Err(Error::new("Unexpected end of the backup"));
+        }
+        self.update(&bytes);
+        Ok(bytes)
+    }
+}
+
+/// Writes all the tables of the snapshot to the backup.
+pub fn export<W: Write>(snapshot: &dyn Snapshot, writer: W) -> Result<BackupInfo> {
+    let mut writer = Checksum::new(writer);
+    let mut header = [0; HEADER_SIZE];
+    header[..8].copy_from_slice(MAGIC);
+    LittleEndian::write_u16(&mut header[8..], BACKUP_VERSION);
+    writer.write(&header)?;
+
+    let mut info = BackupInfo::new();
+    for name in table_names(snapshot) {
+        writer.write(&[TABLE_RECORD])?;
+        writer.write_bytes(name.as_bytes())?;
+        info.tables += 1;
+        let mut iter = snapshot.iter(&name, &[]);
+        while let Some((key, value)) = iter.next() {
+            writer.write(&[ENTRY_RECORD])?;
+            writer.write_bytes(key)?;
+            writer.write_bytes(value)?;
+            info.entries += 1;
+        }
+    }
+
+    let mut footer = [0; 9];
+    footer[0] = END_RECORD;
+    LittleEndian::write_u64(&mut footer[1..], info.entries);
+    writer.write(&footer)?;
+    info.checksum = writer.hash();
+    writer.inner.write_all(info.checksum.as_ref())?;
+    writer.inner.flush()?;
+    Ok(info)
+}
+
+/// Restores the backup into the database.
+///
+/// # Errors
+///
+/// Returns an error if the database is not empty, the backup is malformed or its checksum
+/// does not match the contents. The entries are written to the database in batches,
+/// so the database should be discarded if the restore fails.
+pub fn restore<R: Read>(reader: R, db: &dyn Database) -> Result<BackupInfo> {
+    {
+        let snapshot = db.snapshot();
+        let mut iter = snapshot.iter(INDEXES_METADATA_TABLE_NAME, &[]);
+        if iter.next().is_some() {
+            return Err(Error::new(
+                "Backup can be restored only into an empty database",
+            ));
+        }
+    }
+
+    let mut reader = Checksum::new(reader);
+    let mut header = [0; HEADER_SIZE];
+    reader.read(&mut header)?;
+    if &header[..8] != MAGIC {
+        return Err(Error::new("Not a database backup"));
+    }
+    let version = LittleEndian::read_u16(&header[8..]);
+    if version != BACKUP_VERSION {
+        return Err(Error::new(format!(
+            "Unsupported backup version {}",
+            version
+        )));
+    }
+
+    let mut info = BackupInfo::new();
+    let mut table = None;
+    let mut fork = db.fork();
+    let mut batch_len = 0;
+    loop {
+        match reader.read_u8()? {
+            TABLE_RECORD => {
+                let name = String::from_utf8(reader.read_bytes()?)
+                    .map_err(|_| Error::new("Invalid table name in the backup"))?;
+                table = Some(name);
+                info.tables += 1;
+            }
+            ENTRY_RECORD => {
+                let name = table
+                    .as_ref()
+                    .ok_or_else(|| Error::new("Backup entry does not belong to a table"))?;
+                let key = reader.read_bytes()?;
+                let value = reader.read_bytes()?;
+                fork.put(name, key, value);
+                info.entries += 1;
+                batch_len += 1;
+                if batch_len == BATCH_SIZE {
+                    db.merge(fork.into_patch())?;
+                    fork = db.fork();
+                    batch_len = 0;
+                }
+            }
+            END_RECORD => break,
+            kind => {
+                return Err(Error::new(format!(
+                    "Unknown record type {} in the backup",
+                    kind
+                )))
+            }
+        }
+    }
+
+    let mut entries = [0; 8];
+    reader.read(&mut entries)?;
+    if LittleEndian::read_u64(&entries) != info.entries {
+        return Err(Error::new(
+            "Number of the entries in the backup does not match",
+        ));
+    }
+    info.checksum = reader.hash();
+    let mut checksum = [0; HASH_SIZE];
+    reader.inner.read_exact(&mut checksum)?;
+    if &checksum[..] != info.checksum.as_ref() {
+        return Err(Error::new("Backup checksum mismatch"));
+    }
+    db.merge_sync(fork.into_patch())?;
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export, restore};
+    use storage::{diff::diff_snapshots, Database, ListIndex, MapIndex, MemoryDB};
+
+    fn create_database() -> MemoryDB {
+        let db = MemoryDB::new();
+        let mut fork = db.fork();
+        {
+            let mut index: MapIndex<_, u64, String> = MapIndex::new("map", &mut fork);
+            for i in 0..100 {
+                index.put(&i, i.to_string());
+            }
+            let mut index: ListIndex<_, u64> = ListIndex::new("list", &mut fork);
+            index.extend(0..10);
+        }
+        db.merge(fork.into_patch()).unwrap();
+        db
+    }
+
+    #[test]
+    fn backup_roundtrip() {
+        let source = create_database();
+        let mut backup = Vec::new();
+        let info = export(&*source.snapshot(), &mut backup).unwrap();
+
+        let restored = MemoryDB::new();
+        assert_eq!(restore(&backup[..], &restored).unwrap(), info);
+        assert!(diff_snapshots(&*source.snapshot(), &*restored.snapshot()).is_empty());
+        // The database is not empty anymore.
+        assert!(restore(&backup[..], &restored).is_err());
+    }
+
+    #[test]
+    fn corrupted_backup() {
+        let source = create_database();
+        let mut backup = Vec::new();
+        export(&*source.snapshot(), &mut backup).unwrap();
+
+        let mut corrupted = backup.clone();
+        let position = corrupted.windows(2).position(|w| w == b"99").unwrap();
+        corrupted[position] ^= 1;
+        assert!(restore(&corrupted[..], &MemoryDB::new()).is_err());
+
+        let truncated = &backup[..backup.len() - 1];
+        assert!(restore(truncated, &MemoryDB::new()).is_err());
+
+        let mut unsupported = backup.clone();
+        unsupported[8] = 0xff;
+        assert!(restore(&unsupported[..], &MemoryDB::new()).is_err());
+    }
+}
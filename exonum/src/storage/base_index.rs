@@ -20,7 +20,11 @@
 
 // spell-checker:ignore subprefix
 
-use std::{borrow::Cow, marker::PhantomData};
+use std::{
+    borrow::Cow,
+    collections::Bound::{Excluded, Included, Unbounded},
+    marker::PhantomData,
+};
 
 use super::{Fork, Iter, Snapshot, StorageKey, StorageValue};
 use storage::indexes_metadata::{self, IndexType, INDEXES_METADATA_TABLE_NAME};
@@ -223,6 +227,69 @@ where
             _v: PhantomData,
         }
     }
+
+    /// Returns an iterator over the entries of the index in descending order. The iterator
+    /// element type is *any* key-value pair. An argument `subprefix` allows specifying a subset
+    /// of keys for iteration.
+    pub fn iter_rev<P, K, V>(&self, subprefix: &P) -> BaseIndexIter<K, V>
+    where
+        P: StorageKey,
+        K: StorageKey,
+        V: StorageValue,
+    {
+        let iter_prefix = self.prefixed_key(subprefix);
+        let upper_bound = prefix_upper_bound(&iter_prefix);
+        let to = match upper_bound {
+            Some(ref key) => Excluded(key.as_slice()),
+            None => Unbounded,
+        };
+        BaseIndexIter {
+            base_iter: self.view.as_ref().iter_rev(&self.name, to),
+            base_prefix_len: self.index_id.as_ref().map_or(0, |p| p.len()),
+            index_id: iter_prefix,
+            ended: false,
+            _k: PhantomData,
+            _v: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the entries of the index in descending order starting from the
+    /// specified key. The iterator element type is *any* key-value pair. An argument `subprefix`
+    /// allows specifying a subset of iteration.
+    pub fn iter_rev_from<P, F, K, V>(&self, subprefix: &P, from: &F) -> BaseIndexIter<K, V>
+    where
+        P: StorageKey,
+        F: StorageKey + ?Sized,
+        K: StorageKey,
+        V: StorageValue,
+    {
+        let iter_prefix = self.prefixed_key(subprefix);
+        let iter_from = self.prefixed_key(from);
+        BaseIndexIter {
+            base_iter: self
+                .view
+                .as_ref()
+                .iter_rev(&self.name, Included(iter_from.as_slice())),
+            base_prefix_len: self.index_id.as_ref().map_or(0, |p| p.len()),
+            index_id: iter_prefix,
+            ended: false,
+            _k: PhantomData,
+            _v: PhantomData,
+        }
+    }
+}
+
+/// Returns the least key greater than all the keys starting with `prefix`, or `None`
+/// if there is no such key.
+fn prefix_upper_bound(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut bound = prefix.to_vec();
+    while let Some(last) = bound.pop() {
+        if last < u8::max_value() {
+            bound.push(last + 1);
+            return Some(bound);
+        }
+    }
+    None
 }
 
 impl<'a> BaseIndex<&'a mut Fork> {
@@ -306,7 +373,7 @@ impl<'a, K, V> ::std::fmt::Debug for BaseIndexIter<'a, K, V> {
 
 /// A function that validates an index name. Allowable characters in name: ASCII characters, digits
 /// and underscores.
-fn is_valid_name<S: AsRef<str>>(name: S) -> bool {
+pub(crate) fn is_valid_name<S: AsRef<str>>(name: S) -> bool {
     name.as_ref().as_bytes().iter().all(|c| match *c {
         48...57 | 65...90 | 97...122 | 95 | 46 => true,
         _ => false,
@@ -342,6 +409,14 @@ mod tests {
         assert!(!is_valid_name("1in!dex_Namez"));
     }
 
+    #[test]
+    fn test_prefix_upper_bound() {
+        assert_eq!(prefix_upper_bound(&[]), None);
+        assert_eq!(prefix_upper_bound(&[1, 2]), Some(vec![1, 3]));
+        assert_eq!(prefix_upper_bound(&[1, 255, 255]), Some(vec![2]));
+        assert_eq!(prefix_upper_bound(&[255, 255]), None);
+    }
+
     #[test]
     fn check_valid_name() {
         assert_valid_name("valid_name");
@@ -0,0 +1,102 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checkpoints of the database.
+//!
+//! A checkpoint is a consistent copy of the database state written to a new persistent
+//! database. It is taken from a snapshot, so the node keeps working while the checkpoint
+//! is written. A checkpoint can be used as a hot backup or as the database of a new node,
+//! which then only needs to fetch the blocks committed after the checkpoint instead of
+//! replaying the whole chain.
+
+use std::path::Path;
+
+use super::{diff::table_names, open_database, Database, DbOptions, Error, Result, Snapshot};
+
+/// Number of the entries written to the checkpoint database in a single merge.
+const BATCH_SIZE: usize = 10_000;
+
+/// Writes the contents of the snapshot to a new persistent database at the given path.
+/// The persistent database available in the build is used, see [`open_database`].
+///
+/// # Errors
+///
+/// Returns an error if the path already exists or the database cannot be written.
+///
+/// [`open_database`]: fn.open_database.html
+pub fn write_checkpoint<P: AsRef<Path>>(snapshot: &dyn Snapshot, path: P) -> Result<()> {
+    let path = path.as_ref();
+    if path.exists() {
+        return Err(Error::new(format!(
+            "Checkpoint path {} already exists",
+            path.display()
+        )));
+    }
+
+    let target = open_database(path, &DbOptions::default())?;
+    let mut fork = target.fork();
+    let mut batch_len = 0;
+    for name in table_names(snapshot) {
+        let mut iter = snapshot.iter(&name, &[]);
+        while let Some((key, value)) = iter.next() {
+            fork.put(&name, key.to_vec(), value.to_vec());
+            batch_len += 1;
+            if batch_len == BATCH_SIZE {
+                target.merge(fork.into_patch())?;
+                fork = target.fork();
+                batch_len = 0;
+            }
+        }
+    }
+    target.merge(fork.into_patch())?;
+    target.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::BATCH_SIZE;
+    use storage::{diff::diff_snapshots, open_database, Database, DbOptions, MapIndex, MemoryDB};
+
+    #[test]
+    fn checkpoint_equals_source() {
+        let db = MemoryDB::new();
+        let mut fork = db.fork();
+        {
+            let mut index: MapIndex<_, u64, u64> = MapIndex::new("first", &mut fork);
+            for i in 0..(BATCH_SIZE as u64 * 2 + 1) {
+                index.put(&i, i * 2);
+            }
+            let mut index: MapIndex<_, u64, String> = MapIndex::new("second", &mut fork);
+            index.put(&1, "value".to_owned());
+        }
+        db.merge(fork.into_patch()).unwrap();
+
+        let dir = TempDir::new("exonum_checkpoint").unwrap();
+        let path = dir.path().join("checkpoint");
+        db.checkpoint(&path).unwrap();
+        // Checkpoint cannot overwrite the existing data.
+        assert!(db.checkpoint(&path).is_err());
+
+        let options = DbOptions {
+            create_if_missing: false,
+            ..DbOptions::default()
+        };
+        let checkpoint = open_database(&path, &options).unwrap();
+        assert!(diff_snapshots(&*db.snapshot(), &*checkpoint.snapshot()).is_empty());
+        let index: MapIndex<_, u64, u64> = MapIndex::new("first", checkpoint.snapshot());
+        assert_eq!(index.get(&(BATCH_SIZE as u64)), Some(BATCH_SIZE as u64 * 2));
+    }
+}
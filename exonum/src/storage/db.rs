@@ -15,15 +15,18 @@
 use std::{
     cmp::Ordering::{Equal, Greater, Less},
     collections::{
-        btree_map::{BTreeMap, IntoIter as BtmIntoIter, Iter as BtmIter, Range},
+        btree_map::{BTreeMap, IntoIter as BtmIntoIter, Iter as BtmIter},
         hash_map::{Entry as HmEntry, IntoIter as HmIntoIter, Iter as HmIter},
-        Bound::{Included, Unbounded},
+        Bound::{self, Included, Unbounded},
         HashMap,
     },
     iter::{Iterator as StdIterator, Peekable},
+    ops::{Deref, DerefMut},
+    path::Path,
+    sync::Arc,
 };
 
-use super::Result;
+use super::{checkpoint::write_checkpoint, Result};
 
 /// Map containing changes with a corresponding key.
 #[derive(Debug, Clone)]
@@ -108,6 +111,17 @@ impl Patch {
         self.changes.insert(name, changes);
     }
 
+    /// Applies the changes from another patch on top of this one.
+    pub(crate) fn merge(&mut self, patch: Patch) {
+        for (name, changes) in patch {
+            if let Some(in_changes) = self.changes_mut(&name) {
+                in_changes.data.extend(changes.into_iter());
+                continue;
+            }
+            self.insert_changes(name, changes);
+        }
+    }
+
     /// Returns iterator over changes.
     pub fn iter(&self) -> HmIter<String, Changes> {
         self.changes.iter()
@@ -178,7 +192,8 @@ pub enum Change {
 ///
 /// `Fork` also supports checkpoints ([`checkpoint`], [`commit`] and
 /// [`rollback`] methods), which allows rolling back some of the latest changes (e.g., after
-/// a runtime error).
+/// a runtime error). Services can additionally stage changes to several indices in
+/// a [`WriteBatch`], which is applied to the fork atomically.
 ///
 /// `Fork` implements the [`Snapshot`] trait and provides methods for both reading and
 /// writing data. Thus, `&mut Fork` is used as a storage view for creating
@@ -197,6 +212,7 @@ pub enum Change {
 /// [`checkpoint`]: #method.checkpoint
 /// [`commit`]: #method.commit
 /// [`rollback`]: #method.rollback
+/// [`WriteBatch`]: struct.WriteBatch.html
 
 // FIXME: make &mut Fork "unwind safe". (ECR-176)
 pub struct Fork {
@@ -204,11 +220,69 @@ pub struct Fork {
     patch: Patch,
     changelog: Vec<(String, Vec<u8>, Option<Change>)>,
     logged: bool,
+    batches: usize,
+}
+
+/// A set of changes staged on top of a [`Fork`], which are either applied
+/// all at once or not applied at all.
+///
+/// A batch is created with [`Fork::write_batch`] and dereferences to the underlying
+/// fork, so any indices can be created on top of it with `&mut *batch`. The changes made
+/// through the batch are visible to the subsequent reads from the batch, but are kept
+/// in the fork only if [`commit`] is called. If the batch is dropped without a commit
+/// (e.g., when an error is returned with the `?` operator in the middle of the batch),
+/// all its changes are rolled back.
+///
+/// Batches can be nested and can be used inside the checkpoint created for the transaction
+/// execution; in the latter case, the changes of a committed batch are still rolled back
+/// together with the transaction.
+///
+/// # Examples
+///
+/// ```
+/// # use exonum::storage::{Database, ListIndex, MapIndex, MemoryDB};
+/// let db = MemoryDB::new();
+/// let mut fork = db.fork();
+/// {
+///     let mut batch = fork.write_batch();
+///     ListIndex::new("list", &mut *batch).push(1_u64);
+///     MapIndex::new("map", &mut *batch).put(&1_u64, 2_u64);
+///     // The batch is dropped without commit.
+/// }
+/// assert!(ListIndex::<_, u64>::new("list", &fork).is_empty());
+/// {
+///     let mut batch = fork.write_batch();
+///     ListIndex::new("list", &mut *batch).push(1_u64);
+///     batch.commit();
+/// }
+/// assert_eq!(ListIndex::<_, u64>::new("list", &fork).len(), 1);
+/// ```
+///
+/// [`Fork`]: struct.Fork.html
+/// [`Fork::write_batch`]: struct.Fork.html#method.write_batch
+/// [`commit`]: #method.commit
+pub struct WriteBatch<'a> {
+    fork: &'a mut Fork,
+    changelog_len: usize,
+    logged: bool,
+    finished: bool,
+}
+
+/// A snapshot with the changes of a shared patch applied on top of it.
+///
+/// Unlike a [`Fork`], the snapshot does not own the changes, so the same patch can be
+/// read from several threads without copying it.
+///
+/// [`Fork`]: struct.Fork.html
+pub(crate) struct PatchedSnapshot {
+    snapshot: Box<dyn Snapshot>,
+    patch: Arc<Patch>,
 }
 
 struct ForkIter<'a> {
     snapshot: Iter<'a>,
-    changes: Option<Peekable<Range<'a, Vec<u8>, Change>>>,
+    changes: Option<Peekable<Box<dyn StdIterator<Item = (&'a Vec<u8>, &'a Change)> + 'a>>>,
+    reversed: bool,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -254,12 +328,7 @@ pub trait Database: Send + Sync + 'static {
 
     /// Creates a new fork of the database from its current state.
     fn fork(&self) -> Fork {
-        Fork {
-            snapshot: self.snapshot(),
-            patch: Patch::new(),
-            changelog: Vec::new(),
-            logged: false,
-        }
+        Fork::from_snapshot(self.snapshot())
     }
 
     /// Atomically applies a sequence of patch changes to the database.
@@ -285,6 +354,55 @@ pub trait Database: Send + Sync + 'static {
     /// will be returned. In case of an error, the method guarantees no changes are applied to
     /// the database.
     fn merge_sync(&self, patch: Patch) -> Result<()>;
+
+    /// Ensures that all the merged changes are saved to the persistent storage.
+    ///
+    /// The default implementation does nothing, which is suitable for the databases
+    /// persisting the changes on merge.
+    fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Compacts the data of the indexes (column families) with names starting with
+    /// one of the given prefixes, or of all the indexes if `prefixes` is empty.
+    ///
+    /// Compaction may take significant time and slows down the other database operations,
+    /// so it is better performed during low load. The default implementation does nothing,
+    /// which is suitable for the databases not requiring compaction.
+    fn compact(&self, prefixes: &[String]) -> Result<()> {
+        let _ = prefixes;
+        Ok(())
+    }
+
+    /// Moves the entries to the secondary storage of the database. Each entry is specified
+    /// by the index name and the key prefix; all the keys of the index starting with the prefix
+    /// are moved. The moved entries remain readable, but they should not be changed afterwards.
+    ///
+    /// The default implementation does nothing, which is suitable for the databases
+    /// without a secondary storage. See [`TieredDB`](struct.TieredDB.html) for details.
+    fn archive(&self, entries: &[(String, Vec<u8>)]) -> Result<()> {
+        let _ = entries;
+        Ok(())
+    }
+
+    /// Returns `true` if the database has a secondary storage, so that [`archive`]
+    /// actually moves the entries. The default implementation returns `false`.
+    ///
+    /// [`archive`]: #method.archive
+    fn supports_archive(&self) -> bool {
+        false
+    }
+
+    /// Writes a consistent copy of the current state of the database to a new persistent
+    /// database at the given path. The copy is made from a snapshot, so the other operations
+    /// with the database are not blocked.
+    ///
+    /// The default implementation copies the data with [`write_checkpoint`].
+    ///
+    /// [`write_checkpoint`]: fn.write_checkpoint.html
+    fn checkpoint(&self, path: &Path) -> Result<()> {
+        write_checkpoint(&*self.snapshot(), path)
+    }
 }
 
 /// A read-only snapshot of a storage backend.
@@ -310,6 +428,11 @@ pub trait Snapshot: 'static {
     /// Returns an iterator over the entries of the snapshot in ascending order starting from
     /// the specified key. The iterator element type is `(&[u8], &[u8])`.
     fn iter<'a>(&'a self, name: &str, from: &[u8]) -> Iter<'a>;
+
+    /// Returns an iterator over the entries of the snapshot in descending order starting from
+    /// the greatest key within the specified upper bound. The iterator element type is
+    /// `(&[u8], &[u8])`.
+    fn iter_rev<'a>(&'a self, name: &str, to: Bound<&[u8]>) -> Iter<'a>;
 }
 
 /// A trait that defines a streaming iterator over storage view entries. Unlike
@@ -325,44 +448,121 @@ pub trait Iterator {
 
 impl Snapshot for Fork {
     fn get(&self, name: &str, key: &[u8]) -> Option<Vec<u8>> {
-        if let Some(changes) = self.patch.changes(name) {
-            if let Some(change) = changes.data.get(key) {
-                match *change {
-                    Change::Put(ref v) => return Some(v.clone()),
-                    Change::Delete => return None,
-                }
+        patched_get(&*self.snapshot, &self.patch, name, key)
+    }
+
+    fn contains(&self, name: &str, key: &[u8]) -> bool {
+        patched_contains(&*self.snapshot, &self.patch, name, key)
+    }
+
+    fn iter<'a>(&'a self, name: &str, from: &[u8]) -> Iter<'a> {
+        patched_iter(&*self.snapshot, &self.patch, name, from)
+    }
+
+    fn iter_rev<'a>(&'a self, name: &str, to: Bound<&[u8]>) -> Iter<'a> {
+        patched_iter_rev(&*self.snapshot, &self.patch, name, to)
+    }
+}
+
+impl PatchedSnapshot {
+    /// Creates a snapshot with the given changes applied.
+    pub(crate) fn new(snapshot: Box<dyn Snapshot>, patch: Arc<Patch>) -> Self {
+        Self { snapshot, patch }
+    }
+}
+
+impl Snapshot for PatchedSnapshot {
+    fn get(&self, name: &str, key: &[u8]) -> Option<Vec<u8>> {
+        patched_get(&*self.snapshot, &self.patch, name, key)
+    }
+
+    fn contains(&self, name: &str, key: &[u8]) -> bool {
+        patched_contains(&*self.snapshot, &self.patch, name, key)
+    }
+
+    fn iter<'a>(&'a self, name: &str, from: &[u8]) -> Iter<'a> {
+        patched_iter(&*self.snapshot, &self.patch, name, from)
+    }
+
+    fn iter_rev<'a>(&'a self, name: &str, to: Bound<&[u8]>) -> Iter<'a> {
+        patched_iter_rev(&*self.snapshot, &self.patch, name, to)
+    }
+}
+
+fn patched_get(snapshot: &dyn Snapshot, patch: &Patch, name: &str, key: &[u8]) -> Option<Vec<u8>> {
+    if let Some(changes) = patch.changes(name) {
+        if let Some(change) = changes.data.get(key) {
+            match *change {
+                Change::Put(ref v) => return Some(v.clone()),
+                Change::Delete => return None,
             }
         }
-        self.snapshot.get(name, key)
     }
+    snapshot.get(name, key)
+}
 
-    fn contains(&self, name: &str, key: &[u8]) -> bool {
-        if let Some(changes) = self.patch.changes(name) {
-            if let Some(change) = changes.data.get(key) {
-                match *change {
-                    Change::Put(..) => return true,
-                    Change::Delete => return false,
-                }
+fn patched_contains(snapshot: &dyn Snapshot, patch: &Patch, name: &str, key: &[u8]) -> bool {
+    if let Some(changes) = patch.changes(name) {
+        if let Some(change) = changes.data.get(key) {
+            match *change {
+                Change::Put(..) => return true,
+                Change::Delete => return false,
             }
         }
-        self.snapshot.contains(name, key)
     }
+    snapshot.contains(name, key)
+}
 
-    fn iter<'a>(&'a self, name: &str, from: &[u8]) -> Iter<'a> {
-        let range = (Included(from), Unbounded);
-        let changes = match self.patch.changes(name) {
-            Some(changes) => Some(changes.data.range::<[u8], _>(range).peekable()),
-            None => None,
-        };
+fn patched_iter<'a>(
+    snapshot: &'a dyn Snapshot,
+    patch: &'a Patch,
+    name: &str,
+    from: &[u8],
+) -> Iter<'a> {
+    let range = (Included(from), Unbounded);
+    let changes = patch.changes(name).map(|changes| {
+        let iter: Box<dyn StdIterator<Item = _>> = Box::new(changes.data.range::<[u8], _>(range));
+        iter.peekable()
+    });
+
+    Box::new(ForkIter {
+        snapshot: snapshot.iter(name, from),
+        changes,
+        reversed: false,
+    })
+}
 
-        Box::new(ForkIter {
-            snapshot: self.snapshot.iter(name, from),
-            changes,
-        })
-    }
+fn patched_iter_rev<'a>(
+    snapshot: &'a dyn Snapshot,
+    patch: &'a Patch,
+    name: &str,
+    to: Bound<&[u8]>,
+) -> Iter<'a> {
+    let changes = patch.changes(name).map(|changes| {
+        let iter: Box<dyn StdIterator<Item = _>> =
+            Box::new(changes.data.range::<[u8], _>((Unbounded, to)).rev());
+        iter.peekable()
+    });
+
+    Box::new(ForkIter {
+        snapshot: snapshot.iter_rev(name, to),
+        changes,
+        reversed: true,
+    })
 }
 
 impl Fork {
+    /// Creates a fork with no changes on top of the given snapshot.
+    pub(crate) fn from_snapshot(snapshot: Box<dyn Snapshot>) -> Self {
+        Self {
+            snapshot,
+            patch: Patch::new(),
+            changelog: Vec::new(),
+            logged: false,
+            batches: 0,
+        }
+    }
+
     /// Creates a new checkpoint.
     ///
     /// In Exonum checkpoints are created before applying each transaction to
@@ -388,6 +588,9 @@ impl Fork {
         if !self.logged {
             panic!("call commit before checkpoint");
         }
+        if self.batches > 0 {
+            panic!("call commit after the write batch is finished");
+        }
         self.changelog.clear();
         self.logged = false;
     }
@@ -402,7 +605,33 @@ impl Fork {
         if !self.logged {
             panic!("call rollback before checkpoint");
         }
-        for (name, k, c) in self.changelog.drain(..).rev() {
+        if self.batches > 0 {
+            panic!("call rollback after the write batch is finished");
+        }
+        self.rollback_to(0);
+        self.logged = false;
+    }
+
+    /// Starts a new write batch on top of the fork.
+    ///
+    /// See [`WriteBatch`] for details.
+    ///
+    /// [`WriteBatch`]: struct.WriteBatch.html
+    pub fn write_batch(&mut self) -> WriteBatch {
+        let batch = WriteBatch {
+            changelog_len: self.changelog.len(),
+            logged: self.logged,
+            finished: false,
+            fork: self,
+        };
+        batch.fork.logged = true;
+        batch.fork.batches += 1;
+        batch
+    }
+
+    /// Reverts the changes recorded in the changelog after the given position.
+    fn rollback_to(&mut self, changelog_len: usize) {
+        for (name, k, c) in self.changelog.drain(changelog_len..).rev() {
             if let Some(changes) = self.patch.changes_mut(&name) {
                 match c {
                     Some(change) => changes.data.insert(k, change),
@@ -410,7 +639,6 @@ impl Fork {
                 };
             }
         }
-        self.logged = false;
     }
 
     /// Inserts a key-value pair into the fork.
@@ -503,16 +731,7 @@ impl Fork {
         if self.logged {
             panic!("call merge before commit or rollback");
         }
-
-        for (name, changes) in patch {
-            if let Some(in_changes) = self.patch.changes_mut(&name) {
-                in_changes.data.extend(changes.into_iter());
-                continue;
-            }
-            {
-                self.patch.insert_changes(name.to_owned(), changes);
-            }
-        }
+        self.patch.merge(patch);
     }
 }
 
@@ -534,23 +753,84 @@ impl ::std::fmt::Debug for Fork {
     }
 }
 
+impl<'a> WriteBatch<'a> {
+    /// Applies the changes of the batch to the underlying fork.
+    pub fn commit(mut self) {
+        self.finish();
+        // Without an outer checkpoint or batch, the changes cannot be rolled back anymore.
+        if !self.logged {
+            self.fork.changelog.truncate(self.changelog_len);
+        }
+    }
+
+    /// Discards the changes of the batch.
+    ///
+    /// This is equivalent to dropping the batch.
+    pub fn rollback(self) {}
+
+    fn finish(&mut self) {
+        self.finished = true;
+        self.fork.logged = self.logged;
+        self.fork.batches -= 1;
+    }
+}
+
+impl<'a> Deref for WriteBatch<'a> {
+    type Target = Fork;
+
+    fn deref(&self) -> &Fork {
+        self.fork
+    }
+}
+
+impl<'a> DerefMut for WriteBatch<'a> {
+    fn deref_mut(&mut self) -> &mut Fork {
+        self.fork
+    }
+}
+
+impl<'a> Drop for WriteBatch<'a> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let changelog_len = self.changelog_len;
+            self.fork.rollback_to(changelog_len);
+            self.finish();
+        }
+    }
+}
+
+impl<'a> ::std::fmt::Debug for WriteBatch<'a> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        write!(f, "WriteBatch(..)")
+    }
+}
+
 impl<'a> ForkIter<'a> {
     fn step(&mut self) -> NextIterValue {
         if let Some(ref mut changes) = self.changes {
             match changes.peek() {
                 Some(&(k, change)) => match self.snapshot.peek() {
-                    Some((key, ..)) => match *change {
-                        Change::Put(..) => match k[..].cmp(key) {
-                            Equal => NextIterValue::Replaced,
-                            Less => NextIterValue::Inserted,
-                            Greater => NextIterValue::Stored,
-                        },
-                        Change::Delete => match k[..].cmp(key) {
-                            Equal => NextIterValue::Deleted,
-                            Less => NextIterValue::MissDeleted,
-                            Greater => NextIterValue::Stored,
-                        },
-                    },
+                    Some((key, ..)) => {
+                        // The order of the keys is inverted for the reverse iteration,
+                        // so that the changes are merged in the same way.
+                        let order = if self.reversed {
+                            k[..].cmp(key).reverse()
+                        } else {
+                            k[..].cmp(key)
+                        };
+                        match *change {
+                            Change::Put(..) => match order {
+                                Equal => NextIterValue::Replaced,
+                                Less => NextIterValue::Inserted,
+                                Greater => NextIterValue::Stored,
+                            },
+                            Change::Delete => match order {
+                                Equal => NextIterValue::Deleted,
+                                Less => NextIterValue::MissDeleted,
+                                Greater => NextIterValue::Stored,
+                            },
+                        }
+                    }
                     None => match *change {
                         Change::Put(..) => NextIterValue::Inserted,
                         Change::Delete => NextIterValue::MissDeleted,
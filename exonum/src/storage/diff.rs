@@ -0,0 +1,379 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Comparison of the contents of two databases.
+//!
+//! The tables are compared by the digests of their key ranges: if the digests of a range
+//! are equal in both databases, the range is skipped; otherwise, the range is split by
+//! the next byte of the keys and the differing subranges are compared recursively.
+//! Small ranges are compared entry by entry. Thus, only the divergent parts of
+//! the tables are examined in detail.
+//!
+//! The Merkelized tables, i.e., `ProofListIndex` and `ProofMapIndex` outside of index
+//! families, are compared by their stored Merkle roots first and are not iterated
+//! if the roots are equal.
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{
+    base_index::BaseIndex,
+    indexes_metadata::{
+        IndexMetadata, IndexType, CORE_STORAGE_METADATA_KEY, INDEXES_METADATA_TABLE_NAME,
+    },
+    ProofListIndex, ProofMapIndex, Snapshot,
+};
+use crypto::{Hash, HashStream};
+
+/// Maximal number of entries in a key range compared entry by entry.
+const LEAF_RANGE_SIZE: usize = 16;
+
+/// Difference of the values stored by the key in the compared databases.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum KeyDiff {
+    /// The key is present only in the first database.
+    OnlyInFirst(Vec<u8>),
+    /// The key is present only in the second database.
+    OnlyInSecond(Vec<u8>),
+    /// The key is present in both databases with different values.
+    Changed(Vec<u8>),
+}
+
+impl KeyDiff {
+    /// Returns the key which values differ.
+    pub fn key(&self) -> &[u8] {
+        match *self {
+            KeyDiff::OnlyInFirst(ref key)
+            | KeyDiff::OnlyInSecond(ref key)
+            | KeyDiff::Changed(ref key) => key,
+        }
+    }
+}
+
+/// Differences of a table in the compared databases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableDiff {
+    /// Name of the table.
+    pub name: String,
+    /// Differing keys in the increasing order.
+    pub keys: Vec<KeyDiff>,
+}
+
+/// Digest of the entries within a key range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RangeDigest {
+    entries: usize,
+    hash: Hash,
+}
+
+impl RangeDigest {
+    fn new() -> Self {
+        Self {
+            entries: 0,
+            hash: Hash::zero(),
+        }
+    }
+
+    fn update(&mut self, key: &[u8], value: &[u8]) {
+        let mut lengths = [0; 16];
+        LittleEndian::write_u64(&mut lengths[..8], key.len() as u64);
+        LittleEndian::write_u64(&mut lengths[8..], value.len() as u64);
+        self.hash = HashStream::new()
+            .update(self.hash.as_ref())
+            .update(&lengths)
+            .update(key)
+            .update(value)
+            .hash();
+        self.entries += 1;
+    }
+}
+
+/// Digests of a key range and of its subranges split by the next byte of the keys.
+/// The key equal to the range prefix, if any, corresponds to the `None` subrange.
+#[derive(Debug)]
+struct RangeDigests {
+    total: RangeDigest,
+    subranges: BTreeMap<Option<u8>, RangeDigest>,
+}
+
+impl RangeDigests {
+    fn compute(snapshot: &dyn Snapshot, name: &str, prefix: &[u8]) -> Self {
+        let mut total = RangeDigest::new();
+        let mut subranges = BTreeMap::new();
+        let mut iter = snapshot.iter(name, prefix);
+        while let Some((key, value)) = iter.next() {
+            if !key.starts_with(prefix) {
+                break;
+            }
+            total.update(key, value);
+            subranges
+                .entry(key.get(prefix.len()).cloned())
+                .or_insert_with(RangeDigest::new)
+                .update(key, value);
+        }
+        Self { total, subranges }
+    }
+
+    fn subrange(&self, byte: Option<u8>) -> RangeDigest {
+        self.subranges
+            .get(&byte)
+            .cloned()
+            .unwrap_or_else(RangeDigest::new)
+    }
+}
+
+/// Returns the names of the tables registered in the indexes metadata of the snapshot,
+/// including the metadata table itself.
+pub fn table_names(snapshot: &dyn Snapshot) -> Vec<String> {
+    let mut names = vec![INDEXES_METADATA_TABLE_NAME.to_owned()];
+    let mut iter = snapshot.iter(INDEXES_METADATA_TABLE_NAME, &[]);
+    while let Some((key, _)) = iter.next() {
+        match String::from_utf8(key.to_vec()) {
+            Ok(ref name) if name == CORE_STORAGE_METADATA_KEY => {}
+            Ok(name) => names.push(name),
+            Err(_) => warn!("Skipping index with invalid name {:?}", key),
+        }
+    }
+    names
+}
+
+/// Compares the table with the given name in two snapshots and returns the differing keys
+/// in the increasing order.
+pub fn diff_table(first: &dyn Snapshot, second: &dyn Snapshot, name: &str) -> Vec<KeyDiff> {
+    let mut diffs = Vec::new();
+    if !merkle_roots_equal(first, second, name) {
+        diff_range(first, second, name, &[], &mut diffs);
+    }
+    diffs
+}
+
+/// Checks whether the table is Merkelized in both snapshots and has equal Merkle roots.
+/// The roots are read from the stored tree nodes without iterating over the table.
+fn merkle_roots_equal(first: &dyn Snapshot, second: &dyn Snapshot, name: &str) -> bool {
+    let metadata = |snapshot: &dyn Snapshot| {
+        BaseIndex::indexes_metadata(snapshot).get::<_, IndexMetadata>(name)
+    };
+    let index_type = match (metadata(first), metadata(second)) {
+        // The roots of the indexes within a family are not distinguishable
+        // from the other entries of the table.
+        (Some(ref a), Some(ref b)) if a == b && !a.is_family() => a.index_type(),
+        _ => return false,
+    };
+    match index_type {
+        IndexType::ProofList => {
+            let first: ProofListIndex<_, Vec<u8>> = ProofListIndex::new(name, first);
+            let second: ProofListIndex<_, Vec<u8>> = ProofListIndex::new(name, second);
+            first.len() == second.len() && first.merkle_root() == second.merkle_root()
+        }
+        IndexType::ProofMap => {
+            let first: ProofMapIndex<_, Hash, Vec<u8>> = ProofMapIndex::new(name, first);
+            let second: ProofMapIndex<_, Hash, Vec<u8>> = ProofMapIndex::new(name, second);
+            first.merkle_root() == second.merkle_root()
+        }
+        _ => false,
+    }
+}
+
+/// Compares all the tables of two snapshots and returns the differences of the tables
+/// which contents are not equal.
+pub fn diff_snapshots(first: &dyn Snapshot, second: &dyn Snapshot) -> Vec<TableDiff> {
+    let names: BTreeSet<String> = table_names(first)
+        .into_iter()
+        .chain(table_names(second))
+        .collect();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let keys = diff_table(first, second, &name);
+            if keys.is_empty() {
+                None
+            } else {
+                Some(TableDiff { name, keys })
+            }
+        }).collect()
+}
+
+fn diff_range(
+    first: &dyn Snapshot,
+    second: &dyn Snapshot,
+    name: &str,
+    prefix: &[u8],
+    diffs: &mut Vec<KeyDiff>,
+) {
+    let first_digests = RangeDigests::compute(first, name, prefix);
+    let second_digests = RangeDigests::compute(second, name, prefix);
+    if first_digests.total == second_digests.total {
+        return;
+    }
+    if first_digests.total.entries <= LEAF_RANGE_SIZE
+        && second_digests.total.entries <= LEAF_RANGE_SIZE
+    {
+        diff_entries(first, second, name, prefix, diffs);
+        return;
+    }
+
+    let subranges: BTreeSet<Option<u8>> = first_digests
+        .subranges
+        .keys()
+        .chain(second_digests.subranges.keys())
+        .cloned()
+        .collect();
+    for byte in subranges {
+        if first_digests.subrange(byte) == second_digests.subrange(byte) {
+            continue;
+        }
+        match byte {
+            Some(byte) => {
+                let mut subprefix = prefix.to_vec();
+                subprefix.push(byte);
+                diff_range(first, second, name, &subprefix, diffs);
+            }
+            None => diff_key(first, second, name, prefix, diffs),
+        }
+    }
+}
+
+/// Compares the entries with the given key prefix one by one.
+fn diff_entries(
+    first: &dyn Snapshot,
+    second: &dyn Snapshot,
+    name: &str,
+    prefix: &[u8],
+    diffs: &mut Vec<KeyDiff>,
+) {
+    let collect = |snapshot: &dyn Snapshot| {
+        let mut entries = BTreeMap::new();
+        let mut iter = snapshot.iter(name, prefix);
+        while let Some((key, value)) = iter.next() {
+            if !key.starts_with(prefix) {
+                break;
+            }
+            entries.insert(key.to_vec(), value.to_vec());
+        }
+        entries
+    };
+    let first_entries = collect(first);
+    let second_entries = collect(second);
+
+    let keys: BTreeSet<&Vec<u8>> = first_entries.keys().chain(second_entries.keys()).collect();
+    for key in keys {
+        match (first_entries.get(key), second_entries.get(key)) {
+            (Some(a), Some(b)) if a == b => {}
+            (Some(_), Some(_)) => diffs.push(KeyDiff::Changed(key.clone())),
+            (Some(_), None) => diffs.push(KeyDiff::OnlyInFirst(key.clone())),
+            (None, _) => diffs.push(KeyDiff::OnlyInSecond(key.clone())),
+        }
+    }
+}
+
+/// Compares the values stored by the given key.
+fn diff_key(
+    first: &dyn Snapshot,
+    second: &dyn Snapshot,
+    name: &str,
+    key: &[u8],
+    diffs: &mut Vec<KeyDiff>,
+) {
+    match (first.get(name, key), second.get(name, key)) {
+        (Some(ref a), Some(ref b)) if a == b => {}
+        (Some(_), Some(_)) => diffs.push(KeyDiff::Changed(key.to_vec())),
+        (Some(_), None) => diffs.push(KeyDiff::OnlyInFirst(key.to_vec())),
+        (None, Some(_)) => diffs.push(KeyDiff::OnlyInSecond(key.to_vec())),
+        (None, None) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use byteorder::{BigEndian, ByteOrder};
+
+    use super::{diff_snapshots, diff_table, KeyDiff, LEAF_RANGE_SIZE};
+    use storage::{Database, MapIndex, MemoryDB, ProofListIndex};
+
+    fn fill(db: &MemoryDB, entries: &[(u64, u64)]) {
+        let mut fork = db.fork();
+        {
+            let mut index: MapIndex<_, u64, u64> = MapIndex::new("index", &mut fork);
+            for &(key, value) in entries {
+                index.put(&key, value);
+            }
+        }
+        db.merge(fork.into_patch()).unwrap();
+    }
+
+    #[test]
+    fn diff_equal_databases() {
+        let entries: Vec<_> = (0..1000).map(|i| (i, i * 2)).collect();
+        let (first, second) = (MemoryDB::new(), MemoryDB::new());
+        fill(&first, &entries);
+        fill(&second, &entries);
+        assert!(diff_snapshots(&*first.snapshot(), &*second.snapshot()).is_empty());
+    }
+
+    #[test]
+    fn diff_divergent_databases() {
+        let count = LEAF_RANGE_SIZE as u64 * 100;
+        let entries: Vec<_> = (0..count).map(|i| (i, i * 2)).collect();
+        let (first, second) = (MemoryDB::new(), MemoryDB::new());
+        fill(&first, &entries);
+        fill(&second, &entries);
+        fill(&first, &[(5, 0), (count, 0)]);
+        fill(&second, &[(count + 1, 0)]);
+
+        let key = |i: u64| {
+            let mut key = vec![0; 8];
+            BigEndian::write_u64(&mut key, i);
+            key
+        };
+        let diff = diff_table(&*first.snapshot(), &*second.snapshot(), "index");
+        assert_eq!(
+            diff,
+            vec![
+                KeyDiff::Changed(key(5)),
+                KeyDiff::OnlyInFirst(key(count)),
+                KeyDiff::OnlyInSecond(key(count + 1)),
+            ]
+        );
+
+        let tables = diff_snapshots(&*first.snapshot(), &*second.snapshot());
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].name, "index");
+        assert_eq!(tables[0].keys, diff);
+    }
+    #[test]
+    fn diff_merkelized_tables_by_roots() {
+        let fill_list = |db: &MemoryDB, values: &[u64]| {
+            let mut fork = db.fork();
+            {
+                let mut index = ProofListIndex::new("list", &mut fork);
+                index.extend(values.iter().cloned());
+            }
+            db.merge(fork.into_patch()).unwrap();
+        };
+        let (first, second) = (MemoryDB::new(), MemoryDB::new());
+        fill_list(&first, &[1, 2, 3]);
+        fill_list(&second, &[1, 2, 3]);
+        // An entry outside of the Merkle tree is not examined if the roots are equal.
+        let mut fork = second.fork();
+        fork.put("list", vec![0xff; 8], vec![0]);
+        second.merge(fork.into_patch()).unwrap();
+        assert!(diff_snapshots(&*first.snapshot(), &*second.snapshot()).is_empty());
+
+        fill_list(&second, &[4]);
+        let diff = diff_table(&*first.snapshot(), &*second.snapshot(), "list");
+        assert!(diff.contains(&KeyDiff::OnlyInSecond(vec![0xff; 8])));
+        assert!(diff.contains(&KeyDiff::Changed(vec![])));
+    }
+}
@@ -31,7 +31,7 @@ pub const INDEXES_METADATA_TABLE_NAME: &str = "__INDEXES_METADATA__";
 // Value of this constant is to be changed manually
 // upon the introduction of breaking changes to the storage.
 const CORE_STORAGE_METADATA: StorageMetadata = StorageMetadata { version: 0 };
-const CORE_STORAGE_METADATA_KEY: &str = "__STORAGE_METADATA__";
+pub(crate) const CORE_STORAGE_METADATA_KEY: &str = "__STORAGE_METADATA__";
 
 encoding_struct! {
     struct IndexMetadata {
@@ -40,17 +40,28 @@ encoding_struct! {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// Type of an index recorded in the indexes metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum IndexType {
+    /// [`Entry`](struct.Entry.html).
     Entry,
+    /// [`KeySetIndex`](key_set_index/struct.KeySetIndex.html).
     KeySet,
+    /// [`ListIndex`](list_index/struct.ListIndex.html).
     List,
+    /// [`SparseListIndex`](sparse_list_index/struct.SparseListIndex.html).
     SparseList,
+    /// [`MapIndex`](map_index/struct.MapIndex.html).
     Map,
+    /// [`ProofListIndex`](proof_list_index/struct.ProofListIndex.html).
     ProofList,
+    /// [`ProofMapIndex`](proof_map_index/struct.ProofMapIndex.html).
     ProofMap,
+    /// [`ValueSetIndex`](value_set_index/struct.ValueSetIndex.html).
     ValueSet,
+    /// [`SecondaryIndex`](secondary_index/struct.SecondaryIndex.html).
+    Secondary,
 }
 
 impl From<u8> for IndexType {
@@ -65,6 +76,7 @@ impl From<u8> for IndexType {
             5 => ProofList,
             6 => ProofMap,
             7 => ValueSet,
+            8 => Secondary,
             invalid => panic!(
                 "Unreachable pattern ({:?}) while constructing table type. \
                  Storage data is probably corrupted",
@@ -236,6 +248,27 @@ pub fn set_index_type(name: &str, index_type: IndexType, is_family: bool, view:
     }
 }
 
+/// Checks that each of the given indexes is registered in the indexes metadata.
+pub(crate) fn check_indexes_registered<S: AsRef<str>>(
+    names: &[S],
+    view: &dyn Snapshot,
+) -> Result<(), super::Error> {
+    let metadata = BaseIndex::indexes_metadata(view);
+    for name in names {
+        let name = name.as_ref();
+        if name == INDEXES_METADATA_TABLE_NAME {
+            continue;
+        }
+        if metadata.get::<_, IndexMetadata>(name).is_none() {
+            return Err(super::Error::new(format!(
+                "Index '{}' is absent in the indexes metadata",
+                name
+            )));
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
@@ -250,9 +283,9 @@ mod tests {
         use self::IndexType::*;
 
         let index_types = [
-            Entry, KeySet, List, SparseList, Map, ProofList, ProofMap, ValueSet,
+            Entry, KeySet, List, SparseList, Map, ProofList, ProofMap, ValueSet, Secondary,
         ];
-        let is_family = [true, true, false, false, true, false, true, false];
+        let is_family = [true, true, false, false, true, false, true, false, true];
         for (t, f) in index_types.iter().zip(&is_family) {
             let metadata = IndexMetadata::new(*t, *f);
             assert_eq!(metadata.index_type(), *t);
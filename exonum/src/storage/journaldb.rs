@@ -0,0 +1,288 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An implementation of `JournalDB` database.
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use super::{db::Change, Database, DbOptions, Error, MemoryDB, Patch, Result, Snapshot};
+use crypto::{self, HASH_SIZE};
+
+/// Name of the journal file in the database directory.
+const JOURNAL_FILE: &str = "journal";
+/// Name of the temporary file used during the journal compaction.
+const COMPACTED_JOURNAL_FILE: &str = "journal.compacted";
+
+const PUT_TAG: u8 = 0;
+const DELETE_TAG: u8 = 1;
+
+/// Persistent database implementation written in pure Rust.
+///
+/// `JournalDB` keeps all the data in RAM and appends every merged patch to the journal file
+/// before applying it. When the database is opened, the journal is replayed and compacted
+/// into a single record. The last record is discarded if it was not written completely
+/// (e.g., because of a crash); any other corrupted record makes opening the database fail,
+/// and the journal is left intact.
+///
+/// Unlike `RocksDB`, this database does not depend on native libraries, which allows using it
+/// on the platforms where `RocksDB` cannot be built, for example, in static `musl` binaries
+/// for `aarch64`. Since the whole state is kept in memory, the database is suitable for
+/// the blockchains of moderate size.
+#[derive(Debug)]
+pub struct JournalDB {
+    db: MemoryDB,
+    journal: Mutex<File>,
+}
+
+impl JournalDB {
+    /// Opens a database stored at the specified directory with the specified options.
+    ///
+    /// If the database does not exist at the indicated path and the option
+    /// `create_if_missing` is switched on in `DbOptions`, a new database will
+    /// be created at the indicated path.
+    pub fn open<P: AsRef<Path>>(path: P, options: &DbOptions) -> Result<Self> {
+        let path = path.as_ref();
+        let journal_path = path.join(JOURNAL_FILE);
+        if !journal_path.exists() {
+            if !options.create_if_missing {
+                return Err(Error::new(format!(
+                    "Database does not exist at {}",
+                    path.display()
+                )));
+            }
+            fs::create_dir_all(path)?;
+        }
+
+        let db = MemoryDB::new();
+        if journal_path.exists() {
+            let journal = fs::read(&journal_path)?;
+            Self::replay(&db, &journal)?;
+        }
+
+        // Compact the journal, so that it contains a single record with the current state.
+        let compacted_path = path.join(COMPACTED_JOURNAL_FILE);
+        {
+            let mut compacted = File::create(&compacted_path)?;
+            compacted.write_all(&db.state_record())?;
+            compacted.sync_all()?;
+        }
+        fs::rename(&compacted_path, &journal_path)?;
+
+        let journal = OpenOptions::new().append(true).open(&journal_path)?;
+        Ok(Self {
+            db,
+            journal: Mutex::new(journal),
+        })
+    }
+
+    fn do_merge(&self, patch: Patch, sync: bool) -> Result<()> {
+        let record = Self::patch_record(&patch);
+        let mut journal = self.journal.lock().expect("Expected mutex lock");
+        journal.write_all(&record)?;
+        if sync {
+            journal.sync_data()?;
+        }
+        // The lock is held until the patch is applied to keep the order of changes
+        // the same as in the journal.
+        self.db.apply(patch);
+        Ok(())
+    }
+
+    pub(super) fn replay(db: &MemoryDB, journal: &[u8]) -> Result<()> {
+        let mut offset = 0;
+        while offset < journal.len() {
+            let data = &journal[offset..];
+            match Self::read_record(data) {
+                Record::Complete(body) => {
+                    db.apply(Self::decode_body(body)?);
+                    offset += 4 + body.len() + HASH_SIZE;
+                }
+                Record::Corrupted(record_len) if record_len < data.len() => {
+                    return Err(Error::new(format!(
+                        "Corrupted record in the database journal, offset {}",
+                        offset
+                    )));
+                }
+                Record::Incomplete | Record::Corrupted(..) => {
+                    warn!(
+                        "Discarding incomplete record at the end of the database journal, \
+                         offset {}",
+                        offset
+                    );
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the record at the beginning of `data`.
+    fn read_record(data: &[u8]) -> Record {
+        if data.len() < 4 {
+            return Record::Incomplete;
+        }
+        let body_len = LittleEndian::read_u32(&data[..4]) as usize;
+        let record_len = 4 + body_len + HASH_SIZE;
+        if data.len() < record_len {
+            return Record::Incomplete;
+        }
+        let body = &data[4..4 + body_len];
+        if crypto::hash(body).as_ref() == &data[4 + body_len..record_len] {
+            Record::Complete(body)
+        } else {
+            Record::Corrupted(record_len)
+        }
+    }
+
+    fn decode_body(mut body: &[u8]) -> Result<Vec<(String, Vec<(Vec<u8>, Change)>)>> {
+        let cf_count = read_u32(&mut body)?;
+        let mut changes = Vec::with_capacity(cf_count as usize);
+        for _ in 0..cf_count {
+            let cf_name = String::from_utf8(read_bytes(&mut body)?.to_vec())
+                .map_err(|e| Error::new(format!("Invalid column family name: {}", e)))?;
+            let changes_count = read_u32(&mut body)?;
+            let mut cf_changes = Vec::with_capacity(changes_count as usize);
+            for _ in 0..changes_count {
+                let tag = read_bytes_exact(&mut body, 1)?[0];
+                let key = read_bytes(&mut body)?.to_vec();
+                let change = match tag {
+                    PUT_TAG => Change::Put(read_bytes(&mut body)?.to_vec()),
+                    DELETE_TAG => Change::Delete,
+                    _ => return Err(Error::new(format!("Unknown change tag: {}", tag))),
+                };
+                cf_changes.push((key, change));
+            }
+            changes.push((cf_name, cf_changes));
+        }
+        Ok(changes)
+    }
+
+    fn patch_record(patch: &Patch) -> Vec<u8> {
+        let mut body = Vec::new();
+        write_u32(&mut body, patch.iter().count());
+        for (cf_name, changes) in patch.iter() {
+            write_bytes(&mut body, cf_name.as_bytes());
+            write_u32(&mut body, changes.iter().count());
+            for (key, change) in changes.iter() {
+                match *change {
+                    Change::Put(ref value) => {
+                        body.push(PUT_TAG);
+                        write_bytes(&mut body, key);
+                        write_bytes(&mut body, value);
+                    }
+                    Change::Delete => {
+                        body.push(DELETE_TAG);
+                        write_bytes(&mut body, key);
+                    }
+                }
+            }
+        }
+        Self::record(&body)
+    }
+
+    pub(super) fn state_record(tables: &HashMap<String, BTreeMap<Vec<u8>, Vec<u8>>>) -> Vec<u8> {
+        let mut body = Vec::new();
+        write_u32(&mut body, tables.len());
+        for (cf_name, table) in tables {
+            write_bytes(&mut body, cf_name.as_bytes());
+            write_u32(&mut body, table.len());
+            for (key, value) in table {
+                body.push(PUT_TAG);
+                write_bytes(&mut body, key);
+                write_bytes(&mut body, value);
+            }
+        }
+        Self::record(&body)
+    }
+
+    fn record(body: &[u8]) -> Vec<u8> {
+        let mut record = Vec::with_capacity(4 + body.len() + HASH_SIZE);
+        write_u32(&mut record, body.len());
+        record.extend_from_slice(body);
+        record.extend_from_slice(crypto::hash(body).as_ref());
+        record
+    }
+}
+
+/// Record of the journal.
+enum Record<'a> {
+    /// Body of the record with the matching checksum.
+    Complete(&'a [u8]),
+    /// The data ends before the end of the record.
+    Incomplete,
+    /// The checksum does not match the body. Contains the full length of the record.
+    Corrupted(usize),
+}
+
+impl Database for JournalDB {
+    fn snapshot(&self) -> Box<dyn Snapshot> {
+        self.db.snapshot()
+    }
+
+    fn merge(&self, patch: Patch) -> Result<()> {
+        self.do_merge(patch, false)
+    }
+
+    fn merge_sync(&self, patch: Patch) -> Result<()> {
+        self.do_merge(patch, true)
+    }
+
+    fn flush(&self) -> Result<()> {
+        let journal = self.journal.lock().expect("Expected mutex lock");
+        journal.sync_data().map_err(Into::into)
+    }
+}
+
+impl From<JournalDB> for Arc<dyn Database> {
+    fn from(db: JournalDB) -> Self {
+        Self::from(Box::new(db) as Box<dyn Database>)
+    }
+}
+
+fn write_u32(buffer: &mut Vec<u8>, value: usize) {
+    let mut bytes = [0; 4];
+    LittleEndian::write_u32(&mut bytes, value as u32);
+    buffer.extend_from_slice(&bytes);
+}
+
+fn write_bytes(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    write_u32(buffer, bytes.len());
+    buffer.extend_from_slice(bytes);
+}
+
+fn read_bytes_exact<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if data.len() < len {
+        return Err(Error::new("Unexpected end of the journal record"));
+    }
+    let (bytes, rest) = data.split_at(len);
+    *data = rest;
+    Ok(bytes)
+}
+
+fn read_u32(data: &mut &[u8]) -> Result<u32> {
+    read_bytes_exact(data, 4).map(LittleEndian::read_u32)
+}
+
+fn read_bytes<'a>(data: &mut &'a [u8]) -> Result<&'a [u8]> {
+    let len = read_u32(data)? as usize;
+    read_bytes_exact(data, len)
+}
@@ -17,7 +17,7 @@
 //! The given section contains methods related to `ListIndex` and the iterator
 //! over the items of this list.
 
-use std::{cell::Cell, marker::PhantomData};
+use std::{cell::Cell, cmp, marker::PhantomData};
 
 use super::{
     base_index::{BaseIndex, BaseIndexIter},
@@ -43,15 +43,20 @@ pub struct ListIndex<T, V> {
 
 /// Returns an iterator over the items of a `ListIndex`.
 ///
-/// This struct is created by the [`iter`] or
-/// [`iter_from`] method on [`ListIndex`]. See its documentation for details.
+/// This struct is created by the [`iter`], [`iter_from`], [`iter_rev`] or
+/// [`iter_rev_from`] method on [`ListIndex`]. See its documentation for details.
 ///
 /// [`iter`]: struct.ListIndex.html#method.iter
 /// [`iter_from`]: struct.ListIndex.html#method.iter_from
+/// [`iter_rev`]: struct.ListIndex.html#method.iter_rev
+/// [`iter_rev_from`]: struct.ListIndex.html#method.iter_rev_from
 /// [`ListIndex`]: struct.ListIndex.html
 #[derive(Debug)]
 pub struct ListIndexIter<'a, V> {
     base_iter: BaseIndexIter<'a, u64, V>,
+    // The length of the list is stored under the least key of the index, so the reverse
+    // iteration is limited by the number of the elements.
+    remaining: u64,
 }
 
 impl<T, V> ListIndex<T, V>
@@ -232,6 +237,7 @@ where
     pub fn iter(&self) -> ListIndexIter<V> {
         ListIndexIter {
             base_iter: self.base.iter_from(&(), &0_u64),
+            remaining: u64::max_value(),
         }
     }
 
@@ -257,6 +263,50 @@ where
     pub fn iter_from(&self, from: u64) -> ListIndexIter<V> {
         ListIndexIter {
             base_iter: self.base.iter_from(&(), &from),
+            remaining: u64::max_value(),
+        }
+    }
+
+    /// Returns an iterator over the list in reverse order. The iterator element type is V.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, ListIndex};
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let mut fork = db.fork();
+    /// let mut index = ListIndex::new(name, &mut fork);
+    ///
+    /// index.extend([1, 2, 3].iter().cloned());
+    /// assert_eq!(index.iter_rev().collect::<Vec<_>>(), vec![3, 2, 1]);
+    /// ```
+    pub fn iter_rev(&self) -> ListIndexIter<V> {
+        self.iter_rev_from(u64::max_value())
+    }
+
+    /// Returns an iterator over the list in reverse order starting from the specified position.
+    /// The iterator element type is V.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, ListIndex};
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let mut fork = db.fork();
+    /// let mut index = ListIndex::new(name, &mut fork);
+    ///
+    /// index.extend([1, 2, 3, 4, 5].iter().cloned());
+    /// assert_eq!(index.iter_rev_from(2).collect::<Vec<_>>(), vec![3, 2, 1]);
+    /// ```
+    pub fn iter_rev_from(&self, from: u64) -> ListIndexIter<V> {
+        let remaining = cmp::min(from.saturating_add(1), self.len());
+        ListIndexIter {
+            base_iter: self.base.iter_rev_from(&(), &remaining.saturating_sub(1)),
+            remaining,
         }
     }
 }
@@ -461,6 +511,10 @@ where
     type Item = V;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
         self.base_iter.next().map(|(.., v)| v)
     }
 }
@@ -528,6 +582,17 @@ mod tests {
             list_index.iter_from(3).collect::<Vec<u8>>(),
             Vec::<u8>::new()
         );
+
+        assert_eq!(list_index.iter_rev().collect::<Vec<u8>>(), vec![3, 2, 1]);
+        assert_eq!(list_index.iter_rev_from(1).collect::<Vec<u8>>(), vec![2, 1]);
+        assert_eq!(list_index.iter_rev_from(0).collect::<Vec<u8>>(), vec![1]);
+        assert_eq!(
+            list_index.iter_rev_from(10).collect::<Vec<u8>>(),
+            vec![3, 2, 1]
+        );
+
+        list_index.clear();
+        assert_eq!(list_index.iter_rev().collect::<Vec<u8>>(), Vec::<u8>::new());
     }
 
     mod memorydb_tests {
@@ -582,6 +647,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "rocksdb")]
     mod rocksdb_tests {
         use std::path::Path;
         use storage::{Database, DbOptions, ListIndex, RocksDB};
@@ -42,11 +42,13 @@ pub struct MapIndex<T, K, V> {
 
 /// Returns an iterator over the entries of a `MapIndex`.
 ///
-/// This struct is created by the [`iter`] or
-/// [`iter_from`] method on [`MapIndex`]. See its documentation for additional details.
+/// This struct is created by the [`iter`], [`iter_from`], [`iter_rev`] or
+/// [`iter_rev_from`] method on [`MapIndex`]. See its documentation for additional details.
 ///
 /// [`iter`]: struct.MapIndex.html#method.iter
 /// [`iter_from`]: struct.MapIndex.html#method.iter_from
+/// [`iter_rev`]: struct.MapIndex.html#method.iter_rev
+/// [`iter_rev_from`]: struct.MapIndex.html#method.iter_rev_from
 /// [`MapIndex`]: struct.MapIndex.html
 #[derive(Debug)]
 pub struct MapIndexIter<'a, K, V> {
@@ -289,6 +291,57 @@ where
         }
     }
 
+    /// Returns an iterator over the entries of a map in descending order. The iterator element
+    /// type is (K, V).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, MapIndex};
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let mut fork = db.fork();
+    /// let mut index = MapIndex::new(name, &mut fork);
+    /// index.put(&1_u8, 10_u8);
+    /// index.put(&2_u8, 20_u8);
+    ///
+    /// let entries: Vec<_> = index.iter_rev().collect();
+    /// assert_eq!(entries, vec![(2, 20), (1, 10)]);
+    /// ```
+    pub fn iter_rev(&self) -> MapIndexIter<K, V> {
+        MapIndexIter {
+            base_iter: self.base.iter_rev(&()),
+        }
+    }
+
+    /// Returns an iterator over the entries of a map in descending order starting from the
+    /// specified key. The iterator element type is (K, V).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, MapIndex};
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let snapshot = db.snapshot();
+    /// let index: MapIndex<_, u8, u8> = MapIndex::new(name, &snapshot);
+    ///
+    /// for v in index.iter_rev_from(&2) {
+    ///     println!("{:?}", v);
+    /// }
+    /// ```
+    pub fn iter_rev_from<Q>(&self, from: &Q) -> MapIndexIter<K, V>
+    where
+        K: Borrow<Q>,
+        Q: StorageKey + ?Sized,
+    {
+        MapIndexIter {
+            base_iter: self.base.iter_rev_from(&(), from),
+        }
+    }
+
     /// Returns an iterator over the keys of a map in ascending order starting from the
     /// specified key. The iterator element type is K.
     ///
@@ -600,6 +653,72 @@ mod tests {
         );
     }
 
+    fn iter_rev(db: Box<dyn Database>) {
+        let mut fork = db.fork();
+        {
+            let mut map_index = MapIndex::new(IDX_NAME, &mut fork);
+            for i in 1u8..6 {
+                map_index.put(&i, i);
+            }
+        }
+        db.merge(fork.into_patch()).unwrap();
+
+        // Reverse iteration merges the changes of the fork with the stored entries.
+        let mut fork = db.fork();
+        let mut map_index = MapIndex::new(IDX_NAME, &mut fork);
+        map_index.remove(&2u8);
+        map_index.put(&4u8, 40u8);
+        map_index.put(&6u8, 6u8);
+
+        assert_eq!(
+            map_index.iter_rev().collect::<Vec<(u8, u8)>>(),
+            vec![(6, 6), (5, 5), (4, 40), (3, 3), (1, 1)]
+        );
+        assert_eq!(
+            map_index.iter_rev_from(&10).collect::<Vec<(u8, u8)>>(),
+            vec![(6, 6), (5, 5), (4, 40), (3, 3), (1, 1)]
+        );
+        assert_eq!(
+            map_index.iter_rev_from(&4).collect::<Vec<(u8, u8)>>(),
+            vec![(4, 40), (3, 3), (1, 1)]
+        );
+        assert_eq!(
+            map_index.iter_rev_from(&2).collect::<Vec<(u8, u8)>>(),
+            vec![(1, 1)]
+        );
+        assert_eq!(
+            map_index.iter_rev_from(&0).collect::<Vec<(u8, u8)>>(),
+            Vec::<(u8, u8)>::new()
+        );
+    }
+
+    fn iter_rev_in_family(db: Box<dyn Database>) {
+        let mut fork = db.fork();
+        for id in 0u8..3 {
+            let mut map_index = MapIndex::new_in_family(IDX_NAME, &id, &mut fork);
+            map_index.put(&1u8, id);
+            map_index.put(&2u8, id);
+        }
+
+        let map_index: MapIndex<_, u8, u8> = MapIndex::new_in_family(IDX_NAME, &1u8, &fork);
+        assert_eq!(
+            map_index.iter_rev().collect::<Vec<(u8, u8)>>(),
+            vec![(2, 1), (1, 1)]
+        );
+        assert_eq!(
+            map_index.iter_rev_from(&1).collect::<Vec<(u8, u8)>>(),
+            vec![(1, 1)]
+        );
+
+        db.merge(fork.into_patch()).unwrap();
+        let snapshot = db.snapshot();
+        let map_index: MapIndex<_, u8, u8> = MapIndex::new_in_family(IDX_NAME, &2u8, &snapshot);
+        assert_eq!(
+            map_index.iter_rev().collect::<Vec<(u8, u8)>>(),
+            vec![(2, 2), (1, 2)]
+        );
+    }
+
     fn gen_tempdir_name() -> String {
         thread_rng().sample_iter(&Alphanumeric).take(10).collect()
     }
@@ -628,8 +747,25 @@ mod tests {
             let db = create_database(path);
             super::iter(db);
         }
+
+        #[test]
+        fn test_iter_rev() {
+            let dir = TempDir::new(super::gen_tempdir_name().as_str()).unwrap();
+            let path = dir.path();
+            let db = create_database(path);
+            super::iter_rev(db);
+        }
+
+        #[test]
+        fn test_iter_rev_in_family() {
+            let dir = TempDir::new(super::gen_tempdir_name().as_str()).unwrap();
+            let path = dir.path();
+            let db = create_database(path);
+            super::iter_rev_in_family(db);
+        }
     }
 
+    #[cfg(feature = "rocksdb")]
     mod rocksdb_tests {
         use std::path::Path;
         use storage::Database;
@@ -656,5 +792,21 @@ mod tests {
             let db = create_database(path);
             super::iter(db);
         }
+
+        #[test]
+        fn test_iter_rev() {
+            let dir = TempDir::new(super::gen_tempdir_name().as_str()).unwrap();
+            let path = dir.path();
+            let db = create_database(path);
+            super::iter_rev(db);
+        }
+
+        #[test]
+        fn test_iter_rev_in_family() {
+            let dir = TempDir::new(super::gen_tempdir_name().as_str()).unwrap();
+            let path = dir.path();
+            let db = create_database(path);
+            super::iter_rev_in_family(db);
+        }
     }
 }
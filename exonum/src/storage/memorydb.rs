@@ -16,21 +16,69 @@
 
 use std::{
     clone::Clone,
-    collections::{BTreeMap, HashMap},
-    sync::{Arc, RwLock},
+    collections::{
+        BTreeMap,
+        Bound::{self, Unbounded},
+        HashMap,
+    },
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
 };
 
-use super::{db::Change, Database, Iter, Iterator, Patch, Result, Snapshot};
+use super::{db::Change, Database, Iter, Iterator, JournalDB, Patch, Result, Snapshot};
 
 type DB = HashMap<String, BTreeMap<Vec<u8>, Vec<u8>>>;
+/// Previous values of the changed entries, used to revert the changes.
+type UndoLog = Vec<(String, Vec<u8>, Option<Vec<u8>>)>;
 
 /// Database implementation that stores all the data in RAM.
 ///
 /// This database is only used for testing and experimenting; is not designed to
 /// operate under load in production.
+///
+/// The database can be backed by a file (see [`open`]), which gives small deployments
+/// durability without a persistent database. The whole state of the database is saved to
+/// the file on [`flush`] and, optionally, periodically when patches are merged.
+///
+/// [`open`]: #method.open
+/// [`flush`]: trait.Database.html#method.flush
 #[derive(Default, Debug)]
 pub struct MemoryDB {
     map: RwLock<DB>,
+    persistence: Option<Persistence>,
+}
+
+/// File the state of `MemoryDB` is saved to.
+#[derive(Debug)]
+struct Persistence {
+    path: PathBuf,
+    flush_interval: Option<Duration>,
+    last_flush: Mutex<Instant>,
+}
+
+impl Persistence {
+    fn is_flush_due(&self) -> bool {
+        let last_flush = self.last_flush.lock().expect("Expected mutex lock");
+        self.flush_interval
+            .map_or(false, |interval| last_flush.elapsed() >= interval)
+    }
+
+    fn save(&self, map: &DB) -> Result<()> {
+        let mut last_flush = self.last_flush.lock().expect("Expected mutex lock");
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        {
+            let mut file = File::create(&tmp_path)?;
+            file.write_all(&JournalDB::state_record(map))?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp_path, &self.path)?;
+        *last_flush = Instant::now();
+        Ok(())
+    }
 }
 
 /// An iterator over the entries of a `MemoryDB`.
@@ -44,28 +92,59 @@ impl MemoryDB {
     pub fn new() -> Self {
         Self {
             map: RwLock::new(HashMap::new()),
+            persistence: None,
         }
     }
-}
 
-impl Database for MemoryDB {
-    fn snapshot(&self) -> Box<dyn Snapshot> {
-        Box::new(Self {
-            map: RwLock::new(self.map.read().unwrap().clone()),
+    /// Opens a database backed by the specified file. The state of the database is loaded
+    /// from the file if it exists; otherwise, an empty database is created.
+    ///
+    /// The state is saved to the file only on [`flush`] unless [`with_flush_interval`]
+    /// is used.
+    ///
+    /// [`flush`]: trait.Database.html#method.flush
+    /// [`with_flush_interval`]: #method.with_flush_interval
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let db = Self::new();
+        if path.exists() {
+            let data = fs::read(&path)?;
+            JournalDB::replay(&db, &data)?;
+        }
+        Ok(Self {
+            persistence: Some(Persistence {
+                path,
+                flush_interval: None,
+                last_flush: Mutex::new(Instant::now()),
+            }),
+            ..db
         })
     }
 
-    fn merge(&self, patch: Patch) -> Result<()> {
+    /// Enables periodic saving of the database state to the backing file: the state is saved
+    /// when a patch is merged if more than `interval` has passed since the previous save.
+    ///
+    /// Has no effect if the database is not backed by a file.
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        if let Some(ref mut persistence) = self.persistence {
+            persistence.flush_interval = Some(interval);
+        }
+        self
+    }
+
+    /// Applies changes grouped by the column families to the database.
+    pub(super) fn apply<I, C>(&self, changes: I)
+    where
+        I: IntoIterator<Item = (String, C)>,
+        C: IntoIterator<Item = (Vec<u8>, Change)>,
+    {
         let mut guard = self.map.write().unwrap();
-        for (cf_name, changes) in patch {
-            if !guard.contains_key(&cf_name) {
-                guard.insert(cf_name.clone(), BTreeMap::new());
-            }
-            let table = guard.get_mut(&cf_name).unwrap();
+        for (cf_name, changes) in changes {
+            let table = guard.entry(cf_name).or_insert_with(BTreeMap::new);
             for (key, change) in changes {
                 match change {
-                    Change::Put(ref value) => {
-                        table.insert(key, value.to_vec());
+                    Change::Put(value) => {
+                        table.insert(key, value);
                     }
                     Change::Delete => {
                         table.remove(&key);
@@ -73,11 +152,92 @@ impl Database for MemoryDB {
                 }
             }
         }
+    }
+
+    /// Applies the patch to the map and returns the previous values of the changed entries.
+    fn apply_with_undo(map: &mut DB, patch: Patch) -> UndoLog {
+        let mut undo = Vec::new();
+        for (cf_name, changes) in patch {
+            let table = map.entry(cf_name.clone()).or_insert_with(BTreeMap::new);
+            for (key, change) in changes {
+                let previous = match change {
+                    Change::Put(value) => table.insert(key.clone(), value),
+                    Change::Delete => table.remove(&key),
+                };
+                undo.push((cf_name.clone(), key, previous));
+            }
+        }
+        undo
+    }
+
+    /// Reverts the changes recorded by `apply_with_undo`.
+    fn revert(map: &mut DB, undo: UndoLog) {
+        for (cf_name, key, previous) in undo.into_iter().rev() {
+            let table = map.entry(cf_name).or_insert_with(BTreeMap::new);
+            match previous {
+                Some(value) => {
+                    table.insert(key, value);
+                }
+                None => {
+                    table.remove(&key);
+                }
+            }
+        }
+    }
+
+    /// Returns the journal record with the whole state of the database.
+    pub(super) fn state_record(&self) -> Vec<u8> {
+        JournalDB::state_record(&self.map.read().unwrap())
+    }
+}
+
+impl Database for MemoryDB {
+    fn snapshot(&self) -> Box<dyn Snapshot> {
+        Box::new(Self {
+            map: RwLock::new(self.map.read().unwrap().clone()),
+            persistence: None,
+        })
+    }
+
+    fn merge(&self, patch: Patch) -> Result<()> {
+        self.apply(patch);
+        if let Some(ref persistence) = self.persistence {
+            if persistence.is_flush_due() {
+                // The patch is already applied, so the error is not returned to the caller.
+                if let Err(e) = persistence.save(&self.map.read().unwrap()) {
+                    error!(
+                        "Unable to save database to {}: {}",
+                        persistence.path.display(),
+                        e
+                    );
+                }
+            }
+        }
         Ok(())
     }
 
     fn merge_sync(&self, patch: Patch) -> Result<()> {
-        self.merge(patch)
+        let persistence = match self.persistence {
+            Some(ref persistence) => persistence,
+            None => return self.merge(patch),
+        };
+
+        // The changes are reverted if the state cannot be saved, so that no changes
+        // are applied in this case. The lock is held until the state is saved.
+        let mut guard = self.map.write().unwrap();
+        let undo = Self::apply_with_undo(&mut guard, patch);
+        if let Err(e) = persistence.save(&guard) {
+            Self::revert(&mut guard, undo);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<()> {
+        match self.persistence {
+            Some(ref persistence) => persistence.save(&self.map.read().unwrap()),
+            None => Ok(()),
+        }
     }
 }
 
@@ -111,6 +271,20 @@ impl Snapshot for MemoryDB {
 
         Box::new(MemoryDBIter { data, index: 0 })
     }
+
+    fn iter_rev(&self, name: &str, to: Bound<&[u8]>) -> Iter {
+        let map_guard = self.map.read().unwrap();
+        let data = match map_guard.get(name) {
+            Some(table) => table
+                .range::<[u8], _>((Unbounded, to))
+                .rev()
+                .map(|(k, v)| (k.to_vec(), v.to_vec()))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Box::new(MemoryDBIter { data, index: 0 })
+    }
 }
 
 impl Iterator for MemoryDBIter {
@@ -166,3 +340,77 @@ fn test_memorydb_snapshot() {
     let snapshot = db.snapshot();
     assert!(snapshot.contains(idx_name, vec![2, 3, 4].as_slice()));
 }
+
+#[test]
+fn test_memorydb_open() {
+    use tempdir::TempDir;
+
+    let dir = TempDir::new("exonum_memorydb_open").unwrap();
+    let path = dir.path().join("db");
+    let idx_name = "idx_name";
+    {
+        let db = MemoryDB::open(&path).unwrap();
+        let mut fork = db.fork();
+        fork.put(idx_name, vec![1, 2, 3], vec![123]);
+        db.merge(fork.into_patch()).unwrap();
+        db.flush().unwrap();
+
+        let mut fork = db.fork();
+        fork.put(idx_name, vec![2, 3, 4], vec![234]);
+        db.merge(fork.into_patch()).unwrap();
+    }
+
+    let db = MemoryDB::open(&path).unwrap();
+    let snapshot = db.snapshot();
+    assert_eq!(snapshot.get(idx_name, &[1, 2, 3]), Some(vec![123]));
+    assert!(!snapshot.contains(idx_name, &[2, 3, 4]));
+
+    let mut fork = db.fork();
+    fork.put(idx_name, vec![2, 3, 4], vec![234]);
+    db.merge_sync(fork.into_patch()).unwrap();
+
+    let db = MemoryDB::open(&path).unwrap();
+    assert!(db.snapshot().contains(idx_name, &[2, 3, 4]));
+}
+
+#[test]
+fn test_memorydb_flush_interval() {
+    use std::time::Duration;
+    use tempdir::TempDir;
+
+    let dir = TempDir::new("exonum_memorydb_flush_interval").unwrap();
+    let path = dir.path().join("db");
+    {
+        let db = MemoryDB::open(&path)
+            .unwrap()
+            .with_flush_interval(Duration::from_millis(0));
+        let mut fork = db.fork();
+        fork.put("idx_name", vec![1], vec![1]);
+        db.merge(fork.into_patch()).unwrap();
+    }
+
+    let db = MemoryDB::open(&path).unwrap();
+    assert!(db.snapshot().contains("idx_name", &[1]));
+}
+
+#[test]
+fn test_memorydb_merge_sync_reverts_on_error() {
+    use tempdir::TempDir;
+
+    let dir = TempDir::new("exonum_memorydb_merge_sync").unwrap();
+    // The parent directory of the file does not exist, so the state cannot be saved.
+    let db = MemoryDB::open(dir.path().join("missing").join("db")).unwrap();
+    let mut fork = db.fork();
+    fork.put("idx_name", vec![1], vec![1]);
+    db.merge(fork.into_patch()).unwrap();
+
+    let mut fork = db.fork();
+    fork.put("idx_name", vec![1], vec![2]);
+    fork.put("idx_name", vec![2], vec![2]);
+    fork.remove("idx_name", vec![1]);
+    assert!(db.merge_sync(fork.into_patch()).is_err());
+
+    let snapshot = db.snapshot();
+    assert_eq!(snapshot.get("idx_name", &[1]), Some(vec![1]));
+    assert!(!snapshot.contains("idx_name", &[2]));
+}
@@ -24,7 +24,18 @@
 //! that is, the Exonum process has exclusive access to the DB during blockchain operation.
 //! You can interact with the `Database` from multiple threads by cloning its instance.
 //!
-//! Exonum provides two database types: [`RocksDB`] and [`MemoryDB`].
+//! Exonum provides the following database types:
+//!
+//! - [`RocksDB`] is the default persistent database. It is available if the crate is built
+//!   with the `rocksdb` feature, which is enabled by default. The block cache size,
+//!   the compression and the number of open files of `RocksDB` are tuned with [`DbOptions`].
+//! - [`JournalDB`] is a persistent database written in pure Rust. It can be used on
+//!   the platforms where `RocksDB` cannot be built, e.g., for static `musl` binaries.
+//! - [`MemoryDB`] keeps all the data in RAM and is intended for testing.
+//! - [`TieredDB`] combines two databases, so that the old data can be moved to a cheaper
+//!   disk.
+//!
+//! Use [`open_database`] to open the persistent database available in the build.
 //!
 //! # Snapshot and Fork
 //!
@@ -59,6 +70,10 @@
 //! that their key spaces do not intersect. Isolation is commonly achieved with the help
 //! of column families; see `new_in_family` constructor in the built-in index types.
 //!
+//! Indices of a service are usually named with a common prefix, such as the service name.
+//! A [`Namespace`] builds such names and enumerates the indices created within the namespace,
+//! so that several services do not manage the prefixes manually.
+//!
 //! Merkelized indices can generate cryptographic proofs about inclusion
 //! of entries. Having such a proof, an external client may verify locally that the received data
 //! was authorized by the blockchain validators, without having to replicate
@@ -78,10 +93,16 @@
 //!   proofs of existence and is implemented as a binary Merkle Patricia tree.
 //! - [`KeySetIndex`] and [`ValueSetIndex`] is a set of items, similar to [`BTreeSet`] and
 //!   [`HashSet`].
+//! - [`SecondaryIndex`] maps secondary keys to the keys of another index, so that
+//!   its entries can be looked up without a full scan.
 //!
 //! [`Database`]: trait.Database.html
 //! [`RocksDB`]: struct.RocksDB.html
+//! [`JournalDB`]: struct.JournalDB.html
 //! [`MemoryDB`]: struct.MemoryDB.html
+//! [`TieredDB`]: struct.TieredDB.html
+//! [`open_database`]: fn.open_database.html
+//! [`DbOptions`]: struct.DbOptions.html
 //! [`Snapshot`]: trait.Snapshot.html
 //! [`Fork`]: struct.Fork.html
 //! [`Patch`]: struct.Patch.html
@@ -98,6 +119,8 @@
 //! [`ProofMapIndex`]: proof_map_index/struct.ProofMapIndex.html
 //! [`KeySetIndex`]: key_set_index/struct.KeySetIndex.html
 //! [`ValueSetIndex`]: value_set_index/struct.ValueSetIndex.html
+//! [`SecondaryIndex`]: secondary_index/struct.SecondaryIndex.html
+//! [`Namespace`]: struct.Namespace.html
 //! [doc:storage]: https://exonum.com/doc/architecture/storage
 //! [`Option`]: https://doc.rust-lang.org/std/option/enum.Option.html
 //! [`Box`]: https://doc.rust-lang.org/std/boxed/struct.Box.html
@@ -106,51 +129,92 @@
 //! [`BTreeSet`]: https://doc.rust-lang.org/std/collections/struct.BTreeSet.html
 //! [`HashSet`]: https://doc.rust-lang.org/std/collections/struct.HashSet.html
 
-pub(crate) use self::indexes_metadata::StorageMetadata;
+pub(crate) use self::db::PatchedSnapshot;
+pub(crate) use self::indexes_metadata::{check_indexes_registered, StorageMetadata};
 
 #[doc(no_inline)]
 pub use self::proof_map_index::{HashedKey, MapProof, ProofMapIndex};
 pub use self::{
+    checkpoint::write_checkpoint,
     db::{
         Change, Changes, ChangesIterator, Database, Fork, Iter, Iterator, Patch, PatchIterator,
-        Snapshot,
+        Snapshot, WriteBatch,
     },
     entry::Entry,
     error::Error,
     hash::UniqueHash,
+    indexes_metadata::IndexType,
+    journaldb::JournalDB,
     key_set_index::KeySetIndex,
     keys::StorageKey,
     list_index::ListIndex,
     map_index::MapIndex,
     memorydb::MemoryDB,
-    options::DbOptions,
+    namespace::{IndexInfo, Namespace, NAMESPACE_SEPARATOR},
+    options::{CommitSyncPolicy, CompactionSchedule, CompressionType, DbOptions, DbProfile},
+    proof_encoding::ProofDecodeError,
     proof_list_index::{ListProof, ProofListIndex},
-    rocksdb::RocksDB,
+    read_only::ReadOnlyDB,
+    secondary_index::{IndexedProofMapIndex, SecondaryIndex},
     sparse_list_index::SparseListIndex,
+    tiered::TieredDB,
     value_set_index::ValueSetIndex,
     values::StorageValue,
 };
 
+#[cfg(feature = "rocksdb")]
+pub use self::rocksdb::RocksDB;
+
+use std::path::Path;
+
 /// A specialized `Result` type for I/O operations with storage.
 pub type Result<T> = ::std::result::Result<T, Error>;
 
+/// Opens a persistent database stored at the specified path with the specified options.
+///
+/// `RocksDB` is used if the crate is built with the `rocksdb` feature; otherwise,
+/// `JournalDB` is used.
+#[cfg(feature = "rocksdb")]
+pub fn open_database<P: AsRef<Path>>(path: P, options: &DbOptions) -> Result<Box<dyn Database>> {
+    Ok(Box::new(RocksDB::open(path, options)?))
+}
+
+/// Opens a persistent database stored at the specified path with the specified options.
+///
+/// `RocksDB` is used if the crate is built with the `rocksdb` feature; otherwise,
+/// `JournalDB` is used.
+#[cfg(not(feature = "rocksdb"))]
+pub fn open_database<P: AsRef<Path>>(path: P, options: &DbOptions) -> Result<Box<dyn Database>> {
+    Ok(Box::new(JournalDB::open(path, options)?))
+}
+
 mod base_index;
+mod checkpoint;
 mod db;
 mod entry;
 mod error;
 mod hash;
 mod indexes_metadata;
+mod journaldb;
 mod keys;
 mod memorydb;
+mod namespace;
 mod options;
+mod proof_encoding;
+mod read_only;
+#[cfg(feature = "rocksdb")]
 mod rocksdb;
+mod tiered;
 mod values;
 
+pub mod backup;
+pub mod diff;
 pub mod key_set_index;
 pub mod list_index;
 pub mod map_index;
 pub mod proof_list_index;
 pub mod proof_map_index;
+pub mod secondary_index;
 pub mod sparse_list_index;
 pub mod value_set_index;
 
@@ -0,0 +1,174 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Namespaces of indices.
+
+use super::{
+    base_index::{is_valid_name, BaseIndex},
+    indexes_metadata::{IndexMetadata, IndexType},
+    Snapshot,
+};
+
+/// Separator between the namespace name and the index name in the full name of an index.
+pub const NAMESPACE_SEPARATOR: char = '.';
+
+/// A namespace of indices, e.g., all indices of a single service.
+///
+/// The full name of an index in the namespace is the namespace name and the name
+/// of the index joined with [`NAMESPACE_SEPARATOR`], e.g., `exonum_time.validators_times`.
+/// Since namespace names cannot contain the separator, indices of different namespaces
+/// never collide as long as namespace names are unique (for services, the service name
+/// is a natural choice).
+///
+/// # Examples
+///
+/// ```
+/// # use exonum::storage::{Database, IndexType, ListIndex, MapIndex, MemoryDB, Namespace};
+/// let db = MemoryDB::new();
+/// let mut fork = db.fork();
+/// let namespace = Namespace::new("my_service");
+/// ListIndex::new(namespace.index_name("list"), &mut fork).push(1_u64);
+/// MapIndex::new(namespace.index_name("map"), &mut fork).put(&1_u64, 2_u64);
+///
+/// let indexes = namespace.indexes(&fork);
+/// assert_eq!(indexes.len(), 2);
+/// assert_eq!(indexes[0].name, "my_service.list");
+/// assert_eq!(indexes[0].index_type, IndexType::List);
+/// assert_eq!(indexes[1].name, "my_service.map");
+/// ```
+///
+/// [`NAMESPACE_SEPARATOR`]: constant.NAMESPACE_SEPARATOR.html
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Namespace {
+    name: String,
+    prefix: String,
+}
+
+/// Information about an index stored in the indexes metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexInfo {
+    /// Full name of the index, including the namespace.
+    pub name: String,
+    /// Type of the index.
+    pub index_type: IndexType,
+    /// Whether the index is an index family.
+    pub is_family: bool,
+}
+
+impl Namespace {
+    /// Creates a namespace with the given name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is empty or contains characters other than `a-zA-Z0-9` and `_`.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        let name = name.into();
+        assert!(
+            !name.is_empty() && is_valid_name(&name) && !name.contains(NAMESPACE_SEPARATOR),
+            "Wrong namespace name '{}'. Use: a-zA-Z0-9 and _",
+            name
+        );
+        let prefix = format!("{}{}", name, NAMESPACE_SEPARATOR);
+        Namespace { name, prefix }
+    }
+
+    /// Returns the name of the namespace.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the full name of the index (or the index family) with the given name
+    /// in this namespace.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index_name` is empty.
+    pub fn index_name<S: AsRef<str>>(&self, index_name: S) -> String {
+        let index_name = index_name.as_ref();
+        assert!(!index_name.is_empty(), "Index name cannot be empty");
+        format!("{}{}", self.prefix, index_name)
+    }
+
+    /// Checks if the index with the given full name belongs to this namespace.
+    pub fn contains<S: AsRef<str>>(&self, full_name: S) -> bool {
+        let full_name = full_name.as_ref();
+        full_name.len() > self.prefix.len() && full_name.starts_with(&self.prefix)
+    }
+
+    /// Returns all indices in this namespace that have been created in the storage,
+    /// ordered by their full names.
+    pub fn indexes<T: AsRef<dyn Snapshot>>(&self, view: T) -> Vec<IndexInfo> {
+        let metadata = BaseIndex::indexes_metadata(view);
+        let iter = metadata.iter::<_, String, IndexMetadata>(&self.prefix);
+        iter.map(|(name, metadata)| IndexInfo {
+            name,
+            index_type: metadata.index_type(),
+            is_family: metadata.is_family(),
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storage::{Database, Entry, IndexType, ListIndex, MapIndex, MemoryDB};
+
+    #[test]
+    fn namespace_indexes() {
+        let db = MemoryDB::new();
+        let mut fork = db.fork();
+        let first = Namespace::new("first");
+        let second = Namespace::new("first_second");
+
+        ListIndex::new(first.index_name("list"), &mut fork).push(1_u8);
+        MapIndex::new_in_family(first.index_name("family"), &1_u8, &mut fork).put(&1_u8, 1_u8);
+        Entry::new(second.index_name("entry"), &mut fork).set(1_u8);
+        MapIndex::new("first", &mut fork).put(&1_u8, 1_u8);
+
+        assert_eq!(
+            first.indexes(&fork),
+            vec![
+                IndexInfo {
+                    name: "first.family".to_owned(),
+                    index_type: IndexType::Map,
+                    is_family: true,
+                },
+                IndexInfo {
+                    name: "first.list".to_owned(),
+                    index_type: IndexType::List,
+                    is_family: false,
+                },
+            ]
+        );
+        assert_eq!(
+            second.indexes(&fork),
+            vec![IndexInfo {
+                name: "first_second.entry".to_owned(),
+                index_type: IndexType::Entry,
+                is_family: false,
+            }]
+        );
+        assert!(Namespace::new("empty").indexes(&fork).is_empty());
+
+        assert!(first.contains("first.list"));
+        assert!(!first.contains("first"));
+        assert!(!first.contains("first_second.entry"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrong namespace name 'first.second'")]
+    fn namespace_name_with_separator() {
+        Namespace::new("first.second");
+    }
+}
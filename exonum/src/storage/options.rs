@@ -14,11 +14,23 @@
 
 //! Abstract settings for databases.
 
+use failure;
+
 /// Options for the database.
 ///
-/// These parameters apply to the underlying database of Exonum, currently `RocksDB`.
+/// These parameters apply to the underlying persistent database of Exonum. Some of them
+/// (e.g., `max_open_files`) are specific to `RocksDB` and are ignored by other databases.
+///
+/// The tuning options can be set at once with a named [`DbProfile`](enum.DbProfile.html);
+/// the options specified explicitly take precedence over the values of the profile.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct DbOptions {
+    /// Named profile of the tuning options, applied to the options which are
+    /// not specified explicitly.
+    ///
+    /// Defaults to `None`, meaning that the database defaults are used for such options.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<DbProfile>,
     /// Number of open files that can be used by the database.
     ///
     /// The underlying database opens multiple files during operation. If your system has a
@@ -38,13 +50,408 @@ pub struct DbOptions {
     ///
     /// Defaults to `true`.
     pub create_if_missing: bool,
+    /// Maximum number of concurrent background compactions.
+    ///
+    /// Background compactions of `RocksDB` may cause latency spikes under high load.
+    /// Limiting their number smooths the disk load at the cost of the slower compaction.
+    /// Compactions can also be performed in a maintenance window, see
+    /// [`CompactionSchedule`](struct.CompactionSchedule.html).
+    ///
+    /// Defaults to `None`, meaning that the database default is used.
+    #[serde(default)]
+    pub max_background_compactions: Option<i32>,
+    /// Size of the `RocksDB` cache of uncompressed data blocks, in bytes.
+    ///
+    /// The block cache is the largest consumer of the database memory; it should be
+    /// accounted for in the node memory budget together with the transaction pool limits
+    /// of the node configuration.
+    ///
+    /// Defaults to `None`, meaning that the database default (8 MB) is used.
+    #[serde(default)]
+    pub block_cache_size: Option<usize>,
+    /// Size of a `RocksDB` memtable, in bytes. Larger memtables speed up the bulk writes
+    /// at the cost of the memory usage and the longer recovery on restart.
+    ///
+    /// Defaults to `None`, meaning that the database default (64 MB) is used.
+    #[serde(default)]
+    pub write_buffer_size: Option<usize>,
+    /// Number of bits per key in the bloom filters of the `RocksDB` data blocks.
+    /// Bloom filters reduce the number of disk reads for the point lookups of the missing
+    /// keys; 10 bits per key give about 1% of false positives.
+    ///
+    /// Defaults to `None`, meaning that bloom filters are not used.
+    #[serde(default)]
+    pub bloom_filter_bits: Option<i32>,
+    /// Algorithm used by `RocksDB` to compress the data blocks.
+    ///
+    /// Defaults to `None`, meaning that the database default (`snappy`) is used.
+    #[serde(default)]
+    pub compression_type: Option<CompressionType>,
+    /// Policy of syncing the block commits to the disk.
+    ///
+    /// Defaults to `CommitSyncPolicy::Never`.
+    #[serde(default, skip_serializing_if = "CommitSyncPolicy::is_never")]
+    pub commit_sync: CommitSyncPolicy,
 }
 
 impl Default for DbOptions {
     fn default() -> Self {
         Self {
+            profile: None,
             max_open_files: None,
             create_if_missing: true,
+            max_background_compactions: None,
+            block_cache_size: None,
+            write_buffer_size: None,
+            bloom_filter_bits: None,
+            compression_type: None,
+            commit_sync: CommitSyncPolicy::default(),
         }
     }
 }
+
+impl DbOptions {
+    /// Creates options with the specified tuning profile.
+    pub fn with_profile(profile: DbProfile) -> Self {
+        Self {
+            profile: Some(profile),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the options with the values of the profile applied to the tuning options
+    /// which are not specified explicitly. The options are returned unchanged if
+    /// the profile is not set.
+    pub fn resolve(&self) -> Self {
+        let profile = match self.profile {
+            Some(profile) => profile.options(),
+            None => return *self,
+        };
+        Self {
+            profile: None,
+            max_open_files: self.max_open_files.or(profile.max_open_files),
+            max_background_compactions: self
+                .max_background_compactions
+                .or(profile.max_background_compactions),
+            block_cache_size: self.block_cache_size.or(profile.block_cache_size),
+            write_buffer_size: self.write_buffer_size.or(profile.write_buffer_size),
+            bloom_filter_bits: self.bloom_filter_bits.or(profile.bloom_filter_bits),
+            compression_type: self.compression_type.or(profile.compression_type),
+            ..*self
+        }
+    }
+}
+
+const MB: usize = 1 << 20;
+
+/// Named profile of the database tuning options.
+///
+/// In the configuration file, the profile is specified in the kebab case,
+/// e.g., `profile = "low-memory"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DbProfile {
+    /// Small caches and buffers, and a limited number of open files, for the nodes
+    /// running on constrained hardware.
+    LowMemory,
+    /// Large caches and buffers, bloom filters and more background compactions, for the nodes
+    /// processing a high transaction load.
+    Throughput,
+    /// Bloom filters and a stronger compression, for the nodes keeping the full history
+    /// of a large blockchain and serving the read requests to it.
+    Archival,
+}
+
+impl DbProfile {
+    /// Returns the tuning options of the profile.
+    pub fn options(self) -> DbOptions {
+        let defaults = DbOptions::default();
+        match self {
+            DbProfile::LowMemory => DbOptions {
+                max_open_files: Some(256),
+                max_background_compactions: Some(1),
+                block_cache_size: Some(4 * MB),
+                write_buffer_size: Some(4 * MB),
+                ..defaults
+            },
+            DbProfile::Throughput => DbOptions {
+                max_background_compactions: Some(4),
+                block_cache_size: Some(512 * MB),
+                write_buffer_size: Some(128 * MB),
+                bloom_filter_bits: Some(10),
+                ..defaults
+            },
+            DbProfile::Archival => DbOptions {
+                max_open_files: Some(1024),
+                max_background_compactions: Some(2),
+                block_cache_size: Some(128 * MB),
+                write_buffer_size: Some(64 * MB),
+                bloom_filter_bits: Some(10),
+                compression_type: Some(CompressionType::Zlib),
+                ..defaults
+            },
+        }
+    }
+}
+
+/// Algorithm of the data compression in the database.
+///
+/// In the configuration file, the algorithm is specified in the snake case, e.g., `"lz4"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionType {
+    /// The data is not compressed.
+    None,
+    /// [Snappy](https://google.github.io/snappy/) compression.
+    Snappy,
+    /// [Zlib](https://zlib.net/) compression.
+    Zlib,
+    /// [Bzip2](http://www.bzip.org/) compression.
+    Bz2,
+    /// [LZ4](https://lz4.github.io/lz4/) compression.
+    Lz4,
+    /// High compression mode of [LZ4](https://lz4.github.io/lz4/).
+    Lz4hc,
+}
+
+/// Schedule of the storage compaction performed by the node in a maintenance window,
+/// e.g., at night when the load is low.
+///
+/// The window is specified in hours of the UTC time; the compaction is started once
+/// per each window occurrence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompactionSchedule {
+    /// Hour at which the maintenance window starts, from 0 to 23.
+    pub start_hour: u8,
+    /// Hour at which the maintenance window ends, from 0 to 23. If it is less than
+    /// `start_hour`, the window spans midnight.
+    pub end_hour: u8,
+    /// Prefixes of the names of the indexes to compact. All the indexes are compacted
+    /// if the list is empty.
+    #[serde(default)]
+    pub prefixes: Vec<String>,
+}
+
+impl CompactionSchedule {
+    /// Checks that the hours of the window are valid.
+    pub fn validate(&self) -> Result<(), failure::Error> {
+        ensure!(
+            self.start_hour < 24 && self.end_hour < 24,
+            "Maintenance window hours should be less than 24"
+        );
+        ensure!(
+            self.start_hour != self.end_hour,
+            "Maintenance window cannot be empty"
+        );
+        Ok(())
+    }
+
+    /// Returns `true` if the specified hour is in the maintenance window.
+    pub fn contains_hour(&self, hour: u8) -> bool {
+        if self.start_hour < self.end_hour {
+            hour >= self.start_hour && hour < self.end_hour
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+/// Policy of syncing the writes of the committed blocks to the disk.
+///
+/// A block is committed with a single write to the database, which includes the block itself,
+/// its precommits and the changes of the transactions pool. A synchronous write also makes
+/// durable all the preceding writes, such as the transactions added to the pool or the
+/// consensus messages cache, so that they do not need to be synced separately.
+///
+/// If the node crashes after an unsynced commit, the commit may be lost; the node then
+/// catches up with the network on restart. However, if the majority of validators lose
+/// their last commits simultaneously (e.g., on a power outage in a single data center),
+/// the network may fork. Syncing each block eliminates this risk at the cost of
+/// an `fsync` call per block.
+///
+/// In the configuration file, the policy is specified as `"always"`, `"never"` or
+/// `{ every_n_blocks = N }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitSyncPolicy {
+    /// Each block commit is synced to the disk. The safest and the slowest option.
+    Always,
+    /// Every `N`-th block commit is synced to the disk; up to `N - 1` last blocks may be lost
+    /// on a crash. `N = 1` is equivalent to `Always`; `N = 0` is equivalent to `Never`.
+    EveryNBlocks(u64),
+    /// Block commits are not synced explicitly; the data is written to the disk
+    /// by the operating system. The fastest option, which is used by default.
+    Never,
+}
+
+impl Default for CommitSyncPolicy {
+    fn default() -> Self {
+        CommitSyncPolicy::Never
+    }
+}
+
+impl CommitSyncPolicy {
+    /// Returns `true` if block commits are not synced explicitly.
+    pub fn is_never(&self) -> bool {
+        *self == CommitSyncPolicy::Never
+    }
+
+    /// Returns `true` if the commit of the block at the specified height should be synced
+    /// to the disk.
+    pub fn should_sync(&self, height: u64) -> bool {
+        match *self {
+            CommitSyncPolicy::Always => true,
+            CommitSyncPolicy::EveryNBlocks(0) => false,
+            CommitSyncPolicy::EveryNBlocks(n) => height % n == 0,
+            CommitSyncPolicy::Never => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_sync_policy() {
+        assert!(CommitSyncPolicy::Always.should_sync(5));
+        assert!(!CommitSyncPolicy::Never.should_sync(5));
+        assert!(!CommitSyncPolicy::EveryNBlocks(0).should_sync(0));
+        assert!(CommitSyncPolicy::EveryNBlocks(1).should_sync(5));
+        assert!(CommitSyncPolicy::EveryNBlocks(3).should_sync(6));
+        assert!(!CommitSyncPolicy::EveryNBlocks(3).should_sync(7));
+    }
+
+    #[test]
+    fn test_compaction_schedule() {
+        let schedule = CompactionSchedule {
+            start_hour: 2,
+            end_hour: 5,
+            prefixes: Vec::new(),
+        };
+        assert!(schedule.validate().is_ok());
+        assert!(!schedule.contains_hour(1));
+        assert!(schedule.contains_hour(2));
+        assert!(schedule.contains_hour(4));
+        assert!(!schedule.contains_hour(5));
+
+        let schedule = CompactionSchedule {
+            start_hour: 23,
+            end_hour: 1,
+            prefixes: Vec::new(),
+        };
+        assert!(schedule.contains_hour(23));
+        assert!(schedule.contains_hour(0));
+        assert!(!schedule.contains_hour(1));
+        assert!(!schedule.contains_hour(12));
+
+        let invalid = CompactionSchedule {
+            start_hour: 24,
+            ..schedule.clone()
+        };
+        assert!(invalid.validate().is_err());
+        let empty = CompactionSchedule {
+            end_hour: 23,
+            ..schedule
+        };
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn test_commit_sync_policy_deserialization() {
+        #[derive(Deserialize)]
+        struct Config {
+            database: DbOptions,
+        }
+
+        let config: Config = ::toml::from_str(
+            r#"
+            [database]
+            create_if_missing = true
+            "#,
+        ).unwrap();
+        assert_eq!(config.database.commit_sync, CommitSyncPolicy::Never);
+
+        let config: Config = ::toml::from_str(
+            r#"
+            [database]
+            create_if_missing = true
+            commit_sync = "always"
+            "#,
+        ).unwrap();
+        assert_eq!(config.database.commit_sync, CommitSyncPolicy::Always);
+
+        let config: Config = ::toml::from_str(
+            r#"
+            [database]
+            create_if_missing = true
+            commit_sync = { every_n_blocks = 10 }
+            "#,
+        ).unwrap();
+        assert_eq!(
+            config.database.commit_sync,
+            CommitSyncPolicy::EveryNBlocks(10)
+        );
+    }
+
+    #[test]
+    fn test_db_profile() {
+        #[derive(Deserialize)]
+        struct Config {
+            database: DbOptions,
+        }
+
+        let config: Config = ::toml::from_str(
+            r#"
+            [database]
+            create_if_missing = true
+            profile = "low-memory"
+            block_cache_size = 1048576
+            "#,
+        ).unwrap();
+        assert_eq!(config.database.profile, Some(DbProfile::LowMemory));
+
+        let options = config.database.resolve();
+        assert_eq!(options.profile, None);
+        assert_eq!(options.block_cache_size, Some(1 << 20));
+        assert_eq!(options.write_buffer_size, Some(4 * MB));
+        assert_eq!(options.max_open_files, Some(256));
+        assert!(options.create_if_missing);
+
+        let options = DbOptions::default().resolve();
+        assert_eq!(options.block_cache_size, None);
+        assert_eq!(options.bloom_filter_bits, None);
+
+        let options = DbOptions::with_profile(DbProfile::Archival).resolve();
+        assert_eq!(options.bloom_filter_bits, Some(10));
+        assert_eq!(options.compression_type, Some(CompressionType::Zlib));
+    }
+
+    #[test]
+    fn test_compression_type_deserialization() {
+        #[derive(Deserialize)]
+        struct Config {
+            database: DbOptions,
+        }
+
+        let config: Config = ::toml::from_str(
+            r#"
+            [database]
+            create_if_missing = true
+            "#,
+        ).unwrap();
+        assert_eq!(config.database.compression_type, None);
+
+        let config: Config = ::toml::from_str(
+            r#"
+            [database]
+            create_if_missing = true
+            compression_type = "lz4hc"
+            "#,
+        ).unwrap();
+        assert_eq!(
+            config.database.compression_type,
+            Some(CompressionType::Lz4hc)
+        );
+    }
+}
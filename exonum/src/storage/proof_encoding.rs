@@ -0,0 +1,150 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Common primitives of the compact binary encoding of the Merkle proofs.
+//!
+//! Lengths are encoded as little-endian `u32`, hashes as their 32 raw bytes, and keys
+//! and values of the proofs with their `StorageValue` representation prefixed by its length.
+//! The keys and values are checked with `encoding::Field::check` before decoding.
+//! See `ListProof::to_bytes` and `MapProof::to_bytes` for the layout of the specific proofs.
+
+// Workaround for `failure` see https://github.com/rust-lang-nursery/failure/issues/223 and
+// ECR-1771 for the details.
+#![allow(bare_trait_objects)]
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use std::borrow::Cow;
+
+use super::{StorageKey, StorageValue};
+use crypto::{Hash, HASH_SIZE};
+use encoding::Field;
+
+/// An error returned when a proof cannot be decoded from the binary encoding.
+#[derive(Debug, Fail, Clone, PartialEq)]
+pub enum ProofDecodeError {
+    /// The data ends before the proof is completely read.
+    #[fail(display = "unexpected end of the proof data")]
+    UnexpectedEnd,
+
+    /// A node of the proof has an unknown tag.
+    #[fail(display = "invalid tag of a proof node: {}", _0)]
+    InvalidTag(u8),
+
+    /// A path in the map proof is malformed.
+    #[fail(display = "invalid path in the proof")]
+    InvalidPath,
+
+    /// The proof is nested deeper than any valid proof.
+    #[fail(display = "proof is nested too deeply")]
+    TooDeep,
+
+    /// The data continues after the end of the proof.
+    #[fail(display = "{} trailing bytes after the proof", _0)]
+    TrailingBytes(usize),
+
+    /// A key or a value of the proof is malformed.
+    #[fail(display = "invalid key or value in the proof: {}", _0)]
+    InvalidValue(String),
+}
+
+pub(crate) fn write_u32(buffer: &mut Vec<u8>, value: usize) {
+    let mut bytes = [0; 4];
+    LittleEndian::write_u32(&mut bytes, value as u32);
+    buffer.extend_from_slice(&bytes);
+}
+
+pub(crate) fn write_hash(buffer: &mut Vec<u8>, hash: &Hash) {
+    buffer.extend_from_slice(hash.as_ref());
+}
+
+pub(crate) fn write_value<V: StorageValue + Clone>(buffer: &mut Vec<u8>, value: &V) {
+    let bytes = value.clone().into_bytes();
+    write_u32(buffer, bytes.len());
+    buffer.extend_from_slice(&bytes);
+}
+
+/// Reader of the binary encoding of a proof.
+pub(crate) struct ProofReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ProofReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        ProofReader { data }
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], ProofDecodeError> {
+        if self.data.len() < len {
+            return Err(ProofDecodeError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, ProofDecodeError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_u32(&mut self) -> Result<usize, ProofDecodeError> {
+        Ok(LittleEndian::read_u32(self.read_bytes(4)?) as usize)
+    }
+
+    pub fn read_hash(&mut self) -> Result<Hash, ProofDecodeError> {
+        Ok(<Hash as StorageKey>::read(self.read_bytes(HASH_SIZE)?))
+    }
+
+    pub fn read_value<V>(&mut self) -> Result<V, ProofDecodeError>
+    where
+        V: StorageValue + for<'r> Field<'r>,
+    {
+        let len = self.read_u32()?;
+        let bytes = self.read_bytes(len)?;
+        check_value::<V>(bytes)?;
+        Ok(V::from_bytes(Cow::Borrowed(bytes)))
+    }
+
+    /// Checks that the whole data has been read.
+    pub fn finish(self) -> Result<(), ProofDecodeError> {
+        if self.data.is_empty() {
+            Ok(())
+        } else {
+            Err(ProofDecodeError::TrailingBytes(self.data.len()))
+        }
+    }
+}
+
+/// Checks that the `StorageValue` representation of a key or a value can be decoded.
+fn check_value<V: for<'r> Field<'r>>(bytes: &[u8]) -> Result<(), ProofDecodeError> {
+    let result = if V::is_segment() {
+        // The value is checked as a segment of an enclosing structure.
+        let mut buffer = vec![0; 8];
+        Field::write(&bytes, &mut buffer, 0, 8);
+        V::check(&buffer, 0.into(), 8.into(), 8.into())
+    } else {
+        let size = V::field_size();
+        if bytes.len() != size as usize {
+            return Err(ProofDecodeError::InvalidValue(format!(
+                "expected {} bytes, found {}",
+                size,
+                bytes.len()
+            )));
+        }
+        V::check(bytes, 0.into(), size.into(), size.into())
+    };
+    result
+        .map(drop)
+        .map_err(|e| ProofDecodeError::InvalidValue(e.to_string()))
+}
@@ -48,11 +48,13 @@ pub struct ProofListIndex<T, V> {
 
 /// An iterator over the items of a `ProofListIndex`.
 ///
-/// This struct is created by the [`iter`] or
-/// [`iter_from`] method on [`ProofListIndex`]. See its documentation for details.
+/// This struct is created by the [`iter`], [`iter_from`], [`iter_rev`] or
+/// [`iter_rev_from`] method on [`ProofListIndex`]. See its documentation for details.
 ///
 /// [`iter`]: struct.ProofListIndex.html#method.iter
 /// [`iter_from`]: struct.ProofListIndex.html#method.iter_from
+/// [`iter_rev`]: struct.ProofListIndex.html#method.iter_rev
+/// [`iter_rev_from`]: struct.ProofListIndex.html#method.iter_rev_from
 /// [`ProofListIndex`]: struct.ProofListIndex.html
 #[derive(Debug)]
 pub struct ProofListIndexIter<'a, V> {
@@ -442,6 +444,51 @@ where
             base_iter: self.base.iter_from(&0_u8, &ProofListKey::leaf(from)),
         }
     }
+
+    /// Returns an iterator over the list in reverse order. The iterator element type is V.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, ProofListIndex};
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let snapshot = db.snapshot();
+    /// let index: ProofListIndex<_, u8> = ProofListIndex::new(name, &snapshot);
+    ///
+    /// for val in index.iter_rev() {
+    ///     println!("{}", val);
+    /// }
+    /// ```
+    pub fn iter_rev(&self) -> ProofListIndexIter<V> {
+        ProofListIndexIter {
+            base_iter: self.base.iter_rev(&0_u8),
+        }
+    }
+
+    /// Returns an iterator over the list in reverse order starting from the specified
+    /// position. The iterator element type is V.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, ProofListIndex};
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let snapshot = db.snapshot();
+    /// let index: ProofListIndex<_, u8> = ProofListIndex::new(name, &snapshot);
+    ///
+    /// for val in index.iter_rev_from(1) {
+    ///     println!("{}", val);
+    /// }
+    /// ```
+    pub fn iter_rev_from(&self, from: u64) -> ProofListIndexIter<V> {
+        ProofListIndexIter {
+            base_iter: self.base.iter_rev_from(&0_u8, &ProofListKey::leaf(from)),
+        }
+    }
 }
 
 impl<'a, V> ProofListIndex<&'a mut Fork, V>
@@ -624,25 +671,44 @@ where
 ///
 /// If `hashes` are empty then `Hash::zero()` value is returned.
 pub fn root_hash(hashes: &[Hash]) -> Hash {
-    match hashes.len() {
-        0 => Hash::zero(),
-        1 => hashes[0],
-        _ => {
-            let mut current_hashes = combine_hash_list(hashes);
-            while current_hashes.len() > 1 {
-                current_hashes = combine_hash_list(&current_hashes);
-            }
-            current_hashes[0]
+    streaming_root_hash(hashes.iter().cloned())
+}
+
+/// Computes the root hash of the Merkle tree with the given hashes of the list elements.
+///
+/// The tree is not built; only the roots of the complete subtrees on the right contour
+/// of the tree are kept in memory, so the memory consumption is logarithmic in the number
+/// of elements.
+pub(crate) fn streaming_root_hash<I: IntoIterator<Item = Hash>>(hashes: I) -> Hash {
+    // `contour[i]` is the root of the complete subtree with `2^i` elements which waits
+    // for its right sibling.
+    let mut contour: Vec<Option<Hash>> = Vec::new();
+    for hash in hashes {
+        let mut node = hash;
+        let mut height = 0;
+        while let Some(Some(left)) = contour.get(height).cloned() {
+            node = hash_pair(&left, &node);
+            contour[height] = None;
+            height += 1;
+        }
+        if height == contour.len() {
+            contour.push(Some(node));
+        } else {
+            contour[height] = Some(node);
         }
     }
-}
 
-fn combine_hash_list(hashes: &[Hash]) -> Vec<Hash> {
-    hashes
-        .chunks(2)
-        .map(|pair| match pair {
-            [first, second] => hash_pair(first, second),
-            [single] => hash_one(single),
-            _ => unreachable!(),
-        }).collect()
+    // Nodes without the right sibling are hashed alone, except for the root.
+    let mut root = None;
+    for (height, left) in contour.iter().enumerate() {
+        let is_top = height + 1 == contour.len();
+        root = match (*left, root) {
+            (Some(left), Some(right)) => Some(hash_pair(&left, &right)),
+            (Some(left), None) if is_top => Some(left),
+            (Some(left), None) => Some(hash_one(&left)),
+            (None, Some(right)) => Some(hash_one(&right)),
+            (None, None) => None,
+        };
+    }
+    root.unwrap_or_else(Hash::zero)
 }
@@ -15,8 +15,26 @@
 use serde::{de::Error, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{from_value, Error as SerdeJsonError, Value};
 
-use super::{super::StorageValue, hash_one, hash_pair, key::ProofListKey};
+use super::{
+    super::{
+        proof_encoding::{write_hash, write_value, ProofDecodeError, ProofReader},
+        StorageValue,
+    },
+    hash_one, hash_pair,
+    key::ProofListKey,
+};
 use crypto::Hash;
+use encoding::Field;
+
+const FULL_TAG: u8 = 0;
+const LEFT_TAG: u8 = 1;
+const LEFT_ONLY_TAG: u8 = 2;
+const RIGHT_TAG: u8 = 3;
+const LEAF_TAG: u8 = 4;
+
+// Maximum number of the branches on the path from the root of the proof to a leaf,
+// corresponding to the height of a list with `u64::max_value()` elements.
+const MAX_DEPTH: usize = 64;
 
 /// An enum that represents a proof of existence for a proof list elements.
 #[derive(Debug, PartialEq, Eq)]
@@ -89,6 +107,99 @@ impl<V: StorageValue> ListProof<V> {
     }
 }
 
+impl<V> ListProof<V>
+where
+    V: StorageValue + for<'r> Field<'r>,
+{
+    /// Deserializes the proof from the compact binary format produced by
+    /// [`to_bytes`](#method.to_bytes).
+    ///
+    /// Only the structure of the proof is checked; the proof still needs to be validated
+    /// against the trusted Merkle root hash with [`validate`](#method.validate).
+    /// The values are checked with `Field::check` and decoded with `StorageValue::from_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofDecodeError> {
+        let mut reader = ProofReader::new(bytes);
+        let proof = Self::read(&mut reader, 0)?;
+        reader.finish()?;
+        Ok(proof)
+    }
+
+    fn read(reader: &mut ProofReader, depth: usize) -> Result<Self, ProofDecodeError> {
+        let tag = reader.read_u8()?;
+        if tag != LEAF_TAG && depth == MAX_DEPTH {
+            return Err(ProofDecodeError::TooDeep);
+        }
+        let proof = match tag {
+            FULL_TAG => {
+                let left = Self::read(reader, depth + 1)?;
+                let right = Self::read(reader, depth + 1)?;
+                ListProof::Full(Box::new(left), Box::new(right))
+            }
+            LEFT_TAG => {
+                let left = Self::read(reader, depth + 1)?;
+                ListProof::Left(Box::new(left), Some(reader.read_hash()?))
+            }
+            LEFT_ONLY_TAG => ListProof::Left(Box::new(Self::read(reader, depth + 1)?), None),
+            RIGHT_TAG => {
+                let left = reader.read_hash()?;
+                ListProof::Right(left, Box::new(Self::read(reader, depth + 1)?))
+            }
+            LEAF_TAG => ListProof::Leaf(reader.read_value()?),
+            tag => return Err(ProofDecodeError::InvalidTag(tag)),
+        };
+        Ok(proof)
+    }
+}
+
+impl<V: StorageValue + Clone> ListProof<V> {
+    /// Serializes the proof into the compact binary format, which is considerably
+    /// smaller than JSON.
+    ///
+    /// The nodes of the proof are written in the depth-first order, each starting with
+    /// a one-byte tag:
+    ///
+    /// - `0`: `Full` branch, followed by the left and the right child;
+    /// - `1`: `Left` branch with the right hash, followed by the left child
+    ///   and the 32-byte hash;
+    /// - `2`: `Left` branch without the right hash, followed by the left child;
+    /// - `3`: `Right` branch, followed by the 32-byte hash and the right child;
+    /// - `4`: `Leaf`, followed by the length of the value (little-endian `u32`)
+    ///   and its `StorageValue` representation.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        self.write(&mut buffer);
+        buffer
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        match *self {
+            ListProof::Full(ref left, ref right) => {
+                buffer.push(FULL_TAG);
+                left.write(buffer);
+                right.write(buffer);
+            }
+            ListProof::Left(ref left, Some(ref right)) => {
+                buffer.push(LEFT_TAG);
+                left.write(buffer);
+                write_hash(buffer, right);
+            }
+            ListProof::Left(ref left, None) => {
+                buffer.push(LEFT_ONLY_TAG);
+                left.write(buffer);
+            }
+            ListProof::Right(ref left, ref right) => {
+                buffer.push(RIGHT_TAG);
+                write_hash(buffer, left);
+                right.write(buffer);
+            }
+            ListProof::Leaf(ref value) => {
+                buffer.push(LEAF_TAG);
+                write_value(buffer, value);
+            }
+        }
+    }
+}
+
 impl<V: Serialize> Serialize for ListProof<V> {
     fn serialize<S>(&self, ser: S) -> Result<S::Ok, S::Error>
     where
@@ -21,7 +21,7 @@ use encoding::serialize::{
     json::reexport::{from_str, to_string},
     reexport::Serialize,
 };
-use storage::Database;
+use storage::{Database, MemoryDB, ProofDecodeError};
 
 const IDX_NAME: &'static str = "idx_name";
 
@@ -115,6 +115,13 @@ fn iter(db: Box<dyn Database>) {
         list_index.iter_from(3).collect::<Vec<u8>>(),
         Vec::<u8>::new()
     );
+
+    assert_eq!(list_index.iter_rev().collect::<Vec<u8>>(), vec![3, 2, 1]);
+    assert_eq!(list_index.iter_rev_from(1).collect::<Vec<u8>>(), vec![2, 1]);
+    assert_eq!(
+        list_index.iter_rev_from(10).collect::<Vec<u8>>(),
+        vec![3, 2, 1]
+    );
 }
 
 fn list_index_proof(db: Box<dyn Database>) {
@@ -554,6 +561,64 @@ struct ProofInfo<'a, V: Serialize + 'a> {
     range_end: u64,
 }
 
+#[test]
+fn test_proof_binary_encoding() {
+    let db = MemoryDB::new();
+    let mut fork = db.fork();
+    let mut index = ProofListIndex::new(IDX_NAME, &mut fork);
+    let values = random_values(100);
+    for value in &values {
+        index.push(value.clone());
+    }
+
+    for &(from, to) in &[(0, 1), (5, 6), (0, 100), (17, 53), (99, 100)] {
+        let proof = index.get_range_proof(from, to);
+        let bytes = proof.to_bytes();
+        let decoded: ListProof<Vec<u8>> = ListProof::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, proof);
+
+        // The binary and JSON encodings represent the same proof.
+        let json = to_string(&proof).unwrap();
+        let from_json: ListProof<Vec<u8>> = from_str(&json).unwrap();
+        assert_eq!(from_json.to_bytes(), bytes);
+        assert!(bytes.len() < json.len());
+
+        let entries = decoded.validate(index.merkle_root(), index.len()).unwrap();
+        assert_eq!(entries.len() as u64, to - from);
+    }
+
+    let bytes = index.get_range_proof(3, 7).to_bytes();
+    assert_eq!(
+        ListProof::<Vec<u8>>::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(ProofDecodeError::UnexpectedEnd)
+    );
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        ListProof::<Vec<u8>>::from_bytes(&trailing),
+        Err(ProofDecodeError::TrailingBytes(1))
+    );
+    assert_eq!(
+        ListProof::<Vec<u8>>::from_bytes(&[5]),
+        Err(ProofDecodeError::InvalidTag(5))
+    );
+    // A chain of branches longer than in any list.
+    let deep = vec![2; 100];
+    assert_eq!(
+        ListProof::<Vec<u8>>::from_bytes(&deep),
+        Err(ProofDecodeError::TooDeep)
+    );
+    // Values are checked before decoding.
+    match ListProof::<u64>::from_bytes(&[4, 3, 0, 0, 0, 1, 2, 3]) {
+        Err(ProofDecodeError::InvalidValue(_)) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    match ListProof::<bool>::from_bytes(&[4, 1, 0, 0, 0, 2]) {
+        Err(ProofDecodeError::InvalidValue(_)) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
 mod memorydb_tests {
     use std::path::Path;
     use storage::{Database, MemoryDB};
@@ -666,6 +731,7 @@ mod memorydb_tests {
     }
 }
 
+#[cfg(feature = "rocksdb")]
 mod rocksdb_tests {
     use std::path::Path;
     use storage::{Database, DbOptions, RocksDB};
@@ -821,4 +887,14 @@ mod root_hash_tests {
     fn root_hash_empty() {
         assert_root_hash_correct(&hash_list(&[]));
     }
+
+    #[test]
+    fn root_hash_various_lengths() {
+        for len in 0..70_u32 {
+            let hashes: Vec<_> = (0..len)
+                .map(|i| crypto::hash(&i.to_string().into_bytes()))
+                .collect();
+            assert_root_hash_correct(&hashes);
+        }
+    }
 }
@@ -29,7 +29,7 @@ use self::{
 use super::{
     base_index::{BaseIndex, BaseIndexIter},
     indexes_metadata::IndexType,
-    Fork, Snapshot, StorageKey, StorageValue,
+    Error, Fork, Snapshot, StorageKey, StorageValue,
 };
 use crypto::{CryptoHash, Hash, HashStream};
 
@@ -204,6 +204,80 @@ where
         })
     }
 
+    fn get_node(&self, key: &ProofPath) -> Result<Node<V>, Error> {
+        let node = if key.is_leaf() {
+            self.base.get(key).map(Node::Leaf)
+        } else {
+            self.base.get(key).map(Node::Branch)
+        };
+        node.ok_or_else(|| Error::new(format!("Node {:?} is absent", key)))
+    }
+
+    /// Recomputes the root hash of the tree from the stored values, checking that the hashes
+    /// of the children recorded in every branch node match the recomputed ones. Only the path
+    /// from the root to the visited node is kept in memory.
+    ///
+    /// Returns the root hash and the number of the values reachable from the root, or
+    /// the first found inconsistency.
+    pub(crate) fn recompute_root(&self) -> Result<(Hash, u64), Error> {
+        let root_path = match self.get_root_path() {
+            Some(path) => path,
+            None => return Ok((Hash::zero(), 0)),
+        };
+        match self.get_node(&root_path)? {
+            Node::Leaf(value) => {
+                let hash = HashStream::new()
+                    .update(root_path.as_bytes())
+                    .update(value.hash().as_ref())
+                    .hash();
+                Ok((hash, 1))
+            }
+            Node::Branch(branch) => {
+                let mut leaves = 0;
+                self.check_branch(&root_path, &branch, &mut leaves)?;
+                Ok((branch.hash(), leaves))
+            }
+        }
+    }
+
+    fn check_branch(
+        &self,
+        path: &ProofPath,
+        branch: &BranchNode,
+        leaves: &mut u64,
+    ) -> Result<(), Error> {
+        for &kind in &[ChildKind::Left, ChildKind::Right] {
+            let child_path = branch.child_path(kind);
+            if child_path.len() <= path.len() {
+                return Err(Error::new(format!(
+                    "Child {:?} of the node {:?} is not below it",
+                    child_path, path
+                )));
+            }
+            let child_hash = match self.get_node(&child_path)? {
+                Node::Leaf(value) => {
+                    *leaves += 1;
+                    value.hash()
+                }
+                Node::Branch(child) => {
+                    self.check_branch(&child_path, &child, leaves)?;
+                    child.hash()
+                }
+            };
+            if child_hash != *branch.child_hash(kind) {
+                return Err(Error::new(format!(
+                    "Hash of the child {:?} of the node {:?} does not match: \
+                     expected {:?}, recomputed {:?}",
+                    child_path,
+                    path,
+                    branch.child_hash(kind),
+                    child_hash
+                )));
+            }
+        }
+        Ok(())
+    }
+
     fn get_node_unchecked(&self, key: &ProofPath) -> Node<V> {
         // TODO: Unwraps? (ECR-84)
         if key.is_leaf() {
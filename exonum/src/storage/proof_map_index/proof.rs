@@ -19,11 +19,18 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use super::{
-    key::{BitsRange, ChildKind, ProofMapKey, ProofPath, KEY_SIZE},
+    key::{
+        BitsRange, ChildKind, ProofMapKey, ProofPath, BRANCH_KEY_PREFIX, KEY_SIZE, LEAF_KEY_PREFIX,
+        PROOF_PATH_KIND_POS, PROOF_PATH_LEN_POS, PROOF_PATH_SIZE,
+    },
     node::{BranchNode, Node},
 };
 use crypto::{CryptoHash, Hash, HashStream};
-use storage::StorageValue;
+use encoding::Field;
+use storage::{
+    proof_encoding::{write_hash, write_u32, write_value, ProofDecodeError, ProofReader},
+    StorageKey, StorageValue,
+};
 
 // Expected size of the proof, in number of hashed entries.
 const DEFAULT_PROOF_CAPACITY: usize = 8;
@@ -258,7 +265,7 @@ impl<K, V> Into<(K, Option<V>)> for OptionalEntry<K, V> {
 /// [`get_multiproof()`]: struct.ProofMapIndex.html#method.get_multiproof
 /// [`check()`]: #method.check
 /// [`ProofPath`]: struct.ProofPath.html
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MapProof<K, V> {
     entries: Vec<OptionalEntry<K, V>>,
     proof: Vec<MapProofEntry>,
@@ -474,6 +481,106 @@ impl<K, V> MapProof<K, V> {
     }
 }
 
+const MISSING_ENTRY_TAG: u8 = 0;
+const KV_ENTRY_TAG: u8 = 1;
+
+impl<K, V> MapProof<K, V>
+where
+    K: StorageValue + Clone,
+    V: StorageValue + Clone,
+{
+    /// Serializes the proof into the compact binary format, which is considerably
+    /// smaller than JSON.
+    ///
+    /// The format consists of:
+    ///
+    /// - the number of the entries (little-endian `u32`), followed by the entries.
+    ///   Each entry starts with a one-byte tag: `0` for a missing key, followed by the key,
+    ///   or `1` for a present key, followed by the key and the value. Keys and values
+    ///   are written as their length (little-endian `u32`) and their `StorageValue`
+    ///   representation;
+    /// - the number of the proof nodes (little-endian `u32`), followed by the nodes.
+    ///   Each node is written as the 34-byte storage representation of its `ProofPath`
+    ///   and the 32-byte hash.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        write_u32(&mut buffer, self.entries.len());
+        for entry in &self.entries {
+            match *entry {
+                OptionalEntry::Missing { ref missing } => {
+                    buffer.push(MISSING_ENTRY_TAG);
+                    write_value(&mut buffer, missing);
+                }
+                OptionalEntry::KV { ref key, ref value } => {
+                    buffer.push(KV_ENTRY_TAG);
+                    write_value(&mut buffer, key);
+                    write_value(&mut buffer, value);
+                }
+            }
+        }
+
+        write_u32(&mut buffer, self.proof.len());
+        for entry in &self.proof {
+            let mut path = [0; PROOF_PATH_SIZE];
+            entry.path.write(&mut path);
+            buffer.extend_from_slice(&path);
+            write_hash(&mut buffer, &entry.hash);
+        }
+        buffer
+    }
+}
+
+impl<K, V> MapProof<K, V>
+where
+    K: StorageValue + for<'r> Field<'r>,
+    V: StorageValue + for<'r> Field<'r>,
+{
+    /// Deserializes the proof from the compact binary format produced by
+    /// [`to_bytes`](#method.to_bytes).
+    ///
+    /// Only the structure of the proof is checked; the proof still needs to be verified
+    /// with [`check`](#method.check). The keys and values are checked with `Field::check`
+    /// and decoded with `StorageValue::from_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofDecodeError> {
+        let mut reader = ProofReader::new(bytes);
+
+        let entries_count = reader.read_u32()?;
+        let mut entries = Vec::new();
+        for _ in 0..entries_count {
+            let entry = match reader.read_u8()? {
+                MISSING_ENTRY_TAG => OptionalEntry::missing(reader.read_value()?),
+                KV_ENTRY_TAG => {
+                    let key = reader.read_value()?;
+                    OptionalEntry::value(key, reader.read_value()?)
+                }
+                tag => return Err(ProofDecodeError::InvalidTag(tag)),
+            };
+            entries.push(entry);
+        }
+
+        let proof_count = reader.read_u32()?;
+        let mut proof = Vec::new();
+        for _ in 0..proof_count {
+            let raw_path = reader.read_bytes(PROOF_PATH_SIZE)?;
+            let is_valid = match raw_path[PROOF_PATH_KIND_POS] {
+                LEAF_KEY_PREFIX => raw_path[PROOF_PATH_LEN_POS] == 0,
+                BRANCH_KEY_PREFIX => true,
+                _ => false,
+            };
+            if !is_valid {
+                return Err(ProofDecodeError::InvalidPath);
+            }
+            proof.push(MapProofEntry {
+                path: ProofPath::read(raw_path),
+                hash: reader.read_hash()?,
+            });
+        }
+
+        reader.finish()?;
+        Ok(MapProof { entries, proof })
+    }
+}
+
 impl<K, V> MapProof<K, V>
 where
     K: ProofMapKey,
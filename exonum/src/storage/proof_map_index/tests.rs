@@ -27,7 +27,7 @@ use super::{
 };
 use crypto::{hash, CryptoHash, Hash, HashStream};
 use encoding::serialize::reexport::{DeserializeOwned, Serialize};
-use storage::{Database, Fork, StorageValue};
+use storage::{Database, Fork, MemoryDB, ProofDecodeError, StorageValue};
 
 const IDX_NAME: &'static str = "idx_name";
 
@@ -476,6 +476,69 @@ fn check_multiproofs_for_data<K, V>(
     }
 }
 
+#[test]
+fn test_map_proof_binary_encoding() {
+    let db = MemoryDB::new();
+    let mut fork = db.fork();
+    let mut table = ProofMapIndex::new(IDX_NAME, &mut fork);
+    let keys: Vec<Hash> = (0_u64..100).map(|i| hash(&i.into_bytes())).collect();
+    for (i, key) in keys.iter().enumerate() {
+        table.put(key, vec![i as u8; i % 7]);
+    }
+
+    let missing_key = hash(&[1, 2, 3]);
+    let proofs = vec![
+        table.get_proof(keys[0]),
+        table.get_proof(missing_key),
+        table.get_multiproof(keys[10..30].iter().cloned().chain(Some(missing_key))),
+    ];
+    for proof in proofs {
+        let bytes = proof.to_bytes();
+        let decoded: MapProof<Hash, Vec<u8>> = MapProof::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, proof);
+
+        // The binary and JSON encodings represent the same proof.
+        let json = serde_json::to_string(&proof).unwrap();
+        let from_json: MapProof<Hash, Vec<u8>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(from_json.to_bytes(), bytes);
+        assert!(bytes.len() < json.len());
+
+        let checked = decoded.check().unwrap();
+        assert_eq!(checked.merkle_root(), table.merkle_root());
+    }
+
+    let bytes = table.get_proof(keys[0]).to_bytes();
+    assert_eq!(
+        MapProof::<Hash, Vec<u8>>::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(ProofDecodeError::UnexpectedEnd)
+    );
+    assert_eq!(
+        MapProof::<Hash, Vec<u8>>::from_bytes(&[1, 0, 0, 0, 2]),
+        Err(ProofDecodeError::InvalidTag(2))
+    );
+    // A single proof node with an invalid path kind.
+    let mut invalid_path = vec![0, 0, 0, 0, 1, 0, 0, 0, 2];
+    invalid_path.extend_from_slice(&[0; 33 + 32]);
+    assert_eq!(
+        MapProof::<Hash, Vec<u8>>::from_bytes(&invalid_path),
+        Err(ProofDecodeError::InvalidPath)
+    );
+    // Keys and values are checked before decoding.
+    let mut short_key = vec![1, 0, 0, 0, 0, 2, 0, 0, 0, 1, 2];
+    short_key.extend_from_slice(&[0; 4]);
+    match MapProof::<Hash, Vec<u8>>::from_bytes(&short_key) {
+        Err(ProofDecodeError::InvalidValue(_)) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    let mut short_value = vec![1, 0, 0, 0, 1, 32, 0, 0, 0];
+    short_value.extend_from_slice(&[0; 32]);
+    short_value.extend_from_slice(&[3, 0, 0, 0, 1, 2, 3, 0, 0, 0, 0]);
+    match MapProof::<Hash, u64>::from_bytes(&short_value) {
+        Err(ProofDecodeError::InvalidValue(_)) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
 #[test]
 fn test_invalid_map_proofs() {
     use self::MapProofError::*;
@@ -551,6 +614,40 @@ fn test_invalid_map_proofs() {
     }
 }
 
+#[test]
+fn test_recompute_root() {
+    let db = MemoryDB::new();
+    for &len in &[0, 1, 2, 3, 10, 100] {
+        let mut fork = db.fork();
+        let mut index = ProofMapIndex::new(IDX_NAME, &mut fork);
+        for (key, value) in generate_random_data(len) {
+            index.put(&key, value);
+        }
+        let (root_hash, reachable) = index.recompute_root().unwrap();
+        assert_eq!(root_hash, index.merkle_root());
+        assert_eq!(reachable, len as u64);
+    }
+}
+
+#[test]
+fn test_recompute_root_detects_tampered_value() {
+    let db = MemoryDB::new();
+    let mut fork = db.fork();
+    let data = generate_random_data(10);
+    {
+        let mut index = ProofMapIndex::new(IDX_NAME, &mut fork);
+        for &(ref key, ref value) in &data {
+            index.put(key, value.clone());
+        }
+    }
+    // Replace the value bypassing the index, so that the hashes in the tree are not updated.
+    let path = ProofPath::new(&data[0].0);
+    fork.put(IDX_NAME, path.as_bytes().to_vec(), vec![1, 2, 3]);
+
+    let index: ProofMapIndex<_, [u8; KEY_SIZE], Vec<u8>> = ProofMapIndex::new(IDX_NAME, &fork);
+    assert!(index.recompute_root().is_err());
+}
+
 fn build_proof_in_empty_tree(db: Box<dyn Database>) {
     let mut storage = db.fork();
     let mut table = ProofMapIndex::new(IDX_NAME, &mut storage);
@@ -1385,6 +1482,7 @@ mod memorydb_tests {
     common_tests!{}
 }
 
+#[cfg(feature = "rocksdb")]
 mod rocksdb_tests {
     use std::path::Path;
     use storage::{Database, DbOptions, RocksDB};
@@ -0,0 +1,94 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Read-only wrapper of a database.
+
+use std::path::Path;
+
+use super::{Database, Error, Patch, Result, Snapshot};
+
+/// A database wrapper rejecting all the changes of the wrapped database.
+///
+/// Snapshots and forks of the database work as usual, but merging a patch, compacting
+/// or archiving the data returns an error. Checkpoints can still be written, since they
+/// only read the wrapped database.
+pub struct ReadOnlyDB {
+    inner: Box<dyn Database>,
+}
+
+impl ReadOnlyDB {
+    /// Wraps the given database.
+    pub fn new(inner: Box<dyn Database>) -> Self {
+        Self { inner }
+    }
+
+    fn error() -> Error {
+        Error::new("Database is opened in the read-only mode")
+    }
+}
+
+impl Database for ReadOnlyDB {
+    fn snapshot(&self) -> Box<dyn Snapshot> {
+        self.inner.snapshot()
+    }
+
+    fn merge(&self, _patch: Patch) -> Result<()> {
+        Err(Self::error())
+    }
+
+    fn merge_sync(&self, _patch: Patch) -> Result<()> {
+        Err(Self::error())
+    }
+
+    fn compact(&self, _prefixes: &[String]) -> Result<()> {
+        Err(Self::error())
+    }
+
+    fn archive(&self, _entries: &[(String, Vec<u8>)]) -> Result<()> {
+        Err(Self::error())
+    }
+
+    fn checkpoint(&self, path: &Path) -> Result<()> {
+        self.inner.checkpoint(path)
+    }
+}
+
+impl ::std::fmt::Debug for ReadOnlyDB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        write!(f, "ReadOnlyDB(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReadOnlyDB;
+    use storage::{Database, MemoryDB};
+
+    #[test]
+    fn read_only_db_rejects_changes() {
+        let db = MemoryDB::new();
+        let mut fork = db.fork();
+        fork.put("table", vec![1], vec![2]);
+        db.merge(fork.into_patch()).unwrap();
+
+        let db = ReadOnlyDB::new(Box::new(db));
+        assert_eq!(db.snapshot().get("table", &[1]), Some(vec![2]));
+
+        let mut fork = db.fork();
+        fork.put("table", vec![3], vec![4]);
+        assert!(db.merge(fork.into_patch()).is_err());
+        assert_eq!(db.snapshot().get("table", &[3]), None);
+        assert!(db.compact(&[]).is_err());
+    }
+}
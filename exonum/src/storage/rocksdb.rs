@@ -18,11 +18,23 @@
 
 pub use rocksdb::{BlockBasedOptions as RocksBlockOptions, WriteOptions as RocksDBWriteOptions};
 
-use rocksdb::{self, utils::get_cf_names, DBIterator, Options as RocksDbOptions, WriteBatch};
+use rocksdb::{
+    self, utils::get_cf_names, DBCompressionType, DBIterator, Options as RocksDbOptions, WriteBatch,
+};
 
-use std::{error::Error, fmt, iter::Peekable, mem, path::Path, sync::Arc};
+use std::{
+    collections::Bound::{self, Excluded, Included, Unbounded},
+    error::Error,
+    fmt,
+    iter::Peekable,
+    mem,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use storage::{self, db::Change, Database, DbOptions, Iter, Iterator, Patch, Snapshot};
+use storage::{
+    self, db::Change, CompressionType, Database, DbOptions, Iter, Iterator, Patch, Snapshot,
+};
 
 impl From<rocksdb::Error> for storage::Error {
     fn from(err: rocksdb::Error) -> Self {
@@ -38,17 +50,51 @@ impl From<rocksdb::Error> for storage::Error {
 /// use different databases.
 pub struct RocksDB {
     db: Arc<rocksdb::DB>,
+    path: PathBuf,
 }
 
 impl DbOptions {
     fn to_rocksdb(&self) -> RocksDbOptions {
+        let options = self.resolve();
         let mut defaults = RocksDbOptions::default();
-        defaults.create_if_missing(self.create_if_missing);
-        defaults.set_max_open_files(self.max_open_files.unwrap_or(-1));
+        defaults.create_if_missing(options.create_if_missing);
+        defaults.set_max_open_files(options.max_open_files.unwrap_or(-1));
+        if let Some(compactions) = options.max_background_compactions {
+            defaults.set_max_background_compactions(compactions);
+        }
+        if let Some(buffer_size) = options.write_buffer_size {
+            defaults.set_write_buffer_size(buffer_size);
+        }
+        if options.block_cache_size.is_some() || options.bloom_filter_bits.is_some() {
+            let mut block_options = RocksBlockOptions::default();
+            if let Some(cache_size) = options.block_cache_size {
+                block_options.set_lru_cache(cache_size);
+            }
+            if let Some(bits) = options.bloom_filter_bits {
+                block_options.set_bloom_filter(bits, false);
+            }
+            defaults.set_block_based_table_factory(&block_options);
+        }
+        if let Some(compression_type) = options.compression_type {
+            defaults.set_compression_type(compression_type.into());
+        }
         defaults
     }
 }
 
+impl From<CompressionType> for DBCompressionType {
+    fn from(compression_type: CompressionType) -> Self {
+        match compression_type {
+            CompressionType::None => DBCompressionType::None,
+            CompressionType::Snappy => DBCompressionType::Snappy,
+            CompressionType::Zlib => DBCompressionType::Zlib,
+            CompressionType::Bz2 => DBCompressionType::Bz2,
+            CompressionType::Lz4 => DBCompressionType::Lz4,
+            CompressionType::Lz4hc => DBCompressionType::Lz4hc,
+        }
+    }
+}
+
 /// A snapshot of a `RocksDB`.
 pub struct RocksDBSnapshot {
     snapshot: rocksdb::Snapshot<'static>,
@@ -72,12 +118,15 @@ impl RocksDB {
         let db = {
             if let Ok(names) = get_cf_names(&path) {
                 let cf_names = names.iter().map(|name| name.as_str()).collect::<Vec<_>>();
-                rocksdb::DB::open_cf(&options.to_rocksdb(), path, cf_names.as_ref())?
+                rocksdb::DB::open_cf(&options.to_rocksdb(), &path, cf_names.as_ref())?
             } else {
-                rocksdb::DB::open(&options.to_rocksdb(), path)?
+                rocksdb::DB::open(&options.to_rocksdb(), &path)?
             }
         };
-        Ok(Self { db: Arc::new(db) })
+        Ok(Self {
+            db: Arc::new(db),
+            path: path.as_ref().to_owned(),
+        })
     }
 
     fn do_merge(&self, patch: Patch, w_opts: &RocksDBWriteOptions) -> storage::Result<()> {
@@ -119,6 +168,23 @@ impl Database for RocksDB {
         w_opts.set_sync(true);
         self.do_merge(patch, &w_opts)
     }
+
+    fn compact(&self, prefixes: &[String]) -> storage::Result<()> {
+        let names = get_cf_names(&self.path).map_err(|e| storage::Error::new(e.to_string()))?;
+        let names = names.iter().filter(|name| {
+            prefixes.is_empty()
+                || prefixes
+                    .iter()
+                    .any(|prefix| name.starts_with(prefix.as_str()))
+        });
+        for name in names {
+            if let Some(cf) = self.db.cf_handle(name) {
+                info!("Compacting index {}", name);
+                self.db.compact_range_cf(cf, None, None);
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Snapshot for RocksDBSnapshot {
@@ -148,6 +214,55 @@ impl Snapshot for RocksDBSnapshot {
             value: None,
         })
     }
+
+    fn iter_rev<'a>(&'a self, name: &str, to: Bound<&[u8]>) -> Iter<'a> {
+        use rocksdb::{Direction, IteratorMode};
+        let cf = match self.db.cf_handle(name) {
+            Some(cf) => cf,
+            None => {
+                return Box::new(RocksDBIterator {
+                    iter: self.snapshot.iterator(IteratorMode::End).peekable(),
+                    key: None,
+                    value: None,
+                })
+            }
+        };
+        let iter_from_end = || self.snapshot.iterator_cf(cf, IteratorMode::End).unwrap();
+
+        let mut iter = match to {
+            Included(key) | Excluded(key) => {
+                // Seeking positions the iterator at the first key not less than the bound,
+                // which is skipped unless it satisfies the bound. If there is no such key,
+                // the iteration starts from the last key.
+                let mut iter = self
+                    .snapshot
+                    .iterator_cf(cf, IteratorMode::From(key, Direction::Reverse))
+                    .unwrap()
+                    .peekable();
+                let skip_first = match iter.peek() {
+                    Some(&(ref first, _)) => match to {
+                        Included(..) => &first[..] > key,
+                        _ => &first[..] >= key,
+                    },
+                    None => false,
+                };
+                if skip_first {
+                    iter.next();
+                }
+                let seeked_past_end = iter.peek().is_none() && !skip_first;
+                if seeked_past_end {
+                    iter = iter_from_end().peekable();
+                }
+                iter
+            }
+            Unbounded => iter_from_end().peekable(),
+        };
+        Box::new(RocksDBIterator {
+            iter,
+            key: None,
+            value: None,
+        })
+    }
 }
 
 impl Iterator for RocksDBIterator {
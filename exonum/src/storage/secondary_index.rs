@@ -0,0 +1,625 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An implementation of a secondary index over the entries of another index.
+//!
+//! `SecondaryIndex` maps secondary keys (e.g., names) to the keys of a primary index
+//! (e.g., public keys), so that the entries of the primary index can be looked up
+//! by the secondary key without scanning the primary index. The given section contains
+//! methods related to `SecondaryIndex`, the iterator over the primary keys and
+//! `IndexedProofMapIndex`, which keeps a `ProofMapIndex` and its secondary index in sync.
+
+// spell-checker:ignore subprefix
+
+use std::{borrow::Borrow, fmt, marker::PhantomData};
+
+use super::{
+    base_index::{BaseIndex, BaseIndexIter},
+    indexes_metadata::IndexType,
+    proof_map_index::ProofMapKey,
+    Fork, ProofMapIndex, Snapshot, StorageKey, StorageValue,
+};
+use crypto::{self, Hash};
+
+// Subprefix of the entries mapping secondary keys to primary keys.
+const PRIMARY_KEYS: u8 = 0;
+// Subprefix of the entries mapping primary keys to the hashes of secondary keys.
+const SECONDARY_HASHES: u8 = 1;
+
+/// A mapping from secondary keys to the keys of a primary index.
+///
+/// Every primary key is mapped from at most one secondary key, while several primary
+/// keys may share the same secondary key. The index tracks the secondary key of every
+/// primary key, so that [`insert`] replaces the previous mapping of the primary key
+/// without the need to know the previous secondary key. Thus, the index is kept up to
+/// date by calling `insert` whenever the primary index is written and [`remove`] whenever
+/// an entry is removed from it.
+///
+/// Secondary keys are stored by their hashes, hence the lookup has the same complexity
+/// for keys of any length. `SecondaryIndex` requires that secondary keys implement
+/// the [`StorageKey`] trait and primary keys implement both the [`StorageKey`] and
+/// [`StorageValue`] traits, since the primary keys are stored as values.
+///
+/// Use [`IndexedProofMapIndex`] to have the index updated on every write to the primary
+/// index instead of calling `insert` and `remove` by hand.
+///
+/// [`insert`]: #method.insert
+/// [`remove`]: #method.remove
+/// [`StorageKey`]: ../trait.StorageKey.html
+/// [`StorageValue`]: ../trait.StorageValue.html
+/// [`IndexedProofMapIndex`]: struct.IndexedProofMapIndex.html
+#[derive(Debug)]
+pub struct SecondaryIndex<T, K: ?Sized, PK> {
+    base: BaseIndex<T>,
+    _k: PhantomData<K>,
+    _pk: PhantomData<PK>,
+}
+
+/// Returns an iterator over the primary keys of a `SecondaryIndex`.
+///
+/// This struct is created by the [`primary_keys`] method on [`SecondaryIndex`].
+/// See its documentation for details.
+///
+/// [`primary_keys`]: struct.SecondaryIndex.html#method.primary_keys
+/// [`SecondaryIndex`]: struct.SecondaryIndex.html
+#[derive(Debug)]
+pub struct SecondaryIndexIter<'a, PK> {
+    base_iter: BaseIndexIter<'a, Vec<u8>, PK>,
+}
+
+/// A `ProofMapIndex` paired with a `SecondaryIndex` over its values.
+///
+/// Every [`put`] and [`remove`] writes both indexes, so that the secondary index
+/// always maps the secondary key of every value in the map, as returned by the
+/// `secondary_key` function, to the key of the value. Indexes which existed before
+/// the secondary index was introduced can be indexed once with [`reindex`].
+///
+/// # Examples
+///
+/// ```
+/// use exonum::storage::{MemoryDB, Database, IndexedProofMapIndex};
+/// use exonum::crypto::hash;
+///
+/// fn first_char(value: &String) -> &str {
+///     &value[..1]
+/// }
+///
+/// let db = MemoryDB::new();
+/// let mut fork = db.fork();
+/// let mut index = IndexedProofMapIndex::new("map", "map_index", &mut fork, first_char);
+///
+/// let (alice, bob) = (hash(b"Alice"), hash(b"Bob"));
+/// index.put(&alice, "Alice".to_owned());
+/// index.put(&bob, "Bob".to_owned());
+/// assert_eq!(index.map().get(&alice), Some("Alice".to_owned()));
+/// assert_eq!(index.index().get("B"), Some(bob));
+///
+/// index.put(&bob, "Carol".to_owned());
+/// assert!(!index.index().contains("B"));
+/// ```
+///
+/// [`put`]: #method.put
+/// [`remove`]: #method.remove
+/// [`reindex`]: #method.reindex
+pub struct IndexedProofMapIndex<'a, K, V, SK: ?Sized> {
+    view: &'a mut Fork,
+    map_name: String,
+    index_name: String,
+    secondary_key: fn(&V) -> &SK,
+}
+
+impl<T, K, PK> SecondaryIndex<T, K, PK>
+where
+    T: AsRef<dyn Snapshot>,
+    K: StorageKey + ?Sized,
+    PK: StorageKey + StorageValue + Clone,
+{
+    /// Creates a new index representation based on the name and storage view.
+    ///
+    /// Storage view can be specified as [`&Snapshot`] or [`&mut Fork`]. In the first case, only
+    /// immutable methods are available. In the second case, both immutable and mutable methods are
+    /// available.
+    ///
+    /// [`&Snapshot`]: ../trait.Snapshot.html
+    /// [`&mut Fork`]: ../struct.Fork.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, SecondaryIndex};
+    /// use exonum::crypto::PublicKey;
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let snapshot = db.snapshot();
+    /// let index: SecondaryIndex<_, str, PublicKey> = SecondaryIndex::new(name, &snapshot);
+    /// ```
+    pub fn new<S: AsRef<str>>(index_name: S, view: T) -> Self {
+        Self {
+            base: BaseIndex::new(index_name, IndexType::Secondary, view),
+            _k: PhantomData,
+            _pk: PhantomData,
+        }
+    }
+
+    /// Creates a new index representation based on the name, index ID in family
+    /// and storage view.
+    ///
+    /// Storage view can be specified as [`&Snapshot`] or [`&mut Fork`]. In the first case, only
+    /// immutable methods are available. In the second case, both immutable and mutable methods are
+    /// available.
+    ///
+    /// [`&Snapshot`]: ../trait.Snapshot.html
+    /// [`&mut Fork`]: ../struct.Fork.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, SecondaryIndex};
+    /// use exonum::crypto::PublicKey;
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let index_id = vec![123];
+    /// let snapshot = db.snapshot();
+    /// let index: SecondaryIndex<_, str, PublicKey> =
+    ///     SecondaryIndex::new_in_family(name, &index_id, &snapshot);
+    /// ```
+    pub fn new_in_family<S: AsRef<str>, I: StorageKey>(
+        family_name: S,
+        index_id: &I,
+        view: T,
+    ) -> Self {
+        Self {
+            base: BaseIndex::new_in_family(family_name, index_id, IndexType::Secondary, view),
+            _k: PhantomData,
+            _pk: PhantomData,
+        }
+    }
+
+    /// Returns the least primary key mapped from the secondary key, or `None` if there
+    /// are no such keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, SecondaryIndex};
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let mut fork = db.fork();
+    /// let mut index: SecondaryIndex<_, str, u8> = SecondaryIndex::new(name, &mut fork);
+    /// assert_eq!(index.get("Alice"), None);
+    ///
+    /// index.insert("Alice", &1);
+    /// assert_eq!(index.get("Alice"), Some(1));
+    /// ```
+    pub fn get(&self, key: &K) -> Option<PK> {
+        self.primary_keys(key).next()
+    }
+
+    /// Returns `true` if at least one primary key is mapped from the secondary key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, SecondaryIndex};
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let mut fork = db.fork();
+    /// let mut index: SecondaryIndex<_, str, u8> = SecondaryIndex::new(name, &mut fork);
+    /// assert!(!index.contains("Alice"));
+    ///
+    /// index.insert("Alice", &1);
+    /// assert!(index.contains("Alice"));
+    /// ```
+    pub fn contains(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns an iterator over the primary keys mapped from the secondary key in
+    /// ascending order. The iterator element type is PK.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, SecondaryIndex};
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let mut fork = db.fork();
+    /// let mut index: SecondaryIndex<_, str, u8> = SecondaryIndex::new(name, &mut fork);
+    ///
+    /// index.insert("Alice", &2);
+    /// index.insert("Bob", &3);
+    /// index.insert("Alice", &1);
+    /// assert_eq!(index.primary_keys("Alice").collect::<Vec<_>>(), vec![1, 2]);
+    /// ```
+    pub fn primary_keys(&self, key: &K) -> SecondaryIndexIter<PK> {
+        SecondaryIndexIter {
+            base_iter: self.base.iter(&primary_keys_prefix(&secondary_hash(key))),
+        }
+    }
+
+    /// Returns `true` if the primary key is mapped from any secondary key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, SecondaryIndex};
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let mut fork = db.fork();
+    /// let mut index: SecondaryIndex<_, str, u8> = SecondaryIndex::new(name, &mut fork);
+    /// assert!(!index.contains_primary(&1));
+    ///
+    /// index.insert("Alice", &1);
+    /// assert!(index.contains_primary(&1));
+    /// ```
+    pub fn contains_primary(&self, primary_key: &PK) -> bool {
+        self.base
+            .contains(&prefixed(&[SECONDARY_HASHES], primary_key))
+    }
+
+    /// Returns `true` if the index contains no mappings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, SecondaryIndex};
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let mut fork = db.fork();
+    /// let mut index: SecondaryIndex<_, str, u8> = SecondaryIndex::new(name, &mut fork);
+    /// assert!(index.is_empty());
+    ///
+    /// index.insert("Alice", &1);
+    /// assert!(!index.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.base
+            .iter::<_, Vec<u8>, Hash>(&SECONDARY_HASHES)
+            .next()
+            .is_none()
+    }
+
+    fn secondary_hash_of(&self, primary_key: &PK) -> Option<Hash> {
+        self.base.get(&prefixed(&[SECONDARY_HASHES], primary_key))
+    }
+}
+
+impl<'a, K, PK> SecondaryIndex<&'a mut Fork, K, PK>
+where
+    K: StorageKey + ?Sized,
+    PK: StorageKey + StorageValue + Clone,
+{
+    /// Maps the primary key from the secondary key, replacing the previous mapping
+    /// of the primary key, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, SecondaryIndex};
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let mut fork = db.fork();
+    /// let mut index: SecondaryIndex<_, str, u8> = SecondaryIndex::new(name, &mut fork);
+    ///
+    /// index.insert("Alice", &1);
+    /// index.insert("Bob", &1);
+    /// assert_eq!(index.get("Alice"), None);
+    /// assert_eq!(index.get("Bob"), Some(1));
+    /// ```
+    pub fn insert(&mut self, key: &K, primary_key: &PK) {
+        let hash = secondary_hash(key);
+        match self.secondary_hash_of(primary_key) {
+            Some(ref old_hash) if *old_hash == hash => return,
+            Some(old_hash) => self.remove_primary_key(&old_hash, primary_key),
+            None => {}
+        }
+
+        self.base.put(
+            &prefixed(&primary_keys_prefix(&hash), primary_key),
+            primary_key.clone(),
+        );
+        self.base
+            .put(&prefixed(&[SECONDARY_HASHES], primary_key), hash);
+    }
+
+    /// Removes the mapping of the primary key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, SecondaryIndex};
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let mut fork = db.fork();
+    /// let mut index: SecondaryIndex<_, str, u8> = SecondaryIndex::new(name, &mut fork);
+    ///
+    /// index.insert("Alice", &1);
+    /// index.remove(&1);
+    /// assert!(!index.contains("Alice"));
+    /// ```
+    pub fn remove(&mut self, primary_key: &PK) {
+        if let Some(hash) = self.secondary_hash_of(primary_key) {
+            self.remove_primary_key(&hash, primary_key);
+            self.base
+                .remove(&prefixed(&[SECONDARY_HASHES], primary_key));
+        }
+    }
+
+    /// Maps every primary key from the corresponding secondary key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, SecondaryIndex};
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let mut fork = db.fork();
+    /// let mut index: SecondaryIndex<_, str, u8> = SecondaryIndex::new(name, &mut fork);
+    ///
+    /// index.extend(vec![("Alice", 1), ("Bob", 2)]);
+    /// assert_eq!(index.get("Bob"), Some(2));
+    /// ```
+    pub fn extend<I, Q>(&mut self, entries: I)
+    where
+        I: IntoIterator<Item = (Q, PK)>,
+        Q: Borrow<K>,
+    {
+        for (key, primary_key) in entries {
+            self.insert(key.borrow(), &primary_key);
+        }
+    }
+
+    /// Clears the index, removing all mappings.
+    ///
+    /// # Notes
+    /// Currently, this method is not optimized to delete a large set of data. During the execution of
+    /// this method, the amount of allocated memory is linearly dependent on the number of elements
+    /// in the index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::storage::{MemoryDB, Database, SecondaryIndex};
+    ///
+    /// let db = MemoryDB::new();
+    /// let name = "name";
+    /// let mut fork = db.fork();
+    /// let mut index: SecondaryIndex<_, str, u8> = SecondaryIndex::new(name, &mut fork);
+    ///
+    /// index.insert("Alice", &1);
+    /// index.clear();
+    /// assert!(!index.contains("Alice"));
+    /// ```
+    pub fn clear(&mut self) {
+        self.base.clear()
+    }
+
+    fn remove_primary_key(&mut self, hash: &Hash, primary_key: &PK) {
+        self.base
+            .remove(&prefixed(&primary_keys_prefix(hash), primary_key));
+    }
+}
+
+impl<'a, K, V, SK> IndexedProofMapIndex<'a, K, V, SK>
+where
+    K: ProofMapKey<Output = K> + StorageKey + StorageValue + Clone,
+    V: StorageValue,
+    SK: StorageKey + ?Sized,
+{
+    /// Creates a new representation of the map named `map_name` and its secondary index
+    /// named `index_name`. The secondary key of every value is obtained with `secondary_key`.
+    pub fn new<S: AsRef<str>>(
+        map_name: S,
+        index_name: S,
+        view: &'a mut Fork,
+        secondary_key: fn(&V) -> &SK,
+    ) -> Self {
+        Self {
+            view,
+            map_name: map_name.as_ref().to_owned(),
+            index_name: index_name.as_ref().to_owned(),
+            secondary_key,
+        }
+    }
+
+    /// Returns the primary index.
+    pub fn map(&self) -> ProofMapIndex<&Fork, K, V> {
+        ProofMapIndex::new(&self.map_name, &*self.view)
+    }
+
+    /// Returns the secondary index.
+    pub fn index(&self) -> SecondaryIndex<&Fork, SK, K> {
+        SecondaryIndex::new(&self.index_name, &*self.view)
+    }
+
+    /// Inserts the key-value pair into the map and maps the key from the secondary key
+    /// of the value, replacing the previous mapping of the key.
+    pub fn put(&mut self, key: &K, value: V) {
+        SecondaryIndex::new(&self.index_name, &mut *self.view)
+            .insert((self.secondary_key)(&value), key);
+        ProofMapIndex::new(&self.map_name, &mut *self.view).put(key, value);
+    }
+
+    /// Removes the key from the map and its mapping from the secondary index.
+    pub fn remove(&mut self, key: &K) {
+        SecondaryIndex::<_, SK, K>::new(&self.index_name, &mut *self.view).remove(key);
+        ProofMapIndex::<_, K, V>::new(&self.map_name, &mut *self.view).remove(key);
+    }
+
+    /// Rebuilds the secondary index from the entries of the map.
+    ///
+    /// # Notes
+    /// The entries of the map are collected in memory before they are indexed, so this
+    /// method is intended for one-time migrations of existing maps.
+    pub fn reindex(&mut self) {
+        let entries = self.map().iter().collect::<Vec<_>>();
+        let secondary_key = self.secondary_key;
+        let mut index: SecondaryIndex<_, SK, K> =
+            SecondaryIndex::new(&self.index_name, &mut *self.view);
+        index.clear();
+        index.extend(
+            entries
+                .iter()
+                .map(|&(ref key, ref value)| (secondary_key(value), key.clone())),
+        );
+    }
+}
+
+impl<'a, K, V, SK: ?Sized> fmt::Debug for IndexedProofMapIndex<'a, K, V, SK> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IndexedProofMapIndex")
+            .field("map_name", &self.map_name)
+            .field("index_name", &self.index_name)
+            .finish()
+    }
+}
+
+impl<'a, PK> Iterator for SecondaryIndexIter<'a, PK>
+where
+    PK: StorageValue,
+{
+    type Item = PK;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.base_iter.next().map(|(_, v)| v)
+    }
+}
+
+fn secondary_hash<K: StorageKey + ?Sized>(key: &K) -> Hash {
+    crypto::hash(&prefixed(&[], key))
+}
+
+fn primary_keys_prefix(hash: &Hash) -> Vec<u8> {
+    prefixed(&[PRIMARY_KEYS], hash)
+}
+
+fn prefixed<K: StorageKey + ?Sized>(prefix: &[u8], key: &K) -> Vec<u8> {
+    let mut bytes = vec![0; prefix.len() + key.size()];
+    bytes[..prefix.len()].copy_from_slice(prefix);
+    key.write(&mut bytes[prefix.len()..]);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IndexedProofMapIndex, SecondaryIndex};
+    use crypto::hash;
+    use storage::{Database, MemoryDB, ProofMapIndex};
+
+    const IDX_NAME: &str = "idx_name";
+
+    #[test]
+    fn secondary_keys_do_not_overlap() {
+        let db = MemoryDB::new();
+        let mut fork = db.fork();
+        let mut index: SecondaryIndex<_, str, u64> = SecondaryIndex::new(IDX_NAME, &mut fork);
+
+        index.insert("Al", &3);
+        index.insert("Alice", &1);
+        index.insert("Alice", &2);
+        assert_eq!(index.primary_keys("Al").collect::<Vec<_>>(), vec![3]);
+        assert_eq!(index.primary_keys("Alice").collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(index.primary_keys("A").count(), 0);
+    }
+
+    #[test]
+    fn insert_replaces_previous_mapping() {
+        let db = MemoryDB::new();
+        let mut fork = db.fork();
+        let mut index: SecondaryIndex<_, str, u64> = SecondaryIndex::new(IDX_NAME, &mut fork);
+
+        index.insert("Alice", &1);
+        index.insert("Alice", &2);
+        index.insert("Bob", &1);
+        assert_eq!(index.primary_keys("Alice").collect::<Vec<_>>(), vec![2]);
+        assert_eq!(index.primary_keys("Bob").collect::<Vec<_>>(), vec![1]);
+
+        index.remove(&2);
+        assert!(!index.contains("Alice"));
+        assert!(!index.contains_primary(&2));
+        assert!(index.contains_primary(&1));
+        // Removing an unknown primary key is a no-op.
+        index.remove(&2);
+        assert_eq!(index.get("Bob"), Some(1));
+    }
+
+    #[test]
+    fn family_indexes_are_isolated() {
+        let db = MemoryDB::new();
+        let mut fork = db.fork();
+        {
+            let mut index: SecondaryIndex<_, str, u64> =
+                SecondaryIndex::new_in_family(IDX_NAME, &1_u8, &mut fork);
+            index.insert("Alice", &1);
+        }
+        let index: SecondaryIndex<_, str, u64> =
+            SecondaryIndex::new_in_family(IDX_NAME, &2_u8, &mut fork);
+        assert!(!index.contains("Alice"));
+    }
+
+    #[cfg_attr(feature = "cargo-clippy", allow(clippy::ptr_arg))]
+    fn name(value: &String) -> &str {
+        value
+    }
+
+    #[test]
+    fn indexed_map_keeps_index_in_sync() {
+        let db = MemoryDB::new();
+        let mut fork = db.fork();
+        let mut map = IndexedProofMapIndex::new(IDX_NAME, "idx_name.names", &mut fork, name);
+        let (first, second) = (hash(&[1]), hash(&[2]));
+
+        map.put(&first, "Alice".to_owned());
+        map.put(&second, "Bob".to_owned());
+        map.put(&second, "Alice".to_owned());
+        assert_eq!(map.map().get(&second), Some("Alice".to_owned()));
+        assert_eq!(map.index().primary_keys("Alice").count(), 2);
+        assert!(!map.index().contains("Bob"));
+
+        map.remove(&first);
+        assert_eq!(map.map().get(&first), None);
+        assert_eq!(
+            map.index().primary_keys("Alice").collect::<Vec<_>>(),
+            vec![second]
+        );
+    }
+
+    #[test]
+    fn reindex_existing_entries() {
+        let db = MemoryDB::new();
+        let mut fork = db.fork();
+        let (first, second) = (hash(&[1]), hash(&[2]));
+        {
+            let mut map = ProofMapIndex::new(IDX_NAME, &mut fork);
+            map.put(&first, "Alice".to_owned());
+            map.put(&second, "Bob".to_owned());
+        }
+
+        let mut map = IndexedProofMapIndex::new(IDX_NAME, "idx_name.names", &mut fork, name);
+        assert!(!map.index().contains("Alice"));
+        map.reindex();
+        assert_eq!(map.index().get("Alice"), Some(first));
+        assert_eq!(map.index().get("Bob"), Some(second));
+    }
+}
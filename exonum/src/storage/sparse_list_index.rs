@@ -83,6 +83,30 @@ impl StorageValue for SparseListSize {
 /// as an index.
 /// `SparseListIndex` requires that elements should implement the [`StorageValue`] trait.
 ///
+/// Since indices of the elements are never shifted, `SparseListIndex` is suitable for
+/// queues whose items are referenced by their indices and are consumed out of order.
+///
+/// # Examples
+///
+/// ```
+/// use exonum::storage::{MemoryDB, Database, SparseListIndex};
+///
+/// let db = MemoryDB::new();
+/// let mut fork = db.fork();
+/// let mut pending = SparseListIndex::new("pending", &mut fork);
+/// pending.extend(vec![10_u64, 20, 30]);
+///
+/// // Items are consumed out of order; the indices of the remaining items stay the same.
+/// assert_eq!(Some(20), pending.remove(1));
+/// assert_eq!(Some(30), pending.get(2));
+/// assert_eq!(vec![(0, 10), (2, 30)], pending.iter().collect::<Vec<_>>());
+///
+/// // New items get new indices.
+/// pending.push(40);
+/// assert_eq!(Some(40), pending.get(3));
+/// assert_eq!(3, pending.len());
+/// ```
+///
 /// [`StorageValue`]: ../trait.StorageValue.html
 /// [`ListIndex`]: <../list_index/struct.ListIndex.html>
 #[derive(Debug)]
@@ -446,7 +470,7 @@ where
     /// assert_eq!(None, index.remove(0));
     /// assert_eq!(2, index.capacity());
     /// assert_eq!(1, index.len());
-
+    ///
     /// assert_eq!(Some(12), index.remove(1));
     /// assert_eq!(2, index.capacity());
     /// ```
@@ -779,6 +803,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "rocksdb")]
     mod rocksdb_tests {
         use std::path::Path;
         use storage::{Database, DbOptions, RocksDB};
@@ -12,9 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::{Database, Fork, Snapshot};
+use std::{collections::Bound::Unbounded, sync::Arc};
+
+use super::{Database, Fork, ListIndex, MapIndex, PatchedSnapshot, Snapshot};
 
 const IDX_NAME: &'static str = "idx_name";
+const LIST_NAME: &'static str = "list_name";
+const MAP_NAME: &'static str = "map_name";
 
 fn fork_iter<T: Database>(db: T) {
     let mut fork = db.fork();
@@ -179,8 +183,114 @@ fn changelog<T: Database>(db: T) {
     assert_eq!(fork.get(IDX_NAME, &[4]), None);
 }
 
+fn write_batch<T: Database>(db: T) {
+    fn stage(fork: &mut Fork, value: u64, fail: bool) -> Result<(), String> {
+        let mut batch = fork.write_batch();
+        ListIndex::new(LIST_NAME, &mut *batch).push(value);
+        if fail {
+            return Err(format!("failed to stage {}", value));
+        }
+        MapIndex::new(MAP_NAME, &mut *batch).put(&value, value * 10);
+        batch.commit();
+        Ok(())
+    }
+
+    fn assert_state<S: AsRef<dyn Snapshot>>(view: S, values: &[u64]) {
+        let list = ListIndex::new(LIST_NAME, view.as_ref());
+        let map = MapIndex::new(MAP_NAME, view.as_ref());
+        assert_eq!(list.iter().collect::<Vec<u64>>(), values);
+        assert_eq!(
+            map.iter().collect::<Vec<(u64, u64)>>(),
+            values.iter().map(|&v| (v, v * 10)).collect::<Vec<_>>()
+        );
+    }
+
+    let mut fork = db.fork();
+    stage(&mut fork, 1, false).unwrap();
+    db.merge(fork.into_patch()).unwrap();
+
+    let mut fork = db.fork();
+    stage(&mut fork, 2, false).unwrap();
+    assert_state(&fork, &[1, 2]);
+
+    // No partial state is visible after a failure in the middle of the batch.
+    assert!(stage(&mut fork, 3, true).is_err());
+    assert_state(&fork, &[1, 2]);
+
+    // Committed batches are rolled back together with the checkpoint.
+    fork.checkpoint();
+    stage(&mut fork, 3, false).unwrap();
+    assert_state(&fork, &[1, 2, 3]);
+    assert!(stage(&mut fork, 4, true).is_err());
+    assert_state(&fork, &[1, 2, 3]);
+    fork.rollback();
+    assert_state(&fork, &[1, 2]);
+
+    // Nested batches.
+    {
+        let mut batch = fork.write_batch();
+        stage(&mut batch, 3, false).unwrap();
+        assert!(stage(&mut batch, 4, true).is_err());
+        stage(&mut batch, 5, false).unwrap();
+        assert_state(&*batch, &[1, 2, 3, 5]);
+    }
+    assert_state(&fork, &[1, 2]);
+    {
+        let mut batch = fork.write_batch();
+        stage(&mut batch, 3, false).unwrap();
+        assert!(stage(&mut batch, 4, true).is_err());
+        batch.commit();
+    }
+    assert_state(&fork, &[1, 2, 3]);
+
+    db.merge(fork.into_patch()).unwrap();
+    assert_state(db.snapshot(), &[1, 2, 3]);
+}
+
+fn patched_snapshot<T: Database>(db: T) {
+    let mut fork = db.fork();
+    fork.put(IDX_NAME, vec![10], vec![10]);
+    fork.put(IDX_NAME, vec![20], vec![20]);
+    fork.put(IDX_NAME, vec![30], vec![30]);
+    db.merge(fork.into_patch()).unwrap();
+
+    let mut fork = db.fork();
+    fork.put(IDX_NAME, vec![5], vec![5]);
+    fork.put(IDX_NAME, vec![20], vec![21]);
+    fork.remove(IDX_NAME, vec![30]);
+    let patch = Arc::new(fork.into_patch());
+
+    let snapshot = PatchedSnapshot::new(db.snapshot(), Arc::clone(&patch));
+    assert_eq!(snapshot.get(IDX_NAME, &[5]), Some(vec![5]));
+    assert_eq!(snapshot.get(IDX_NAME, &[10]), Some(vec![10]));
+    assert_eq!(snapshot.get(IDX_NAME, &[20]), Some(vec![21]));
+    assert!(!snapshot.contains(IDX_NAME, &[30]));
+
+    let mut values = Vec::new();
+    let mut iter = snapshot.iter(IDX_NAME, &[]);
+    while let Some((k, v)) = iter.next() {
+        values.push((k[0], v[0]));
+    }
+    assert_eq!(values, vec![(5, 5), (10, 10), (20, 21)]);
+
+    let mut values = Vec::new();
+    let mut iter = snapshot.iter_rev(IDX_NAME, Unbounded);
+    while let Some((k, v)) = iter.next() {
+        values.push((k[0], v[0]));
+    }
+    assert_eq!(values, vec![(20, 21), (10, 10), (5, 5)]);
+
+    // The patch is shared rather than copied into the snapshot.
+    assert_eq!(Arc::strong_count(&patch), 2);
+    drop(snapshot);
+    assert_eq!(Arc::strong_count(&patch), 1);
+}
+
 mod memorydb_tests {
-    use super::super::MemoryDB;
+    use std::{sync::Arc, thread};
+
+    use super::super::{Database, MemoryDB, Snapshot, TieredDB};
+    use super::IDX_NAME;
 
     fn memorydb_database() -> MemoryDB {
         MemoryDB::new()
@@ -195,10 +305,240 @@ mod memorydb_tests {
     fn test_memory_changelog() {
         super::changelog(memorydb_database());
     }
+
+    #[test]
+    fn test_memory_write_batch() {
+        super::write_batch(memorydb_database());
+    }
+
+    #[test]
+    fn test_memory_patched_snapshot() {
+        super::patched_snapshot(memorydb_database());
+    }
+
+    #[test]
+    fn test_tiered_fork_iter() {
+        super::fork_iter(tiered_database());
+    }
+
+    #[test]
+    fn test_tiered_archive() {
+        let db = tiered_database();
+        let mut fork = db.fork();
+        fork.put(IDX_NAME, vec![1, 1], vec![1]);
+        fork.put(IDX_NAME, vec![1, 2], vec![2]);
+        fork.put(IDX_NAME, vec![2, 1], vec![3]);
+        db.merge(fork.into_patch()).unwrap();
+
+        db.archive(&[(IDX_NAME.to_owned(), vec![1])]).unwrap();
+
+        let snapshot = db.snapshot();
+        assert_eq!(snapshot.get(IDX_NAME, &[1, 1]), Some(vec![1]));
+        assert!(snapshot.contains(IDX_NAME, &[1, 2]));
+        assert_eq!(snapshot.get(IDX_NAME, &[2, 1]), Some(vec![3]));
+
+        // New changes of the archived entries shadow the cold values.
+        let mut fork = db.fork();
+        fork.put(IDX_NAME, vec![1, 2], vec![4]);
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = db.snapshot();
+        let mut values = Vec::new();
+        let mut iter = snapshot.iter(IDX_NAME, &[]);
+        while let Some((k, v)) = iter.next() {
+            values.push((k.to_vec(), v[0]));
+        }
+        assert_eq!(
+            values,
+            vec![(vec![1, 1], 1), (vec![1, 2], 4), (vec![2, 1], 3)]
+        );
+    }
+
+    #[test]
+    fn test_tiered_remove_archived() {
+        let db = tiered_database();
+        assert!(db.supports_archive());
+        let mut fork = db.fork();
+        fork.put(IDX_NAME, vec![1, 1], vec![1]);
+        fork.put(IDX_NAME, vec![1, 2], vec![2]);
+        db.merge(fork.into_patch()).unwrap();
+        db.archive(&[(IDX_NAME.to_owned(), vec![1])]).unwrap();
+
+        // Removals hide the archived values.
+        let mut fork = db.fork();
+        fork.remove(IDX_NAME, vec![1, 1]);
+        db.merge(fork.into_patch()).unwrap();
+        let snapshot = db.snapshot();
+        assert_eq!(snapshot.get(IDX_NAME, &[1, 1]), None);
+        assert_eq!(snapshot.get(IDX_NAME, &[1, 2]), Some(vec![2]));
+
+        let mut fork = db.fork();
+        fork.remove_by_prefix(IDX_NAME, None);
+        db.merge_sync(fork.into_patch()).unwrap();
+        let snapshot = db.snapshot();
+        assert!(snapshot.iter(IDX_NAME, &[]).next().is_none());
+    }
+
+    #[test]
+    fn test_tiered_concurrent_snapshots() {
+        const ITERATIONS: usize = 1_000;
+
+        // An entry is moved between two keys; the snapshots must see it at one of them.
+        let db = Arc::new(tiered_database());
+        let mut fork = db.fork();
+        fork.put(IDX_NAME, vec![1], vec![1]);
+        db.merge(fork.into_patch()).unwrap();
+
+        let writer = {
+            let db = Arc::clone(&db);
+            thread::spawn(move || {
+                for _ in 0..ITERATIONS {
+                    db.archive(&[(IDX_NAME.to_owned(), vec![1])]).unwrap();
+                    let mut fork = db.fork();
+                    fork.remove(IDX_NAME, vec![1]);
+                    fork.put(IDX_NAME, vec![2], vec![1]);
+                    db.merge(fork.into_patch()).unwrap();
+
+                    let mut fork = db.fork();
+                    fork.remove(IDX_NAME, vec![2]);
+                    fork.put(IDX_NAME, vec![1], vec![1]);
+                    db.merge_sync(fork.into_patch()).unwrap();
+                }
+            })
+        };
+
+        for _ in 0..ITERATIONS {
+            let snapshot = db.snapshot();
+            assert_ne!(
+                snapshot.contains(IDX_NAME, &[1]),
+                snapshot.contains(IDX_NAME, &[2])
+            );
+        }
+        writer.join().unwrap();
+    }
+
+    fn tiered_database() -> TieredDB {
+        TieredDB::new(Box::new(memorydb_database()), Box::new(memorydb_database()))
+    }
+}
+
+mod journaldb_tests {
+    use std::{
+        fs::{self, OpenOptions},
+        path::Path,
+    };
+    use storage::{Database, DbOptions, JournalDB, ListIndex};
+    use tempdir::TempDir;
+
+    fn journaldb_database(path: &Path) -> JournalDB {
+        let options = DbOptions::default();
+        JournalDB::open(path, &options).unwrap()
+    }
+
+    fn push_values(db: &JournalDB, values: &[u64]) {
+        let mut fork = db.fork();
+        {
+            let mut index = ListIndex::new("list_index", &mut fork);
+            index.extend(values.iter().cloned());
+        }
+        db.merge(fork.into_patch()).unwrap();
+    }
+
+    fn stored_values(db: &JournalDB) -> Vec<u64> {
+        let snapshot = db.snapshot();
+        let index: ListIndex<_, u64> = ListIndex::new("list_index", &snapshot);
+        index.iter().collect()
+    }
+
+    #[test]
+    fn test_journaldb_fork_iter() {
+        let dir = TempDir::new("exonum_journaldb1").unwrap();
+        super::fork_iter(journaldb_database(dir.path()));
+    }
+
+    #[test]
+    fn test_journaldb_changelog() {
+        let dir = TempDir::new("exonum_journaldb2").unwrap();
+        super::changelog(journaldb_database(dir.path()));
+    }
+
+    #[test]
+    fn test_journaldb_reopen() {
+        let dir = TempDir::new("exonum_journaldb_reopen").unwrap();
+        {
+            let db = journaldb_database(dir.path());
+            push_values(&db, &[1, 2]);
+            push_values(&db, &[3]);
+        }
+        {
+            let db = journaldb_database(dir.path());
+            assert_eq!(stored_values(&db), vec![1, 2, 3]);
+            push_values(&db, &[4]);
+        }
+        let db = journaldb_database(dir.path());
+        assert_eq!(stored_values(&db), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_journaldb_incomplete_record() {
+        let dir = TempDir::new("exonum_journaldb_incomplete").unwrap();
+        {
+            let db = journaldb_database(dir.path());
+            push_values(&db, &[1, 2]);
+        }
+        let journal_path = dir.path().join("journal");
+        {
+            // The journal is compacted when the database is opened.
+            let db = journaldb_database(dir.path());
+            let journal_len = fs::metadata(&journal_path).unwrap().len();
+            push_values(&db, &[3]);
+
+            // Emulate a crash during writing the last record.
+            let file = OpenOptions::new().write(true).open(&journal_path).unwrap();
+            let full_len = file.metadata().unwrap().len();
+            file.set_len(journal_len + (full_len - journal_len) / 2).unwrap();
+        }
+
+        let db = journaldb_database(dir.path());
+        assert_eq!(stored_values(&db), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_journaldb_corrupted_record() {
+        let dir = TempDir::new("exonum_journaldb_corrupted").unwrap();
+        let journal_path = dir.path().join("journal");
+        let corrupted_offset = {
+            let db = journaldb_database(dir.path());
+            let journal_len = fs::metadata(&journal_path).unwrap().len();
+            push_values(&db, &[1]);
+            push_values(&db, &[2]);
+            journal_len
+        };
+
+        // Corrupt the record followed by another record.
+        let mut journal = fs::read(&journal_path).unwrap();
+        journal[corrupted_offset as usize + 8] ^= 1;
+        fs::write(&journal_path, &journal).unwrap();
+
+        assert!(JournalDB::open(dir.path(), &DbOptions::default()).is_err());
+        // The journal is not compacted.
+        assert_eq!(fs::read(&journal_path).unwrap(), journal);
+    }
+
+    #[test]
+    fn test_journaldb_missing() {
+        let dir = TempDir::new("exonum_journaldb_missing").unwrap();
+        let options = DbOptions {
+            create_if_missing: false,
+            ..DbOptions::default()
+        };
+        assert!(JournalDB::open(dir.path().join("db"), &options).is_err());
+    }
 }
 
+#[cfg(feature = "rocksdb")]
 mod rocksdb_tests {
-    use super::super::{DbOptions, RocksDB};
+    use super::super::{CompressionType, DbOptions, DbProfile, RocksDB};
     use std::path::Path;
     use storage::{Database, ListIndex, Snapshot};
     use tempdir::TempDir;
@@ -222,6 +562,38 @@ mod rocksdb_tests {
         super::changelog(rocksdb_database(path));
     }
 
+    #[test]
+    fn test_rocksdb_write_batch() {
+        let dir = TempDir::new("exonum_rocksdb_write_batch").unwrap();
+        let path = dir.path();
+        super::write_batch(rocksdb_database(path));
+    }
+
+    #[test]
+    fn test_rocksdb_tuning_options() {
+        let dir = TempDir::new("exonum_rocksdb_tuning").unwrap();
+        let options = DbOptions {
+            max_open_files: Some(64),
+            block_cache_size: Some(1 << 20),
+            compression_type: Some(CompressionType::Lz4),
+            ..DbOptions::default()
+        };
+        super::fork_iter(RocksDB::open(dir.path(), &options).unwrap());
+    }
+
+    #[test]
+    fn test_rocksdb_tuning_profiles() {
+        for &profile in &[
+            DbProfile::LowMemory,
+            DbProfile::Throughput,
+            DbProfile::Archival,
+        ] {
+            let dir = TempDir::new("exonum_rocksdb_profile").unwrap();
+            let options = DbOptions::with_profile(profile);
+            super::fork_iter(RocksDB::open(dir.path(), &options).unwrap());
+        }
+    }
+
     #[ignore]
     #[test]
     fn test_multiple_patch() {
@@ -0,0 +1,248 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An implementation of the database with the data split between two tiers.
+
+use std::{
+    cmp::Ordering,
+    collections::Bound,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use super::{Change, Database, Iter, Iterator, Patch, Result, Snapshot};
+
+/// Database keeping the rarely accessed data in a secondary (cold) database.
+///
+/// All the changes are written to the primary (hot) database. The entries are moved to
+/// the cold database with [`archive`], after which they are read from the cold database
+/// transparently: a snapshot looks up the hot database first and falls back to the cold one.
+///
+/// Hence, only the entries which are not changed afterwards, such as the data of the
+/// committed blocks, should be archived. This allows keeping the hot database on a small
+/// fast disk, while the bulk of the history resides on a cheaper one. The removals are
+/// applied to both databases, so that the removed entries are not read from the cold one.
+///
+/// [`archive`]: ../trait.Database.html#method.archive
+pub struct TieredDB {
+    hot: Box<dyn Database>,
+    cold: Box<dyn Database>,
+    /// Serializes archiving with the merges removing entries, so that an entry removed
+    /// during archiving is not moved to the cold database afterwards. Snapshots are
+    /// taken under the lock as well, since both operations change the two databases
+    /// one after another.
+    lock: Mutex<()>,
+}
+
+impl TieredDB {
+    /// Creates a database on top of the given hot and cold databases.
+    pub fn new(hot: Box<dyn Database>, cold: Box<dyn Database>) -> Self {
+        Self {
+            hot,
+            cold,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<()> {
+        self.lock
+            .lock()
+            .expect("Unable to lock the tiered database")
+    }
+
+    /// Returns a patch applying the removals contained in the given patch to the cold
+    /// database, or `None` if the patch removes nothing.
+    fn cold_removals(&self, patch: &Patch) -> Option<Patch> {
+        let mut removals = patch
+            .iter()
+            .flat_map(|(name, changes)| {
+                changes
+                    .iter()
+                    .filter(|&(_, change)| *change == Change::Delete)
+                    .map(move |(key, _)| (name, key))
+            }).peekable();
+        removals.peek()?;
+
+        let mut fork = self.cold.fork();
+        for (name, key) in removals {
+            fork.remove(name, key.clone());
+        }
+        Some(fork.into_patch())
+    }
+}
+
+impl Database for TieredDB {
+    fn snapshot(&self) -> Box<dyn Snapshot> {
+        // Otherwise, the snapshot could miss an entry removed from the cold database
+        // but not yet written to the hot one.
+        let _lock = self.lock();
+        Box::new(TieredSnapshot {
+            hot: self.hot.snapshot(),
+            cold: self.cold.snapshot(),
+        })
+    }
+
+    fn merge(&self, patch: Patch) -> Result<()> {
+        let removals = match self.cold_removals(&patch) {
+            Some(removals) => removals,
+            None => return self.hot.merge(patch),
+        };
+        // The removals are applied to the cold database first, so that a failure
+        // in between does not make the cold values visible again.
+        let _lock = self.lock();
+        self.cold.merge(removals)?;
+        self.hot.merge(patch)
+    }
+
+    fn merge_sync(&self, patch: Patch) -> Result<()> {
+        let removals = match self.cold_removals(&patch) {
+            Some(removals) => removals,
+            None => return self.hot.merge_sync(patch),
+        };
+        let _lock = self.lock();
+        self.cold.merge_sync(removals)?;
+        self.hot.merge_sync(patch)
+    }
+
+    fn flush(&self) -> Result<()> {
+        self.cold.flush()?;
+        self.hot.flush()
+    }
+
+    fn compact(&self, prefixes: &[String]) -> Result<()> {
+        self.hot.compact(prefixes)?;
+        self.cold.compact(prefixes)
+    }
+
+    fn archive(&self, entries: &[(String, Vec<u8>)]) -> Result<()> {
+        let _lock = self.lock();
+        let snapshot = self.hot.snapshot();
+        let mut cold_fork = self.cold.fork();
+        let mut hot_fork = self.hot.fork();
+        for &(ref name, ref prefix) in entries {
+            let mut iter = snapshot.iter(name, prefix);
+            while let Some((key, value)) = iter.next() {
+                if !key.starts_with(prefix) {
+                    break;
+                }
+                cold_fork.put(name, key.to_vec(), value.to_vec());
+                hot_fork.remove(name, key.to_vec());
+            }
+        }
+        // The entries are removed from the hot database only after they are persisted
+        // in the cold one, so a failure in between leaves them readable.
+        self.cold.merge_sync(cold_fork.into_patch())?;
+        self.hot.merge(hot_fork.into_patch())
+    }
+
+    fn supports_archive(&self) -> bool {
+        true
+    }
+}
+
+impl From<TieredDB> for Arc<dyn Database> {
+    fn from(db: TieredDB) -> Self {
+        Self::from(Box::new(db) as Box<dyn Database>)
+    }
+}
+
+impl ::std::fmt::Debug for TieredDB {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        write!(f, "TieredDB(..)")
+    }
+}
+
+struct TieredSnapshot {
+    hot: Box<dyn Snapshot>,
+    cold: Box<dyn Snapshot>,
+}
+
+impl Snapshot for TieredSnapshot {
+    fn get(&self, name: &str, key: &[u8]) -> Option<Vec<u8>> {
+        self.hot.get(name, key).or_else(|| self.cold.get(name, key))
+    }
+
+    fn contains(&self, name: &str, key: &[u8]) -> bool {
+        self.hot.contains(name, key) || self.cold.contains(name, key)
+    }
+
+    fn iter<'a>(&'a self, name: &str, from: &[u8]) -> Iter<'a> {
+        Box::new(TieredIter {
+            hot: self.hot.iter(name, from),
+            cold: self.cold.iter(name, from),
+            reversed: false,
+        })
+    }
+
+    fn iter_rev<'a>(&'a self, name: &str, to: Bound<&[u8]>) -> Iter<'a> {
+        Box::new(TieredIter {
+            hot: self.hot.iter_rev(name, to),
+            cold: self.cold.iter_rev(name, to),
+            reversed: true,
+        })
+    }
+}
+
+/// Iterator merging the entries of the hot and cold databases in the key order.
+/// The hot entry is preferred if both databases contain the same key.
+struct TieredIter<'a> {
+    hot: Iter<'a>,
+    cold: Iter<'a>,
+    reversed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Tier {
+    Hot,
+    Cold,
+}
+
+impl<'a> TieredIter<'a> {
+    /// Returns the tier containing the next entry.
+    fn next_tier(&mut self) -> Option<Tier> {
+        let reversed = self.reversed;
+        let order = match (self.hot.peek(), self.cold.peek()) {
+            (Some((hot_key, _)), Some((cold_key, _))) if reversed => {
+                Some(hot_key.cmp(cold_key).reverse())
+            }
+            (Some((hot_key, _)), Some((cold_key, _))) => Some(hot_key.cmp(cold_key)),
+            (Some(_), None) => Some(Ordering::Less),
+            (None, Some(_)) => Some(Ordering::Greater),
+            (None, None) => None,
+        };
+        match order? {
+            Ordering::Less => Some(Tier::Hot),
+            Ordering::Greater => Some(Tier::Cold),
+            Ordering::Equal => {
+                self.cold.next();
+                Some(Tier::Hot)
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for TieredIter<'a> {
+    fn next(&mut self) -> Option<(&[u8], &[u8])> {
+        match self.next_tier()? {
+            Tier::Hot => self.hot.next(),
+            Tier::Cold => self.cold.next(),
+        }
+    }
+
+    fn peek(&mut self) -> Option<(&[u8], &[u8])> {
+        match self.next_tier()? {
+            Tier::Hot => self.hot.peek(),
+            Tier::Cold => self.cold.peek(),
+        }
+    }
+}
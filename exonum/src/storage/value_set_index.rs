@@ -397,3 +397,54 @@ impl<'a> Iterator for ValueSetIndexHashes<'a> {
         self.base_iter.next().map(|(k, ..)| k)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Database, MemoryDB};
+    use super::*;
+    use crypto::CryptoHash;
+
+    const INDEX_NAME: &str = "test_index_name";
+
+    #[test]
+    fn str_value() {
+        let db = MemoryDB::new();
+        let mut fork = db.fork();
+
+        const VALUE: &str = "value_1";
+
+        let mut index: ValueSetIndex<_, String> = ValueSetIndex::new(INDEX_NAME, &mut fork);
+        assert_eq!(false, index.contains(&VALUE.to_owned()));
+
+        index.insert(VALUE.to_owned());
+        assert_eq!(true, index.contains(&VALUE.to_owned()));
+        assert_eq!(true, index.contains_by_hash(&VALUE.to_owned().hash()));
+
+        index.remove_by_hash(&VALUE.to_owned().hash());
+        assert_eq!(false, index.contains(&VALUE.to_owned()));
+    }
+
+    #[test]
+    fn iteration_by_hashes() {
+        let db = MemoryDB::new();
+        let mut fork = db.fork();
+
+        let mut index: ValueSetIndex<_, u64> = ValueSetIndex::new(INDEX_NAME, &mut fork);
+        for value in 0..10 {
+            index.insert(value);
+        }
+        // Inserting the same value twice does not duplicate it.
+        index.insert(5);
+
+        let mut hashes = (0..10_u64).map(|value| value.hash()).collect::<Vec<_>>();
+        hashes.sort();
+        assert_eq!(index.hashes().collect::<Vec<_>>(), hashes);
+        assert_eq!(
+            index.hashes_from(&hashes[5]).collect::<Vec<_>>(),
+            &hashes[5..]
+        );
+        assert!(index
+            .iter()
+            .all(|(hash, value)| hash == value.hash() && value < 10));
+    }
+}
@@ -210,6 +210,32 @@ impl StorageValue for i64 {
     }
 }
 
+/// Uses little-endian encoding.
+impl StorageValue for u128 {
+    fn into_bytes(self) -> Vec<u8> {
+        let mut v = vec![0; 16];
+        LittleEndian::write_u128(&mut v, self);
+        v
+    }
+
+    fn from_bytes(value: Cow<[u8]>) -> Self {
+        LittleEndian::read_u128(value.as_ref())
+    }
+}
+
+/// Uses little-endian encoding.
+impl StorageValue for i128 {
+    fn into_bytes(self) -> Vec<u8> {
+        let mut v = vec![0; 16];
+        LittleEndian::write_i128(&mut v, self);
+        v
+    }
+
+    fn from_bytes(value: Cow<[u8]>) -> Self {
+        LittleEndian::read_i128(value.as_ref())
+    }
+}
+
 impl StorageValue for Hash {
     fn into_bytes(self) -> Vec<u8> {
         self.as_ref().to_vec()
@@ -382,6 +408,20 @@ mod tests {
         assert_round_trip_eq(&values);
     }
 
+    #[test]
+    fn u128_round_trip() {
+        let values = [u128::min_value(), 1, u128::max_value()];
+
+        assert_round_trip_eq(&values);
+    }
+
+    #[test]
+    fn i128_round_trip() {
+        let values = [i128::min_value(), -1, 0, 1, i128::max_value()];
+
+        assert_round_trip_eq(&values);
+    }
+
     #[test]
     fn bool_round_trip() {
         let values = [false, true];
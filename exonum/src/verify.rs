@@ -0,0 +1,201 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of the blockchain data by light clients.
+//!
+//! The module gathers the checks a client needs to trust the data returned by a node
+//! without access to the blockchain storage: verification of block headers against
+//! the validator keys, and verification of the Merkle proofs of lists and maps.
+//! None of them depends on a database backend or on the node; the proofs may be
+//! transmitted as JSON or in their compact binary encoding (see `ListProof::to_bytes`
+//! and `MapProof::to_bytes`).
+//!
+//! A typical proof of a service data returned by a node consists of three parts:
+//!
+//! - a [`BlockProof`], i.e., the latest block header with the precommits of the validators,
+//!   checked with [`BlockProof::verify`];
+//! - a [`MapProof`] of the root hash of a service table in the `state_hash` of the block,
+//!   checked together with the block by [`verify_table_proof`];
+//! - a proof of the data in the service table, e.g., a `MapProof` or a [`ListProof`],
+//!   checked against the root hash of the table returned by `verify_table_proof`.
+//!
+//! Note that the module is a part of the `exonum` crate and is thus built together with
+//! the node stack. Clients which cannot afford it, e.g., embedded devices or WASM clients,
+//! should use the standalone `exonum-verify` crate, which depends only on `exonum-crypto`
+//! and verifies the blocks and the proofs in their binary encoding.
+//!
+//! [`BlockProof`]: ../blockchain/struct.BlockProof.html
+//! [`BlockProof::verify`]: ../blockchain/struct.BlockProof.html#method.verify
+//! [`MapProof`]: ../storage/proof_map_index/struct.MapProof.html
+//! [`ListProof`]: ../storage/proof_list_index/enum.ListProof.html
+//! [`verify_table_proof`]: fn.verify_table_proof.html
+
+use failure;
+
+pub use blockchain::{verify_block, Block, BlockProof, ValidatorKeys};
+pub use storage::{
+    proof_list_index::{ListProof, ListProofError},
+    proof_map_index::{CheckedMapProof, MapProof, MapProofError},
+    ProofDecodeError,
+};
+
+use blockchain::Blockchain;
+use crypto::Hash;
+
+/// Verifies that the block is authenticated by the supermajority of the validators
+/// and that `to_table` proves the root hash of the table with index `table_idx` of
+/// the service with `service_id` in the `state_hash` of the block.
+///
+/// Returns the root hash of the table, against which the proofs of the table data
+/// should be checked. See [`verify_block`](fn.verify_block.html) for the requirements
+/// to `validator_keys`.
+pub fn verify_table_proof(
+    block_proof: &BlockProof,
+    validator_keys: &[ValidatorKeys],
+    to_table: MapProof<Hash, Hash>,
+    service_id: u16,
+    table_idx: usize,
+) -> Result<Hash, failure::Error> {
+    block_proof.verify(validator_keys)?;
+
+    let to_table = to_table.check()?;
+    ensure!(
+        to_table.merkle_root() == *block_proof.block.state_hash(),
+        "Table proof does not match the state hash of the block at height {}",
+        block_proof.block.height()
+    );
+
+    let table_key = Blockchain::service_table_unique_key(service_id, table_idx);
+    to_table
+        .entries()
+        .find(|&(key, _)| *key == table_key)
+        .map(|(_, hash)| *hash)
+        .ok_or_else(|| {
+            format_err!(
+                "Table {} of service {} is missing in the proof",
+                table_idx,
+                service_id
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crypto::{gen_keypair, hash, SecretKey};
+    use helpers::{Height, Round, ValidatorId};
+    use messages::{Message, Precommit};
+    use storage::{Database, MemoryDB, ProofMapIndex};
+
+    const SERVICE_ID: u16 = 5;
+
+    fn create_block_proof(
+        state_hash: &Hash,
+        keys: &[ValidatorKeys],
+        secret_keys: &[SecretKey],
+    ) -> BlockProof {
+        let block = Block::new(
+            ValidatorId(0),
+            Height(5),
+            0,
+            &hash(&[1, 2]),
+            &Hash::zero(),
+            state_hash,
+        );
+        let precommits = keys
+            .iter()
+            .zip(secret_keys)
+            .enumerate()
+            .map(|(i, (validator_keys, secret_key))| {
+                let precommit = Precommit::new(
+                    ValidatorId(i as u16),
+                    block.height(),
+                    Round(1),
+                    &hash(&[1]),
+                    &block.hash(),
+                    Utc::now(),
+                );
+                Message::concrete(precommit, validator_keys.consensus_key, secret_key)
+            })
+            .collect();
+        BlockProof { block, precommits }
+    }
+
+    #[test]
+    fn test_verify_table_proof() {
+        let (keys, secret_keys): (Vec<_>, Vec<_>) = (0..4)
+            .map(|_| {
+                let (consensus_key, secret_key) = gen_keypair();
+                let keys = ValidatorKeys {
+                    consensus_key,
+                    service_key: gen_keypair().0,
+                };
+                (keys, secret_key)
+            })
+            .unzip();
+
+        let db = MemoryDB::new();
+        let mut fork = db.fork();
+        let table_key = Blockchain::service_table_unique_key(SERVICE_ID, 0);
+        let table_root = hash(&[42]);
+        let mut state = ProofMapIndex::new("state", &mut fork);
+        state.put(&table_key, table_root);
+        state.put(
+            &Blockchain::service_table_unique_key(SERVICE_ID, 1),
+            hash(&[43]),
+        );
+
+        let block_proof = create_block_proof(&state.merkle_root(), &keys, &secret_keys);
+        let root = verify_table_proof(
+            &block_proof,
+            &keys,
+            state.get_proof(table_key),
+            SERVICE_ID,
+            0,
+        ).unwrap();
+        assert_eq!(root, table_root);
+
+        // The table is missing in the proof.
+        let missing = Blockchain::service_table_unique_key(SERVICE_ID, 2);
+        assert!(
+            verify_table_proof(&block_proof, &keys, state.get_proof(missing), SERVICE_ID, 2)
+                .is_err()
+        );
+        // The proof does not match the block.
+        let other_block_proof = create_block_proof(&hash(&[1]), &keys, &secret_keys);
+        assert!(verify_table_proof(
+            &other_block_proof,
+            &keys,
+            state.get_proof(table_key),
+            SERVICE_ID,
+            0
+        ).is_err());
+        // The block is not authenticated by the validators.
+        let other_keys: Vec<_> = (0..4)
+            .map(|_| ValidatorKeys {
+                consensus_key: gen_keypair().0,
+                service_key: gen_keypair().0,
+            })
+            .collect();
+        assert!(verify_table_proof(
+            &block_proof,
+            &other_keys,
+            state.get_proof(table_key),
+            SERVICE_ID,
+            0
+        ).is_err());
+    }
+}
@@ -17,6 +17,8 @@
 extern crate futures;
 use self::futures::sync::mpsc;
 
+#[cfg(feature = "graphql")]
+use exonum::api::node::public::graphql::{Event, GraphQLProvider, Wallet};
 use exonum::{
     blockchain::{
         Blockchain, ExecutionError, ExecutionResult, Schema, Service, Transaction,
@@ -26,7 +28,7 @@ use exonum::{
     encoding::Error as EncodingError,
     messages::{Message, RawTransaction, Signed},
     node::ApiSender,
-    storage::{MemoryDB, Snapshot},
+    storage::{Fork, MapIndex, MemoryDB, Snapshot},
 };
 
 pub const SERVICE_ID: u16 = 0;
@@ -46,9 +48,23 @@ transactions! {
     }
 }
 
+/// Returns the names of the created wallets.
+pub fn wallet_names<T: AsRef<Snapshot>>(view: T) -> MapIndex<T, PublicKey, String> {
+    MapIndex::new("my-service.wallet_names", view)
+}
+
+/// Returns the hashes of the transactions which have created the wallets.
+pub fn wallet_txs<T: AsRef<Snapshot>>(view: T) -> MapIndex<T, PublicKey, Hash> {
+    MapIndex::new("my-service.wallet_txs", view)
+}
+
 impl Transaction for CreateWallet {
-    fn execute(&self, _: TransactionContext) -> ExecutionResult {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
         if self.name().starts_with("Al") {
+            let tx_hash = context.tx_hash();
+            let fork: &mut Fork = context.fork();
+            wallet_names(&mut *fork).put(self.pubkey(), self.name().to_owned());
+            wallet_txs(fork).put(self.pubkey(), tx_hash);
             Ok(())
         } else {
             Err(ExecutionError::with_description(
@@ -83,6 +99,44 @@ impl Service for MyService {
     fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<Transaction>, EncodingError> {
         ExplorerTransactions::tx_from_raw(raw).map(ExplorerTransactions::into)
     }
+
+    #[cfg(feature = "graphql")]
+    fn graphql_provider(&self) -> Option<&GraphQLProvider> {
+        Some(self)
+    }
+}
+
+#[cfg(feature = "graphql")]
+impl GraphQLProvider for MyService {
+    fn wallet(&self, snapshot: &Snapshot, pub_key: &PublicKey) -> Option<Wallet> {
+        let name = wallet_names(snapshot).get(pub_key)?;
+        let tx_hash = wallet_txs(snapshot).get(pub_key)?;
+        Some(Wallet {
+            pub_key: *pub_key,
+            name,
+            balance: 0,
+            history: vec![tx_hash],
+        })
+    }
+
+    fn events(&self, snapshot: &Snapshot, tx_hash: &Hash) -> Vec<Event> {
+        let schema = Schema::new(snapshot);
+        let is_successful = schema
+            .transaction_results()
+            .get(tx_hash)
+            .map_or(false, |result| result.0.is_ok());
+        if !is_successful {
+            return Vec::new();
+        }
+        let raw = schema.transactions().get(tx_hash).unwrap();
+        match ExplorerTransactions::tx_from_raw(raw.payload().clone()) {
+            Ok(ExplorerTransactions::CreateWallet(tx)) => vec![Event {
+                name: "wallet_created".to_owned(),
+                data: json!({ "pub_key": tx.pubkey(), "name": tx.name() }),
+            }],
+            _ => Vec::new(),
+        }
+    }
 }
 
 /// Generates a keypair from a fixed passphrase.
@@ -0,0 +1,296 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for the GraphQL API over the blockchain data.
+
+use exonum::{
+    api::node::public::graphql::GraphQLContext,
+    blockchain::{Blockchain, Schema},
+    crypto,
+    messages::{Message, RawTransaction, Signed},
+};
+use serde_json::{self, Value};
+
+use blockchain::{create_block, create_blockchain, CreateWallet, SERVICE_ID};
+
+fn execute(blockchain: &Blockchain, query: &str) -> Value {
+    execute_in(&GraphQLContext::new(blockchain.clone()), query)
+}
+
+fn execute_in(context: &GraphQLContext, query: &str) -> Value {
+    let request = serde_json::from_value(json!({ "query": query })).unwrap();
+    let response = context.execute(&request).unwrap();
+    assert!(response.get("errors").is_none(), "{}", response);
+    response["data"].clone()
+}
+
+fn execute_err(blockchain: &Blockchain, query: &str) -> String {
+    let request = serde_json::from_value(json!({ "query": query })).unwrap();
+    GraphQLContext::new(blockchain.clone())
+        .execute(&request)
+        .unwrap_err()
+        .to_string()
+}
+
+fn create_wallet(name: &str) -> Signed<RawTransaction> {
+    let (pub_key, key) = crypto::gen_keypair();
+    Message::sign_transaction(CreateWallet::new(&pub_key, name), SERVICE_ID, pub_key, &key)
+}
+
+#[test]
+fn test_graphql_blocks_and_transactions() {
+    let mut blockchain = create_blockchain();
+    let tx_alice = create_wallet("Alice");
+    let tx_bob = create_wallet("Bob");
+    create_block(&mut blockchain, vec![tx_alice.clone(), tx_bob.clone()]);
+
+    let data = execute(
+        &blockchain,
+        "{
+            height
+            block(height: 1) {
+                height
+                txCount
+                transactions(count: 10) {
+                    transactions { hash status success }
+                    nextCursor
+                }
+            }
+        }",
+    );
+    assert_eq!(
+        data,
+        json!({
+            "height": 1,
+            "block": {
+                "height": 1,
+                "txCount": 2,
+                "transactions": {
+                    "transactions": [
+                        {
+                            "hash": tx_alice.hash().to_hex(),
+                            "status": "committed",
+                            "success": true,
+                        },
+                        {
+                            "hash": tx_bob.hash().to_hex(),
+                            "status": "committed",
+                            "success": false,
+                        },
+                    ],
+                    "nextCursor": null,
+                },
+            },
+        })
+    );
+}
+
+#[test]
+fn test_graphql_wallets_and_events() {
+    let mut blockchain = create_blockchain();
+    let tx_alice = create_wallet("Alice");
+    let tx_bob = create_wallet("Bob");
+    create_block(&mut blockchain, vec![tx_alice.clone(), tx_bob.clone()]);
+
+    let query = format!(
+        "{{
+            alice: wallet(pubKey: \"{}\") {{
+                serviceId
+                name
+                balance
+                transactions(count: 10) {{ transactions {{ hash block {{ height }} }} }}
+            }}
+            bob: wallet(pubKey: \"{}\") {{ name }}
+            events(height: 1, count: 10) {{
+                events {{
+                    serviceId
+                    name
+                    data
+                    transaction {{ hash }}
+                }}
+            }}
+            block(height: 1) {{
+                transactions(count: 10) {{ transactions {{ events {{ name }} }} }}
+            }}
+        }}",
+        tx_alice.author().to_hex(),
+        tx_bob.author().to_hex(),
+    );
+    let data = execute(&blockchain, &query);
+
+    let event_data = json!({ "pub_key": tx_alice.author(), "name": "Alice" });
+    assert_eq!(
+        data,
+        json!({
+            "alice": {
+                "serviceId": SERVICE_ID,
+                "name": "Alice",
+                "balance": "0",
+                "transactions": {
+                    "transactions": [{
+                        "hash": tx_alice.hash().to_hex(),
+                        "block": { "height": 1 },
+                    }],
+                },
+            },
+            // The transaction creating the wallet of Bob has failed.
+            "bob": null,
+            "events": {
+                "events": [{
+                    "serviceId": SERVICE_ID,
+                    "name": "wallet_created",
+                    "data": event_data.to_string(),
+                    "transaction": { "hash": tx_alice.hash().to_hex() },
+                }],
+            },
+            "block": {
+                "transactions": {
+                    "transactions": [
+                        { "events": [{ "name": "wallet_created" }] },
+                        { "events": [] },
+                    ],
+                },
+            },
+        })
+    );
+}
+
+#[test]
+fn test_graphql_single_snapshot() {
+    let mut blockchain = create_blockchain();
+    let tx_alice = create_wallet("Alice");
+    let mut fork = blockchain.fork();
+    Schema::new(&mut fork).add_transaction_into_pool(tx_alice.clone());
+    blockchain.merge(fork.into_patch()).unwrap();
+
+    // The context is created while the transaction is in the pool, so the response
+    // must not depend on the blocks committed later.
+    let context = GraphQLContext::new(blockchain.clone());
+    create_block(&mut blockchain, vec![tx_alice.clone()]);
+
+    let query = format!(
+        "{{
+            height
+            transaction(hash: \"{}\") {{ status block {{ height }} events {{ name }} }}
+            wallet(pubKey: \"{}\") {{ name }}
+        }}",
+        tx_alice.hash().to_hex(),
+        tx_alice.author().to_hex(),
+    );
+    assert_eq!(
+        execute_in(&context, &query),
+        json!({
+            "height": 0,
+            "transaction": { "status": "in-pool", "block": null, "events": [] },
+            "wallet": null,
+        })
+    );
+    assert_eq!(execute(&blockchain, &query)["height"], json!(1));
+}
+
+#[test]
+fn test_graphql_nested_pages() {
+    let mut blockchain = create_blockchain();
+    let tx_alice = create_wallet("Alice");
+    let tx_bob = create_wallet("Bob");
+    create_block(&mut blockchain, vec![tx_alice.clone(), tx_bob.clone()]);
+
+    let data = execute(
+        &blockchain,
+        "{
+            block(height: 1) {
+                transactions(count: 1) { transactions { hash } nextCursor }
+                events(count: 1) { events { name } nextCursor }
+            }
+        }",
+    );
+    let block = &data["block"];
+    assert_eq!(
+        block["transactions"]["transactions"],
+        json!([{ "hash": tx_alice.hash().to_hex() }])
+    );
+    assert_eq!(
+        block["events"]["events"],
+        json!([{ "name": "wallet_created" }])
+    );
+    assert_eq!(
+        block["transactions"]["nextCursor"],
+        block["events"]["nextCursor"]
+    );
+
+    let cursor = block["transactions"]["nextCursor"].as_str().unwrap();
+    let query = format!(
+        "{{
+            block(height: 1) {{
+                transactions(count: 1, cursor: \"{0}\") {{ transactions {{ hash }} nextCursor }}
+                events(count: 1, cursor: \"{0}\") {{ events {{ name }} nextCursor }}
+            }}
+        }}",
+        cursor
+    );
+    assert_eq!(
+        execute(&blockchain, &query)["block"],
+        json!({
+            "transactions": {
+                "transactions": [{ "hash": tx_bob.hash().to_hex() }],
+                "nextCursor": null,
+            },
+            "events": { "events": [], "nextCursor": null },
+        })
+    );
+
+    // Wallet transactions are paged by the cursors of the transactions.
+    let query = format!(
+        "{{
+            wallet(pubKey: \"{}\") {{
+                empty: transactions(count: 0) {{ transactions {{ hash }} nextCursor }}
+                transactions(count: 1) {{ transactions {{ hash cursor }} nextCursor }}
+            }}
+        }}",
+        tx_alice.author().to_hex()
+    );
+    let wallet = execute(&blockchain, &query)["wallet"].clone();
+    assert_eq!(wallet["empty"]["transactions"], json!([]));
+    assert_eq!(
+        wallet["empty"]["nextCursor"],
+        wallet["transactions"]["transactions"][0]["cursor"]
+    );
+    assert_eq!(wallet["transactions"]["nextCursor"], Value::Null);
+}
+
+#[test]
+fn test_graphql_query_limits() {
+    let blockchain = create_blockchain();
+
+    // `Block.transactions` and `Transaction.block` form a cycle.
+    let cycle = (0..6).fold("height".to_owned(), |query, _| {
+        format!(
+            "transactions(count: 1) {{ transactions {{ block {{ {} }} }} }}",
+            query
+        )
+    });
+    let query = format!("{{ block(height: 0) {{ {} }} }}", cycle);
+    let error = execute_err(&blockchain, &query);
+    assert!(error.contains("Query depth exceeds"), "{}", error);
+
+    let error = execute_err(
+        &blockchain,
+        "{
+            blocks(count: 1000) {
+                blocks { transactions(count: 1000) { transactions { hash } } }
+            }
+        }",
+    );
+    assert!(error.contains("Query complexity exceeds"), "{}", error);
+}
@@ -39,6 +39,8 @@ use blockchain::{
 };
 
 mod blockchain;
+#[cfg(feature = "graphql")]
+mod graphql;
 
 #[test]
 fn test_explorer_basics() {
@@ -406,6 +408,26 @@ fn test_transaction_iterator() {
     assert_eq!(create_wallet_positions, vec![0, 1]);
 }
 
+#[test]
+fn test_explorer_prefetch() {
+    let mut blockchain = create_blockchain();
+    let mut txs = tx_generator();
+    for _ in 0..4 {
+        create_block(&mut blockchain, txs.by_ref().take(2).collect());
+    }
+
+    let explorer = BlockchainExplorer::new(&blockchain);
+    let mut progress = Vec::new();
+    let from = explorer.prefetch(3, |loaded| progress.push(loaded));
+    assert_eq!(from, Height(2));
+    assert_eq!(progress, vec![1, 2, 3]);
+
+    let mut progress = Vec::new();
+    let from = explorer.prefetch(100, |loaded| progress.push(loaded));
+    assert_eq!(from, Height(0));
+    assert_eq!(progress, vec![1, 2, 3, 4, 5]);
+}
+
 #[test]
 fn test_block_with_transactions() {
     let mut blockchain = create_blockchain();
@@ -441,6 +463,39 @@ fn test_block_with_transactions_index_overflow() {
     assert!(block[6].status().is_ok());
 }
 
+#[test]
+fn test_transaction_range_proof() {
+    let mut blockchain = create_blockchain();
+    let txs: Vec<_> = tx_generator().take(5).collect();
+    let tx_hashes: Vec<_> = txs.iter().map(|tx| tx.hash()).collect();
+    create_block(&mut blockchain, txs);
+
+    let explorer = BlockchainExplorer::new(&blockchain);
+    let block = explorer.block(Height(1)).unwrap();
+    let header = block.header();
+
+    let proof = block.transaction_range_proof(1, 4);
+    let entries = proof
+        .validate(*header.tx_hash(), u64::from(header.tx_count()))
+        .unwrap();
+    assert_eq!(
+        entries,
+        vec![(1, &tx_hashes[1]), (2, &tx_hashes[2]), (3, &tx_hashes[3])]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Illegal range boundaries")]
+fn test_transaction_range_proof_out_of_bounds() {
+    let mut blockchain = create_blockchain();
+    let txs: Vec<_> = tx_generator().take(5).collect();
+    create_block(&mut blockchain, txs);
+
+    let explorer = BlockchainExplorer::new(&blockchain);
+    let block = explorer.block(Height(1)).unwrap();
+    block.transaction_range_proof(3, 6);
+}
+
 #[test]
 fn test_committed_transaction_roundtrip() {
     let mut blockchain = create_blockchain();
@@ -492,3 +547,26 @@ fn test_block_with_transactions_roundtrip() {
         block[0].content().message()
     );
 }
+
+#[test]
+fn test_cursor_roundtrip() {
+    let mut blockchain = create_blockchain();
+    let txs: Vec<_> = tx_generator().take(3).collect();
+    create_block(&mut blockchain, txs.clone());
+
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    let location = schema.transactions_locations().get(&txs[2].hash()).unwrap();
+    let cursor = Cursor::from(location);
+    assert_eq!(cursor, Cursor::new(Height(1), 2));
+
+    let json = serde_json::to_value(&cursor).unwrap();
+    assert!(json.is_string());
+    let cursor_copy: Cursor = serde_json::from_value(json).unwrap();
+    assert_eq!(cursor_copy, cursor);
+    assert_eq!(cursor.to_string().parse::<Cursor>().unwrap(), cursor);
+
+    assert!("".parse::<Cursor>().is_err());
+    assert!("not a cursor".parse::<Cursor>().is_err());
+    assert!("0001".parse::<Cursor>().is_err());
+}
@@ -36,7 +36,7 @@ use exonum::{
     encoding::Error as EncodingError,
     helpers,
     messages::RawTransaction,
-    node::{ApiSender, ExternalMessage, Node},
+    node::{ApiSender, ExternalMessage, MemoryNetwork, Node},
     storage::{Database, Fork, MemoryDB, Snapshot},
 };
 
@@ -97,12 +97,23 @@ struct RunHandle {
 }
 
 fn run_nodes(count: u16, start_port: u16) -> (Vec<RunHandle>, Vec<oneshot::Receiver<()>>) {
+    run_nodes_with(count, start_port, |node| node)
+}
+
+fn run_nodes_with<F>(
+    count: u16,
+    start_port: u16,
+    configure: F,
+) -> (Vec<RunHandle>, Vec<oneshot::Receiver<()>>)
+where
+    F: Fn(Node) -> Node,
+{
     let mut node_threads = Vec::new();
     let mut commit_rxs = Vec::new();
     for node_cfg in helpers::generate_testnet_config(count, start_port) {
         let (commit_tx, commit_rx) = oneshot::channel();
         let service = Box::new(CommitWatcherService(Mutex::new(Some(commit_tx))));
-        let node = Node::new(MemoryDB::new(), vec![service], node_cfg, None);
+        let node = configure(Node::new(MemoryDB::new(), vec![service], node_cfg, None));
         let api_tx = node.channel();
         node_threads.push(RunHandle {
             node_thread: thread::spawn(move || {
@@ -136,6 +147,27 @@ fn test_node_run() {
     }
 }
 
+#[test]
+fn test_node_run_in_memory() {
+    let network = MemoryNetwork::new().with_latency(Duration::from_millis(5));
+    let (nodes, commit_rxs) = run_nodes_with(4, 16_400, |node| node.with_network(network.clone()));
+
+    let mut core = Core::new().unwrap();
+    let duration = Duration::from_secs(60);
+    for rx in commit_rxs {
+        let future = rx.into_future().timeout(duration).map_err(drop);
+        core.run(future).expect("failed commit");
+    }
+
+    for handle in nodes {
+        handle
+            .api_tx
+            .send_external_message(ExternalMessage::Shutdown)
+            .unwrap();
+        handle.node_thread.join().unwrap();
+    }
+}
+
 #[test]
 fn test_node_restart_regression() {
     let start_node = |node_cfg, db, init_times| {
@@ -0,0 +1,38 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// This is a regression test for the binary format of the protocol messages.
+// If the format is changed intentionally, update the snapshot with
+// `describe-format --output tests/testdata/wire_format.json`.
+extern crate exonum;
+extern crate serde_json;
+
+use exonum::encoding::{check_layouts, core_layouts, StructLayout};
+
+use std::fs::File;
+
+const SNAPSHOT_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/testdata/wire_format.json"
+);
+
+#[test]
+fn test_wire_format() {
+    let file = File::open(SNAPSHOT_PATH).expect("Can't open snapshot file");
+    let expected: Vec<StructLayout> =
+        serde_json::from_reader(file).expect("Can't parse snapshot file");
+    if let Err(e) = check_layouts(&expected, &core_layouts()) {
+        panic!("{}", e);
+    }
+}
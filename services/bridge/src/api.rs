@@ -0,0 +1,65 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Exonum-bridge API.
+
+use exonum::{api, blockchain::Block, helpers::Height};
+
+use {BridgeSchema, ForeignChainConfig};
+
+/// Query for the verified header of the foreign block.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ForeignBlockQuery {
+    /// Height of the foreign block.
+    pub height: Height,
+}
+
+/// Implements the exonum-bridge public API.
+#[derive(Debug)]
+pub struct PublicApi;
+
+impl PublicApi {
+    /// Endpoint for getting the foreign chain configuration.
+    pub fn foreign_chain(
+        state: &api::ServiceApiState,
+        _query: (),
+    ) -> api::Result<Option<ForeignChainConfig>> {
+        let view = state.snapshot();
+        let schema = BridgeSchema::new(&view);
+        Ok(schema.foreign_chain())
+    }
+
+    /// Endpoint for getting the verified header of the foreign block at the given height.
+    pub fn foreign_block(
+        state: &api::ServiceApiState,
+        query: ForeignBlockQuery,
+    ) -> api::Result<Block> {
+        let view = state.snapshot();
+        let schema = BridgeSchema::new(&view);
+        schema.foreign_block(query.height).ok_or_else(|| {
+            api::Error::NotFound(format!(
+                "Foreign block at height {} is unknown",
+                query.height
+            ))
+        })
+    }
+
+    /// Used to extend Api.
+    pub fn wire(builder: &mut api::ServiceApiBuilder) {
+        builder
+            .public_scope()
+            .endpoint("v1/foreign_chain", Self::foreign_chain)
+            .endpoint("v1/foreign_block", Self::foreign_block);
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The cross-chain read bridge service for Exonum.
+//!
+//! The bridge follows another Exonum blockchain (the *foreign chain*) without
+//! connecting to its network. Anyone can submit a header of a foreign block together
+//! with its precommits; the service accepts the header only if it is authenticated by
+//! the supermajority of the foreign validators listed in the service configuration.
+//!
+//! Local services can then check proofs of the foreign state against the accepted
+//! headers with [`BridgeSchema::verify_state_proof`], e.g., to accept a payment that
+//! references an asset registered on the foreign chain.
+//!
+//! The foreign validator set is a part of the global configuration of the local
+//! blockchain, so it can be updated with the configuration service when the validators
+//! of the foreign chain change.
+//!
+//! [`BridgeSchema::verify_state_proof`]: schema/struct.BridgeSchema.html#method.verify_state_proof
+
+#![deny(
+    missing_debug_implementations,
+    missing_docs,
+    unsafe_code,
+    bare_trait_objects
+)]
+
+#[macro_use]
+extern crate exonum;
+#[macro_use]
+extern crate failure;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+
+/// Node API.
+pub mod api;
+/// Proofs of the foreign state.
+pub mod proof;
+/// Database schema.
+pub mod schema;
+/// Node transactions.
+pub mod transactions;
+
+use exonum::{
+    api::ServiceApiBuilder,
+    blockchain::{Service, Transaction, TransactionInfo, TransactionSet, ValidatorKeys},
+    crypto::Hash,
+    encoding::{self, serialize::json::reexport::Value},
+    helpers::fabric::{Context, ServiceFactory},
+    messages::RawTransaction,
+    storage::{Fork, Snapshot},
+};
+use schema::BridgeSchema;
+
+use transactions::*;
+
+/// Bridge service id.
+pub const SERVICE_ID: u16 = 5;
+/// Bridge service name.
+pub const SERVICE_NAME: &str = "exonum_bridge";
+
+/// Description of the foreign chain stored in the global configuration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForeignChainConfig {
+    /// Human-readable name of the foreign chain.
+    pub name: String,
+    /// Keys of the foreign validators in the order of their validator ids.
+    ///
+    /// Only consensus keys are used by the bridge; the list can be copied
+    /// as is from the actual configuration of the foreign chain.
+    pub validator_keys: Vec<ValidatorKeys>,
+}
+
+/// Define the service.
+#[derive(Debug)]
+pub struct BridgeService {
+    /// Foreign chain configuration written into the genesis block.
+    config: ForeignChainConfig,
+}
+
+impl BridgeService {
+    /// Create a new `BridgeService` following the given foreign chain.
+    pub fn new(config: ForeignChainConfig) -> BridgeService {
+        BridgeService { config }
+    }
+}
+
+impl Service for BridgeService {
+    fn service_name(&self) -> &str {
+        SERVICE_NAME
+    }
+
+    fn state_hash(&self, snapshot: &dyn Snapshot) -> Vec<Hash> {
+        let schema = BridgeSchema::new(snapshot);
+        schema.state_hash()
+    }
+
+    fn service_id(&self) -> u16 {
+        SERVICE_ID
+    }
+
+    fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<dyn Transaction>, encoding::Error> {
+        BridgeTransactions::tx_from_raw(raw).map(Into::into)
+    }
+
+    fn transaction_types(&self) -> Vec<TransactionInfo> {
+        TransactionInfo::from_set::<BridgeTransactions>()
+    }
+
+    /// Puts the foreign chain configuration into the genesis configuration.
+    fn initialize(&self, _fork: &mut Fork) -> Value {
+        serde_json::to_value(&self.config).expect("Cannot serialize foreign chain config")
+    }
+
+    fn wire_api(&self, builder: &mut ServiceApiBuilder) {
+        api::PublicApi::wire(builder);
+    }
+}
+
+/// A bridge service creator for the `NodeBuilder`.
+#[derive(Debug)]
+pub struct BridgeServiceFactory {
+    config: ForeignChainConfig,
+}
+
+impl BridgeServiceFactory {
+    /// Create a new factory for the given foreign chain.
+    pub fn new(config: ForeignChainConfig) -> BridgeServiceFactory {
+        BridgeServiceFactory { config }
+    }
+}
+
+impl ServiceFactory for BridgeServiceFactory {
+    fn service_name(&self) -> &str {
+        SERVICE_NAME
+    }
+
+    fn make_service(&mut self, _: &Context) -> Box<dyn Service> {
+        Box::new(BridgeService::new(self.config.clone()))
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use exonum::{
+    blockchain::Blockchain,
+    crypto::Hash,
+    helpers::Height,
+    storage::{
+        proof_map_index::{MapProofError, ProofMapKey},
+        MapProof, StorageValue,
+    },
+};
+
+/// Proof of entries of a service table in the state of the foreign chain.
+///
+/// The proof consists of two parts, similar to the proofs returned by the services
+/// of the foreign chain itself: the proof from the `state_hash` of the foreign block
+/// to the root hash of the table, and the proof from the root hash of the table
+/// to the requested entries.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForeignStateProof<K, V> {
+    /// Height of the foreign block the proof is built for.
+    pub height: Height,
+    /// Proof from the `state_hash` of the block to the root hash of the table.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Proof from the root hash of the table to the entries.
+    pub to_entries: MapProof<K, V>,
+}
+
+/// Errors emitted when a proof of the foreign state cannot be verified.
+#[derive(Debug, Fail)]
+pub enum ProofError {
+    /// The header of the foreign block has not been submitted to the bridge.
+    #[fail(display = "Unknown foreign block at height {}", _0)]
+    UnknownBlock(Height),
+
+    /// One of the map proofs is malformed.
+    #[fail(display = "Malformed proof: {}", _0)]
+    Malformed(#[cause] MapProofError),
+
+    /// The proof does not lead to the `state_hash` of the foreign block.
+    #[fail(display = "Proof does not match the state hash of the foreign block")]
+    StateHashMismatch,
+
+    /// The proof does not contain the requested table.
+    #[fail(display = "Proof does not contain the table {} of service {}", _1, _0)]
+    MissingTable(u16, usize),
+
+    /// The proof of entries does not lead to the root hash of the table.
+    #[fail(display = "Proof does not match the root hash of the table")]
+    TableHashMismatch,
+}
+
+impl From<MapProofError> for ProofError {
+    fn from(e: MapProofError) -> Self {
+        ProofError::Malformed(e)
+    }
+}
+
+impl<K, V> ForeignStateProof<K, V>
+where
+    K: ProofMapKey + Clone,
+    V: StorageValue + Clone,
+{
+    /// Checks the proof against the `state_hash` of the foreign block and returns
+    /// the proven entries of the table `table_idx` of the foreign service `service_id`.
+    ///
+    /// Keys missing from the table are returned with `None` values.
+    pub fn check(
+        self,
+        state_hash: &Hash,
+        service_id: u16,
+        table_idx: usize,
+    ) -> Result<Vec<(K, Option<V>)>, ProofError> {
+        let to_table = self.to_table.check()?;
+        if to_table.merkle_root() != *state_hash {
+            return Err(ProofError::StateHashMismatch);
+        }
+
+        let table_key = Blockchain::service_table_unique_key(service_id, table_idx);
+        let table_hash = to_table
+            .entries()
+            .find(|&(key, _)| *key == table_key)
+            .map(|(_, hash)| *hash)
+            .ok_or(ProofError::MissingTable(service_id, table_idx))?;
+
+        let to_entries = self.to_entries.check()?;
+        if to_entries.merkle_root() != table_hash {
+            return Err(ProofError::TableHashMismatch);
+        }
+
+        Ok(to_entries
+            .all_entries()
+            .map(|(key, value)| (key.clone(), value.cloned()))
+            .collect())
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use exonum::{
+    blockchain::{Block, Schema},
+    crypto::{CryptoHash, Hash},
+    helpers::Height,
+    storage::{
+        proof_map_index::ProofMapKey, Fork, MapIndex, ProofMapIndex, Snapshot, StorageValue,
+    },
+};
+use serde_json;
+
+use proof::{ForeignStateProof, ProofError};
+use {ForeignChainConfig, SERVICE_NAME};
+
+/// `Exonum-bridge` service database schema.
+#[derive(Debug)]
+pub struct BridgeSchema<T> {
+    view: T,
+}
+
+impl<T: AsRef<dyn Snapshot>> BridgeSchema<T> {
+    /// Constructs schema for the given `snapshot`.
+    pub fn new(view: T) -> Self {
+        BridgeSchema { view }
+    }
+
+    /// Returns the table that stores verified foreign block headers by their hashes.
+    pub fn foreign_blocks(&self) -> ProofMapIndex<&dyn Snapshot, Hash, Block> {
+        ProofMapIndex::new("exonum_bridge.foreign_blocks", self.view.as_ref())
+    }
+
+    /// Returns the table that maps heights of the verified foreign blocks to their hashes.
+    pub fn foreign_block_hashes(&self) -> MapIndex<&dyn Snapshot, u64, Hash> {
+        MapIndex::new("exonum_bridge.foreign_block_hashes", self.view.as_ref())
+    }
+
+    /// Returns the verified header of the foreign block at the given height.
+    pub fn foreign_block(&self, height: Height) -> Option<Block> {
+        self.foreign_block_hashes()
+            .get(&height.0)
+            .and_then(|hash| self.foreign_blocks().get(&hash))
+    }
+
+    /// Returns the foreign chain configuration from the actual configuration
+    /// of the blockchain.
+    pub fn foreign_chain(&self) -> Option<ForeignChainConfig> {
+        Schema::new(self.view.as_ref())
+            .actual_configuration()
+            .services
+            .get(SERVICE_NAME)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    /// Verifies the proof of the foreign state against the verified header of
+    /// the foreign block and returns the proven entries of the table `table_idx`
+    /// of the foreign service `service_id`.
+    ///
+    /// Local services should call this method from their transactions to make sure
+    /// that the referenced foreign data is authentic.
+    pub fn verify_state_proof<K, V>(
+        &self,
+        service_id: u16,
+        table_idx: usize,
+        proof: ForeignStateProof<K, V>,
+    ) -> Result<Vec<(K, Option<V>)>, ProofError>
+    where
+        K: ProofMapKey + Clone,
+        V: StorageValue + Clone,
+    {
+        let block = self
+            .foreign_block(proof.height)
+            .ok_or(ProofError::UnknownBlock(proof.height))?;
+        proof.check(block.state_hash(), service_id, table_idx)
+    }
+
+    /// Returns hashes for stored tables.
+    pub fn state_hash(&self) -> Vec<Hash> {
+        vec![self.foreign_blocks().merkle_root()]
+    }
+}
+
+impl<'a> BridgeSchema<&'a mut Fork> {
+    /// Mutable reference to the ['foreign_blocks'][1] index.
+    ///
+    /// [1]: struct.BridgeSchema.html#method.foreign_blocks
+    pub fn foreign_blocks_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, Block> {
+        ProofMapIndex::new("exonum_bridge.foreign_blocks", self.view)
+    }
+
+    /// Mutable reference to the ['foreign_block_hashes'][1] index.
+    ///
+    /// [1]: struct.BridgeSchema.html#method.foreign_block_hashes
+    pub fn foreign_block_hashes_mut(&mut self) -> MapIndex<&mut Fork, u64, Hash> {
+        MapIndex::new("exonum_bridge.foreign_block_hashes", self.view)
+    }
+
+    /// Saves the verified header of the foreign block.
+    pub fn add_foreign_block(&mut self, block: Block) {
+        let hash = block.hash();
+        self.foreign_block_hashes_mut().put(&block.height().0, hash);
+        self.foreign_blocks_mut().put(&hash, block);
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Workaround for `failure` see https://github.com/rust-lang-nursery/failure/issues/223 and
+// ECR-1771 for the details.
+#![allow(bare_trait_objects)]
+
+use exonum::{
+    blockchain::{
+        verify_block, Block, ExecutionError, ExecutionResult, Transaction, TransactionContext,
+    },
+    crypto::{PublicKey, SecretKey},
+    messages::{Consensus, Message, Precommit, RawTransaction, Signed},
+};
+
+use super::SERVICE_ID;
+use schema::BridgeSchema;
+
+/// Common errors emitted by transactions during execution.
+#[derive(Debug, Fail)]
+#[repr(u8)]
+pub enum Error {
+    /// The foreign chain is not described in the actual configuration.
+    #[fail(display = "Foreign chain is not configured")]
+    NotConfigured = 0,
+
+    /// One of the precommits cannot be parsed.
+    #[fail(display = "Malformed precommit")]
+    MalformedPrecommit = 1,
+
+    /// The block is not authenticated by the foreign validators.
+    #[fail(display = "Foreign block is not authenticated by the foreign validators")]
+    UnverifiedBlock = 2,
+
+    /// A block at the same height has already been accepted.
+    #[fail(display = "Foreign block at this height is already known")]
+    DuplicateBlock = 3,
+}
+
+impl From<Error> for ExecutionError {
+    fn from(value: Error) -> ExecutionError {
+        let description = value.to_string();
+        ExecutionError::with_description(value as u8, description)
+    }
+}
+
+transactions! {
+    /// Define BridgeService transaction.
+    pub BridgeTransactions {
+
+        /// Transaction submitting a header of the foreign block.
+        ///
+        /// The transaction can be signed by anyone, since the block is accepted
+        /// only with the precommits of the foreign validators.
+        struct TxForeignBlock {
+            /// Header of the foreign block.
+            block: Block,
+            /// Serialized precommits of the foreign validators for the block.
+            precommits: Vec<Vec<u8>>,
+        }
+    }
+}
+
+impl TxForeignBlock {
+    /// Creates a signed transaction from the foreign block and its precommits,
+    /// e.g., obtained from the explorer API of the foreign chain.
+    pub fn sign(
+        block: Block,
+        precommits: &[Signed<Precommit>],
+        public_key: &PublicKey,
+        secret_key: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        let precommits = precommits
+            .iter()
+            .map(|precommit| precommit.signed_message().raw().to_vec())
+            .collect();
+        Message::sign_transaction(
+            TxForeignBlock::new(block, precommits),
+            SERVICE_ID,
+            *public_key,
+            secret_key,
+        )
+    }
+
+    fn parse_precommits(&self) -> Result<Vec<Signed<Precommit>>, Error> {
+        self.precommits()
+            .into_iter()
+            .map(|raw| match Message::from_raw_buffer(raw) {
+                Ok(Message::Consensus(Consensus::Precommit(precommit))) => Ok(precommit),
+                _ => Err(Error::MalformedPrecommit),
+            }).collect()
+    }
+}
+
+impl Transaction for TxForeignBlock {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let mut schema = BridgeSchema::new(context.fork());
+        let config = schema.foreign_chain().ok_or(Error::NotConfigured)?;
+
+        let block = self.block();
+        if schema.foreign_block_hashes().contains(&block.height().0) {
+            Err(Error::DuplicateBlock)?
+        }
+
+        let precommits = self.parse_precommits()?;
+        verify_block(&block, &precommits, &config.validator_keys).map_err(|e| {
+            ExecutionError::with_description(Error::UnverifiedBlock as u8, e.to_string())
+        })?;
+
+        schema.add_foreign_block(block);
+        Ok(())
+    }
+}
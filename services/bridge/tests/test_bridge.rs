@@ -0,0 +1,168 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[macro_use]
+extern crate assert_matches;
+extern crate chrono;
+extern crate exonum;
+extern crate exonum_bridge;
+#[macro_use]
+extern crate exonum_testkit;
+extern crate exonum_time;
+#[macro_use]
+extern crate pretty_assertions;
+
+use chrono::{DateTime, Utc};
+use exonum::{
+    blockchain::{Schema, TransactionErrorType, TransactionResult},
+    crypto::{gen_keypair, PublicKey},
+    helpers::Height,
+    messages::{RawTransaction, Signed},
+    storage::Snapshot,
+};
+use exonum_bridge::{
+    proof::{ForeignStateProof, ProofError},
+    schema::BridgeSchema,
+    transactions::TxForeignBlock,
+    BridgeService, ForeignChainConfig,
+};
+use exonum_testkit::{TestKit, TestKitBuilder};
+use exonum_time::{schema::TimeSchema, transactions::TxTime, TimeService};
+
+/// Creates a foreign chain running the time service with one recorded validator time.
+fn create_foreign_chain() -> (TestKit, PublicKey, DateTime<Utc>) {
+    let mut foreign = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_service(TimeService::new())
+        .create();
+    let validator = foreign.network().validators()[0].clone();
+    let (public_key, secret_key) = validator.service_keypair();
+    let time = Utc::now();
+    foreign.create_block_with_transactions(txvec![TxTime::sign(time, public_key, secret_key)]);
+    (foreign, *public_key, time)
+}
+
+fn create_local_chain(foreign: &TestKit) -> TestKit {
+    let config = ForeignChainConfig {
+        name: "foreign".to_owned(),
+        validator_keys: foreign.actual_configuration().validator_keys,
+    };
+    TestKitBuilder::validator()
+        .with_validators(1)
+        .with_service(BridgeService::new(config))
+        .create()
+}
+
+fn foreign_block_tx(
+    foreign: &TestKit,
+    height: Height,
+    precommits: usize,
+) -> Signed<RawTransaction> {
+    let explorer = foreign.explorer();
+    let block = explorer.block(height).unwrap();
+    let (public_key, secret_key) = gen_keypair();
+    TxForeignBlock::sign(
+        block.header().clone(),
+        &block.precommits()[..precommits],
+        &public_key,
+        &secret_key,
+    )
+}
+
+fn time_proof<T: AsRef<Snapshot>>(
+    snapshot: T,
+    key: PublicKey,
+) -> ForeignStateProof<PublicKey, DateTime<Utc>> {
+    let snapshot = snapshot.as_ref();
+    ForeignStateProof {
+        height: Schema::new(snapshot).height(),
+        to_table: Schema::new(snapshot).get_proof_to_service_table(exonum_time::SERVICE_ID, 0),
+        to_entries: TimeSchema::new(snapshot).validators_times().get_proof(key),
+    }
+}
+
+#[test]
+fn test_foreign_state_proof() {
+    let (foreign, validator_key, time) = create_foreign_chain();
+    let mut local = create_local_chain(&foreign);
+
+    let proof = time_proof(foreign.snapshot(), validator_key);
+    assert_matches!(
+        BridgeSchema::new(local.snapshot()).verify_state_proof(exonum_time::SERVICE_ID, 0, proof),
+        Err(ProofError::UnknownBlock(Height(1)))
+    );
+
+    local.create_block_with_transactions(txvec![foreign_block_tx(&foreign, Height(1), 4)]);
+    let foreign_block = foreign.explorer().block(Height(1)).unwrap().into_header();
+    assert_eq!(
+        BridgeSchema::new(local.snapshot()).foreign_block(Height(1)),
+        Some(foreign_block)
+    );
+
+    let proof = time_proof(foreign.snapshot(), validator_key);
+    let entries = BridgeSchema::new(local.snapshot())
+        .verify_state_proof(exonum_time::SERVICE_ID, 0, proof)
+        .unwrap();
+    assert_eq!(entries, vec![(validator_key, Some(time))]);
+
+    // The proof of the `validators_times` table does not prove the `time` table.
+    let proof = time_proof(foreign.snapshot(), validator_key);
+    assert_matches!(
+        BridgeSchema::new(local.snapshot()).verify_state_proof(exonum_time::SERVICE_ID, 1, proof),
+        Err(ProofError::TableHashMismatch)
+    );
+}
+
+#[test]
+fn test_foreign_block_without_majority() {
+    let (foreign, ..) = create_foreign_chain();
+    let mut local = create_local_chain(&foreign);
+
+    let tx = foreign_block_tx(&foreign, Height(1), 2);
+    local.create_block_with_transactions(txvec![tx.clone()]);
+
+    let result = Schema::new(local.snapshot())
+        .transaction_results()
+        .get(&tx.hash());
+    match result {
+        Some(TransactionResult(Err(e))) => {
+            assert_eq!(e.error_type(), TransactionErrorType::Code(2));
+        }
+        _ => panic!("Expected Err(), found None or Ok()"),
+    }
+    assert_eq!(
+        BridgeSchema::new(local.snapshot()).foreign_block(Height(1)),
+        None
+    );
+}
+
+#[test]
+fn test_duplicate_foreign_block() {
+    let (foreign, ..) = create_foreign_chain();
+    let mut local = create_local_chain(&foreign);
+
+    local.create_block_with_transactions(txvec![foreign_block_tx(&foreign, Height(1), 4)]);
+    let tx = foreign_block_tx(&foreign, Height(1), 3);
+    local.create_block_with_transactions(txvec![tx.clone()]);
+
+    let result = Schema::new(local.snapshot())
+        .transaction_results()
+        .get(&tx.hash());
+    match result {
+        Some(TransactionResult(Err(e))) => {
+            assert_eq!(e.error_type(), TransactionErrorType::Code(3));
+        }
+        _ => panic!("Expected Err(), found None or Ok()"),
+    }
+}
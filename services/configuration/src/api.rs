@@ -14,10 +14,12 @@
 
 use exonum::{
     api::{self, ServiceApiBuilder, ServiceApiState},
-    blockchain::{Schema as CoreSchema, StoredConfiguration},
+    blockchain::{
+        BlockProof, ConfigReference, Schema as CoreSchema, StoredConfiguration, CORE_SERVICE,
+    },
     crypto::{CryptoHash, Hash},
     helpers::Height,
-    storage::StorageValue,
+    storage::{ListProof, MapProof, Snapshot, StorageValue},
 };
 
 use super::{Propose, ProposeData, Schema, Vote, VoteAgainst, VotingDecision};
@@ -60,6 +62,47 @@ pub struct HashQuery {
     pub hash: Hash,
 }
 
+/// Query for the configuration actual at the given height.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct HeightQuery {
+    /// Height of the block. The default value is the height of the latest block.
+    pub height: Option<Height>,
+}
+
+/// Configuration actual at a certain height with the proof of its authenticity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigByHeightInfo {
+    /// Height for which the configuration is returned.
+    pub height: Height,
+    /// Hash of the configuration.
+    pub hash: Hash,
+    /// Configuration contents.
+    pub config: StoredConfiguration,
+    /// Proof of the configuration reference in the history of configurations.
+    pub history_proof: ListProof<ConfigReference>,
+    /// Proof of the history root hash in the state hash of the latest block. The proof
+    /// shows the absence of the root hash until the height set in the `configs_history_from`
+    /// field of the consensus configuration.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Latest block with its precommits.
+    pub block_proof: BlockProof,
+}
+
+/// History of configurations with the proof of its authenticity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigHistoryInfo {
+    /// References to the configurations in the increasing order of their activation height.
+    pub history: Vec<ConfigReference>,
+    /// Proof of the whole history of configurations.
+    pub history_proof: ListProof<ConfigReference>,
+    /// Proof of the history root hash in the state hash of the latest block. The proof
+    /// shows the absence of the root hash until the height set in the `configs_history_from`
+    /// field of the consensus configuration.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Latest block with its precommits.
+    pub block_proof: BlockProof,
+}
+
 /// Filter for stored configurations.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub struct FilterQuery {
@@ -161,6 +204,73 @@ impl PublicApi {
         committed_configs
     }
 
+    /// Index of the `configs_actual_from` table in the core state hash.
+    const CONFIGS_HISTORY_TABLE: usize = 2;
+
+    fn latest_block_proofs<T: AsRef<dyn Snapshot>>(
+        core_schema: &CoreSchema<T>,
+    ) -> (MapProof<Hash, Hash>, BlockProof) {
+        let to_table =
+            core_schema.get_proof_to_service_table(CORE_SERVICE, Self::CONFIGS_HISTORY_TABLE);
+        let block_proof = core_schema
+            .block_and_precommits(core_schema.height())
+            .expect("Latest block is absent");
+        (to_table, block_proof)
+    }
+
+    fn handle_config_history(
+        state: &ServiceApiState,
+        _query: (),
+    ) -> api::Result<ConfigHistoryInfo> {
+        let snapshot = state.snapshot();
+        let core_schema = CoreSchema::new(&snapshot);
+        let actual_from = core_schema.configs_actual_from();
+        let history = actual_from.iter().collect();
+        let history_proof = actual_from.get_range_proof(0, actual_from.len());
+        let (to_table, block_proof) = Self::latest_block_proofs(&core_schema);
+        Ok(ConfigHistoryInfo {
+            history,
+            history_proof,
+            to_table,
+            block_proof,
+        })
+    }
+
+    fn handle_config_by_height(
+        state: &ServiceApiState,
+        query: HeightQuery,
+    ) -> api::Result<ConfigByHeightInfo> {
+        let snapshot = state.snapshot();
+        let core_schema = CoreSchema::new(&snapshot);
+        let next_height = core_schema.height().next();
+        let height = query.height.unwrap_or_else(|| core_schema.height());
+        if height > next_height {
+            return Err(api::Error::BadRequest(format!(
+                "Requested height {} is greater than the next height of the blockchain {}",
+                height, next_height
+            )));
+        }
+
+        let (reference, history_proof) = core_schema
+            .configuration_reference_by_height(height)
+            .ok_or_else(|| {
+                api::Error::NotFound(format!("No configuration for height {}", height))
+            })?;
+        let hash = *reference.cfg_hash();
+        let config = core_schema
+            .configuration_by_hash(&hash)
+            .unwrap_or_else(|| panic!("Config with hash {:?} is absent in configs table", hash));
+        let (to_table, block_proof) = Self::latest_block_proofs(&core_schema);
+        Ok(ConfigByHeightInfo {
+            height,
+            hash,
+            config,
+            history_proof,
+            to_table,
+            block_proof,
+        })
+    }
+
     fn handle_actual_config(state: &ServiceApiState, _query: ()) -> api::Result<ConfigHashInfo> {
         let config = CoreSchema::new(state.snapshot()).actual_configuration();
         Ok(Self::config_with_proofs(state, config))
@@ -218,7 +328,9 @@ impl PublicApi {
             .endpoint("v1/configs", Self::handle_config_by_hash)
             .endpoint("v1/configs/votes", Self::handle_votes_for_propose)
             .endpoint("v1/configs/proposed", Self::handle_proposed_configs)
-            .endpoint("v1/configs/committed", Self::handle_committed_configs);
+            .endpoint("v1/configs/committed", Self::handle_committed_configs)
+            .endpoint("v1/configs/history", Self::handle_config_history)
+            .endpoint("v1/configs/by_height", Self::handle_config_by_height);
     }
 }
 
@@ -210,6 +210,21 @@ pub fn generate_testnet_config(
             mempool: Default::default(),
             services_configs: service_config.clone(),
             database: Default::default(),
-            thread_pool_size: Default::default(),
+            threads: Default::default(),
+            plugins: Default::default(),
+            propose_precheck: false,
+            compaction: None,
+            status_file: None,
+            consensus_archive: None,
+            ping_interval: None,
+            max_clock_skew: None,
+            propose_ahead: false,
+            cold_storage: None,
+            memory_db: None,
+            pruning: None,
+            alerting: None,
+            paranoid: false,
+            replica: None,
+            forensics: None,
         }).collect::<Vec<_>>()
 }
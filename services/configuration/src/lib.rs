@@ -92,7 +92,7 @@ use serde_json::to_value;
 use exonum::encoding::serialize::json::reexport::Value;
 use exonum::{
     api::ServiceApiBuilder,
-    blockchain::{self, Transaction, TransactionSet},
+    blockchain::{self, Transaction, TransactionInfo, TransactionSet},
     crypto::Hash,
     encoding::Error as EncodingError,
     helpers::fabric::{self, keys, Command, CommandExtension, CommandName, Context},
@@ -142,6 +142,10 @@ impl blockchain::Service for Service {
         ConfigurationTransactions::tx_from_raw(raw).map(Into::into)
     }
 
+    fn transaction_types(&self) -> Vec<TransactionInfo> {
+        TransactionInfo::from_set::<ConfigurationTransactions>()
+    }
+
     fn wire_api(&self, builder: &mut ServiceApiBuilder) {
         api::PublicApi::wire(builder);
         api::PrivateApi::wire(builder);
@@ -15,7 +15,7 @@
 // spell-checker:ignore postpropose, postvote
 
 use exonum::{
-    blockchain::{Schema, StoredConfiguration},
+    blockchain::{verify_block, Blockchain, Schema, StoredConfiguration, CORE_SERVICE},
     crypto::{CryptoHash, Hash},
     helpers::{Height, ValidatorId},
 };
@@ -26,8 +26,8 @@ use super::{
     ConfigurationTestKit,
 };
 use api::{
-    ConfigHashInfo, ConfigInfo, FilterQuery, HashQuery, ProposeHashInfo, ProposeResponse,
-    VoteResponse, VotesInfo,
+    ConfigByHeightInfo, ConfigHashInfo, ConfigHistoryInfo, ConfigInfo, FilterQuery, HashQuery,
+    HeightQuery, ProposeHashInfo, ProposeResponse, VoteResponse, VotesInfo,
 };
 use SERVICE_NAME;
 
@@ -52,6 +52,10 @@ trait ConfigurationApiTest {
 
     fn votes_for_propose(&self, cfg_hash: Hash) -> VotesInfo;
 
+    fn config_history(&self) -> ConfigHistoryInfo;
+
+    fn config_by_height(&self, height: Option<Height>) -> ConfigByHeightInfo;
+
     fn post_config_propose(&self, cfg: &StoredConfiguration) -> ProposeResponse;
 
     fn post_config_vote(&self, cfg_hash: Hash) -> VoteResponse;
@@ -112,6 +116,19 @@ impl ConfigurationApiTest for TestKitApi {
             .unwrap()
     }
 
+    fn config_history(&self) -> ConfigHistoryInfo {
+        self.public(ApiKind::Service(SERVICE_NAME))
+            .get("v1/configs/history")
+            .unwrap()
+    }
+
+    fn config_by_height(&self, height: Option<Height>) -> ConfigByHeightInfo {
+        self.public(ApiKind::Service(SERVICE_NAME))
+            .query(&HeightQuery { height })
+            .get("v1/configs/by_height")
+            .unwrap()
+    }
+
     fn post_config_propose(&self, cfg: &StoredConfiguration) -> ProposeResponse {
         self.private(ApiKind::Service(SERVICE_NAME))
             .query(cfg)
@@ -468,6 +485,84 @@ fn test_all_committed() {
     );
 }
 
+#[test]
+fn test_config_history() {
+    let mut testkit: TestKit = TestKit::configuration_default();
+    let initial_cfg = Schema::new(&testkit.snapshot()).actual_configuration();
+    let new_cfg = {
+        let mut cfg = testkit.configuration_change_proposal();
+        cfg.set_actual_from(Height(10));
+        cfg.set_service_config("message", "First config change");
+        cfg.stored_configuration().clone()
+    };
+    testkit.apply_configuration(ValidatorId(0), new_cfg.clone());
+    testkit.create_blocks_until(Height(12));
+
+    let api = testkit.api();
+    let history = api.config_history();
+    let actual_from = history
+        .history
+        .iter()
+        .map(|reference| (reference.actual_from(), *reference.cfg_hash()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        actual_from,
+        vec![
+            (Height(0), initial_cfg.hash()),
+            (Height(10), new_cfg.hash()),
+        ]
+    );
+
+    let info = api.config_by_height(Some(Height(9)));
+    assert_eq!(info.height, Height(9));
+    assert_eq!(info.hash, initial_cfg.hash());
+    assert_eq!(info.config, initial_cfg);
+
+    let info = api.config_by_height(Some(Height(10)));
+    assert_eq!(info.hash, new_cfg.hash());
+    assert_eq!(info.config, new_cfg);
+
+    let info = api.config_by_height(None);
+    assert_eq!(info.height, Height(12));
+    assert_eq!(info.hash, new_cfg.hash());
+}
+
+#[test]
+fn test_config_by_height_proof() {
+    let mut testkit: TestKit = TestKit::configuration_default();
+    let initial_cfg = Schema::new(&testkit.snapshot()).actual_configuration();
+    let new_cfg = {
+        let mut cfg = testkit.configuration_change_proposal();
+        cfg.set_actual_from(Height(10));
+        cfg.set_service_config("message", "First config change");
+        let mut consensus = cfg.consensus_configuration().clone();
+        consensus.configs_history_from = Some(Height(10));
+        cfg.set_consensus_configuration(consensus);
+        cfg.stored_configuration().clone()
+    };
+    testkit.apply_configuration(ValidatorId(0), new_cfg.clone());
+    testkit.create_blocks_until(Height(12));
+
+    let info = testkit.api().config_by_height(Some(Height(5)));
+    let block = &info.block_proof.block;
+    verify_block(block, &info.block_proof.precommits, &new_cfg.validator_keys).unwrap();
+
+    let to_table = info.to_table.check().unwrap();
+    assert_eq!(to_table.merkle_root(), *block.state_hash());
+    let history_key = Blockchain::service_table_unique_key(CORE_SERVICE, 2);
+    let history_root = *to_table
+        .entries()
+        .find(|&(key, _)| *key == history_key)
+        .unwrap()
+        .1;
+
+    let entries = info.history_proof.validate(history_root, 2).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].0, 0);
+    assert_eq!(*entries[0].1.cfg_hash(), initial_cfg.hash());
+    assert_eq!(info.config.hash(), initial_cfg.hash());
+}
+
 #[test]
 fn test_post_propose_tx() {
     let mut testkit: TestKit = TestKit::configuration_default();
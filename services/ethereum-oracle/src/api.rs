@@ -0,0 +1,80 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Exonum-ethereum-oracle API.
+
+use exonum::api;
+
+use schema::{EthEvent, OracleSchema};
+
+/// Maximum number of events returned by a single request.
+pub const MAX_EVENTS_PER_REQUEST: u64 = 1000;
+
+/// Query for the committed Ethereum events.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct EventsQuery {
+    /// Index of the first returned event.
+    pub start: u64,
+    /// Number of the returned events. Should not be greater than `MAX_EVENTS_PER_REQUEST`.
+    pub count: u64,
+}
+
+/// Information about the oracle progress.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OracleInfo {
+    /// Number of the first Ethereum block that has not been observed yet.
+    pub next_block: Option<u64>,
+    /// Number of the committed events.
+    pub events_count: u64,
+}
+
+/// Implements the exonum-ethereum-oracle public API.
+#[derive(Debug)]
+pub struct PublicApi;
+
+impl PublicApi {
+    /// Endpoint for getting the oracle progress.
+    pub fn info(state: &api::ServiceApiState, _query: ()) -> api::Result<OracleInfo> {
+        let view = state.snapshot();
+        let schema = OracleSchema::new(&view);
+        Ok(OracleInfo {
+            next_block: schema.next_block().get(),
+            events_count: schema.events().len(),
+        })
+    }
+
+    /// Endpoint for getting the committed events.
+    pub fn events(state: &api::ServiceApiState, query: EventsQuery) -> api::Result<Vec<EthEvent>> {
+        if query.count > MAX_EVENTS_PER_REQUEST {
+            return Err(api::Error::BadRequest(format!(
+                "Max events count per request exceeded ({})",
+                MAX_EVENTS_PER_REQUEST
+            )));
+        }
+
+        let view = state.snapshot();
+        let schema = OracleSchema::new(&view);
+        let events = schema.events();
+        let end = events.len().min(query.start.saturating_add(query.count));
+        Ok((query.start..end).filter_map(|i| events.get(i)).collect())
+    }
+
+    /// Used to extend Api.
+    pub fn wire(builder: &mut api::ServiceApiBuilder) {
+        builder
+            .public_scope()
+            .endpoint("v1/info", Self::info)
+            .endpoint("v1/events", Self::events);
+    }
+}
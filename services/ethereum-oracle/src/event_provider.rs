@@ -0,0 +1,261 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use exonum::{crypto::Hash, encoding::serialize::decode_hex};
+use failure;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use std::{
+    fmt,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use schema::EthEvent;
+
+/// Timeout of requests to the Ethereum JSON-RPC endpoint in seconds.
+pub const RPC_TIMEOUT_SECS: u64 = 3;
+
+/// A helper trait that provides the node with events of the Ethereum contract.
+pub trait EventProvider: Send + Sync + fmt::Debug {
+    /// Returns the number of the latest Ethereum block.
+    fn latest_block(&self) -> Result<u64, failure::Error>;
+
+    /// Returns events of the contract with the given address in the Ethereum blocks
+    /// from `from_block` to `to_block` inclusive, ordered by the block number and
+    /// the log index.
+    fn events(
+        &self,
+        contract_address: &str,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<EthEvent>, failure::Error>;
+}
+
+/// Provider of events obtained from an Ethereum node over JSON-RPC.
+pub struct JsonRpcEventProvider {
+    url: String,
+    client: Client,
+}
+
+impl JsonRpcEventProvider {
+    /// Creates a provider for the JSON-RPC endpoint at the given URL.
+    pub fn new<S: Into<String>>(url: S) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(RPC_TIMEOUT_SECS))
+            .build()
+            .expect("Cannot create HTTP client");
+        JsonRpcEventProvider {
+            url: url.into(),
+            client,
+        }
+    }
+
+    fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, failure::Error> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let response: RpcResponse<T> = self
+            .client
+            .post(&self.url)
+            .json(&request)
+            .send()?
+            .error_for_status()?
+            .json()?;
+        match response {
+            RpcResponse {
+                result: Some(result),
+                ..
+            } => Ok(result),
+            RpcResponse {
+                error: Some(error), ..
+            } => bail!(
+                "`{}` failed with code {}: {}",
+                method,
+                error.code,
+                error.message
+            ),
+            _ => bail!("`{}` returned an empty response", method),
+        }
+    }
+}
+
+impl fmt::Debug for JsonRpcEventProvider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("JsonRpcEventProvider")
+            .field("url", &self.url)
+            .finish()
+    }
+}
+
+impl EventProvider for JsonRpcEventProvider {
+    fn latest_block(&self) -> Result<u64, failure::Error> {
+        let number: String = self.call("eth_blockNumber", json!([]))?;
+        parse_quantity(&number)
+    }
+
+    fn events(
+        &self,
+        contract_address: &str,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<EthEvent>, failure::Error> {
+        let filter = json!({
+            "address": contract_address,
+            "fromBlock": format!("0x{:x}", from_block),
+            "toBlock": format!("0x{:x}", to_block),
+        });
+        let logs: Vec<RpcLog> = self.call("eth_getLogs", json!([filter]))?;
+        logs.into_iter()
+            .filter(|log| !log.removed)
+            .map(RpcLog::into_event)
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcLog {
+    block_number: String,
+    log_index: String,
+    transaction_hash: String,
+    topics: Vec<String>,
+    data: String,
+    #[serde(default)]
+    removed: bool,
+}
+
+impl RpcLog {
+    fn into_event(self) -> Result<EthEvent, failure::Error> {
+        let topics = self
+            .topics
+            .iter()
+            .map(|topic| parse_hash(topic))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(EthEvent::new(
+            parse_quantity(&self.block_number)?,
+            parse_quantity(&self.log_index)?,
+            &parse_hash(&self.transaction_hash)?,
+            &topics,
+            &parse_data(&self.data)?,
+        ))
+    }
+}
+
+fn strip_prefix(value: &str) -> Result<&str, failure::Error> {
+    ensure!(
+        value.starts_with("0x"),
+        "`{}` is not prefixed with 0x",
+        value
+    );
+    Ok(&value[2..])
+}
+
+fn parse_quantity(value: &str) -> Result<u64, failure::Error> {
+    Ok(u64::from_str_radix(strip_prefix(value)?, 16)?)
+}
+
+fn parse_data(value: &str) -> Result<Vec<u8>, failure::Error> {
+    Ok(decode_hex(strip_prefix(value)?)?)
+}
+
+fn parse_hash(value: &str) -> Result<Hash, failure::Error> {
+    Hash::from_slice(&parse_data(value)?).ok_or_else(|| format_err!("`{}` is not a hash", value))
+}
+
+/// Mock event provider for service testing.
+///
+/// Similar to the mock time provider of the time service, clones of the provider
+/// control the same state as the original instance.
+#[derive(Debug, Clone, Default)]
+pub struct MockEventProvider {
+    state: Arc<RwLock<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    latest_block: u64,
+    events: Vec<EthEvent>,
+}
+
+impl MockEventProvider {
+    /// Creates a new `MockEventProvider` without events.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of the latest Ethereum block.
+    pub fn set_latest_block(&self, latest_block: u64) {
+        self.state.write().unwrap().latest_block = latest_block;
+    }
+
+    /// Adds an event reported by the provider.
+    pub fn add_event(&self, event: EthEvent) {
+        self.state.write().unwrap().events.push(event);
+    }
+}
+
+impl EventProvider for MockEventProvider {
+    fn latest_block(&self) -> Result<u64, failure::Error> {
+        Ok(self.state.read().unwrap().latest_block)
+    }
+
+    fn events(
+        &self,
+        _contract_address: &str,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<EthEvent>, failure::Error> {
+        let mut events = self
+            .state
+            .read()
+            .unwrap()
+            .events
+            .iter()
+            .filter(|event| event.block_number() >= from_block && event.block_number() <= to_block)
+            .cloned()
+            .collect::<Vec<_>>();
+        events.sort_by_key(|event| (event.block_number(), event.log_index()));
+        Ok(events)
+    }
+}
+
+impl From<JsonRpcEventProvider> for Box<dyn EventProvider> {
+    fn from(provider: JsonRpcEventProvider) -> Self {
+        Box::new(provider) as Box<dyn EventProvider>
+    }
+}
+
+impl From<MockEventProvider> for Box<dyn EventProvider> {
+    fn from(provider: MockEventProvider) -> Self {
+        Box::new(provider) as Box<dyn EventProvider>
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Ethereum event oracle service for Exonum.
+//!
+//! The oracle reflects events emitted by an Ethereum contract in the Exonum blockchain.
+//! Every validator watches an Ethereum node over JSON-RPC in a background thread and,
+//! after each block commit, reports the events of the contract in the next range
+//! of Ethereum blocks with a transaction. The events are committed to the blockchain
+//! state once the identical observation is submitted by at least 2/3+1 of the validators.
+//! Ranges are observed only after they have received the configured number
+//! of confirmations in Ethereum, so that honest validators observe the same events.
+//! A validator replaces its observation if the Ethereum node reports different events
+//! for the range later, e.g., after a chain reorganization deeper than expected.
+//!
+//! The oracle configuration (the contract address, the first watched block, etc.) is
+//! a part of the global configuration of the blockchain, while the address of the
+//! Ethereum node is local to every validator.
+
+#![deny(
+    missing_debug_implementations,
+    missing_docs,
+    unsafe_code,
+    bare_trait_objects
+)]
+
+#[macro_use]
+extern crate exonum;
+#[macro_use]
+extern crate failure;
+#[macro_use]
+extern crate log;
+extern crate reqwest;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_json;
+
+/// Node API.
+pub mod api;
+/// Providers of the Ethereum events.
+pub mod event_provider;
+/// Background observation of the Ethereum events.
+pub mod observer;
+/// Database schema.
+pub mod schema;
+/// Node transactions.
+pub mod transactions;
+
+use exonum::{
+    api::ServiceApiBuilder,
+    blockchain::{Service, ServiceContext, Transaction, TransactionInfo, TransactionSet},
+    crypto::{CryptoHash, Hash},
+    encoding::{self, serialize::json::reexport::Value},
+    helpers::fabric::{Context, ServiceFactory},
+    messages::RawTransaction,
+    storage::{Fork, Snapshot},
+};
+use schema::OracleSchema;
+
+use event_provider::{EventProvider, JsonRpcEventProvider};
+use observer::Observer;
+use transactions::*;
+
+/// Ethereum oracle service id.
+pub const SERVICE_ID: u16 = 6;
+/// Ethereum oracle service name.
+pub const SERVICE_NAME: &str = "exonum_ethereum_oracle";
+
+/// Oracle configuration stored in the global configuration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OracleConfig {
+    /// Address of the watched contract as a hex string with the `0x` prefix.
+    pub contract_address: String,
+    /// Number of the first watched Ethereum block.
+    pub start_block: u64,
+    /// Number of Ethereum blocks covered by a single observation.
+    pub blocks_per_observation: u64,
+    /// Number of Ethereum blocks that must be built on top of the observed range
+    /// before the range is observed.
+    pub confirmations: u64,
+}
+
+/// Define the service.
+#[derive(Debug)]
+pub struct EthereumOracleService {
+    /// Oracle configuration written into the genesis block.
+    config: OracleConfig,
+    /// Observer of the Ethereum events.
+    observer: Observer,
+}
+
+impl EthereumOracleService {
+    /// Create a new `EthereumOracleService` with event provider `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `config.blocks_per_observation` is zero.
+    pub fn new<T: Into<Box<dyn EventProvider>>>(
+        config: OracleConfig,
+        provider: T,
+    ) -> EthereumOracleService {
+        assert!(
+            config.blocks_per_observation > 0,
+            "Observation must cover at least one block"
+        );
+        EthereumOracleService {
+            config,
+            observer: Observer::new(provider.into()),
+        }
+    }
+}
+
+impl Service for EthereumOracleService {
+    fn service_name(&self) -> &str {
+        SERVICE_NAME
+    }
+
+    fn state_hash(&self, snapshot: &dyn Snapshot) -> Vec<Hash> {
+        let schema = OracleSchema::new(snapshot);
+        schema.state_hash()
+    }
+
+    fn service_id(&self) -> u16 {
+        SERVICE_ID
+    }
+
+    fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<dyn Transaction>, encoding::Error> {
+        OracleTransactions::tx_from_raw(raw).map(Into::into)
+    }
+
+    fn transaction_types(&self) -> Vec<TransactionInfo> {
+        TransactionInfo::from_set::<OracleTransactions>()
+    }
+
+    /// Puts the oracle configuration into the genesis configuration.
+    fn initialize(&self, fork: &mut Fork) -> Value {
+        OracleSchema::new(fork)
+            .next_block_mut()
+            .set(self.config.start_block);
+        serde_json::to_value(&self.config).expect("Cannot serialize oracle config")
+    }
+
+    /// Creates transaction with the observed events after commit of the block
+    /// and requests the next observation of the pending range.
+    fn after_commit(&self, context: &ServiceContext) {
+        // The transaction must be created by the validator.
+        if context.validator_id().is_none() {
+            return;
+        }
+
+        let schema = OracleSchema::new(context.snapshot());
+        let config = match schema.config() {
+            Some(config) => config,
+            None => return,
+        };
+        let from_block = schema.next_block().get().unwrap_or(config.start_block);
+
+        // The observation is broadcast unless it is already accepted as the vote
+        // of this validator.
+        if let Some(observation) = self.observer.observation(from_block) {
+            if schema.votes().get(context.public_key()) != Some(observation.hash()) {
+                context.broadcast_transaction(TxObservation::new(observation));
+            }
+        }
+        self.observer.request(&config, from_block);
+    }
+
+    fn wire_api(&self, builder: &mut ServiceApiBuilder) {
+        api::PublicApi::wire(builder);
+    }
+}
+
+/// An Ethereum oracle service creator for the `NodeBuilder`.
+#[derive(Debug)]
+pub struct EthereumOracleServiceFactory {
+    config: OracleConfig,
+    rpc_url: String,
+}
+
+impl EthereumOracleServiceFactory {
+    /// Create a new factory watching the Ethereum node at `rpc_url`.
+    pub fn new<S: Into<String>>(config: OracleConfig, rpc_url: S) -> Self {
+        EthereumOracleServiceFactory {
+            config,
+            rpc_url: rpc_url.into(),
+        }
+    }
+}
+
+impl ServiceFactory for EthereumOracleServiceFactory {
+    fn service_name(&self) -> &str {
+        SERVICE_NAME
+    }
+
+    fn make_service(&mut self, _: &Context) -> Box<dyn Service> {
+        Box::new(EthereumOracleService::new(
+            self.config.clone(),
+            JsonRpcEventProvider::new(self.rpc_url.clone()),
+        ))
+    }
+}
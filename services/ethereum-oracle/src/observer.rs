@@ -0,0 +1,116 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use failure;
+
+use std::{
+    sync::{
+        mpsc::{channel, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use event_provider::EventProvider;
+use schema::Observation;
+use OracleConfig;
+
+/// Observer of the Ethereum events running in a separate thread.
+///
+/// Requests to the Ethereum node may take a long time, so they are not performed
+/// in the `after_commit` hook of the service. Instead, the hook requests the observation
+/// of the pending range and broadcasts the observations cached by the observer thread.
+#[derive(Debug)]
+pub struct Observer {
+    requests: Mutex<Sender<(OracleConfig, u64)>>,
+    state: Arc<Mutex<ObserverState>>,
+}
+
+#[derive(Debug, Default)]
+struct ObserverState {
+    in_progress: bool,
+    observation: Option<Observation>,
+}
+
+impl Observer {
+    /// Starts the observer thread that requests the events from the given provider.
+    pub fn new(provider: Box<dyn EventProvider>) -> Self {
+        let (requests, receiver) = channel::<(OracleConfig, u64)>();
+        let state = Arc::new(Mutex::new(ObserverState::default()));
+        let thread_state = Arc::clone(&state);
+        thread::Builder::new()
+            .name("ethereum-oracle".to_owned())
+            .spawn(move || {
+                // The thread stops once the observer is dropped.
+                for (config, from_block) in receiver {
+                    let result = observe(provider.as_ref(), &config, from_block);
+                    let mut state = thread_state.lock().unwrap();
+                    state.in_progress = false;
+                    match result {
+                        Ok(Some(observation)) => state.observation = Some(observation),
+                        Ok(None) => {}
+                        Err(e) => warn!(
+                            "Cannot observe Ethereum events starting from block {}: {}",
+                            from_block, e
+                        ),
+                    }
+                }
+            }).expect("Cannot spawn the observer thread");
+
+        Observer {
+            requests: Mutex::new(requests),
+            state,
+        }
+    }
+
+    /// Returns the latest observation of the range starting from the given block.
+    pub fn observation(&self, from_block: u64) -> Option<Observation> {
+        self.state
+            .lock()
+            .unwrap()
+            .observation
+            .as_ref()
+            .filter(|observation| observation.from_block() == from_block)
+            .cloned()
+    }
+
+    /// Requests the observation of the range starting from the given block, unless
+    /// another observation is in progress. The result is available via `observation`
+    /// once the range is confirmed in Ethereum.
+    pub fn request(&self, config: &OracleConfig, from_block: u64) {
+        let mut state = self.state.lock().unwrap();
+        if state.in_progress {
+            return;
+        }
+        state.in_progress = self
+            .requests
+            .lock()
+            .unwrap()
+            .send((config.clone(), from_block))
+            .is_ok();
+    }
+}
+
+fn observe(
+    provider: &dyn EventProvider,
+    config: &OracleConfig,
+    from_block: u64,
+) -> Result<Option<Observation>, failure::Error> {
+    let to_block = from_block + config.blocks_per_observation - 1;
+    if provider.latest_block()? < to_block + config.confirmations {
+        return Ok(None);
+    }
+    let events = provider.events(&config.contract_address, from_block, to_block)?;
+    Ok(Some(Observation::new(from_block, to_block, events)))
+}
@@ -0,0 +1,120 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use exonum::{
+    blockchain::Schema,
+    crypto::{Hash, PublicKey},
+    storage::{Entry, Fork, ProofListIndex, ProofMapIndex, Snapshot},
+};
+use serde_json;
+
+use {OracleConfig, SERVICE_NAME};
+
+encoding_struct! {
+    /// Event emitted by the watched Ethereum contract.
+    struct EthEvent {
+        /// Number of the Ethereum block containing the event.
+        block_number: u64,
+        /// Index of the event in the Ethereum block.
+        log_index: u64,
+        /// Hash of the Ethereum transaction that emitted the event.
+        transaction_hash: &Hash,
+        /// Indexed topics of the event.
+        topics: &[Hash],
+        /// Non-indexed data of the event.
+        data: &[u8],
+    }
+}
+
+encoding_struct! {
+    /// Events of the watched contract in a range of Ethereum blocks, as seen by a validator.
+    struct Observation {
+        /// The first Ethereum block of the range.
+        from_block: u64,
+        /// The last Ethereum block of the range, inclusive.
+        to_block: u64,
+        /// Events in the range ordered by the block number and the log index.
+        events: Vec<EthEvent>,
+    }
+}
+
+/// `Exonum-ethereum-oracle` service database schema.
+#[derive(Debug)]
+pub struct OracleSchema<T> {
+    view: T,
+}
+
+impl<T: AsRef<dyn Snapshot>> OracleSchema<T> {
+    /// Constructs schema for the given `snapshot`.
+    pub fn new(view: T) -> Self {
+        OracleSchema { view }
+    }
+
+    /// Returns the table that stores the Ethereum events agreed upon by the validators.
+    pub fn events(&self) -> ProofListIndex<&dyn Snapshot, EthEvent> {
+        ProofListIndex::new("exonum_ethereum_oracle.events", self.view.as_ref())
+    }
+
+    /// Returns the number of the first Ethereum block that has not been observed yet.
+    pub fn next_block(&self) -> Entry<&dyn Snapshot, u64> {
+        Entry::new("exonum_ethereum_oracle.next_block", self.view.as_ref())
+    }
+
+    /// Returns the table that stores the hashes of the observations of the pending
+    /// block range submitted by the validators.
+    pub fn votes(&self) -> ProofMapIndex<&dyn Snapshot, PublicKey, Hash> {
+        ProofMapIndex::new("exonum_ethereum_oracle.votes", self.view.as_ref())
+    }
+
+    /// Returns the oracle configuration from the actual configuration of the blockchain.
+    pub fn config(&self) -> Option<OracleConfig> {
+        Schema::new(self.view.as_ref())
+            .actual_configuration()
+            .services
+            .get(SERVICE_NAME)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    /// Returns hashes for stored tables.
+    pub fn state_hash(&self) -> Vec<Hash> {
+        vec![
+            self.events().merkle_root(),
+            self.next_block().hash(),
+            self.votes().merkle_root(),
+        ]
+    }
+}
+
+impl<'a> OracleSchema<&'a mut Fork> {
+    /// Mutable reference to the ['events'][1] index.
+    ///
+    /// [1]: struct.OracleSchema.html#method.events
+    pub fn events_mut(&mut self) -> ProofListIndex<&mut Fork, EthEvent> {
+        ProofListIndex::new("exonum_ethereum_oracle.events", self.view)
+    }
+
+    /// Mutable reference to the ['next_block'][1] index.
+    ///
+    /// [1]: struct.OracleSchema.html#method.next_block
+    pub fn next_block_mut(&mut self) -> Entry<&mut Fork, u64> {
+        Entry::new("exonum_ethereum_oracle.next_block", self.view)
+    }
+
+    /// Mutable reference to the ['votes'][1] index.
+    ///
+    /// [1]: struct.OracleSchema.html#method.votes
+    pub fn votes_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, Hash> {
+        ProofMapIndex::new("exonum_ethereum_oracle.votes", self.view)
+    }
+}
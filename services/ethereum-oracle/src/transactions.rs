@@ -0,0 +1,155 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Workaround for `failure` see https://github.com/rust-lang-nursery/failure/issues/223 and
+// ECR-1771 for the details.
+#![allow(bare_trait_objects)]
+
+use exonum::{
+    blockchain::{ExecutionError, ExecutionResult, Schema, Transaction, TransactionContext},
+    crypto::{CryptoHash, PublicKey, SecretKey},
+    messages::{Message, RawTransaction, Signed},
+    node::state::State,
+    storage::Fork,
+};
+
+use super::SERVICE_ID;
+use schema::{Observation, OracleSchema};
+
+/// Common errors emitted by transactions during execution.
+#[derive(Debug, Fail)]
+#[repr(u8)]
+pub enum Error {
+    /// The sender of the transaction is not among the active validators.
+    #[fail(display = "Not authored by a validator")]
+    UnknownSender = 0,
+
+    /// The oracle is not described in the actual configuration.
+    #[fail(display = "Oracle is not configured")]
+    NotConfigured = 1,
+
+    /// The observation does not cover the pending range of Ethereum blocks.
+    #[fail(display = "Observation does not cover the pending range of blocks")]
+    UnexpectedRange = 2,
+
+    /// Events of the observation are out of the range or are not ordered.
+    #[fail(display = "Observation contains invalid events")]
+    InvalidEvents = 3,
+
+    /// The validator has already submitted the same observation of the pending range.
+    #[fail(display = "Validator has already submitted this observation")]
+    AlreadyVoted = 4,
+}
+
+impl From<Error> for ExecutionError {
+    fn from(value: Error) -> ExecutionError {
+        let description = value.to_string();
+        ExecutionError::with_description(value as u8, description)
+    }
+}
+
+transactions! {
+    /// Define EthereumOracleService transaction.
+    pub OracleTransactions {
+
+        /// Transaction that is sent by the validator after observing the events
+        /// in the pending range of Ethereum blocks.
+        struct TxObservation {
+            /// Observed events.
+            observation: Observation,
+        }
+    }
+}
+
+impl TxObservation {
+    #[doc(hidden)]
+    pub fn sign(
+        observation: Observation,
+        public_key: &PublicKey,
+        secret_key: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            TxObservation::new(observation),
+            SERVICE_ID,
+            *public_key,
+            secret_key,
+        )
+    }
+
+    fn check_observation(&self, fork: &Fork) -> ExecutionResult {
+        let schema = OracleSchema::new(fork);
+        let config = schema.config().ok_or(Error::NotConfigured)?;
+        let from_block = schema.next_block().get().unwrap_or(config.start_block);
+        let observation = self.observation();
+
+        if observation.from_block() != from_block
+            || observation.to_block() != from_block + config.blocks_per_observation - 1
+        {
+            Err(Error::UnexpectedRange)?
+        }
+
+        let events = observation.events();
+        let in_range = events.iter().all(|event| {
+            event.block_number() >= observation.from_block()
+                && event.block_number() <= observation.to_block()
+        });
+        let ordered = events.windows(2).all(|pair| {
+            (pair[0].block_number(), pair[0].log_index())
+                < (pair[1].block_number(), pair[1].log_index())
+        });
+        if !in_range || !ordered {
+            Err(Error::InvalidEvents)?
+        }
+        Ok(())
+    }
+
+    fn vote(&self, fork: &mut Fork, author: &PublicKey) -> ExecutionResult {
+        let keys = Schema::new(&fork).actual_configuration().validator_keys;
+        if !keys.iter().any(|k| k.service_key == *author) {
+            Err(Error::UnknownSender)?
+        }
+
+        // The validator may replace its vote with a different observation.
+        let mut schema = OracleSchema::new(fork);
+        let observation = self.observation();
+        let observation_hash = observation.hash();
+        if schema.votes().get(author) == Some(observation_hash) {
+            Err(Error::AlreadyVoted)?
+        }
+        schema.votes_mut().put(author, observation_hash);
+
+        // Only the votes of the actual validators are counted.
+        let votes = {
+            let votes = schema.votes();
+            keys.iter()
+                .filter(|k| votes.get(&k.service_key) == Some(observation_hash))
+                .count()
+        };
+        if votes >= State::byzantine_majority_count(keys.len()) {
+            schema.events_mut().extend(observation.events());
+            schema.next_block_mut().set(observation.to_block() + 1);
+            schema.votes_mut().clear();
+        }
+        Ok(())
+    }
+}
+
+impl Transaction for TxObservation {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let author = context.author();
+        let fork = context.fork();
+        self.check_observation(fork)?;
+        self.vote(fork, &author)
+    }
+}
@@ -0,0 +1,224 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate exonum;
+extern crate exonum_ethereum_oracle;
+#[macro_use]
+extern crate exonum_testkit;
+#[macro_use]
+extern crate pretty_assertions;
+
+use exonum::{
+    blockchain::{Schema, TransactionErrorType, TransactionResult},
+    crypto::{gen_keypair, hash, CryptoHash, Hash},
+    helpers::Height,
+    messages::{RawTransaction, Signed},
+    storage::Snapshot,
+};
+use exonum_ethereum_oracle::{
+    event_provider::MockEventProvider,
+    schema::{EthEvent, Observation, OracleSchema},
+    transactions::{Error, TxObservation},
+    EthereumOracleService, OracleConfig,
+};
+use exonum_testkit::{TestKit, TestKitBuilder};
+
+use std::{thread, time::Duration};
+
+fn oracle_config() -> OracleConfig {
+    OracleConfig {
+        contract_address: "0x0000000000000000000000000000000000000001".to_owned(),
+        start_block: 10,
+        blocks_per_observation: 5,
+        confirmations: 2,
+    }
+}
+
+fn create_testkit(provider: &MockEventProvider) -> TestKit {
+    TestKitBuilder::validator()
+        .with_validators(4)
+        .with_service(EthereumOracleService::new(
+            oracle_config(),
+            provider.clone(),
+        ))
+        .create()
+}
+
+fn event(block_number: u64, log_index: u64) -> EthEvent {
+    EthEvent::new(
+        block_number,
+        log_index,
+        &hash(&[block_number as u8]),
+        &[hash(b"Transfer")],
+        &[log_index as u8],
+    )
+}
+
+fn observation_tx(
+    testkit: &TestKit,
+    validator: usize,
+    events: Vec<EthEvent>,
+) -> Signed<RawTransaction> {
+    let validator = &testkit.network().validators()[validator];
+    let (public_key, secret_key) = validator.service_keypair();
+    TxObservation::sign(Observation::new(10, 14, events), public_key, secret_key)
+}
+
+/// Creates blocks until the vote of the node is equal to the expected one.
+/// Events are observed in a background thread, so it may take several blocks.
+fn wait_for_vote(testkit: &mut TestKit, expected: Hash) {
+    let us = *testkit.us().service_keypair().0;
+    for _ in 0..100 {
+        testkit.create_block();
+        if OracleSchema::new(testkit.snapshot()).votes().get(&us) == Some(expected) {
+            return;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("The node has not submitted the expected observation");
+}
+
+fn assert_transaction_result<S: AsRef<Snapshot>>(
+    snapshot: S,
+    transaction: &Signed<RawTransaction>,
+    expected_code: u8,
+) {
+    let result = Schema::new(snapshot)
+        .transaction_results()
+        .get(&transaction.hash());
+    match result {
+        Some(TransactionResult(Err(e))) => {
+            assert_eq!(e.error_type(), TransactionErrorType::Code(expected_code));
+        }
+        _ => panic!("Expected Err(), found None or Ok()"),
+    }
+}
+
+#[test]
+fn test_events_are_committed_by_majority() {
+    let provider = MockEventProvider::new();
+    provider.add_event(event(14, 0));
+    provider.add_event(event(12, 1));
+    provider.add_event(event(15, 0));
+    let mut testkit = create_testkit(&provider);
+
+    // The range 10..=14 does not have enough confirmations yet.
+    provider.set_latest_block(15);
+    testkit.create_blocks_until(Height(3));
+    assert!(OracleSchema::new(testkit.snapshot())
+        .votes()
+        .iter()
+        .next()
+        .is_none());
+
+    // The node observes the range and submits its observation.
+    provider.set_latest_block(16);
+    let events = vec![event(12, 1), event(14, 0)];
+    wait_for_vote(
+        &mut testkit,
+        Observation::new(10, 14, events.clone()).hash(),
+    );
+
+    // The second validator agrees, another validator reports a different observation.
+    let agreeing = observation_tx(&testkit, 1, events.clone());
+    let different = observation_tx(&testkit, 2, vec![event(12, 1)]);
+    testkit.create_block_with_transactions(txvec![agreeing, different]);
+    {
+        let snapshot = testkit.snapshot();
+        let schema = OracleSchema::new(&snapshot);
+        assert_eq!(schema.events().len(), 0);
+        assert_eq!(schema.next_block().get(), Some(10));
+    }
+
+    // The third matching observation commits the events.
+    let agreeing = observation_tx(&testkit, 3, events.clone());
+    testkit.create_block_with_transactions(txvec![agreeing]);
+    let snapshot = testkit.snapshot();
+    let schema = OracleSchema::new(&snapshot);
+    assert_eq!(schema.events().iter().collect::<Vec<_>>(), events);
+    assert_eq!(schema.next_block().get(), Some(15));
+    assert!(schema.votes().iter().next().is_none());
+}
+
+#[test]
+fn test_invalid_observations() {
+    let provider = MockEventProvider::new();
+    let mut testkit = create_testkit(&provider);
+
+    let (public_key, secret_key) = {
+        let validator = &testkit.network().validators()[1];
+        let (public_key, secret_key) = validator.service_keypair();
+        (*public_key, secret_key.clone())
+    };
+    let unexpected_range =
+        TxObservation::sign(Observation::new(15, 19, vec![]), &public_key, &secret_key);
+    let invalid_events = TxObservation::sign(
+        Observation::new(10, 14, vec![event(14, 0), event(12, 0)]),
+        &public_key,
+        &secret_key,
+    );
+    let (unknown_key, unknown_secret) = gen_keypair();
+    let unknown_sender = TxObservation::sign(
+        Observation::new(10, 14, vec![]),
+        &unknown_key,
+        &unknown_secret,
+    );
+    testkit.create_block_with_transactions(txvec![
+        unexpected_range.clone(),
+        invalid_events.clone(),
+        unknown_sender.clone(),
+    ]);
+
+    let snapshot = testkit.snapshot();
+    assert_transaction_result(&snapshot, &unexpected_range, Error::UnexpectedRange as u8);
+    assert_transaction_result(&snapshot, &invalid_events, Error::InvalidEvents as u8);
+    assert_transaction_result(&snapshot, &unknown_sender, Error::UnknownSender as u8);
+
+    let vote = observation_tx(&testkit, 1, vec![event(11, 0)]);
+    let duplicate = observation_tx(&testkit, 1, vec![event(11, 0)]);
+    testkit.create_block_with_transactions(txvec![vote]);
+    testkit.create_block_with_transactions(txvec![duplicate.clone()]);
+    assert_transaction_result(testkit.snapshot(), &duplicate, Error::AlreadyVoted as u8);
+}
+
+#[test]
+fn test_observation_is_replaced() {
+    let provider = MockEventProvider::new();
+    provider.add_event(event(12, 0));
+    provider.set_latest_block(16);
+    let mut testkit = create_testkit(&provider);
+    wait_for_vote(
+        &mut testkit,
+        Observation::new(10, 14, vec![event(12, 0)]).hash(),
+    );
+
+    // The Ethereum node reports an additional event in the range, e.g.,
+    // after a reorganization of its chain.
+    provider.add_event(event(13, 0));
+    let events = vec![event(12, 0), event(13, 0)];
+    wait_for_vote(
+        &mut testkit,
+        Observation::new(10, 14, events.clone()).hash(),
+    );
+
+    // The replaced vote is counted together with the matching observations.
+    let agreeing = (1..3)
+        .map(|validator| observation_tx(&testkit, validator, events.clone()))
+        .collect::<Vec<_>>();
+    testkit.create_block_with_transactions(agreeing);
+    let snapshot = testkit.snapshot();
+    let schema = OracleSchema::new(&snapshot);
+    assert_eq!(schema.events().iter().collect::<Vec<_>>(), events);
+    assert_eq!(schema.next_block().get(), Some(15));
+}
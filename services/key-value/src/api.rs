@@ -0,0 +1,121 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Exonum-key-value API.
+
+use exonum::{
+    api::{self, ServiceApiBuilder, ServiceApiState},
+    blockchain::{self, BlockProof},
+    crypto::{Hash, PublicKey},
+    storage::MapProof,
+};
+
+use schema::{entry_key, namespace_key, Entry, KeyValueSchema, Namespace};
+use SERVICE_ID;
+
+/// Describes the query parameters for the `namespace_info` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NamespaceQuery {
+    /// Name of the namespace.
+    pub name: String,
+}
+
+/// Describes the query parameters for the endpoints of a single entry.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EntryQuery {
+    /// Name of the namespace.
+    pub namespace: String,
+    /// Key of the entry.
+    pub key: String,
+}
+
+/// Namespace with its access control list.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct NamespaceInfo {
+    /// Namespace record.
+    pub namespace: Namespace,
+    /// Keys granted write access to the namespace in addition to its owner.
+    pub writers: Vec<PublicKey>,
+}
+
+/// Proof of an entry (or its absence) in a namespace.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EntryProof {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the whole database table.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Proof of the namespace in this table.
+    pub to_namespace: MapProof<Hash, Namespace>,
+    /// Proof of the entry against the root hash of the namespace entries.
+    pub to_entry: MapProof<Hash, Entry>,
+}
+
+/// Implements the exonum-key-value public API.
+#[derive(Debug)]
+pub struct PublicApi;
+
+impl PublicApi {
+    /// Endpoint for getting the namespace with its access control list.
+    pub fn namespace_info(
+        state: &ServiceApiState,
+        query: NamespaceQuery,
+    ) -> api::Result<NamespaceInfo> {
+        let schema = KeyValueSchema::new(state.snapshot());
+        let namespace = schema
+            .namespace(&query.name)
+            .ok_or_else(|| api::Error::NotFound("Namespace not found".to_owned()))?;
+        let writers = schema.writers(&query.name).iter().collect();
+        Ok(NamespaceInfo { namespace, writers })
+    }
+
+    /// Endpoint for getting a single entry.
+    pub fn entry(state: &ServiceApiState, query: EntryQuery) -> api::Result<Entry> {
+        KeyValueSchema::new(state.snapshot())
+            .entry(&query.namespace, &query.key)
+            .ok_or_else(|| api::Error::NotFound("Entry not found".to_owned()))
+    }
+
+    /// Endpoint for getting the proof of an entry.
+    pub fn entry_proof(state: &ServiceApiState, query: EntryQuery) -> api::Result<EntryProof> {
+        let snapshot = state.snapshot();
+        let general_schema = blockchain::Schema::new(&snapshot);
+        let block_proof = general_schema
+            .block_and_precommits(general_schema.height())
+            .unwrap();
+        let to_table = general_schema.get_proof_to_service_table(SERVICE_ID, 0);
+        let schema = KeyValueSchema::new(&snapshot);
+        let to_namespace = schema
+            .namespaces()
+            .get_proof(namespace_key(&query.namespace));
+        let to_entry = schema
+            .entries(&query.namespace)
+            .get_proof(entry_key(&query.key));
+        Ok(EntryProof {
+            block_proof,
+            to_table,
+            to_namespace,
+            to_entry,
+        })
+    }
+
+    /// Used to extend Api.
+    pub fn wire(builder: &mut ServiceApiBuilder) {
+        builder
+            .public_scope()
+            .endpoint("v1/namespaces/info", Self::namespace_info)
+            .endpoint("v1/entries", Self::entry)
+            .endpoint("v1/entries/proof", Self::entry_proof);
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The general-purpose key-value storage service for Exonum.
+//!
+//! Entries are grouped into namespaces. A namespace is created by a
+//! `TxCreateNamespace` transaction; its author becomes the owner of the namespace and
+//! may grant write access to other keys with `TxGrant` and `TxRevoke` transactions.
+//! The owner and the granted keys modify entries with `TxPut` and `TxRemove`
+//! transactions. The total size of the keys and values in a namespace is limited
+//! by the quota set on its creation.
+//!
+//! The entries of a namespace are kept in a separate Merkelized index, whose root hash
+//! is stored in the namespace record. Thus, the public API provides the proof of
+//! an entry as a chain of proofs from the block header to the namespace and from
+//! the namespace to the entry.
+
+#![deny(
+    missing_debug_implementations,
+    missing_docs,
+    unsafe_code,
+    bare_trait_objects
+)]
+
+#[macro_use]
+extern crate exonum;
+#[macro_use]
+extern crate failure;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+
+/// Node API.
+pub mod api;
+/// Database schema.
+pub mod schema;
+/// Node transactions.
+pub mod transactions;
+
+use exonum::{
+    api::ServiceApiBuilder,
+    blockchain::{Service, Transaction, TransactionInfo, TransactionSet},
+    crypto::Hash,
+    encoding,
+    helpers::fabric::{Context, ServiceFactory},
+    messages::RawTransaction,
+    storage::Snapshot,
+};
+
+use schema::KeyValueSchema;
+use transactions::KeyValueTransactions;
+
+/// Key-value service id.
+pub const SERVICE_ID: u16 = 8;
+/// Key-value service name.
+pub const SERVICE_NAME: &str = "exonum_key_value";
+
+/// Define the service.
+#[derive(Debug, Default)]
+pub struct KeyValueService;
+
+impl Service for KeyValueService {
+    fn service_name(&self) -> &str {
+        SERVICE_NAME
+    }
+
+    fn state_hash(&self, snapshot: &dyn Snapshot) -> Vec<Hash> {
+        let schema = KeyValueSchema::new(snapshot);
+        schema.state_hash()
+    }
+
+    fn service_id(&self) -> u16 {
+        SERVICE_ID
+    }
+
+    fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<dyn Transaction>, encoding::Error> {
+        KeyValueTransactions::tx_from_raw(raw).map(Into::into)
+    }
+
+    fn transaction_types(&self) -> Vec<TransactionInfo> {
+        TransactionInfo::from_set::<KeyValueTransactions>()
+    }
+
+    fn wire_api(&self, builder: &mut ServiceApiBuilder) {
+        api::PublicApi::wire(builder);
+    }
+}
+
+/// A key-value service creator for the `NodeBuilder`.
+#[derive(Debug)]
+pub struct KeyValueServiceFactory;
+
+impl ServiceFactory for KeyValueServiceFactory {
+    fn service_name(&self) -> &str {
+        SERVICE_NAME
+    }
+
+    fn make_service(&mut self, _: &Context) -> Box<dyn Service> {
+        Box::new(KeyValueService)
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use exonum::{
+    crypto::{self, Hash, PublicKey},
+    storage::{Fork, KeySetIndex, ProofMapIndex, Snapshot},
+};
+
+encoding_struct! {
+    /// Namespace of key-value entries.
+    struct Namespace {
+        /// Name of the namespace.
+        name: &str,
+        /// Owner of the namespace, who manages its access control list.
+        owner: &PublicKey,
+        /// Maximal total size of the keys and values in the namespace, in bytes.
+        quota: u64,
+        /// Total size of the keys and values in the namespace, in bytes.
+        used: u64,
+        /// Root hash of the entries of the namespace.
+        entries_hash: &Hash,
+    }
+}
+
+impl Namespace {
+    /// Returns a copy of this namespace with the updated size and root hash of the entries.
+    pub fn set_entries(self, used: u64, entries_hash: &Hash) -> Self {
+        Self::new(self.name(), self.owner(), self.quota(), used, entries_hash)
+    }
+}
+
+encoding_struct! {
+    /// Key-value entry of a namespace.
+    struct Entry {
+        /// Key of the entry.
+        key: &str,
+        /// Value of the entry.
+        value: &[u8],
+        /// Author of the last modification of the entry.
+        author: &PublicKey,
+    }
+}
+
+impl Entry {
+    /// Returns the size of the entry accounted in the namespace quota.
+    pub fn size(&self) -> u64 {
+        (self.key().len() + self.value().len()) as u64
+    }
+}
+
+/// Returns the key of the namespace in the `namespaces` index.
+pub fn namespace_key(name: &str) -> Hash {
+    crypto::hash(name.as_bytes())
+}
+
+/// Returns the key of the entry in the `entries` index of its namespace.
+pub fn entry_key(key: &str) -> Hash {
+    crypto::hash(key.as_bytes())
+}
+
+/// `Exonum-key-value` service database schema.
+#[derive(Debug)]
+pub struct KeyValueSchema<T> {
+    view: T,
+}
+
+impl<T: AsRef<dyn Snapshot>> KeyValueSchema<T> {
+    /// Constructs schema for the given `snapshot`.
+    pub fn new(view: T) -> Self {
+        KeyValueSchema { view }
+    }
+
+    /// Returns the table that stores the namespaces keyed by the hash of their names.
+    pub fn namespaces(&self) -> ProofMapIndex<&dyn Snapshot, Hash, Namespace> {
+        ProofMapIndex::new("exonum_key_value.namespaces", self.view.as_ref())
+    }
+
+    /// Returns the namespace with the given name.
+    pub fn namespace(&self, name: &str) -> Option<Namespace> {
+        self.namespaces().get(&namespace_key(name))
+    }
+
+    /// Returns the table that stores the entries of the namespace keyed by the hash
+    /// of their keys.
+    pub fn entries(&self, namespace: &str) -> ProofMapIndex<&dyn Snapshot, Hash, Entry> {
+        ProofMapIndex::new_in_family(
+            "exonum_key_value.entries",
+            &namespace_key(namespace),
+            self.view.as_ref(),
+        )
+    }
+
+    /// Returns the entry of the namespace with the given key.
+    pub fn entry(&self, namespace: &str, key: &str) -> Option<Entry> {
+        self.entries(namespace).get(&entry_key(key))
+    }
+
+    /// Returns the table that stores the keys granted write access to the namespace
+    /// in addition to its owner.
+    pub fn writers(&self, namespace: &str) -> KeySetIndex<&dyn Snapshot, PublicKey> {
+        KeySetIndex::new_in_family(
+            "exonum_key_value.writers",
+            &namespace_key(namespace),
+            self.view.as_ref(),
+        )
+    }
+
+    /// Checks whether the key may modify the entries of the namespace.
+    pub fn can_write(&self, namespace: &Namespace, key: &PublicKey) -> bool {
+        namespace.owner() == key || self.writers(namespace.name()).contains(key)
+    }
+
+    /// Returns hashes for stored tables.
+    pub fn state_hash(&self) -> Vec<Hash> {
+        vec![self.namespaces().merkle_root()]
+    }
+}
+
+impl<'a> KeyValueSchema<&'a mut Fork> {
+    /// Mutable reference to the ['namespaces'][1] index.
+    ///
+    /// [1]: struct.KeyValueSchema.html#method.namespaces
+    pub fn namespaces_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, Namespace> {
+        ProofMapIndex::new("exonum_key_value.namespaces", &mut self.view)
+    }
+
+    /// Mutable reference to the ['entries'][1] index.
+    ///
+    /// [1]: struct.KeyValueSchema.html#method.entries
+    pub fn entries_mut(&mut self, namespace: &str) -> ProofMapIndex<&mut Fork, Hash, Entry> {
+        ProofMapIndex::new_in_family(
+            "exonum_key_value.entries",
+            &namespace_key(namespace),
+            &mut self.view,
+        )
+    }
+
+    /// Mutable reference to the ['writers'][1] index.
+    ///
+    /// [1]: struct.KeyValueSchema.html#method.writers
+    pub fn writers_mut(&mut self, namespace: &str) -> KeySetIndex<&mut Fork, PublicKey> {
+        KeySetIndex::new_in_family(
+            "exonum_key_value.writers",
+            &namespace_key(namespace),
+            &mut self.view,
+        )
+    }
+
+    /// Creates an empty namespace.
+    pub fn create_namespace(&mut self, name: &str, owner: &PublicKey, quota: u64) {
+        let entries_hash = self.entries(name).merkle_root();
+        let namespace = Namespace::new(name, owner, quota, 0, &entries_hash);
+        self.namespaces_mut().put(&namespace_key(name), namespace);
+    }
+
+    /// Puts the entry to the namespace, replacing the entry with the same key, if any,
+    /// and updates the size and root hash of the namespace entries.
+    pub fn put_entry(&mut self, namespace: Namespace, entry: Entry) {
+        let old_size = self
+            .entry(namespace.name(), entry.key())
+            .map_or(0, |old| old.size());
+        let used = namespace.used() - old_size + entry.size();
+        let entries_hash = {
+            let mut entries = self.entries_mut(namespace.name());
+            entries.put(&entry_key(entry.key()), entry);
+            entries.merkle_root()
+        };
+        self.update_namespace(namespace.set_entries(used, &entries_hash));
+    }
+
+    /// Removes the entry from the namespace and updates the size and root hash
+    /// of the namespace entries.
+    ///
+    /// Panics if there is no entry with the given key.
+    pub fn remove_entry(&mut self, namespace: Namespace, key: &str) {
+        let old = self
+            .entry(namespace.name(), key)
+            .expect("Entry does not exist");
+        let used = namespace.used() - old.size();
+        let entries_hash = {
+            let mut entries = self.entries_mut(namespace.name());
+            entries.remove(&entry_key(key));
+            entries.merkle_root()
+        };
+        self.update_namespace(namespace.set_entries(used, &entries_hash));
+    }
+
+    fn update_namespace(&mut self, namespace: Namespace) {
+        let key = namespace_key(namespace.name());
+        self.namespaces_mut().put(&key, namespace);
+    }
+}
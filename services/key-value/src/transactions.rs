@@ -0,0 +1,344 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Workaround for `failure` see https://github.com/rust-lang-nursery/failure/issues/223 and
+// ECR-1771 for the details.
+#![allow(bare_trait_objects)]
+
+use exonum::{
+    blockchain::{ExecutionError, ExecutionResult, Transaction, TransactionContext},
+    crypto::{PublicKey, SecretKey},
+    messages::{Message, RawTransaction, Signed},
+    storage::Fork,
+};
+
+use super::SERVICE_ID;
+use schema::{Entry, KeyValueSchema, Namespace};
+
+/// Maximal quota of a namespace, in bytes.
+pub const MAX_NAMESPACE_QUOTA: u64 = 1 << 20;
+
+/// Common errors emitted by transactions during execution.
+#[derive(Debug, Fail)]
+#[repr(u8)]
+pub enum Error {
+    /// The namespace name is empty.
+    ///
+    /// Can be emitted by `TxCreateNamespace`.
+    #[fail(display = "Invalid namespace name")]
+    InvalidName = 0,
+
+    /// The quota is zero or exceeds `MAX_NAMESPACE_QUOTA`.
+    ///
+    /// Can be emitted by `TxCreateNamespace`.
+    #[fail(display = "Invalid namespace quota")]
+    InvalidQuota = 1,
+
+    /// The namespace already exists.
+    ///
+    /// Can be emitted by `TxCreateNamespace`.
+    #[fail(display = "Namespace already exists")]
+    NamespaceExists = 2,
+
+    /// The namespace does not exist.
+    #[fail(display = "Namespace not found")]
+    NamespaceNotFound = 3,
+
+    /// Author of the transaction is not the owner of the namespace.
+    ///
+    /// Can be emitted by `TxGrant` or `TxRevoke`.
+    #[fail(display = "Sender is not the owner of the namespace")]
+    NotOwner = 4,
+
+    /// The key to revoke has no write access to the namespace.
+    ///
+    /// Can be emitted by `TxRevoke`.
+    #[fail(display = "Key is not a writer of the namespace")]
+    WriterNotFound = 5,
+
+    /// Author of the transaction has no write access to the namespace.
+    ///
+    /// Can be emitted by `TxPut` or `TxRemove`.
+    #[fail(display = "Sender has no write access to the namespace")]
+    AccessDenied = 6,
+
+    /// The entry does not fit into the namespace quota.
+    ///
+    /// Can be emitted by `TxPut`.
+    #[fail(display = "Namespace quota exceeded")]
+    QuotaExceeded = 7,
+
+    /// The entry does not exist.
+    ///
+    /// Can be emitted by `TxRemove`.
+    #[fail(display = "Entry not found")]
+    EntryNotFound = 8,
+}
+
+impl From<Error> for ExecutionError {
+    fn from(value: Error) -> ExecutionError {
+        let description = value.to_string();
+        ExecutionError::with_description(value as u8, description)
+    }
+}
+
+transactions! {
+    /// Define KeyValueService transaction.
+    pub KeyValueTransactions {
+
+        /// Transaction creating a namespace owned by the author.
+        struct TxCreateNamespace {
+            /// Name of the namespace.
+            name: &str,
+            /// Maximal total size of the keys and values in the namespace, in bytes.
+            quota: u64,
+        }
+
+        /// Transaction granting write access to the namespace.
+        ///
+        /// Can be submitted only by the owner of the namespace.
+        struct TxGrant {
+            /// Name of the namespace.
+            namespace: &str,
+            /// Key granted write access.
+            writer: &PublicKey,
+            /// Auxiliary number to guarantee non-idempotence of transactions.
+            seed: u64,
+        }
+
+        /// Transaction revoking write access to the namespace.
+        ///
+        /// Can be submitted only by the owner of the namespace.
+        struct TxRevoke {
+            /// Name of the namespace.
+            namespace: &str,
+            /// Key whose write access is revoked.
+            writer: &PublicKey,
+            /// Auxiliary number to guarantee non-idempotence of transactions.
+            seed: u64,
+        }
+
+        /// Transaction putting an entry to the namespace, replacing the entry with
+        /// the same key, if any.
+        struct TxPut {
+            /// Name of the namespace.
+            namespace: &str,
+            /// Key of the entry.
+            key: &str,
+            /// Value of the entry.
+            value: &[u8],
+            /// Auxiliary number to guarantee non-idempotence of transactions.
+            seed: u64,
+        }
+
+        /// Transaction removing an entry from the namespace.
+        struct TxRemove {
+            /// Name of the namespace.
+            namespace: &str,
+            /// Key of the entry.
+            key: &str,
+            /// Auxiliary number to guarantee non-idempotence of transactions.
+            seed: u64,
+        }
+    }
+}
+
+impl TxCreateNamespace {
+    #[doc(hidden)]
+    pub fn sign(
+        name: &str,
+        quota: u64,
+        public_key: &PublicKey,
+        secret_key: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            TxCreateNamespace::new(name, quota),
+            SERVICE_ID,
+            *public_key,
+            secret_key,
+        )
+    }
+}
+
+impl TxGrant {
+    #[doc(hidden)]
+    pub fn sign(
+        namespace: &str,
+        writer: &PublicKey,
+        seed: u64,
+        public_key: &PublicKey,
+        secret_key: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            TxGrant::new(namespace, writer, seed),
+            SERVICE_ID,
+            *public_key,
+            secret_key,
+        )
+    }
+}
+
+impl TxRevoke {
+    #[doc(hidden)]
+    pub fn sign(
+        namespace: &str,
+        writer: &PublicKey,
+        seed: u64,
+        public_key: &PublicKey,
+        secret_key: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            TxRevoke::new(namespace, writer, seed),
+            SERVICE_ID,
+            *public_key,
+            secret_key,
+        )
+    }
+}
+
+impl TxPut {
+    #[doc(hidden)]
+    pub fn sign(
+        namespace: &str,
+        key: &str,
+        value: &[u8],
+        seed: u64,
+        public_key: &PublicKey,
+        secret_key: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            TxPut::new(namespace, key, value, seed),
+            SERVICE_ID,
+            *public_key,
+            secret_key,
+        )
+    }
+}
+
+impl TxRemove {
+    #[doc(hidden)]
+    pub fn sign(
+        namespace: &str,
+        key: &str,
+        seed: u64,
+        public_key: &PublicKey,
+        secret_key: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            TxRemove::new(namespace, key, seed),
+            SERVICE_ID,
+            *public_key,
+            secret_key,
+        )
+    }
+}
+
+impl Transaction for TxCreateNamespace {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        if self.name().is_empty() {
+            Err(Error::InvalidName)?
+        }
+        if self.quota() == 0 || self.quota() > MAX_NAMESPACE_QUOTA {
+            Err(Error::InvalidQuota)?
+        }
+
+        let author = context.author();
+        let mut schema = KeyValueSchema::new(context.fork());
+        if schema.namespace(self.name()).is_some() {
+            Err(Error::NamespaceExists)?
+        }
+        schema.create_namespace(self.name(), &author, self.quota());
+        Ok(())
+    }
+}
+
+impl Transaction for TxGrant {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let author = context.author();
+        let mut schema = KeyValueSchema::new(context.fork());
+        owned_namespace(&schema, self.namespace(), &author)?;
+        schema.writers_mut(self.namespace()).insert(*self.writer());
+        Ok(())
+    }
+}
+
+impl Transaction for TxRevoke {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let author = context.author();
+        let mut schema = KeyValueSchema::new(context.fork());
+        owned_namespace(&schema, self.namespace(), &author)?;
+        if !schema.writers(self.namespace()).contains(self.writer()) {
+            Err(Error::WriterNotFound)?
+        }
+        schema.writers_mut(self.namespace()).remove(self.writer());
+        Ok(())
+    }
+}
+
+impl Transaction for TxPut {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let author = context.author();
+        let mut schema = KeyValueSchema::new(context.fork());
+        let namespace = writable_namespace(&schema, self.namespace(), &author)?;
+
+        let entry = Entry::new(self.key(), self.value(), &author);
+        let old_size = schema
+            .entry(self.namespace(), self.key())
+            .map_or(0, |old| old.size());
+        if namespace.used() - old_size + entry.size() > namespace.quota() {
+            Err(Error::QuotaExceeded)?
+        }
+        schema.put_entry(namespace, entry);
+        Ok(())
+    }
+}
+
+impl Transaction for TxRemove {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let author = context.author();
+        let mut schema = KeyValueSchema::new(context.fork());
+        let namespace = writable_namespace(&schema, self.namespace(), &author)?;
+        if schema.entry(self.namespace(), self.key()).is_none() {
+            Err(Error::EntryNotFound)?
+        }
+        schema.remove_entry(namespace, self.key());
+        Ok(())
+    }
+}
+
+/// Returns the namespace if it is owned by `author`.
+fn owned_namespace(
+    schema: &KeyValueSchema<&mut Fork>,
+    name: &str,
+    author: &PublicKey,
+) -> Result<Namespace, Error> {
+    let namespace = schema.namespace(name).ok_or(Error::NamespaceNotFound)?;
+    if namespace.owner() != author {
+        return Err(Error::NotOwner);
+    }
+    Ok(namespace)
+}
+
+/// Returns the namespace if `author` has write access to it.
+fn writable_namespace(
+    schema: &KeyValueSchema<&mut Fork>,
+    name: &str,
+    author: &PublicKey,
+) -> Result<Namespace, Error> {
+    let namespace = schema.namespace(name).ok_or(Error::NamespaceNotFound)?;
+    if !schema.can_write(&namespace, author) {
+        return Err(Error::AccessDenied);
+    }
+    Ok(namespace)
+}
@@ -0,0 +1,234 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[macro_use]
+extern crate assert_matches;
+extern crate exonum;
+extern crate exonum_key_value;
+#[macro_use]
+extern crate exonum_testkit;
+#[macro_use]
+extern crate pretty_assertions;
+
+use exonum::{
+    api,
+    blockchain::{Schema, TransactionErrorType, TransactionResult},
+    crypto::{gen_keypair, PublicKey, SecretKey},
+    messages::{RawTransaction, Signed},
+};
+use exonum_key_value::{
+    api::{EntryProof, EntryQuery, NamespaceInfo, NamespaceQuery},
+    schema::{Entry, KeyValueSchema},
+    transactions::{
+        Error, TxCreateNamespace, TxGrant, TxPut, TxRemove, TxRevoke, MAX_NAMESPACE_QUOTA,
+    },
+    KeyValueService, SERVICE_NAME,
+};
+use exonum_testkit::{ApiKind, TestKit, TestKitBuilder};
+
+const NAMESPACE: &str = "config";
+
+fn create_testkit() -> TestKit {
+    TestKitBuilder::validator()
+        .with_service(KeyValueService)
+        .create()
+}
+
+/// Creates a namespace owned by a new key. Returns the key of the owner.
+fn create_namespace(testkit: &mut TestKit, quota: u64) -> (PublicKey, SecretKey) {
+    let (owner, owner_secret) = gen_keypair();
+    let tx = TxCreateNamespace::sign(NAMESPACE, quota, &owner, &owner_secret);
+    testkit.create_block_with_transactions(txvec![tx.clone()]);
+    assert_success(testkit, &tx);
+    (owner, owner_secret)
+}
+
+fn assert_success(testkit: &TestKit, tx: &Signed<RawTransaction>) {
+    let result = Schema::new(testkit.snapshot())
+        .transaction_results()
+        .get(&tx.hash());
+    assert_matches!(result, Some(TransactionResult(Ok(()))));
+}
+
+fn assert_error(testkit: &TestKit, tx: &Signed<RawTransaction>, error: Error) {
+    let result = Schema::new(testkit.snapshot())
+        .transaction_results()
+        .get(&tx.hash());
+    match result {
+        Some(TransactionResult(Err(e))) => {
+            assert_eq!(e.error_type(), TransactionErrorType::Code(error as u8))
+        }
+        _ => panic!("Expected Err(), found None or Ok()"),
+    }
+}
+
+fn entry_value(testkit: &TestKit, key: &str) -> Option<Vec<u8>> {
+    KeyValueSchema::new(testkit.snapshot())
+        .entry(NAMESPACE, key)
+        .map(|entry| entry.value().to_vec())
+}
+
+#[test]
+fn test_access_control() {
+    let mut testkit = create_testkit();
+    let (owner, owner_secret) = create_namespace(&mut testkit, 1024);
+    let (writer, writer_secret) = gen_keypair();
+
+    let tx = TxPut::sign(NAMESPACE, "a", b"owner", 0, &owner, &owner_secret);
+    testkit.create_block_with_transactions(txvec![tx.clone()]);
+    assert_success(&testkit, &tx);
+
+    // Keys without write access can modify neither entries nor the ACL.
+    let tx_put = TxPut::sign(NAMESPACE, "a", b"writer", 0, &writer, &writer_secret);
+    let tx_grant = TxGrant::sign(NAMESPACE, &writer, 0, &writer, &writer_secret);
+    testkit.create_block_with_transactions(txvec![tx_put.clone(), tx_grant.clone()]);
+    assert_error(&testkit, &tx_put, Error::AccessDenied);
+    assert_error(&testkit, &tx_grant, Error::NotOwner);
+    assert_eq!(entry_value(&testkit, "a"), Some(b"owner".to_vec()));
+
+    let tx_grant = TxGrant::sign(NAMESPACE, &writer, 0, &owner, &owner_secret);
+    let tx_put = TxPut::sign(NAMESPACE, "a", b"writer", 1, &writer, &writer_secret);
+    testkit.create_block_with_transactions(txvec![tx_grant.clone(), tx_put.clone()]);
+    assert_success(&testkit, &tx_grant);
+    assert_success(&testkit, &tx_put);
+    let entry = KeyValueSchema::new(testkit.snapshot())
+        .entry(NAMESPACE, "a")
+        .unwrap();
+    assert_eq!(entry.value(), b"writer");
+    assert_eq!(entry.author(), &writer);
+
+    let info: NamespaceInfo = testkit
+        .api()
+        .public(ApiKind::Service(SERVICE_NAME))
+        .query(&NamespaceQuery {
+            name: NAMESPACE.to_owned(),
+        })
+        .get("v1/namespaces/info")
+        .unwrap();
+    assert_eq!(info.namespace.owner(), &owner);
+    assert_eq!(info.writers, vec![writer]);
+
+    let tx_revoke = TxRevoke::sign(NAMESPACE, &writer, 0, &owner, &owner_secret);
+    let tx_remove = TxRemove::sign(NAMESPACE, "a", 0, &writer, &writer_secret);
+    testkit.create_block_with_transactions(txvec![tx_revoke.clone(), tx_remove.clone()]);
+    assert_success(&testkit, &tx_revoke);
+    assert_error(&testkit, &tx_remove, Error::AccessDenied);
+
+    let tx_revoke = TxRevoke::sign(NAMESPACE, &writer, 1, &owner, &owner_secret);
+    let tx_remove = TxRemove::sign(NAMESPACE, "a", 0, &owner, &owner_secret);
+    testkit.create_block_with_transactions(txvec![tx_revoke.clone(), tx_remove.clone()]);
+    assert_error(&testkit, &tx_revoke, Error::WriterNotFound);
+    assert_success(&testkit, &tx_remove);
+    assert_eq!(entry_value(&testkit, "a"), None);
+}
+
+#[test]
+fn test_quota() {
+    let mut testkit = create_testkit();
+    let (owner, owner_secret) = create_namespace(&mut testkit, 10);
+
+    let tx_fits = TxPut::sign(NAMESPACE, "a", &[0; 5], 0, &owner, &owner_secret);
+    let tx_exceeds = TxPut::sign(NAMESPACE, "b", &[0; 4], 0, &owner, &owner_secret);
+    testkit.create_block_with_transactions(txvec![tx_fits.clone(), tx_exceeds.clone()]);
+    assert_success(&testkit, &tx_fits);
+    assert_error(&testkit, &tx_exceeds, Error::QuotaExceeded);
+
+    // Replaced entries are not accounted twice.
+    let tx_replace = TxPut::sign(NAMESPACE, "a", &[0; 9], 0, &owner, &owner_secret);
+    testkit.create_block_with_transactions(txvec![tx_replace.clone()]);
+    assert_success(&testkit, &tx_replace);
+    let namespace = KeyValueSchema::new(testkit.snapshot())
+        .namespace(NAMESPACE)
+        .unwrap();
+    assert_eq!(namespace.used(), 10);
+
+    let tx_remove = TxRemove::sign(NAMESPACE, "a", 0, &owner, &owner_secret);
+    let tx_put = TxPut::sign(NAMESPACE, "b", &[0; 4], 1, &owner, &owner_secret);
+    testkit.create_block_with_transactions(txvec![tx_remove.clone(), tx_put.clone()]);
+    assert_success(&testkit, &tx_remove);
+    assert_success(&testkit, &tx_put);
+    let namespace = KeyValueSchema::new(testkit.snapshot())
+        .namespace(NAMESPACE)
+        .unwrap();
+    assert_eq!(namespace.used(), 5);
+}
+
+#[test]
+fn test_create_namespace_errors() {
+    let mut testkit = create_testkit();
+    create_namespace(&mut testkit, 10);
+    let (author, author_secret) = gen_keypair();
+
+    let tx_exists = TxCreateNamespace::sign(NAMESPACE, 10, &author, &author_secret);
+    let tx_empty = TxCreateNamespace::sign("", 10, &author, &author_secret);
+    let tx_zero = TxCreateNamespace::sign("other", 0, &author, &author_secret);
+    let tx_large =
+        TxCreateNamespace::sign("other", MAX_NAMESPACE_QUOTA + 1, &author, &author_secret);
+    let tx_missing = TxPut::sign("missing", "a", b"value", 0, &author, &author_secret);
+    testkit.create_block_with_transactions(txvec![
+        tx_exists.clone(),
+        tx_empty.clone(),
+        tx_zero.clone(),
+        tx_large.clone(),
+        tx_missing.clone(),
+    ]);
+    assert_error(&testkit, &tx_exists, Error::NamespaceExists);
+    assert_error(&testkit, &tx_empty, Error::InvalidName);
+    assert_error(&testkit, &tx_zero, Error::InvalidQuota);
+    assert_error(&testkit, &tx_large, Error::InvalidQuota);
+    assert_error(&testkit, &tx_missing, Error::NamespaceNotFound);
+}
+
+#[test]
+fn test_entry_proof() {
+    let mut testkit = create_testkit();
+    let (owner, owner_secret) = create_namespace(&mut testkit, 1024);
+    testkit.create_block_with_transactions(txvec![
+        TxPut::sign(NAMESPACE, "a", b"first", 0, &owner, &owner_secret),
+        TxPut::sign(NAMESPACE, "b", b"second", 0, &owner, &owner_secret),
+    ]);
+
+    let api = testkit.api();
+    let query = EntryQuery {
+        namespace: NAMESPACE.to_owned(),
+        key: "b".to_owned(),
+    };
+    let entry: Entry = api
+        .public(ApiKind::Service(SERVICE_NAME))
+        .query(&query)
+        .get("v1/entries")
+        .unwrap();
+    assert_eq!(entry.value(), b"second");
+
+    let proof: EntryProof = api
+        .public(ApiKind::Service(SERVICE_NAME))
+        .query(&query)
+        .get("v1/entries/proof")
+        .unwrap();
+    let to_namespace = proof.to_namespace.check().unwrap();
+    let (_, namespace) = to_namespace.entries().next().unwrap();
+    let to_entry = proof.to_entry.check().unwrap();
+    assert_eq!(&to_entry.merkle_root(), namespace.entries_hash());
+    let (_, proved_entry) = to_entry.entries().next().unwrap();
+    assert_eq!(proved_entry, &entry);
+
+    let result: api::Result<Entry> = api
+        .public(ApiKind::Service(SERVICE_NAME))
+        .query(&EntryQuery {
+            namespace: NAMESPACE.to_owned(),
+            key: "c".to_owned(),
+        })
+        .get("v1/entries");
+    assert_matches!(result, Err(api::Error::NotFound(_)));
+}
@@ -0,0 +1,248 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Exonum-private-groups API.
+//!
+//! The public API exposes only the on-chain records of private transactions, i.e.,
+//! the payloads are redacted. The private API is used by the operators of the member
+//! nodes to store the payloads received off-chain, to view them and to generate
+//! the proofs of membership.
+
+use exonum::{
+    api::{self, ServiceApiBuilder, ServiceApiState},
+    blockchain::{self, BlockProof},
+    crypto::{Hash, PublicKey},
+    encoding::serialize::encode_hex,
+    storage::MapProof,
+};
+
+use membership::{MemberProof, PrivatePayload};
+use schema::{PrivateGroupsSchema, PrivateRecord};
+use store::PayloadStore;
+use SERVICE_ID;
+
+/// Describes the query parameters for the endpoints of a single private transaction.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct TransactionQuery {
+    /// Hash of the private transaction.
+    pub tx_hash: Hash,
+}
+
+/// Describes the query parameters for the `member_proof` endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct MemberQuery {
+    /// Hash of the private transaction.
+    pub tx_hash: Hash,
+    /// Public key of the member.
+    pub pub_key: PublicKey,
+}
+
+/// Private payload submitted to the node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayloadSubmission {
+    /// Hash of the private transaction.
+    pub tx_hash: Hash,
+    /// Payload committed by the transaction.
+    pub payload: PrivatePayload,
+}
+
+/// Private transaction as rendered by the API.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrivateTransactionView {
+    /// Hash of the transaction.
+    pub tx_hash: Hash,
+    /// On-chain record of the transaction.
+    pub record: PrivateRecord,
+    /// Members of the group, or `None` if redacted.
+    pub members: Option<Vec<PublicKey>>,
+    /// Payload data in hex representation, or `None` if redacted.
+    pub data: Option<String>,
+}
+
+/// Proof of the on-chain record of a private transaction.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordProof {
+    /// Proof of the last block.
+    pub block_proof: BlockProof,
+    /// Proof of the whole database table.
+    pub to_table: MapProof<Hash, Hash>,
+    /// Proof of the specific record in this table.
+    pub to_record: MapProof<Hash, PrivateRecord>,
+}
+
+/// Proof that a public key is a member of the group of a private transaction.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MembershipProof {
+    /// Proof of the on-chain record with the commitment to the members.
+    pub record_proof: RecordProof,
+    /// Proof of the member against the commitment.
+    pub member_proof: MemberProof,
+}
+
+/// Implements the exonum-private-groups public API.
+#[derive(Debug)]
+pub struct PublicApi;
+
+impl PublicApi {
+    /// Endpoint for getting the redacted private transaction.
+    pub fn transaction(
+        state: &ServiceApiState,
+        query: TransactionQuery,
+    ) -> api::Result<PrivateTransactionView> {
+        let record = record(state, &query.tx_hash)?;
+        Ok(PrivateTransactionView {
+            tx_hash: query.tx_hash,
+            record,
+            members: None,
+            data: None,
+        })
+    }
+
+    /// Endpoint for getting the proof of the on-chain record of a private transaction.
+    pub fn record_proof(
+        state: &ServiceApiState,
+        query: TransactionQuery,
+    ) -> api::Result<RecordProof> {
+        Ok(record_proof(state, query.tx_hash))
+    }
+
+    /// Used to extend Api.
+    pub fn wire(builder: &mut ServiceApiBuilder) {
+        builder
+            .public_scope()
+            .endpoint("v1/transactions", Self::transaction)
+            .endpoint("v1/records/proof", Self::record_proof);
+    }
+}
+
+/// Implements the exonum-private-groups private API.
+#[derive(Debug)]
+pub struct PrivateApi;
+
+impl PrivateApi {
+    /// Endpoint for storing the private payload of a committed transaction.
+    ///
+    /// The payload is accepted only if it matches the commitments of the transaction.
+    pub fn add_payload(
+        store: &PayloadStore,
+        state: &ServiceApiState,
+        submission: PayloadSubmission,
+    ) -> api::Result<()> {
+        let record = record(state, &submission.tx_hash)?;
+        let payload = submission.payload;
+        if payload.hash() != *record.payload_hash() {
+            Err(api::Error::BadRequest(
+                "Payload does not match the committed hash".to_owned(),
+            ))?
+        }
+        if payload.group().commitment() != *record.members_commitment()
+            || payload.group().members().len() as u64 != record.members_count()
+        {
+            Err(api::Error::BadRequest(
+                "Group does not match the committed members".to_owned(),
+            ))?
+        }
+        store
+            .put(&submission.tx_hash, payload)
+            .map_err(|e| api::Error::InternalError(e.into()))
+    }
+
+    /// Endpoint for getting the private transaction with the payload, if the node has it.
+    pub fn transaction(
+        store: &PayloadStore,
+        state: &ServiceApiState,
+        query: TransactionQuery,
+    ) -> api::Result<PrivateTransactionView> {
+        let record = record(state, &query.tx_hash)?;
+        let payload = payload(store, &query.tx_hash)?;
+        Ok(PrivateTransactionView {
+            tx_hash: query.tx_hash,
+            record,
+            members: payload.as_ref().map(|p| p.group().members()),
+            data: payload.as_ref().map(|p| encode_hex(p.data())),
+        })
+    }
+
+    /// Endpoint for getting the proof of membership in the group of a private transaction.
+    pub fn member_proof(
+        store: &PayloadStore,
+        state: &ServiceApiState,
+        query: MemberQuery,
+    ) -> api::Result<MembershipProof> {
+        let payload = payload(store, &query.tx_hash)?.ok_or_else(|| {
+            api::Error::NotFound("Payload of the transaction is unknown".to_owned())
+        })?;
+        let member_proof = payload
+            .group()
+            .member_proof(&query.pub_key)
+            .ok_or_else(|| api::Error::NotFound("Key is not a member of the group".to_owned()))?;
+        Ok(MembershipProof {
+            record_proof: record_proof(state, query.tx_hash),
+            member_proof,
+        })
+    }
+
+    /// Used to extend Api.
+    pub fn wire(builder: &mut ServiceApiBuilder, store: &PayloadStore) {
+        let (add_store, tx_store, proof_store) = (store.clone(), store.clone(), store.clone());
+        builder
+            .private_scope()
+            .endpoint_mut(
+                "v1/payloads",
+                move |state: &ServiceApiState, submission: PayloadSubmission| {
+                    Self::add_payload(&add_store, state, submission)
+                },
+            ).endpoint(
+                "v1/transactions",
+                move |state: &ServiceApiState, query: TransactionQuery| {
+                    Self::transaction(&tx_store, state, query)
+                },
+            ).endpoint(
+                "v1/members/proof",
+                move |state: &ServiceApiState, query: MemberQuery| {
+                    Self::member_proof(&proof_store, state, query)
+                },
+            );
+    }
+}
+
+fn record(state: &ServiceApiState, tx_hash: &Hash) -> api::Result<PrivateRecord> {
+    PrivateGroupsSchema::new(state.snapshot())
+        .records()
+        .get(tx_hash)
+        .ok_or_else(|| api::Error::NotFound("Private transaction not found".to_owned()))
+}
+
+fn payload(store: &PayloadStore, tx_hash: &Hash) -> api::Result<Option<PrivatePayload>> {
+    store
+        .get(tx_hash)
+        .map_err(|e| api::Error::InternalError(e.into()))
+}
+
+fn record_proof(state: &ServiceApiState, tx_hash: Hash) -> RecordProof {
+    let snapshot = state.snapshot();
+    let general_schema = blockchain::Schema::new(&snapshot);
+    let block_proof = general_schema
+        .block_and_precommits(general_schema.height())
+        .unwrap();
+    let to_table = general_schema.get_proof_to_service_table(SERVICE_ID, 0);
+    let to_record = PrivateGroupsSchema::new(&snapshot)
+        .records()
+        .get_proof(tx_hash);
+    RecordProof {
+        block_proof,
+        to_table,
+        to_record,
+    }
+}
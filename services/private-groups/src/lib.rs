@@ -0,0 +1,144 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The service of private subgroup transactions for Exonum.
+//!
+//! A private transaction shares a payload among a listed set of members, while only
+//! the hash of the payload and a commitment to the members go on chain. The payload and
+//! the list of members are distributed among the members off-chain; each member submits
+//! them to its node via the private API, where they are checked against the on-chain
+//! commitments and kept in the local [`PayloadStore`].
+//!
+//! The public API and the blockchain explorer render private transactions without
+//! the payloads. The private API of a node that holds the payload renders the full
+//! transaction and provides the proofs of membership, which allow a member to prove
+//! that it belongs to the group without disclosing the other members. See
+//! the [`membership`] module for details on the commitment scheme.
+//!
+//! [`PayloadStore`]: store/struct.PayloadStore.html
+//! [`membership`]: membership/index.html
+
+#![deny(
+    missing_debug_implementations,
+    missing_docs,
+    unsafe_code,
+    bare_trait_objects
+)]
+
+#[macro_use]
+extern crate exonum;
+#[macro_use]
+extern crate failure;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+
+/// Node API.
+pub mod api;
+pub mod membership;
+/// Database schema.
+pub mod schema;
+/// Local store of the private payloads.
+pub mod store;
+/// Node transactions.
+pub mod transactions;
+
+use exonum::{
+    api::ServiceApiBuilder,
+    blockchain::{Service, Transaction, TransactionInfo, TransactionSet},
+    crypto::Hash,
+    encoding,
+    helpers::fabric::{Context, ServiceFactory},
+    messages::RawTransaction,
+    storage::Snapshot,
+};
+
+use std::path::PathBuf;
+
+use schema::PrivateGroupsSchema;
+use store::PayloadStore;
+use transactions::PrivateGroupsTransactions;
+
+/// Private groups service id.
+pub const SERVICE_ID: u16 = 7;
+/// Private groups service name.
+pub const SERVICE_NAME: &str = "exonum_private_groups";
+
+/// Define the service.
+#[derive(Debug)]
+pub struct PrivateGroupsService {
+    /// Store of the payloads known to the node.
+    store: PayloadStore,
+}
+
+impl PrivateGroupsService {
+    /// Create a new `PrivateGroupsService` keeping the payloads in `store`.
+    pub fn new(store: PayloadStore) -> PrivateGroupsService {
+        PrivateGroupsService { store }
+    }
+}
+
+impl Service for PrivateGroupsService {
+    fn service_name(&self) -> &str {
+        SERVICE_NAME
+    }
+
+    fn state_hash(&self, snapshot: &dyn Snapshot) -> Vec<Hash> {
+        let schema = PrivateGroupsSchema::new(snapshot);
+        schema.state_hash()
+    }
+
+    fn service_id(&self) -> u16 {
+        SERVICE_ID
+    }
+
+    fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<dyn Transaction>, encoding::Error> {
+        PrivateGroupsTransactions::tx_from_raw(raw).map(Into::into)
+    }
+
+    fn transaction_types(&self) -> Vec<TransactionInfo> {
+        TransactionInfo::from_set::<PrivateGroupsTransactions>()
+    }
+
+    fn wire_api(&self, builder: &mut ServiceApiBuilder) {
+        api::PublicApi::wire(builder);
+        api::PrivateApi::wire(builder, &self.store);
+    }
+}
+
+/// A private groups service creator for the `NodeBuilder`.
+#[derive(Debug)]
+pub struct PrivateGroupsServiceFactory {
+    payloads_dir: PathBuf,
+}
+
+impl PrivateGroupsServiceFactory {
+    /// Create a new factory keeping the payloads in the `payloads_dir` directory.
+    pub fn new<P: Into<PathBuf>>(payloads_dir: P) -> Self {
+        PrivateGroupsServiceFactory {
+            payloads_dir: payloads_dir.into(),
+        }
+    }
+}
+
+impl ServiceFactory for PrivateGroupsServiceFactory {
+    fn service_name(&self) -> &str {
+        SERVICE_NAME
+    }
+
+    fn make_service(&mut self, _: &Context) -> Box<dyn Service> {
+        let store = PayloadStore::open(&self.payloads_dir).expect("Cannot open payload store");
+        Box::new(PrivateGroupsService::new(store))
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Private groups and the proofs of membership in them.
+//!
+//! The members of a group are committed on chain by the root hash of a Merkle tree.
+//! Every leaf of the tree is computed from the public key of a member and a blinding
+//! factor derived from the secret salt of the group. Thus, the commitment reveals
+//! nothing about the members to the parties that do not know the salt, and a member can
+//! prove its membership without disclosing the salt or the other members.
+
+use exonum::{
+    crypto::{Hash, HashStream, PublicKey},
+    storage::{Database, ListProof, MemoryDB, ProofListIndex},
+};
+
+/// Maximum number of members in a group.
+pub const MAX_MEMBERS: u64 = 256;
+
+encoding_struct! {
+    /// Group of the parties that share a private payload.
+    ///
+    /// The group is never stored on chain; it is distributed among the members together
+    /// with the payload.
+    struct PrivateGroup {
+        /// Secret salt of the group.
+        salt: &Hash,
+        /// Public keys of the members.
+        members: Vec<PublicKey>,
+    }
+}
+
+encoding_struct! {
+    /// Private payload shared among the members of a group.
+    ///
+    /// Only the hash of the payload is committed on chain.
+    struct PrivatePayload {
+        /// Group the payload is shared with.
+        group: PrivateGroup,
+        /// Payload data.
+        data: &[u8],
+    }
+}
+
+/// Proof that a public key belongs to a group with the given commitment.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemberProof {
+    /// Public key of the member.
+    pub member: PublicKey,
+    /// Blinding factor of the member.
+    pub blinding: Hash,
+    /// Position of the member in the group.
+    pub index: u64,
+    /// Number of the members in the group.
+    pub members_count: u64,
+    /// Proof of the member leaf in the Merkle tree of the group.
+    pub proof: ListProof<Hash>,
+}
+
+impl PrivateGroup {
+    /// Returns the blinding factor of the member leaf.
+    pub fn blinding(&self, member: &PublicKey) -> Hash {
+        HashStream::new()
+            .update(self.salt().as_ref())
+            .update(member.as_ref())
+            .hash()
+    }
+
+    /// Returns the commitment to the members of the group.
+    pub fn commitment(&self) -> Hash {
+        let db = MemoryDB::new();
+        let mut fork = db.fork();
+        let mut leaves = ProofListIndex::new("leaves", &mut fork);
+        leaves.extend(self.leaves());
+        leaves.merkle_root()
+    }
+
+    /// Returns the proof of membership for `member`, or `None` if `member` is not
+    /// in the group.
+    pub fn member_proof(&self, member: &PublicKey) -> Option<MemberProof> {
+        let index = self.members().iter().position(|key| key == member)? as u64;
+
+        let db = MemoryDB::new();
+        let mut fork = db.fork();
+        let mut leaves = ProofListIndex::new("leaves", &mut fork);
+        leaves.extend(self.leaves());
+        Some(MemberProof {
+            member: *member,
+            blinding: self.blinding(member),
+            index,
+            members_count: leaves.len(),
+            proof: leaves.get_proof(index),
+        })
+    }
+
+    fn leaves(&self) -> Vec<Hash> {
+        self.members()
+            .iter()
+            .map(|member| member_leaf(&self.blinding(member), member))
+            .collect()
+    }
+}
+
+impl MemberProof {
+    /// Checks the proof against the commitment to the members of a group.
+    pub fn verify(&self, commitment: &Hash) -> bool {
+        let leaf = member_leaf(&self.blinding, &self.member);
+        match self.proof.validate(*commitment, self.members_count) {
+            Ok(entries) => entries == [(self.index, &leaf)],
+            Err(_) => false,
+        }
+    }
+}
+
+fn member_leaf(blinding: &Hash, member: &PublicKey) -> Hash {
+    HashStream::new()
+        .update(blinding.as_ref())
+        .update(member.as_ref())
+        .hash()
+}
@@ -0,0 +1,65 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use exonum::{
+    crypto::{Hash, PublicKey},
+    storage::{Fork, ProofMapIndex, Snapshot},
+};
+
+encoding_struct! {
+    /// On-chain record of a private transaction.
+    struct PrivateRecord {
+        /// Author of the transaction.
+        author: &PublicKey,
+        /// Hash of the private payload.
+        payload_hash: &Hash,
+        /// Commitment to the members of the group the payload is shared with.
+        members_commitment: &Hash,
+        /// Number of the members in the group.
+        members_count: u64,
+    }
+}
+
+/// `Exonum-private-groups` service database schema.
+#[derive(Debug)]
+pub struct PrivateGroupsSchema<T> {
+    view: T,
+}
+
+impl<T: AsRef<dyn Snapshot>> PrivateGroupsSchema<T> {
+    /// Constructs schema for the given `snapshot`.
+    pub fn new(view: T) -> Self {
+        PrivateGroupsSchema { view }
+    }
+
+    /// Returns the table that stores the records of private transactions keyed by
+    /// the transaction hash.
+    pub fn records(&self) -> ProofMapIndex<&dyn Snapshot, Hash, PrivateRecord> {
+        ProofMapIndex::new("exonum_private_groups.records", self.view.as_ref())
+    }
+
+    /// Returns hashes for stored tables.
+    pub fn state_hash(&self) -> Vec<Hash> {
+        vec![self.records().merkle_root()]
+    }
+}
+
+impl<'a> PrivateGroupsSchema<&'a mut Fork> {
+    /// Mutable reference to the ['records'][1] index.
+    ///
+    /// [1]: struct.PrivateGroupsSchema.html#method.records
+    pub fn records_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, PrivateRecord> {
+        ProofMapIndex::new("exonum_private_groups.records", self.view)
+    }
+}
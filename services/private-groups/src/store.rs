@@ -0,0 +1,72 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use exonum::{crypto::Hash, encoding::serialize::encode_hex, storage::StorageValue};
+
+use std::{
+    borrow::Cow,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use membership::PrivatePayload;
+
+/// Local store of the private payloads known to the node.
+///
+/// The payloads are kept in the files named after the hashes of the corresponding
+/// transactions. The store is not a part of the blockchain state; every node keeps
+/// only the payloads submitted to it via the private API.
+#[derive(Debug, Clone)]
+pub struct PayloadStore {
+    root: PathBuf,
+}
+
+impl PayloadStore {
+    /// Opens the store in the `root` directory, creating the directory if necessary.
+    pub fn open<P: AsRef<Path>>(root: P) -> io::Result<Self> {
+        fs::create_dir_all(root.as_ref())?;
+        Ok(PayloadStore {
+            root: root.as_ref().to_owned(),
+        })
+    }
+
+    /// Returns the root directory of the store.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the payload of the transaction with the given hash, if the node has it.
+    pub fn get(&self, tx_hash: &Hash) -> io::Result<Option<PrivatePayload>> {
+        let mut file = match fs::File::open(self.path(tx_hash)) {
+            Ok(file) => file,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        Ok(Some(PrivatePayload::from_bytes(Cow::Owned(bytes))))
+    }
+
+    /// Saves the payload of the transaction with the given hash.
+    pub fn put(&self, tx_hash: &Hash, payload: PrivatePayload) -> io::Result<()> {
+        let mut file = fs::File::create(self.path(tx_hash))?;
+        file.write_all(&payload.into_bytes())?;
+        file.sync_all()
+    }
+
+    fn path(&self, tx_hash: &Hash) -> PathBuf {
+        self.root.join(encode_hex(tx_hash))
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Workaround for `failure` see https://github.com/rust-lang-nursery/failure/issues/223 and
+// ECR-1771 for the details.
+#![allow(bare_trait_objects)]
+
+use exonum::{
+    blockchain::{ExecutionError, ExecutionResult, Transaction, TransactionContext},
+    crypto::{Hash, PublicKey, SecretKey},
+    messages::{Message, RawTransaction, Signed},
+};
+
+use super::SERVICE_ID;
+use membership::MAX_MEMBERS;
+use schema::{PrivateGroupsSchema, PrivateRecord};
+
+/// Common errors emitted by transactions during execution.
+#[derive(Debug, Fail)]
+#[repr(u8)]
+pub enum Error {
+    /// The group is empty or has too many members.
+    #[fail(display = "Invalid number of group members")]
+    InvalidMembersCount = 0,
+}
+
+impl From<Error> for ExecutionError {
+    fn from(value: Error) -> ExecutionError {
+        let description = value.to_string();
+        ExecutionError::with_description(value as u8, description)
+    }
+}
+
+transactions! {
+    /// Define PrivateGroupsService transaction.
+    pub PrivateGroupsTransactions {
+
+        /// Transaction committing a private payload shared among the members of a group.
+        ///
+        /// The payload and the group are distributed among the members off-chain;
+        /// the transaction contains only their commitments.
+        struct TxCommitPrivate {
+            /// Hash of the private payload.
+            payload_hash: &Hash,
+            /// Commitment to the members of the group.
+            members_commitment: &Hash,
+            /// Number of the members in the group.
+            members_count: u64,
+        }
+    }
+}
+
+impl TxCommitPrivate {
+    #[doc(hidden)]
+    pub fn sign(
+        payload_hash: &Hash,
+        members_commitment: &Hash,
+        members_count: u64,
+        public_key: &PublicKey,
+        secret_key: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(
+            TxCommitPrivate::new(payload_hash, members_commitment, members_count),
+            SERVICE_ID,
+            *public_key,
+            secret_key,
+        )
+    }
+}
+
+impl Transaction for TxCommitPrivate {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        if self.members_count() == 0 || self.members_count() > MAX_MEMBERS {
+            Err(Error::InvalidMembersCount)?
+        }
+
+        let author = context.author();
+        let tx_hash = context.tx_hash();
+        let record = PrivateRecord::new(
+            &author,
+            self.payload_hash(),
+            self.members_commitment(),
+            self.members_count(),
+        );
+        PrivateGroupsSchema::new(context.fork())
+            .records_mut()
+            .put(&tx_hash, record);
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[macro_use]
+extern crate assert_matches;
+extern crate exonum;
+extern crate exonum_private_groups;
+#[macro_use]
+extern crate exonum_testkit;
+#[macro_use]
+extern crate pretty_assertions;
+extern crate tempdir;
+
+use exonum::{
+    api,
+    blockchain::{Schema, TransactionErrorType, TransactionResult},
+    crypto::{gen_keypair, hash, CryptoHash, PublicKey, SecretKey},
+    messages::{RawTransaction, Signed},
+};
+use exonum_private_groups::{
+    api::{
+        MemberQuery, MembershipProof, PayloadSubmission, PrivateTransactionView, TransactionQuery,
+    },
+    membership::{PrivateGroup, PrivatePayload},
+    schema::PrivateGroupsSchema,
+    store::PayloadStore,
+    transactions::{Error, TxCommitPrivate},
+    PrivateGroupsService, SERVICE_NAME,
+};
+use exonum_testkit::{ApiKind, TestKit, TestKitBuilder};
+use tempdir::TempDir;
+
+fn create_testkit(dir: &TempDir) -> TestKit {
+    let store = PayloadStore::open(dir.path()).unwrap();
+    TestKitBuilder::validator()
+        .with_service(PrivateGroupsService::new(store))
+        .create()
+}
+
+fn private_payload(members: &[PublicKey], data: &[u8]) -> PrivatePayload {
+    let group = PrivateGroup::new(&hash(b"salt"), members.to_vec());
+    PrivatePayload::new(group, data)
+}
+
+fn commit_tx(
+    payload: &PrivatePayload,
+    public_key: &PublicKey,
+    secret_key: &SecretKey,
+) -> Signed<RawTransaction> {
+    let group = payload.group();
+    TxCommitPrivate::sign(
+        &payload.hash(),
+        &group.commitment(),
+        group.members().len() as u64,
+        public_key,
+        secret_key,
+    )
+}
+
+#[test]
+fn test_private_transaction() {
+    let dir = TempDir::new("exonum_private_groups").unwrap();
+    let mut testkit = create_testkit(&dir);
+    let api = testkit.api();
+
+    let (author, author_secret) = gen_keypair();
+    let (member, _) = gen_keypair();
+    let (outsider, _) = gen_keypair();
+    let payload = private_payload(&[author, member], b"confidential");
+    let tx = commit_tx(&payload, &author, &author_secret);
+    testkit.create_block_with_transactions(txvec![tx.clone()]);
+
+    let record = PrivateGroupsSchema::new(testkit.snapshot())
+        .records()
+        .get(&tx.hash())
+        .unwrap();
+    assert_eq!(record.author(), &author);
+    assert_eq!(record.payload_hash(), &payload.hash());
+    assert_eq!(record.members_count(), 2);
+
+    // The public API renders the transaction without the payload.
+    let query = TransactionQuery { tx_hash: tx.hash() };
+    let view: PrivateTransactionView = api
+        .public(ApiKind::Service(SERVICE_NAME))
+        .query(&query)
+        .get("v1/transactions")
+        .unwrap();
+    assert_eq!(view.record, record);
+    assert_eq!(view.members, None);
+    assert_eq!(view.data, None);
+
+    // The node does not know the payload until it is submitted.
+    let view: PrivateTransactionView = api
+        .private(ApiKind::Service(SERVICE_NAME))
+        .query(&query)
+        .get("v1/transactions")
+        .unwrap();
+    assert_eq!(view.data, None);
+
+    let submission = PayloadSubmission {
+        tx_hash: tx.hash(),
+        payload: payload.clone(),
+    };
+    api.private(ApiKind::Service(SERVICE_NAME))
+        .query(&submission)
+        .post::<()>("v1/payloads")
+        .unwrap();
+    let view: PrivateTransactionView = api
+        .private(ApiKind::Service(SERVICE_NAME))
+        .query(&query)
+        .get("v1/transactions")
+        .unwrap();
+    assert_eq!(view.members, Some(vec![author, member]));
+    assert_eq!(view.data, Some("636f6e666964656e7469616c".to_owned()));
+
+    // Members can prove their membership, but the proof does not fit other keys.
+    let proof: MembershipProof = api
+        .private(ApiKind::Service(SERVICE_NAME))
+        .query(&MemberQuery {
+            tx_hash: tx.hash(),
+            pub_key: member,
+        })
+        .get("v1/members/proof")
+        .unwrap();
+    let to_record = proof.record_proof.to_record.check().unwrap();
+    let (_, proved_record) = to_record.entries().next().unwrap();
+    assert_eq!(proved_record, &record);
+    assert!(proof.member_proof.verify(record.members_commitment()));
+    let mut forged = proof.member_proof;
+    forged.member = outsider;
+    assert!(!forged.verify(record.members_commitment()));
+
+    let result: api::Result<MembershipProof> = api
+        .private(ApiKind::Service(SERVICE_NAME))
+        .query(&MemberQuery {
+            tx_hash: tx.hash(),
+            pub_key: outsider,
+        })
+        .get("v1/members/proof");
+    assert_matches!(result, Err(api::Error::NotFound(_)));
+}
+
+#[test]
+fn test_mismatched_payload() {
+    let dir = TempDir::new("exonum_private_groups").unwrap();
+    let mut testkit = create_testkit(&dir);
+    let api = testkit.api();
+
+    let (author, author_secret) = gen_keypair();
+    let (member, _) = gen_keypair();
+    let payload = private_payload(&[author, member], b"confidential");
+    let tx = commit_tx(&payload, &author, &author_secret);
+    testkit.create_block_with_transactions(txvec![tx.clone()]);
+
+    let forged_data = private_payload(&[author, member], b"forged");
+    let forged_group = private_payload(&[author], b"confidential");
+    for forged in vec![forged_data, forged_group] {
+        let submission = PayloadSubmission {
+            tx_hash: tx.hash(),
+            payload: forged,
+        };
+        let result: api::Result<()> = api
+            .private(ApiKind::Service(SERVICE_NAME))
+            .query(&submission)
+            .post("v1/payloads");
+        assert_matches!(result, Err(api::Error::BadRequest(_)));
+    }
+
+    let store = PayloadStore::open(dir.path()).unwrap();
+    assert!(store.get(&tx.hash()).unwrap().is_none());
+}
+
+#[test]
+fn test_invalid_members_count() {
+    let dir = TempDir::new("exonum_private_groups").unwrap();
+    let mut testkit = create_testkit(&dir);
+
+    let (author, author_secret) = gen_keypair();
+    let payload = private_payload(&[], b"confidential");
+    let tx = commit_tx(&payload, &author, &author_secret);
+    testkit.create_block_with_transactions(txvec![tx.clone()]);
+
+    let result = Schema::new(testkit.snapshot())
+        .transaction_results()
+        .get(&tx.hash());
+    match result {
+        Some(TransactionResult(Err(e))) => assert_eq!(
+            e.error_type(),
+            TransactionErrorType::Code(Error::InvalidMembersCount as u8)
+        ),
+        _ => panic!("Expected Err(), found None or Ok()"),
+    }
+    assert!(PrivateGroupsSchema::new(testkit.snapshot())
+        .records()
+        .get(&tx.hash())
+        .is_none());
+}
+
+#[test]
+fn test_payload_store() {
+    let dir = TempDir::new("exonum_private_groups").unwrap();
+    let store = PayloadStore::open(dir.path()).unwrap();
+    let (member, _) = gen_keypair();
+    let payload = private_payload(&[member], b"data");
+    let tx_hash = hash(b"tx");
+
+    assert!(store.get(&tx_hash).unwrap().is_none());
+    store.put(&tx_hash, payload.clone()).unwrap();
+    assert_eq!(store.get(&tx_hash).unwrap(), Some(payload));
+}
@@ -27,6 +27,32 @@ pub struct ValidatorTime {
     pub public_key: PublicKey,
     /// Time of the validator.
     pub time: Option<DateTime<Utc>>,
+    /// Difference in milliseconds between the time of the validator and the consolidated
+    /// time, or `None` if either of them is unknown. Validators submit their times after
+    /// each block commit, so a large difference indicates that the validator clock is skewed.
+    #[serde(default)]
+    pub skew: Option<i64>,
+}
+
+impl ValidatorTime {
+    fn new(
+        public_key: PublicKey,
+        time: Option<DateTime<Utc>>,
+        consolidated_time: Option<DateTime<Utc>>,
+    ) -> Self {
+        let skew = match (time, consolidated_time) {
+            (Some(time), Some(consolidated_time)) => Some(
+                time.signed_duration_since(consolidated_time)
+                    .num_milliseconds(),
+            ),
+            _ => None,
+        };
+        ValidatorTime {
+            public_key,
+            time,
+            skew,
+        }
+    }
 }
 
 /// Implements the exonum-time public API.
@@ -65,14 +91,13 @@ impl PrivateApi {
         let view = state.snapshot();
         let schema = TimeSchema::new(&view);
         let idx = schema.validators_times();
+        let consolidated_time = schema.time().get();
 
         // The times of all validators for which time is known.
         let validators_times = idx
             .iter()
-            .map(|(public_key, time)| ValidatorTime {
-                public_key,
-                time: Some(time),
-            }).collect::<Vec<_>>();
+            .map(|(public_key, time)| ValidatorTime::new(public_key, Some(time), consolidated_time))
+            .collect::<Vec<_>>();
         Ok(validators_times)
     }
 
@@ -85,14 +110,15 @@ impl PrivateApi {
         let validator_keys = Schema::new(&view).actual_configuration().validator_keys;
         let schema = TimeSchema::new(&view);
         let idx = schema.validators_times();
+        let consolidated_time = schema.time().get();
 
         // The times of current validators.
         // `None` if the time of the validator is unknown.
         let validators_times = validator_keys
             .iter()
-            .map(|validator| ValidatorTime {
-                public_key: validator.service_key,
-                time: idx.get(&validator.service_key),
+            .map(|validator| {
+                let time = idx.get(&validator.service_key);
+                ValidatorTime::new(validator.service_key, time, consolidated_time)
             }).collect::<Vec<_>>();
         Ok(validators_times)
     }
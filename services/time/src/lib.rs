@@ -47,7 +47,7 @@ pub mod transactions;
 
 use exonum::{
     api::ServiceApiBuilder,
-    blockchain::{Service, ServiceContext, Transaction, TransactionSet},
+    blockchain::{Service, ServiceContext, Transaction, TransactionInfo, TransactionSet},
     crypto::Hash,
     encoding::{self, serialize::json::reexport::Value},
     helpers::fabric::{Context, ServiceFactory},
@@ -111,6 +111,10 @@ impl Service for TimeService {
         TimeTransactions::tx_from_raw(raw).map(Into::into)
     }
 
+    fn transaction_types(&self) -> Vec<TransactionInfo> {
+        TransactionInfo::from_set::<TimeTransactions>()
+    }
+
     fn initialize(&self, _fork: &mut Fork) -> Value {
         Value::Null
     }
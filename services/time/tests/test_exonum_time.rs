@@ -511,6 +511,39 @@ fn assert_all_validators_times_eq(
     assert_eq!(*expected_validators_times, validators_times);
 }
 
+#[test]
+fn test_validators_time_skew() {
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(3)
+        .with_service(TimeService::new())
+        .create();
+    let mut api = testkit.api();
+    let validators = testkit.network().validators().to_vec();
+
+    let time = Utc::now();
+    let txs = validators
+        .iter()
+        .enumerate()
+        .map(|(i, validator)| {
+            let (pub_key, sec_key) = validator.service_keypair();
+            TxTime::sign(time + Duration::seconds(10 * i as i64), pub_key, sec_key)
+        }).collect::<Vec<_>>();
+    for tx in txs {
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+    assert_current_time_eq(&mut api, Some(time + Duration::seconds(20)));
+
+    let skews = HashMap::<_, _>::from_iter(
+        get_current_validators_times(&mut api)
+            .into_iter()
+            .map(|validator| (validator.public_key, validator.skew)),
+    );
+    for (i, validator) in validators.iter().enumerate() {
+        let expected_skew = Some(10_000 * i as i64 - 20_000);
+        assert_eq!(skews[validator.service_keypair().0], expected_skew);
+    }
+}
+
 #[test]
 fn test_endpoint_api() {
     let mut testkit = TestKitBuilder::validator()
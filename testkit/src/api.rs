@@ -239,6 +239,30 @@ where
         Self::response_to_api_result(response)
     }
 
+    /// Sends a delete request to the testing API endpoint and decodes response as
+    /// the corresponding type.
+    pub fn delete<R>(&self, endpoint: &str) -> api::Result<R>
+    where
+        R: DeserializeOwned + 'static,
+    {
+        let url = format!(
+            "{url}{access}/{prefix}/{endpoint}",
+            url = self.test_server_url,
+            access = self.access,
+            prefix = self.prefix,
+            endpoint = endpoint
+        );
+
+        trace!("DELETE {}", url);
+
+        let response = self
+            .test_client
+            .delete(&url)
+            .send()
+            .expect("Unable to send request");
+        Self::response_to_api_result(response)
+    }
+
     /// Converts reqwest Response to api::Result.
     fn response_to_api_result<R>(mut response: Response) -> api::Result<R>
     where
@@ -268,9 +292,13 @@ where
                 trace!("Body: {}", body);
                 serde_json::from_str(&body).expect("Unable to deserialize body")
             }),
-            StatusCode::FORBIDDEN => Err(api::Error::Unauthorized),
+            StatusCode::UNAUTHORIZED => Err(api::Error::Unauthorized),
+            StatusCode::FORBIDDEN => Err(api::Error::Forbidden),
             StatusCode::BAD_REQUEST => Err(api::Error::BadRequest(error(response))),
             StatusCode::NOT_FOUND => Err(api::Error::NotFound(error(response))),
+            StatusCode::GONE => Err(api::Error::Gone(error(response))),
+            StatusCode::TOO_MANY_REQUESTS => Err(api::Error::TooManyRequests),
+            StatusCode::SERVICE_UNAVAILABLE => Err(api::Error::ServiceUnavailable(error(response))),
             s if s.is_server_error() => Err(api::Error::InternalError(format_err!(
                 "{}",
                 error(response)
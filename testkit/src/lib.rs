@@ -168,7 +168,7 @@ use futures::{sync::mpsc, Future, Stream};
 use tokio_core::reactor::Core;
 
 use std::sync::{Arc, RwLock};
-use std::{fmt, net::SocketAddr};
+use std::{collections::HashSet, fmt, net::SocketAddr};
 
 use exonum::{
     api::{
@@ -437,10 +437,15 @@ impl TestKit {
                                 schema.add_transaction_into_pool(tx.clone());
                             }
                         }
+                        ExternalMessage::EvictTransaction(hash) => {
+                            schema.reject_transaction(&hash).ok();
+                        }
                         ExternalMessage::PeerAdd(_)
                         | ExternalMessage::Enable(_)
                         | ExternalMessage::Rebroadcast
                         | ExternalMessage::Shutdown => { /* Ignored */ }
+                        #[cfg(debug_assertions)]
+                        ExternalMessage::Chaos(_) => { /* Ignored */ }
                     }
                 }
                 blockchain.merge(fork.into_patch()).unwrap();
@@ -755,6 +760,7 @@ impl TestKit {
     /// # Panics
     ///
     /// - Panics in the case any of transaction hashes are not in the pool.
+    /// - Panics if the same transaction hash is specified more than once.
     pub fn create_block_with_tx_hashes(
         &mut self,
         tx_hashes: &[crypto::Hash],
@@ -764,15 +770,22 @@ impl TestKit {
         {
             let snapshot = self.blockchain.snapshot();
             let schema = CoreSchema::new(&snapshot);
+            let mut unique_hashes = HashSet::new();
             for hash in tx_hashes {
                 assert!(schema.transactions_pool().contains(hash));
+                assert!(
+                    unique_hashes.insert(hash),
+                    "Duplicate transaction in the block: {:?}",
+                    hash
+                );
             }
         }
 
         self.do_create_block(tx_hashes)
     }
 
-    /// Creates block with all transactions in the pool.
+    /// Creates block with all transactions in the pool, except for the transactions
+    /// with uncommitted dependencies.
     ///
     /// # Return value
     ///
@@ -783,7 +796,18 @@ impl TestKit {
         let snapshot = self.blockchain.snapshot();
         let schema = CoreSchema::new(&snapshot);
         let txs = schema.transactions_pool();
-        let tx_hashes: Vec<_> = txs.iter().collect();
+        let tx_hashes: Vec<_> = txs
+            .iter()
+            .filter(|tx_hash| {
+                schema
+                    .transactions()
+                    .get(tx_hash)
+                    .and_then(|raw| self.blockchain.tx_from_raw(raw.payload().clone()).ok())
+                    .and_then(|tx| tx.depends_on())
+                    .map_or(true, |dependency| {
+                        schema.transactions_locations().contains(&dependency)
+                    })
+            }).collect();
         {
             let blockchain = self.blockchain_mut();
             let fork = blockchain.fork();
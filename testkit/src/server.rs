@@ -86,11 +86,8 @@ impl TestkitServerApi {
         }
 
         let mut testkit = self.write();
-        if testkit.height() >= height {
-            let rollback_blocks = (testkit.height().0 - height.0 + 1) as usize;
-            for _ in 0..rollback_blocks {
-                testkit.rollback();
-            }
+        for _ in height.iter_to(testkit.height().next()) {
+            testkit.rollback();
         }
 
         let explorer = BlockchainExplorer::new(&testkit.blockchain);
@@ -22,11 +22,12 @@ extern crate serde_derive;
 extern crate serde_json;
 
 use exonum::{
+    api::{node::public::explorer::ValidatorsInfo, Error as ApiError},
     blockchain::Schema,
     crypto::CryptoHash,
     helpers::{Height, ValidatorId},
 };
-use exonum_testkit::TestKitBuilder;
+use exonum_testkit::{ApiKind, TestKitBuilder};
 
 #[test]
 fn test_following_config() {
@@ -146,6 +147,57 @@ fn test_exclude_from_validators() {
     );
 }
 
+#[test]
+fn test_validators_api() {
+    let mut testkit = TestKitBuilder::validator().with_validators(2).create();
+    let old_config = testkit.actual_configuration();
+
+    let cfg_change_height = Height(5);
+    let proposal = {
+        let mut cfg = testkit.configuration_change_proposal();
+        let validator = cfg.validators()[1].clone();
+        cfg.set_actual_from(cfg_change_height);
+        cfg.set_validators(vec![validator]);
+        cfg
+    };
+    let new_config = proposal.stored_configuration().clone();
+    testkit.commit_configuration_change(proposal);
+    testkit.create_blocks_until(Height(7));
+
+    let api = testkit.api();
+    let info: ValidatorsInfo = api
+        .public(ApiKind::Explorer)
+        .get("v1/validators?height=4")
+        .unwrap();
+    assert_eq!(info.height, Height(4));
+    assert_eq!(info.actual_from, Height(0));
+    assert_eq!(info.config_hash, old_config.hash());
+    assert_eq!(info.validator_keys, old_config.validator_keys);
+    assert_eq!(info.consensus, old_config.consensus);
+
+    let info: ValidatorsInfo = api
+        .public(ApiKind::Explorer)
+        .get("v1/validators?height=5")
+        .unwrap();
+    assert_eq!(info.actual_from, cfg_change_height);
+    assert_eq!(info.config_hash, new_config.hash());
+    assert_eq!(info.validator_keys, new_config.validator_keys);
+
+    // The latest height is used by default.
+    let info: ValidatorsInfo = api.public(ApiKind::Explorer).get("v1/validators").unwrap();
+    assert_eq!(info.height, Height(7));
+    assert_eq!(info.config_hash, new_config.hash());
+
+    let error = api
+        .public(ApiKind::Explorer)
+        .get::<ValidatorsInfo>("v1/validators?height=100")
+        .unwrap_err();
+    match error {
+        ApiError::BadRequest(_) => {}
+        other => panic!("Unexpected error: {:?}", other),
+    }
+}
+
 #[test]
 fn test_change_service_config() {
     #[derive(Debug, Serialize, Deserialize, Clone)]
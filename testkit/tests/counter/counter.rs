@@ -16,7 +16,8 @@
 use exonum::{
     api,
     blockchain::{
-        ExecutionError, ExecutionResult, Service, Transaction, TransactionContext, TransactionSet,
+        ExecutionError, ExecutionResult, Service, Transaction, TransactionContext, TransactionInfo,
+        TransactionSet,
     },
     crypto::{Hash, PublicKey, SecretKey},
     encoding,
@@ -80,6 +81,11 @@ transactions! {
 
         struct TxReset {
         }
+
+        struct TxIncrementAfter {
+            by: u64,
+            after: &Hash,
+        }
     }
 }
 
@@ -120,6 +126,29 @@ impl Transaction for TxReset {
     }
 }
 
+impl TxIncrementAfter {
+    pub fn sign(
+        author: &PublicKey,
+        by: u64,
+        after: &Hash,
+        key: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(TxIncrementAfter::new(by, after), SERVICE_ID, *author, key)
+    }
+}
+
+impl Transaction for TxIncrementAfter {
+    fn depends_on(&self) -> Option<Hash> {
+        Some(*self.after())
+    }
+
+    fn execute(&self, mut tc: TransactionContext) -> ExecutionResult {
+        let mut schema = CounterSchema::new(tc.fork());
+        schema.inc_count(self.by());
+        Ok(())
+    }
+}
+
 // // // // API // // // //
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -194,6 +223,10 @@ impl Service for CounterService {
         Ok(tx.into())
     }
 
+    fn transaction_types(&self) -> Vec<TransactionInfo> {
+        TransactionInfo::from_set::<CounterTransactions>()
+    }
+
     fn wire_api(&self, builder: &mut api::ServiceApiBuilder) {
         CounterApi::wire(builder)
     }
@@ -29,18 +29,27 @@ extern crate serde_derive;
 extern crate serde_json;
 
 use exonum::{
-    api::{node::public::explorer::TransactionQuery, Error as ApiError},
-    blockchain::TransactionErrorType as ErrorType,
+    api::{
+        node::{
+            private::{MempoolQuery, MempoolTransaction},
+            public::explorer::{DecodedTransaction, TransactionQuery},
+        },
+        Error as ApiError,
+    },
+    blockchain::{
+        RegisteredService, Schema as CoreSchema, TransactionErrorType as ErrorType, TransactionInfo,
+    },
     crypto::{self, CryptoHash, PublicKey},
-    encoding::serialize::FromHex,
+    encoding::serialize::{encode_hex, FromHex},
     helpers::Height,
-    messages::{self, RawTransaction, Signed},
+    messages::{self, Message, RawTransaction, Signed},
 };
 use exonum_testkit::{ApiKind, ComparableSnapshot, TestKit, TestKitApi, TestKitBuilder};
 use serde_json::Value;
 
 use counter::{
-    CounterSchema, CounterService, TransactionResponse, TxIncrement, TxReset, ADMIN_KEY,
+    CounterSchema, CounterService, TransactionResponse, TxIncrement, TxIncrementAfter, TxReset,
+    ADMIN_KEY, SERVICE_ID,
 };
 
 mod counter;
@@ -268,6 +277,49 @@ fn test_duplicate_tx() {
     assert_eq!(counter, 5);
 }
 
+#[test]
+fn test_duplicate_tx_in_pool() {
+    let (mut testkit, _) = init_testkit();
+    let (pubkey, key) = crypto::gen_keypair();
+    let tx = TxIncrement::sign(&pubkey, 5, &key);
+
+    testkit.add_tx(tx.clone());
+    testkit.add_tx(tx.clone());
+    {
+        let snapshot = testkit.snapshot();
+        let schema = CoreSchema::new(&snapshot);
+        assert_eq!(schema.transactions_pool_len(), 1);
+    }
+
+    testkit.create_block();
+    testkit.add_tx(tx.clone());
+    let snapshot = testkit.snapshot();
+    let schema = CoreSchema::new(&snapshot);
+    assert_eq!(schema.transactions_pool_len(), 0);
+    assert!(!testkit.is_tx_in_pool(&tx.hash()));
+}
+
+#[should_panic(expected = "Duplicate transaction in the block")]
+#[test]
+fn test_duplicate_tx_in_block() {
+    let (mut testkit, _) = init_testkit();
+    let (pubkey, key) = crypto::gen_keypair();
+    let tx = TxIncrement::sign(&pubkey, 5, &key);
+    testkit.create_block_with_transactions(txvec![tx.clone(), tx]);
+}
+
+#[test]
+fn test_same_tx_payload_in_different_services() {
+    let (pubkey, key) = crypto::gen_keypair();
+    let tx = Message::sign_transaction(TxIncrement::new(5), SERVICE_ID, pubkey, &key);
+    let other_tx = Message::sign_transaction(TxIncrement::new(5), SERVICE_ID + 1, pubkey, &key);
+    assert_eq!(
+        tx.payload().clone().service_transaction(),
+        other_tx.payload().clone().service_transaction()
+    );
+    assert_ne!(tx.hash(), other_tx.hash());
+}
+
 #[test]
 fn test_probe_advanced() {
     let (mut testkit, api) = init_testkit();
@@ -554,6 +606,188 @@ fn test_explorer_blocks() {
     assert_eq!(range.end, Height(5));
 }
 
+#[test]
+fn test_explorer_blocks_cursor() {
+    use exonum::api::node::public::explorer::{BlocksQuery, BlocksRange};
+    use exonum::helpers::Height;
+
+    let (mut testkit, api) = init_testkit();
+    testkit.create_blocks_until(Height(4));
+
+    let response: BlocksRange = api
+        .public(ApiKind::Explorer)
+        .get("v1/blocks?count=3")
+        .unwrap();
+    let heights: Vec<_> = response.blocks.iter().map(|block| block.height()).collect();
+    assert_eq!(heights, vec![Height(4), Height(3), Height(2)]);
+    let cursor = response.next_cursor.expect("No cursor for the next page");
+
+    // New blocks committed in between the requests do not affect the following page.
+    testkit.create_block();
+
+    let response: BlocksRange = api
+        .public(ApiKind::Explorer)
+        .query(&BlocksQuery {
+            count: 3,
+            cursor: Some(cursor),
+            ..Default::default()
+        }).get("v1/blocks")
+        .unwrap();
+    let heights: Vec<_> = response.blocks.iter().map(|block| block.height()).collect();
+    assert_eq!(heights, vec![Height(1), Height(0)]);
+    assert_eq!(response.range.start, Height(0));
+    assert_eq!(response.next_cursor, None);
+}
+
+#[test]
+fn test_explorer_blocks_large_range() {
+    use exonum::api::node::public::explorer::{
+        BlocksQuery, BlocksRange, MAX_BLOCKS_PER_REQUEST, MAX_STREAMED_BLOCKS,
+    };
+    use exonum::helpers::Height;
+
+    let (mut testkit, api) = init_testkit();
+    let last_height = Height(MAX_BLOCKS_PER_REQUEST as u64 + 10);
+    testkit.create_blocks_until(last_height);
+
+    // JSON responses are streamed, so the number of blocks is not limited
+    // by `MAX_BLOCKS_PER_REQUEST`.
+    let response: BlocksRange = api
+        .public(ApiKind::Explorer)
+        .query(&BlocksQuery {
+            count: MAX_BLOCKS_PER_REQUEST + 5,
+            ..Default::default()
+        }).get("v1/blocks")
+        .unwrap();
+    assert_eq!(response.blocks.len(), MAX_BLOCKS_PER_REQUEST + 5);
+    assert_eq!(response.blocks[0].height(), last_height);
+    assert_eq!(response.range.start, Height(6));
+    assert_eq!(response.range.end, last_height.next());
+    assert_eq!(response.times, None);
+
+    let cursor = response.next_cursor.expect("No cursor for the next page");
+    let response: BlocksRange = api
+        .public(ApiKind::Explorer)
+        .query(&BlocksQuery {
+            count: 5,
+            cursor: Some(cursor),
+            add_blocks_time: true,
+            ..Default::default()
+        }).get("v1/blocks")
+        .unwrap();
+    let heights: Vec<_> = response.blocks.iter().map(|block| block.height()).collect();
+    assert_eq!(heights, (1..6).rev().map(Height).collect::<Vec<_>>());
+    assert_eq!(response.times.map(|times| times.len()), Some(5));
+    assert_eq!(response.range, Height(1)..Height(6));
+    let cursor = response.next_cursor.expect("No cursor for the next page");
+
+    let response: BlocksRange = api
+        .public(ApiKind::Explorer)
+        .query(&BlocksQuery {
+            count: 5,
+            cursor: Some(cursor),
+            ..Default::default()
+        }).get("v1/blocks")
+        .unwrap();
+    assert_eq!(response.blocks.len(), 1);
+    assert_eq!(response.range, Height(0)..Height(1));
+    assert_eq!(response.next_cursor, None);
+
+    let response: Result<BlocksRange, _> = api
+        .public(ApiKind::Explorer)
+        .query(&BlocksQuery {
+            count: MAX_STREAMED_BLOCKS + 1,
+            ..Default::default()
+        }).get("v1/blocks");
+    assert_matches!(response, Err(ApiError::BadRequest(_)));
+}
+
+#[test]
+fn test_explorer_transactions_cursor() {
+    use exonum::api::node::public::explorer::{TransactionsQuery, TransactionsRange};
+    use exonum::helpers::Height;
+
+    let (mut testkit, api) = init_testkit();
+    let (pubkey, key) = crypto::gen_keypair();
+    let txs = vec![
+        TxIncrement::sign(&pubkey, 1, &key),
+        TxIncrement::sign(&pubkey, 2, &key),
+        TxIncrement::sign(&pubkey, 3, &key),
+    ];
+    testkit.create_block_with_transactions(txvec![txs[0].clone(), txs[1].clone()]);
+    testkit.create_block();
+    testkit.create_block_with_transaction(txs[2].clone()); // height == 3
+
+    let response: TransactionsRange = api
+        .public(ApiKind::Explorer)
+        .get("v1/transactions/range?count=2")
+        .unwrap();
+    let hashes: Vec<_> = response
+        .transactions
+        .iter()
+        .map(|tx| tx.content.hash())
+        .collect();
+    assert_eq!(hashes, vec![txs[2].hash(), txs[1].hash()]);
+    assert_eq!(response.transactions[0].location.block_height(), Height(3));
+    assert_eq!(response.transactions[1].location.block_height(), Height(1));
+    assert_eq!(response.transactions[1].location.position_in_block(), 1);
+    let cursor = response.next_cursor.expect("No cursor for the next page");
+
+    // New transactions committed in between the requests do not affect the following page.
+    testkit.create_block_with_transaction(TxIncrement::sign(&pubkey, 4, &key));
+
+    let response: TransactionsRange = api
+        .public(ApiKind::Explorer)
+        .query(&TransactionsQuery {
+            count: 2,
+            cursor: Some(cursor),
+        }).get("v1/transactions/range")
+        .unwrap();
+    let hashes: Vec<_> = response
+        .transactions
+        .iter()
+        .map(|tx| tx.content.hash())
+        .collect();
+    assert_eq!(hashes, vec![txs[0].hash()]);
+    assert_eq!(response.next_cursor, None);
+}
+
+#[test]
+fn test_explorer_transactions_scan_limit() {
+    use exonum::api::node::public::explorer::{
+        TransactionsQuery, TransactionsRange, MAX_SCANNED_BLOCKS_PER_REQUEST,
+    };
+    use exonum::explorer::Cursor;
+    use exonum::helpers::Height;
+
+    let (mut testkit, api) = init_testkit();
+    let (pubkey, key) = crypto::gen_keypair();
+    let tx = TxIncrement::sign(&pubkey, 1, &key);
+    testkit.create_block_with_transaction(tx.clone());
+    let last_height = Height(MAX_SCANNED_BLOCKS_PER_REQUEST as u64 + 5);
+    testkit.create_blocks_until(last_height);
+
+    // The scan stops at the limit even though no transactions have been found.
+    let response: TransactionsRange = api
+        .public(ApiKind::Explorer)
+        .get("v1/transactions/range?count=1")
+        .unwrap();
+    assert!(response.transactions.is_empty());
+    let cursor = response.next_cursor.expect("No cursor for the next page");
+    assert_eq!(cursor, Cursor::new(Height(5), u64::max_value()));
+
+    let response: TransactionsRange = api
+        .public(ApiKind::Explorer)
+        .query(&TransactionsQuery {
+            count: 1,
+            cursor: Some(cursor),
+        }).get("v1/transactions/range")
+        .unwrap();
+    assert_eq!(response.transactions.len(), 1);
+    assert_eq!(response.transactions[0].content.hash(), tx.hash());
+    assert_eq!(response.next_cursor, None);
+}
+
 #[test]
 fn test_explorer_single_block() {
     use exonum::explorer::BlockchainExplorer;
@@ -748,3 +982,164 @@ fn test_boxed_tx() {
         counter::SERVICE_ID
     );
 }
+
+#[test]
+fn test_services_registry() {
+    let (_, api) = init_testkit();
+
+    let services: Vec<RegisteredService> = api.public(ApiKind::System).get("v1/services").unwrap();
+    assert_eq!(
+        services,
+        vec![RegisteredService {
+            id: SERVICE_ID,
+            name: "counter".to_owned(),
+            transactions: vec![
+                TransactionInfo {
+                    id: 0,
+                    name: "TxIncrement".to_owned(),
+                },
+                TransactionInfo {
+                    id: 1,
+                    name: "TxReset".to_owned(),
+                },
+                TransactionInfo {
+                    id: 2,
+                    name: "TxIncrementAfter".to_owned(),
+                },
+            ],
+        }]
+    );
+}
+
+#[test]
+fn test_transaction_dependency() {
+    let mut testkit = TestKit::for_service(CounterService);
+    let (pubkey, key) = crypto::gen_keypair();
+    let tx = TxIncrement::sign(&pubkey, 5, &key);
+    let dependent_tx = TxIncrementAfter::sign(&pubkey, 3, &tx.hash(), &key);
+
+    // The dependent transaction is held in the pool until its dependency is committed.
+    testkit.api().send(dependent_tx.clone());
+    let block = testkit.create_block();
+    assert!(block.is_empty());
+    testkit.api().send(tx.clone());
+    let block = testkit.create_block();
+    assert_eq!(block.len(), 1);
+    assert_eq!(block[0].content().message(), &tx);
+    let block = testkit.create_block();
+    assert_eq!(block.len(), 1);
+    assert_eq!(block[0].content().message(), &dependent_tx);
+    assert!(block[0].status().is_ok());
+    assert_eq!(CounterSchema::new(&testkit.snapshot()).count(), Some(8));
+}
+
+#[test]
+fn test_transaction_unmet_dependency() {
+    let mut testkit = TestKit::for_service(CounterService);
+    let (pubkey, key) = crypto::gen_keypair();
+    let tx = TxIncrement::sign(&pubkey, 5, &key);
+    let dependent_tx = TxIncrementAfter::sign(&pubkey, 3, &tx.hash(), &key);
+
+    // A transaction ordered before its dependency fails without being executed.
+    let block = testkit.create_block_with_transactions(txvec![dependent_tx, tx]);
+    assert_eq!(
+        block[0].status().unwrap_err().error_type(),
+        ErrorType::UnmetDependency
+    );
+    assert!(block[1].status().is_ok());
+    assert_eq!(CounterSchema::new(&testkit.snapshot()).count(), Some(5));
+}
+
+#[test]
+fn test_mempool_transactions() {
+    let (mut testkit, api) = init_testkit();
+    let (alice, alice_key) = crypto::gen_keypair();
+    let (bob, bob_key) = crypto::gen_keypair();
+    let tx_alice = TxIncrement::sign(&alice, 5, &alice_key);
+    let tx_bob = TxIncrement::sign(&bob, 3, &bob_key);
+    testkit.add_tx(tx_alice.clone());
+    testkit.add_tx(tx_bob.clone());
+
+    let pool: Vec<MempoolTransaction> = api
+        .private(ApiKind::System)
+        .query(&MempoolQuery {
+            author: Some(alice),
+        })
+        .get("v1/mempool/transactions")
+        .unwrap();
+    assert_eq!(
+        pool,
+        vec![MempoolTransaction {
+            tx_hash: tx_alice.hash(),
+            author: alice,
+            service_id: SERVICE_ID,
+            size: tx_alice.signed_message().raw().len(),
+        }]
+    );
+
+    let pool: Vec<MempoolTransaction> = api
+        .private(ApiKind::System)
+        .get("v1/mempool/transactions")
+        .unwrap();
+    assert_eq!(pool.len(), 2);
+
+    let path = format!("v1/mempool/transactions/{}", tx_alice.hash().to_hex());
+    api.private(ApiKind::System).delete::<()>(&path).unwrap();
+    testkit.poll_events();
+    assert!(!testkit.is_tx_in_pool(&tx_alice.hash()));
+    assert!(testkit.is_tx_in_pool(&tx_bob.hash()));
+
+    let err = api
+        .private(ApiKind::System)
+        .delete::<()>(&path)
+        .unwrap_err();
+    assert_matches!(err, ApiError::NotFound(_));
+
+    testkit.create_block();
+    let snapshot = testkit.snapshot();
+    assert_eq!(CounterSchema::new(&snapshot).count(), Some(3));
+}
+
+#[test]
+fn test_decode_transaction() {
+    let (_, api) = init_testkit();
+    let (pubkey, key) = crypto::gen_keypair();
+    let tx = TxIncrement::sign(&pubkey, 5, &key);
+
+    let decoded: DecodedTransaction = api
+        .public(ApiKind::Explorer)
+        .query(&json!({ "tx_body": messages::to_hex_string(&tx) }))
+        .post("v1/blockchain/decode")
+        .unwrap();
+    assert_eq!(
+        decoded,
+        DecodedTransaction {
+            tx_hash: tx.hash(),
+            author: pubkey,
+            signature_valid: true,
+            service_id: SERVICE_ID,
+            service_name: Some("counter".to_owned()),
+            message_id: 0,
+            message_type: Some("TxIncrement".to_owned()),
+            content: json!({ "by": "5" }),
+        }
+    );
+
+    // The transaction with a corrupted signature is decoded as well.
+    let mut bytes = tx.signed_message().raw().to_vec();
+    *bytes.last_mut().unwrap() ^= 1;
+    let decoded: DecodedTransaction = api
+        .public(ApiKind::Explorer)
+        .query(&json!({ "tx_body": encode_hex(&bytes) }))
+        .post("v1/blockchain/decode")
+        .unwrap();
+    assert!(!decoded.signature_valid);
+    assert_eq!(decoded.message_type, Some("TxIncrement".to_owned()));
+
+    let error = api
+        .public(ApiKind::Explorer)
+        .query(&json!({ "tx_body": "deadbeef" }))
+        .post::<DecodedTransaction>("v1/blockchain/decode")
+        .unwrap_err();
+    assert_matches!(error, ApiError::BadRequest(_));
+}
@@ -0,0 +1,147 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Simple ledger service with a Merkelized state used to check execution determinism.
+
+use exonum::{
+    blockchain::{
+        ExecutionError, ExecutionResult, Service, Transaction, TransactionContext, TransactionSet,
+    },
+    crypto::{Hash, PublicKey, SecretKey},
+    encoding,
+    messages::{Message, RawTransaction, Signed},
+    storage::{Fork, ProofMapIndex, Snapshot},
+};
+
+pub const SERVICE_ID: u16 = 2;
+
+// // // // Schema // // // //
+
+pub struct LedgerSchema<T> {
+    view: T,
+}
+
+impl<T: AsRef<Snapshot>> LedgerSchema<T> {
+    pub fn new(view: T) -> Self {
+        LedgerSchema { view }
+    }
+
+    pub fn balances(&self) -> ProofMapIndex<&Snapshot, PublicKey, u64> {
+        ProofMapIndex::new("ledger.balances", self.view.as_ref())
+    }
+
+    pub fn balance(&self, key: &PublicKey) -> u64 {
+        self.balances().get(key).unwrap_or(0)
+    }
+}
+
+impl<'a> LedgerSchema<&'a mut Fork> {
+    fn balances_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, u64> {
+        ProofMapIndex::new("ledger.balances", self.view)
+    }
+}
+
+// // // // Transactions // // // //
+
+transactions! {
+    pub LedgerTransactions {
+
+        struct TxIssue {
+            amount: u64,
+            seed: u64,
+        }
+
+        struct TxTransfer {
+            to: &PublicKey,
+            amount: u64,
+            seed: u64,
+        }
+    }
+}
+
+impl TxIssue {
+    pub fn sign(amount: u64, seed: u64, pk: &PublicKey, sk: &SecretKey) -> Signed<RawTransaction> {
+        Message::sign_transaction(TxIssue::new(amount, seed), SERVICE_ID, *pk, sk)
+    }
+}
+
+impl Transaction for TxIssue {
+    fn execute(&self, mut tc: TransactionContext) -> ExecutionResult {
+        let author = tc.author();
+        let mut schema = LedgerSchema::new(tc.fork());
+        let balance = schema
+            .balance(&author)
+            .checked_add(self.amount())
+            .ok_or_else(|| ExecutionError::new(0))?;
+        schema.balances_mut().put(&author, balance);
+        Ok(())
+    }
+}
+
+impl TxTransfer {
+    pub fn sign(
+        to: &PublicKey,
+        amount: u64,
+        seed: u64,
+        pk: &PublicKey,
+        sk: &SecretKey,
+    ) -> Signed<RawTransaction> {
+        Message::sign_transaction(TxTransfer::new(to, amount, seed), SERVICE_ID, *pk, sk)
+    }
+}
+
+impl Transaction for TxTransfer {
+    fn execute(&self, mut tc: TransactionContext) -> ExecutionResult {
+        let author = tc.author();
+        if &author == self.to() {
+            Err(ExecutionError::new(1))?
+        }
+
+        let mut schema = LedgerSchema::new(tc.fork());
+        let sender_balance = schema.balance(&author);
+        if sender_balance < self.amount() {
+            Err(ExecutionError::new(2))?
+        }
+        let receiver_balance = schema.balance(self.to());
+
+        let mut balances = schema.balances_mut();
+        balances.put(&author, sender_balance - self.amount());
+        balances.put(self.to(), receiver_balance + self.amount());
+        Ok(())
+    }
+}
+
+// // // // Service // // // //
+
+#[derive(Debug, Clone, Copy)]
+pub struct LedgerService;
+
+impl Service for LedgerService {
+    fn service_name(&self) -> &str {
+        "ledger"
+    }
+
+    fn state_hash(&self, snapshot: &Snapshot) -> Vec<Hash> {
+        vec![LedgerSchema::new(snapshot).balances().merkle_root()]
+    }
+
+    fn service_id(&self) -> u16 {
+        SERVICE_ID
+    }
+
+    fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<Transaction>, encoding::Error> {
+        let tx = LedgerTransactions::tx_from_raw(raw)?;
+        Ok(tx.into())
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Determinism tests for the transaction execution.
+//!
+//! The tests generate blocks of pseudo-random transactions from a fixed seed and check that
+//! the resulting state does not depend on the environment of the node: the set of validators,
+//! the iteration order of hash maps, or the time of execution. The suite is also run on
+//! several platforms in CI to catch architecture-specific behavior: the digest of the last
+//! block is compared with the golden values, which must not change between platforms
+//! or releases unless the transaction format or the execution rules are changed on purpose.
+
+#[macro_use]
+extern crate exonum;
+extern crate exonum_testkit;
+#[macro_use]
+extern crate pretty_assertions;
+extern crate rand;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+
+use exonum::{
+    blockchain::{Blockchain, Schema as CoreSchema, TransactionSet},
+    crypto::{self, Hash, PublicKey, SecretKey, Seed, SEED_LENGTH},
+    encoding::serialize::FromHex,
+    helpers::Height,
+    messages::{RawTransaction, Signed},
+};
+use exonum_testkit::{TestKit, TestKitBuilder};
+use rand::{prng::XorShiftRng, Rng, SeedableRng};
+
+use ledger::{LedgerSchema, LedgerService, LedgerTransactions, TxIssue, TxTransfer, SERVICE_ID};
+
+mod ledger;
+
+const RNG_SEED: [u8; 16] = [
+    0x45, 0x78, 0x6f, 0x6e, 0x75, 0x6d, 0x20, 0x64, 0x65, 0x74, 0x65, 0x72, 0x6d, 0x69, 0x6e, 0x65,
+];
+const WALLETS_COUNT: u8 = 8;
+const BLOCKS_COUNT: usize = 20;
+const TXS_PER_BLOCK: usize = 16;
+
+/// Golden digest of the last block of the scenario.
+const GOLDEN_TX_HASH: &str = "42a74242fe1dadc3550432254417abe3635664ac2023e1683e3d6e55082fb5e2";
+const GOLDEN_TX_RESULTS_HASH: &str =
+    "ebb85736e44f0d9b7eddac0cca9db4bb396f1f79de65e304f4f405eec25485a8";
+const GOLDEN_SERVICE_STATE_HASH: &str =
+    "258118b38da557b56106d32c913fdbf006bd7ffa5ab00918d0759fc1e3816cb3";
+
+/// Digest of the state after the block execution that should not depend on the node environment.
+#[derive(Debug, PartialEq)]
+struct BlockDigest {
+    height: Height,
+    tx_hash: Hash,
+    tx_results_hash: Hash,
+    service_state_hash: Hash,
+}
+
+fn create_testkit(validators_count: u16) -> TestKit {
+    TestKitBuilder::validator()
+        .with_validators(validators_count)
+        .with_service(LedgerService)
+        .create()
+}
+
+fn wallets() -> Vec<(PublicKey, SecretKey)> {
+    (1..=WALLETS_COUNT)
+        .map(|i| crypto::gen_keypair_from_seed(&Seed::new([i; SEED_LENGTH])))
+        .collect()
+}
+
+fn random_transactions(
+    rng: &mut XorShiftRng,
+    wallets: &[(PublicKey, SecretKey)],
+) -> Vec<Signed<RawTransaction>> {
+    (0..TXS_PER_BLOCK)
+        .map(|_| {
+            let (ref pk, ref sk) = wallets[rng.gen_range(0, wallets.len())];
+            if rng.gen_bool(0.25) {
+                TxIssue::sign(rng.gen_range(1, 1_000), rng.gen(), pk, sk)
+            } else {
+                let (ref to, _) = wallets[rng.gen_range(0, wallets.len())];
+                TxTransfer::sign(to, rng.gen_range(1, 500), rng.gen(), pk, sk)
+            }
+        }).collect()
+}
+
+fn block_digest(testkit: &TestKit) -> BlockDigest {
+    let snapshot = testkit.snapshot();
+    let schema = CoreSchema::new(&snapshot);
+    let block = schema.last_block();
+    let service_state_hash = schema
+        .state_hash_aggregator()
+        .get(&Blockchain::service_table_unique_key(SERVICE_ID, 0))
+        .expect("Service state hash is absent in the aggregator");
+    assert_eq!(
+        service_state_hash,
+        LedgerSchema::new(&snapshot).balances().merkle_root()
+    );
+
+    BlockDigest {
+        height: block.height(),
+        tx_hash: *block.tx_hash(),
+        tx_results_hash: schema.transaction_results().merkle_root(),
+        service_state_hash,
+    }
+}
+
+fn run_scenario(mut testkit: TestKit) -> Vec<BlockDigest> {
+    let mut rng = XorShiftRng::from_seed(RNG_SEED);
+    let wallets = wallets();
+    (0..BLOCKS_COUNT)
+        .map(|_| {
+            let txs = random_transactions(&mut rng, &wallets);
+            testkit.create_block_with_transactions(txs);
+            block_digest(&testkit)
+        }).collect()
+}
+
+#[test]
+fn test_deterministic_transactions_generation() {
+    let wallets = wallets();
+    let first = random_transactions(&mut XorShiftRng::from_seed(RNG_SEED), &wallets);
+    let second = random_transactions(&mut XorShiftRng::from_seed(RNG_SEED), &wallets);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_deterministic_execution() {
+    let first = run_scenario(create_testkit(1));
+    let second = run_scenario(create_testkit(1));
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_execution_matches_golden_digest() {
+    let digests = run_scenario(create_testkit(1));
+    let expected = BlockDigest {
+        height: Height(BLOCKS_COUNT as u64),
+        tx_hash: Hash::from_hex(GOLDEN_TX_HASH).unwrap(),
+        tx_results_hash: Hash::from_hex(GOLDEN_TX_RESULTS_HASH).unwrap(),
+        service_state_hash: Hash::from_hex(GOLDEN_SERVICE_STATE_HASH).unwrap(),
+    };
+    assert_eq!(digests.last(), Some(&expected));
+}
+
+#[test]
+fn test_execution_does_not_depend_on_validators() {
+    let first = run_scenario(create_testkit(1));
+    let second = run_scenario(create_testkit(4));
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_balances_are_consistent() {
+    let mut testkit = create_testkit(1);
+    let mut rng = XorShiftRng::from_seed(RNG_SEED);
+    let wallets = wallets();
+    let mut issued = 0;
+
+    for _ in 0..BLOCKS_COUNT {
+        let txs = random_transactions(&mut rng, &wallets);
+        let block = testkit.create_block_with_transactions(txs);
+        issued += block
+            .transactions
+            .iter()
+            .filter(|tx| tx.status().is_ok())
+            .filter_map(|tx| {
+                let payload = tx.content().message().payload().clone();
+                match LedgerTransactions::tx_from_raw(payload).unwrap() {
+                    LedgerTransactions::TxIssue(tx) => Some(tx.amount()),
+                    LedgerTransactions::TxTransfer(_) => None,
+                }
+            }).sum::<u64>();
+    }
+
+    let snapshot = testkit.snapshot();
+    let schema = LedgerSchema::new(&snapshot);
+    let total: u64 = wallets.iter().map(|(pk, _)| schema.balance(pk)).sum();
+    assert_eq!(total, issued);
+}
@@ -26,6 +26,7 @@ use exonum::{
 };
 
 pub const SERVICE_ID: u16 = 512;
+pub const EMITTING_SERVICE_ID: u16 = 513;
 
 transactions! {
     pub HandleCommitTransactions {
@@ -36,12 +37,27 @@ transactions! {
     }
 }
 
+transactions! {
+    pub EmittedTransactions {
+
+        struct TxEmitted {
+            height: Height,
+        }
+    }
+}
+
 impl Transaction for TxAfterCommit {
     fn execute(&self, _context: TransactionContext) -> ExecutionResult {
         Ok(())
     }
 }
 
+impl Transaction for TxEmitted {
+    fn execute(&self, _context: TransactionContext) -> ExecutionResult {
+        Ok(())
+    }
+}
+
 pub struct AfterCommitService;
 
 impl Service for AfterCommitService {
@@ -67,3 +83,29 @@ impl Service for AfterCommitService {
         context.broadcast_transaction(tx);
     }
 }
+
+pub struct EmittingService;
+
+impl Service for EmittingService {
+    fn service_name(&self) -> &str {
+        "emitting"
+    }
+
+    fn state_hash(&self, _: &Snapshot) -> Vec<Hash> {
+        Vec::new()
+    }
+
+    fn service_id(&self) -> u16 {
+        EMITTING_SERVICE_ID
+    }
+
+    fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<Transaction>, encoding::Error> {
+        let tx = EmittedTransactions::tx_from_raw(raw)?;
+        Ok(tx.into())
+    }
+
+    fn after_commit(&self, context: &ServiceContext) {
+        let tx = TxEmitted::new(context.height());
+        context.emit_transaction(tx);
+    }
+}
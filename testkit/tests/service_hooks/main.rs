@@ -21,9 +21,16 @@ extern crate serde_json;
 extern crate serde_derive;
 
 // HACK: Silent "dead_code" warning.
-pub use hooks::{AfterCommitService, HandleCommitTransactions, TxAfterCommit, SERVICE_ID};
+pub use hooks::{
+    AfterCommitService, EmittedTransactions, EmittingService, HandleCommitTransactions,
+    TxAfterCommit, TxEmitted, EMITTING_SERVICE_ID, SERVICE_ID,
+};
 
-use exonum::{blockchain::TransactionSet, helpers::Height, messages::Message};
+use exonum::{
+    blockchain::TransactionSet,
+    helpers::{Height, ValidatorId},
+    messages::Message,
+};
 use exonum_testkit::TestKitBuilder;
 
 mod hooks;
@@ -60,3 +67,27 @@ fn test_after_commit() {
         .all(|block| block.len() == if block.height() == Height(1) { 0 } else { 1 });
     assert!(expected_block_sizes);
 }
+
+#[test]
+fn test_emit_transaction() {
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(2)
+        .with_service(EmittingService)
+        .create();
+
+    // The validators take turns emitting transactions; the testkit node is the validator #0,
+    // so it emits transactions only after committing the blocks with even heights.
+    for i in 1..6 {
+        let block = testkit.create_block();
+        if i > 1 && i % 2 == 1 {
+            assert_eq!(block.len(), 1);
+            let message = block[0].content().message().payload().clone();
+            let EmittedTransactions::TxEmitted(message) =
+                EmittedTransactions::tx_from_raw(message).unwrap();
+            assert_eq!(message, TxEmitted::new(Height(i - 1)));
+            assert_eq!(block[0].emitted_by(), Some(ValidatorId(0)));
+        } else {
+            assert!(block.is_empty());
+        }
+    }
+}
@@ -19,10 +19,10 @@ extern crate pretty_assertions;
 
 use exonum::{
     api::node::{
-        private::NodeInfo,
+        private::{CompactQuery, IndexStatsInfo, NodeInfo},
         public::system::{ConnectivityStatus, ConsensusStatus, HealthCheckInfo},
     },
-    helpers::user_agent,
+    helpers::{user_agent, Height},
     messages::PROTOCOL_MAJOR_VERSION,
 };
 use exonum_testkit::{ApiKind, TestKitBuilder};
@@ -39,8 +39,14 @@ fn healthcheck() {
 
     let info: HealthCheckInfo = api.public(ApiKind::System).get("v1/healthcheck").unwrap();
     let expected = HealthCheckInfo {
+        healthy: false,
         consensus_status: ConsensusStatus::Enabled,
         connectivity: ConnectivityStatus::NotConnected,
+        quorum_lost: None,
+        prefetch: None,
+        replica: None,
+        finalized_height: Height(0),
+        height: Height(1),
     };
     assert_eq!(info, expected);
 }
@@ -91,3 +97,51 @@ fn rebroadcast() {
         ()
     )
 }
+
+#[test]
+fn flush() {
+    let testkit = TestKitBuilder::validator().with_validators(2).create();
+    let api = testkit.api();
+
+    assert_eq!(
+        api.private(ApiKind::System).post::<()>("v1/flush").unwrap(),
+        ()
+    )
+}
+
+#[test]
+fn compact() {
+    let testkit = TestKitBuilder::validator().with_validators(2).create();
+    let api = testkit.api();
+
+    let query = CompactQuery {
+        prefixes: vec!["core.".to_owned()],
+    };
+    assert_eq!(
+        api.private(ApiKind::System)
+            .query(&query)
+            .post::<()>("v1/compact")
+            .unwrap(),
+        ()
+    )
+}
+
+#[test]
+fn storage_stats() {
+    let mut testkit = TestKitBuilder::validator().with_validators(2).create();
+    testkit.create_blocks_until(Height(3));
+    let api = testkit.api();
+
+    let stats: Vec<IndexStatsInfo> = api.private(ApiKind::System).get("v1/storage").unwrap();
+    let blocks = stats
+        .iter()
+        .find(|stats| stats.name == "core.blocks")
+        .unwrap();
+    // The genesis block and three created blocks.
+    assert_eq!(blocks.entries, 4);
+    assert!(blocks.bytes > 0);
+    assert_eq!(blocks.modified_at, Height(3));
+
+    assert!(stats.iter().all(|stats| stats.name != "core.storage_stats"));
+    assert!(stats.windows(2).all(|pair| pair[0].bytes >= pair[1].bytes));
+}
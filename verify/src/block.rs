@@ -0,0 +1,199 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use std::collections::HashSet;
+
+use super::{reader::Reader, Error};
+use crypto::{self, Hash, PublicKey, Signature, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+
+// Class and type of the `Precommit` consensus message.
+const PRECOMMIT_CLASS: u8 = 1;
+const PRECOMMIT_TYPE: u8 = 0;
+// Size of the `Precommit` payload: validator id, height, round, propose and block hashes
+// and time (seconds and nanoseconds).
+const PRECOMMIT_SIZE: usize = 2 + 8 + 4 + 32 + 32 + 12;
+
+/// Header of a block, i.e., the `Block` structure of the `exonum` crate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockHeader {
+    /// Identifier of the validator which has proposed the block.
+    pub proposer_id: u16,
+    /// Height of the block.
+    pub height: u64,
+    /// Number of transactions in the block.
+    pub tx_count: u32,
+    /// Hash of the previous block.
+    pub prev_hash: Hash,
+    /// Root hash of the Merkle tree of transactions in the block.
+    pub tx_hash: Hash,
+    /// Hash of the blockchain state after applying transactions in the block.
+    pub state_hash: Hash,
+}
+
+impl BlockHeader {
+    /// Decodes the header from its binary representation.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(bytes);
+        let block = BlockHeader {
+            proposer_id: reader.read_u16()?,
+            height: reader.read_u64()?,
+            tx_count: reader.read_u32()?,
+            prev_hash: reader.read_hash()?,
+            tx_hash: reader.read_hash()?,
+            state_hash: reader.read_hash()?,
+        };
+        reader.finish()?;
+        Ok(block)
+    }
+
+    /// Returns the binary representation of the header.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0; 14];
+        LittleEndian::write_u16(&mut bytes[0..2], self.proposer_id);
+        LittleEndian::write_u64(&mut bytes[2..10], self.height);
+        LittleEndian::write_u32(&mut bytes[10..14], self.tx_count);
+        bytes.extend_from_slice(self.prev_hash.as_ref());
+        bytes.extend_from_slice(self.tx_hash.as_ref());
+        bytes.extend_from_slice(self.state_hash.as_ref());
+        bytes
+    }
+
+    /// Returns the hash of the block, which is referenced by the precommits
+    /// of the validators and by the next block.
+    pub fn hash(&self) -> Hash {
+        crypto::hash(&self.to_bytes())
+    }
+}
+
+/// `Precommit` consensus message of a validator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Precommit {
+    /// Consensus key of the validator which has signed the message.
+    pub author: PublicKey,
+    /// Identifier of the validator.
+    pub validator: u16,
+    /// Height of the block.
+    pub height: u64,
+    /// Consensus round of the precommit.
+    pub round: u32,
+    /// Hash of the proposal of the block.
+    pub propose_hash: Hash,
+    /// Hash of the block.
+    pub block_hash: Hash,
+}
+
+impl Precommit {
+    /// Decodes the precommit from a signed message and verifies its signature.
+    pub fn from_signed_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != PUBLIC_KEY_LENGTH + 2 + PRECOMMIT_SIZE + SIGNATURE_LENGTH {
+            return Err(Error::InvalidPrecommit(format!(
+                "unexpected message length {}",
+                bytes.len()
+            )));
+        }
+        let (data, signature) = bytes.split_at(bytes.len() - SIGNATURE_LENGTH);
+        let author = PublicKey::from_slice(&data[..PUBLIC_KEY_LENGTH]).unwrap();
+        let signature = Signature::from_slice(signature).unwrap();
+        if !crypto::verify(&signature, data, &author) {
+            return Err(Error::InvalidPrecommit("invalid signature".to_owned()));
+        }
+
+        let mut reader = Reader::new(&data[PUBLIC_KEY_LENGTH..]);
+        if (reader.read_u8()?, reader.read_u8()?) != (PRECOMMIT_CLASS, PRECOMMIT_TYPE) {
+            return Err(Error::InvalidPrecommit(
+                "message is not a precommit".to_owned(),
+            ));
+        }
+        Ok(Precommit {
+            author,
+            validator: reader.read_u16()?,
+            height: reader.read_u64()?,
+            round: reader.read_u32()?,
+            propose_hash: reader.read_hash()?,
+            block_hash: reader.read_hash()?,
+        })
+    }
+}
+
+/// Verifies that the block header is authenticated by the supermajority of validators.
+///
+/// `precommits` are the signed `Precommit` messages of the validators, as returned by
+/// the node. The function checks that:
+///
+/// - every precommit has a valid signature of the consensus key of its validator
+///   from `consensus_keys`,
+/// - every precommit references the hash and the height of `block`,
+/// - all precommits belong to the same round and come from different validators,
+/// - there are precommits from at least 2/3+1 of the validators.
+///
+/// `consensus_keys` must correspond to the configuration that was actual at
+/// the height of the block, in the order of the validator identifiers. The genesis
+/// block has no precommits and cannot be verified with this function.
+pub fn verify_block(
+    block: &BlockHeader,
+    precommits: &[Vec<u8>],
+    consensus_keys: &[PublicKey],
+) -> Result<(), Error> {
+    let block_hash = block.hash();
+    let mut round = None;
+    let mut validators = HashSet::with_capacity(precommits.len());
+
+    for precommit in precommits {
+        let precommit = Precommit::from_signed_bytes(precommit)?;
+        let validator = precommit.validator;
+        match consensus_keys.get(validator as usize) {
+            Some(key) if *key == precommit.author => {}
+            Some(_) => {
+                return Err(Error::InvalidPrecommit(format!(
+                    "precommit is not signed by validator {}",
+                    validator
+                )))
+            }
+            None => {
+                return Err(Error::InvalidPrecommit(format!(
+                    "unknown validator {}",
+                    validator
+                )))
+            }
+        }
+        if precommit.height != block.height || precommit.block_hash != block_hash {
+            return Err(Error::InvalidPrecommit(format!(
+                "precommit of validator {} does not correspond to the block",
+                validator
+            )));
+        }
+        if *round.get_or_insert(precommit.round) != precommit.round {
+            return Err(Error::InvalidPrecommit(
+                "precommits belong to different rounds".to_owned(),
+            ));
+        }
+        if !validators.insert(validator) {
+            return Err(Error::InvalidPrecommit(format!(
+                "several precommits from validator {}",
+                validator
+            )));
+        }
+    }
+
+    let required = consensus_keys.len() * 2 / 3 + 1;
+    if validators.len() < required {
+        return Err(Error::NotEnoughPrecommits {
+            found: validators.len(),
+            required,
+        });
+    }
+    Ok(())
+}
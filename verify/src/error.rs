@@ -0,0 +1,87 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{error, fmt};
+
+/// An error returned when the data returned by a node cannot be verified.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The data ends before the block or the proof is completely read.
+    UnexpectedEnd,
+    /// The data continues after the end of the block or the proof.
+    TrailingBytes(usize),
+    /// A node of the proof has an unknown tag.
+    InvalidTag(u8),
+    /// A path in the map proof is malformed.
+    InvalidPath,
+    /// The proof is nested deeper than any valid proof.
+    TooDeep,
+    /// A key or a value of the proof is malformed.
+    InvalidValue(String),
+    /// The structure of the proof does not correspond to a Merkle tree.
+    InvalidProof(&'static str),
+    /// The hash of the proof is not equal to the trusted root hash.
+    UnmatchedRootHash,
+    /// A precommit is malformed or does not authenticate the block.
+    InvalidPrecommit(String),
+    /// The block has less precommits than required.
+    NotEnoughPrecommits {
+        /// Number of the precommits from different validators.
+        found: usize,
+        /// Required number of the precommits.
+        required: usize,
+    },
+    /// The table proof does not contain the requested table.
+    MissingTable {
+        /// Identifier of the service.
+        service_id: u16,
+        /// Index of the table within the service.
+        table_idx: u16,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::UnexpectedEnd => write!(f, "unexpected end of the data"),
+            Error::TrailingBytes(len) => write!(f, "{} trailing bytes after the data", len),
+            Error::InvalidTag(tag) => write!(f, "invalid tag of a proof node: {}", tag),
+            Error::InvalidPath => write!(f, "invalid path in the proof"),
+            Error::TooDeep => write!(f, "proof is nested too deeply"),
+            Error::InvalidValue(ref e) => write!(f, "invalid key or value in the proof: {}", e),
+            Error::InvalidProof(e) => write!(f, "invalid proof: {}", e),
+            Error::UnmatchedRootHash => write!(f, "proof does not match the trusted root hash"),
+            Error::InvalidPrecommit(ref e) => write!(f, "invalid precommit: {}", e),
+            Error::NotEnoughPrecommits { found, required } => write!(
+                f,
+                "block has {} precommits, at least {} are required",
+                found, required
+            ),
+            Error::MissingTable {
+                service_id,
+                table_idx,
+            } => write!(
+                f,
+                "table {} of service {} is missing in the proof",
+                table_idx, service_id
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        "verification error"
+    }
+}
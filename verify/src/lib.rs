@@ -0,0 +1,143 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of the Exonum blockchain data by light clients.
+//!
+//! The crate checks the data returned by a node without access to the blockchain
+//! storage and without the node stack: it depends only on `exonum-crypto`. It provides
+//! verification of block headers against the consensus keys of the validators and
+//! verification of the Merkle proofs of lists and maps in their compact binary encoding
+//! (see `ListProof::to_bytes` and `MapProof::to_bytes` in the `exonum` crate).
+//!
+//! A typical proof of a service data returned by a node consists of three parts:
+//!
+//! - the latest block header with the precommits of the validators, checked with
+//!   [`verify_block`];
+//! - a [`MapProof`] of the root hash of a service table in the `state_hash` of the block,
+//!   checked together with the block by [`verify_table_proof`];
+//! - a proof of the data in the service table, e.g., a `MapProof` or a [`ListProof`],
+//!   checked against the root hash of the table returned by `verify_table_proof`.
+//!
+//! The crate is not `no_std`, since `exonum-crypto` requires the standard library.
+//!
+//! [`verify_block`]: fn.verify_block.html
+//! [`verify_table_proof`]: fn.verify_table_proof.html
+//! [`MapProof`]: struct.MapProof.html
+//! [`ListProof`]: enum.ListProof.html
+
+#![deny(
+    missing_debug_implementations,
+    missing_docs,
+    unsafe_code,
+    bare_trait_objects
+)]
+
+extern crate byteorder;
+extern crate exonum_crypto as crypto;
+
+pub use block::{verify_block, BlockHeader, Precommit};
+pub use error::Error;
+pub use list::ListProof;
+pub use map::{CheckedMapProof, MapProof};
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crypto::{Hash, HASH_SIZE};
+
+mod block;
+mod error;
+mod list;
+mod map;
+mod reader;
+
+/// A value of a Merkle proof.
+///
+/// The hash of the value in the Merkle tree is the hash of its binary representation
+/// for all values except `Hash`, which is used in the tree as is. Values of other types
+/// are verified as `Vec<u8>` and are decoded by the client afterwards.
+pub trait ProofValue: Sized {
+    /// Decodes the value from its binary representation.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Error>;
+
+    /// Returns the hash of the value in the Merkle tree.
+    fn hash(&self) -> Hash;
+}
+
+impl ProofValue for Hash {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Hash::from_slice(bytes).ok_or_else(|| {
+            Error::InvalidValue(format!(
+                "expected {} bytes, found {}",
+                HASH_SIZE,
+                bytes.len()
+            ))
+        })
+    }
+
+    fn hash(&self) -> Hash {
+        *self
+    }
+}
+
+impl ProofValue for Vec<u8> {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(bytes.to_vec())
+    }
+
+    fn hash(&self) -> Hash {
+        crypto::hash(self)
+    }
+}
+
+/// Returns the key of the root hash of the table with index `table_idx` of the service
+/// with `service_id` in the `state_hash` of a block.
+pub fn service_table_key(service_id: u16, table_idx: u16) -> Hash {
+    let mut bytes = [0; 4];
+    LittleEndian::write_u16(&mut bytes[0..2], service_id);
+    LittleEndian::write_u16(&mut bytes[2..4], table_idx);
+    crypto::hash(&bytes)
+}
+
+/// Verifies that the block is authenticated by the supermajority of the validators
+/// and that `to_table` proves the root hash of the table with index `table_idx` of
+/// the service with `service_id` in the `state_hash` of the block.
+///
+/// Returns the root hash of the table, against which the proofs of the table data
+/// should be checked. See [`verify_block`](fn.verify_block.html) for the requirements
+/// to `consensus_keys`.
+pub fn verify_table_proof(
+    block: &BlockHeader,
+    precommits: &[Vec<u8>],
+    consensus_keys: &[crypto::PublicKey],
+    to_table: MapProof<Hash>,
+    service_id: u16,
+    table_idx: u16,
+) -> Result<Hash, Error> {
+    verify_block(block, precommits, consensus_keys)?;
+
+    let to_table = to_table.check()?;
+    if to_table.merkle_root() != block.state_hash {
+        return Err(Error::UnmatchedRootHash);
+    }
+
+    let table_key = service_table_key(service_id, table_idx);
+    let table_root = to_table
+        .entries()
+        .find(|&(key, _)| &key[..] == table_key.as_ref())
+        .map(|(_, hash)| *hash);
+    table_root.ok_or(Error::MissingTable {
+        service_id,
+        table_idx,
+    })
+}
@@ -0,0 +1,134 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{reader::Reader, Error, ProofValue};
+use crypto::{self, Hash, HashStream};
+
+const FULL_TAG: u8 = 0;
+const LEFT_TAG: u8 = 1;
+const LEFT_ONLY_TAG: u8 = 2;
+const RIGHT_TAG: u8 = 3;
+const LEAF_TAG: u8 = 4;
+
+// Maximum number of the branches on the path from the root of the proof to a leaf,
+// corresponding to the height of a list with `u64::max_value()` elements.
+const MAX_DEPTH: usize = 64;
+
+/// A proof of existence for the elements of a `ProofListIndex`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ListProof<V> {
+    /// A branch of proof in which both children contain requested elements.
+    Full(Box<ListProof<V>>, Box<ListProof<V>>),
+    /// A branch of proof in which only the left child contains requested elements.
+    Left(Box<ListProof<V>>, Option<Hash>),
+    /// A branch of proof in which only the right child contains requested elements.
+    Right(Hash, Box<ListProof<V>>),
+    /// A leaf of proof with requested element.
+    Leaf(V),
+}
+
+fn hash_pair(left: &Hash, right: &Hash) -> Hash {
+    HashStream::new()
+        .update(left.as_ref())
+        .update(right.as_ref())
+        .hash()
+}
+
+impl<V: ProofValue> ListProof<V> {
+    /// Deserializes the proof from the compact binary format produced by
+    /// `ListProof::to_bytes` of the `exonum` crate.
+    ///
+    /// Only the structure of the proof is checked; the proof still needs to be validated
+    /// against the trusted Merkle root hash with [`validate`](#method.validate).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(bytes);
+        let proof = Self::read(&mut reader, 0)?;
+        reader.finish()?;
+        Ok(proof)
+    }
+
+    fn read(reader: &mut Reader, depth: usize) -> Result<Self, Error> {
+        let tag = reader.read_u8()?;
+        if tag != LEAF_TAG && depth == MAX_DEPTH {
+            return Err(Error::TooDeep);
+        }
+        let proof = match tag {
+            FULL_TAG => {
+                let left = Self::read(reader, depth + 1)?;
+                let right = Self::read(reader, depth + 1)?;
+                ListProof::Full(Box::new(left), Box::new(right))
+            }
+            LEFT_TAG => {
+                let left = Self::read(reader, depth + 1)?;
+                ListProof::Left(Box::new(left), Some(reader.read_hash()?))
+            }
+            LEFT_ONLY_TAG => ListProof::Left(Box::new(Self::read(reader, depth + 1)?), None),
+            RIGHT_TAG => {
+                let left = reader.read_hash()?;
+                ListProof::Right(left, Box::new(Self::read(reader, depth + 1)?))
+            }
+            LEAF_TAG => ListProof::Leaf(reader.read_value()?),
+            tag => return Err(Error::InvalidTag(tag)),
+        };
+        Ok(proof)
+    }
+
+    fn collect<'a>(
+        &'a self,
+        height: u8,
+        index: u64,
+        vec: &mut Vec<(u64, &'a V)>,
+    ) -> Result<Hash, Error> {
+        if height == 0 {
+            return Err(Error::InvalidProof("unexpected branch"));
+        }
+        let (left, right) = (index << 1, (index << 1) + 1);
+        let hash = match *self {
+            ListProof::Full(ref left_proof, ref right_proof) => hash_pair(
+                &left_proof.collect(height - 1, left, vec)?,
+                &right_proof.collect(height - 1, right, vec)?,
+            ),
+            ListProof::Left(ref left_proof, Some(ref right_hash)) => {
+                hash_pair(&left_proof.collect(height - 1, left, vec)?, right_hash)
+            }
+            ListProof::Left(ref left_proof, None) => {
+                crypto::hash(left_proof.collect(height - 1, left, vec)?.as_ref())
+            }
+            ListProof::Right(ref left_hash, ref right_proof) => {
+                hash_pair(left_hash, &right_proof.collect(height - 1, right, vec)?)
+            }
+            ListProof::Leaf(ref value) => {
+                if height > 1 {
+                    return Err(Error::InvalidProof("unexpected leaf"));
+                }
+                vec.push((index, value));
+                value.hash()
+            }
+        };
+        Ok(hash)
+    }
+
+    /// Verifies the correctness of the proof by the trusted Merkle root hash and the number of
+    /// elements in the list.
+    ///
+    /// If the proof is valid, a vector with indices and references to elements is returned.
+    pub fn validate(&self, merkle_root: Hash, len: u64) -> Result<Vec<(u64, &V)>, Error> {
+        let mut vec = Vec::new();
+        let height = len.next_power_of_two().trailing_zeros() as u8 + 1;
+        if self.collect(height, 0, &mut vec)? != merkle_root {
+            return Err(Error::UnmatchedRootHash);
+        }
+        Ok(vec)
+    }
+}
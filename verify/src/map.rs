@@ -0,0 +1,328 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::{min, Ordering};
+
+use super::{reader::Reader, Error, ProofValue};
+use crypto::{self, Hash, HashStream, HASH_SIZE};
+
+const KEY_SIZE: usize = HASH_SIZE;
+const PATH_SIZE: usize = KEY_SIZE + 2;
+const KEY_BITS: u16 = KEY_SIZE as u16 * 8;
+
+const BRANCH_PATH_PREFIX: u8 = 0;
+const LEAF_PATH_PREFIX: u8 = 1;
+
+const MISSING_ENTRY_TAG: u8 = 0;
+const KV_ENTRY_TAG: u8 = 1;
+
+/// Path to a node of the Merkle Patricia tree, i.e., a prefix of a key in bits.
+/// The bits of the key after the end of the path are zeroed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ProofPath {
+    key: [u8; KEY_SIZE],
+    len: u16,
+}
+
+impl ProofPath {
+    fn leaf(key: &[u8; KEY_SIZE]) -> Self {
+        ProofPath {
+            key: *key,
+            len: KEY_BITS,
+        }
+    }
+
+    /// Reads the path from its 34-byte binary representation: the kind of the node,
+    /// the key and the length of the path in bits (zero for leaves).
+    fn read(raw: &[u8]) -> Result<Self, Error> {
+        let len = match (raw[0], raw[PATH_SIZE - 1]) {
+            (LEAF_PATH_PREFIX, 0) => KEY_BITS,
+            (BRANCH_PATH_PREFIX, len) => u16::from(len),
+            _ => return Err(Error::InvalidPath),
+        };
+        let mut key = [0; KEY_SIZE];
+        key.copy_from_slice(&raw[1..=KEY_SIZE]);
+        Ok(ProofPath { key, len }.prefix(len))
+    }
+
+    fn write(&self) -> [u8; PATH_SIZE] {
+        let mut raw = [0; PATH_SIZE];
+        raw[1..=KEY_SIZE].copy_from_slice(&self.key);
+        if self.is_leaf() {
+            raw[0] = LEAF_PATH_PREFIX;
+        } else {
+            raw[0] = BRANCH_PATH_PREFIX;
+            raw[PATH_SIZE - 1] = self.len as u8;
+        }
+        raw
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.len == KEY_BITS
+    }
+
+    fn bit(&self, index: u16) -> u8 {
+        (self.key[(index / 8) as usize] >> (index % 8)) & 1
+    }
+
+    fn prefix(&self, len: u16) -> Self {
+        let mut key = self.key;
+        for (i, byte) in key.iter_mut().enumerate() {
+            let start = i as u16 * 8;
+            if start >= len {
+                *byte = 0;
+            } else if start + 8 > len {
+                *byte &= (1 << (len - start)) - 1;
+            }
+        }
+        ProofPath { key, len }
+    }
+
+    fn common_prefix_len(&self, other: &Self) -> u16 {
+        let max_len = min(self.len, other.len);
+        (0..max_len)
+            .find(|&i| self.bit(i) != other.bit(i))
+            .unwrap_or(max_len)
+    }
+
+    fn starts_with(&self, other: &Self) -> bool {
+        self.common_prefix_len(other) == other.len
+    }
+}
+
+impl Ord for ProofPath {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let common_len = self.common_prefix_len(other);
+        if common_len < min(self.len, other.len) {
+            self.bit(common_len).cmp(&other.bit(common_len))
+        } else {
+            self.len.cmp(&other.len)
+        }
+    }
+}
+
+impl PartialOrd for ProofPath {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl ProofValue for [u8; KEY_SIZE] {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != KEY_SIZE {
+            return Err(Error::InvalidValue(format!(
+                "expected a key of {} bytes, found {}",
+                KEY_SIZE,
+                bytes.len()
+            )));
+        }
+        let mut key = [0; KEY_SIZE];
+        key.copy_from_slice(bytes);
+        Ok(key)
+    }
+
+    fn hash(&self) -> Hash {
+        crypto::hash(self)
+    }
+}
+
+/// A proof of existence or absence of the keys in a `ProofMapIndex`.
+///
+/// Only the maps with 32-byte keys written into the tree as is (i.e., `Hash`,
+/// `PublicKey` and `[u8; 32]` keys) are supported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapProof<V> {
+    entries: Vec<([u8; KEY_SIZE], Option<V>)>,
+    proof: Vec<(ProofPath, Hash)>,
+}
+
+/// Version of `MapProof` obtained after verification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckedMapProof<V> {
+    entries: Vec<([u8; KEY_SIZE], Option<V>)>,
+    hash: Hash,
+}
+
+/// Computes the root hash of the Merkle Patricia tree from the nodes sorted
+/// by their paths; see `MapProof::check` of the `exonum` crate for the details.
+fn collect(entries: &[(ProofPath, Hash)]) -> Result<Hash, Error> {
+    fn hash_branch(left: &(ProofPath, Hash), right: &(ProofPath, Hash)) -> Hash {
+        HashStream::new()
+            .update(left.1.as_ref())
+            .update(right.1.as_ref())
+            .update(&left.0.write())
+            .update(&right.0.write())
+            .hash()
+    }
+
+    fn common_prefix(x: &ProofPath, y: &ProofPath) -> ProofPath {
+        x.prefix(x.common_prefix_len(y))
+    }
+
+    fn fold(contour: &mut Vec<(ProofPath, Hash)>, last_prefix: ProofPath) -> Option<ProofPath> {
+        let last_entry = contour.pop().unwrap();
+        let penultimate_entry = contour.pop().unwrap();
+        contour.push((last_prefix, hash_branch(&penultimate_entry, &last_entry)));
+
+        if contour.len() > 1 {
+            let penultimate_entry = contour[contour.len() - 2];
+            Some(common_prefix(&penultimate_entry.0, &last_prefix))
+        } else {
+            None
+        }
+    }
+
+    match entries.len() {
+        0 => Ok(Hash::zero()),
+        1 => {
+            let (ref path, ref hash) = entries[0];
+            if !path.is_leaf() {
+                return Err(Error::InvalidProof("non-terminal node as a single key"));
+            }
+            Ok(HashStream::new()
+                .update(&path.write())
+                .update(hash.as_ref())
+                .hash())
+        }
+        _ => {
+            let mut contour = Vec::with_capacity(8);
+            // Invariant: equal to the common prefix of the 2 last nodes in the contour.
+            let mut last_prefix = common_prefix(&entries[0].0, &entries[1].0);
+            contour.push(entries[0]);
+            contour.push(entries[1]);
+
+            for entry in entries.iter().skip(2) {
+                let new_prefix = common_prefix(&contour.last().unwrap().0, &entry.0);
+                while contour.len() > 1 && new_prefix.len < last_prefix.len {
+                    if let Some(prefix) = fold(&mut contour, last_prefix) {
+                        last_prefix = prefix;
+                    }
+                }
+                contour.push(*entry);
+                last_prefix = new_prefix;
+            }
+
+            while contour.len() > 1 {
+                if let Some(prefix) = fold(&mut contour, last_prefix) {
+                    last_prefix = prefix;
+                }
+            }
+            Ok(contour[0].1)
+        }
+    }
+}
+
+impl<V: ProofValue> MapProof<V> {
+    /// Deserializes the proof from the compact binary format produced by
+    /// `MapProof::to_bytes` of the `exonum` crate.
+    ///
+    /// Only the structure of the proof is checked; the proof still needs to be verified
+    /// with [`check`](#method.check).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(bytes);
+
+        let entries_count = reader.read_u32()?;
+        let mut entries = Vec::new();
+        for _ in 0..entries_count {
+            let entry = match reader.read_u8()? {
+                MISSING_ENTRY_TAG => (reader.read_value()?, None),
+                KV_ENTRY_TAG => {
+                    let key = reader.read_value()?;
+                    (key, Some(reader.read_value()?))
+                }
+                tag => return Err(Error::InvalidTag(tag)),
+            };
+            entries.push(entry);
+        }
+
+        let proof_count = reader.read_u32()?;
+        let mut proof = Vec::new();
+        for _ in 0..proof_count {
+            let path = ProofPath::read(reader.read_bytes(PATH_SIZE)?)?;
+            proof.push((path, reader.read_hash()?));
+        }
+
+        reader.finish()?;
+        Ok(MapProof { entries, proof })
+    }
+
+    fn precheck(&self) -> Result<(), Error> {
+        for window in self.proof.windows(2) {
+            let (prev_path, path) = (&window[0].0, &window[1].0);
+            match prev_path.cmp(path) {
+                Ordering::Less if path.starts_with(prev_path) => {
+                    return Err(Error::InvalidProof("embedded paths"));
+                }
+                Ordering::Less => {}
+                Ordering::Equal => return Err(Error::InvalidProof("duplicate path")),
+                Ordering::Greater => return Err(Error::InvalidProof("invalid path ordering")),
+            }
+        }
+
+        // It suffices to check the closest smaller path in the proof for each entry.
+        for (key, _) in &self.entries {
+            let path = ProofPath::leaf(key);
+            match self.proof.binary_search_by(|(other, _)| other.cmp(&path)) {
+                Ok(_) => return Err(Error::InvalidProof("duplicate path")),
+                Err(index) if index > 0 && path.starts_with(&self.proof[index - 1].0) => {
+                    return Err(Error::InvalidProof("embedded paths"));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifies the proof and computes the Merkle root hash of the map. The root hash
+    /// should be compared with the trusted one by the caller.
+    pub fn check(self) -> Result<CheckedMapProof<V>, Error> {
+        self.precheck()?;
+        let (mut proof, entries) = (self.proof, self.entries);
+
+        proof.extend(entries.iter().filter_map(|(key, value)| {
+            value
+                .as_ref()
+                .map(|value| (ProofPath::leaf(key), value.hash()))
+        }));
+        proof.sort_unstable_by_key(|entry| entry.0);
+        if proof.windows(2).any(|window| window[0].0 == window[1].0) {
+            return Err(Error::InvalidProof("duplicate path"));
+        }
+
+        collect(&proof).map(|hash| CheckedMapProof { entries, hash })
+    }
+}
+
+impl<V> CheckedMapProof<V> {
+    /// Retrieves references to keys that the proof shows as missing from the map.
+    pub fn missing_keys(&self) -> impl Iterator<Item = &[u8; KEY_SIZE]> {
+        self.entries.iter().filter_map(|entry| match *entry {
+            (ref key, None) => Some(key),
+            _ => None,
+        })
+    }
+
+    /// Retrieves references to key-value pairs that the proof shows as present in the map.
+    pub fn entries(&self) -> impl Iterator<Item = (&[u8; KEY_SIZE], &V)> {
+        self.entries.iter().filter_map(|entry| match *entry {
+            (ref key, Some(ref value)) => Some((key, value)),
+            _ => None,
+        })
+    }
+
+    /// Returns the Merkle root hash of the map that this proof is constructed for.
+    pub fn merkle_root(&self) -> Hash {
+        self.hash
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reader of the binary encoding of the blocks and the proofs.
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use super::{Error, ProofValue};
+use crypto::{Hash, HASH_SIZE};
+
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Reader { data }
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.data.len() < len {
+            return Err(Error::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, Error> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, Error> {
+        Ok(LittleEndian::read_u16(self.read_bytes(2)?))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, Error> {
+        Ok(LittleEndian::read_u32(self.read_bytes(4)?))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, Error> {
+        Ok(LittleEndian::read_u64(self.read_bytes(8)?))
+    }
+
+    pub fn read_hash(&mut self) -> Result<Hash, Error> {
+        Ok(Hash::from_slice(self.read_bytes(HASH_SIZE)?).expect("Invalid hash length"))
+    }
+
+    /// Reads a key or a value prefixed by its length.
+    pub fn read_value<V: ProofValue>(&mut self) -> Result<V, Error> {
+        let len = self.read_u32()? as usize;
+        V::from_bytes(self.read_bytes(len)?)
+    }
+
+    /// Checks that the whole data has been read.
+    pub fn finish(self) -> Result<(), Error> {
+        if self.data.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::TrailingBytes(self.data.len()))
+        }
+    }
+}
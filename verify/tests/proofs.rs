@@ -0,0 +1,216 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks that the data produced by the node is verified by the standalone verifier.
+
+extern crate chrono;
+extern crate exonum;
+extern crate exonum_verify;
+
+use chrono::Utc;
+use exonum::{
+    blockchain::{Block, Blockchain},
+    crypto::{gen_keypair, hash, Hash, PublicKey, SecretKey},
+    helpers::{Height, Round, ValidatorId},
+    messages::{Message, Precommit},
+    storage::{Database, MemoryDB, ProofListIndex, ProofMapIndex, StorageValue},
+};
+use exonum_verify::{
+    service_table_key, verify_block, verify_table_proof, BlockHeader, Error, ListProof, MapProof,
+};
+
+const SERVICE_ID: u16 = 5;
+
+fn create_validators(count: usize) -> (Vec<PublicKey>, Vec<SecretKey>) {
+    (0..count).map(|_| gen_keypair()).unzip()
+}
+
+fn create_block(state_hash: &Hash) -> Block {
+    Block::new(
+        ValidatorId(0),
+        Height(5),
+        3,
+        &hash(&[1, 2]),
+        &hash(&[3]),
+        state_hash,
+    )
+}
+
+fn sign_block(block: &Block, keys: &[PublicKey], secret_keys: &[SecretKey]) -> Vec<Vec<u8>> {
+    keys.iter()
+        .zip(secret_keys)
+        .enumerate()
+        .map(|(i, (key, secret_key))| {
+            let precommit = Precommit::new(
+                ValidatorId(i as u16),
+                block.height(),
+                Round(1),
+                &hash(&[1]),
+                &block.hash(),
+                Utc::now(),
+            );
+            Message::concrete(precommit, *key, secret_key).serialize()
+        })
+        .collect()
+}
+
+#[test]
+fn verify_signed_block() {
+    let (keys, secret_keys) = create_validators(4);
+    let block = create_block(&hash(&[4]));
+    let header = BlockHeader::from_bytes(&block.clone().into_bytes()).unwrap();
+    assert_eq!(header.height, 5);
+    assert_eq!(header.hash(), block.hash());
+
+    let precommits = sign_block(&block, &keys, &secret_keys);
+    assert!(verify_block(&header, &precommits, &keys).is_ok());
+    assert!(verify_block(&header, &precommits[..3], &keys).is_ok());
+    match verify_block(&header, &precommits[..2], &keys) {
+        Err(Error::NotEnoughPrecommits {
+            found: 2,
+            required: 3,
+        }) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+
+    // Precommits of another block.
+    let other_block = create_block(&hash(&[5]));
+    let other_precommits = sign_block(&other_block, &keys, &secret_keys);
+    assert!(verify_block(&header, &other_precommits, &keys).is_err());
+    // Precommits of other validators.
+    let (other_keys, _) = create_validators(4);
+    assert!(verify_block(&header, &precommits, &other_keys).is_err());
+    // Duplicate precommits.
+    let duplicates = vec![precommits[0].clone(); 3];
+    assert!(verify_block(&header, &duplicates, &keys).is_err());
+    // Corrupted signature.
+    let mut corrupted = precommits.clone();
+    let last = corrupted[0].len() - 1;
+    corrupted[0][last] ^= 1;
+    assert!(verify_block(&header, &corrupted, &keys).is_err());
+}
+
+#[test]
+fn verify_list_proof() {
+    let db = MemoryDB::new();
+    let mut fork = db.fork();
+    let mut list = ProofListIndex::new("list", &mut fork);
+    list.extend(0_u64..11);
+
+    let bytes = list.get_range_proof(3, 7).to_bytes();
+    let proof = ListProof::<Vec<u8>>::from_bytes(&bytes).unwrap();
+    let values = proof.validate(list.merkle_root(), list.len()).unwrap();
+    assert_eq!(
+        values
+            .into_iter()
+            .map(|(index, value)| (index, u64::from_bytes(value.clone().into())))
+            .collect::<Vec<_>>(),
+        (3..7).map(|i| (i, i)).collect::<Vec<_>>()
+    );
+
+    match proof.validate(hash(&[1]), list.len()) {
+        Err(Error::UnmatchedRootHash) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    assert!(proof.validate(list.merkle_root(), 100).is_err());
+}
+
+#[test]
+fn verify_map_proof() {
+    let db = MemoryDB::new();
+    let mut fork = db.fork();
+    let mut map = ProofMapIndex::new("map", &mut fork);
+    let keys: Vec<_> = (0_u8..20).map(|i| hash(&[i])).collect();
+    for (i, key) in keys.iter().enumerate() {
+        map.put(key, i as u64);
+    }
+
+    let missing = hash(&[100]);
+    let bytes = map
+        .get_multiproof(vec![keys[3], keys[17], missing])
+        .to_bytes();
+    let proof = MapProof::<Vec<u8>>::from_bytes(&bytes)
+        .unwrap()
+        .check()
+        .unwrap();
+    assert_eq!(proof.merkle_root(), map.merkle_root());
+    assert_eq!(
+        proof
+            .missing_keys()
+            .map(|key| Hash::from_slice(key).unwrap())
+            .collect::<Vec<_>>(),
+        vec![missing]
+    );
+    let mut entries = proof
+        .entries()
+        .map(|(key, value)| {
+            (
+                Hash::from_slice(key).unwrap(),
+                u64::from_bytes(value.clone().into()),
+            )
+        })
+        .collect::<Vec<_>>();
+    entries.sort();
+    let mut expected = vec![(keys[3], 3), (keys[17], 17)];
+    expected.sort();
+    assert_eq!(entries, expected);
+
+    // Proofs of an empty map and of a map with a single entry.
+    let mut fork = db.fork();
+    let mut empty = ProofMapIndex::<_, Hash, u64>::new("empty", &mut fork);
+    let proof = MapProof::<Vec<u8>>::from_bytes(&empty.get_proof(missing).to_bytes()).unwrap();
+    assert_eq!(proof.check().unwrap().merkle_root(), Hash::zero());
+    empty.put(&keys[0], 0);
+    let proof = MapProof::<Vec<u8>>::from_bytes(&empty.get_proof(missing).to_bytes()).unwrap();
+    assert_eq!(proof.check().unwrap().merkle_root(), empty.merkle_root());
+}
+
+#[test]
+fn verify_service_table() {
+    let (keys, secret_keys) = create_validators(4);
+    let db = MemoryDB::new();
+    let mut fork = db.fork();
+    let table_root = hash(&[42]);
+    let mut state = ProofMapIndex::new("state", &mut fork);
+    state.put(
+        &Blockchain::service_table_unique_key(SERVICE_ID, 0),
+        table_root,
+    );
+    state.put(
+        &Blockchain::service_table_unique_key(SERVICE_ID, 1),
+        hash(&[43]),
+    );
+    assert_eq!(
+        service_table_key(SERVICE_ID, 1),
+        Blockchain::service_table_unique_key(SERVICE_ID, 1)
+    );
+
+    let block = create_block(&state.merkle_root());
+    let header = BlockHeader::from_bytes(&block.clone().into_bytes()).unwrap();
+    let precommits = sign_block(&block, &keys, &secret_keys);
+    let table_key = Blockchain::service_table_unique_key(SERVICE_ID, 0);
+    let to_table = MapProof::from_bytes(&state.get_proof(table_key).to_bytes()).unwrap();
+    let root = verify_table_proof(&header, &precommits, &keys, to_table, SERVICE_ID, 0).unwrap();
+    assert_eq!(root, table_root);
+
+    let missing = Blockchain::service_table_unique_key(SERVICE_ID, 2);
+    let to_table = MapProof::from_bytes(&state.get_proof(missing).to_bytes()).unwrap();
+    match verify_table_proof(&header, &precommits, &keys, to_table, SERVICE_ID, 2) {
+        Err(Error::MissingTable {
+            service_id: SERVICE_ID,
+            table_idx: 2,
+        }) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}